@@ -0,0 +1,104 @@
+//! What this build of television is made of, for bug reports and for
+//! scripts that need to know what is available (see `tv about`).
+use color_eyre::Result;
+use serde_json::{json, Value};
+use television_previewers::previewers::previewer_names;
+
+use crate::config::{config_file_path, get_data_dir};
+use crate::doctor::{channel_names, TerminalCapabilities};
+
+/// The optional cargo features, and whether they are enabled in this build.
+const FEATURES: [(&str, bool); 11] = [
+    ("syntax-highlighting", cfg!(feature = "syntax-highlighting")),
+    ("image-preview", cfg!(feature = "image-preview")),
+    ("http-requests", cfg!(feature = "http-requests")),
+    ("openapi", cfg!(feature = "openapi")),
+    ("dns", cfg!(feature = "dns")),
+    ("cron", cfg!(feature = "cron")),
+    ("fonts", cfg!(feature = "fonts")),
+    ("processes", cfg!(feature = "processes")),
+    ("docker", cfg!(feature = "docker")),
+    ("scripting", cfg!(feature = "scripting")),
+    ("plugins", cfg!(feature = "plugins")),
+];
+
+/// Print what this build is made of, as JSON if `json` is set.
+pub fn run(json: bool) -> Result<()> {
+    let about = about(|name| std::env::var(name).ok());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&about)?);
+        return Ok(());
+    }
+    println!("tv {}", about["version"].as_str().unwrap_or_default());
+    for key in ["features", "channels", "previewers"] {
+        let names: Vec<&str> = about[key]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        println!("{key}: {}", names.join(", "));
+    }
+    let terminal = &about["terminal"];
+    println!(
+        "terminal: truecolor {}, graphics {}, OSC52 {}, tmux {}",
+        terminal["truecolor"],
+        terminal["graphics"].as_str().unwrap_or_default(),
+        terminal["osc52"],
+        terminal["tmux"]
+    );
+    println!(
+        "config: {}",
+        about["config_file"].as_str().unwrap_or_default()
+    );
+    println!("data: {}", about["data_dir"].as_str().unwrap_or_default());
+    Ok(())
+}
+
+/// The description of this build, with the terminal capabilities detected
+/// from the given environment.
+fn about(env: impl Fn(&str) -> Option<String>) -> Value {
+    let features: Vec<&str> = FEATURES
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name)
+        .collect();
+    let terminal = TerminalCapabilities::detect(env);
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": features,
+        "channels": channel_names(),
+        "previewers": previewer_names(),
+        "terminal": {
+            "truecolor": terminal.truecolor,
+            "graphics": terminal.graphics.to_string(),
+            "osc52": terminal.osc52,
+            "tmux": terminal.tmux,
+        },
+        "config_file": config_file_path(),
+        "data_dir": get_data_dir(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_about() {
+        let env =
+            |name: &str| (name == "TERM").then(|| "xterm-kitty".to_string());
+        let about = about(env);
+        assert_eq!(about["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(about["terminal"]["graphics"], "kitty graphics");
+        assert_eq!(about["terminal"]["osc52"], true);
+        assert!(about["channels"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("files")));
+        assert_eq!(
+            about["features"].as_array().unwrap().len(),
+            FEATURES.iter().filter(|(_, enabled)| *enabled).count()
+        );
+    }
+}
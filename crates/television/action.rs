@@ -31,10 +31,24 @@ pub enum Action {
     SelectEntry,
     /// Select the entry currently under the cursor and exit the application.
     SelectAndExit,
+    /// Switch to the channel selected in the remote control, asking for its
+    /// parameter (e.g. the root directory of the files channel).
+    SelectEntryWithParameter,
+    /// Add the entry under the cursor to the selection (or remove it from
+    /// it) and move to the next entry.
+    ToggleSelection,
     /// Select the next entry in the currently focused list.
     SelectNextEntry,
     /// Select the previous entry in the currently focused list.
     SelectPrevEntry,
+    /// Select the next entry, wrapping around past the end of the list.
+    SelectNextEntryWrap,
+    /// Select the previous entry, wrapping around past the start of the list.
+    SelectPrevEntryWrap,
+    /// Select the next entry, stopping at the end of the list.
+    SelectNextEntryNoWrap,
+    /// Select the previous entry, stopping at the start of the list.
+    SelectPrevEntryNoWrap,
     /// Copy the currently selected entry to the clipboard.
     CopyEntryToClipboard,
     // preview actions
@@ -46,8 +60,69 @@ pub enum Action {
     ScrollPreviewHalfPageUp,
     /// Scroll the preview down by half a page.
     ScrollPreviewHalfPageDown,
+    /// Scroll the preview up by a page.
+    ScrollPreviewPageUp,
+    /// Scroll the preview down by a page.
+    ScrollPreviewPageDown,
+    /// Show or hide the preview pane.
+    TogglePreview,
     /// Open the currently selected entry in the default application.
     OpenEntry,
+    /// Open the directory containing the currently selected file in the
+    /// default application (e.g. a file manager).
+    OpenContainingDirectory,
+    /// Edit the currently selected entry (if its channel supports it), or
+    /// open the currently selected file in `$EDITOR`.
+    EditEntry,
+    /// Hide the currently selected entry from the files and text channels of
+    /// the current project (or un-hide it from the hidden entries channel).
+    HideEntry,
+    /// Attach a note to the currently selected entry (this opens the note
+    /// input, or saves the note when it is already open).
+    AnnotateEntry,
+    /// Close the note input without saving the note.
+    CancelAnnotation,
+    /// Pin the currently selected entry to the top of the results.
+    PinEntry,
+    /// Unpin the currently selected entry.
+    UnpinEntry,
+    /// Restrict the lines of the journal, external and cable channels to a
+    /// time range, asking for its bounds.
+    FilterTimeRange,
+    /// Compute the checksums (sha256 and md5) of the selected file and copy
+    /// the sha256 one to the clipboard.
+    ComputeChecksum,
+    /// Export the matched entries as a quickfix list (`file:line:col:text`
+    /// lines), e.g. to open them in an editor.
+    ExportQuickfix,
+    /// Delete the selected file (or terminate the selected process), after
+    /// confirmation.
+    DeleteEntry,
+    /// Answer the confirmation prompt (yes or no).
+    AnswerConfirmation(bool),
+    /// Rename (or move) the selected file, asking for its new path.
+    RenameEntry,
+    /// Undo the last reversible action (hiding an entry, renaming or
+    /// deleting a file).
+    Undo,
+    /// Carry out the action of the input prompt with its value.
+    SubmitPrompt,
+    /// Close the input prompt without carrying out its action.
+    CancelPrompt,
+    /// Complete the value of the input prompt.
+    CompletePrompt,
+    /// Toggle tail mode, where the newest entry stays selected as entries
+    /// arrive.
+    ToggleTailMode,
+    /// Switch the files and text channels to the next search scope: the
+    /// whole project, the current package, the current directory.
+    CycleSearchScope,
+    /// Toggle listing only the files modified recently in the files
+    /// channel.
+    ToggleChangedWithin,
+    /// Toggle displaying raw values (bytes, seconds, plain numbers) instead
+    /// of human-friendly ones in the status bar, metadata and previews.
+    ToggleRawValues,
     // application actions
     /// Tick the application state.
     Tick,
@@ -61,6 +136,15 @@ pub enum Action {
     Help,
     /// Signal an error with the given message.
     Error(String),
+    /// Run the given shell command in the foreground, giving it control of
+    /// the terminal until it exits.
+    RunCommand(String),
+    /// Quit the application, printing the given output instead of the
+    /// selected entry.
+    QuitWithOutput(String),
+    /// Select the entry and exit, printing the given key before it (see
+    /// `--expect`).
+    Expect(String),
     /// No operation.
     NoOp,
     // channel actions
@@ -68,4 +152,23 @@ pub enum Action {
     ToggleRemoteControl,
     /// Toggle the remote control in `send to channel` mode.
     ToggleSendToChannel,
+    /// Toggle the remote control in `open with` mode, listing the
+    /// applications the selected file can be opened with.
+    ToggleOpenWith,
+    /// List the extensions of the files found by the files channel, to only
+    /// list the files of one of them.
+    ToggleFileTypes,
+    /// Switch to the channel the selected entry leads to (e.g. the files of
+    /// the selected git repository).
+    ZapEntry,
+    // control socket actions
+    /// Replace the pattern of the current channel.
+    SetInput(String),
+    /// Select the result at the given position.
+    SelectEntryAt(usize),
+    /// Load the entries of the current channel again.
+    ReloadChannel,
+    /// Preview the given entry (as printed when selected) with the given
+    /// text instead of its previewer.
+    SetPreview { entry: String, content: String },
 }
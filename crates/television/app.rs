@@ -1,18 +1,26 @@
 use std::sync::Arc;
 
 use color_eyre::Result;
-use tokio::sync::{mpsc, Mutex};
+use tokio::select;
+use tokio::sync::{mpsc, oneshot, Mutex};
 use tracing::{debug, info};
 
+use crate::listen::Listener;
+use crate::output::OutputFifo;
+use crate::state::Overlay;
 use crate::television::{Mode, Television};
 use crate::{
     action::Action,
-    config::Config,
+    config::{input_action, Config},
     event::{Event, EventLoop, Key},
     render::{render, RenderingTask},
 };
+use television_channels::bus::{Bus, Message};
 use television_channels::channels::TelevisionChannel;
-use television_channels::entry::Entry;
+use television_channels::context::Context;
+use television_channels::entry::{Entry, PreviewType};
+use television_channels::startup::Startup;
+use television_utils::format;
 
 /// The main application struct that holds the state of the application.
 pub struct App {
@@ -24,6 +32,11 @@ pub struct App {
     frame_rate: f64,
     /// The television instance that handles channels and entries.
     television: Arc<Mutex<Television>>,
+    /// Holds the channels' heavy work until the first frame is drawn.
+    startup: Startup,
+    /// The bus the background workers of this app send their messages
+    /// through.
+    bus: Bus,
     /// A flag that indicates whether the application should quit during the next frame.
     should_quit: bool,
     /// A flag that indicates whether the application should suspend during the next frame.
@@ -38,10 +51,22 @@ pub struct App {
     event_abort_tx: mpsc::UnboundedSender<()>,
     /// A sender channel for rendering tasks.
     render_tx: mpsc::UnboundedSender<RenderingTask>,
+    /// The control socket, if any (see `--listen`).
+    listener: Option<Listener>,
+    /// Where the confirmed entries are written instead of quitting, if
+    /// anywhere (see `--output-fifo`).
+    output_fifo: Option<OutputFifo>,
+    /// The key the selection was confirmed with, if one of the ones given
+    /// to `--expect`.
+    expected_key: Option<String>,
 }
 
 impl App {
+    /// Create the app watching the given channel, created (like the ones it
+    /// switches to) with the given context.
     pub fn new(
+        config: Config,
+        ctx: Context,
         channel: TelevisionChannel,
         tick_rate: f64,
         frame_rate: f64,
@@ -50,38 +75,75 @@ impl App {
         let (render_tx, _) = mpsc::unbounded_channel();
         let (_, event_rx) = mpsc::unbounded_channel();
         let (event_abort_tx, _) = mpsc::unbounded_channel();
-        let television = Arc::new(Mutex::new(Television::new(channel)));
+        format::set_raw(config.ui.raw_values);
+        let startup = ctx.startup.clone();
+        let bus = ctx.bus.clone();
+        let television = Arc::new(Mutex::new(Television::new(channel, ctx)));
 
         Ok(Self {
             tick_rate,
             frame_rate,
             television,
+            startup,
+            bus,
             should_quit: false,
             should_suspend: false,
-            config: Config::new()?,
+            config,
             action_tx,
             action_rx,
             event_rx,
             event_abort_tx,
             render_tx,
+            listener: None,
+            output_fifo: None,
+            expected_key: None,
         })
     }
 
+    /// Open a control socket on the given address, telling its clients what
+    /// happens and carrying out their commands.
+    ///
+    /// # Errors
+    /// If the address can't be listened on.
+    pub async fn listen(&mut self, address: &str) -> Result<()> {
+        self.listener = Some(Listener::bind(address).await?);
+        Ok(())
+    }
+
+    /// Write the confirmed entries to the given file or named pipe and keep
+    /// the picker open, instead of quitting with them.
+    pub fn output_to(&mut self, output_fifo: OutputFifo) {
+        self.output_fifo = Some(output_fifo);
+    }
+
+    /// The key the selection was confirmed with, if one of the ones bound
+    /// to `Expect` (see `--expect`).
+    pub fn expected_key(&self) -> Option<&str> {
+        self.expected_key.as_deref()
+    }
+
+    /// Use the picker as a pure menu, without an input prompt, whatever the
+    /// channel.
+    pub fn set_no_input(&mut self) {
+        self.config.ui.no_input = true;
+    }
+
     /// Run the application main loop.
     ///
     /// This function will start the event loop and the rendering loop and handle
     /// all actions that are sent to the application.
-    /// The function will return the selected entry if the application is exited.
+    /// The function will return the entries to output if the application is
+    /// exited.
     ///
     /// # Arguments
     /// * `is_output_tty` - A flag that indicates whether the output is a tty.
     ///
     /// # Returns
-    /// The selected entry (if any) if the application is exited.
+    /// The entries to output (if any) once the application is exited.
     ///
     /// # Errors
     /// If an error occurs during the execution of the application.
-    pub async fn run(&mut self, is_output_tty: bool) -> Result<Option<Entry>> {
+    pub async fn run(&mut self, is_output_tty: bool) -> Result<Vec<Entry>> {
         info!("Starting backend event loop");
         let event_loop = EventLoop::new(self.tick_rate, true);
         self.event_rx = event_loop.rx;
@@ -94,6 +156,7 @@ impl App {
         let action_tx_r = self.action_tx.clone();
         let config_r = self.config.clone();
         let television_r = self.television.clone();
+        let startup_r = self.startup.clone();
         let frame_rate = self.frame_rate;
         let rendering_task = tokio::spawn(async move {
             render(
@@ -101,6 +164,7 @@ impl App {
                 action_tx_r,
                 config_r,
                 television_r,
+                startup_r,
                 frame_rate,
                 is_output_tty,
             )
@@ -110,23 +174,46 @@ impl App {
         // event handling loop
         debug!("Starting event handling loop");
         let action_tx = self.action_tx.clone();
+        let mut message_rx = self.bus.connect();
         loop {
-            // handle event and convert to action
-            if let Some(event) = self.event_rx.recv().await {
-                let action = self.convert_event_to_action(event).await;
-                action_tx.send(action)?;
+            select! {
+                // handle event and convert to action
+                Some(event) = self.event_rx.recv() => {
+                    let action = self.convert_event_to_action(event).await;
+                    action_tx.send(action)?;
+                }
+                // handle the messages of the background workers
+                Some(message) = message_rx.recv() => {
+                    self.handle_message(message).await?;
+                }
+                // handle the commands of the control socket's clients
+                Some(action) = next_command(self.listener.as_mut()) => {
+                    action_tx.send(action)?;
+                }
+                else => {}
             }
 
-            let maybe_selected = self.handle_actions().await?;
+            let selected = self.handle_actions().await?;
+            self.notify_listener().await;
 
             if self.should_quit {
+                // don't leave walks and commands running behind us
+                self.television.lock().await.shutdown();
                 // send a termination signal to the event loop
                 self.event_abort_tx.send(())?;
 
                 // wait for the rendering task to finish
                 rendering_task.await??;
 
-                return Ok(maybe_selected);
+                if let Some(listener) = self.listener.take() {
+                    listener
+                        .close(
+                            selected.iter().map(Entry::stdout_repr).collect(),
+                        )
+                        .await;
+                }
+
+                return Ok(selected);
             }
         }
     }
@@ -145,29 +232,50 @@ impl App {
         match event {
             Event::Input(keycode) => {
                 info!("{:?}", keycode);
-                // text input events
-                match keycode {
-                    Key::Backspace => return Action::DeletePrevChar,
-                    Key::Delete => return Action::DeleteNextChar,
-                    Key::Left => return Action::GoToPrevChar,
-                    Key::Right => return Action::GoToNextChar,
-                    Key::Home | Key::Ctrl('a') => {
-                        return Action::GoToInputStart
+                let television = self.television.lock().await;
+                match television.state.overlay() {
+                    // the confirmation prompt only accepts its answers
+                    Some(Overlay::Confirmation(confirmation)) => {
+                        return confirmation
+                            .answer(keycode)
+                            .map_or(Action::NoOp, Action::AnswerConfirmation);
+                    }
+                    // the input prompt only accepts text input, enter, tab
+                    // and esc
+                    Some(Overlay::Prompt(_)) => {
+                        return input_action(keycode).unwrap_or(
+                            match keycode {
+                                Key::Enter => Action::SubmitPrompt,
+                                Key::Tab => Action::CompletePrompt,
+                                Key::Esc => Action::CancelPrompt,
+                                _ => Action::NoOp,
+                            },
+                        );
                     }
-                    Key::End | Key::Ctrl('e') => return Action::GoToInputEnd,
-                    Key::Char(c) => return Action::AddInputChar(c),
-                    _ => {}
+                    // the note input only accepts text input, enter and esc
+                    Some(Overlay::Annotation(_)) => {
+                        return input_action(keycode).unwrap_or(
+                            match keycode {
+                                Key::Enter => Action::AnnotateEntry,
+                                Key::Esc => Action::CancelAnnotation,
+                                _ => Action::NoOp,
+                            },
+                        );
+                    }
+                    None => {}
+                }
+                // text input events
+                if let Some(action) = input_action(keycode) {
+                    return action;
                 }
                 // get action based on keybindings
                 self.config
-                    .keybindings
-                    .get(&self.television.lock().await.mode)
-                    .and_then(|keymap| keymap.get(&keycode).cloned())
-                    .unwrap_or(if let Key::Char(c) = keycode {
-                        Action::AddInputChar(c)
-                    } else {
-                        Action::NoOp
-                    })
+                    .action_for_key(
+                        &television.current_channel_name(),
+                        television.mode(),
+                        keycode,
+                    )
+                    .unwrap_or(Action::NoOp)
             }
             // terminal events
             Event::Tick => Action::Tick,
@@ -178,17 +286,47 @@ impl App {
         }
     }
 
+    /// Tell the clients of the control socket about the current pattern and
+    /// selection, if they changed.
+    async fn notify_listener(&mut self) {
+        let Some(listener) = &mut self.listener else {
+            return;
+        };
+        let mut television = self.television.lock().await;
+        let entry = television
+            .get_selected_entry(Some(Mode::Channel))
+            .map(|entry| entry.stdout_repr());
+        let index = television
+            .results_picker
+            .selected()
+            .filter(|_| entry.is_some());
+        listener.update(television.results_picker.input.value(), index, entry);
+    }
+
+    /// Handle a message sent on the bus by a worker running in the
+    /// background.
+    ///
+    /// # Errors
+    /// If the rendering loop can't be reached.
+    async fn handle_message(&mut self, message: Message) -> Result<()> {
+        self.television.lock().await.handle_message(message);
+        // what's on screen changed, no need to wait for the next frame
+        self.action_tx.send(Action::Render)?;
+        Ok(())
+    }
+
     /// Handle actions.
     ///
     /// This function will handle all actions that are sent to the application.
-    /// The function will return the selected entry if the application is exited.
+    /// The function will return the entries to output if the application is
+    /// exited.
     ///
     /// # Returns
-    /// The selected entry (if any) if the application is exited.
+    /// The entries to output (if any) if the application is exited.
     ///
     /// # Errors
     /// If an error occurs during the execution of the application.
-    async fn handle_actions(&mut self) -> Result<Option<Entry>> {
+    async fn handle_actions(&mut self) -> Result<Vec<Entry>> {
         while let Ok(action) = self.action_rx.try_recv() {
             if action != Action::Tick && action != Action::Render {
                 debug!("{action:?}");
@@ -206,14 +344,29 @@ impl App {
                     self.should_suspend = false;
                     self.render_tx.send(RenderingTask::Resume)?;
                 }
-                Action::SelectAndExit => {
+                Action::SelectAndExit | Action::Expect(_) => {
+                    if let Action::Expect(ref key) = action {
+                        self.expected_key = Some(key.clone());
+                    }
+                    let mut television = self.television.lock().await;
+                    let entries = television.get_output_entries();
+                    let Some(output_fifo) = &self.output_fifo else {
+                        self.should_quit = true;
+                        self.render_tx.send(RenderingTask::Quit)?;
+                        return Ok(entries);
+                    };
+                    output_fifo.send(&entries);
+                    // the next entries are picked afresh
+                    television.selection.clear();
+                }
+                Action::QuitWithOutput(ref output) => {
                     self.should_quit = true;
                     self.render_tx.send(RenderingTask::Quit)?;
-                    return Ok(self
-                        .television
-                        .lock()
-                        .await
-                        .get_selected_entry(Some(Mode::Channel)));
+                    return Ok(vec![Entry::new(
+                        String::new(),
+                        PreviewType::Basic,
+                    )
+                    .with_output(output.clone())]);
                 }
                 Action::ClearScreen => {
                     self.render_tx.send(RenderingTask::ClearScreen)?;
@@ -224,6 +377,9 @@ impl App {
                 Action::Render => {
                     self.render_tx.send(RenderingTask::Render)?;
                 }
+                Action::RunCommand(ref command) => {
+                    self.run_command(command.clone()).await?;
+                }
                 _ => {}
             }
             // forward action to the television handler
@@ -233,6 +389,42 @@ impl App {
                 self.action_tx.send(action)?;
             };
         }
-        Ok(None)
+        Ok(Vec::new())
+    }
+
+    /// Run a shell command in the foreground.
+    ///
+    /// The event loop is stopped while the command is running so that it
+    /// doesn't steal the command's input, and restarted once the command
+    /// has exited.
+    ///
+    /// # Errors
+    /// If the event loop or the rendering loop can't be reached.
+    async fn run_command(&mut self, command: String) -> Result<()> {
+        self.event_abort_tx.send(())?;
+        while let Some(event) = self.event_rx.recv().await {
+            if matches!(event, Event::Closed) {
+                break;
+            }
+        }
+
+        let (done_tx, done_rx) = oneshot::channel();
+        self.render_tx
+            .send(RenderingTask::RunCommand(command, done_tx))?;
+        done_rx.await?;
+
+        let event_loop = EventLoop::new(self.tick_rate, true);
+        self.event_rx = event_loop.rx;
+        self.event_abort_tx = event_loop.abort_tx;
+        Ok(())
+    }
+}
+
+/// The next command of the control socket's clients, if there is a control
+/// socket.
+async fn next_command(listener: Option<&mut Listener>) -> Option<Action> {
+    match listener {
+        Some(listener) => listener.command().await,
+        None => std::future::pending().await,
     }
 }
@@ -0,0 +1,268 @@
+//! Bundles of the user's setup, to move it between machines (see `tv export`
+//! and `tv import`).
+//!
+//! A bundle is a gzipped tarball holding the config file, scripts and
+//! plugins under `config/`, and the pins, notes and hidden entries of the
+//! data directory under `data/`. The recently selected entries are only
+//! bundled on demand, and the logs and the trash of deleted files never are.
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use color_eyre::eyre::{bail, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use television_channels::annotations::ANNOTATIONS_DIR;
+use television_channels::hidden::HIDDEN_ENTRIES_DIR;
+use television_channels::pins::PINS_DIR;
+use television_channels::recent::RECENT_ENTRIES_DIR;
+
+use crate::config::{
+    get_config_dir, get_data_dir, CONFIG_FILE_NAME, PLUGINS_DIR_NAME,
+    SCRIPTS_DIR_NAME,
+};
+
+const CONFIG_PREFIX: &str = "config";
+const DATA_PREFIX: &str = "data";
+
+/// The files and directories of the config directory that are bundled.
+const CONFIG_FILES: [&str; 3] =
+    [CONFIG_FILE_NAME, SCRIPTS_DIR_NAME, PLUGINS_DIR_NAME];
+
+/// The stores of the data directory that are always bundled.
+const DATA_STORES: [&str; 3] = [PINS_DIR, ANNOTATIONS_DIR, HIDDEN_ENTRIES_DIR];
+
+/// Bundle the user's setup into the given archive, along with the recently
+/// selected entries if `history` is set.
+pub fn export(path: &Path, history: bool) -> Result<()> {
+    let count = write_bundle(
+        File::create(path)?,
+        &get_config_dir(),
+        &get_data_dir(),
+        history,
+    )?;
+    println!("Exported {count} files to {}", path.display());
+    Ok(())
+}
+
+/// Restore the setup bundled in the given archive, overwriting the existing
+/// files only if `force` is set.
+pub fn import(path: &Path, force: bool) -> Result<()> {
+    let summary = read_bundle(
+        File::open(path)?,
+        &get_config_dir(),
+        &get_data_dir(),
+        force,
+    )?;
+    println!("Imported {} files", summary.imported.len());
+    if !summary.skipped.is_empty() {
+        println!(
+            "Kept {} existing files (use --force to overwrite them):",
+            summary.skipped.len()
+        );
+        for path in &summary.skipped {
+            println!("  {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Write the bundle of the given directories, returning how many files it
+/// holds.
+fn write_bundle(
+    writer: impl Write,
+    config_dir: &Path,
+    data_dir: &Path,
+    history: bool,
+) -> Result<usize> {
+    let mut builder =
+        tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+    let mut count = 0;
+    for name in CONFIG_FILES {
+        count += append(
+            &mut builder,
+            &config_dir.join(name),
+            &Path::new(CONFIG_PREFIX).join(name),
+        )?;
+    }
+    let history = history.then_some(RECENT_ENTRIES_DIR);
+    for store in DATA_STORES.into_iter().chain(history) {
+        count += append(
+            &mut builder,
+            &data_dir.join(store),
+            &Path::new(DATA_PREFIX).join(store),
+        )?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(count)
+}
+
+/// Add the given file, or the files of the given directory, to the bundle
+/// under the given name, returning how many were added.
+fn append<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &Path,
+    name: &Path,
+) -> Result<usize> {
+    if path.is_file() {
+        builder.append_path_with_name(path, name)?;
+        return Ok(1);
+    }
+    let Ok(read_dir) = std::fs::read_dir(path) else {
+        return Ok(0);
+    };
+    let mut paths: Vec<PathBuf> = read_dir
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .collect();
+    paths.sort();
+    let mut count = 0;
+    for path in paths {
+        if let Some(file_name) = path.file_name() {
+            count += append(builder, &path, &name.join(file_name))?;
+        }
+    }
+    Ok(count)
+}
+
+/// The files written when importing a bundle, and the existing ones that
+/// were kept.
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    imported: Vec<PathBuf>,
+    skipped: Vec<PathBuf>,
+}
+
+/// Extract a bundle into the given directories.
+fn read_bundle(
+    reader: impl Read,
+    config_dir: &Path,
+    data_dir: &Path,
+    force: bool,
+) -> Result<Summary> {
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    let mut summary = Summary::default();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        let Some(destination) = destination(&path, config_dir, data_dir)
+        else {
+            bail!("unexpected file in the bundle: {}", path.display());
+        };
+        if destination.exists() && !force {
+            summary.skipped.push(destination);
+            continue;
+        }
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        entry.unpack(&destination)?;
+        summary.imported.push(destination);
+    }
+    Ok(summary)
+}
+
+/// Where the file of a bundle at the given path is imported to, unless it
+/// doesn't belong in the config or data directory.
+fn destination(
+    path: &Path,
+    config_dir: &Path,
+    data_dir: &Path,
+) -> Option<PathBuf> {
+    let mut components = path.components();
+    let base = match components.next()? {
+        Component::Normal(prefix) if prefix == CONFIG_PREFIX => config_dir,
+        Component::Normal(prefix) if prefix == DATA_PREFIX => data_dir,
+        _ => return None,
+    };
+    let relative = components.as_path();
+    let is_safe = relative.components().next().is_some()
+        && relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    is_safe.then(|| base.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_destination() {
+        let (config, data) = (Path::new("/c"), Path::new("/d"));
+        assert_eq!(
+            destination(Path::new("config/scripts/a.rhai"), config, data),
+            Some(PathBuf::from("/c/scripts/a.rhai"))
+        );
+        assert_eq!(
+            destination(Path::new("data/pins/files"), config, data),
+            Some(PathBuf::from("/d/pins/files"))
+        );
+        for path in ["config", "data/../../etc/passwd", "/etc/passwd", "x/y"] {
+            assert_eq!(destination(Path::new(path), config, data), None);
+        }
+    }
+
+    #[test]
+    fn test_bundle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let (config, data) = (root.join("config"), root.join("data"));
+        for (path, content) in [
+            (config.join(CONFIG_FILE_NAME), "[ui]\n"),
+            (
+                config.join(SCRIPTS_DIR_NAME).join("a.rhai"),
+                "fn entries() {}",
+            ),
+            (config.join("notes.txt"), "not bundled"),
+            (data.join(PINS_DIR).join("files"), "src/main.rs\n"),
+            (data.join(RECENT_ENTRIES_DIR).join("files"), "README.md\n"),
+            (data.join("television.log"), "not bundled"),
+        ] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let mut bundle = Vec::new();
+        assert_eq!(
+            write_bundle(&mut bundle, &config, &data, false).unwrap(),
+            3
+        );
+        let mut with_history = Vec::new();
+        assert_eq!(
+            write_bundle(&mut with_history, &config, &data, true).unwrap(),
+            4
+        );
+
+        let (config2, data2) = (root.join("config2"), root.join("data2"));
+        std::fs::create_dir_all(&config2).unwrap();
+        std::fs::write(config2.join(CONFIG_FILE_NAME), "mine").unwrap();
+        let summary =
+            read_bundle(&bundle[..], &config2, &data2, false).unwrap();
+        assert_eq!(
+            summary,
+            Summary {
+                imported: vec![
+                    config2.join(SCRIPTS_DIR_NAME).join("a.rhai"),
+                    data2.join(PINS_DIR).join("files"),
+                ],
+                skipped: vec![config2.join(CONFIG_FILE_NAME)],
+            }
+        );
+        assert_eq!(
+            std::fs::read_to_string(data2.join(PINS_DIR).join("files"))
+                .unwrap(),
+            "src/main.rs\n"
+        );
+        assert!(!data2.join(RECENT_ENTRIES_DIR).exists());
+
+        let summary =
+            read_bundle(&with_history[..], &config2, &data2, true).unwrap();
+        assert_eq!(summary.imported.len(), 4);
+        assert_eq!(
+            std::fs::read_to_string(config2.join(CONFIG_FILE_NAME)).unwrap(),
+            "[ui]\n"
+        );
+        assert!(data2.join(RECENT_ENTRIES_DIR).join("files").exists());
+    }
+}
@@ -1,14 +1,27 @@
-use clap::Parser;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::builder::{EnumValueParser, PossibleValue, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::{Parser, Subcommand, ValueEnum};
 
 use crate::config::{get_config_dir, get_data_dir};
-use television_channels::channels::CliTvChannel;
+use crate::init::Shell as InitShell;
+use crate::output::OutputFormat;
+use crate::popup::Popup;
+use television_channels::channels::{
+    compiled_out_error, missing_channel_feature, CliTvChannel,
+};
+use television_channels::filters::parse_age;
+use television_utils::fields::Fields;
 
 #[derive(Parser, Debug)]
 #[command(author, version = version(), about)]
 pub struct Cli {
-    /// Which channel shall we watch?
-    #[arg(value_enum, default_value = "files")]
-    pub channel: CliTvChannel,
+    /// Which channel shall we watch? (either a built-in channel or a cable
+    /// channel defined in the config file)
+    #[arg(value_parser = ChannelParser, default_value = "files")]
+    pub channel: ChannelArg,
 
     /// Tick rate, i.e. number of ticks per second
     #[arg(short, long, value_name = "FLOAT", default_value_t = 50.0)]
@@ -17,6 +30,283 @@ pub struct Cli {
     /// Frame rate, i.e. number of frames per second
     #[arg(short, long, value_name = "FLOAT", default_value_t = 60.0)]
     pub frame_rate: f64,
+
+    /// Use the picker as a pure menu, without an input prompt (typing a
+    /// character jumps to the next entry starting with it)
+    #[arg(long, default_value_t = false)]
+    pub no_input: bool,
+
+    /// The theme of the UI, either built-in (`default`, `light`, `gruvbox`,
+    /// `catppuccin`) or defined in the config file, instead of `ui.theme`
+    #[arg(long, value_name = "NAME")]
+    pub theme: Option<String>,
+
+    /// Print the entries of the channel matching this pattern (best matches
+    /// first) without starting the interactive picker, e.g. in scripts
+    #[arg(long, value_name = "PATTERN")]
+    pub filter: Option<String>,
+
+    /// Preview http requests by executing them and displaying the response
+    /// (requires `hurl` for `.hurl` files and `httpyac` for `.http` files)
+    #[arg(long, default_value_t = false)]
+    pub execute_requests: bool,
+
+    /// Path to the OpenAPI (or Swagger) spec to use with the `openapi`
+    /// channel (defaults to `openapi.{yaml,yml,json}` or
+    /// `swagger.{yaml,yml,json}` in the current directory)
+    #[arg(long, value_name = "PATH")]
+    pub spec: Option<PathBuf>,
+
+    /// Only list the files with these extensions in the `files` channel
+    /// (comma-separated, e.g. `rs,toml`); other files are skipped while
+    /// walking the file system
+    #[arg(long = "type", value_name = "TYPES", value_delimiter = ',')]
+    pub file_types: Vec<String>,
+
+    /// Only list the files modified within this duration in the `files`
+    /// channel (with an `s`, `m`, `h`, `d` or `w` unit, e.g. `2d`), to find
+    /// what was just worked on
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub changed_within: Option<Duration>,
+
+    /// Command whose output lines the `external` channel lists, as they are
+    /// printed (e.g. `rg --files` or `journalctl -f`)
+    #[arg(long, value_name = "CMD")]
+    pub source_command: Option<String>,
+
+    /// Command previewing the entries of the channel tv starts with, where
+    /// `{}` is replaced by the entry (e.g. `bat --color=always {}`), instead
+    /// of the channel's own previewer
+    #[arg(long, value_name = "CMD")]
+    pub preview: Option<String>,
+
+    /// Split the lines read from stdin into fields separated by this string
+    /// (e.g. `,`, or `\t` for tabs) instead of by whitespace, for
+    /// `--with-nth`, `--nth` and `--accept-nth`
+    #[arg(long, value_name = "STR")]
+    pub delimiter: Option<String>,
+
+    /// Only display these fields of the lines read from stdin, as a
+    /// comma-separated list of fields numbered from 1 (or from -1 for the
+    /// last one) and ranges of them (e.g. `1,3..`)
+    #[arg(long, value_name = "FIELDS")]
+    pub with_nth: Option<Fields>,
+
+    /// Only match against these of the displayed fields of the lines read
+    /// from stdin (same format as `--with-nth`)
+    #[arg(long, value_name = "FIELDS")]
+    pub nth: Option<Fields>,
+
+    /// Print these fields of the selected lines read from stdin (same format
+    /// as `--with-nth`) instead of the whole lines
+    #[arg(long, value_name = "FIELDS")]
+    pub accept_nth: Option<Fields>,
+
+    /// Read the lines from stdin separated by NUL characters instead of
+    /// newlines, e.g. from `find -print0`, for paths containing newlines
+    #[arg(long, default_value_t = false)]
+    pub read0: bool,
+
+    /// Open the selected file (at the selected line, if any) in the Neovim
+    /// instance listening on this socket path or `host:port` address
+    /// (e.g. `$NVIM` from a Neovim terminal) instead of printing it
+    #[arg(long, value_name = "SOCKET")]
+    pub nvim_server: Option<String>,
+
+    /// Listen on this socket path or `host:port` address for clients to
+    /// drive the picker (setting the query, selecting a result, reloading
+    /// the channel) and be told about its changes, as JSON lines
+    #[arg(long, value_name = "ADDRESS")]
+    pub listen: Option<String>,
+
+    /// How to print the selected entry, e.g. as a command for an editor
+    #[arg(long, value_enum, default_value_t = OutputFormat::Default)]
+    pub output_format: OutputFormat,
+
+    /// Separate the printed entries (when several are selected) with NUL
+    /// characters instead of newlines, e.g. for `xargs -0`
+    #[arg(long, default_value_t = false)]
+    pub print0: bool,
+
+    /// Also confirm the selection with these keys (comma separated, e.g.
+    /// `ctrl-v,ctrl-x`), printing the key it was confirmed with (an empty
+    /// line for the usual one) before the selected entries
+    #[arg(long, value_name = "KEYS", value_delimiter = ',')]
+    pub expect: Vec<String>,
+
+    /// Write the selected entries to this file or named pipe each time the
+    /// selection is confirmed, keeping the picker open, instead of printing
+    /// them once and exiting
+    #[arg(long, value_name = "PATH")]
+    pub output_fifo: Option<PathBuf>,
+
+    /// When running inside tmux, open in a popup (`center` or `bottom`,
+    /// optionally followed by its `WxH` size, e.g. `bottom,100%x40%`) and
+    /// print the selection in the current pane
+    #[arg(
+        long,
+        value_name = "POSITION[,WxH]",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "center"
+    )]
+    pub tmux: Option<Popup>,
+
+    /// When running inside Zellij, open in a floating pane (same format as
+    /// `--tmux`) and print the selection in the current pane
+    #[arg(
+        long,
+        value_name = "POSITION[,WxH]",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "center"
+    )]
+    pub zellij: Option<Popup>,
+
+    /// Apply this profile of the config file (a `[profiles.<name>]` table
+    /// overriding any of its settings), instead of the one listing the
+    /// current host in its `hosts`, if any
+    #[arg(long, value_name = "NAME", global = true)]
+    pub profile: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Interactive first-run setup: install the shell widgets and
+    /// completions, write a starter config and check optional dependencies
+    Setup,
+    /// Print the shell widgets (ctrl-t: insert a file, ctrl-r: search the
+    /// history, alt-c: change directory), to load from the shell's rc file
+    /// (e.g. `eval "$(tv init bash)"`)
+    Init {
+        #[arg(value_enum)]
+        shell: InitShell,
+    },
+    /// Diagnose the environment: terminal capabilities, optional tools,
+    /// config validity and keybinding conflicts
+    Doctor,
+    /// Print the version, the enabled features, the compiled channels and
+    /// previewers, the detected terminal capabilities and the config path
+    About {
+        /// Print them as JSON, for bug reports and wrapper scripts
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the resolved keybindings of each mode (defaults merged with the
+    /// config file) and flag conflicting ones
+    Keybindings,
+    /// List, search, prune or forget the entries recently selected in each
+    /// channel (see `recent_first`)
+    Recent {
+        #[command(subcommand)]
+        command: RecentCommand,
+    },
+    /// Bundle the config file, scripts, plugins, pins, notes and hidden
+    /// entries into an archive, to import them on another machine
+    Export {
+        /// The archive to write (a gzipped tarball)
+        path: PathBuf,
+        /// Also bundle the entries recently selected in each channel
+        #[arg(long)]
+        history: bool,
+    },
+    /// Restore the setup bundled by `tv export`, keeping the existing files
+    Import {
+        /// The archive to read
+        path: PathBuf,
+        /// Overwrite the existing files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Install the latest release of tv from GitHub, in place of the running
+    /// binary
+    Update {
+        /// Only check whether a newer release is available
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum RecentCommand {
+    /// List the channels with recent entries, or the recent entries of a
+    /// channel along with how long ago they were selected
+    List { channel: Option<String> },
+    /// Print the recent entries containing a pattern (case-insensitive), in
+    /// any channel
+    Search { pattern: String },
+    /// Drop the duplicate entries and the ones beyond the given limits, in
+    /// the given channel or in all of them
+    Prune {
+        channel: Option<String>,
+        /// Drop the entries selected longer ago than this (e.g. `30d`)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        older_than: Option<Duration>,
+        /// Keep at most this many entries per channel
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+    },
+    /// Forget the recent entries of the given channel, or of all of them
+    Purge { channel: Option<String> },
+}
+
+/// The channel given on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChannelArg {
+    Builtin(CliTvChannel),
+    /// The name of a cable channel, which can only be checked once the
+    /// config file is loaded.
+    Cable(String),
+}
+
+/// Parses the channel argument, with a helpful error message for the
+/// channels that were compiled out of this build.
+#[derive(Clone, Debug)]
+struct ChannelParser;
+
+impl TypedValueParser for ChannelParser {
+    type Value = ChannelArg;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        if let Some(feature) =
+            missing_channel_feature(&value.to_string_lossy())
+        {
+            return Err(clap::Error::raw(
+                ErrorKind::InvalidValue,
+                format!(
+                    "{}\n",
+                    compiled_out_error(&value.to_string_lossy(), feature)
+                ),
+            )
+            .with_cmd(cmd));
+        }
+        match EnumValueParser::<CliTvChannel>::new().parse_ref(cmd, arg, value)
+        {
+            Ok(channel) => Ok(ChannelArg::Builtin(channel)),
+            Err(e) if e.kind() == ErrorKind::InvalidValue => {
+                Ok(ChannelArg::Cable(value.to_string_lossy().into_owned()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            CliTvChannel::value_variants()
+                .iter()
+                .filter_map(ValueEnum::to_possible_value),
+        ))
+    }
 }
 
 const VERSION_MESSAGE: &str = concat!(
@@ -59,3 +349,11 @@ Config directory: {config_dir_path}
 Data directory: {data_dir_path}"
     )
 }
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    parse_age(value).ok_or_else(|| {
+        format!(
+            "invalid duration `{value}` (expected e.g. `30m`, `2d` or `1w`)"
+        )
+    })
+}
@@ -0,0 +1,59 @@
+//! Copying text to the system clipboard, either through the native
+//! clipboard of the desktop or by asking the terminal to do it with an
+//! OSC52 escape sequence (which also works over SSH).
+use std::io::{stderr, Write};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use color_eyre::eyre::{eyre, Result};
+use copypasta::{ClipboardContext, ClipboardProvider};
+
+use crate::config::ClipboardConfig;
+
+/// Copy the given text to the clipboard with the given backend.
+///
+/// In `auto` mode, the terminal is asked to copy the text when running over
+/// SSH (where the native clipboard is the remote machine's), or when the
+/// native clipboard can't be reached.
+pub fn copy(text: &str, backend: ClipboardConfig) -> Result<()> {
+    match backend {
+        ClipboardConfig::Native => native(text),
+        ClipboardConfig::Osc52 => osc52(text),
+        ClipboardConfig::Auto if is_ssh_session() => osc52(text),
+        ClipboardConfig::Auto => native(text).or_else(|_| osc52(text)),
+    }
+}
+
+fn is_ssh_session() -> bool {
+    std::env::var_os("SSH_TTY").is_some()
+        || std::env::var_os("SSH_CONNECTION").is_some()
+}
+
+fn native(text: &str) -> Result<()> {
+    ClipboardContext::new()
+        .and_then(|mut ctx| ctx.set_contents(text.to_string()))
+        .map_err(|e| eyre!("{e}"))
+}
+
+/// Write the OSC52 sequence to the terminal the UI is drawn on.
+fn osc52(text: &str) -> Result<()> {
+    let mut stderr = stderr();
+    stderr.write_all(osc52_sequence(text).as_bytes())?;
+    stderr.flush()?;
+    Ok(())
+}
+
+/// The OSC52 sequence setting the clipboard to the given text.
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", BASE64.encode(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("tv"), "\x1b]52;c;dHY=\x07");
+        assert_eq!(osc52_sequence(""), "\x1b]52;c;\x07");
+    }
+}
@@ -1,21 +1,34 @@
-use std::{collections::HashMap, env, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
 
 use crate::{
     action::Action,
     event::{convert_raw_event_to_key, Key},
     television::Mode,
+    ui::theme::DEFAULT_THEME,
 };
 use color_eyre::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use derive_deref::{Deref, DerefMut};
-use directories::ProjectDirs;
-use lazy_static::lazy_static;
 use ratatui::style::{Color, Modifier, Style};
 use serde::{de::Deserializer, Deserialize};
+use television_channels::channels::cable::CableChannelPrototype;
+#[cfg(feature = "plugins")]
+use television_channels::channels::plugin::load_plugins;
+#[cfg(feature = "scripting")]
+use television_channels::channels::script::load_scripts;
+use television_channels::context::Context;
+use television_channels::filters::parse_age;
+use television_channels::recent::RecentLimits;
+use television_channels::scope::Scope;
+use television_fuzzy::matcher::config::Chunking;
 use television_previewers::previewers::{self, PreviewerConfig};
-use tracing::{info, warn};
+pub use television_utils::dirs::{get_config_dir, get_data_dir};
+use television_utils::image::GraphicsProtocol;
+use television_utils::sandbox::Sandbox;
+use television_utils::shell::ExecutionBackend;
+use tracing::{debug, warn};
 
-const CONFIG: &str = include_str!("../../.config/config.toml");
+pub(crate) const CONFIG: &str = include_str!("../../.config/config.toml");
 
 #[allow(dead_code, clippy::module_name_repetitions)]
 #[derive(Clone, Debug, Deserialize, Default)]
@@ -27,11 +40,79 @@ pub struct AppConfig {
 }
 
 const DEFAULT_UI_SCALE: u16 = 90;
+const DEFAULT_NARROW_WIDTH: u16 = 100;
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct UiConfig {
     pub use_nerd_font_icons: bool,
     pub ui_scale: u16,
+    /// The terminal width (in columns) below which the layout adapts to
+    /// narrow terminals.
+    #[serde(default = "default_narrow_width")]
+    pub narrow_width: u16,
+    /// The (lowercase) names of the channels for which the preview pane is
+    /// hidden by default (it can still be shown with `TogglePreview`).
+    #[serde(default)]
+    pub no_preview_channels: Vec<String>,
+    /// Use the picker as a pure menu, without an input prompt: typing a
+    /// character jumps to the next entry starting with it.
+    #[serde(default)]
+    pub no_input: bool,
+    /// The (lowercase) names of the channels to use as menus (see
+    /// `no_input`).
+    #[serde(default)]
+    pub no_input_channels: Vec<String>,
+    /// Whether several entries can be selected with `ToggleSelection` to be
+    /// output together.
+    #[serde(default = "default_multi_select")]
+    pub multi_select: bool,
+    /// The input prompt.
+    #[serde(default = "default_prompt")]
+    pub prompt: String,
+    /// The symbol displayed in front of the selected entry.
+    #[serde(default = "default_pointer")]
+    pub pointer: String,
+    /// The symbol displayed in front of the entries selected with
+    /// `ToggleSelection`.
+    #[serde(default = "default_marker")]
+    pub marker: String,
+    /// The ellipsis used when shortening text (e.g. preview titles).
+    #[serde(default = "default_ellipsis")]
+    pub ellipsis: String,
+    /// Whether moving past the last (or first) entry wraps around to the
+    /// other end of the list.
+    #[serde(default = "default_wrap_navigation")]
+    pub wrap_navigation: bool,
+    /// Placeholder texts displayed in the empty input, by (lowercase)
+    /// channel name.
+    #[serde(default)]
+    pub placeholders: HashMap<String, String>,
+    /// Whether to display the rest of the top result's name as a hint after
+    /// the input (when it starts with the input).
+    #[serde(default = "default_completion_hints")]
+    pub completion_hints: bool,
+    /// Whether destructive actions (e.g. deleting a file) ask for
+    /// confirmation first.
+    #[serde(default = "default_confirm_destructive_actions")]
+    pub confirm_destructive_actions: bool,
+    /// Secondary preview panes, by (lowercase) channel name.
+    #[serde(default)]
+    pub secondary_previews: HashMap<String, SecondaryPreviewConfig>,
+    /// The (lowercase) names of the channels that can be watched, all of
+    /// them if empty.
+    #[serde(default)]
+    pub available_channels: Vec<String>,
+    /// The name of the theme of the UI, either built-in or defined in
+    /// `themes`.
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// How entries are copied to the clipboard.
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+    /// Whether to display raw values (bytes, seconds, plain numbers) instead
+    /// of human-friendly ones (see `ToggleRawValues`).
+    #[serde(default)]
+    pub raw_values: bool,
 }
 
 impl Default for UiConfig {
@@ -39,10 +120,128 @@ impl Default for UiConfig {
         Self {
             use_nerd_font_icons: false,
             ui_scale: DEFAULT_UI_SCALE,
+            narrow_width: DEFAULT_NARROW_WIDTH,
+            no_preview_channels: Vec::new(),
+            no_input: false,
+            no_input_channels: Vec::new(),
+            multi_select: default_multi_select(),
+            prompt: default_prompt(),
+            pointer: default_pointer(),
+            marker: default_marker(),
+            ellipsis: default_ellipsis(),
+            wrap_navigation: default_wrap_navigation(),
+            placeholders: HashMap::new(),
+            completion_hints: default_completion_hints(),
+            confirm_destructive_actions: default_confirm_destructive_actions(),
+            secondary_previews: HashMap::new(),
+            available_channels: Vec::new(),
+            theme: default_theme(),
+            clipboard: ClipboardConfig::default(),
+            raw_values: false,
         }
     }
 }
 
+fn default_narrow_width() -> u16 {
+    DEFAULT_NARROW_WIDTH
+}
+
+const DEFAULT_PROMPT: &str = "> ";
+const DEFAULT_POINTER: &str = "> ";
+const DEFAULT_MARKER: &str = "+ ";
+const DEFAULT_ELLIPSIS: &str = "…";
+
+fn default_prompt() -> String {
+    DEFAULT_PROMPT.to_string()
+}
+
+fn default_pointer() -> String {
+    DEFAULT_POINTER.to_string()
+}
+
+fn default_marker() -> String {
+    DEFAULT_MARKER.to_string()
+}
+
+fn default_ellipsis() -> String {
+    DEFAULT_ELLIPSIS.to_string()
+}
+
+fn default_wrap_navigation() -> bool {
+    true
+}
+
+fn default_multi_select() -> bool {
+    true
+}
+
+fn default_completion_hints() -> bool {
+    true
+}
+
+fn default_confirm_destructive_actions() -> bool {
+    true
+}
+
+fn default_theme() -> String {
+    DEFAULT_THEME.to_string()
+}
+
+/// A user defined theme: the colors (e.g. `blue`, `light-red`, `#ff8800` or
+/// `208`) and styles (a color and modifiers, e.g. `bold red`) overriding
+/// those of the built-in theme it's based on.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// The built-in theme this one is based on, `default` if not set.
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub border: Option<String>,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub results_count: Option<String>,
+    #[serde(default)]
+    pub result_name: Option<String>,
+    #[serde(default)]
+    pub result_value: Option<String>,
+    #[serde(default)]
+    pub result_line_number: Option<String>,
+    #[serde(default)]
+    pub selection_bg: Option<String>,
+    #[serde(default)]
+    pub matched: Option<String>,
+    #[serde(default)]
+    pub preview_title: Option<String>,
+    #[serde(default)]
+    pub preview_content: Option<String>,
+    #[serde(default)]
+    pub preview_gutter: Option<String>,
+    #[serde(default)]
+    pub preview_gutter_selected: Option<String>,
+    #[serde(default)]
+    pub preview_selection_bg: Option<String>,
+}
+
+const DEFAULT_SECONDARY_PREVIEW_HEIGHT: u16 = 30;
+
+/// A secondary preview pane, stacked below the main preview.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct SecondaryPreviewConfig {
+    /// The command whose output to display, where `{}` is replaced by the
+    /// selected entry. The entry's metadata is displayed if not set.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// The height of the pane (in percentage of the preview area).
+    #[serde(default = "default_secondary_preview_height")]
+    pub height: u16,
+}
+
+fn default_secondary_preview_height() -> u16 {
+    DEFAULT_SECONDARY_PREVIEW_HEIGHT
+}
+
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct PreviewersConfig {
     #[serde(default)]
@@ -52,12 +251,19 @@ pub struct PreviewersConfig {
     pub file: FilePreviewerConfig,
     #[serde(default)]
     pub env_var: EnvVarPreviewerConfig,
+    #[serde(default)]
+    pub command: CommandPreviewerConfig,
+    #[serde(default)]
+    pub image: ImagePreviewerConfig,
 }
 
 impl From<PreviewersConfig> for PreviewerConfig {
     fn from(val: PreviewersConfig) -> Self {
         PreviewerConfig::default()
             .file(previewers::FilePreviewerConfig::new(val.file.theme.clone()))
+            .command(previewers::CommandPreviewerConfig::new(
+                val.command.sandbox(),
+            ))
     }
 }
 
@@ -76,6 +282,199 @@ pub struct FilePreviewerConfig {
 #[derive(Clone, Debug, Deserialize, Default)]
 pub struct EnvVarPreviewerConfig {}
 
+/// How images are drawn in the preview pane.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImageProtocolConfig {
+    /// The best protocol the terminal supports.
+    #[default]
+    Auto,
+    Kitty,
+    Sixel,
+    Iterm2,
+    HalfBlocks,
+}
+
+/// How text is copied to the clipboard (see `clipboard::copy`).
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardConfig {
+    /// The terminal over SSH or when the native clipboard is unavailable,
+    /// the native clipboard otherwise.
+    #[default]
+    Auto,
+    Native,
+    Osc52,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct ImagePreviewerConfig {
+    #[serde(default)]
+    pub protocol: ImageProtocolConfig,
+}
+
+impl ImagePreviewerConfig {
+    /// The protocol used to draw images, detected from the environment if
+    /// not configured.
+    pub fn protocol(&self) -> GraphicsProtocol {
+        match self.protocol {
+            ImageProtocolConfig::Auto => {
+                GraphicsProtocol::detect(|name| std::env::var(name).ok())
+            }
+            ImageProtocolConfig::Kitty => GraphicsProtocol::Kitty,
+            ImageProtocolConfig::Sixel => GraphicsProtocol::Sixel,
+            ImageProtocolConfig::Iterm2 => GraphicsProtocol::Iterm2,
+            ImageProtocolConfig::HalfBlocks => GraphicsProtocol::HalfBlocks,
+        }
+    }
+}
+
+/// The restrictions preview commands run under.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct CommandPreviewerConfig {
+    /// How long (in seconds) a command may run before being killed.
+    #[serde(default = "default_command_timeout")]
+    pub timeout: f64,
+    /// How many bytes of output a command may write before being killed.
+    #[serde(default = "default_command_max_output_size")]
+    pub max_output_size: usize,
+    /// The environment variables passed through to the commands, on top of
+    /// the usual ones (`PATH`, `HOME`, `LANG`, ...).
+    #[serde(default)]
+    pub env: Vec<String>,
+    /// The directory commands run in, the current directory if not set.
+    #[serde(default)]
+    pub working_directory: Option<String>,
+}
+
+impl Default for CommandPreviewerConfig {
+    fn default() -> Self {
+        Self {
+            timeout: default_command_timeout(),
+            max_output_size: default_command_max_output_size(),
+            env: Vec::new(),
+            working_directory: None,
+        }
+    }
+}
+
+impl CommandPreviewerConfig {
+    pub fn sandbox(&self) -> Sandbox {
+        Sandbox {
+            timeout: Duration::try_from_secs_f64(self.timeout)
+                .unwrap_or(Duration::ZERO),
+            max_output_size: self.max_output_size,
+            env: self.env.clone(),
+            working_directory: self
+                .working_directory
+                .as_ref()
+                .map(PathBuf::from),
+        }
+    }
+}
+
+fn default_command_timeout() -> f64 {
+    Sandbox::default().timeout.as_secs_f64()
+}
+
+fn default_command_max_output_size() -> usize {
+    Sandbox::default().max_output_size
+}
+
+/// Channel specific settings.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct ChannelConfig {
+    /// How often (in seconds) to reload the channel's entries in the
+    /// background, for channels backed by commands.
+    #[serde(default)]
+    pub refresh_interval: Option<f64>,
+    /// The number of worker threads of the channel's fuzzy matcher.
+    #[serde(default)]
+    pub matcher_threads: Option<usize>,
+    /// The number of entries pushed in bulk into the channel's fuzzy matcher
+    /// between two yield points.
+    #[serde(default)]
+    pub chunk_size: Option<usize>,
+    /// The number of entries pushed but not yet taken into account by the
+    /// channel's fuzzy matcher above which pushing backs off.
+    #[serde(default)]
+    pub high_watermark: Option<usize>,
+    /// Whether to display the recently selected entries first when the
+    /// pattern is empty.
+    #[serde(default)]
+    pub recent_first: bool,
+    /// Whether to start in tail mode, where the newest entry stays selected
+    /// as entries arrive.
+    #[serde(default)]
+    pub tail: bool,
+    /// A command previewing the channel's entries instead of its own
+    /// previewer, where `{}` is replaced by the (shell-quoted) entry.
+    #[serde(default)]
+    pub preview_command: Option<String>,
+    /// The keybindings overriding the global ones in this channel, by mode.
+    #[serde(default)]
+    pub keybindings: KeyBindings,
+}
+
+impl ChannelConfig {
+    /// How the channel pushes its entries in bulk into its fuzzy matcher, if
+    /// configured.
+    pub fn chunking(&self) -> Option<Chunking> {
+        if self.chunk_size.is_none() && self.high_watermark.is_none() {
+            return None;
+        }
+        let default = Chunking::default();
+        Some(Chunking {
+            chunk_size: self.chunk_size.unwrap_or(default.chunk_size),
+            high_watermark: self
+                .high_watermark
+                .unwrap_or(default.high_watermark),
+        })
+    }
+}
+
+/// Where the matched entries are exported to as a quickfix list, and what
+/// to do with it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct QuickfixConfig {
+    /// The file the quickfix list is written to (`-` to print it to stdout
+    /// and exit), `quickfix.txt` in the data directory if not set.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// A command run once the quickfix list is written (e.g. to open it in
+    /// an editor), where `{}` is replaced by the path of the file.
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+/// How many of the entries selected in each channel are remembered (see
+/// `recent_first` and `tv recent`).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct RecentConfig {
+    /// How many entries are kept per channel, 100 if not set.
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+    /// How long ago entries may have been selected to be kept (e.g. `30d`),
+    /// unbounded if not set.
+    #[serde(default)]
+    pub max_age: Option<String>,
+}
+
+impl RecentConfig {
+    pub fn limits(&self) -> RecentLimits {
+        let default = RecentLimits::default();
+        RecentLimits {
+            max_entries: self.max_entries.unwrap_or(default.max_entries),
+            max_age: self.max_age.as_deref().and_then(|age| {
+                parse_age(age).or_else(|| {
+                    warn!("Invalid recent.max_age: {age}");
+                    None
+                })
+            }),
+        }
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct Config {
@@ -89,29 +488,107 @@ pub struct Config {
     pub ui: UiConfig,
     #[serde(default)]
     pub previewers: PreviewersConfig,
+    /// Channel specific settings, by (lowercase) channel name.
+    #[serde(default)]
+    pub channels: HashMap<String, ChannelConfig>,
+    #[serde(default)]
+    pub quickfix: QuickfixConfig,
+    #[serde(default)]
+    pub recent: RecentConfig,
+    #[serde(default)]
+    pub execution: ExecutionConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    /// Ad-hoc channels listing the lines printed by a shell command.
+    #[serde(default)]
+    pub cable_channels: Vec<CableChannelPrototype>,
+    /// User defined themes, by name.
+    #[serde(default)]
+    pub themes: HashMap<String, ThemeConfig>,
+    /// The name of the profile applied on top of the config file, if any.
+    #[serde(skip)]
+    pub profile: Option<String>,
 }
 
-lazy_static! {
-    pub static ref PROJECT_NAME: String = String::from("television");
-    pub static ref PROJECT_NAME_UPPER: String = PROJECT_NAME.to_uppercase().to_string();
-    pub static ref DATA_FOLDER: Option<PathBuf> =
-        // if `TELEVISION_DATA` is set, use that as the data directory
-        env::var_os(format!("{}_DATA", PROJECT_NAME_UPPER.clone())).or_else(|| {
-            // otherwise, use the XDG data directory
-            env::var_os("XDG_DATA_HOME")
-        }).map(PathBuf::from).map(|p| p.join(PROJECT_NAME.as_str())).filter(|p| p.is_absolute());
-    pub static ref CONFIG_FOLDER: Option<PathBuf> =
-        // if `TELEVISION_CONFIG` is set, use that as the config directory
-        env::var_os(format!("{}_CONFIG", PROJECT_NAME_UPPER.clone())).or_else(|| {
-            // otherwise, use the XDG config directory
-            env::var_os("XDG_CONFIG_HOME")
-        }).map(PathBuf::from).map(|p| p.join(PROJECT_NAME.as_str())).filter(|p| p.is_absolute());
+/// How applications launched in the background (e.g. from open with mode)
+/// are run.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutionBackendConfig {
+    #[default]
+    Child,
+    Setsid,
+    SystemdRun,
 }
 
-const CONFIG_FILE_NAME: &str = "config.toml";
+impl From<ExecutionBackendConfig> for ExecutionBackend {
+    fn from(val: ExecutionBackendConfig) -> Self {
+        match val {
+            ExecutionBackendConfig::Child => ExecutionBackend::Child,
+            ExecutionBackendConfig::Setsid => ExecutionBackend::Setsid,
+            ExecutionBackendConfig::SystemdRun => ExecutionBackend::SystemdRun,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct ExecutionConfig {
+    #[serde(default)]
+    pub backend: ExecutionBackendConfig,
+}
+
+/// Where the files and text channels search.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct SearchConfig {
+    /// The scope searched by default, which can be switched at runtime with
+    /// `CycleSearchScope`.
+    #[serde(default)]
+    pub scope: Scope,
+    /// The directories the git repos channel looks for repositories in (`~`
+    /// is expanded), the home directory if empty.
+    #[serde(default)]
+    pub repo_roots: Vec<String>,
+}
+
+impl SearchConfig {
+    pub fn repo_roots(&self) -> Vec<PathBuf> {
+        self.repo_roots
+            .iter()
+            .map(|root| expand_home(root))
+            .collect()
+    }
+}
+
+pub(crate) const CONFIG_FILE_NAME: &str = "config.toml";
+pub(crate) const SCRIPTS_DIR_NAME: &str = "scripts";
+pub(crate) const PLUGINS_DIR_NAME: &str = "plugins";
+/// The table of the config file holding the profiles, by name.
+const PROFILES_KEY: &str = "profiles";
+/// The key of a profile listing the hosts it is applied on by default.
+const PROFILE_HOSTS_KEY: &str = "hosts";
+
+/// The path of the user's config file.
+pub fn config_file_path() -> PathBuf {
+    get_config_dir().join(CONFIG_FILE_NAME)
+}
+
+/// The directory of the user's scripts (see `channels::script`).
+#[cfg(feature = "scripting")]
+pub fn scripts_dir() -> PathBuf {
+    get_config_dir().join(SCRIPTS_DIR_NAME)
+}
+
+/// The directory of the user's plugins (see `channels::plugin`).
+#[cfg(feature = "plugins")]
+pub fn plugins_dir() -> PathBuf {
+    get_config_dir().join(PLUGINS_DIR_NAME)
+}
 
 impl Config {
-    pub fn new() -> Result<Self, config::ConfigError> {
+    /// Load the config file on top of the default config, then the given
+    /// profile on top of it (or, if none is given, the first one listing the
+    /// current host in its `hosts`).
+    pub fn new(profile: Option<&str>) -> Result<Self, config::ConfigError> {
         let default_config: Config =
             toml::from_str(CONFIG).expect("default config");
 
@@ -136,7 +613,37 @@ impl Config {
             warn!("No config file found at {:?}", config_dir);
         }
 
+        let user_config =
+            std::fs::read_to_string(config_dir.join(CONFIG_FILE_NAME))
+                .ok()
+                .and_then(|content| content.parse::<toml::Table>().ok())
+                .unwrap_or_default();
+        let profile =
+            find_profile(&user_config, profile, hostname().as_deref())
+                .map_err(config::ConfigError::Message)?;
+        if let Some((name, overrides)) = &profile {
+            debug!("Using the {:?} profile", name);
+            let overrides = toml::to_string(overrides)
+                .map_err(|e| config::ConfigError::Foreign(Box::new(e)))?;
+            builder = builder.add_source(config::File::from_str(
+                &overrides,
+                config::FileFormat::Toml,
+            ));
+        }
+
         let mut cfg: Self = builder.build()?.try_deserialize()?;
+        cfg.profile = profile.map(|(name, _)| name);
+
+        // conflicts are lost once keys are parsed, look for them as written
+        if let Some(conflicts) =
+            std::fs::read_to_string(config_dir.join(CONFIG_FILE_NAME))
+                .ok()
+                .and_then(|content| config_keybinding_conflicts(&content).ok())
+        {
+            for conflict in conflicts {
+                warn!("Keybinding conflict: {}", conflict);
+            }
+        }
 
         for (mode, default_bindings) in default_config.keybindings.iter() {
             let user_bindings = cfg.keybindings.entry(*mode).or_default();
@@ -153,36 +660,190 @@ impl Config {
 
         Ok(cfg)
     }
+
+    /// The context the channels of a picker are created with: the settings
+    /// they read (the cable channels, the search scope, the matchers'
+    /// threads, ...) along with the user's scripts and plugins.
+    pub fn channel_context(&self) -> Context {
+        let mut ctx = Context::default();
+        ctx.set_cable_channels(self.cable_channels.clone());
+        #[cfg(feature = "scripting")]
+        ctx.set_scripts(
+            load_scripts(&scripts_dir())
+                .into_iter()
+                .filter_map(|script| {
+                    script.inspect_err(|e| warn!("Skipping script: {e}")).ok()
+                })
+                .collect(),
+        );
+        #[cfg(feature = "plugins")]
+        ctx.set_plugins(
+            load_plugins(&plugins_dir())
+                .into_iter()
+                .filter_map(|plugin| {
+                    plugin.inspect_err(|e| warn!("Skipping plugin: {e}")).ok()
+                })
+                .collect(),
+        );
+        ctx.set_scope(self.search.scope);
+        ctx.set_recent_limits(self.recent.limits());
+        ctx.set_repo_roots(self.search.repo_roots());
+        ctx.set_available_channels(self.ui.available_channels.clone());
+        // the matchers' thread pools are created along with the channels
+        for (name, channel_config) in &self.channels {
+            if let Some(n_threads) = channel_config.matcher_threads {
+                ctx.set_matcher_threads(name, n_threads);
+            }
+            if let Some(chunking) = channel_config.chunking() {
+                ctx.set_chunking(name, chunking);
+            }
+        }
+        ctx
+    }
+
+    /// The action bound to the given key in the given mode, the keybindings
+    /// of the given channel taking precedence over the global ones.
+    pub fn action_for_key(
+        &self,
+        channel: &str,
+        mode: Mode,
+        key: Key,
+    ) -> Option<Action> {
+        [
+            self.channels.get(channel).map(|c| &c.keybindings),
+            Some(&self.keybindings),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|keybindings| keybindings.get(&mode)?.get(&key).cloned())
+    }
+
+    /// Make the given keys confirm the selection tagged with their name (see
+    /// `--expect`), whatever they are bound to.
+    ///
+    /// # Errors
+    /// If one of the keys isn't valid.
+    pub fn expect_keys(&mut self, keys: &[String]) -> Result<(), String> {
+        for raw in keys {
+            let key = parse_key(raw)?;
+            self.keybindings
+                .entry(Mode::Channel)
+                .or_default()
+                .insert(key, Action::Expect(raw.clone()));
+            for channel in self.channels.values_mut() {
+                if let Some(bindings) =
+                    channel.keybindings.get_mut(&Mode::Channel)
+                {
+                    bindings.remove(&key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The keymap of the given mode in the given channel: the global
+    /// keybindings, overridden by the channel's.
+    pub fn keymap(&self, channel: &str, mode: Mode) -> HashMap<Key, Action> {
+        let mut keymap =
+            self.keybindings.get(&mode).cloned().unwrap_or_default();
+        if let Some(overrides) = self
+            .channels
+            .get(channel)
+            .and_then(|c| c.keybindings.get(&mode))
+        {
+            keymap.extend(overrides.iter().map(|(k, a)| (*k, a.clone())));
+        }
+        keymap
+    }
 }
 
-pub fn get_data_dir() -> PathBuf {
-    let directory = if let Some(s) = DATA_FOLDER.clone() {
-        s
-    } else if let Some(proj_dirs) = project_directory() {
-        proj_dirs.data_local_dir().to_path_buf()
-    } else {
-        PathBuf::from(".").join(".data")
+/// The profile of the given config file to apply: the one with the given
+/// name, else the first one (by name) listing the given host. The profile's
+/// settings are returned without its `hosts`.
+fn find_profile(
+    user_config: &toml::Table,
+    name: Option<&str>,
+    host: Option<&str>,
+) -> Result<Option<(String, toml::Table)>, String> {
+    let no_profiles = toml::Table::new();
+    let profiles = match user_config.get(PROFILES_KEY) {
+        Some(toml::Value::Table(profiles)) => profiles,
+        Some(_) => return Err(format!("`{PROFILES_KEY}` must be a table")),
+        None => &no_profiles,
+    };
+    let lists_host = |profile: &toml::Value| {
+        let hosts = profile
+            .get(PROFILE_HOSTS_KEY)
+            .and_then(toml::Value::as_array);
+        host.zip(hosts).is_some_and(|(host, hosts)| {
+            hosts
+                .iter()
+                .filter_map(toml::Value::as_str)
+                .any(|h| h.eq_ignore_ascii_case(host))
+        })
+    };
+    let mut names: Vec<&String> = profiles.keys().collect();
+    names.sort();
+    let name = match name {
+        Some(name) => names.iter().find(|n| **n == name).ok_or_else(|| {
+            format!(
+                "unknown profile `{name}` (expected one of: {})",
+                names
+                    .iter()
+                    .map(|n| n.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?,
+        None => match names.iter().find(|n| lists_host(&profiles[**n])) {
+            Some(name) => name,
+            None => return Ok(None),
+        },
+    };
+    let Some(toml::Value::Table(settings)) = profiles.get(*name) else {
+        return Err(format!("the `{name}` profile must be a table"));
     };
-    directory
+    let mut settings = settings.clone();
+    settings.remove(PROFILE_HOSTS_KEY);
+    Ok(Some(((*name).clone(), settings)))
 }
 
-pub fn get_config_dir() -> PathBuf {
-    let directory = if let Some(s) = CONFIG_FOLDER.clone() {
-        s
-    } else if let Some(proj_dirs) = project_directory() {
-        proj_dirs.config_local_dir().to_path_buf()
-    } else {
-        PathBuf::from(".").join(".config")
-    };
-    info!("Using config directory: {:?}", directory);
-    directory
+/// The name of the machine, to pick the profile to apply.
+fn hostname() -> Option<String> {
+    #[cfg(unix)]
+    {
+        let mut buffer = [0u8; 256];
+        // SAFETY: the buffer is valid for its whole length
+        let result = unsafe {
+            libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len())
+        };
+        if result != 0 {
+            return None;
+        }
+        let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+        Some(String::from_utf8_lossy(&buffer[..len]).into_owned())
+    }
+    #[cfg(not(unix))]
+    {
+        std::env::var("COMPUTERNAME").ok()
+    }
 }
 
-fn project_directory() -> Option<ProjectDirs> {
-    ProjectDirs::from("com", "alexpasmantier", env!("CARGO_PKG_NAME"))
+/// Expand a leading `~` to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), directories::BaseDirs::new()) {
+        (Some(rest), Some(base_dirs))
+            if rest.is_empty() || rest.starts_with(['/', '\\']) =>
+        {
+            base_dirs
+                .home_dir()
+                .join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
 }
 
-#[derive(Clone, Debug, Default, Deref, DerefMut)]
+#[derive(Clone, Debug, Default, Deref, DerefMut, PartialEq)]
 pub struct KeyBindings(pub HashMap<Mode, HashMap<Key, Action>>);
 
 impl<'de> Deserialize<'de> for KeyBindings {
@@ -191,7 +852,7 @@ impl<'de> Deserialize<'de> for KeyBindings {
         D: Deserializer<'de>,
     {
         let parsed_map =
-            HashMap::<Mode, HashMap<String, Action>>::deserialize(
+            HashMap::<Mode, HashMap<String, BoundAction>>::deserialize(
                 deserializer,
             )?;
 
@@ -200,16 +861,241 @@ impl<'de> Deserialize<'de> for KeyBindings {
             .map(|(mode, inner_map)| {
                 let converted_inner_map = inner_map
                     .into_iter()
-                    .map(|(key_str, cmd)| (parse_key(&key_str).unwrap(), cmd))
-                    .collect();
-                (mode, converted_inner_map)
+                    .map(|(key_str, cmd)| Ok((parse_key(&key_str)?, cmd.0)))
+                    .collect::<Result<_, String>>()?;
+                Ok((mode, converted_inner_map))
             })
-            .collect();
+            .collect::<Result<_, String>>()
+            .map_err(serde::de::Error::custom)?;
 
         Ok(KeyBindings(keybindings))
     }
 }
 
+/// An action bound to a key in a config file (see `parse_action`).
+struct BoundAction(Action);
+
+impl<'de> Deserialize<'de> for BoundAction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        parse_action(&raw)
+            .map(BoundAction)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parse the name of an action bound to a key, either in pascal case (e.g.
+/// `SelectNextEntry`) or in snake case (`select_next_entry`).
+pub fn parse_action(raw: &str) -> Result<Action, String> {
+    let name: String = if raw.starts_with(|c: char| c.is_ascii_lowercase()) {
+        raw.split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_ascii_uppercase().to_string() + chars.as_str()
+                })
+            })
+            .collect()
+    } else {
+        raw.to_string()
+    };
+    Action::deserialize(serde::de::value::StrDeserializer::<
+        serde::de::value::Error,
+    >::new(&name))
+    .map_err(|_| format!("unknown action `{raw}`"))
+}
+
+/// The keybindings of a config file as written, i.e. without parsing the
+/// key specifications, by mode.
+pub type RawKeyBindings = HashMap<Mode, HashMap<String, Action>>;
+
+/// Parse raw keybindings, resolving the names of their actions.
+fn into_raw_keybindings(
+    bindings: HashMap<Mode, HashMap<String, BoundAction>>,
+) -> RawKeyBindings {
+    bindings
+        .into_iter()
+        .map(|(mode, bindings)| {
+            let bindings = bindings
+                .into_iter()
+                .map(|(key, action)| (key, action.0))
+                .collect();
+            (mode, bindings)
+        })
+        .collect()
+}
+
+/// The keys handled by the input field before keybindings are looked up,
+/// along with their actions. These keys therefore can't be bound, and
+/// neither can characters, which are added to the input.
+pub const INPUT_KEYS: [(Key, Action); 8] = [
+    (Key::Backspace, Action::DeletePrevChar),
+    (Key::Delete, Action::DeleteNextChar),
+    (Key::Left, Action::GoToPrevChar),
+    (Key::Right, Action::GoToNextChar),
+    (Key::Home, Action::GoToInputStart),
+    (Key::Ctrl('a'), Action::GoToInputStart),
+    (Key::End, Action::GoToInputEnd),
+    (Key::Ctrl('e'), Action::GoToInputEnd),
+];
+
+/// The action of the input field for the given key, if any.
+pub fn input_action(key: Key) -> Option<Action> {
+    match key {
+        Key::Char(c) => Some(Action::AddInputChar(c)),
+        _ => INPUT_KEYS
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, action)| action.clone()),
+    }
+}
+
+/// Parse the keybindings of a config file as written, i.e. without parsing
+/// the key specifications.
+pub fn raw_keybindings(
+    config_file: &str,
+) -> Result<RawKeyBindings, toml::de::Error> {
+    #[derive(Deserialize)]
+    struct RawConfig {
+        #[serde(default)]
+        keybindings: HashMap<Mode, HashMap<String, BoundAction>>,
+    }
+    toml::from_str::<RawConfig>(config_file)
+        .map(|c| into_raw_keybindings(c.keybindings))
+}
+
+/// Parse the keybindings of the channels of a config file as written (see
+/// `raw_keybindings`), by channel name.
+pub fn raw_channel_keybindings(
+    config_file: &str,
+) -> Result<HashMap<String, RawKeyBindings>, toml::de::Error> {
+    #[derive(Deserialize)]
+    struct RawChannelConfig {
+        #[serde(default)]
+        keybindings: HashMap<Mode, HashMap<String, BoundAction>>,
+    }
+    #[derive(Deserialize)]
+    struct RawConfig {
+        #[serde(default)]
+        channels: HashMap<String, RawChannelConfig>,
+    }
+    toml::from_str::<RawConfig>(config_file).map(|c| {
+        c.channels
+            .into_iter()
+            .map(|(name, channel)| {
+                (name, into_raw_keybindings(channel.keybindings))
+            })
+            .filter(|(_, bindings)| !bindings.is_empty())
+            .collect()
+    })
+}
+
+/// Describe the keybinding conflicts of a config file, the global ones first
+/// and then the ones of the channels (prefixed with the channel's name).
+pub fn config_keybinding_conflicts(
+    config_file: &str,
+) -> Result<Vec<String>, toml::de::Error> {
+    let mut conflicts: Vec<String> =
+        keybinding_conflicts(&raw_keybindings(config_file)?)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+    let mut channels: Vec<_> =
+        raw_channel_keybindings(config_file)?.into_iter().collect();
+    channels.sort_by(|a, b| a.0.cmp(&b.0));
+    for (name, bindings) in channels {
+        conflicts.extend(
+            keybinding_conflicts(&bindings)
+                .iter()
+                .map(|conflict| format!("channels.{name} {conflict}")),
+        );
+    }
+    Ok(conflicts)
+}
+
+/// A problem with the keybindings of a mode.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeybindingConflict {
+    /// Several of the given key specifications resolve to the same key, so
+    /// only one of their actions is kept.
+    Duplicate {
+        mode: Mode,
+        key: Key,
+        bindings: Vec<(String, Action)>,
+    },
+    /// The key is handled by the input field, so the action never fires.
+    Reserved {
+        mode: Mode,
+        key: Key,
+        action: Action,
+    },
+}
+
+impl std::fmt::Display for KeybindingConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeybindingConflict::Duplicate {
+                mode,
+                key,
+                bindings,
+            } => {
+                let bindings = bindings
+                    .iter()
+                    .map(|(raw, action)| format!("`{raw}` = {action:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "[{mode}] {key} is bound several times: {bindings}")
+            }
+            KeybindingConflict::Reserved { mode, key, action } => write!(
+                f,
+                "[{mode}] {key} is reserved for the input field, {action:?} can't be bound to it"
+            ),
+        }
+    }
+}
+
+/// Find the conflicts in the given keybindings, as written in a config file
+/// (i.e. before the key specifications are parsed).
+///
+/// Key specifications that can't be parsed are ignored.
+pub fn keybinding_conflicts(raw: &RawKeyBindings) -> Vec<KeybindingConflict> {
+    let mut conflicts = Vec::new();
+    for (mode, bindings) in raw {
+        let mut by_key: HashMap<Key, Vec<(String, Action)>> = HashMap::new();
+        for (raw_key, action) in bindings {
+            if let Ok(key) = parse_key(raw_key) {
+                by_key
+                    .entry(key)
+                    .or_default()
+                    .push((raw_key.clone(), action.clone()));
+            }
+        }
+        for (key, mut bindings) in by_key {
+            bindings.sort_by(|a, b| a.0.cmp(&b.0));
+            if input_action(key).is_some() {
+                conflicts.extend(bindings.into_iter().map(|(_, action)| {
+                    KeybindingConflict::Reserved {
+                        mode: *mode,
+                        key,
+                        action,
+                    }
+                }));
+            } else if bindings.len() > 1 {
+                conflicts.push(KeybindingConflict::Duplicate {
+                    mode: *mode,
+                    key,
+                    bindings,
+                });
+            }
+        }
+    }
+    conflicts.sort_by_key(ToString::to_string);
+    conflicts
+}
+
 fn parse_key_event(raw: &str) -> Result<KeyEvent, String> {
     let raw_lower = raw.to_ascii_lowercase();
     let (remaining, modifiers) = extract_modifiers(&raw_lower);
@@ -513,6 +1399,346 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_deserialize_secondary_previews() {
+        let ui: UiConfig = toml::from_str(
+            r#"
+            use_nerd_font_icons = false
+            ui_scale = 80
+
+            [secondary_previews.files]
+            command = "git log --oneline -- {}"
+            height = 40
+
+            [secondary_previews.text]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            ui.secondary_previews["files"],
+            SecondaryPreviewConfig {
+                command: Some("git log --oneline -- {}".to_string()),
+                height: 40,
+            }
+        );
+        assert_eq!(
+            ui.secondary_previews["text"],
+            SecondaryPreviewConfig {
+                command: None,
+                height: DEFAULT_SECONDARY_PREVIEW_HEIGHT,
+            }
+        );
+    }
+
+    #[test]
+    fn test_default_config_placeholders() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert_eq!(config.ui.placeholders["files"], "Search files…");
+        assert!(config.ui.completion_hints);
+    }
+
+    #[test]
+    fn test_deserialize_channels() {
+        let config: Config = toml::from_str(
+            r#"
+            [ui]
+            use_nerd_font_icons = false
+            ui_scale = 80
+
+            [channels.alias]
+            refresh_interval = 2.5
+
+            [channels.files]
+            matcher_threads = 16
+            high_watermark = 1000
+            preview_command = "file {}"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.channels["alias"].refresh_interval, Some(2.5));
+        assert_eq!(config.channels["alias"].matcher_threads, None);
+        assert_eq!(config.channels["files"].matcher_threads, Some(16));
+        assert_eq!(
+            config.channels["files"].chunking(),
+            Some(Chunking {
+                high_watermark: 1000,
+                ..Chunking::default()
+            })
+        );
+        assert_eq!(config.channels["alias"].chunking(), None);
+        assert_eq!(
+            config.channels["files"].preview_command.as_deref(),
+            Some("file {}")
+        );
+    }
+
+    #[test]
+    fn test_command_previewer_config() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert_eq!(config.previewers.command.sandbox(), Sandbox::default());
+
+        let config: PreviewersConfig = toml::from_str(
+            r#"
+            file.theme = "Nord"
+            command.timeout = 0.5
+            command.env = ["HTTP_PROXY"]
+            "#,
+        )
+        .unwrap();
+        let sandbox = config.command.sandbox();
+        assert_eq!(sandbox.timeout, Duration::from_millis(500));
+        assert_eq!(sandbox.env, vec!["HTTP_PROXY".to_string()]);
+        assert_eq!(sandbox.max_output_size, 1024 * 1024);
+    }
+
+    #[test]
+    fn test_image_previewer_config() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert_eq!(
+            config.previewers.image.protocol,
+            ImageProtocolConfig::Auto
+        );
+
+        let config: ImagePreviewerConfig =
+            toml::from_str(r#"protocol = "half-blocks""#).unwrap();
+        assert_eq!(config.protocol(), GraphicsProtocol::HalfBlocks);
+        assert!(
+            toml::from_str::<ImagePreviewerConfig>(r#"protocol = "png""#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_execution_config() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert_eq!(config.execution.backend, ExecutionBackendConfig::Child);
+
+        let config: ExecutionConfig =
+            toml::from_str(r#"backend = "systemd-run""#).unwrap();
+        assert_eq!(
+            ExecutionBackend::from(config.backend),
+            ExecutionBackend::SystemdRun
+        );
+    }
+
+    #[test]
+    fn test_cable_channels() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert!(config.cable_channels.is_empty());
+
+        let config: Config = toml::from_str(&format!(
+            r#"{CONFIG}
+            [[cable_channels]]
+            name = "dirs"
+            source_command = "ls -d */"
+            preview_command = "ls {{}}"
+            "#
+        ))
+        .unwrap();
+        assert_eq!(
+            config.cable_channels,
+            vec![CableChannelPrototype {
+                name: "dirs".to_string(),
+                source_command: "ls -d */".to_string(),
+                preview_command: Some("ls {}".to_string()),
+                output: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_profile() {
+        let user_config: toml::Table = toml::from_str(
+            r#"
+            [ui]
+            ui_scale = 80
+
+            [profiles.work]
+            hosts = ["work-laptop", "build-01"]
+            ui.available_channels = ["files", "text", "gitrepos"]
+            search.repo_roots = ["~/work"]
+            previewers.file.theme = "Nord"
+
+            [profiles.personal]
+            previewers.file.theme = "Monokai Extended"
+            "#,
+        )
+        .unwrap();
+        let (name, settings) =
+            find_profile(&user_config, None, Some("Work-Laptop"))
+                .unwrap()
+                .unwrap();
+        assert_eq!(name, "work");
+        assert!(!settings.contains_key(PROFILE_HOSTS_KEY));
+        assert_eq!(
+            settings["previewers"]["file"]["theme"].as_str(),
+            Some("Nord")
+        );
+        // an explicit profile wins over the host
+        let (name, _) =
+            find_profile(&user_config, Some("personal"), Some("build-01"))
+                .unwrap()
+                .unwrap();
+        assert_eq!(name, "personal");
+        assert_eq!(find_profile(&user_config, None, Some("other")), Ok(None));
+        assert_eq!(find_profile(&user_config, None, None), Ok(None));
+        assert_eq!(
+            find_profile(&user_config, Some("home"), None),
+            Err("unknown profile `home` (expected one of: personal, work)"
+                .to_string())
+        );
+        assert_eq!(find_profile(&toml::Table::new(), None, None), Ok(None));
+
+        let search: SearchConfig =
+            toml::from_str(r#"repo_roots = ["~/work", "/src"]"#).unwrap();
+        let home = directories::BaseDirs::new().unwrap();
+        assert_eq!(
+            search.repo_roots(),
+            vec![home.home_dir().join("work"), PathBuf::from("/src")]
+        );
+    }
+
+    #[test]
+    fn test_recent_limits() {
+        assert_eq!(RecentConfig::default().limits(), RecentLimits::default());
+        let recent: RecentConfig =
+            toml::from_str("max_entries = 20\nmax_age = \"2w\"").unwrap();
+        assert_eq!(
+            recent.limits(),
+            RecentLimits {
+                max_entries: 20,
+                max_age: Some(Duration::from_secs(14 * 24 * 60 * 60)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_keybinding() {
+        let result = toml::from_str::<Config>(
+            r#"
+            [ui]
+            use_nerd_font_icons = false
+            ui_scale = 80
+
+            [keybindings.Channel]
+            ctrl-nope = "Quit"
+            "#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(
+            parse_action("SelectNextEntry"),
+            Ok(Action::SelectNextEntry)
+        );
+        assert_eq!(
+            parse_action("select_next_entry"),
+            Ok(Action::SelectNextEntry)
+        );
+        assert_eq!(parse_action("quit"), Ok(Action::Quit));
+        assert!(parse_action("select_next").is_err());
+        assert!(parse_action("AddInputChar").is_err());
+    }
+
+    #[test]
+    fn test_channel_keybindings() {
+        let config = toml::from_str::<Config>(
+            r#"
+            [ui]
+            use_nerd_font_icons = false
+            ui_scale = 80
+
+            [keybindings.Channel]
+            esc = "quit"
+            ctrl-o = "EditEntry"
+
+            [channels.files.keybindings.Channel]
+            ctrl-o = "open_entry"
+            "#,
+        )
+        .unwrap();
+        let ctrl_o = Key::Ctrl('o');
+        assert_eq!(
+            config.action_for_key("files", Mode::Channel, ctrl_o),
+            Some(Action::OpenEntry)
+        );
+        assert_eq!(
+            config.action_for_key("text", Mode::Channel, ctrl_o),
+            Some(Action::EditEntry)
+        );
+        assert_eq!(
+            config.action_for_key("files", Mode::Channel, Key::Esc),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            config.keymap("files", Mode::Channel),
+            HashMap::from([
+                (Key::Esc, Action::Quit),
+                (ctrl_o, Action::OpenEntry)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_expect_keys() {
+        let mut config = toml::from_str::<Config>(
+            r#"
+            [ui]
+            use_nerd_font_icons = false
+            ui_scale = 80
+
+            [channels.files.keybindings.Channel]
+            ctrl-o = "open_entry"
+            "#,
+        )
+        .unwrap();
+        config
+            .expect_keys(&["ctrl-o".to_string(), "alt-v".to_string()])
+            .unwrap();
+        assert_eq!(
+            config.action_for_key("files", Mode::Channel, Key::Ctrl('o')),
+            Some(Action::Expect("ctrl-o".to_string()))
+        );
+        assert_eq!(
+            config.action_for_key("text", Mode::Channel, Key::Alt('v')),
+            Some(Action::Expect("alt-v".to_string()))
+        );
+        assert!(config.expect_keys(&["ctrl-nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_keybinding_conflicts() {
+        let raw = HashMap::from([(
+            Mode::Channel,
+            HashMap::from([
+                ("ctrl-j".to_string(), Action::SelectNextEntry),
+                ("Ctrl-J".to_string(), Action::SelectPrevEntry),
+                ("left".to_string(), Action::Quit),
+                ("ctrl-k".to_string(), Action::SelectPrevEntry),
+            ]),
+        )]);
+        assert_eq!(
+            keybinding_conflicts(&raw),
+            vec![
+                KeybindingConflict::Duplicate {
+                    mode: Mode::Channel,
+                    key: Key::Ctrl('j'),
+                    bindings: vec![
+                        ("Ctrl-J".to_string(), Action::SelectPrevEntry),
+                        ("ctrl-j".to_string(), Action::SelectNextEntry),
+                    ],
+                },
+                KeybindingConflict::Reserved {
+                    mode: Mode::Channel,
+                    key: Key::Left,
+                    action: Action::Quit,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_style_default() {
         let style = parse_style("");
@@ -563,7 +1789,7 @@ mod tests {
 
     #[test]
     fn test_config() -> Result<()> {
-        let c = Config::new()?;
+        let c = Config::new(None)?;
         assert_eq!(
             c.keybindings
                 .get(&Mode::Channel)
@@ -0,0 +1,448 @@
+#[cfg(feature = "plugins")]
+use crate::config::plugins_dir;
+#[cfg(feature = "scripting")]
+use crate::config::scripts_dir;
+use clap::ValueEnum;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+#[cfg(feature = "plugins")]
+use television_channels::channels::plugin::load_plugins;
+#[cfg(feature = "scripting")]
+use television_channels::channels::script::load_scripts;
+use television_channels::channels::CliTvChannel;
+use television_utils::image::GraphicsProtocol;
+use television_utils::shell::find_executable;
+
+use crate::config::{config_file_path, config_keybinding_conflicts, Config};
+use crate::setup::OPTIONAL_DEPENDENCIES;
+use crate::ui::theme::Theme;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// The result of a single diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Check {
+    status: Status,
+    name: String,
+    detail: String,
+}
+
+impl Check {
+    fn new(status: Status, name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            name: name.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Diagnose the environment television runs in (terminal capabilities,
+/// optional tools, config and keybindings) and print the results.
+///
+/// Fails if any error was found, so that it can be used in scripts.
+pub fn run(profile: Option<&str>) -> Result<()> {
+    let env = |name: &str| std::env::var(name).ok();
+    let sections = [
+        ("Terminal", terminal_checks(env)),
+        ("Tools", tool_checks()),
+        (
+            "Config",
+            [config_checks(profile), script_checks(), plugin_checks()]
+                .concat(),
+        ),
+    ];
+
+    let mut errors = 0;
+    for (title, checks) in sections {
+        println!("{title}");
+        for check in checks {
+            let marker = match check.status {
+                Status::Ok => "✓",
+                Status::Warning => "!",
+                Status::Error => {
+                    errors += 1;
+                    "✗"
+                }
+            };
+            println!("  {marker} {:<16} {}", check.name, check.detail);
+        }
+        println!();
+    }
+    if errors > 0 {
+        bail!("{errors} problem(s) found");
+    }
+    println!("No problems found.");
+    Ok(())
+}
+
+/// The capabilities of the terminal.
+///
+/// They can't be queried without taking over the terminal, so they're
+/// inferred from the environment variables terminals set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TerminalCapabilities {
+    pub truecolor: bool,
+    pub graphics: GraphicsProtocol,
+    /// Whether the terminal sets the clipboard on OSC52 sequences.
+    pub osc52: bool,
+    pub tmux: bool,
+}
+
+impl TerminalCapabilities {
+    pub fn detect(env: impl Fn(&str) -> Option<String>) -> Self {
+        let term = env("TERM").unwrap_or_default();
+        let term_program = env("TERM_PROGRAM").unwrap_or_default();
+        let osc52 = term.contains("kitty")
+            || env("KITTY_WINDOW_ID").is_some()
+            || term_program == "WezTerm"
+            || term.contains("ghostty")
+            || term_program == "ghostty"
+            || term.contains("alacritty")
+            || term.starts_with("foot")
+            || term_program == "iTerm.app"
+            || env("WT_SESSION").is_some();
+        Self {
+            truecolor: matches!(
+                env("COLORTERM").as_deref(),
+                Some("truecolor" | "24bit")
+            ),
+            graphics: GraphicsProtocol::detect(&env),
+            osc52,
+            tmux: env("TMUX").is_some(),
+        }
+    }
+}
+
+fn terminal_checks(env: impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let capabilities = TerminalCapabilities::detect(env);
+
+    let truecolor = if capabilities.truecolor {
+        Check::new(Status::Ok, "truecolor", "supported")
+    } else {
+        Check::new(
+            Status::Warning,
+            "truecolor",
+            "not detected, colors will be approximated (set \
+             `COLORTERM=truecolor` if your terminal supports it)",
+        )
+    };
+
+    let graphics = match capabilities.graphics {
+        _ if !cfg!(feature = "image-preview") => Check::new(
+            Status::Warning,
+            "graphics",
+            "built without the `image-preview` feature, images are \
+             previewed as binary files",
+        ),
+        GraphicsProtocol::HalfBlocks => Check::new(
+            Status::Warning,
+            "graphics",
+            "no graphics protocol detected, images are previewed with \
+             unicode half blocks",
+        ),
+        protocol => Check::new(
+            Status::Ok,
+            "graphics",
+            format!("images are previewed with {protocol}"),
+        ),
+    };
+
+    let osc52 = if capabilities.tmux {
+        Check::new(
+            Status::Warning,
+            "OSC52",
+            "running in tmux, the clipboard can only be reached with \
+             `set -g set-clipboard on`",
+        )
+    } else if capabilities.osc52 {
+        Check::new(Status::Ok, "OSC52", "supported")
+    } else {
+        Check::new(
+            Status::Warning,
+            "OSC52",
+            "not detected, copying may not work over SSH",
+        )
+    };
+
+    vec![truecolor, graphics, osc52]
+}
+
+fn tool_checks() -> Vec<Check> {
+    OPTIONAL_DEPENDENCIES
+        .iter()
+        .map(|(tool, unlocks)| match find_executable(tool) {
+            Some(path) => {
+                Check::new(Status::Ok, tool, format!("{}", path.display()))
+            }
+            None => Check::new(
+                Status::Warning,
+                tool,
+                format!("missing, install it for {unlocks}"),
+            ),
+        })
+        .collect()
+}
+
+fn config_checks(profile: Option<&str>) -> Vec<Check> {
+    let path = config_file_path();
+    if !path.is_file() {
+        return vec![Check::new(
+            Status::Ok,
+            "config file",
+            format!(
+                "none at {}, using the defaults (run `tv setup` to write one)",
+                path.display()
+            ),
+        )];
+    }
+    let mut checks = Vec::new();
+    match Config::new(profile) {
+        Ok(config) => {
+            checks.push(Check::new(
+                Status::Ok,
+                "config file",
+                format!("{} is valid", path.display()),
+            ));
+            if let Some(profile) = &config.profile {
+                checks.push(Check::new(
+                    Status::Ok,
+                    "profile",
+                    format!("`{profile}` applied"),
+                ));
+            }
+            checks.push(theme_check(&config));
+            checks.extend(unknown_channel_checks(&config));
+        }
+        Err(e) => {
+            checks.push(Check::new(
+                Status::Error,
+                "config file",
+                format!("{} is invalid: {e}", path.display()),
+            ));
+        }
+    }
+    checks.push(keybinding_checks(
+        &std::fs::read_to_string(&path).unwrap_or_default(),
+    ));
+    checks
+}
+
+/// Flag the scripts that don't compile, which are skipped.
+#[cfg(feature = "scripting")]
+fn script_checks() -> Vec<Check> {
+    let dir = scripts_dir();
+    let scripts = load_scripts(&dir);
+    let mut checks: Vec<Check> = scripts
+        .iter()
+        .filter_map(|script| script.as_ref().err())
+        .map(|e| Check::new(Status::Error, "script", e.to_string()))
+        .collect();
+    if checks.is_empty() && !scripts.is_empty() {
+        checks.push(Check::new(
+            Status::Ok,
+            "scripts",
+            format!("{} loaded from {}", scripts.len(), dir.display()),
+        ));
+    }
+    checks
+}
+
+#[cfg(not(feature = "scripting"))]
+fn script_checks() -> Vec<Check> {
+    Vec::new()
+}
+
+/// Flag the plugins that aren't valid, which are skipped.
+#[cfg(feature = "plugins")]
+fn plugin_checks() -> Vec<Check> {
+    let dir = plugins_dir();
+    let plugins = load_plugins(&dir);
+    let mut checks: Vec<Check> = plugins
+        .iter()
+        .filter_map(|plugin| plugin.as_ref().err())
+        .map(|e| Check::new(Status::Error, "plugin", e.to_string()))
+        .collect();
+    if checks.is_empty() && !plugins.is_empty() {
+        checks.push(Check::new(
+            Status::Ok,
+            "plugins",
+            format!("{} loaded from {}", plugins.len(), dir.display()),
+        ));
+    }
+    checks
+}
+
+#[cfg(not(feature = "plugins"))]
+fn plugin_checks() -> Vec<Check> {
+    Vec::new()
+}
+
+/// The lowercase names of the channels, as used in channel specific
+/// settings.
+pub(crate) fn channel_names() -> Vec<String> {
+    CliTvChannel::value_variants()
+        .iter()
+        .map(|c| c.to_string().to_lowercase())
+        .chain(std::iter::once("stdin".to_string()))
+        .collect()
+}
+
+/// Flag the channel specific settings that don't refer to any channel (e.g.
+/// misspelled or compiled out).
+fn unknown_channel_checks(config: &Config) -> Vec<Check> {
+    let mut known = channel_names();
+    known.extend(
+        config
+            .cable_channels
+            .iter()
+            .map(|prototype| prototype.name.to_lowercase()),
+    );
+    #[cfg(feature = "scripting")]
+    known.extend(
+        load_scripts(&scripts_dir())
+            .into_iter()
+            .flatten()
+            .filter(|script| script.is_channel())
+            .map(|script| script.name.to_lowercase()),
+    );
+    #[cfg(feature = "plugins")]
+    known.extend(
+        load_plugins(&plugins_dir())
+            .into_iter()
+            .flatten()
+            .map(|plugin| plugin.name.to_lowercase()),
+    );
+    let settings: [(&str, Vec<&String>); 6] = [
+        (
+            "ui.no_preview_channels",
+            config.ui.no_preview_channels.iter().collect(),
+        ),
+        (
+            "ui.no_input_channels",
+            config.ui.no_input_channels.iter().collect(),
+        ),
+        ("ui.placeholders", config.ui.placeholders.keys().collect()),
+        (
+            "ui.secondary_previews",
+            config.ui.secondary_previews.keys().collect(),
+        ),
+        ("channels", config.channels.keys().collect()),
+        (
+            "ui.available_channels",
+            config.ui.available_channels.iter().collect(),
+        ),
+    ];
+    let mut checks: Vec<Check> = settings
+        .into_iter()
+        .flat_map(|(setting, channels)| {
+            channels
+                .into_iter()
+                .filter(|c| !known.contains(c))
+                .map(|c| {
+                    Check::new(
+                        Status::Warning,
+                        setting,
+                        format!(
+                            "unknown channel `{c}` (expected one of: {})",
+                            known.join(", ")
+                        ),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    checks.sort_by(|a, b| a.detail.cmp(&b.detail));
+    checks
+}
+
+fn theme_check(config: &Config) -> Check {
+    match Theme::from_config(&config.ui.theme, &config.themes) {
+        Ok(_) => Check::new(
+            Status::Ok,
+            "theme",
+            format!("`{}` applied", config.ui.theme),
+        ),
+        Err(e) => Check::new(Status::Error, "theme", e),
+    }
+}
+
+fn keybinding_checks(config_file: &str) -> Check {
+    let Ok(conflicts) = config_keybinding_conflicts(config_file) else {
+        return Check::new(
+            Status::Error,
+            "keybindings",
+            "couldn't be read, see the config file error above",
+        );
+    };
+    if conflicts.is_empty() {
+        Check::new(Status::Ok, "keybindings", "no conflicts")
+    } else {
+        Check::new(
+            Status::Error,
+            "keybindings",
+            conflicts.join("\n                     "),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_checks() {
+        let env = |name: &str| match name {
+            "COLORTERM" => Some("truecolor".to_string()),
+            "TERM" => Some("xterm-kitty".to_string()),
+            "TMUX" => Some("/tmp/tmux-1000/default".to_string()),
+            _ => None,
+        };
+        let statuses: Vec<_> = terminal_checks(env)
+            .into_iter()
+            .map(|c| (c.name, c.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("truecolor".to_string(), Status::Ok),
+                // tmux doesn't forward the graphics protocols
+                ("graphics".to_string(), Status::Warning),
+                ("OSC52".to_string(), Status::Warning),
+            ]
+        );
+        assert!(terminal_checks(|_| None)
+            .iter()
+            .all(|c| c.status == Status::Warning));
+    }
+
+    #[test]
+    fn test_theme_check() {
+        let mut config = Config::default();
+        assert_eq!(theme_check(&config).status, Status::Ok);
+        config.ui.theme = "nope".to_string();
+        assert_eq!(theme_check(&config).status, Status::Error);
+    }
+
+    #[test]
+    fn test_keybinding_checks() {
+        let check = keybinding_checks(
+            r#"
+            [keybindings.Channel]
+            ctrl-j = "SelectNextEntry"
+            Ctrl-J = "SelectPrevEntry"
+            "#,
+        );
+        assert_eq!(check.status, Status::Error);
+        assert!(check.detail.contains("bound several times"));
+        assert_eq!(
+            keybinding_checks("[ui]\nui_scale = 80\n").status,
+            Status::Ok
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Running the picker from other applications, without spawning `tv`.
+use std::io::{stdout, IsTerminal};
+
+use color_eyre::Result;
+use television_channels::channels::{CliTvChannel, TelevisionChannel};
+use television_channels::context::Context;
+use television_channels::entry::Entry;
+
+use crate::app::App;
+use crate::config::Config;
+use crate::undo;
+
+/// The tick rate and frame rate of the `tv` binary.
+const TICK_RATE: f64 = 50.0;
+const FRAME_RATE: f64 = 60.0;
+
+/// Creates the channel of a picker from its context.
+type ChannelFactory = Box<dyn FnOnce(&Context) -> TelevisionChannel>;
+
+/// A picker to run over a channel, returning the entries the user selected.
+///
+/// The picker takes over the terminal while it runs: applications with a
+/// TUI of their own should leave the alternate screen and raw mode first,
+/// and restore them once it returns.
+///
+/// # Examples
+/// ```no_run
+/// use television::{CliTvChannel, Picker};
+///
+/// let entries = Picker::new()
+///     .channel(|ctx| CliTvChannel::Env.to_channel(ctx))
+///     .multi(true)
+///     .run()?;
+/// for entry in entries {
+///     println!("{}", entry.stdout_repr());
+/// }
+/// # Ok::<(), color_eyre::Report>(())
+/// ```
+pub struct Picker {
+    channel: Option<ChannelFactory>,
+    config: Option<Config>,
+    multi: bool,
+    no_input: bool,
+}
+
+impl Default for Picker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Picker {
+    /// A picker over the files of the current directory, configured by the
+    /// user's config file (as `tv` is).
+    pub fn new() -> Self {
+        Self {
+            channel: None,
+            config: None,
+            multi: true,
+            no_input: false,
+        }
+    }
+
+    /// Pick among the entries of the channel created by the given function,
+    /// from the context of the picker (i.e. with the settings of its
+    /// configuration, such as the cable channels or the search scope).
+    #[must_use]
+    pub fn channel(
+        mut self,
+        channel: impl FnOnce(&Context) -> TelevisionChannel + 'static,
+    ) -> Self {
+        self.channel = Some(Box::new(channel));
+        self
+    }
+
+    /// Use the given configuration instead of loading the user's.
+    #[must_use]
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Whether several entries may be selected (with `ToggleSelection`)
+    /// instead of only the one under the cursor.
+    #[must_use]
+    pub fn multi(mut self, multi: bool) -> Self {
+        self.multi = multi;
+        self
+    }
+
+    /// Use the picker as a pure menu, without an input prompt.
+    #[must_use]
+    pub fn no_input(mut self, no_input: bool) -> Self {
+        self.no_input = no_input;
+        self
+    }
+
+    /// Run the picker until the user confirms their selection, returning
+    /// the selected entries, or exits, returning none.
+    ///
+    /// This starts an async runtime: from async code, use `run_async`
+    /// instead.
+    ///
+    /// # Errors
+    /// If the user's config file is invalid, or if the terminal can't be
+    /// drawn to.
+    pub fn run(self) -> Result<Vec<Entry>> {
+        tokio::runtime::Runtime::new()?.block_on(self.run_async())
+    }
+
+    /// Run the picker from async code (see `run`), on a multi-threaded
+    /// tokio runtime.
+    ///
+    /// # Errors
+    /// If the user's config file is invalid, or if the terminal can't be
+    /// drawn to.
+    pub async fn run_async(self) -> Result<Vec<Entry>> {
+        let mut config = match self.config {
+            Some(config) => config,
+            None => Config::new(None)?,
+        };
+        config.ui.multi_select &= self.multi;
+        let ctx = config.channel_context();
+        let channel = match self.channel {
+            Some(channel) => channel(&ctx),
+            None => CliTvChannel::Files.to_channel(&ctx),
+        };
+        let mut app = App::new(config, ctx, channel, TICK_RATE, FRAME_RATE)?;
+        if self.no_input {
+            app.set_no_input();
+        }
+        let selection = app.run(stdout().is_terminal()).await;
+        // the deleted files can't be restored anymore
+        undo::empty_trash();
+        selection
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let picker = Picker::new().multi(false).no_input(true);
+        assert!(picker.channel.is_none());
+        assert!(!picker.multi);
+        assert!(picker.no_input);
+    }
+}
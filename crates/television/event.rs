@@ -270,6 +270,8 @@ pub fn convert_raw_event_to_key(event: KeyEvent) -> Key {
         },
         Char(c) => match event.modifiers {
             KeyModifiers::NONE | KeyModifiers::SHIFT => Key::Char(c),
+            // terminals send the same byte for ctrl-_ and ctrl-7
+            KeyModifiers::CONTROL if c == '7' => Key::Ctrl('_'),
             KeyModifiers::CONTROL => Key::Ctrl(c),
             KeyModifiers::ALT => Key::Alt(c),
             _ => Key::Null,
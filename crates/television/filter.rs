@@ -0,0 +1,63 @@
+//! The non-interactive filter mode (see `--filter`): the entries of a
+//! channel matching a pattern are printed without starting the TUI, the way
+//! `fzf --filter` does.
+use std::time::Duration;
+
+use television_channels::channels::{OnAir, TelevisionChannel};
+use television_channels::entry::Entry;
+
+/// How often the channel is polled while it loads and matches its entries.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The entries of the given channel matching the pattern, best matches
+/// first, once the channel is done loading them.
+pub async fn filter(
+    channel: &mut TelevisionChannel,
+    pattern: &str,
+) -> Vec<Entry> {
+    channel.find(pattern);
+    loop {
+        // checked before polling, so that the last poll sees every entry
+        let loading = channel.loading();
+        // the matcher only makes progress when its results are polled
+        channel.results(0, 0);
+        if !loading && !channel.running() {
+            break;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    // unlike the displayed results, these are the entries as output
+    (0..channel.result_count())
+        .filter_map(|index| channel.get_result(index))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_filter() {
+        use television_channels::channels::cable::{
+            CableChannelPrototype, Channel as CableChannel,
+        };
+        use television_channels::context::Context;
+
+        let mut channel = TelevisionChannel::Cable(CableChannel::new(
+            &Context::default(),
+            CableChannelPrototype {
+                name: "planets".to_string(),
+                source_command:
+                    "sleep 0.1; printf 'mars\\nvenus\\nmercury\\n'".to_string(),
+                preview_command: None,
+                output: None,
+            },
+        ));
+        let entries = filter(&mut channel, "mr").await;
+        assert_eq!(
+            entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(),
+            ["mars", "mercury"]
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Shell integration (see `tv init`): widgets inserting the files selected in
+//! the files channel at the cursor, quoted for the shell (ctrl-t), replacing
+//! the command line with a command from the history channel (ctrl-r) and
+//! changing to a directory listed by the external channel (alt-c).
+//!
+//! The directories are listed with `find`, or with the command in
+//! `TV_CD_COMMAND` if set (e.g. `fd --type d`).
+use clap::ValueEnum;
+
+/// The shells widgets are available for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// The shell of the same name, if widgets are available for it.
+    pub fn from_completion_shell(shell: clap_complete::Shell) -> Option<Self> {
+        match shell {
+            clap_complete::Shell::Bash => Some(Shell::Bash),
+            clap_complete::Shell::Zsh => Some(Shell::Zsh),
+            clap_complete::Shell::Fish => Some(Shell::Fish),
+            _ => None,
+        }
+    }
+
+    /// The widgets and their keybindings.
+    pub fn script(self) -> &'static str {
+        match self {
+            Shell::Bash => BASH_INIT,
+            Shell::Zsh => ZSH_INIT,
+            Shell::Fish => FISH_INIT,
+        }
+    }
+
+    /// The line loading the widgets, to add to the shell's rc file.
+    pub fn rc_line(self) -> &'static str {
+        match self {
+            Shell::Bash => "eval \"$(tv init bash)\"",
+            Shell::Zsh => "eval \"$(tv init zsh)\"",
+            Shell::Fish => "tv init fish | source",
+        }
+    }
+}
+
+const BASH_INIT: &str = r#"__tv_cd_command() {
+  if [[ -n "$TV_CD_COMMAND" ]]; then
+    printf '%s' "$TV_CD_COMMAND"
+  else
+    printf '%s' "find . -mindepth 1 -name .git -prune -o -type d -print"
+  fi
+}
+
+__tv_files_widget() {
+  local selected
+  local -a entries
+  selected="$(tv files </dev/tty)" || return
+  [[ -n "$selected" ]] || return
+  mapfile -t entries <<<"$selected"
+  selected="$(printf '%q ' "${entries[@]}")"
+  READLINE_LINE="${READLINE_LINE:0:$READLINE_POINT}$selected${READLINE_LINE:$READLINE_POINT}"
+  READLINE_POINT=$((READLINE_POINT + ${#selected}))
+}
+
+__tv_history_widget() {
+  local selected
+  builtin history -a
+  selected="$(HISTFILE="${HISTFILE:-$HOME/.bash_history}" SHELL=bash tv history </dev/tty)" || return
+  if [[ -n "$selected" ]]; then
+    READLINE_LINE="$selected"
+    READLINE_POINT=${#selected}
+  fi
+}
+
+__tv_cd_widget() {
+  local selected
+  selected="$(tv external --source-command "$(__tv_cd_command)" --preview 'ls -A {}' </dev/tty)" || return
+  [[ -n "$selected" ]] && builtin cd -- "$selected"
+}
+
+bind -x '"\C-t": __tv_files_widget'
+bind -x '"\C-r": __tv_history_widget'
+bind -x '"\ec": __tv_cd_widget'
+"#;
+
+const ZSH_INIT: &str = r#"__tv_cd_command() {
+  print -rn -- "${TV_CD_COMMAND:-find . -mindepth 1 -name .git -prune -o -type d -print}"
+}
+
+__tv_files_widget() {
+  local -a selected
+  selected=(${(f)"$(tv files </dev/tty)"})
+  (( $#selected )) && LBUFFER+="${(j: :)${(@q)selected}} "
+  zle reset-prompt
+}
+
+__tv_history_widget() {
+  local selected
+  [[ -n "$HISTFILE" ]] && fc -AI
+  selected="$(HISTFILE="$HISTFILE" SHELL=zsh tv history </dev/tty)"
+  if [[ -n "$selected" ]]; then
+    BUFFER="$selected"
+    CURSOR=$#BUFFER
+  fi
+  zle reset-prompt
+}
+
+__tv_cd_widget() {
+  local selected precmd
+  selected="$(tv external --source-command "$(__tv_cd_command)" --preview 'ls -A {}' </dev/tty)"
+  if [[ -n "$selected" ]]; then
+    builtin cd -- "$selected"
+    for precmd in $precmd_functions; do
+      "$precmd"
+    done
+  fi
+  zle reset-prompt
+}
+
+zle -N __tv_files_widget
+zle -N __tv_history_widget
+zle -N __tv_cd_widget
+bindkey '^T' __tv_files_widget
+bindkey '^R' __tv_history_widget
+bindkey '\ec' __tv_cd_widget
+"#;
+
+const FISH_INIT: &str = r#"function __tv_cd_command
+    if set -q TV_CD_COMMAND
+        printf '%s' $TV_CD_COMMAND
+    else
+        printf '%s' "find . -mindepth 1 -name .git -prune -o -type d -print"
+    end
+end
+
+function __tv_files_widget
+    set -l selected (tv files </dev/tty)
+    test -n "$selected"; and commandline -i -- (string join ' ' -- (string escape -- $selected))' '
+    commandline -f repaint
+end
+
+function __tv_history_widget
+    history save
+    set -l selected (env SHELL=fish tv history </dev/tty | string collect)
+    test -n "$selected"; and commandline -r -- $selected
+    commandline -f repaint
+end
+
+function __tv_cd_widget
+    set -l selected (tv external --source-command (__tv_cd_command) --preview 'ls -A {}' </dev/tty)
+    test -n "$selected"; and cd -- $selected
+    commandline -f repaint
+end
+
+bind \ct __tv_files_widget
+bind \cr __tv_history_widget
+bind \ec __tv_cd_widget
+"#;
+
+/// Print the widgets of the given shell.
+pub fn run(shell: Shell) {
+    print!("{}", shell.script());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use television_utils::shell::find_executable;
+
+    #[test]
+    fn test_scripts_parse() {
+        for (shell, program, check) in [
+            (Shell::Bash, "bash", "-n"),
+            (Shell::Zsh, "zsh", "-n"),
+            (Shell::Fish, "fish", "--no-execute"),
+        ] {
+            if find_executable(program).is_none() {
+                continue;
+            }
+            let status = Command::new(program)
+                .args([check, "-c", shell.script()])
+                .status()
+                .unwrap();
+            assert!(status.success(), "{program} rejects its widgets");
+        }
+    }
+
+    #[test]
+    fn test_keybindings() {
+        for shell in Shell::value_variants() {
+            let script = shell.script();
+            for widget in
+                ["__tv_files_widget", "__tv_history_widget", "__tv_cd_widget"]
+            {
+                // defined, then bound to a key
+                assert!(script.matches(widget).count() >= 2, "{shell:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_files_widget_quotes_selection() {
+        for (shell, quoting) in [
+            (Shell::Bash, "printf '%q '"),
+            (Shell::Zsh, "${(@q)selected}"),
+            (Shell::Fish, "string escape --"),
+        ] {
+            assert!(shell.script().contains(quoting), "{shell:?}");
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use color_eyre::Result;
+
+use crate::action::Action;
+use crate::config::{
+    config_file_path, input_action, keybinding_conflicts, parse_key,
+    raw_channel_keybindings, raw_keybindings, Config, KeyBindings,
+    RawKeyBindings, CONFIG, INPUT_KEYS,
+};
+use crate::event::Key;
+use crate::television::Mode;
+
+const MODES: [Mode; 5] = [
+    Mode::Channel,
+    Mode::RemoteControl,
+    Mode::SendToChannel,
+    Mode::OpenWith,
+    Mode::FileTypes,
+];
+
+/// Print the resolved keymap of each mode (the default keybindings merged
+/// with the ones from the config file), along with any conflicts.
+pub fn run(profile: Option<&str>) -> Result<()> {
+    let config = Config::new(profile)?;
+    let defaults: Config = toml::from_str(CONFIG).expect("default config");
+    let content =
+        std::fs::read_to_string(config_file_path()).unwrap_or_default();
+    let user = raw_keybindings(&content).unwrap_or_default();
+    let channels = raw_channel_keybindings(&content).unwrap_or_default();
+    print!(
+        "{}",
+        describe_keymap(
+            &config.keybindings,
+            &defaults.keybindings,
+            &user,
+            &channels
+        )
+    );
+    Ok(())
+}
+
+/// Describe the resolved keymap, telling which bindings come from the config
+/// file and which default ones they override, followed by the keybindings
+/// overridden by channels.
+fn describe_keymap(
+    resolved: &KeyBindings,
+    defaults: &KeyBindings,
+    user: &RawKeyBindings,
+    channels: &HashMap<String, RawKeyBindings>,
+) -> String {
+    let mut out = String::new();
+    for mode in MODES {
+        let _ = writeln!(out, "{mode}");
+        let user_keys: Vec<Key> = user
+            .get(&mode)
+            .map(|bindings| {
+                bindings.keys().filter_map(|k| parse_key(k).ok()).collect()
+            })
+            .unwrap_or_default();
+        let mut bindings: Vec<(&Key, &Action)> = resolved
+            .get(&mode)
+            .map(|keymap| keymap.iter().collect())
+            .unwrap_or_default();
+        bindings.sort_by_key(|(key, _)| key.to_string().to_lowercase());
+        for (key, action) in bindings {
+            let default = defaults.get(&mode).and_then(|d| d.get(key));
+            let origin = match default {
+                _ if input_action(*key).is_some() => {
+                    "  (shadowed by the input field)".to_string()
+                }
+                _ if !user_keys.contains(key) => String::new(),
+                Some(default) if default != action => {
+                    format!("  (config, overrides {default:?})")
+                }
+                _ => "  (config)".to_string(),
+            };
+            let _ =
+                writeln!(out, "  {:<16} {action:?}{origin}", key.to_string());
+        }
+        let _ = writeln!(out);
+    }
+
+    let _ = writeln!(out, "Input field (all modes)");
+    for (key, action) in &INPUT_KEYS {
+        let _ = writeln!(out, "  {:<16} {action:?}", key.to_string());
+    }
+    let _ = writeln!(out, "  {:<16} AddInputChar", "<character>");
+
+    let mut conflicts: Vec<String> = keybinding_conflicts(user)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let mut channels: Vec<_> = channels.iter().collect();
+    channels.sort_by_key(|(name, _)| *name);
+    for (name, bindings) in channels {
+        for mode in MODES {
+            let mut bindings: Vec<(String, &Action)> = bindings
+                .get(&mode)
+                .map(|bindings| {
+                    bindings
+                        .iter()
+                        .filter_map(|(key, action)| {
+                            Some((parse_key(key).ok()?.to_string(), action))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            if bindings.is_empty() {
+                continue;
+            }
+            bindings.sort_by_key(|(key, _)| key.to_lowercase());
+            let _ = writeln!(out, "\n{mode} (channels.{name})");
+            for (key, action) in bindings {
+                let _ = writeln!(out, "  {key:<16} {action:?}");
+            }
+        }
+        conflicts.extend(
+            keybinding_conflicts(bindings)
+                .iter()
+                .map(|conflict| format!("channels.{name} {conflict}")),
+        );
+    }
+
+    if !conflicts.is_empty() {
+        let _ = writeln!(out, "\nConflicts");
+        for conflict in conflicts {
+            let _ = writeln!(out, "  {conflict}");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_keymap() {
+        let defaults = KeyBindings(HashMap::from([(
+            Mode::Channel,
+            HashMap::from([
+                (Key::Esc, Action::Quit),
+                (Key::Ctrl('n'), Action::SelectNextEntry),
+            ]),
+        )]));
+        let user = HashMap::from([(
+            Mode::Channel,
+            HashMap::from([
+                ("ctrl-n".to_string(), Action::SelectPrevEntry),
+                ("ctrl-j".to_string(), Action::SelectNextEntry),
+                ("left".to_string(), Action::Quit),
+            ]),
+        )]);
+        let resolved = KeyBindings(HashMap::from([(
+            Mode::Channel,
+            HashMap::from([
+                (Key::Esc, Action::Quit),
+                (Key::Ctrl('n'), Action::SelectPrevEntry),
+                (Key::Ctrl('j'), Action::SelectNextEntry),
+                (Key::Left, Action::Quit),
+            ]),
+        )]));
+        let channels = HashMap::from([(
+            "files".to_string(),
+            HashMap::from([(
+                Mode::Channel,
+                HashMap::from([
+                    ("ctrl-o".to_string(), Action::OpenEntry),
+                    ("home".to_string(), Action::Quit),
+                ]),
+            )]),
+        )]);
+        let description =
+            describe_keymap(&resolved, &defaults, &user, &channels);
+        assert!(description.starts_with(
+            "Channel
+  Ctrl-j           SelectNextEntry  (config)
+  Ctrl-n           SelectPrevEntry  (config, overrides SelectNextEntry)
+  Esc              Quit
+  Left             Quit  (shadowed by the input field)
+"
+        ));
+        assert!(description.contains(
+            "Channel (channels.files)
+  Ctrl-o           OpenEntry
+  Home             Quit
+"
+        ));
+        assert!(description.contains(
+            "Conflicts
+  [Channel] Left is reserved for the input field"
+        ));
+        assert!(description.contains(
+            "channels.files [Channel] Home is reserved for the input field"
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+//! The television picker: its channels, the matching and previewing of
+//! their entries and the terminal UI, as used by the `tv` binary.
+//!
+//! Other applications may also run the picker themselves (see `Picker`).
+mod about;
+mod action;
+mod app;
+mod bundle;
+mod cli;
+mod clipboard;
+mod config;
+mod doctor;
+mod embed;
+mod errors;
+mod event;
+mod filter;
+mod init;
+mod keybindings;
+mod listen;
+mod logging;
+mod nvim;
+mod output;
+mod picker;
+mod popup;
+mod quickfix;
+mod recent;
+mod render;
+mod run;
+mod scroll_memory;
+mod setup;
+mod state;
+mod television;
+mod tui;
+mod ui;
+mod undo;
+mod update;
+
+pub use config::Config;
+pub use embed::Picker;
+pub use television_channels::channels::{
+    CliTvChannel, TelevisionChannel, UnitChannel,
+};
+pub use television_channels::context::Context;
+pub use television_channels::entry::Entry;
+
+/// The entry point of the `tv` binary, not meant to be used by other
+/// applications.
+#[doc(hidden)]
+pub use run::run;
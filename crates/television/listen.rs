@@ -0,0 +1,369 @@
+//! The control socket (see `--listen`): the clients connected to it are
+//! told what happens in the picker and may drive it, one JSON object per
+//! line each way.
+//!
+//! The events sent to the clients:
+//! ```text
+//! {"event":"query_changed","query":"main"}
+//! {"event":"selection_changed","index":0,"entry":"src/main.rs"}
+//! {"event":"confirmed","entries":["src/main.rs"]}
+//! {"event":"aborted"}
+//! ```
+//! and the commands they may send:
+//! ```text
+//! {"command":"set_query","query":"lib"}
+//! {"command":"select","index":2}
+//! {"command":"reload"}
+//! {"command":"preview","entry":"src/main.rs","content":"\u001b[1mfn\u001b[0m main()"}
+//! ```
+//! Previews sent by the clients replace the ones of the channel for the
+//! given entries (as printed when selected) until the channel is reloaded.
+use std::time::Duration;
+
+use color_eyre::eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::action::Action;
+
+/// How many events a slow client may lag behind before missing some.
+const EVENT_CAPACITY: usize = 256;
+
+/// How long the last events may take to reach the clients when quitting.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Something that happened in the picker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The query of the channel changed.
+    QueryChanged { query: String },
+    /// Another entry is under the cursor, `index` being its position among
+    /// the results (`None` when there are no results).
+    SelectionChanged {
+        index: Option<usize>,
+        entry: Option<String>,
+    },
+    /// The picker was exited with these entries, as they are printed.
+    Confirmed { entries: Vec<String> },
+    /// The picker was exited without selecting anything.
+    Aborted,
+    /// A line sent by the client wasn't a valid command.
+    Error { message: String },
+}
+
+/// A command sent by a client.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Replace the query of the channel.
+    SetQuery { query: String },
+    /// Move the cursor to the result at the given position.
+    Select { index: usize },
+    /// Load the entries of the channel again.
+    Reload,
+    /// Preview the given entry with this text, which may be colored by ANSI
+    /// escape sequences.
+    Preview { entry: String, content: String },
+}
+
+impl From<Command> for Action {
+    fn from(command: Command) -> Self {
+        match command {
+            Command::SetQuery { query } => Action::SetInput(query),
+            Command::Select { index } => Action::SelectEntryAt(index),
+            Command::Reload => Action::ReloadChannel,
+            Command::Preview { entry, content } => {
+                Action::SetPreview { entry, content }
+            }
+        }
+    }
+}
+
+/// The control socket, accepting clients in the background.
+pub struct Listener {
+    events: broadcast::Sender<Event>,
+    /// The commands of the clients, as actions.
+    commands: mpsc::UnboundedReceiver<Action>,
+    accept_task: JoinHandle<()>,
+    /// Closed once every client is done sending the events.
+    clients_done: mpsc::Receiver<()>,
+    /// The path of the Unix socket, removed when quitting.
+    path: Option<String>,
+    /// The last query and selection told to the clients.
+    query: Option<String>,
+    selection: Option<(Option<usize>, Option<String>)>,
+}
+
+impl Listener {
+    /// Listen on the given address: a `host:port` TCP address, or the path
+    /// of a Unix socket.
+    pub async fn bind(address: &str) -> Result<Self> {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        let (action_tx, commands) = mpsc::unbounded_channel();
+        let (done_tx, clients_done) = mpsc::channel(1);
+        let clients = Clients {
+            events: events.clone(),
+            action_tx,
+            done_tx,
+        };
+        let is_tcp = address
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+        let (accept_task, path) = if is_tcp {
+            let listener = tokio::net::TcpListener::bind(address)
+                .await
+                .wrap_err_with(|| format!("can't listen on {address}"))?;
+            let task = tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    clients.spawn(stream);
+                }
+            });
+            (task, None)
+        } else {
+            (accept_unix(address, clients)?, Some(address.to_string()))
+        };
+        Ok(Self {
+            events,
+            commands,
+            accept_task,
+            clients_done,
+            path,
+            query: None,
+            selection: None,
+        })
+    }
+
+    /// The next command of the clients.
+    pub async fn command(&mut self) -> Option<Action> {
+        self.commands.recv().await
+    }
+
+    /// Tell the clients about the current query and selection, if they
+    /// changed since last time.
+    pub fn update(
+        &mut self,
+        query: &str,
+        index: Option<usize>,
+        entry: Option<String>,
+    ) {
+        if self.query.as_deref() != Some(query) {
+            self.query = Some(query.to_string());
+            self.send(Event::QueryChanged {
+                query: query.to_string(),
+            });
+        }
+        let selection = (index, entry);
+        if self.selection.as_ref() != Some(&selection) {
+            self.send(Event::SelectionChanged {
+                index,
+                entry: selection.1.clone(),
+            });
+            self.selection = Some(selection);
+        }
+    }
+
+    /// Tell the clients how the picker was exited, and wait (for a little
+    /// while) for them to get the last events.
+    pub async fn close(mut self, entries: Vec<String>) {
+        self.send(if entries.is_empty() {
+            Event::Aborted
+        } else {
+            Event::Confirmed { entries }
+        });
+        // the clients stop once they sent the events left
+        self.accept_task.abort();
+        let _ = (&mut self.accept_task).await;
+        drop(self.events);
+        let _ = tokio::time::timeout(FLUSH_TIMEOUT, self.clients_done.recv())
+            .await;
+        if let Some(path) = &self.path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    fn send(&self, event: Event) {
+        debug!("{event:?}");
+        // no clients connected
+        let _ = self.events.send(event);
+    }
+}
+
+#[cfg(unix)]
+fn accept_unix(address: &str, clients: Clients) -> Result<JoinHandle<()>> {
+    let listener = tokio::net::UnixListener::bind(address)
+        .wrap_err_with(|| format!("can't listen on {address}"))?;
+    Ok(tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            clients.spawn(stream);
+        }
+    }))
+}
+
+#[cfg(not(unix))]
+fn accept_unix(address: &str, _clients: Clients) -> Result<JoinHandle<()>> {
+    color_eyre::eyre::bail!(
+        "{address} isn't a `host:port` address (Unix sockets aren't \
+         supported on this platform)"
+    )
+}
+
+/// What the clients are served with.
+struct Clients {
+    events: broadcast::Sender<Event>,
+    action_tx: mpsc::UnboundedSender<Action>,
+    /// Held by each client until it is done.
+    done_tx: mpsc::Sender<()>,
+}
+
+impl Clients {
+    fn spawn<S>(&self, stream: S)
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let events = self.events.subscribe();
+        let action_tx = self.action_tx.clone();
+        let done_tx = self.done_tx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve(stream, events, action_tx).await {
+                debug!("Control socket client: {e}");
+            }
+            drop(done_tx);
+        });
+    }
+}
+
+/// Send the events to the client and its commands to the application,
+/// until either the client or the application is gone.
+async fn serve<S>(
+    stream: S,
+    mut events: broadcast::Receiver<Event>,
+    action_tx: mpsc::UnboundedSender<Action>,
+) -> std::io::Result<()>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+    loop {
+        let event = tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    return Ok(());
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<Command>(&line) {
+                    Ok(command) => {
+                        if action_tx.send(command.into()).is_err() {
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                    Err(e) => Event::Error {
+                        message: e.to_string(),
+                    },
+                }
+            }
+            event = events.recv() => match event {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+        };
+        let mut line = serde_json::to_string(&event)?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            serde_json::to_string(&Event::SelectionChanged {
+                index: Some(0),
+                entry: Some("src/main.rs".to_string()),
+            })
+            .unwrap(),
+            r#"{"event":"selection_changed","index":0,"entry":"src/main.rs"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Event::Aborted).unwrap(),
+            r#"{"event":"aborted"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Command>(
+                r#"{"command":"set_query","query":"lib"}"#
+            )
+            .unwrap(),
+            Command::SetQuery {
+                query: "lib".to_string()
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<Command>(r#"{"command":"reload"}"#)
+                .unwrap(),
+            Command::Reload
+        );
+        assert_eq!(
+            serde_json::from_str::<Command>(
+                r#"{"command":"preview","entry":"a","content":"b\nc"}"#
+            )
+            .unwrap(),
+            Command::Preview {
+                entry: "a".to_string(),
+                content: "b\nc".to_string()
+            }
+        );
+        assert!(serde_json::from_str::<Command>(r#"{"command":"x"}"#).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_listener() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tv.sock");
+        let mut listener =
+            Listener::bind(path.to_str().unwrap()).await.unwrap();
+        let client = tokio::net::UnixStream::connect(&path).await.unwrap();
+        let (reader, mut writer) = tokio::io::split(client);
+        let mut lines = BufReader::new(reader).lines();
+
+        writer
+            .write_all(b"{\"command\":\"select\",\"index\":2}\nnope\n")
+            .await
+            .unwrap();
+        assert_eq!(listener.command().await, Some(Action::SelectEntryAt(2)));
+        let error = lines.next_line().await.unwrap().unwrap();
+        assert!(error.starts_with(r#"{"event":"error""#));
+
+        listener.update("ma", Some(0), Some("mars".to_string()));
+        // nothing changed
+        listener.update("ma", Some(0), Some("mars".to_string()));
+        listener.close(vec!["mars".to_string()]).await;
+        let mut received = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            received.push(line);
+        }
+        assert_eq!(
+            received,
+            [
+                r#"{"event":"query_changed","query":"ma"}"#,
+                r#"{"event":"selection_changed","index":0,"entry":"mars"}"#,
+                r#"{"event":"confirmed","entries":["mars"]}"#,
+            ]
+        );
+        assert!(!path.exists());
+    }
+}
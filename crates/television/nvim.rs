@@ -0,0 +1,158 @@
+//! A minimal client for Neovim's msgpack-rpc API, used to open the selected
+//! entry in a running Neovim instance (see `--nvim-server`).
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+
+use color_eyre::eyre::{bail, Result};
+use television_channels::entry::{Entry, PreviewType};
+
+/// Open the given entry in the Neovim instance listening on `server` (a
+/// socket path or a `host:port` address), at its line if it has one.
+///
+/// Returns `false` (and does nothing) for entries that aren't files.
+pub fn edit(server: &str, entry: &Entry) -> Result<bool> {
+    if entry.preview_type != PreviewType::Files {
+        return Ok(false);
+    }
+    // Neovim's working directory may not be ours
+    let path = std::path::absolute(&entry.name)?;
+    let command = edit_command(&path.to_string_lossy(), entry.line_number);
+    let mut stream = connect(server)?;
+    stream.write_all(&request("nvim_command", &command))?;
+    stream.flush()?;
+    read_response(&mut stream)?;
+    Ok(true)
+}
+
+trait Stream: Read + Write {}
+impl<T: Read + Write> Stream for T {}
+
+fn connect(server: &str) -> io::Result<Box<dyn Stream>> {
+    #[cfg(unix)]
+    if std::path::Path::new(server).exists() {
+        return Ok(Box::new(std::os::unix::net::UnixStream::connect(server)?));
+    }
+    Ok(Box::new(TcpStream::connect(server)?))
+}
+
+/// The ex command opening the given file at the given line.
+fn edit_command(path: &str, line: Option<usize>) -> String {
+    let mut command = String::from("edit ");
+    if let Some(line) = line {
+        command.push_str(&format!("+{line} "));
+    }
+    // same as vim's `fnameescape()`
+    for c in path.chars() {
+        if " \t\n*?[{`$\\%#'\"|!<".contains(c) {
+            command.push('\\');
+        }
+        command.push(c);
+    }
+    command
+}
+
+/// Encode a msgpack-rpc request (`[0, msgid, method, [argument]]`).
+fn request(method: &str, argument: &str) -> Vec<u8> {
+    // a 4 elements array, a request type of 0 and a msgid of 1
+    let mut message = vec![0x94, 0x00, 0x01];
+    push_str(&mut message, method);
+    // a single element array
+    message.push(0x91);
+    push_str(&mut message, argument);
+    message
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn push_str(message: &mut Vec<u8>, s: &str) {
+    let len = s.len();
+    if len < 32 {
+        message.push(0xa0 | len as u8);
+    } else if len <= 0xff {
+        message.extend([0xd9, len as u8]);
+    } else if len <= 0xffff {
+        message.push(0xda);
+        message.extend((len as u16).to_be_bytes());
+    } else {
+        message.push(0xdb);
+        message.extend((len as u32).to_be_bytes());
+    }
+    message.extend(s.as_bytes());
+}
+
+/// Read the response to the request and bail if it's an error.
+///
+/// Responses are `[1, msgid, error, result]` arrays where `error` is nil on
+/// success; `nvim_command`'s result is nil too, so the response is a
+/// handful of bytes, and the error message is the first string it contains.
+fn read_response(stream: &mut impl Read) -> Result<()> {
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header)?;
+    if header[..3] != [0x94, 0x01, 0x01] {
+        bail!("unexpected response from Neovim");
+    }
+    // nil error
+    if header[3] == 0xc0 {
+        return Ok(());
+    }
+    let mut rest = Vec::new();
+    let mut buffer = [0u8; 1024];
+    // the error is followed by the nil result
+    while !rest.ends_with(&[0xc0]) {
+        let n = stream.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        rest.extend(&buffer[..n]);
+    }
+    let message: String = String::from_utf8_lossy(&rest)
+        .chars()
+        .skip_while(|c| !c.is_ascii_alphabetic())
+        .take_while(|c| !c.is_control() && *c != char::REPLACEMENT_CHARACTER)
+        .collect();
+    bail!("Neovim error: {message}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_command() {
+        assert_eq!(
+            edit_command("src/main.rs", Some(12)),
+            "edit +12 src/main.rs"
+        );
+        assert_eq!(edit_command("my file#1", None), "edit my\\ file\\#1");
+    }
+
+    #[test]
+    fn test_request() {
+        assert_eq!(
+            request("nvim_command", "edit a"),
+            [
+                &[0x94, 0x00, 0x01, 0xac][..],
+                b"nvim_command",
+                &[0x91, 0xa6],
+                b"edit a"
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_read_response() {
+        assert!(
+            read_response(&mut &[0x94, 0x01, 0x01, 0xc0, 0xc0][..]).is_ok()
+        );
+        let error = [
+            &[0x94, 0x01, 0x01, 0x92, 0x00, 0xb0][..],
+            b"E37: No write...",
+            &[0xc0],
+        ]
+        .concat();
+        assert_eq!(
+            read_response(&mut &error[..]).unwrap_err().to_string(),
+            "Neovim error: E37: No write..."
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Output adapters formatting the selected entry for editors (see
+//! `--output-format`), and the file the confirmed entries are written to
+//! when the picker stays open (see `--output-fifo`).
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use clap::ValueEnum;
+use television_channels::bus::{Bus, Message};
+use television_channels::entry::{Entry, PreviewType};
+use tracing::warn;
+
+use crate::quickfix::quickfix_line;
+
+/// How the selected entry is printed, to integrate with editors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// The entry as is (`file:line` for entries with a line number)
+    #[default]
+    Default,
+    /// A Kakoune command opening the file (`edit 'file' line col`)
+    Kakoune,
+    /// A Helix command opening the file (`:open file:line:col`)
+    Helix,
+    /// A quickfix line (`file:line:col:text`)
+    Quickfix,
+}
+
+impl OutputFormat {
+    /// Format the selected entry.
+    ///
+    /// Entries that aren't files are printed as is whatever the format.
+    pub fn format(self, entry: &Entry) -> String {
+        if entry.preview_type != PreviewType::Files {
+            return entry.stdout_repr();
+        }
+        let line = entry.line_number.unwrap_or(1);
+        let column = 1;
+        match self {
+            OutputFormat::Default => entry.stdout_repr(),
+            OutputFormat::Kakoune => format!(
+                "edit '{}' {line} {column}",
+                entry.name.replace('\'', "''")
+            ),
+            OutputFormat::Helix => format!(
+                ":open {}:{line}:{column}",
+                entry.name.replace(' ', "\\ ")
+            ),
+            OutputFormat::Quickfix => {
+                quickfix_line(entry).unwrap_or_else(|| entry.stdout_repr())
+            }
+        }
+    }
+}
+
+/// The key of the notifications about the output file.
+const OUTPUT_TOAST: &str = "output";
+
+/// Writes the confirmed entries to a file or named pipe, in the background
+/// since opening a named pipe blocks until someone reads it.
+pub struct OutputFifo {
+    format: OutputFormat,
+    separator: char,
+    tx: mpsc::Sender<String>,
+}
+
+impl OutputFifo {
+    /// Create the writer, reporting whether each write succeeded through the
+    /// given bus.
+    pub fn new(
+        path: PathBuf,
+        format: OutputFormat,
+        separator: char,
+        bus: Bus,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel::<String>();
+        std::thread::spawn(move || {
+            // kept open for the readers waiting for more entries
+            let mut file = None;
+            for text in rx {
+                let (failed, message) = match write(&mut file, &path, &text) {
+                    Ok(()) => (false, format!("Sent to {}", path.display())),
+                    Err(e) => {
+                        warn!("Can't write to {}: {e}", path.display());
+                        (
+                            true,
+                            format!("Can't write to {}: {e}", path.display()),
+                        )
+                    }
+                };
+                bus.send(Message::Finished {
+                    key: OUTPUT_TOAST.to_string(),
+                    text: message,
+                    failed,
+                });
+            }
+        });
+        Self {
+            format,
+            separator,
+            tx,
+        }
+    }
+
+    /// Write the given entries, each followed by the separator.
+    pub fn send(&self, entries: &[Entry]) {
+        if entries.is_empty() {
+            return;
+        }
+        let text = entries
+            .iter()
+            .map(|entry| {
+                format!("{}{}", self.format.format(entry), self.separator)
+            })
+            .collect();
+        // the writing thread only stops with us
+        let _ = self.tx.send(text);
+    }
+}
+
+/// Write the text to the open file, or to the file opened again if it can't
+/// be written to anymore (e.g. the named pipe's reader went away).
+fn write(
+    file: &mut Option<File>,
+    path: &Path,
+    text: &str,
+) -> std::io::Result<()> {
+    if let Some(open) = file {
+        if open.write_all(text.as_bytes()).is_ok() && open.flush().is_ok() {
+            return Ok(());
+        }
+    }
+    *file = None;
+    let mut open = OpenOptions::new().append(true).create(true).open(path)?;
+    open.write_all(text.as_bytes())?;
+    open.flush()?;
+    *file = Some(open);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let entry = Entry::new("it's here.rs".to_string(), PreviewType::Files)
+            .with_line_number(12);
+        assert_eq!(OutputFormat::Default.format(&entry), "it's here.rs:12");
+        assert_eq!(
+            OutputFormat::Kakoune.format(&entry),
+            "edit 'it''s here.rs' 12 1"
+        );
+        assert_eq!(
+            OutputFormat::Helix.format(&entry),
+            ":open it's\\ here.rs:12:1"
+        );
+        assert_eq!(
+            OutputFormat::Quickfix.format(&entry),
+            "it's here.rs:12:1:"
+        );
+        let env = Entry::new("HOME".to_string(), PreviewType::EnvVar);
+        assert_eq!(OutputFormat::Kakoune.format(&env), "HOME");
+    }
+
+    #[test]
+    fn test_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("output");
+        let mut file = None;
+        write(&mut file, &path, "a\n").unwrap();
+        write(&mut file, &path, "b\n").unwrap();
+        assert!(file.is_some());
+        // appended to when opened again
+        write(&mut None, &path, "c\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\nb\nc\n");
+    }
+}
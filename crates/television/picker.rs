@@ -59,23 +59,50 @@ impl Picker {
         self.relative_state.select(index);
     }
 
-    pub(crate) fn select_next(&mut self, total_items: usize, height: usize) {
+    /// Select the entry at the given index, scrolling the view just enough
+    /// for it to be visible.
+    pub(crate) fn select_index(&mut self, index: usize, height: usize) {
+        let visible_items = height.saturating_sub(2).max(1);
+        if index < self.view_offset {
+            self.view_offset = index;
+        } else if index >= self.view_offset + visible_items {
+            self.view_offset = index + 1 - visible_items;
+        }
+        self.select(Some(index));
+        self.relative_select(Some(index - self.view_offset));
+    }
+
+    /// Select the next entry, wrapping around to the other end of the list
+    /// past the last entry if `wrap` is set.
+    pub(crate) fn select_next(
+        &mut self,
+        total_items: usize,
+        height: usize,
+        wrap: bool,
+    ) {
         if self._inverted {
-            self._select_prev(total_items, height);
+            self._select_prev(total_items, height, wrap);
         } else {
-            self._select_next(total_items, height);
+            self._select_next(total_items, height, wrap);
         }
     }
 
-    pub(crate) fn select_prev(&mut self, total_items: usize, height: usize) {
+    /// Select the previous entry, wrapping around to the other end of the
+    /// list past the first entry if `wrap` is set.
+    pub(crate) fn select_prev(
+        &mut self,
+        total_items: usize,
+        height: usize,
+        wrap: bool,
+    ) {
         if self._inverted {
-            self._select_next(total_items, height);
+            self._select_next(total_items, height, wrap);
         } else {
-            self._select_prev(total_items, height);
+            self._select_prev(total_items, height, wrap);
         }
     }
 
-    fn _select_next(&mut self, total_items: usize, height: usize) {
+    fn _select_next(&mut self, total_items: usize, height: usize, wrap: bool) {
         let selected = self.selected().unwrap_or(0);
         let relative_selected = self.relative_selected().unwrap_or(0);
         if selected > 0 {
@@ -84,7 +111,7 @@ impl Picker {
             if relative_selected == 0 {
                 self.view_offset = self.view_offset.saturating_sub(1);
             }
-        } else {
+        } else if wrap {
             self.view_offset =
                 total_items.saturating_sub(height.saturating_sub(2));
             self.select(Some(total_items.saturating_sub(1)));
@@ -92,7 +119,10 @@ impl Picker {
         }
     }
 
-    fn _select_prev(&mut self, total_items: usize, height: usize) {
+    fn _select_prev(&mut self, total_items: usize, height: usize, wrap: bool) {
+        if !wrap && self.selected().unwrap_or(0) + 1 >= total_items {
+            return;
+        }
         let new_index = (self.selected().unwrap_or(0) + 1) % total_items;
         self.select(Some(new_index));
         if new_index == 0 {
@@ -113,3 +143,49 @@ impl Picker {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_index() {
+        let mut picker = Picker::default();
+        // 5 visible items
+        picker.select_index(3, 7);
+        assert_eq!(picker.selected(), Some(3));
+        assert_eq!(picker.view_offset, 0);
+        assert_eq!(picker.relative_selected(), Some(3));
+
+        picker.select_index(8, 7);
+        assert_eq!(picker.view_offset, 4);
+        assert_eq!(picker.relative_selected(), Some(4));
+
+        picker.select_index(2, 7);
+        assert_eq!(picker.view_offset, 2);
+        assert_eq!(picker.relative_selected(), Some(0));
+    }
+
+    #[test]
+    fn test_select_without_wrapping() {
+        let mut picker = Picker::default();
+        picker.reset_selection();
+        picker.select_next(3, 7, false);
+        assert_eq!(picker.selected(), Some(0));
+        picker.select_prev(3, 7, false);
+        picker.select_prev(3, 7, false);
+        assert_eq!(picker.selected(), Some(2));
+        picker.select_prev(3, 7, false);
+        assert_eq!(picker.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_select_with_wrapping() {
+        let mut picker = Picker::default();
+        picker.reset_selection();
+        picker.select_next(3, 7, true);
+        assert_eq!(picker.selected(), Some(2));
+        picker.select_prev(3, 7, true);
+        assert_eq!(picker.selected(), Some(0));
+    }
+}
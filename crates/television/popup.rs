@@ -0,0 +1,415 @@
+//! Relaunching `tv` in a tmux popup or a Zellij floating pane (see `--tmux`
+//! and `--zellij`).
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{stdin, stdout, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::{bail, Result};
+use television_utils::stdin::is_readable_stdin;
+use television_utils::strings::shell_quote;
+
+/// How often to check whether `tv` exited in the Zellij floating pane.
+const ZELLIJ_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long to wait for `tv` to start in the Zellij floating pane.
+const ZELLIJ_START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where the popup is displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PopupPosition {
+    Center,
+    Bottom,
+}
+
+/// The position and dimensions of the popup, e.g. `bottom,100%x40%`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Popup {
+    pub position: PopupPosition,
+    /// A number of columns or a percentage of the window's width
+    pub width: String,
+    /// A number of lines or a percentage of the window's height
+    pub height: String,
+}
+
+impl FromStr for Popup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, size) = match s.split_once(',') {
+            Some((position, size)) => (position, Some(size)),
+            None if s.contains('x') => ("center", Some(s)),
+            None => (s, None),
+        };
+        let position = match position {
+            "center" => PopupPosition::Center,
+            "bottom" => PopupPosition::Bottom,
+            _ => {
+                return Err(format!(
+                    "unknown position `{position}` (expected `center` or \
+                     `bottom`)"
+                ))
+            }
+        };
+        let (width, height) = match size {
+            Some(size) => {
+                let (width, height) =
+                    size.split_once('x').ok_or_else(|| {
+                        format!("invalid size `{size}` (expected `WxH`)")
+                    })?;
+                for dimension in [width, height] {
+                    let digits =
+                        dimension.strip_suffix('%').unwrap_or(dimension);
+                    if digits.is_empty()
+                        || !digits.chars().all(|c| c.is_ascii_digit())
+                    {
+                        return Err(format!(
+                            "invalid dimension `{dimension}` (expected a \
+                             number, optionally followed by `%`)"
+                        ));
+                    }
+                }
+                (width, height)
+            }
+            None => match position {
+                PopupPosition::Center => ("80%", "60%"),
+                PopupPosition::Bottom => ("100%", "40%"),
+            },
+        };
+        Ok(Popup {
+            position,
+            width: width.to_string(),
+            height: height.to_string(),
+        })
+    }
+}
+
+/// The terminal multiplexers `tv` can open a popup in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multiplexer {
+    Tmux,
+    Zellij,
+}
+
+impl Multiplexer {
+    /// Whether `tv` runs inside this multiplexer.
+    pub fn is_running(self) -> bool {
+        let var = match self {
+            Multiplexer::Tmux => "TMUX",
+            Multiplexer::Zellij => "ZELLIJ",
+        };
+        std::env::var_os(var).is_some_and(|value| !value.is_empty())
+    }
+}
+
+/// Run `tv` with the same arguments (but `--tmux` and `--zellij`) in a popup
+/// of the given multiplexer, and print its selection.
+///
+/// The standard input, if any, is forwarded to the popup through a temporary
+/// file, and so is the selection back from it.
+pub fn run(multiplexer: Multiplexer, popup: &Popup) -> Result<()> {
+    let input = is_readable_stdin()
+        .then(|| -> Result<PathBuf> {
+            let path = temp_file("in");
+            std::io::copy(&mut stdin(), &mut File::create(&path)?)?;
+            Ok(path)
+        })
+        .transpose()?;
+    let output = temp_file("out");
+    File::create(&output)?;
+
+    let exe = std::env::current_exe()?;
+    let mut command = shell_quote(&exe.to_string_lossy());
+    for arg in forwarded_args(std::env::args_os().skip(1)) {
+        command.push(' ');
+        command.push_str(&shell_quote(&arg.to_string_lossy()));
+    }
+    if let Some(input) = &input {
+        command.push_str(&format!(" < {}", quote_path(input)));
+    }
+    command.push_str(&format!(" > {}", quote_path(&output)));
+
+    let result = match multiplexer {
+        Multiplexer::Tmux => run_in_tmux(popup, &command),
+        Multiplexer::Zellij => run_in_zellij(popup, &command),
+    };
+    let selection = std::fs::read(&output);
+    if let Some(input) = &input {
+        let _ = std::fs::remove_file(input);
+    }
+    let _ = std::fs::remove_file(&output);
+    result?;
+    stdout().write_all(&selection?)?;
+    Ok(())
+}
+
+fn run_in_tmux(popup: &Popup, command: &str) -> Result<()> {
+    let status = Command::new("tmux")
+        .args(tmux_args(popup))
+        .arg("-d")
+        .arg(std::env::current_dir()?)
+        .arg(command)
+        .status()?;
+    if !status.success() {
+        bail!("failed to open a tmux popup (tmux 3.2 or newer is required)");
+    }
+    Ok(())
+}
+
+/// `zellij run` returns as soon as the floating pane is opened, so the
+/// command signals its exit by creating a file, after writing the PID of its
+/// shell to another one.
+fn run_in_zellij(popup: &Popup, command: &str) -> Result<()> {
+    let done = temp_file("done");
+    let pid = temp_file("pid");
+    let _ = std::fs::remove_file(&done);
+    let _ = std::fs::remove_file(&pid);
+    // also signal it if the pane is closed
+    let command = format!(
+        "echo $$ > {}; trap 'touch {}' EXIT HUP INT TERM; {command}",
+        quote_path(&pid),
+        quote_path(&done)
+    );
+    let status = Command::new("zellij")
+        .args(zellij_args(popup))
+        .arg("--cwd")
+        .arg(std::env::current_dir()?)
+        .args(["--", "sh", "-c"])
+        .arg(command)
+        .status()?;
+    if !status.success() {
+        bail!("failed to open a Zellij floating pane");
+    }
+    let result = wait_for_zellij_pane(&done, &pid, ZELLIJ_START_TIMEOUT);
+    let _ = std::fs::remove_file(&done);
+    let _ = std::fs::remove_file(&pid);
+    result
+}
+
+/// Wait for the command of the Zellij floating pane to signal its exit.
+///
+/// This gives up if the command doesn't start within `timeout` (e.g. if
+/// the pane was never shown), or if its shell is gone without signalling
+/// its exit (e.g. if it was killed).
+fn wait_for_zellij_pane(
+    done: &Path,
+    pid: &Path,
+    timeout: Duration,
+) -> Result<()> {
+    let start = Instant::now();
+    let mut shell = None;
+    while !done.exists() {
+        match shell {
+            None => {
+                shell = std::fs::read_to_string(pid)
+                    .ok()
+                    .and_then(|pid| pid.trim().parse::<i32>().ok());
+                if shell.is_none() && start.elapsed() > timeout {
+                    bail!(
+                        "tv did not start in the Zellij floating pane \
+                         within {}s",
+                        timeout.as_secs()
+                    );
+                }
+            }
+            // it may have signalled its exit right before dying
+            Some(shell) if !is_alive(shell) && !done.exists() => {
+                bail!("tv exited unexpectedly in the Zellij floating pane");
+            }
+            Some(_) => {}
+        }
+        std::thread::sleep(ZELLIJ_POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+/// Whether the process with the given PID is still running.
+#[cfg(unix)]
+fn is_alive(pid: i32) -> bool {
+    // SAFETY: the null signal is never sent, only checked for
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn is_alive(_pid: i32) -> bool {
+    true
+}
+
+/// The `tmux display-popup` arguments for the given popup, but the working
+/// directory and the command.
+fn tmux_args(popup: &Popup) -> Vec<String> {
+    let y = match popup.position {
+        PopupPosition::Center => "C",
+        // right above the status line
+        PopupPosition::Bottom => "S",
+    };
+    [
+        "display-popup",
+        "-E",
+        "-x",
+        "C",
+        "-y",
+        y,
+        "-w",
+        &popup.width,
+        "-h",
+        &popup.height,
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+/// The `zellij run` arguments for the given popup, but the working directory
+/// and the command.
+///
+/// Zellij has no notion of centering, so the popup is only positioned when
+/// its dimensions are percentages.
+fn zellij_args(popup: &Popup) -> Vec<String> {
+    let mut args = [
+        "run",
+        "--floating",
+        "--close-on-exit",
+        "--name",
+        "tv",
+        "--width",
+        &popup.width,
+        "--height",
+        &popup.height,
+    ]
+    .map(String::from)
+    .to_vec();
+    let percent = |dimension: &str| {
+        dimension
+            .strip_suffix('%')
+            .and_then(|p| p.parse::<u8>().ok())
+    };
+    if let Some(width) = percent(&popup.width) {
+        args.extend([
+            "-x".to_string(),
+            format!("{}%", 100u8.saturating_sub(width) / 2),
+        ]);
+    }
+    if let Some(height) = percent(&popup.height) {
+        let margin = 100u8.saturating_sub(height);
+        let y = match popup.position {
+            PopupPosition::Center => margin / 2,
+            PopupPosition::Bottom => margin,
+        };
+        args.extend(["-y".to_string(), format!("{y}%")]);
+    }
+    args
+}
+
+/// The command line arguments, without `--tmux` and `--zellij`.
+fn forwarded_args(
+    args: impl Iterator<Item = OsString>,
+) -> impl Iterator<Item = OsString> {
+    args.filter(|arg| {
+        let arg = arg.to_string_lossy();
+        !["--tmux", "--zellij"].iter().any(|flag| {
+            arg == *flag
+                || arg.strip_prefix(flag).is_some_and(|v| v.starts_with('='))
+        })
+    })
+}
+
+fn quote_path(path: &Path) -> String {
+    shell_quote(&path.to_string_lossy())
+}
+
+fn temp_file(suffix: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("tv-popup-{}-{suffix}", std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_popup() {
+        assert_eq!(
+            "bottom".parse(),
+            Ok(Popup {
+                position: PopupPosition::Bottom,
+                width: "100%".to_string(),
+                height: "40%".to_string(),
+            })
+        );
+        assert_eq!(
+            "center,120x30".parse(),
+            Ok(Popup {
+                position: PopupPosition::Center,
+                width: "120".to_string(),
+                height: "30".to_string(),
+            })
+        );
+        assert_eq!(
+            "50%x50%".parse::<Popup>().map(|p| p.position),
+            Ok(PopupPosition::Center)
+        );
+        assert!("top".parse::<Popup>().is_err());
+        assert!("center,80%".parse::<Popup>().is_err());
+        assert!("center,ax10".parse::<Popup>().is_err());
+    }
+
+    #[test]
+    fn test_zellij_args() {
+        let popup: Popup = "bottom,80%x30%".parse().unwrap();
+        assert_eq!(
+            zellij_args(&popup)[5..],
+            ["--width", "80%", "--height", "30%", "-x", "10%", "-y", "70%"]
+        );
+        let popup: Popup = "center,100x30".parse().unwrap();
+        assert_eq!(
+            zellij_args(&popup)[5..],
+            ["--width", "100", "--height", "30"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wait_for_zellij_pane() {
+        let done = temp_file("test-done");
+        let pid = temp_file("test-pid");
+        let timeout = Duration::from_millis(100);
+
+        // the command never started
+        assert!(wait_for_zellij_pane(&done, &pid, timeout).is_err());
+
+        // its shell died without signalling its exit
+        let mut shell = Command::new("true").spawn().unwrap();
+        shell.wait().unwrap();
+        std::fs::write(&pid, shell.id().to_string()).unwrap();
+        assert!(wait_for_zellij_pane(&done, &pid, timeout).is_err());
+
+        // it signalled its exit
+        File::create(&done).unwrap();
+        assert!(wait_for_zellij_pane(&done, &pid, timeout).is_ok());
+
+        std::fs::remove_file(done).unwrap();
+        std::fs::remove_file(pid).unwrap();
+    }
+
+    #[test]
+    fn test_forwarded_args() {
+        let args = [
+            "text",
+            "--tmux=bottom",
+            "--no-input",
+            "--tmux",
+            "--zellij=center",
+            "--tmuxfoo",
+        ]
+        .map(OsString::from)
+        .into_iter();
+        assert_eq!(
+            forwarded_args(args).collect::<Vec<_>>(),
+            ["text", "--no-input", "--tmuxfoo"]
+        );
+    }
+}
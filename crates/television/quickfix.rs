@@ -0,0 +1,58 @@
+use television_channels::entry::{Entry, PreviewType};
+
+/// Format an entry as a quickfix line (`file:line:col:text`), as understood
+/// by vim's `-q` option (or `:cfile`) and most editors' location lists.
+///
+/// The column is the one of the first matched character of the entry's value,
+/// or 1. Entries that aren't files are skipped.
+pub fn quickfix_line(entry: &Entry) -> Option<String> {
+    if entry.preview_type != PreviewType::Files {
+        return None;
+    }
+    let text = entry.value.as_deref().unwrap_or_default();
+    let column = entry
+        .value_match_ranges
+        .as_ref()
+        .and_then(|ranges| ranges.first())
+        // match ranges are char indices, quickfix columns are byte ones
+        .and_then(|(start, _)| text.char_indices().nth(*start as usize))
+        .map_or(1, |(byte, _)| byte + 1);
+    Some(format!(
+        "{}:{}:{}:{}",
+        entry.display_name(),
+        entry.line_number.unwrap_or(1),
+        column,
+        text
+    ))
+}
+
+/// Format the given entries as a quickfix list, one line per entry.
+pub fn quickfix_list(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .filter_map(quickfix_line)
+        .map(|line| line + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quickfix_list() {
+        let entries = vec![
+            Entry::new("src/main.rs12".to_string(), PreviewType::Files)
+                .with_display_name("src/main.rs".to_string())
+                .with_value("let é = foo();".to_string())
+                .with_value_match_ranges(vec![(8, 9), (9, 10)])
+                .with_line_number(12),
+            Entry::new("README.md".to_string(), PreviewType::Files),
+            Entry::new("HOME".to_string(), PreviewType::EnvVar),
+        ];
+        assert_eq!(
+            quickfix_list(&entries),
+            "src/main.rs:12:10:let é = foo();\nREADME.md:1:1:\n"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use color_eyre::Result;
+use television_channels::filters::format_age;
+use television_channels::recent::{RecentEntries, RecentLimits};
+
+use crate::cli::RecentCommand;
+
+/// Run a `tv recent` subcommand on the recent entries stores, bounded by
+/// the given limits.
+pub fn run(command: RecentCommand, limits: RecentLimits) -> Result<()> {
+    match command {
+        RecentCommand::List { channel: None } => {
+            print!("{}", describe_channels(&RecentEntries::all(limits)));
+        }
+        RecentCommand::List {
+            channel: Some(channel),
+        } => {
+            print!("{}", describe_entries(&stores(Some(&channel), limits)[0]));
+        }
+        RecentCommand::Search { pattern } => {
+            print!("{}", search(&RecentEntries::all(limits), &pattern));
+        }
+        RecentCommand::Prune {
+            channel,
+            older_than,
+            keep,
+        } => {
+            for mut recent in stores(channel.as_deref(), limits) {
+                let removed = recent.prune(keep, older_than)?;
+                println!("{}: removed {removed} entries", recent.channel());
+            }
+        }
+        RecentCommand::Purge { channel } => {
+            for mut recent in stores(channel.as_deref(), limits) {
+                recent.purge()?;
+                println!("{}: forgot all entries", recent.channel());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The recent entries of the given channel, or of all of them.
+fn stores(channel: Option<&str>, limits: RecentLimits) -> Vec<RecentEntries> {
+    match channel {
+        Some(channel) => {
+            vec![RecentEntries::for_channel(&channel.to_lowercase(), limits)]
+        }
+        None => RecentEntries::all(limits),
+    }
+}
+
+fn describe_channels(stores: &[RecentEntries]) -> String {
+    let mut out = String::new();
+    for recent in stores.iter().filter(|r| !r.is_empty()) {
+        let _ =
+            writeln!(out, "{}\t{}", recent.channel(), recent.entries().len());
+    }
+    out
+}
+
+fn describe_entries(recent: &RecentEntries) -> String {
+    let mut out = String::new();
+    for entry in recent.entries() {
+        let _ = writeln!(out, "{}\t{}", rounded_age(entry.age()), entry.key);
+    }
+    out
+}
+
+fn search(stores: &[RecentEntries], pattern: &str) -> String {
+    let pattern = pattern.to_lowercase();
+    let mut out = String::new();
+    for recent in stores {
+        for entry in recent.entries() {
+            if entry.key.to_lowercase().contains(&pattern) {
+                let _ = writeln!(out, "{}\t{}", recent.channel(), entry.key);
+            }
+        }
+    }
+    out
+}
+
+/// Format an age in its largest whole unit (e.g. `1h` for 90 minutes).
+fn rounded_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    let unit = [7 * 24 * 60 * 60, 24 * 60 * 60, 60 * 60, 60]
+        .into_iter()
+        .find(|unit| seconds >= *unit)
+        .unwrap_or(1);
+    format_age(Duration::from_secs(seconds / unit * unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounded_age() {
+        assert_eq!(rounded_age(Duration::from_secs(0)), "0s");
+        assert_eq!(rounded_age(Duration::from_secs(42)), "42s");
+        assert_eq!(rounded_age(Duration::from_secs(90 * 60)), "1h");
+        assert_eq!(rounded_age(Duration::from_secs(3 * 24 * 60 * 60)), "3d");
+        assert_eq!(rounded_age(Duration::from_secs(15 * 24 * 60 * 60)), "2w");
+    }
+}
@@ -1,14 +1,18 @@
 use color_eyre::Result;
 use ratatui::layout::Rect;
 use std::{
-    io::{stderr, stdout, LineWriter},
+    io::{stderr, stdout, LineWriter, Write},
+    process::Stdio,
     sync::Arc,
 };
+use television_channels::startup::Startup;
+use television_utils::shell::shell_command;
 use tracing::{debug, warn};
 
 use tokio::{
+    process::Command,
     select,
-    sync::{mpsc, Mutex},
+    sync::{mpsc, oneshot, Mutex},
 };
 
 use crate::television::Television;
@@ -21,6 +25,9 @@ pub enum RenderingTask {
     Resize(u16, u16),
     Resume,
     Suspend,
+    /// Leave the tui to run the given command, notifying the sender once it
+    /// has exited and the tui has been restored.
+    RunCommand(String, oneshot::Sender<()>),
     Quit,
 }
 
@@ -44,6 +51,7 @@ pub async fn render(
     action_tx: mpsc::UnboundedSender<Action>,
     config: Config,
     television: Arc<Mutex<Television>>,
+    startup: Startup,
     frame_rate: f64,
     is_output_tty: bool,
 ) -> Result<()> {
@@ -80,6 +88,7 @@ pub async fn render(
                     match task {
                         RenderingTask::ClearScreen => {
                             tui.terminal.clear()?;
+                            television.lock().await.reset_graphics();
                         }
                         RenderingTask::Render => {
                             let mut television = television.lock().await;
@@ -96,14 +105,23 @@ pub async fn render(
                                                 .send(Action::Error(format!("Failed to draw: {err:?}")));
                                         }
                                     })?;
-
+                                    // images are written over the frame
+                                    let graphics = television.take_graphics();
+                                    if !graphics.is_empty() {
+                                        let backend = tui.terminal.backend_mut();
+                                        backend.write_all(graphics.as_bytes())?;
+                                        backend.flush()?;
+                                    }
                                 } else {
                                     warn!("Terminal area too large");
                                 }
                             }
+                            // the first frame is out, the channels may start loading entries
+                            startup.release();
                         }
                         RenderingTask::Resize(w, h) => {
                             tui.resize(Rect::new(0, 0, w, h))?;
+                            television.lock().await.reset_graphics();
                             action_tx.send(Action::Render)?;
                         }
                         RenderingTask::Suspend => {
@@ -114,6 +132,28 @@ pub async fn render(
                         }
                         RenderingTask::Resume => {
                             tui.enter()?;
+                            television.lock().await.reset_graphics();
+                        }
+                        RenderingTask::RunCommand(command, done_tx) => {
+                            tui.exit()?;
+                            let mut cmd = Command::from(shell_command(&command));
+                            if !is_output_tty {
+                                // keep stdout clean for the selected entry
+                                cmd.stdout(Stdio::from(stderr()));
+                            }
+                            match cmd.status().await {
+                                Ok(status) if !status.success() => {
+                                    warn!("Command {:?} exited with {}", command, status);
+                                }
+                                Err(e) => {
+                                    warn!("Failed to run command {:?}: {:?}", command, e);
+                                }
+                                Ok(_) => {}
+                            }
+                            tui.enter()?;
+                            television.lock().await.reset_graphics();
+                            action_tx.send(Action::Render)?;
+                            let _ = done_tx.send(());
                         }
                         RenderingTask::Quit => {
                             tui.exit()?;
@@ -0,0 +1,248 @@
+//! The `tv` binary: its command line, the subcommands and the picker it
+//! runs over the channel it was asked for.
+use std::io::{stdout, IsTerminal, Write};
+
+use clap::Parser;
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use television_channels::channels::cable::Channel as CableChannel;
+use television_channels::channels::{
+    plugin_channel, script_channel, CliTvChannel, OnAir, TelevisionChannel,
+};
+use television_channels::entry::Entry;
+use tracing::{debug, info};
+
+use crate::app::App;
+use crate::cli::{ChannelArg, Cli, Command};
+use crate::config::Config;
+use crate::output::OutputFifo;
+use crate::popup::Multiplexer;
+use crate::ui::theme::Theme;
+use crate::{
+    about, bundle, doctor, errors, filter, init, keybindings, logging, nvim,
+    popup, recent, setup, undo, update,
+};
+#[cfg(feature = "http-requests")]
+use television_channels::channels::http_requests::Channel as HttpRequestsChannel;
+#[cfg(feature = "openapi")]
+use television_channels::channels::openapi::Channel as OpenapiChannel;
+use television_channels::channels::stdin::{
+    Channel as StdinChannel, FieldOptions,
+};
+use television_utils::fields::Delimiter;
+use television_utils::stdin::is_readable_stdin;
+
+/// Run `tv` with the arguments it was given on the command line.
+///
+/// # Errors
+/// If the command line or the config file is invalid, or if the picker or
+/// the subcommand failed.
+pub async fn run() -> Result<()> {
+    errors::init()?;
+    logging::init()?;
+
+    let args = Cli::parse();
+    match args.command {
+        Some(Command::Setup) => return setup::run(),
+        Some(Command::Init { shell }) => {
+            init::run(shell);
+            return Ok(());
+        }
+        Some(Command::Doctor) => return doctor::run(args.profile.as_deref()),
+        Some(Command::About { json }) => return about::run(json),
+        Some(Command::Keybindings) => {
+            return keybindings::run(args.profile.as_deref())
+        }
+        Some(Command::Export { path, history }) => {
+            return bundle::export(&path, history)
+        }
+        Some(Command::Import { path, force }) => {
+            return bundle::import(&path, force)
+        }
+        Some(Command::Update { check }) => return update::run(check),
+        Some(Command::Recent { command }) => {
+            let config = Config::new(args.profile.as_deref())?;
+            return recent::run(command, config.recent.limits());
+        }
+        None => {}
+    }
+    for (multiplexer, popup) in [
+        (Multiplexer::Tmux, &args.tmux),
+        (Multiplexer::Zellij, &args.zellij),
+    ] {
+        if let Some(popup) =
+            popup.as_ref().filter(|_| multiplexer.is_running())
+        {
+            return popup::run(multiplexer, popup);
+        }
+    }
+    let mut config = Config::new(args.profile.as_deref())?;
+    if let Some(theme) = &args.theme {
+        config.ui.theme.clone_from(theme);
+    }
+    // fail early rather than silently falling back to the default theme
+    if let Err(e) = Theme::from_config(&config.ui.theme, &config.themes) {
+        bail!(e);
+    }
+    config.expect_keys(&args.expect).map_err(|e| eyre!(e))?;
+    let mut ctx = config.channel_context();
+    if let Some(command) = &args.source_command {
+        ctx.set_source_command(command);
+    }
+    ctx.set_file_types(args.file_types.clone());
+    ctx.set_changed_within(args.changed_within);
+    // don't let the initial channel compete with drawing the first frame
+    if args.filter.is_none() {
+        ctx.startup.hold();
+    }
+
+    let channel = {
+        if is_readable_stdin() {
+            debug!("Using stdin channel");
+            let options = FieldOptions {
+                delimiter: args
+                    .delimiter
+                    .as_deref()
+                    .map(Delimiter::new)
+                    .unwrap_or_default(),
+                with_nth: args.with_nth.clone(),
+                nth: args.nth.clone(),
+                accept_nth: args.accept_nth.clone(),
+            };
+            let separator = if args.read0 { b'\0' } else { b'\n' };
+            TelevisionChannel::Stdin(StdinChannel::new(
+                &ctx, &options, separator,
+            ))
+        } else {
+            debug!("Using {:?} channel", args.channel);
+            let name = match &args.channel {
+                ChannelArg::Builtin(c) => c.to_string(),
+                ChannelArg::Cable(name) => name.clone(),
+            };
+            if !ctx.is_channel_available(&name) {
+                bail!(
+                    "the `{name}` channel is not available (see \
+                     `ui.available_channels` in the config file)"
+                );
+            }
+            match args.channel.clone() {
+                #[cfg(feature = "http-requests")]
+                ChannelArg::Builtin(CliTvChannel::HttpRequests)
+                    if args.execute_requests =>
+                {
+                    TelevisionChannel::HttpRequests(
+                        HttpRequestsChannel::with_execution(&ctx),
+                    )
+                }
+                #[cfg(feature = "openapi")]
+                ChannelArg::Builtin(CliTvChannel::Openapi) => {
+                    TelevisionChannel::Openapi(OpenapiChannel::new(
+                        &ctx,
+                        args.spec.clone(),
+                    ))
+                }
+                ChannelArg::Builtin(CliTvChannel::External)
+                    if args.source_command.is_none() =>
+                {
+                    bail!(
+                        "the `external` channel requires a command to run \
+                         (`--source-command`)"
+                    )
+                }
+                ChannelArg::Builtin(c) => c.to_channel(&ctx),
+                ChannelArg::Cable(name) => match ctx.cable_channel(&name) {
+                    Some(prototype) => TelevisionChannel::Cable(
+                        CableChannel::new(&ctx, prototype),
+                    ),
+                    None => script_channel(&ctx, &name)
+                        .or_else(|| plugin_channel(&ctx, &name))
+                        .ok_or_else(|| {
+                            eyre!(
+                                "unknown channel `{name}` (it is neither a \
+                                 built-in channel, a cable channel defined \
+                                 in the config file, a script listing \
+                                 entries nor a plugin)"
+                            )
+                        })?,
+                },
+            }
+        }
+    };
+    if let Some(pattern) = &args.filter {
+        let mut channel = channel;
+        let entries = filter::filter(&mut channel, pattern).await;
+        channel.shutdown();
+        return print_entries(&entries, None, &args);
+    }
+
+    if let Some(command) = &args.preview {
+        config
+            .channels
+            .entry(channel.name().to_lowercase())
+            .or_default()
+            .preview_command = Some(command.clone());
+    }
+
+    let bus = ctx.bus.clone();
+    let mut app: App =
+        App::new(config, ctx, channel, args.tick_rate, args.frame_rate)?;
+    if args.no_input {
+        app.set_no_input();
+    }
+    if let Some(address) = &args.listen {
+        app.listen(address).await?;
+    }
+    if let Some(path) = &args.output_fifo {
+        app.output_to(OutputFifo::new(
+            path.clone(),
+            args.output_format,
+            if args.print0 { '\0' } else { '\n' },
+            bus,
+        ));
+    }
+
+    let selection = app.run(stdout().is_terminal()).await;
+    // the deleted files can't be restored anymore
+    undo::empty_trash();
+    let mut entries = selection?;
+    if let Some(server) = &args.nvim_server {
+        let mut not_edited = Vec::new();
+        for entry in entries {
+            if !nvim::edit(server, &entry)? {
+                not_edited.push(entry);
+            }
+        }
+        entries = not_edited;
+    }
+    let expected_key = (!args.expect.is_empty() && !entries.is_empty())
+        .then(|| app.expected_key().unwrap_or_default().to_string());
+    print_entries(&entries, expected_key.as_deref(), &args)
+}
+
+/// Print the given entries to stdout, as requested on the command line,
+/// after the key they were confirmed with (see `--expect`).
+fn print_entries(
+    entries: &[Entry],
+    key: Option<&str>,
+    args: &Cli,
+) -> Result<()> {
+    let separator = if args.print0 { '\0' } else { '\n' };
+    let mut stdout = stdout().lock();
+    let result = key
+        .map_or(Ok(()), |key| write!(stdout, "{key}{separator}"))
+        .and_then(|()| {
+            entries.iter().try_for_each(|entry| {
+                info!("{:?}", entry);
+                write!(
+                    stdout,
+                    "{}{separator}",
+                    args.output_format.format(entry)
+                )
+            })
+        });
+    match result.and_then(|()| stdout.flush()) {
+        // e.g. piped to `head`
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::{HashMap, VecDeque};
+
+/// How many preview scroll positions are remembered.
+const DEFAULT_CAPACITY: usize = 512;
+
+/// The scroll positions of the previews the user scrolled during the
+/// session, so that going back to an entry restores where they left off.
+///
+/// The least recently used positions are forgotten once the capacity is
+/// reached.
+#[derive(Debug)]
+pub struct ScrollMemory {
+    positions: HashMap<String, u16>,
+    /// The keys, from the least to the most recently used.
+    recency: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Default for ScrollMemory {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl ScrollMemory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            positions: HashMap::new(),
+            recency: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// The remembered scroll position for the given key, if any.
+    pub fn get(&mut self, key: &str) -> Option<u16> {
+        let position = self.positions.get(key).copied()?;
+        self.touch(key);
+        Some(position)
+    }
+
+    /// Remember the scroll position for the given key.
+    pub fn set(&mut self, key: &str, position: u16) {
+        if self.positions.insert(key.to_string(), position).is_some() {
+            self.touch(key);
+            return;
+        }
+        self.recency.push_back(key.to_string());
+        if self.recency.len() > self.capacity {
+            if let Some(oldest) = self.recency.pop_front() {
+                self.positions.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &str) {
+        if let Some(i) = self.recency.iter().position(|k| k == key) {
+            if let Some(key) = self.recency.remove(i) {
+                self.recency.push_back(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_memory() {
+        let mut memory = ScrollMemory::with_capacity(2);
+        memory.set("a", 10);
+        memory.set("b", 20);
+        // "a" becomes the most recently used one
+        assert_eq!(memory.get("a"), Some(10));
+        memory.set("c", 30);
+        assert_eq!(memory.get("b"), None);
+        assert_eq!(memory.get("a"), Some(10));
+        memory.set("c", 35);
+        memory.set("d", 40);
+        assert_eq!(memory.get("a"), None);
+        assert_eq!(memory.get("c"), Some(35));
+        assert_eq!(memory.get("d"), Some(40));
+    }
+}
@@ -0,0 +1,349 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+use directories::BaseDirs;
+use television_utils::shell::find_executable;
+
+use crate::cli::Cli;
+use crate::config::{config_file_path, CONFIG};
+use crate::init;
+
+const BIN_NAME: &str = "tv";
+
+/// The marker preceding the line loading the shell widgets in the user's rc
+/// file, used to avoid installing them twice.
+const WIDGET_MARKER: &str = "# television shell widget (added by `tv setup`)";
+
+/// The optional tools used by some channels and previews, along with what
+/// they unlock.
+pub const OPTIONAL_DEPENDENCIES: [(&str, &str); 11] = [
+    ("fc-list", "listing installed fonts in the `fonts` channel"),
+    (
+        "journalctl",
+        "listing the systemd journal in the `journal` channel",
+    ),
+    ("crontab", "listing crontab entries in the `cron` channel"),
+    ("systemctl", "listing systemd timers in the `cron` channel"),
+    ("dig", "DNS lookups in the `dns` channel previews"),
+    (
+        "docker",
+        "the `docker-containers` and `docker-images` channels",
+    ),
+    ("curl", "updating tv to the latest release (`tv update`)"),
+    ("hurl", "executing `.hurl` requests (`--execute-requests`)"),
+    (
+        "httpyac",
+        "executing `.http` requests (`--execute-requests`)",
+    ),
+    (
+        "git",
+        "the `git log` secondary preview (see `ui.secondary_previews`)",
+    ),
+    (
+        "bat",
+        "custom syntaxes and themes for previews (`bat cache --build`)",
+    ),
+];
+
+/// Run the interactive first-run setup: install the shell widgets and
+/// completions, write a starter config and check the optional dependencies.
+pub fn run() -> Result<()> {
+    if !io::stdin().is_terminal() {
+        bail!("`tv setup` is interactive and must be run from a terminal");
+    }
+    let mut prompt = Prompt::new(io::stdin().lock(), io::stdout());
+    println!("Welcome to television! Let's get you set up.\n");
+
+    match Shell::from_env() {
+        Some(shell) => {
+            println!("Detected shell: {shell}");
+            setup_shell_widget(&mut prompt, shell)?;
+            setup_completions(&mut prompt, shell)?;
+        }
+        None => println!(
+            "Couldn't detect your shell, skipping the shell integration."
+        ),
+    }
+    println!();
+
+    setup_config(&mut prompt)?;
+    println!();
+
+    check_dependencies();
+    println!("\nAll set, enjoy the show!");
+    Ok(())
+}
+
+fn setup_shell_widget<R: BufRead, W: Write>(
+    prompt: &mut Prompt<R, W>,
+    shell: Shell,
+) -> Result<()> {
+    let (Some(init_shell), Some(rc_file)) =
+        (init::Shell::from_completion_shell(shell), rc_file(shell))
+    else {
+        println!("No shell widgets are available for {shell} yet.");
+        return Ok(());
+    };
+    let rc_content = std::fs::read_to_string(&rc_file).unwrap_or_default();
+    if rc_content.contains(WIDGET_MARKER) {
+        println!("The shell widgets are already installed in {rc_file:?}.");
+        return Ok(());
+    }
+    if prompt.confirm(
+        &format!(
+            "Install the shell widgets (ctrl-t: files, ctrl-r: history, \
+             alt-c: directories) in {}?",
+            rc_file.display()
+        ),
+        true,
+    )? {
+        if let Some(parent) = rc_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&rc_file)?;
+        writeln!(file, "\n{WIDGET_MARKER}\n{}", init_shell.rc_line())?;
+        println!("Installed, restart your shell to use it.");
+    }
+    Ok(())
+}
+
+fn setup_completions<R: BufRead, W: Write>(
+    prompt: &mut Prompt<R, W>,
+    shell: Shell,
+) -> Result<()> {
+    let Some(path) = completions_file(shell) else {
+        println!("Installing completions for {shell} isn't supported yet.");
+        return Ok(());
+    };
+    if prompt.confirm(
+        &format!("Install completions in {}?", path.display()),
+        true,
+    )? {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::File::create(&path)?;
+        clap_complete::generate(
+            shell,
+            &mut Cli::command(),
+            BIN_NAME,
+            &mut file,
+        );
+        println!("Installed.");
+        if shell == Shell::Zsh {
+            println!(
+                "Make sure `fpath+=~/.zfunc` comes before `compinit` in your .zshrc."
+            );
+        }
+    }
+    Ok(())
+}
+
+fn setup_config<R: BufRead, W: Write>(
+    prompt: &mut Prompt<R, W>,
+) -> Result<()> {
+    let path = config_file_path();
+    if path.exists()
+        && !prompt.confirm(
+            &format!(
+                "A config already exists at {}, overwrite it?",
+                path.display()
+            ),
+            false,
+        )?
+    {
+        return Ok(());
+    }
+    let themes = available_themes();
+    let theme = if themes.is_empty() {
+        None
+    } else {
+        prompt.choose("Pick a theme for file previews", &themes)?
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, starter_config(theme))?;
+    println!("Wrote a starter config to {}.", path.display());
+    Ok(())
+}
+
+fn check_dependencies() {
+    println!("Optional dependencies:");
+    for (tool, unlocks) in OPTIONAL_DEPENDENCIES {
+        let status = if find_executable(tool).is_some() {
+            "found"
+        } else {
+            "missing"
+        };
+        println!("  {tool:<10} {status:<8} {unlocks}");
+    }
+}
+
+#[cfg(feature = "syntax-highlighting")]
+fn available_themes() -> Vec<String> {
+    television_utils::syntax::load_highlighting_assets()
+        .themes()
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(not(feature = "syntax-highlighting"))]
+fn available_themes() -> Vec<String> {
+    Vec::new()
+}
+
+/// The default config, using the given theme for file previews.
+fn starter_config(theme: Option<&str>) -> String {
+    let Some(theme) = theme else {
+        return CONFIG.to_string();
+    };
+    CONFIG
+        .lines()
+        .map(|line| {
+            if line.starts_with("theme = ") {
+                format!("theme = {theme:?}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// The file the shell widgets are loaded from.
+fn rc_file(shell: Shell) -> Option<PathBuf> {
+    let home = BaseDirs::new()?.home_dir().to_path_buf();
+    match shell {
+        Shell::Bash => Some(home.join(".bashrc")),
+        Shell::Zsh => Some(
+            std::env::var_os("ZDOTDIR")
+                .map_or(home, PathBuf::from)
+                .join(".zshrc"),
+        ),
+        Shell::Fish => Some(fish_config_dir(&home).join("conf.d/tv.fish")),
+        _ => None,
+    }
+}
+
+/// The file the completions are installed in.
+fn completions_file(shell: Shell) -> Option<PathBuf> {
+    let home = BaseDirs::new()?.home_dir().to_path_buf();
+    match shell {
+        Shell::Bash => Some(
+            std::env::var_os("XDG_DATA_HOME")
+                .map_or_else(|| home.join(".local/share"), PathBuf::from)
+                .join("bash-completion/completions")
+                .join(BIN_NAME),
+        ),
+        Shell::Zsh => Some(home.join(".zfunc/_tv")),
+        Shell::Fish => {
+            Some(fish_config_dir(&home).join("completions/tv.fish"))
+        }
+        _ => None,
+    }
+}
+
+fn fish_config_dir(home: &Path) -> PathBuf {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map_or_else(|| home.join(".config"), PathBuf::from)
+        .join("fish")
+}
+
+/// Asks questions on the terminal.
+struct Prompt<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompt<R, W> {
+    fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    fn ask(&mut self, question: &str) -> Result<String> {
+        write!(self.output, "{question} ")?;
+        self.output.flush()?;
+        let mut answer = String::new();
+        self.input.read_line(&mut answer)?;
+        Ok(answer.trim().to_string())
+    }
+
+    /// Ask a yes/no question, an empty answer meaning the default.
+    fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        let hint = if default { "[Y/n]" } else { "[y/N]" };
+        loop {
+            match self
+                .ask(&format!("{question} {hint}"))?
+                .to_lowercase()
+                .as_str()
+            {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+
+    /// Ask to pick one of the given choices, by number or name. An empty
+    /// answer picks none.
+    fn choose<'a>(
+        &mut self,
+        question: &str,
+        choices: &'a [String],
+    ) -> Result<Option<&'a str>> {
+        for (i, choice) in choices.iter().enumerate() {
+            writeln!(self.output, "  {:>3}. {choice}", i + 1)?;
+        }
+        loop {
+            let answer = self
+                .ask(&format!("{question} (leave empty for the default):"))?;
+            if answer.is_empty() {
+                return Ok(None);
+            }
+            let choice = answer
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| choices.get(i.checked_sub(1)?))
+                .or_else(|| {
+                    choices.iter().find(|c| c.eq_ignore_ascii_case(&answer))
+                });
+            if let Some(choice) = choice {
+                return Ok(Some(choice));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_starter_config() {
+        let config = starter_config(Some("Nord"));
+        assert!(config.contains("\ntheme = \"Nord\"\n"));
+        assert_eq!(config.lines().count(), CONFIG.lines().count());
+        assert_eq!(starter_config(None), CONFIG);
+    }
+
+    #[test]
+    fn test_prompt() {
+        let mut output = Vec::new();
+        let mut prompt =
+            Prompt::new("maybe\nn\n\n7\nnord\n".as_bytes(), &mut output);
+        assert!(!prompt.confirm("Sure?", true).unwrap());
+        assert!(prompt.confirm("Sure?", true).unwrap());
+        let themes = vec!["Monokai".to_string(), "Nord".to_string()];
+        assert_eq!(prompt.choose("Theme?", &themes).unwrap(), Some("Nord"));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::television::{ConfirmedAction, Mode, PromptedAction};
+use crate::ui::confirm::Confirmation;
+use crate::ui::input::Input;
+use crate::ui::prompt::Prompt;
+use tracing::debug;
+
+/// A popup drawn over the current mode, which captures all input until it
+/// is closed.
+pub(crate) enum Overlay {
+    /// A destructive action waiting for the user's confirmation.
+    Confirmation(Confirmation<ConfirmedAction>),
+    /// An action waiting for a value typed in the input prompt.
+    Prompt(Prompt<PromptedAction>),
+    /// The note being written for the selected entry.
+    Annotation(Input),
+}
+
+/// A change of the state of the television.
+pub(crate) enum Transition {
+    /// Open the remote control to switch to another channel.
+    OpenRemoteControl,
+    /// Open the remote control to send the results to another channel.
+    OpenSendToChannel,
+    /// Open the list of applications the selected file can be opened with.
+    OpenWith,
+    /// Open the list of the types of the files found.
+    OpenFileTypes,
+    /// Go back to browsing the current channel.
+    Back,
+    /// Open the given popup over the current mode.
+    Open(Overlay),
+}
+
+/// The state of the television: the mode it is in, which decides the
+/// keybindings in use, and the popup opened over it, if any.
+///
+/// The state only changes through `apply`, which refuses the transitions
+/// that make no sense from the current state (e.g. opening the remote
+/// control while a prompt is open).
+pub struct State {
+    mode: Mode,
+    overlay: Option<Overlay>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Channel,
+            overlay: None,
+        }
+    }
+}
+
+impl State {
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub(crate) fn overlay(&self) -> Option<&Overlay> {
+        self.overlay.as_ref()
+    }
+
+    pub(crate) fn overlay_mut(&mut self) -> Option<&mut Overlay> {
+        self.overlay.as_mut()
+    }
+
+    /// The mode the given transition leads to, or `None` if it can't be
+    /// taken from the current state.
+    fn target(&self, transition: &Transition) -> Option<Mode> {
+        if self.overlay.is_some() {
+            return None;
+        }
+        match (self.mode, transition) {
+            (Mode::Channel, Transition::OpenRemoteControl) => {
+                Some(Mode::RemoteControl)
+            }
+            (
+                Mode::Channel | Mode::RemoteControl,
+                Transition::OpenSendToChannel,
+            ) => Some(Mode::SendToChannel),
+            (Mode::Channel, Transition::OpenWith) => Some(Mode::OpenWith),
+            (Mode::Channel, Transition::OpenFileTypes) => {
+                Some(Mode::FileTypes)
+            }
+            (mode, Transition::Back) if mode != Mode::Channel => {
+                Some(Mode::Channel)
+            }
+            (mode, Transition::Open(_)) => Some(mode),
+            _ => None,
+        }
+    }
+
+    /// Whether the given transition can be taken from the current state.
+    pub(crate) fn can(&self, transition: &Transition) -> bool {
+        self.target(transition).is_some()
+    }
+
+    /// Take the given transition, leaving the state untouched if it can't
+    /// be taken from the current state.
+    ///
+    /// # Returns
+    /// Whether the transition was taken.
+    pub(crate) fn apply(&mut self, transition: Transition) -> bool {
+        let Some(mode) = self.target(&transition) else {
+            debug!("Ignoring transition from {} mode", self.mode);
+            return false;
+        };
+        self.mode = mode;
+        if let Transition::Open(overlay) = transition {
+            self.overlay = Some(overlay);
+        }
+        true
+    }
+
+    /// Close the popup opened over the current mode, if any.
+    pub(crate) fn close_overlay(&mut self) -> Option<Overlay> {
+        self.overlay.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions() {
+        let mut state = State::default();
+        assert!(!state.apply(Transition::Back));
+        assert!(state.apply(Transition::OpenRemoteControl));
+        assert_eq!(state.mode(), Mode::RemoteControl);
+        assert!(!state.can(&Transition::OpenWith));
+        assert!(state.apply(Transition::OpenSendToChannel));
+        assert_eq!(state.mode(), Mode::SendToChannel);
+        assert!(state.apply(Transition::Back));
+        assert_eq!(state.mode(), Mode::Channel);
+
+        // popups stack over the current mode and block every other
+        // transition until they are closed
+        assert!(state.apply(Transition::OpenFileTypes));
+        assert!(state
+            .apply(Transition::Open(Overlay::Annotation(Input::default()))));
+        assert_eq!(state.mode(), Mode::FileTypes);
+        assert!(!state.apply(Transition::Back));
+        assert!(!state
+            .apply(Transition::Open(Overlay::Annotation(Input::default()))));
+        assert!(matches!(
+            state.close_overlay(),
+            Some(Overlay::Annotation(_))
+        ));
+        assert!(state.overlay().is_none());
+        assert!(state.apply(Transition::Back));
+        assert_eq!(state.mode(), Mode::Channel);
+    }
+}
@@ -1,24 +1,72 @@
 use crate::picker::Picker;
+use crate::quickfix::quickfix_list;
+use crate::scroll_memory::ScrollMemory;
+use crate::state::{Overlay, State, Transition};
+use crate::ui::confirm::Confirmation;
+use crate::ui::image::Graphics;
 use crate::ui::input::actions::InputActionHandler;
-use crate::ui::layout::{Dimensions, Layout};
+use crate::ui::input::Input;
+use crate::ui::layout::{Dimensions, Layout, LayoutOptions};
+use crate::ui::pane::PaneCache;
+use crate::ui::preview::PreviewPaneKey;
+use crate::ui::prompt::Prompt;
 use crate::ui::spinner::Spinner;
 use crate::ui::spinner::SpinnerState;
-use crate::{action::Action, config::Config};
+use crate::ui::theme::Theme;
+use crate::ui::toast::{ToastLevel, Toasts};
+use crate::undo::{self, UndoStack, Undoable};
+use crate::{
+    action::Action,
+    clipboard,
+    config::{ClipboardConfig, Config},
+};
 use color_eyre::Result;
-use copypasta::{ClipboardContext, ClipboardProvider};
 use futures::executor::block_on;
-use ratatui::{layout::Rect, style::Color, widgets::Paragraph, Frame};
+use ratatui::{
+    layout::Rect,
+    widgets::{Clear, Paragraph},
+    Frame,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use strum::Display;
+use television_channels::annotations::Annotations;
+use television_channels::bus::{Bus, Message};
+#[cfg(feature = "processes")]
+use television_channels::channels::processes;
+#[cfg(feature = "scripting")]
+use television_channels::channels::script::{report_error, Script};
 use television_channels::channels::{
+    cable::Channel as CableChannel, channel_parameter,
     remote_control::RemoteControl, OnAir, TelevisionChannel, UnitChannel,
 };
-use television_channels::entry::{Entry, ENTRY_PLACEHOLDER};
+use television_channels::context::{Context, FromContext};
+use television_channels::entry::{
+    Entry, PreviewCommand, PreviewType, ENTRY_PLACEHOLDER,
+};
+use television_channels::filters::format_age;
+use television_channels::hidden::HiddenEntries;
+use television_channels::pins::Pins;
+use television_channels::recent::RecentEntries;
+use television_channels::selection::Selection;
+use television_channels::time_range::TimeRange;
 use television_previewers::previewers;
-use television_previewers::previewers::Previewer;
-use television_utils::strings::EMPTY_STRING;
+use television_previewers::previewers::{Preview, Previewer};
+use television_utils::checksum::{Checksum, ChecksumStatus};
+use television_utils::desktop::{
+    applications_for, open_default, DesktopApplication,
+};
+use television_utils::dirs::get_data_dir;
+use television_utils::files::complete_path;
+use television_utils::format;
+use television_utils::image::GraphicsProtocol;
+use television_utils::shell::editor;
+use television_utils::strings::{shell_quote, EMPTY_STRING};
 use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, warn};
 
 #[derive(
     PartialEq, Copy, Clone, Hash, Eq, Debug, Serialize, Deserialize, Display,
@@ -27,6 +75,8 @@ pub enum Mode {
     Channel,
     RemoteControl,
     SendToChannel,
+    OpenWith,
+    FileTypes,
 }
 
 pub struct Television {
@@ -34,15 +84,28 @@ pub struct Television {
     pub config: Config,
     pub(crate) channel: TelevisionChannel,
     pub(crate) remote_control: TelevisionChannel,
-    pub mode: Mode,
+    /// The mode the television is in and the popup opened over it, if any.
+    pub(crate) state: State,
     current_pattern: String,
     pub(crate) results_picker: Picker,
     pub(crate) rc_picker: Picker,
     results_area_height: u32,
     pub previewer: Previewer,
     pub preview_scroll: Option<u16>,
+    /// The scroll positions of the previews scrolled by the user.
+    scroll_memory: ScrollMemory,
     pub preview_pane_height: u16,
     current_preview_total_lines: u16,
+    /// Whether the preview pane was shown or hidden by the user for the
+    /// current channel (see `preview_shown`).
+    show_preview: Option<bool>,
+    /// Whether tail mode was turned on or off for the current channel (see
+    /// `tail_mode`).
+    tail: Option<bool>,
+    /// When the current channel was last refreshed (see `refresh_interval`).
+    last_refresh: Instant,
+    /// Whether the terminal is narrower than the configured `narrow_width`.
+    pub(crate) narrow: bool,
     /// A cache for meta paragraphs (i.e. previews like "Not Supported", etc.).
     ///
     /// The key is a tuple of the preview name and the dimensions of the
@@ -52,37 +115,184 @@ pub struct Television {
     pub meta_paragraph_cache: HashMap<(String, u16, u16), Paragraph<'static>>,
     pub(crate) spinner: Spinner,
     pub(crate) spinner_state: SpinnerState,
+    /// What the channels are created with, when switching to them.
+    pub(crate) context: Context,
+    /// The notes attached to the entries of the current channel.
+    pub(crate) annotations: Annotations,
+    /// The entries pinned to the top of the results of the current channel.
+    pub(crate) pins: Pins,
+    /// The entries recently selected in the current channel.
+    recent_entries: RecentEntries,
+    /// The entries selected to be output together (see `ToggleSelection`),
+    /// as opposed to the one under the cursor.
+    pub(crate) selection: Selection,
+    /// The indices (among the channel's results) of the entries displayed
+    /// first, in display order: the pinned entries and, with an empty
+    /// pattern, the recently selected ones (see `recent_first`).
+    promoted_results: Vec<u32>,
+    /// The pattern and result counts `promoted_results` was computed for.
+    promoted_results_key: Option<(String, u32, u32)>,
+    /// The entry selected in the results as of the last frame, along with
+    /// its index and the pattern then (see `keep_selected_entry`).
+    last_selected_entry: Option<(usize, String, Entry)>,
+    /// The checksums of the last file they were requested for.
+    pub(crate) checksum: Option<Checksum>,
+    /// The file being opened and the applications it can be opened with,
+    /// in open with mode.
+    open_with: Option<(PathBuf, Vec<DesktopApplication>)>,
+    /// The notifications of the actions that are running or just finished.
+    pub(crate) toasts: Toasts,
+    /// The last rendering of the preview pane.
+    pub(crate) preview_pane: PaneCache<PreviewPaneKey>,
+    /// The previews sent by the clients of the control socket, by the
+    /// output of the entries they preview.
+    pushed_previews: HashMap<String, Arc<Preview>>,
+    /// The colors and styles of the UI.
+    pub(crate) theme: Theme,
+    /// How images are drawn in the preview pane.
+    pub(crate) graphics_protocol: GraphicsProtocol,
+    /// The images written to the terminal.
+    pub(crate) graphics: Graphics,
+    /// The reversible actions of the session.
+    undo_stack: UndoStack,
+    /// How recently the files must have been modified when toggling the
+    /// modification time filter of the files channel on.
+    changed_within: Duration,
+}
+
+/// The destructive actions, carried out once confirmed (see
+/// `ui.confirm_destructive_actions`).
+#[derive(Debug, Clone)]
+pub(crate) enum ConfirmedAction {
+    DeleteFile(PathBuf),
+    /// Renaming a file over an existing one.
+    RenameFile {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    #[cfg(feature = "processes")]
+    TerminateProcess {
+        pid: u32,
+        name: String,
+    },
+}
+
+/// The actions needing a value, carried out once it is submitted in the
+/// input prompt.
+#[derive(Debug, Clone)]
+pub(crate) enum PromptedAction {
+    /// Renaming the given file to the submitted path.
+    RenameFile(PathBuf),
+    /// Asking for the end of the time range the lines of the current
+    /// channel's command are restricted to, from the submitted start.
+    FilterSince,
+    /// Restricting the lines of the current channel's command to the time
+    /// range from the given start to the submitted end.
+    FilterUntil(String),
+    /// Switching to the channel with the given name, created from the
+    /// submitted parameter.
+    SwitchChannel(String),
 }
 
+/// The key of the toast reporting the progress of the checksums.
+const CHECKSUM_TOAST: &str = "checksum";
+
+/// How often the progress of the checksums is reported.
+const CHECKSUM_REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The default name of the file quickfix lists are exported to.
+const QUICKFIX_FILE_NAME: &str = "quickfix.txt";
+
+/// How many of the top results are searched for pinned and recent entries.
+const PROMOTED_RESULTS_SCAN_LIMIT: u32 = 10_000;
+
+/// The entry of file types mode listing the files of any type again.
+const ALL_FILE_TYPES: &str = "all files";
+
+/// The examples of the bounds of a time range given in its prompts.
+const TIME_RANGE_EXAMPLES: &str = "e.g. 2h, yesterday, 2024-11-30 14:00";
+
+/// How recently the files must have been modified when toggling the
+/// modification time filter on, unless `--changed-within` says otherwise.
+const DEFAULT_CHANGED_WITHIN: Duration = Duration::from_secs(24 * 60 * 60);
+
 impl Television {
+    /// Watch the given channel, creating the ones switched to with the
+    /// given context.
     #[must_use]
-    pub fn new(mut channel: TelevisionChannel) -> Self {
+    pub fn new(mut channel: TelevisionChannel, context: Context) -> Self {
         channel.find(EMPTY_STRING);
         let spinner = Spinner::default();
+        let channel_name = channel.name().to_lowercase();
+        let annotations = Annotations::for_channel(&channel_name);
+        let pins = Pins::for_channel(&channel_name);
+        let recent_entries =
+            RecentEntries::for_channel(&channel_name, context.recent_limits());
         Self {
             action_tx: None,
             config: Config::default(),
             channel,
             remote_control: TelevisionChannel::RemoteControl(
-                RemoteControl::default(),
+                RemoteControl::from_context(&context),
             ),
-            mode: Mode::Channel,
+            state: State::default(),
             current_pattern: EMPTY_STRING.to_string(),
             results_picker: Picker::default(),
             rc_picker: Picker::default().inverted(),
             results_area_height: 0,
-            previewer: Previewer::default(),
+            previewer: Previewer::new(None, &context),
             preview_scroll: None,
+            scroll_memory: ScrollMemory::default(),
             preview_pane_height: 0,
             current_preview_total_lines: 0,
+            show_preview: None,
+            tail: None,
+            last_refresh: Instant::now(),
+            narrow: false,
             meta_paragraph_cache: HashMap::new(),
             spinner,
             spinner_state: SpinnerState::from(&spinner),
+            annotations,
+            pins,
+            recent_entries,
+            selection: Selection::default(),
+            promoted_results: Vec::new(),
+            promoted_results_key: None,
+            last_selected_entry: None,
+            checksum: None,
+            open_with: None,
+            toasts: Toasts::default(),
+            preview_pane: PaneCache::default(),
+            pushed_previews: HashMap::new(),
+            theme: Theme::default(),
+            graphics_protocol: GraphicsProtocol::HalfBlocks,
+            graphics: Graphics::default(),
+            undo_stack: UndoStack::default(),
+            changed_within: context
+                .changed_within()
+                .unwrap_or(DEFAULT_CHANGED_WITHIN),
+            context,
         }
     }
 
-    pub fn current_channel(&self) -> UnitChannel {
-        UnitChannel::from(&self.channel)
+    /// The mode the television is in, which decides the keybindings in
+    /// use.
+    pub fn mode(&self) -> Mode {
+        self.state.mode()
+    }
+
+    /// The note being written for the selected entry, if any.
+    pub(crate) fn annotation_input(&self) -> Option<&Input> {
+        match self.state.overlay() {
+            Some(Overlay::Annotation(input)) => Some(input),
+            _ => None,
+        }
+    }
+
+    /// The lowercase name of the current channel, as used to configure
+    /// channel specific settings.
+    pub(crate) fn current_channel_name(&self) -> String {
+        self.channel.name().to_lowercase()
     }
 
     pub fn change_channel(&mut self, channel: TelevisionChannel) {
@@ -90,16 +300,1056 @@ impl Television {
         self.reset_picker_selection();
         self.reset_picker_input();
         self.current_pattern = EMPTY_STRING.to_string();
-        self.channel.shutdown();
+        // the new channel might not show its preview by default
+        self.show_preview = None;
+        self.tail = None;
+        self.shutdown();
+        self.pushed_previews.clear();
+        self.last_selected_entry = None;
         self.channel = channel;
+        self.state.close_overlay();
+        self.annotations =
+            Annotations::for_channel(&self.current_channel_name());
+        self.pins = Pins::for_channel(&self.current_channel_name());
+        self.recent_entries = RecentEntries::for_channel(
+            &self.current_channel_name(),
+            self.context.recent_limits(),
+        );
+        self.selection.clear();
+        self.promoted_results.clear();
+        self.promoted_results_key = None;
+    }
+
+    /// Hide the selected entry from the files and text channels of the
+    /// current project (or un-hide it when browsing the hidden entries) and
+    /// reload the channel, keeping the current pattern.
+    fn toggle_selected_entry_hidden(&mut self) -> Result<()> {
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return Ok(());
+        };
+        let mut hidden_entries = HiddenEntries::for_current_dir();
+        match self.channel {
+            TelevisionChannel::Files(_) | TelevisionChannel::Text(_) => {
+                hidden_entries.hide(&entry.name)?;
+                self.undo_stack.push(Undoable::Hide(entry.name));
+            }
+            TelevisionChannel::HiddenEntries(_) => {
+                hidden_entries.unhide(&entry.name)?;
+                self.undo_stack.push(Undoable::Unhide(entry.name));
+            }
+            _ => return Ok(()),
+        }
+        self.reload_channel();
+        Ok(())
+    }
+
+    /// Revert the last reversible action, reporting how it went.
+    fn undo(&mut self) {
+        let Some(action) = self.undo_stack.pop() else {
+            self.toasts.info("Nothing to undo".to_string());
+            return;
+        };
+        let result = match &action {
+            Undoable::Hide(entry) => {
+                HiddenEntries::for_current_dir().unhide(entry)
+            }
+            Undoable::Unhide(entry) => {
+                HiddenEntries::for_current_dir().hide(entry)
+            }
+            Undoable::Rename { from, to } => {
+                restore_file(to, from).map_err(Into::into)
+            }
+            Undoable::Delete { path, trashed } => {
+                restore_file(trashed, path).map_err(Into::into)
+            }
+        };
+        match result {
+            Ok(()) => {
+                debug!("Undid {:?}", action);
+                self.toasts.success(action.description());
+                if matches!(
+                    self.channel,
+                    TelevisionChannel::Files(_)
+                        | TelevisionChannel::Text(_)
+                        | TelevisionChannel::HiddenEntries(_)
+                ) {
+                    self.reload_channel();
+                }
+            }
+            Err(e) => self.toasts.error(format!("Failed to undo: {e}")),
+        }
+    }
+
+    /// How to undo the last action, to be appended to its report.
+    fn undo_hint(&self) -> String {
+        self.config
+            .keymap(&self.current_channel_name(), Mode::Channel)
+            .into_iter()
+            .find(|(_, action)| *action == Action::Undo)
+            .map(|(key, _)| format!(" ({key} to undo)"))
+            .unwrap_or_default()
+    }
+
+    /// Move the files and text channels on to the next search scope (the
+    /// project, its current package, the current directory), reloading the
+    /// current channel if it is one of them.
+    fn cycle_search_scope(&mut self) {
+        self.context.set_scope(self.context.scope().next());
+        self.toasts.info(format!(
+            "Searching the {} ({})",
+            self.context.scope(),
+            self.context.search_root().display()
+        ));
+        if self.searches_files() {
+            self.reload_channel();
+        }
+    }
+
+    /// Toggle listing only the files modified recently in the files
+    /// channel, reloading it if it is the current one.
+    fn toggle_changed_within(&mut self) {
+        if self.context.changed_within().is_some() {
+            self.context.set_changed_within(None);
+            self.toasts.info("Listing all files".to_string());
+        } else {
+            self.context.set_changed_within(Some(self.changed_within));
+            self.toasts.info(format!(
+                "Listing the files changed within {}",
+                format_age(self.changed_within)
+            ));
+        }
+        if matches!(self.channel, TelevisionChannel::Files(_)) {
+            self.reload_channel();
+        }
+    }
+
+    /// Whether the current channel searches a directory tree, in the current
+    /// search scope.
+    pub(crate) fn searches_files(&self) -> bool {
+        matches!(
+            self.channel,
+            TelevisionChannel::Files(_) | TelevisionChannel::Text(_)
+        )
+    }
+
+    /// Stop the background work of the current channel: loading its
+    /// entries and running the commands previewing them.
+    pub fn shutdown(&mut self) {
+        self.channel.shutdown();
+        self.previewer.cancel_all();
+    }
+
+    /// Reload the current channel, keeping the current pattern.
+    fn reload_channel(&mut self) {
+        self.shutdown();
+        self.channel = match &self.channel {
+            // cable channels are created from their prototype
+            TelevisionChannel::Cable(channel) => TelevisionChannel::Cable(
+                CableChannel::new(&self.context, channel.prototype().clone()),
+            ),
+            channel => UnitChannel::from(channel).to_channel(&self.context),
+        };
+        self.channel.find(&self.current_pattern);
+        self.reset_picker_selection();
+        self.reset_preview_scroll();
+    }
+
+    /// Whether the current channel lists the lines printed by a command,
+    /// which can be restricted to a time range.
+    pub(crate) fn runs_command(&self) -> bool {
+        match self.channel {
+            #[cfg(feature = "journal")]
+            TelevisionChannel::Journal(_) => true,
+            TelevisionChannel::External(_) | TelevisionChannel::Cable(_) => {
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Ask for the confirmation of the given action, or carry it out right
+    /// away if confirmations are disabled.
+    fn confirm(
+        &mut self,
+        message: String,
+        action: ConfirmedAction,
+    ) -> Result<()> {
+        if self.config.ui.confirm_destructive_actions {
+            self.state.apply(Transition::Open(Overlay::Confirmation(
+                Confirmation::new(message, action),
+            )));
+            Ok(())
+        } else {
+            self.run_confirmed_action(action)
+        }
+    }
+
+    fn run_confirmed_action(&mut self, action: ConfirmedAction) -> Result<()> {
+        match action {
+            ConfirmedAction::DeleteFile(path) => match undo::trash(&path) {
+                Ok(trashed) => {
+                    debug!("Deleted {:?}", path);
+                    let message = format!("Deleted {}", path.display());
+                    match trashed {
+                        Some(trashed) => {
+                            self.toasts
+                                .success(message + self.undo_hint().as_str());
+                            self.undo_stack
+                                .push(Undoable::Delete { path, trashed });
+                        }
+                        None => self.toasts.success(message),
+                    }
+                    self.reload_channel();
+                }
+                Err(e) => self.toasts.error(format!(
+                    "Failed to delete {}: {e}",
+                    path.display()
+                )),
+            },
+            ConfirmedAction::RenameFile { from, to } => {
+                match std::fs::rename(&from, &to) {
+                    Ok(()) => {
+                        debug!("Renamed {:?} to {:?}", from, to);
+                        self.toasts.success(format!(
+                            "Renamed {} to {}{}",
+                            from.display(),
+                            to.display(),
+                            self.undo_hint()
+                        ));
+                        self.undo_stack.push(Undoable::Rename { from, to });
+                        self.reload_channel();
+                    }
+                    Err(e) => self.toasts.error(format!(
+                        "Failed to rename {}: {e}",
+                        from.display()
+                    )),
+                }
+            }
+            #[cfg(feature = "processes")]
+            ConfirmedAction::TerminateProcess { pid, name } => {
+                match processes::terminate(pid) {
+                    Ok(()) => {
+                        debug!("Terminated process {}", pid);
+                        self.toasts.success(format!("Terminated {name}"));
+                        self.reload_channel();
+                    }
+                    Err(e) => self
+                        .toasts
+                        .error(format!("Failed to terminate {name}: {e}")),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn run_prompted_action(
+        &mut self,
+        action: PromptedAction,
+        value: &str,
+    ) -> Result<()> {
+        match action {
+            PromptedAction::RenameFile(from) => {
+                let to = PathBuf::from(value);
+                if value.is_empty() || to == from {
+                    return Ok(());
+                }
+                if to.exists() {
+                    self.confirm(
+                        format!("Overwrite {}?", to.display()),
+                        ConfirmedAction::RenameFile { from, to },
+                    )
+                } else {
+                    self.run_confirmed_action(ConfirmedAction::RenameFile {
+                        from,
+                        to,
+                    })
+                }
+            }
+            PromptedAction::FilterSince => {
+                if let Err(e) = TimeRange::new(value, "") {
+                    self.toasts.error(e);
+                    return Ok(());
+                }
+                let until = self.context.time_range().until();
+                self.state.apply(Transition::Open(Overlay::Prompt(
+                    Prompt::new(
+                        format!("Until ({TIME_RANGE_EXAMPLES})"),
+                        until.unwrap_or_default().to_string(),
+                        PromptedAction::FilterUntil(value.to_string()),
+                    ),
+                )));
+                Ok(())
+            }
+            PromptedAction::FilterUntil(since) => {
+                match TimeRange::new(&since, value) {
+                    Ok(range) => self.filter_time_range(range),
+                    Err(e) => self.toasts.error(e),
+                }
+                Ok(())
+            }
+            PromptedAction::SwitchChannel(name) => {
+                match TelevisionChannel::with_parameter(
+                    &self.context,
+                    &name,
+                    value,
+                ) {
+                    Ok(channel) => self.switch_from_remote_control(channel),
+                    Err(e) => self.toasts.error(e),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Ask for the time range the lines of the current channel's command
+    /// are restricted to, its start then its end (empty for none).
+    fn prompt_time_range(&mut self) {
+        if !self.runs_command() {
+            return;
+        }
+        self.state
+            .apply(Transition::Open(Overlay::Prompt(Prompt::new(
+                format!("Since ({TIME_RANGE_EXAMPLES})"),
+                self.context
+                    .time_range()
+                    .since()
+                    .unwrap_or_default()
+                    .to_string(),
+                PromptedAction::FilterSince,
+            ))));
+    }
+
+    /// Restrict the lines of the commands of the journal, external and
+    /// cable channels to the given time range, running the current one
+    /// again.
+    fn filter_time_range(&mut self, range: TimeRange) {
+        if range.is_empty() {
+            self.toasts
+                .info("Listing the lines of any time".to_string());
+        } else {
+            self.toasts.info(format!("Listing the lines {range}"));
+        }
+        self.context.set_time_range(range);
+        self.reload_channel();
+    }
+
+    /// Ask for the parameter of the channel selected in the remote control
+    /// before switching to it, switching right away if it takes none.
+    fn prompt_channel_parameter(&mut self) {
+        let Some(entry) = self.get_selected_entry(Some(Mode::RemoteControl))
+        else {
+            return;
+        };
+        let Some(parameter) = channel_parameter(&entry.name) else {
+            match TelevisionChannel::from_entry(&self.context, &entry) {
+                Ok(channel) => self.switch_from_remote_control(channel),
+                Err(e) => self.toasts.error(e),
+            }
+            return;
+        };
+        let mut prompt = Prompt::new(
+            parameter.description.to_string(),
+            String::new(),
+            PromptedAction::SwitchChannel(entry.name),
+        );
+        if parameter.is_path {
+            prompt = prompt.with_completion(complete_path);
+        }
+        self.state.apply(Transition::Open(Overlay::Prompt(prompt)));
+    }
+
+    /// Leave the remote control for the given channel.
+    fn switch_from_remote_control(&mut self, channel: TelevisionChannel) {
+        // this resets the RC picker
+        self.reset_picker_selection();
+        self.reset_picker_input();
+        self.remote_control.find(EMPTY_STRING);
+        self.state.apply(Transition::Back);
+        self.change_channel(channel);
+    }
+
+    /// Switch to the channel the selected entry leads to, if any.
+    fn zap_selected_entry(&mut self) {
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return;
+        };
+        if let Some(channel) = self.channel.zap(&self.context, &entry) {
+            self.change_channel(channel);
+        }
+    }
+
+    /// Ask for the new path of the file selected in the files channel, and
+    /// rename it.
+    fn rename_selected_entry(&mut self) {
+        if !matches!(self.channel, TelevisionChannel::Files(_)) {
+            return;
+        }
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return;
+        };
+        self.state.apply(Transition::Open(Overlay::Prompt(
+            Prompt::new(
+                "Rename to".to_string(),
+                entry.name.clone(),
+                PromptedAction::RenameFile(PathBuf::from(&entry.name)),
+            )
+            .with_completion(complete_path),
+        )));
+    }
+
+    /// Delete the file selected in the files channel, after confirmation.
+    fn delete_selected_entry(&mut self) -> Result<()> {
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return Ok(());
+        };
+        match self.channel {
+            TelevisionChannel::Files(_) => {
+                let path = PathBuf::from(&entry.name);
+                self.confirm(
+                    format!("Delete {}?", path.display()),
+                    ConfirmedAction::DeleteFile(path),
+                )
+            }
+            #[cfg(feature = "processes")]
+            TelevisionChannel::Processes(_) => {
+                let Some(pid) = processes::entry_pid(&entry) else {
+                    return Ok(());
+                };
+                self.confirm(
+                    format!("Terminate {}?", entry.name),
+                    ConfirmedAction::TerminateProcess {
+                        pid,
+                        name: entry.name,
+                    },
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Open the note input for the selected entry, prefilled with its
+    /// current note, or save the note if the input is already open.
+    fn annotate_selected_entry(&mut self) -> Result<()> {
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return Ok(());
+        };
+        if self.annotation_input().is_some() {
+            if let Some(Overlay::Annotation(input)) =
+                self.state.close_overlay()
+            {
+                self.annotations.set(&entry, input.value())?;
+            }
+        } else {
+            self.state.apply(Transition::Open(Overlay::Annotation(
+                Input::new(
+                    self.annotations
+                        .get(&entry)
+                        .unwrap_or_default()
+                        .to_string(),
+                ),
+            )));
+        }
+        Ok(())
+    }
+
+    /// Add the entry under the cursor to the selection (or remove it from
+    /// it) and move to the next entry.
+    fn toggle_selected_entry_in_selection(&mut self) {
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return;
+        };
+        self.selection.toggle(&entry);
+        self.leave_tail_mode();
+        self.reset_preview_scroll();
+        self.select_next_entry(self.config.ui.wrap_navigation);
+    }
+
+    /// The entries to output: the selected ones if any, the one under the
+    /// cursor otherwise (as replaced by the plugin of a plugin channel).
+    pub fn get_output_entries(&mut self) -> Vec<Entry> {
+        let entries = if self.selection.is_empty() {
+            self.get_selected_entry(Some(Mode::Channel))
+                .into_iter()
+                .collect()
+        } else {
+            self.selection.entries().to_vec()
+        };
+        #[cfg(feature = "plugins")]
+        if let TelevisionChannel::Plugin(channel) = &self.channel {
+            return channel.on_select(entries);
+        }
+        entries
+    }
+
+    fn toggle_selected_entry_pinned(&mut self, pin: bool) -> Result<()> {
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return Ok(());
+        };
+        if pin {
+            self.pins.pin(&entry)?;
+        } else {
+            self.pins.unpin(&entry)?;
+        }
+        self.promoted_results_key = None;
+        self.update_promoted_results();
+        Ok(())
+    }
+
+    /// Whether the selection follows the newest entry as entries arrive,
+    /// either because the user turned tail mode on or because the channel
+    /// starts in tail mode (see `tail`).
+    pub(crate) fn tail_mode(&self) -> bool {
+        self.tail.unwrap_or_else(|| {
+            self.config
+                .channels
+                .get(&self.current_channel_name())
+                .is_some_and(|c| c.tail)
+        })
+    }
+
+    /// Turn tail mode off when the user moves the selection in the results.
+    fn leave_tail_mode(&mut self) {
+        if self.mode() == Mode::Channel && self.tail_mode() {
+            self.tail = Some(false);
+        }
+    }
+
+    /// Select the newest entry (i.e. the last one), if it isn't already.
+    pub(crate) fn select_newest_entry(&mut self) {
+        let result_count = self.channel.result_count() as usize;
+        if result_count == 0
+            || self.results_picker.selected() == Some(result_count - 1)
+        {
+            return;
+        }
+        self.results_picker
+            .select_index(result_count - 1, self.results_area_height as usize);
+        self.reset_preview_scroll();
+    }
+
+    /// The given entry, previewed by the preview command configured for the
+    /// current channel if any.
+    fn with_preview_command(&self, mut entry: Entry) -> Entry {
+        if let Some(command) = self
+            .config
+            .channels
+            .get(&self.current_channel_name())
+            .and_then(|c| c.preview_command.as_ref())
+        {
+            entry.preview_type = PreviewType::Command(PreviewCommand::new(
+                command.replace("{}", &shell_quote(&entry.name)),
+            ));
+        }
+        entry
+    }
+
+    /// Whether the recently selected entries are displayed first when the
+    /// pattern is empty, for the current channel.
+    fn recent_first(&self) -> bool {
+        self.config
+            .channels
+            .get(&self.current_channel_name())
+            .is_some_and(|c| c.recent_first)
+    }
+
+    /// Find the pinned and recent entries among the (top) results, unless
+    /// the results didn't change since the last time.
+    pub(crate) fn update_promoted_results(&mut self) {
+        let recent_first = self.current_pattern.is_empty()
+            && self.recent_first()
+            && !self.recent_entries.is_empty();
+        if self.pins.is_empty() && !recent_first {
+            self.promoted_results.clear();
+            return;
+        }
+        let key = (
+            self.current_pattern.clone(),
+            self.channel.result_count(),
+            self.channel.total_count(),
+        );
+        if self.promoted_results_key.as_ref() == Some(&key) {
+            return;
+        }
+        let scanned = key.1.min(PROMOTED_RESULTS_SCAN_LIMIT);
+        let mut pinned = Vec::new();
+        let mut recent = Vec::new();
+        for (i, entry) in (0..).zip(self.channel.results(scanned, 0)) {
+            if self.pins.contains(&entry) {
+                pinned.push(i);
+            } else if let Some(rank) = recent_first
+                .then(|| self.recent_entries.rank(&entry))
+                .flatten()
+            {
+                recent.push((rank, i));
+            }
+        }
+        recent.sort_unstable();
+        pinned.extend(recent.into_iter().map(|(_, i)| i));
+        self.promoted_results = pinned;
+        self.promoted_results_key = Some(key);
+    }
+
+    /// Select the entry selected as of the last frame again if the entries
+    /// were refreshed under it, i.e. if another entry took its place while
+    /// neither the selection nor the pattern changed, as long as it's still
+    /// among the top results.
+    pub(crate) fn keep_selected_entry(&mut self) {
+        let selected = self.results_picker.selected();
+        let entry = self.get_selected_entry(Some(Mode::Channel));
+        if let (Some((index, pattern, last)), Some(selected)) =
+            (self.last_selected_entry.take(), selected)
+        {
+            if index == selected
+                && pattern == self.current_pattern
+                && !self.channel.loading()
+                && !entry.as_ref().is_some_and(|e| same_entry(e, &last))
+            {
+                let scanned = self
+                    .channel
+                    .result_count()
+                    .min(PROMOTED_RESULTS_SCAN_LIMIT);
+                let found = (0..)
+                    .zip(self.channel.results(scanned, 0))
+                    .find(|(_, e)| same_entry(e, &last));
+                if let Some((i, _)) = found {
+                    self.results_picker.select_index(
+                        displayed_index(&self.promoted_results, i) as usize,
+                        self.results_area_height as usize,
+                    );
+                    self.last_selected_entry = Some((
+                        self.results_picker.selected().unwrap_or(0),
+                        pattern,
+                        last,
+                    ));
+                    return;
+                }
+            }
+        }
+        self.last_selected_entry = selected
+            .zip(entry)
+            .map(|(i, entry)| (i, self.current_pattern.clone(), entry));
+    }
+
+    fn channel_index(&self, index: u32) -> u32 {
+        channel_index(&self.promoted_results, index)
+    }
+
+    /// Whether pinned or recent entries are moved before the other results.
+    pub(crate) fn promotes_results(&self) -> bool {
+        !self.promoted_results.is_empty()
+    }
+
+    /// The results displayed from the given offset, pinned and recent
+    /// entries first.
+    pub(crate) fn displayed_results(
+        &mut self,
+        num_entries: u32,
+        offset: u32,
+    ) -> Vec<Entry> {
+        if self.promoted_results.is_empty() {
+            return self.channel.results(num_entries, offset);
+        }
+        let end = (offset + num_entries).min(self.channel.result_count());
+        let promoted_count =
+            u32::try_from(self.promoted_results.len()).unwrap();
+        let mut entries: Vec<Entry> = (offset..end.min(promoted_count))
+            .filter_map(|i| {
+                self.channel
+                    .results(1, self.promoted_results[i as usize])
+                    .pop()
+            })
+            .collect();
+        let first = offset.max(promoted_count);
+        if end > first {
+            let start = self.channel_index(first);
+            let results =
+                self.channel.results(end - first + promoted_count, start);
+            entries.extend(
+                (start..)
+                    .zip(results)
+                    .filter(|(i, _)| !self.promoted_results.contains(i))
+                    .map(|(_, entry)| entry)
+                    .take((end - first) as usize),
+            );
+        }
+        entries
+    }
+
+    /// Whether the picker is used as a pure menu, without an input prompt.
+    pub(crate) fn no_input(&self) -> bool {
+        self.config.ui.no_input
+            || self
+                .config
+                .ui
+                .no_input_channels
+                .contains(&self.current_channel_name())
+    }
+
+    /// Select the next entry (after the selected one, wrapping around) whose
+    /// name starts with the given character, ignoring case.
+    fn jump_to_entry_starting_with(&mut self, c: char) {
+        let count = self.channel.result_count();
+        let selected = self.results_picker.selected().unwrap_or(0);
+        let selected = u32::try_from(selected).unwrap_or_default();
+        let starts_with =
+            |entry: &Entry| {
+                entry.name.chars().next().is_some_and(|first| {
+                    first.to_lowercase().eq(c.to_lowercase())
+                })
+            };
+        // the results are only fetched until one matches
+        if let Some(index) =
+            (1..=count).map(|i| (selected + i) % count).find(|&i| {
+                self.channel
+                    .get_result(self.channel_index(i))
+                    .is_some_and(|entry| starts_with(&entry))
+            })
+        {
+            self.leave_tail_mode();
+            self.results_picker.select_index(
+                index as usize,
+                self.results_area_height as usize,
+            );
+            self.reset_preview_scroll();
+        }
+    }
+
+    /// The rest of the top result's name, when it starts with the current
+    /// pattern (ignoring case).
+    pub(crate) fn completion_hint(&self) -> Option<String> {
+        if !self.config.ui.completion_hints || self.current_pattern.is_empty()
+        {
+            return None;
+        }
+        let entry = self.channel.get_result(self.channel_index(0))?;
+        let prefix_len = self.current_pattern.len();
+        entry
+            .name
+            .get(..prefix_len)
+            .filter(|prefix| {
+                prefix.eq_ignore_ascii_case(&self.current_pattern)
+            })
+            .map(|_| entry.name[prefix_len..].to_string())
+            .filter(|rest| !rest.is_empty())
+    }
+
+    /// Refresh the current channel in the background if its refresh
+    /// interval has elapsed.
+    fn maybe_refresh_channel(&mut self) {
+        let Some(interval) = self
+            .config
+            .channels
+            .get(&self.current_channel_name())
+            .and_then(|c| c.refresh_interval)
+        else {
+            return;
+        };
+        if self.last_refresh.elapsed().as_secs_f64() >= interval {
+            self.channel.refresh();
+            self.last_refresh = Instant::now();
+        }
+    }
+
+    /// Start computing the checksums of the selected file (or copy them to
+    /// the clipboard again if they were already computed).
+    fn compute_selected_entry_checksum(&mut self) {
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return;
+        };
+        if entry.preview_type != PreviewType::Files
+            || !Path::new(&entry.name).is_file()
+        {
+            return;
+        }
+        let checksum = match &self.checksum {
+            Some(checksum) if checksum.path == Path::new(&entry.name) => {
+                checksum.clone()
+            }
+            _ => self
+                .checksum
+                .insert(Checksum::spawn(entry.name.into()))
+                .clone(),
+        };
+        tokio::spawn(report_checksum(
+            checksum,
+            self.config.ui.clipboard,
+            self.context.bus.clone(),
+        ));
+    }
+
+    /// Copy the selected entries to the clipboard, one per line, or the
+    /// entry under the cursor if none are selected.
+    fn copy_selected_entries(&mut self) {
+        let entries: Vec<Entry> = if self.selection.is_empty() {
+            self.get_selected_entry(None).into_iter().collect()
+        } else {
+            self.selection.entries().to_vec()
+        };
+        if entries.is_empty() {
+            return;
+        }
+        let text = entries
+            .iter()
+            .map(|entry| entry.output.as_deref().unwrap_or(&entry.name))
+            .collect::<Vec<_>>()
+            .join("\n");
+        match clipboard::copy(&text, self.config.ui.clipboard) {
+            Ok(()) if entries.len() == 1 => {
+                self.toasts.success("Copied to the clipboard".to_string());
+            }
+            Ok(()) => self.toasts.success(format!(
+                "Copied {} entries to the clipboard",
+                entries.len()
+            )),
+            Err(e) => self
+                .toasts
+                .error(format!("Failed to copy to the clipboard: {e}")),
+        }
+    }
+
+    /// Handle a message sent on the bus by a worker running in the
+    /// background.
+    pub fn handle_message(&mut self, message: Message) {
+        match message {
+            Message::Progress { key, text, percent } => {
+                self.toasts.progress(&key, text, percent);
+            }
+            Message::Finished { key, text, failed } => {
+                let level = if failed {
+                    ToastLevel::Error
+                } else {
+                    ToastLevel::Success
+                };
+                self.toasts.finish(&key, text, level);
+            }
+            // the preview is picked up from the previewer's cache when
+            // drawing
+            Message::PreviewReady(_) => {}
+        }
+    }
+
+    /// List the applications the selected file can be opened with, in the
+    /// remote control.
+    fn open_selected_entry_with(&mut self) {
+        if !self.state.can(&Transition::OpenWith) {
+            return;
+        }
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return;
+        };
+        if entry.preview_type != PreviewType::Files {
+            return;
+        }
+        let path = PathBuf::from(&entry.name);
+        let applications = applications_for(&path);
+        if applications.is_empty() {
+            debug!("No application to open {:?} with", path);
+            return;
+        }
+        self.remote_control = TelevisionChannel::RemoteControl(
+            RemoteControl::with_applications(
+                applications.iter().map(|app| app.name.clone()).collect(),
+            ),
+        );
+        self.open_with = Some((path, applications));
+        self.state.apply(Transition::OpenWith);
+    }
+
+    /// Open the selected file or directory (or the directory containing it)
+    /// with its default application.
+    fn open_selected_file(
+        &mut self,
+        containing_directory: bool,
+    ) -> Result<()> {
+        let Some(entry) = self.get_selected_entry(Some(Mode::Channel)) else {
+            return Ok(());
+        };
+        if !matches!(
+            entry.preview_type,
+            PreviewType::Files | PreviewType::Directory
+        ) {
+            return Ok(());
+        }
+        let mut path = PathBuf::from(&entry.name);
+        if containing_directory {
+            path = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+        }
+        open_default(&path, self.config.execution.backend.into())?;
+        Ok(())
+    }
+
+    /// Open the file with the selected application and leave open with
+    /// mode.
+    fn open_with_selected_application(&mut self, name: &str) -> Result<()> {
+        let Some((path, applications)) = self.open_with.take() else {
+            return Ok(());
+        };
+        if let Some(app) = applications.iter().find(|app| app.name == name) {
+            if app.terminal {
+                self.action_tx
+                    .as_ref()
+                    .unwrap()
+                    .send(Action::RunCommand(app.command(&path)))?;
+            } else {
+                app.launch(&path, self.config.execution.backend.into())?;
+            }
+        }
+        self.leave_remote_control_mode();
+        Ok(())
+    }
+
+    /// Run the action of a script on the entry selected in the channel, in
+    /// the background, and leave the remote control.
+    ///
+    /// The channel is reloaded once the action is done, since it likely
+    /// changed the entries (e.g. by deleting one), unless it returned a
+    /// command to run in the foreground.
+    #[cfg(feature = "scripting")]
+    fn run_script_action(&mut self, script: Script, action: String) {
+        let entry = self
+            .get_selected_entry(Some(Mode::Channel))
+            .map(|entry| entry.stdout_repr());
+        self.leave_remote_control_mode();
+        let (Some(entry), Some(action_tx)) = (entry, self.action_tx.clone())
+        else {
+            return;
+        };
+        let bus = self.context.bus.clone();
+        tokio::task::spawn_blocking(move || {
+            let next = match script.run_action(&action, &entry) {
+                Ok(Some(command)) => Action::RunCommand(command),
+                Ok(None) => Action::ReloadChannel,
+                Err(e) => {
+                    report_error(&bus, &e);
+                    return;
+                }
+            };
+            let _ = action_tx.send(next);
+        });
+    }
+
+    /// List the extensions of the files found by the files channel, with
+    /// how many files have them, in the remote control.
+    fn list_file_types(&mut self) {
+        if !self.state.can(&Transition::OpenFileTypes) {
+            return;
+        }
+        let TelevisionChannel::Files(files) = &self.channel else {
+            return;
+        };
+        let counts = files.extension_counts();
+        if counts.is_empty() {
+            return;
+        }
+        let mut labels: Vec<String> = counts
+            .iter()
+            .map(|(extension, count)| {
+                format!("{extension} ({})", format::count(*count as u64))
+            })
+            .collect();
+        if !self.context.file_types().is_empty() {
+            let total: usize = counts.iter().map(|(_, count)| count).sum();
+            labels.insert(
+                0,
+                format!("{ALL_FILE_TYPES} ({})", format::count(total as u64)),
+            );
+        }
+        self.remote_control = TelevisionChannel::RemoteControl(
+            RemoteControl::with_file_types(labels),
+        );
+        self.state.apply(Transition::OpenFileTypes);
+    }
+
+    /// Reload the files channel with only the files of the selected type
+    /// (or all of them), and leave file types mode.
+    fn list_selected_file_type(&mut self, label: &str) {
+        let name = label.rsplit_once(" (").map_or(label, |(name, _)| name);
+        if name == ALL_FILE_TYPES {
+            self.context.set_file_types(Vec::new());
+            self.toasts.info("Listing all files".to_string());
+        } else {
+            self.context.set_file_types(vec![name.to_string()]);
+            self.toasts.info(format!("Listing .{name} files"));
+        }
+        self.leave_remote_control_mode();
+        self.reload_channel();
+    }
+
+    /// Go back to channel mode from one of the modes using the remote
+    /// control, resetting its picker.
+    fn leave_remote_control_mode(&mut self) {
+        self.reset_picker_input();
+        self.remote_control.find(EMPTY_STRING);
+        self.reset_picker_selection();
+        self.state.apply(Transition::Back);
+    }
+
+    /// Export the matched entries as a quickfix list: write it to the
+    /// configured file and run the configured command on it, or print it
+    /// and exit.
+    fn export_quickfix(&mut self) -> Result<()> {
+        let entries = self.displayed_results(self.channel.result_count(), 0);
+        let list = quickfix_list(&entries);
+        if list.is_empty() {
+            return Ok(());
+        }
+        let action_tx = self.action_tx.as_ref().unwrap();
+        let path = match self.config.quickfix.path.as_deref() {
+            Some("-") => {
+                action_tx.send(Action::QuitWithOutput(
+                    list.trim_end().to_string(),
+                ))?;
+                return Ok(());
+            }
+            Some(path) => PathBuf::from(path),
+            None => get_data_dir().join(QUICKFIX_FILE_NAME),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, list)?;
+        debug!("Exported quickfix list to {:?}", path);
+        if let Some(command) = &self.config.quickfix.command {
+            let command =
+                command.replace("{}", &shell_quote(&path.to_string_lossy()));
+            action_tx.send(Action::RunCommand(command))?;
+        } else {
+            self.toasts.success(format!(
+                "Exported {} entries to {}",
+                entries.len(),
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the preview pane is shown, which is the case by default unless
+    /// the terminal is narrow or the preview is disabled for the channel.
+    ///
+    /// Previews are only computed while the pane is shown.
+    fn preview_shown(&self) -> bool {
+        self.show_preview.unwrap_or_else(|| {
+            !self.narrow
+                && !self
+                    .config
+                    .ui
+                    .no_preview_channels
+                    .contains(&self.current_channel_name())
+        })
     }
 
     fn find(&mut self, pattern: &str) {
-        match self.mode {
+        match self.mode() {
             Mode::Channel => {
                 self.channel.find(pattern);
             }
-            Mode::RemoteControl | Mode::SendToChannel => {
+            Mode::RemoteControl
+            | Mode::SendToChannel
+            | Mode::OpenWith
+            | Mode::FileTypes => {
                 self.remote_control.find(pattern);
             }
         }
@@ -107,24 +1357,29 @@ impl Television {
 
     #[must_use]
     pub fn get_selected_entry(&mut self, mode: Option<Mode>) -> Option<Entry> {
-        match mode.unwrap_or(self.mode) {
+        match mode.unwrap_or(self.mode()) {
             Mode::Channel => self.results_picker.selected().and_then(|i| {
-                self.channel.get_result(u32::try_from(i).unwrap())
+                self.channel
+                    .get_result(self.channel_index(u32::try_from(i).unwrap()))
+            }),
+            Mode::RemoteControl
+            | Mode::SendToChannel
+            | Mode::OpenWith
+            | Mode::FileTypes => self.rc_picker.selected().and_then(|i| {
+                self.remote_control.get_result(u32::try_from(i).unwrap())
             }),
-            Mode::RemoteControl | Mode::SendToChannel => {
-                self.rc_picker.selected().and_then(|i| {
-                    self.remote_control.get_result(u32::try_from(i).unwrap())
-                })
-            }
         }
     }
 
-    pub fn select_prev_entry(&mut self) {
-        let (result_count, picker) = match self.mode {
+    pub fn select_prev_entry(&mut self, wrap: bool) {
+        let (result_count, picker) = match self.mode() {
             Mode::Channel => {
                 (self.channel.result_count(), &mut self.results_picker)
             }
-            Mode::RemoteControl | Mode::SendToChannel => {
+            Mode::RemoteControl
+            | Mode::SendToChannel
+            | Mode::OpenWith
+            | Mode::FileTypes => {
                 (self.remote_control.total_count(), &mut self.rc_picker)
             }
         };
@@ -134,15 +1389,19 @@ impl Television {
         picker.select_prev(
             result_count as usize,
             self.results_area_height as usize,
+            wrap,
         );
     }
 
-    pub fn select_next_entry(&mut self) {
-        let (result_count, picker) = match self.mode {
+    pub fn select_next_entry(&mut self, wrap: bool) {
+        let (result_count, picker) = match self.mode() {
             Mode::Channel => {
                 (self.channel.result_count(), &mut self.results_picker)
             }
-            Mode::RemoteControl | Mode::SendToChannel => {
+            Mode::RemoteControl
+            | Mode::SendToChannel
+            | Mode::OpenWith
+            | Mode::FileTypes => {
                 (self.remote_control.total_count(), &mut self.rc_picker)
             }
         };
@@ -152,6 +1411,7 @@ impl Television {
         picker.select_next(
             result_count as usize,
             self.results_area_height as usize,
+            wrap,
         );
     }
 
@@ -160,23 +1420,32 @@ impl Television {
     }
 
     fn reset_picker_selection(&mut self) {
-        match self.mode {
+        match self.mode() {
             Mode::Channel => self.results_picker.reset_selection(),
-            Mode::RemoteControl | Mode::SendToChannel => {
-                self.rc_picker.reset_selection()
-            }
+            Mode::RemoteControl
+            | Mode::SendToChannel
+            | Mode::OpenWith
+            | Mode::FileTypes => self.rc_picker.reset_selection(),
         }
     }
 
     fn reset_picker_input(&mut self) {
-        match self.mode {
+        match self.mode() {
             Mode::Channel => self.results_picker.reset_input(),
-            Mode::RemoteControl | Mode::SendToChannel => {
-                self.rc_picker.reset_input()
-            }
+            Mode::RemoteControl
+            | Mode::SendToChannel
+            | Mode::OpenWith
+            | Mode::FileTypes => self.rc_picker.reset_input(),
         }
     }
 
+    /// How many lines of the preview are displayed at once (without the
+    /// borders of the preview pane), at least 2 so that half pages are
+    /// never empty.
+    fn preview_page_height(&self) -> u16 {
+        self.preview_pane_height.saturating_sub(2).max(2)
+    }
+
     pub fn scroll_preview_down(&mut self, offset: u16) {
         if self.preview_scroll.is_none() {
             self.preview_scroll = Some(0);
@@ -189,19 +1458,38 @@ impl Television {
                 ),
             );
         }
+        self.remember_preview_scroll();
     }
 
     pub fn scroll_preview_up(&mut self, offset: u16) {
         if let Some(scroll) = self.preview_scroll {
             self.preview_scroll = Some(scroll.saturating_sub(offset));
         }
+        self.remember_preview_scroll();
     }
-}
 
-// Styles
-//  input
-pub(crate) const DEFAULT_INPUT_FG: Color = Color::LightRed;
-pub(crate) const DEFAULT_RESULTS_COUNT_FG: Color = Color::LightRed;
+    /// The key under which the preview scroll position of the given entry
+    /// is remembered.
+    fn scroll_memory_key(&self, entry: &Entry) -> String {
+        format!(
+            "{}:{}:{}",
+            self.current_channel_name(),
+            entry.name,
+            entry.line_number.unwrap_or_default()
+        )
+    }
+
+    fn remember_preview_scroll(&mut self) {
+        let (Some(scroll), Some(entry)) = (
+            self.preview_scroll,
+            self.get_selected_entry(Some(Mode::Channel)),
+        ) else {
+            return;
+        };
+        let key = self.scroll_memory_key(&entry);
+        self.scroll_memory.set(&key, scroll);
+    }
+}
 
 impl Television {
     /// Register an action handler that can send actions for processing if necessary.
@@ -228,6 +1516,14 @@ impl Television {
     /// * `Result<()>` - An Ok result or an error.
     pub fn register_config_handler(&mut self, config: Config) -> Result<()> {
         self.config = config;
+        // the theme was checked on startup
+        self.theme =
+            Theme::from_config(&self.config.ui.theme, &self.config.themes)
+                .unwrap_or_else(|e| {
+                    warn!("{}", e);
+                    Theme::default()
+                });
+        self.graphics_protocol = self.config.previewers.image.protocol();
         let previewer_config =
             std::convert::Into::<previewers::PreviewerConfig>::into(
                 self.config.previewers.clone(),
@@ -236,6 +1532,80 @@ impl Television {
         Ok(())
     }
 
+    /// Remove the images written to the terminal, drawing them again on the
+    /// next frame if they're still displayed (e.g. once the screen was
+    /// cleared).
+    pub fn reset_graphics(&mut self) {
+        if self.graphics.is_shown() {
+            self.graphics.clear();
+            self.preview_pane = PaneCache::default();
+        }
+    }
+
+    /// The escape sequences drawing the images of the last frame, to write
+    /// to the terminal once the frame is drawn.
+    pub fn take_graphics(&mut self) -> String {
+        self.graphics.take_pending()
+    }
+
+    /// Let the popup opened over the current mode, if any, handle the
+    /// given action.
+    ///
+    /// # Returns
+    /// * `Result<bool>` - Whether the popup handled the action.
+    fn update_overlay(&mut self, action: &Action) -> Result<bool> {
+        let is_input_action = matches!(
+            action,
+            Action::AddInputChar(_)
+                | Action::DeletePrevChar
+                | Action::DeleteNextChar
+                | Action::GoToInputEnd
+                | Action::GoToInputStart
+                | Action::GoToNextChar
+                | Action::GoToPrevChar
+        );
+        match (self.state.overlay_mut(), action) {
+            (
+                Some(Overlay::Confirmation(_)),
+                Action::AnswerConfirmation(yes),
+            ) => {
+                if let Some(Overlay::Confirmation(confirmation)) =
+                    self.state.close_overlay()
+                {
+                    if *yes {
+                        self.run_confirmed_action(confirmation.action)?;
+                    }
+                }
+            }
+            // the input prompt and the note input capture the input actions
+            (Some(Overlay::Prompt(prompt)), _) if is_input_action => {
+                prompt.handle_action(action);
+            }
+            (Some(Overlay::Annotation(input)), _) if is_input_action => {
+                input.handle_action(action);
+            }
+            (Some(Overlay::Prompt(prompt)), Action::CompletePrompt) => {
+                prompt.complete();
+            }
+            (Some(Overlay::Prompt(_)), Action::SubmitPrompt) => {
+                if let Some(Overlay::Prompt(prompt)) =
+                    self.state.close_overlay()
+                {
+                    self.run_prompted_action(
+                        prompt.action,
+                        prompt.input.value(),
+                    )?;
+                }
+            }
+            (Some(Overlay::Prompt(_)), Action::CancelPrompt)
+            | (Some(Overlay::Annotation(_)), Action::CancelAnnotation) => {
+                self.state.close_overlay();
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
     /// Update the state of the component based on a received action.
     ///
     /// # Arguments
@@ -244,7 +1614,43 @@ impl Television {
     /// # Returns
     /// * `Result<Option<Action>>` - An action to be processed or none.
     pub async fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        if self.update_overlay(&action)? {
+            return Ok(None);
+        }
         match action {
+            // in menu mode, typed characters jump to entries instead
+            Action::AddInputChar(c)
+                if self.mode() == Mode::Channel && self.no_input() =>
+            {
+                self.jump_to_entry_starting_with(c);
+            }
+            Action::DeletePrevChar
+            | Action::DeleteNextChar
+            | Action::GoToInputEnd
+            | Action::GoToInputStart
+            | Action::GoToNextChar
+            | Action::GoToPrevChar
+                if self.mode() == Mode::Channel && self.no_input() => {}
+            // accept the completion hint when the cursor is at the end
+            Action::GoToNextChar
+                if self.mode() == Mode::Channel
+                    && self.results_picker.input.cursor()
+                        == self
+                            .results_picker
+                            .input
+                            .value()
+                            .chars()
+                            .count()
+                    && self.completion_hint().is_some() =>
+            {
+                let completion = self.current_pattern.clone()
+                    + self.completion_hint().unwrap_or_default().as_str();
+                self.results_picker.input = Input::new(completion.clone());
+                self.find(&completion);
+                self.current_pattern = completion;
+                self.reset_picker_selection();
+                self.reset_preview_scroll();
+            }
             // handle input actions
             Action::AddInputChar(_)
             | Action::DeletePrevChar
@@ -253,11 +1659,12 @@ impl Television {
             | Action::GoToInputStart
             | Action::GoToNextChar
             | Action::GoToPrevChar => {
-                let input = match self.mode {
+                let input = match self.mode() {
                     Mode::Channel => &mut self.results_picker.input,
-                    Mode::RemoteControl | Mode::SendToChannel => {
-                        &mut self.rc_picker.input
-                    }
+                    Mode::RemoteControl
+                    | Mode::SendToChannel
+                    | Mode::OpenWith
+                    | Mode::FileTypes => &mut self.rc_picker.input,
                 };
                 input.handle_action(&action);
                 match action {
@@ -276,87 +1683,239 @@ impl Television {
                 }
             }
             Action::SelectNextEntry => {
+                self.leave_tail_mode();
                 self.reset_preview_scroll();
-                self.select_next_entry();
+                self.select_next_entry(self.config.ui.wrap_navigation);
             }
             Action::SelectPrevEntry => {
+                self.leave_tail_mode();
+                self.reset_preview_scroll();
+                self.select_prev_entry(self.config.ui.wrap_navigation);
+            }
+            Action::SelectNextEntryWrap | Action::SelectNextEntryNoWrap => {
+                self.leave_tail_mode();
                 self.reset_preview_scroll();
-                self.select_prev_entry();
+                self.select_next_entry(action == Action::SelectNextEntryWrap);
+            }
+            Action::SelectPrevEntryWrap | Action::SelectPrevEntryNoWrap => {
+                self.leave_tail_mode();
+                self.reset_preview_scroll();
+                self.select_prev_entry(action == Action::SelectPrevEntryWrap);
             }
             Action::ScrollPreviewDown => self.scroll_preview_down(1),
             Action::ScrollPreviewUp => self.scroll_preview_up(1),
-            Action::ScrollPreviewHalfPageDown => self.scroll_preview_down(20),
-            Action::ScrollPreviewHalfPageUp => self.scroll_preview_up(20),
-            Action::ToggleRemoteControl => match self.mode {
-                Mode::Channel => {
-                    self.remote_control = TelevisionChannel::RemoteControl(
-                        RemoteControl::default(),
-                    );
-                    self.mode = Mode::RemoteControl;
-                }
-                Mode::RemoteControl => {
-                    // this resets the RC picker
-                    self.reset_picker_input();
-                    self.remote_control.find(EMPTY_STRING);
+            Action::ScrollPreviewHalfPageDown => {
+                self.scroll_preview_down(self.preview_page_height() / 2);
+            }
+            Action::ScrollPreviewHalfPageUp => {
+                self.scroll_preview_up(self.preview_page_height() / 2);
+            }
+            Action::ScrollPreviewPageDown => {
+                self.scroll_preview_down(self.preview_page_height());
+            }
+            Action::ScrollPreviewPageUp => {
+                self.scroll_preview_up(self.preview_page_height());
+            }
+            Action::Tick => {
+                self.maybe_refresh_channel();
+                self.toasts.tick(Instant::now());
+            }
+            Action::TogglePreview => {
+                self.show_preview = Some(!self.preview_shown());
+            }
+            Action::ExportQuickfix if self.mode() == Mode::Channel => {
+                self.export_quickfix()?;
+            }
+            Action::ComputeChecksum if self.mode() == Mode::Channel => {
+                self.compute_selected_entry_checksum();
+            }
+            Action::DeleteEntry if self.mode() == Mode::Channel => {
+                self.delete_selected_entry()?;
+            }
+            Action::RenameEntry if self.mode() == Mode::Channel => {
+                self.rename_selected_entry();
+            }
+            Action::CycleSearchScope if self.mode() == Mode::Channel => {
+                self.cycle_search_scope();
+            }
+            Action::ZapEntry if self.mode() == Mode::Channel => {
+                self.zap_selected_entry();
+            }
+            Action::ToggleChangedWithin if self.mode() == Mode::Channel => {
+                self.toggle_changed_within();
+            }
+            Action::ToggleRawValues if self.mode() == Mode::Channel => {
+                format::set_raw(!format::is_raw());
+                // the cached previews were formatted the other way
+                self.previewer
+                    .set_config(self.config.previewers.clone().into());
+                self.preview_pane = PaneCache::default();
+            }
+            Action::SetInput(ref pattern) if self.mode() == Mode::Channel => {
+                self.results_picker.input = Input::new(pattern.clone());
+                if *pattern != self.current_pattern {
+                    self.current_pattern.clone_from(pattern);
+                    self.find(pattern);
                     self.reset_picker_selection();
-                    self.mode = Mode::Channel;
+                    self.reset_preview_scroll();
+                }
+            }
+            Action::SelectEntryAt(index)
+                if self.mode() == Mode::Channel
+                    && index < self.channel.result_count() as usize =>
+            {
+                self.leave_tail_mode();
+                self.results_picker
+                    .select_index(index, self.results_area_height as usize);
+                self.reset_preview_scroll();
+            }
+            Action::ReloadChannel if self.mode() == Mode::Channel => {
+                self.pushed_previews.clear();
+                self.channel.refresh();
+                self.last_refresh = Instant::now();
+            }
+            Action::SetPreview { entry, content } => {
+                let preview = Preview::from_text(entry.clone(), &content);
+                self.pushed_previews.insert(entry, Arc::new(preview));
+            }
+            Action::ToggleTailMode if self.mode() == Mode::Channel => {
+                self.tail = Some(!self.tail_mode());
+            }
+            Action::ToggleRemoteControl => match self.mode() {
+                Mode::Channel => {
+                    if self.state.apply(Transition::OpenRemoteControl) {
+                        self.remote_control = TelevisionChannel::RemoteControl(
+                            RemoteControl::from_context(&self.context),
+                        );
+                    }
                 }
-                Mode::SendToChannel => {}
+                Mode::RemoteControl => self.leave_remote_control_mode(),
+                Mode::SendToChannel | Mode::OpenWith | Mode::FileTypes => {}
             },
             Action::SelectEntry => {
                 if let Some(entry) = self.get_selected_entry(None) {
-                    match self.mode {
-                        Mode::Channel => self
-                            .action_tx
-                            .as_ref()
-                            .unwrap()
-                            .send(Action::SelectAndExit)?,
+                    match self.mode() {
+                        Mode::Channel => {
+                            if self.recent_first() {
+                                self.recent_entries.record(&entry)?;
+                            }
+                            self.action_tx
+                                .as_ref()
+                                .unwrap()
+                                .send(Action::SelectAndExit)?;
+                        }
                         Mode::RemoteControl => {
-                            if let Ok(new_channel) =
-                                // FIXME: this is kind of shitty
-                                TelevisionChannel::try_from(&entry)
+                            #[cfg(feature = "scripting")]
+                            if let Some((script, action)) =
+                                self.context.script_action(&entry.name)
                             {
-                                // this resets the RC picker
-                                self.reset_picker_selection();
-                                self.reset_picker_input();
-                                self.remote_control.find(EMPTY_STRING);
-                                self.mode = Mode::Channel;
-                                self.change_channel(new_channel);
+                                self.run_script_action(script, action);
+                                return Ok(None);
+                            }
+                            // compiled out channels are listed too, and
+                            // tell which feature they require
+                            match TelevisionChannel::from_entry(
+                                &self.context,
+                                &entry,
+                            ) {
+                                Ok(new_channel) => self
+                                    .switch_from_remote_control(new_channel),
+                                Err(e) => self.toasts.error(e),
                             }
                         }
+                        Mode::OpenWith => {
+                            self.open_with_selected_application(&entry.name)?;
+                        }
+                        Mode::FileTypes => {
+                            self.list_selected_file_type(&entry.name);
+                        }
                         Mode::SendToChannel => {
-                            let new_channel = self
-                                .channel
-                                .transition_to(entry.name.as_str().into());
-                            self.reset_picker_selection();
-                            self.reset_picker_input();
-                            self.remote_control.find(EMPTY_STRING);
-                            self.mode = Mode::Channel;
+                            let new_channel = self.channel.transition_to(
+                                &self.context,
+                                entry.name.as_str().into(),
+                            );
+                            self.leave_remote_control_mode();
                             self.change_channel(new_channel);
                         }
                     }
                 }
             }
-            Action::CopyEntryToClipboard => {
-                if self.mode == Mode::Channel {
-                    if let Some(entry) = self.get_selected_entry(None) {
-                        let mut ctx = ClipboardContext::new().unwrap();
-                        ctx.set_contents(entry.name).unwrap();
-                    }
+            Action::SelectEntryWithParameter
+                if self.mode() == Mode::RemoteControl =>
+            {
+                self.prompt_channel_parameter();
+            }
+            Action::CopyEntryToClipboard if self.mode() == Mode::Channel => {
+                self.copy_selected_entries();
+            }
+            Action::OpenEntry if self.mode() == Mode::Channel => {
+                self.open_selected_file(false)?;
+            }
+            Action::OpenContainingDirectory
+                if self.mode() == Mode::Channel =>
+            {
+                self.open_selected_file(true)?;
+            }
+            Action::EditEntry if self.mode() == Mode::Channel => {
+                if let Some(command) = self
+                    .get_selected_entry(None)
+                    .and_then(|entry| edit_command(&entry, &editor()))
+                {
+                    self.action_tx
+                        .as_ref()
+                        .unwrap()
+                        .send(Action::RunCommand(command))?;
                 }
             }
-            Action::ToggleSendToChannel => match self.mode {
+            Action::Undo if self.mode() == Mode::Channel => self.undo(),
+            Action::ToggleSelection
+                if self.mode() == Mode::Channel
+                    && self.config.ui.multi_select =>
+            {
+                self.toggle_selected_entry_in_selection();
+            }
+            Action::HideEntry if self.mode() == Mode::Channel => {
+                self.toggle_selected_entry_hidden()?;
+            }
+            Action::AnnotateEntry if self.mode() == Mode::Channel => {
+                self.annotate_selected_entry()?;
+            }
+            Action::FilterTimeRange if self.mode() == Mode::Channel => {
+                self.prompt_time_range();
+            }
+            Action::PinEntry | Action::UnpinEntry
+                if self.mode() == Mode::Channel =>
+            {
+                self.toggle_selected_entry_pinned(action == Action::PinEntry)?;
+            }
+            Action::ToggleSendToChannel => match self.mode() {
                 Mode::Channel | Mode::RemoteControl => {
-                    self.mode = Mode::SendToChannel;
-                    self.remote_control = TelevisionChannel::RemoteControl(
-                        RemoteControl::with_transitions_from(&self.channel),
-                    );
+                    if self.state.apply(Transition::OpenSendToChannel) {
+                        self.remote_control = TelevisionChannel::RemoteControl(
+                            RemoteControl::with_transitions_from(
+                                &self.context,
+                                &self.channel,
+                            ),
+                        );
+                    }
                 }
-                Mode::SendToChannel => {
-                    self.reset_picker_input();
-                    self.remote_control.find(EMPTY_STRING);
-                    self.reset_picker_selection();
-                    self.mode = Mode::Channel;
+                Mode::SendToChannel => self.leave_remote_control_mode(),
+                Mode::OpenWith | Mode::FileTypes => {}
+            },
+            Action::ToggleOpenWith => match self.mode() {
+                Mode::Channel => self.open_selected_entry_with(),
+                Mode::OpenWith => {
+                    self.open_with = None;
+                    self.leave_remote_control_mode();
+                }
+                Mode::RemoteControl
+                | Mode::SendToChannel
+                | Mode::FileTypes => {}
+            },
+            Action::ToggleFileTypes => match self.mode() {
+                Mode::Channel => self.list_file_types(),
+                Mode::FileTypes => self.leave_remote_control_mode(),
+                Mode::RemoteControl | Mode::SendToChannel | Mode::OpenWith => {
                 }
             },
             _ => {}
@@ -373,10 +1932,19 @@ impl Television {
     /// # Returns
     /// * `Result<()>` - An Ok result or an error.
     pub fn draw(&mut self, f: &mut Frame, area: Rect) -> Result<()> {
+        self.narrow = area.width < self.config.ui.narrow_width;
+        let show_preview = self.preview_shown();
+        let secondary_preview_config = self.secondary_preview_config();
         let layout = Layout::build(
             &Dimensions::from(self.config.ui.ui_scale),
             area,
-            !matches!(self.mode, Mode::Channel),
+            LayoutOptions {
+                input: !self.no_input() || self.annotation_input().is_some(),
+                remote_control: self.mode() != Mode::Channel,
+                narrow: self.narrow,
+                preview: show_preview,
+            },
+            secondary_preview_config.as_ref().map(|c| c.height),
         );
 
         // help bar (metadata, keymaps, logo)
@@ -389,29 +1957,234 @@ impl Television {
         self.draw_results_list(f, &layout)?;
 
         // bottom left block: input
-        self.draw_input_box(f, &layout)?;
+        if !self.no_input() || self.annotation_input().is_some() {
+            self.draw_input_box(f, &layout)?;
+        }
 
-        let selected_entry = self
-            .get_selected_entry(Some(Mode::Channel))
-            .unwrap_or(ENTRY_PLACEHOLDER);
-        let preview = block_on(self.previewer.preview(&selected_entry));
+        if show_preview {
+            let selected_entry = self
+                .get_selected_entry(Some(Mode::Channel))
+                .map_or(ENTRY_PLACEHOLDER, |entry| {
+                    self.with_preview_command(entry)
+                });
+            if self.preview_scroll.is_none() {
+                let key = self.scroll_memory_key(&selected_entry);
+                self.preview_scroll = self.scroll_memory.get(&key);
+            }
+            let preview = match self
+                .pushed_previews
+                .get(&selected_entry.stdout_repr())
+            {
+                Some(preview) => preview.clone(),
+                None => block_on(self.previewer.preview(&selected_entry)),
+            };
 
-        // top right block: preview title
-        self.current_preview_total_lines = preview.total_lines();
-        self.draw_preview_title_block(f, &layout, &selected_entry, &preview)?;
+            // top right block: preview title
+            self.current_preview_total_lines = preview.total_lines();
+            self.draw_preview_title_block(
+                f,
+                &layout,
+                &selected_entry,
+                &preview,
+            )?;
 
-        // bottom right block: preview content
-        self.draw_preview_content_block(
-            f,
-            &layout,
-            &selected_entry,
-            &preview,
-        )?;
+            // bottom right block: preview content
+            self.draw_preview_content_block(
+                f,
+                &layout,
+                &selected_entry,
+                &preview,
+            )?;
+
+            // bottom right block (optional): secondary preview
+            if let (Some(config), Some(area)) =
+                (secondary_preview_config, layout.secondary_preview_window)
+            {
+                self.draw_secondary_preview_block(
+                    f,
+                    area,
+                    &selected_entry,
+                    &config,
+                );
+            }
+        } else {
+            self.reset_graphics();
+        }
 
         // remote control
-        if matches!(self.mode, Mode::RemoteControl | Mode::SendToChannel) {
-            self.draw_remote_control(f, &layout.remote_control.unwrap())?;
+        if let Some(area) = layout.remote_control {
+            if self.narrow {
+                // the remote control covers the channel on narrow terminals
+                f.render_widget(Clear, area);
+            }
+            self.draw_remote_control(f, &area)?;
+        }
+
+        self.toasts.draw(f, area);
+        match self.state.overlay() {
+            Some(Overlay::Prompt(prompt)) => prompt.draw(f, area),
+            Some(Overlay::Confirmation(confirmation)) => {
+                confirmation.draw(f, area);
+            }
+            // the note input is drawn in the input box
+            Some(Overlay::Annotation(_)) | None => {}
         }
         Ok(())
     }
 }
+
+/// Report the progress of the checksums on the given bus until they are
+/// computed, then copy the sha256 one to the clipboard.
+async fn report_checksum(
+    checksum: Checksum,
+    backend: ClipboardConfig,
+    bus: Bus,
+) {
+    let name = checksum.path.display().to_string();
+    let message = loop {
+        match checksum.status() {
+            ChecksumStatus::Running(percent) => {
+                bus.send(Message::Progress {
+                    key: CHECKSUM_TOAST.to_string(),
+                    text: format!("Computing the checksums of {name}"),
+                    percent,
+                });
+                tokio::time::sleep(CHECKSUM_REPORT_INTERVAL).await;
+            }
+            ChecksumStatus::Done(checksums) => {
+                break match clipboard::copy(&checksums.sha256, backend) {
+                    Ok(()) => Message::Finished {
+                        key: CHECKSUM_TOAST.to_string(),
+                        text: format!("Copied the sha256 of {name}"),
+                        failed: false,
+                    },
+                    Err(e) => Message::Finished {
+                        key: CHECKSUM_TOAST.to_string(),
+                        text: format!(
+                            "Failed to copy the sha256 of {name}: {e}"
+                        ),
+                        failed: true,
+                    },
+                };
+            }
+            ChecksumStatus::Failed(e) => {
+                break Message::Finished {
+                    key: CHECKSUM_TOAST.to_string(),
+                    text: format!(
+                        "Failed to compute the checksums of {name}: {e}"
+                    ),
+                    failed: true,
+                };
+            }
+        }
+    };
+    bus.send(message);
+}
+
+/// The shell command editing the given entry: the one of its channel, or
+/// the given editor for files, opened at the entry's line if it has one
+/// (e.g. `vim +12 src/main.rs`).
+fn edit_command(entry: &Entry, editor: &str) -> Option<String> {
+    if let Some(command) = &entry.edit_command {
+        return Some(command.clone());
+    }
+    if entry.preview_type != PreviewType::Files {
+        return None;
+    }
+    let path = shell_quote(&entry.name);
+    Some(match entry.line_number {
+        Some(line) => format!("{editor} +{line} {path}"),
+        None => format!("{editor} {path}"),
+    })
+}
+
+/// Move a file back to where it was, unless another file took its place.
+fn restore_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if to.exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display()),
+        ));
+    }
+    std::fs::rename(from, to)
+}
+
+/// The index among the channel's results of the entry displayed at the given
+/// index, the promoted results being displayed first.
+fn channel_index(promoted_results: &[u32], index: u32) -> u32 {
+    if let Some(&i) = promoted_results.get(index as usize) {
+        return i;
+    }
+    let mut promoted = promoted_results.to_vec();
+    promoted.sort_unstable();
+    let mut i = index - u32::try_from(promoted.len()).unwrap();
+    for promoted in promoted {
+        if promoted <= i {
+            i += 1;
+        }
+    }
+    i
+}
+
+/// The index at which the given result is displayed, the inverse of
+/// `channel_index`.
+fn displayed_index(promoted_results: &[u32], index: u32) -> u32 {
+    if let Some(i) = promoted_results.iter().position(|&i| i == index) {
+        return u32::try_from(i).unwrap();
+    }
+    let promoted_after = promoted_results.iter().filter(|&&i| i > index);
+    index + u32::try_from(promoted_after.count()).unwrap()
+}
+
+/// Whether both entries are the same, regardless of their matched
+/// characters.
+fn same_entry(a: &Entry, b: &Entry) -> bool {
+    a.name == b.name && a.line_number == b.line_number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_index() {
+        // results 0..6 displayed as 4, 2, 0, 1, 3, 5
+        let promoted = [4, 2];
+        let displayed: Vec<u32> =
+            (0..6).map(|i| channel_index(&promoted, i)).collect();
+        assert_eq!(displayed, vec![4, 2, 0, 1, 3, 5]);
+        assert_eq!(channel_index(&[], 3), 3);
+    }
+
+    #[test]
+    fn test_displayed_index() {
+        let promoted = [4, 2];
+        for displayed in 0..6 {
+            let index = channel_index(&promoted, displayed);
+            assert_eq!(displayed_index(&promoted, index), displayed);
+        }
+        assert_eq!(displayed_index(&[], 3), 3);
+    }
+
+    #[test]
+    fn test_edit_command() {
+        let file = Entry::new("src/main.rs".to_string(), PreviewType::Files);
+        assert_eq!(
+            edit_command(&file, "nvim"),
+            Some("nvim 'src/main.rs'".to_string())
+        );
+        assert_eq!(
+            edit_command(&file.clone().with_line_number(12), "nvim"),
+            Some("nvim +12 'src/main.rs'".to_string())
+        );
+        let branch = Entry::new("main".to_string(), PreviewType::Basic);
+        assert_eq!(edit_command(&branch, "nvim"), None);
+        assert_eq!(
+            edit_command(
+                &branch.with_edit_command("git switch main".to_string()),
+                "nvim"
+            ),
+            Some("git switch main".to_string())
+        );
+    }
+}
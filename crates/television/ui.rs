@@ -1,15 +1,18 @@
-use ratatui::style::Color;
-
+pub mod ansi;
+pub mod confirm;
 pub(crate) mod help;
+pub mod image;
 pub mod input;
 pub mod keymap;
 pub mod layout;
 pub mod logo;
 pub mod metadata;
 mod mode;
+pub mod pane;
 pub mod preview;
+pub mod prompt;
 mod remote_control;
 pub mod results;
 pub mod spinner;
-
-pub const BORDER_COLOR: Color = Color::Blue;
+pub mod theme;
+pub mod toast;
@@ -0,0 +1,142 @@
+use std::ops::Range;
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+use television_utils::ansi::{AnsiColor, AnsiStyle};
+use television_utils::indices::byte_ranges;
+use television_utils::strings::next_char_boundary;
+
+/// The given style with the colors and attributes of the given ANSI style
+/// on top.
+pub fn patch_style(style: Style, ansi: &AnsiStyle) -> Style {
+    let color = |color: AnsiColor| match color {
+        AnsiColor::Indexed(index) => Color::Indexed(index),
+        AnsiColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    };
+    let mut style = style;
+    if let Some(fg) = ansi.fg {
+        style = style.fg(color(fg));
+    }
+    if let Some(bg) = ansi.bg {
+        style = style.bg(color(bg));
+    }
+    for (set, modifier) in [
+        (ansi.bold, Modifier::BOLD),
+        (ansi.dim, Modifier::DIM),
+        (ansi.italic, Modifier::ITALIC),
+        (ansi.underlined, Modifier::UNDERLINED),
+        (ansi.reversed, Modifier::REVERSED),
+        (ansi.crossed_out, Modifier::CROSSED_OUT),
+    ] {
+        if set {
+            style = style.add_modifier(modifier);
+        }
+    }
+    style
+}
+
+/// The spans of the given text in the given style, its parts in the given
+/// byte ranges being styled by their ANSI style, and the characters in the
+/// given ranges (as in `Entry::name_match_ranges`) by the match style.
+pub fn ansi_spans(
+    text: &str,
+    styles: &[(Range<usize>, AnsiStyle)],
+    match_ranges: &[(u32, u32)],
+    style: Style,
+    match_style: Style,
+) -> Vec<Span<'static>> {
+    let match_ranges = byte_ranges(text, match_ranges);
+    let mut boundaries: Vec<usize> = styles
+        .iter()
+        .map(|(range, _)| range)
+        .chain(&match_ranges)
+        .flat_map(|range| [range.start, range.end])
+        .chain([0, text.len()])
+        .map(|i| next_char_boundary(text, i.min(text.len())))
+        .collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+    boundaries
+        .windows(2)
+        .map(|bounds| {
+            let (start, end) = (bounds[0], bounds[1]);
+            let mut span_style = style;
+            if let Some((_, ansi)) =
+                styles.iter().find(|(range, _)| range.contains(&start))
+            {
+                span_style = patch_style(span_style, ansi);
+            }
+            if match_ranges.iter().any(|range| range.contains(&start)) {
+                span_style = span_style.patch(match_style);
+            }
+            Span::styled(text[start..end].to_string(), span_style)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_ansi_spans() {
+        let red = AnsiStyle {
+            fg: Some(AnsiColor::Indexed(1)),
+            bold: true,
+            ..AnsiStyle::default()
+        };
+        let style = Style::default().fg(Color::Blue);
+        let match_style = Style::default().fg(Color::Yellow);
+        let spans = ansi_spans(
+            "error: oops",
+            &[(0..5, red)],
+            &[(3, 8)],
+            style,
+            match_style,
+        );
+        assert_eq!(
+            spans,
+            vec![
+                Span::styled(
+                    "err",
+                    style.fg(Color::Indexed(1)).add_modifier(Modifier::BOLD)
+                ),
+                Span::styled("or", match_style.add_modifier(Modifier::BOLD)),
+                Span::styled(": o", match_style),
+                Span::styled("ops", style),
+            ]
+        );
+        assert!(ansi_spans("", &[], &[], style, match_style).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn test_ansi_spans_cover_text(
+            text in "\\PC{0,16}",
+            styled in proptest::collection::vec(0usize..40, 0..6),
+            matched in proptest::collection::vec((0u32..20, 0u32..20), 0..6),
+        ) {
+            // arbitrary byte offsets, possibly in the middle of characters
+            let styles: Vec<(Range<usize>, AnsiStyle)> = styled
+                .chunks_exact(2)
+                .map(|bounds| {
+                    (bounds[0]..bounds[1], AnsiStyle {
+                        bold: true,
+                        ..AnsiStyle::default()
+                    })
+                })
+                .collect();
+            let spans = ansi_spans(
+                &text,
+                &styles,
+                &matched,
+                Style::default(),
+                Style::default().fg(Color::Red),
+            );
+            let rendered: String =
+                spans.iter().map(|span| span.content.as_ref()).collect();
+            prop_assert_eq!(rendered, text);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Clear, Padding, Paragraph, Wrap,
+};
+use ratatui::Frame;
+
+use crate::event::Key;
+
+const CONFIRMATION_COLOR: Color = Color::LightRed;
+const CONFIRMATION_MIN_WIDTH: u16 = 30;
+const CONFIRMATION_MAX_WIDTH: u16 = 80;
+
+/// A yes/no prompt guarding an action of type `T` (e.g. deleting a file),
+/// which is carried out once the user answers yes.
+#[derive(Debug, Clone)]
+pub struct Confirmation<T> {
+    pub message: String,
+    /// The answer picked with enter.
+    pub default: bool,
+    pub action: T,
+}
+
+impl<T> Confirmation<T> {
+    /// A prompt answered no by default.
+    pub fn new(message: String, action: T) -> Self {
+        Self {
+            message,
+            default: false,
+            action,
+        }
+    }
+
+    /// The answer given by pressing the given key, if any.
+    pub fn answer(&self, key: Key) -> Option<bool> {
+        match key {
+            Key::Char('y' | 'Y') => Some(true),
+            Key::Char('n' | 'N') | Key::Esc => Some(false),
+            Key::Enter => Some(self.default),
+            _ => None,
+        }
+    }
+
+    /// Draw the prompt in the middle of the given area.
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let (yes, no) = if self.default {
+            ("[Y]es", "[n]o")
+        } else {
+            ("[y]es", "[N]o")
+        };
+        let width = u16::try_from(self.message.chars().count())
+            .unwrap_or(u16::MAX)
+            .saturating_add(4)
+            .clamp(CONFIRMATION_MIN_WIDTH, CONFIRMATION_MAX_WIDTH)
+            .min(area.width);
+        let inner_width = width.saturating_sub(4).max(1);
+        let message_lines = u16::try_from(
+            self.message.chars().count().div_ceil(inner_width.into()),
+        )
+        .unwrap_or(1)
+        .max(1);
+        // the message, a blank line and the answers, in the borders
+        let height = (message_lines + 4).min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        let block = Block::default()
+            .title_top(Line::from(" Confirm ").alignment(Alignment::Center))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(CONFIRMATION_COLOR))
+            .padding(Padding::horizontal(1));
+        let mut lines: Vec<Line> =
+            vec![Line::from(self.message.as_str()).bold(), Line::default()];
+        lines.push(
+            Line::from(vec![
+                Span::styled(yes, Style::default().fg(CONFIRMATION_COLOR)),
+                Span::raw(" / "),
+                Span::styled(no, Style::default().fg(CONFIRMATION_COLOR)),
+            ])
+            .alignment(Alignment::Center),
+        );
+        f.render_widget(Clear, popup);
+        f.render_widget(
+            Paragraph::new(lines).block(block).wrap(Wrap { trim: true }),
+            popup,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        let confirmation = Confirmation::new("Delete?".to_string(), ());
+        assert_eq!(confirmation.answer(Key::Char('y')), Some(true));
+        assert_eq!(confirmation.answer(Key::Esc), Some(false));
+        assert_eq!(confirmation.answer(Key::Enter), Some(false));
+        assert_eq!(confirmation.answer(Key::Char('x')), None);
+        let confirmation = Confirmation {
+            default: true,
+            ..confirmation
+        };
+        assert_eq!(confirmation.answer(Key::Enter), Some(true));
+    }
+}
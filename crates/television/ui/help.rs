@@ -28,7 +28,7 @@ impl Television {
     ) -> color_eyre::Result<()> {
         self.draw_metadata_block(f, layout.help_bar_left);
         self.draw_keymaps_block(f, layout.help_bar_middle)?;
-        draw_logo_block(f, layout.help_bar_right, mode_color(self.mode));
+        draw_logo_block(f, layout.help_bar_right, mode_color(self.mode()));
         Ok(())
     }
 
@@ -0,0 +1,213 @@
+use std::fmt::Write;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use television_utils::image::{GraphicsProtocol, Image, KITTY_DELETE_ALL};
+
+/// The size of a cell in pixels when the terminal doesn't report it.
+const DEFAULT_CELL_SIZE: (u32, u32) = (10, 20);
+
+/// The images drawn with a terminal graphics protocol.
+///
+/// Ratatui only knows about text, so these images are written to the
+/// terminal once the frame is drawn (see `take_pending`), over cells that
+/// ratatui is told to leave alone.
+#[derive(Debug, Default)]
+pub struct Graphics {
+    /// The escape sequences to write once the frame is drawn.
+    pending: String,
+    /// Whether an image written to the terminal may still be on screen.
+    shown: bool,
+}
+
+impl Graphics {
+    /// Draw the given image in the given area of the buffer, centered and
+    /// downsized to fit if needed.
+    ///
+    /// This removes the images drawn so far.
+    pub fn draw(
+        &mut self,
+        protocol: GraphicsProtocol,
+        image: &Image,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        self.clear();
+        if area.is_empty() {
+            return;
+        }
+        let Some((cell_width, cell_height)) = cell_size(protocol) else {
+            draw_half_blocks(image, area, buf);
+            return;
+        };
+        let (width, height) = image.fit(
+            u32::from(area.width) * cell_width,
+            u32::from(area.height) * cell_height,
+        );
+        let image = image.resize(width, height);
+        // both fit since the image fits in the area
+        let columns = width.div_ceil(cell_width) as u16;
+        let rows = height.div_ceil(cell_height) as u16;
+        let x = area.x + (area.width - columns) / 2;
+        let y = area.y + (area.height - rows) / 2;
+        for row in y..y + rows {
+            for column in x..x + columns {
+                buf[(column, row)].set_skip(true);
+            }
+        }
+        if let Some(sequence) = protocol.encode(&image) {
+            // save the cursor, erase what ratatui drew under the image
+            // before it was told to skip these cells (transparent pixels
+            // would show it), draw the image from its top left corner and
+            // restore the cursor
+            self.pending.push_str("\x1b7");
+            for row in y..y + rows {
+                let _ = write!(
+                    self.pending,
+                    "\x1b[{};{}H\x1b[{columns}X",
+                    row + 1,
+                    x + 1
+                );
+            }
+            let _ = write!(
+                self.pending,
+                "\x1b[{};{}H{sequence}\x1b8",
+                y + 1,
+                x + 1
+            );
+            self.shown = true;
+        }
+    }
+
+    /// Remove the images written to the terminal.
+    ///
+    /// Text drawn over sixels and iTerm2 images replaces them, but kitty
+    /// images must be explicitly deleted.
+    pub fn clear(&mut self) {
+        if self.shown {
+            self.pending.push_str(KITTY_DELETE_ALL);
+            self.shown = false;
+        }
+    }
+
+    pub fn is_shown(&self) -> bool {
+        self.shown
+    }
+
+    /// The escape sequences to write to the terminal once the frame is
+    /// drawn.
+    pub fn take_pending(&mut self) -> String {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// The size of the terminal cells in pixels, or `None` when the images are
+/// drawn with half blocks.
+fn cell_size(protocol: GraphicsProtocol) -> Option<(u32, u32)> {
+    if protocol == GraphicsProtocol::HalfBlocks {
+        return None;
+    }
+    Some(
+        crossterm::terminal::window_size()
+            .ok()
+            .filter(|size| {
+                size.width >= size.columns
+                    && size.height >= size.rows
+                    && size.columns > 0
+                    && size.rows > 0
+                    && size.width > 0
+            })
+            .map_or(DEFAULT_CELL_SIZE, |size| {
+                (
+                    u32::from(size.width / size.columns),
+                    u32::from(size.height / size.rows),
+                )
+            }),
+    )
+}
+
+/// Draw the given image with the upper and lower half block characters,
+/// each cell displaying two pixels, one above the other.
+fn draw_half_blocks(image: &Image, area: Rect, buf: &mut Buffer) {
+    let (width, height) =
+        image.fit(u32::from(area.width), u32::from(area.height) * 2);
+    let image = image.resize(width, height);
+    // both fit since the image fits in the area
+    let columns = width as u16;
+    let rows = height.div_ceil(2) as u16;
+    let x0 = area.x + (area.width - columns) / 2;
+    let y0 = area.y + (area.height - rows) / 2;
+    let color = |x: u32, y: u32| {
+        let [r, g, b, a] = image.pixel(x, y);
+        (a >= 128).then_some(Color::Rgb(r, g, b))
+    };
+    for row in 0..rows {
+        for column in 0..columns {
+            let (x, y) = (u32::from(column), u32::from(row) * 2);
+            let upper = color(x, y);
+            let lower = (y + 1 < height).then(|| color(x, y + 1)).flatten();
+            let cell = &mut buf[(x0 + column, y0 + row)];
+            match (upper, lower) {
+                (Some(upper), lower) => cell
+                    .set_symbol("▀")
+                    .set_fg(upper)
+                    .set_bg(lower.unwrap_or(Color::Reset)),
+                (None, Some(lower)) => {
+                    cell.set_symbol("▄").set_fg(lower).set_bg(Color::Reset)
+                }
+                (None, None) => cell.set_symbol(" "),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_half_blocks() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let clear = [0, 0, 0, 0];
+        let image = Image::new(2, 3, vec![red, clear, blue, red, red, clear]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 2));
+        let mut graphics = Graphics::default();
+        graphics.draw(
+            GraphicsProtocol::HalfBlocks,
+            &image,
+            buf.area,
+            &mut buf,
+        );
+        assert!(graphics.take_pending().is_empty());
+
+        // the image is centered horizontally
+        let cell = &buf[(1, 0)];
+        assert_eq!(cell.symbol(), "▀");
+        assert_eq!(cell.fg, Color::Rgb(255, 0, 0));
+        assert_eq!(cell.bg, Color::Rgb(0, 0, 255));
+        assert_eq!(buf[(2, 0)].symbol(), "▄");
+        assert_eq!(buf[(2, 1)].symbol(), " ");
+        assert_eq!(buf[(0, 0)].symbol(), " ");
+    }
+
+    #[test]
+    fn test_draw_with_graphics_protocol() {
+        let image = Image::new(1, 1, vec![[255, 255, 255, 255]]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 3));
+        let mut graphics = Graphics::default();
+        graphics.draw(GraphicsProtocol::Sixel, &image, buf.area, &mut buf);
+        // the image is written in the middle cell, which ratatui skips
+        assert!(graphics
+            .take_pending()
+            .starts_with("\x1b7\x1b[2;2H\x1b[1X\x1b[2;2H\x1bP"));
+        assert!(buf[(1, 1)].skip);
+        assert!(!buf[(0, 0)].skip);
+
+        graphics.clear();
+        assert_eq!(graphics.take_pending(), KITTY_DELETE_ALL);
+        graphics.clear();
+        assert!(graphics.take_pending().is_empty());
+    }
+}
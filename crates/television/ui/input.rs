@@ -1,6 +1,5 @@
 use crate::television::Television;
 use crate::ui::layout::Layout;
-use crate::ui::BORDER_COLOR;
 use color_eyre::eyre::Result;
 use ratatui::layout::{
     Alignment, Constraint, Direction, Layout as RatatuiLayout,
@@ -11,6 +10,7 @@ use ratatui::text::Line;
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
 use ratatui::Frame;
 use television_channels::channels::OnAir;
+use television_utils::format;
 
 pub mod actions;
 pub mod backend;
@@ -50,8 +50,8 @@ pub type InputResponse = Option<StateChanged>;
 ///
 /// Example:
 ///
-/// ```
-/// use tui_input::Input;
+/// ```ignore
+/// use television::ui::input::Input;
 ///
 /// let input: Input = "Hello World".into();
 ///
@@ -418,11 +418,16 @@ impl Television {
         f: &mut Frame,
         layout: &Layout,
     ) -> Result<()> {
+        let title = if self.annotation_input().is_some() {
+            " Note "
+        } else {
+            " Pattern "
+        };
         let input_block = Block::default()
-            .title_top(Line::from(" Pattern ").alignment(Alignment::Center))
+            .title_top(Line::from(title).alignment(Alignment::Center))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(BORDER_COLOR))
+            .border_style(Style::default().fg(self.theme.border))
             .style(Style::default());
 
         let input_block_inner = input_block.inner(layout.input);
@@ -432,51 +437,71 @@ impl Television {
 
         f.render_widget(input_block, layout.input);
 
+        let prompt = Span::styled(
+            self.config.ui.prompt.clone(),
+            Style::default().fg(self.theme.prompt).bold(),
+        );
+
         // split input block into 4 parts: prompt symbol, input, result count, spinner
-        let total_count = self.channel.total_count();
+        let total_count = format::count(u64::from(self.channel.total_count()));
         let inner_input_chunks = RatatuiLayout::default()
             .direction(Direction::Horizontal)
             .constraints([
                 // prompt symbol
-                Constraint::Length(2),
+                Constraint::Length(u16::try_from(prompt.width())?),
                 // input field
                 Constraint::Fill(1),
                 // result count
-                Constraint::Length(
-                    3 * ((total_count as f32).log10().ceil() as u16 + 1) + 3,
-                ),
+                Constraint::Length(u16::try_from(
+                    2 * total_count.chars().count() + 5,
+                )?),
                 // spinner
                 Constraint::Length(1),
             ])
             .split(input_block_inner);
 
         let arrow_block = Block::default();
-        let arrow = Paragraph::new(Span::styled(
-            "> ",
-            Style::default()
-                .fg(crate::television::DEFAULT_INPUT_FG)
-                .bold(),
-        ))
-        .block(arrow_block);
+        let arrow = Paragraph::new(prompt).block(arrow_block);
         f.render_widget(arrow, inner_input_chunks[0]);
 
         let interactive_input_block = Block::default();
         // keep 2 for borders and 1 for cursor
         let width = inner_input_chunks[1].width.max(3) - 3;
-        let scroll = self.results_picker.input.visual_scroll(width as usize);
-        let input = Paragraph::new(self.results_picker.input.value())
+        let input = self
+            .annotation_input()
+            .unwrap_or(&self.results_picker.input);
+        let scroll = input.visual_scroll(width as usize);
+        let visual_cursor = input.visual_cursor();
+        let value = input.value();
+        let input_line = if self.annotation_input().is_some() {
+            Line::from(value.to_string())
+        } else if value.is_empty() {
+            // placeholder
+            Line::from(
+                self.config
+                    .ui
+                    .placeholders
+                    .get(&self.current_channel_name())
+                    .map(|placeholder| Span::raw(placeholder.clone()).dim())
+                    .unwrap_or_default(),
+            )
+        } else {
+            // input followed by the completion hint
+            let mut spans = vec![Span::raw(value.to_string())];
+            if let Some(hint) = self.completion_hint() {
+                spans.push(Span::raw(hint).dim().not_bold());
+            }
+            Line::from(spans)
+        };
+        let input = Paragraph::new(input_line)
             .scroll((0, u16::try_from(scroll)?))
             .block(interactive_input_block)
-            .style(
-                Style::default()
-                    .fg(crate::television::DEFAULT_INPUT_FG)
-                    .bold()
-                    .italic(),
-            )
+            .style(Style::default().fg(self.theme.prompt).bold().italic())
             .alignment(Alignment::Left);
         f.render_widget(input, inner_input_chunks[1]);
 
-        if self.channel.running() {
+        // also spinning while e.g. a slow command piped to stdin runs
+        if self.channel.running() || self.channel.loading() {
             f.render_stateful_widget(
                 self.spinner,
                 inner_input_chunks[3],
@@ -489,16 +514,14 @@ impl Television {
         let result_count_paragraph = Paragraph::new(Span::styled(
             format!(
                 " {} / {} ",
-                if result_count == 0 {
+                format::count(if result_count == 0 {
                     0
                 } else {
-                    self.results_picker.selected().unwrap_or(0) + 1
-                },
-                result_count,
+                    self.results_picker.selected().unwrap_or(0) as u64 + 1
+                }),
+                format::count(u64::from(result_count)),
             ),
-            Style::default()
-                .fg(crate::television::DEFAULT_RESULTS_COUNT_FG)
-                .italic(),
+            Style::default().fg(self.theme.results_count).italic(),
         ))
         .block(result_count_block)
         .alignment(Alignment::Right);
@@ -509,10 +532,7 @@ impl Television {
         f.set_cursor_position((
             // Put cursor past the end of the input text
             inner_input_chunks[1].x
-                + u16::try_from(
-                    self.results_picker.input.visual_cursor().max(scroll)
-                        - scroll,
-                )?,
+                + u16::try_from(visual_cursor.max(scroll) - scroll)?,
             // Move one line down, from the border to the input line
             inner_input_chunks[1].y,
         ));
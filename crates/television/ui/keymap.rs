@@ -1,4 +1,4 @@
-use color_eyre::eyre::{OptionExt, Result};
+use color_eyre::eyre::Result;
 use ratatui::{
     layout::Constraint,
     style::{Color, Style},
@@ -18,7 +18,7 @@ const ACTION_COLOR: Color = Color::DarkGray;
 
 impl Television {
     pub fn build_keymap_table<'a>(&self) -> Result<Table<'a>> {
-        match self.mode {
+        match self.mode() {
             Mode::Channel => self.build_keymap_table_for_channel(),
             Mode::RemoteControl => {
                 self.build_keymap_table_for_channel_selection()
@@ -26,12 +26,14 @@ impl Television {
             Mode::SendToChannel => {
                 self.build_keymap_table_for_channel_transitions()
             }
+            Mode::OpenWith => self.build_keymap_table_for_open_with(),
+            Mode::FileTypes => self.build_keymap_table_for_file_types(),
         }
     }
 
     fn build_keymap_table_for_channel<'a>(&self) -> Result<Table<'a>> {
-        let keymap = self.keymap_for_mode()?;
-        let key_color = mode_color(self.mode);
+        let keymap = &self.keymap_for_mode();
+        let key_color = mode_color(self.mode());
 
         // Results navigation
         let prev = keys_for_action(keymap, &Action::SelectPrevEntry);
@@ -116,8 +118,8 @@ impl Television {
     fn build_keymap_table_for_channel_selection<'a>(
         &self,
     ) -> Result<Table<'a>> {
-        let keymap = self.keymap_for_mode()?;
-        let key_color = mode_color(self.mode);
+        let keymap = &self.keymap_for_mode();
+        let key_color = mode_color(self.mode());
 
         // Results navigation
         let prev = keys_for_action(keymap, &Action::SelectPrevEntry);
@@ -162,8 +164,8 @@ impl Television {
     fn build_keymap_table_for_channel_transitions<'a>(
         &self,
     ) -> Result<Table<'a>> {
-        let keymap = self.keymap_for_mode()?;
-        let key_color = mode_color(self.mode);
+        let keymap = &self.keymap_for_mode();
+        let key_color = mode_color(self.mode());
 
         // Results navigation
         let prev = keys_for_action(keymap, &Action::SelectPrevEntry);
@@ -205,24 +207,104 @@ impl Television {
         ))
     }
 
-    /// Get the keymap for the current mode.
-    ///
-    /// # Returns
-    /// A reference to the keymap for the current mode.
-    fn keymap_for_mode(&self) -> Result<&HashMap<Key, Action>> {
-        let keymap = self
-            .config
-            .keybindings
-            .get(&self.mode)
-            .ok_or_eyre("No keybindings found for the current Mode")?;
-        Ok(keymap)
+    fn build_keymap_table_for_open_with<'a>(&self) -> Result<Table<'a>> {
+        let keymap = &self.keymap_for_mode();
+        let key_color = mode_color(self.mode());
+
+        // Results navigation
+        let prev = keys_for_action(keymap, &Action::SelectPrevEntry);
+        let next = keys_for_action(keymap, &Action::SelectNextEntry);
+        let results_row = Row::new(build_cells_for_key_groups(
+            "Browse applications",
+            vec![prev, next],
+            key_color,
+        ));
+
+        // Select entry
+        let select_entry_keys = keys_for_action(keymap, &Action::SelectEntry);
+        let select_entry_row = Row::new(build_cells_for_key_groups(
+            "Open with application",
+            vec![select_entry_keys],
+            key_color,
+        ));
+
+        // Cancel
+        let cancel_keys = keys_for_action(keymap, &Action::ToggleOpenWith);
+        let cancel_row = Row::new(build_cells_for_key_groups(
+            "Cancel",
+            vec![cancel_keys],
+            key_color,
+        ));
+
+        // Quit
+        let quit_keys = keys_for_action(keymap, &Action::Quit);
+        let quit_row = Row::new(build_cells_for_key_groups(
+            "Quit",
+            vec![quit_keys],
+            key_color,
+        ));
+
+        Ok(Table::new(
+            vec![results_row, select_entry_row, cancel_row, quit_row],
+            vec![Constraint::Fill(1), Constraint::Fill(2)],
+        ))
+    }
+
+    fn build_keymap_table_for_file_types<'a>(&self) -> Result<Table<'a>> {
+        let keymap = &self.keymap_for_mode();
+        let key_color = mode_color(self.mode());
+
+        // Results navigation
+        let prev = keys_for_action(keymap, &Action::SelectPrevEntry);
+        let next = keys_for_action(keymap, &Action::SelectNextEntry);
+        let results_row = Row::new(build_cells_for_key_groups(
+            "Browse file types",
+            vec![prev, next],
+            key_color,
+        ));
+
+        // Select entry
+        let select_entry_keys = keys_for_action(keymap, &Action::SelectEntry);
+        let select_entry_row = Row::new(build_cells_for_key_groups(
+            "List files of type",
+            vec![select_entry_keys],
+            key_color,
+        ));
+
+        // Cancel
+        let cancel_keys = keys_for_action(keymap, &Action::ToggleFileTypes);
+        let cancel_row = Row::new(build_cells_for_key_groups(
+            "Cancel",
+            vec![cancel_keys],
+            key_color,
+        ));
+
+        // Quit
+        let quit_keys = keys_for_action(keymap, &Action::Quit);
+        let quit_row = Row::new(build_cells_for_key_groups(
+            "Quit",
+            vec![quit_keys],
+            key_color,
+        ));
+
+        Ok(Table::new(
+            vec![results_row, select_entry_row, cancel_row, quit_row],
+            vec![Constraint::Fill(1), Constraint::Fill(2)],
+        ))
+    }
+
+    /// Get the keymap for the current mode, including the keybindings of
+    /// the current channel.
+    fn keymap_for_mode(&self) -> HashMap<Key, Action> {
+        self.config
+            .keymap(&self.current_channel_name(), self.mode())
     }
 }
 
 /// Build the corresponding spans for a group of keys.
 ///
 /// # Example
-/// ```rust
+/// ```ignore
 /// use ratatui::text::Span;
 /// use television::ui::help::build_spans_for_key_groups;
 ///
@@ -240,7 +322,7 @@ fn build_cells_for_key_groups(
     group_name: &str,
     key_groups: Vec<Vec<String>>,
     key_color: Color,
-) -> Vec<Cell> {
+) -> Vec<Cell<'_>> {
     if key_groups.is_empty() || key_groups.iter().all(Vec::is_empty) {
         return vec![group_name.into(), "No keybindings".into()];
     }
@@ -273,7 +355,7 @@ fn build_cells_for_key_groups(
 /// Get the keys for a given action.
 ///
 /// # Example
-/// ```rust
+/// ```ignore
 /// use std::collections::HashMap;
 /// use television::action::Action;
 /// use television::ui::help::keys_for_action;
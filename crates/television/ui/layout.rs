@@ -24,6 +24,20 @@ impl Default for Dimensions {
     }
 }
 
+/// What the layout is made of, besides the help bar and the results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayoutOptions {
+    /// Whether the input field is shown.
+    pub input: bool,
+    /// Whether the remote control is shown.
+    pub remote_control: bool,
+    /// Whether the terminal is narrow, in which case the remote control
+    /// takes up the whole area below the help bar.
+    pub narrow: bool,
+    /// Whether the preview is shown.
+    pub preview: bool,
+}
+
 pub struct Layout {
     pub help_bar_left: Rect,
     pub help_bar_middle: Rect,
@@ -32,6 +46,7 @@ pub struct Layout {
     pub input: Rect,
     pub preview_title: Rect,
     pub preview_window: Rect,
+    pub secondary_preview_window: Option<Rect>,
     pub remote_control: Option<Rect>,
 }
 
@@ -45,6 +60,7 @@ impl Layout {
         input: Rect,
         preview_title: Rect,
         preview_window: Rect,
+        secondary_preview_window: Option<Rect>,
         remote_control: Option<Rect>,
     ) -> Self {
         Self {
@@ -55,20 +71,34 @@ impl Layout {
             input,
             preview_title,
             preview_window,
+            secondary_preview_window,
             remote_control,
         }
     }
 
+    /// Build the layout for the given area.
+    ///
+    /// On narrow terminals, the remote control takes up the whole area below
+    /// the help bar. When the preview is hidden, the results and input take
+    /// up its space and the preview areas are empty. Likewise, the results
+    /// take up the input's space when there is no input.
     pub fn build(
         dimensions: &Dimensions,
         area: Rect,
-        with_remote: bool,
+        options: LayoutOptions,
+        secondary_preview_height: Option<u16>,
     ) -> Self {
+        let LayoutOptions {
+            input: with_input,
+            remote_control: with_remote,
+            narrow,
+            preview: show_preview,
+        } = options;
         let main_block = centered_rect(dimensions.x, dimensions.y, area);
         // split the main block into two vertical chunks (help bar + rest)
         let hz_chunks = layout::Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Max(9), Constraint::Fill(1)])
+            .constraints([Constraint::Max(10), Constraint::Fill(1)])
             .split(main_block);
 
         // split the help bar into three horizontal chunks (left + center + right)
@@ -85,15 +115,13 @@ impl Layout {
             .split(hz_chunks[0]);
 
         // split the main block into two vertical chunks
-        let constraints = if with_remote {
-            vec![
-                Constraint::Fill(1),
-                Constraint::Fill(1),
-                Constraint::Length(24),
-            ]
-        } else {
-            vec![Constraint::Percentage(50), Constraint::Percentage(50)]
-        };
+        let mut constraints = vec![Constraint::Fill(1)];
+        if show_preview {
+            constraints.push(Constraint::Fill(1));
+        }
+        if with_remote && !narrow {
+            constraints.push(Constraint::Length(24));
+        }
         let vt_chunks = layout::Layout::default()
             .direction(Direction::Horizontal)
             .constraints(constraints)
@@ -102,14 +130,45 @@ impl Layout {
         // left block: results + input field
         let left_chunks = layout::Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(3), Constraint::Length(3)])
+            .constraints([
+                Constraint::Min(3),
+                Constraint::Length(if with_input { 3 } else { 0 }),
+            ])
             .split(vt_chunks[0]);
 
         // right block: preview title + preview
-        let right_chunks = layout::Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(3)])
-            .split(vt_chunks[1]);
+        let (preview_title, preview_window, secondary_preview_window) =
+            if show_preview {
+                let right_chunks = layout::Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(3), Constraint::Min(3)])
+                    .split(vt_chunks[1]);
+                // preview: main preview + optional secondary preview (stacked)
+                if let Some(height) = secondary_preview_height {
+                    let preview_chunks = layout::Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([
+                            Constraint::Min(3),
+                            Constraint::Percentage(height.min(100)),
+                        ])
+                        .split(right_chunks[1]);
+                    (
+                        right_chunks[0],
+                        preview_chunks[0],
+                        Some(preview_chunks[1]),
+                    )
+                } else {
+                    (right_chunks[0], right_chunks[1], None)
+                }
+            } else {
+                (Rect::default(), Rect::default(), None)
+            };
+
+        let remote_control = match (with_remote, narrow) {
+            (false, _) => None,
+            (true, true) => Some(hz_chunks[1]),
+            (true, false) => vt_chunks.last().copied(),
+        };
 
         Self::new(
             help_bar_chunks[0],
@@ -117,13 +176,10 @@ impl Layout {
             help_bar_chunks[2],
             left_chunks[0],
             left_chunks[1],
-            right_chunks[0],
-            right_chunks[1],
-            if with_remote {
-                Some(vt_chunks[2])
-            } else {
-                None
-            },
+            preview_title,
+            preview_window,
+            secondary_preview_window,
+            remote_control,
         )
     }
 }
@@ -154,3 +210,82 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 // UI size
 const UI_WIDTH_PERCENT: u16 = 95;
 const UI_HEIGHT_PERCENT: u16 = 95;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect::new(0, 0, 80, 40);
+
+    #[test]
+    fn test_layout_without_preview() {
+        let layout = Layout::build(
+            &Dimensions::from(100),
+            AREA,
+            LayoutOptions {
+                input: true,
+                remote_control: false,
+                narrow: true,
+                preview: false,
+            },
+            None,
+        );
+        assert_eq!(layout.results.width, AREA.width);
+        assert_eq!(layout.preview_window, Rect::default());
+        assert!(layout.remote_control.is_none());
+    }
+
+    #[test]
+    fn test_layout_without_input() {
+        let layout = Layout::build(
+            &Dimensions::from(100),
+            AREA,
+            LayoutOptions {
+                input: false,
+                remote_control: false,
+                narrow: false,
+                preview: true,
+            },
+            None,
+        );
+        assert_eq!(layout.input.height, 0);
+        assert_eq!(layout.results.bottom(), AREA.bottom());
+    }
+
+    #[test]
+    fn test_narrow_layout_remote_control_is_full_screen() {
+        let layout = Layout::build(
+            &Dimensions::from(100),
+            AREA,
+            LayoutOptions {
+                input: true,
+                remote_control: true,
+                narrow: true,
+                preview: true,
+            },
+            None,
+        );
+        let remote_control = layout.remote_control.unwrap();
+        assert_eq!(remote_control.width, AREA.width);
+        assert_eq!(remote_control.bottom(), AREA.bottom());
+    }
+
+    #[test]
+    fn test_layout_with_secondary_preview() {
+        let layout = Layout::build(
+            &Dimensions::from(100),
+            AREA,
+            LayoutOptions {
+                input: true,
+                remote_control: true,
+                narrow: false,
+                preview: true,
+            },
+            Some(50),
+        );
+        let secondary = layout.secondary_preview_window.unwrap();
+        assert_eq!(secondary.x, layout.preview_window.x);
+        assert_eq!(secondary.y, layout.preview_window.bottom());
+        assert_eq!(layout.remote_control.unwrap().width, 24);
+    }
+}
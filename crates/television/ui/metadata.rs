@@ -5,6 +5,9 @@ use ratatui::{
     widgets::{Cell, Row, Table},
 };
 
+use television_channels::channels::OnAir;
+use television_utils::format;
+
 use crate::television::Television;
 use crate::ui::mode::mode_color;
 
@@ -78,7 +81,7 @@ impl Television {
                 Style::default().fg(METADATA_FIELD_NAME_COLOR),
             )),
             Cell::from(Span::styled(
-                self.current_channel().to_string(),
+                self.channel.name(),
                 Style::default().fg(METADATA_FIELD_VALUE_COLOR),
             )),
         ]);
@@ -89,23 +92,64 @@ impl Television {
                 Style::default().fg(METADATA_FIELD_NAME_COLOR),
             )),
             Cell::from(Span::styled(
-                self.mode.to_string(),
-                Style::default().fg(mode_color(self.mode)),
+                self.mode().to_string(),
+                Style::default().fg(mode_color(self.mode())),
+            )),
+        ]);
+
+        let previews_size =
+            self.previewer.memory_usage() + self.preview_pane.memory_usage();
+        let memory_row = Row::new(vec![
+            Cell::from(Span::styled(
+                "memory: ",
+                Style::default().fg(METADATA_FIELD_NAME_COLOR),
+            )),
+            Cell::from(Span::styled(
+                format!(
+                    "{} + {} previews",
+                    format::size(self.channel.memory_usage() as u64),
+                    format::size(previews_size as u64),
+                ),
+                Style::default().fg(METADATA_FIELD_VALUE_COLOR),
             )),
         ]);
 
+        let mut rows = vec![
+            version_row,
+            target_triple_row,
+            build_row,
+            current_dir_row,
+            current_channel_row,
+            current_mode_row,
+        ];
+        if self.searches_files() {
+            rows.push(Row::new(vec![
+                Cell::from(Span::styled(
+                    "search scope: ",
+                    Style::default().fg(METADATA_FIELD_NAME_COLOR),
+                )),
+                Cell::from(Span::styled(
+                    self.context.scope().to_string(),
+                    Style::default().fg(METADATA_FIELD_VALUE_COLOR),
+                )),
+            ]));
+        }
+        if self.runs_command() && !self.context.time_range().is_empty() {
+            rows.push(Row::new(vec![
+                Cell::from(Span::styled(
+                    "time range: ",
+                    Style::default().fg(METADATA_FIELD_NAME_COLOR),
+                )),
+                Cell::from(Span::styled(
+                    self.context.time_range().to_string(),
+                    Style::default().fg(METADATA_FIELD_VALUE_COLOR),
+                )),
+            ]));
+        }
+        rows.push(memory_row);
+
         let widths = vec![Constraint::Fill(1), Constraint::Fill(2)];
 
-        Table::new(
-            vec![
-                version_row,
-                target_triple_row,
-                build_row,
-                current_dir_row,
-                current_channel_row,
-                current_mode_row,
-            ],
-            widths,
-        )
+        Table::new(rows, widths)
     }
 }
@@ -0,0 +1,89 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+
+/// The last rendering of a pane of the UI, reused as long as what the pane
+/// displays (its key) and its area don't change, instead of rebuilding its
+/// widgets on every frame.
+#[derive(Debug)]
+pub struct PaneCache<K> {
+    key: Option<K>,
+    buffer: Buffer,
+}
+
+impl<K> Default for PaneCache<K> {
+    fn default() -> Self {
+        Self {
+            key: None,
+            buffer: Buffer::empty(Rect::default()),
+        }
+    }
+}
+
+impl<K: PartialEq> PaneCache<K> {
+    /// Draw the pane in the given area of the buffer, calling `render` to
+    /// rebuild it only if its key or its area changed since it was last
+    /// drawn.
+    ///
+    /// # Returns
+    /// Whether the pane was rebuilt.
+    pub fn draw(
+        &mut self,
+        buffer: &mut Buffer,
+        area: Rect,
+        key: K,
+        render: impl FnOnce(Rect, &mut Buffer),
+    ) -> bool {
+        let area = area.intersection(buffer.area);
+        let rebuild =
+            self.buffer.area != area || self.key.as_ref() != Some(&key);
+        if rebuild {
+            self.buffer = Buffer::empty(area);
+            render(area, &mut self.buffer);
+            self.key = Some(key);
+        }
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buffer[(x, y)] = self.buffer[(x, y)].clone();
+            }
+        }
+        rebuild
+    }
+
+    /// The approximate number of bytes taken by the last rendering.
+    pub fn memory_usage(&self) -> usize {
+        self.buffer.content.len()
+            * std::mem::size_of::<ratatui::buffer::Cell>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::widgets::{Paragraph, Widget};
+
+    #[test]
+    fn test_pane_cache() {
+        let mut frame = Buffer::empty(Rect::new(0, 0, 10, 3));
+        let mut pane = PaneCache::default();
+        let area = Rect::new(2, 1, 5, 1);
+        let render = |area, buf: &mut Buffer| {
+            Paragraph::new("hello").render(area, buf);
+        };
+        assert!(pane.draw(&mut frame, area, 1, render));
+        assert_eq!(
+            frame,
+            Buffer::with_lines(["          ", "  hello   ", "          "])
+        );
+
+        // the next frame starts from scratch, the pane is copied over
+        let mut frame = Buffer::empty(Rect::new(0, 0, 10, 3));
+        assert!(!pane.draw(&mut frame, area, 1, |_, _| unreachable!()));
+        assert_eq!(
+            frame,
+            Buffer::with_lines(["          ", "  hello   ", "          "])
+        );
+
+        assert!(pane.draw(&mut frame, area, 2, render));
+        assert!(pane.draw(&mut frame, Rect::new(0, 0, 5, 1), 2, render));
+    }
+}
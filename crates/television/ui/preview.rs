@@ -1,27 +1,51 @@
+use crate::config::SecondaryPreviewConfig;
 use crate::television::Television;
+use crate::ui::ansi::ansi_spans;
 use crate::ui::layout::Layout;
-use crate::ui::BORDER_COLOR;
+use crate::ui::theme::Theme;
 use color_eyre::eyre::Result;
+use futures::executor::block_on;
 use ratatui::layout::{Alignment, Rect};
 use ratatui::prelude::{Color, Line, Modifier, Span, Style, Stylize, Text};
-use ratatui::widgets::{Block, BorderType, Borders, Padding, Paragraph, Wrap};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Padding, Paragraph, Widget, Wrap,
+};
 use ratatui::Frame;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
-use syntect::highlighting::Color as SyntectColor;
 use television_channels::channels::OnAir;
-use television_channels::entry::Entry;
+use television_channels::entry::{Entry, PreviewCommand, PreviewType};
 use television_previewers::previewers::{
-    Preview, PreviewContent, FILE_TOO_LARGE_MSG, PREVIEW_NOT_SUPPORTED_MSG,
+    meta, Preview, PreviewContent, FILE_TOO_LARGE_MSG,
+    PREVIEW_NOT_SUPPORTED_MSG,
+};
+use television_utils::checksum::ChecksumStatus;
+use television_utils::format;
+use television_utils::hexdump::Hexdump;
+use television_utils::strings::{
+    shell_quote, shrink_with_custom_ellipsis, EMPTY_STRING,
 };
-use television_utils::strings::{shrink_with_ellipsis, EMPTY_STRING};
+#[cfg(feature = "syntax-highlighting")]
+use television_utils::syntax::HighlightedLine;
+
+/// What the preview pane displays, telling whether it must be laid out
+/// again (see `PaneCache`).
+#[derive(Debug, Clone)]
+pub struct PreviewPaneKey {
+    preview: Arc<Preview>,
+    scroll: u16,
+    target_line: Option<u16>,
+}
 
-//  preview
-pub const DEFAULT_PREVIEW_TITLE_FG: Color = Color::Blue;
-const DEFAULT_SELECTED_PREVIEW_BG: Color = Color::Rgb(50, 50, 50);
-const DEFAULT_PREVIEW_CONTENT_FG: Color = Color::Rgb(150, 150, 180);
-const DEFAULT_PREVIEW_GUTTER_FG: Color = Color::Rgb(70, 70, 70);
-const DEFAULT_PREVIEW_GUTTER_SELECTED_FG: Color = Color::Rgb(255, 150, 150);
+impl PartialEq for PreviewPaneKey {
+    fn eq(&self, other: &Self) -> bool {
+        // previews are never modified, only replaced in the cache
+        Arc::ptr_eq(&self.preview, &other.preview)
+            && self.scroll == other.scroll
+            && self.target_line == other.target_line
+    }
+}
 
 impl Television {
     pub(crate) fn draw_preview_title_block(
@@ -32,9 +56,11 @@ impl Television {
         preview: &Arc<Preview>,
     ) -> Result<()> {
         let mut preview_title_spans = Vec::new();
-        if selected_entry.icon.is_some() && self.config.ui.use_nerd_font_icons
+        if let Some(icon) = selected_entry
+            .icon
+            .as_ref()
+            .filter(|_| self.config.ui.use_nerd_font_icons)
         {
-            let icon = selected_entry.icon.as_ref().unwrap();
             preview_title_spans.push(Span::styled(
                 {
                     let mut icon_str = String::from(icon.icon);
@@ -45,25 +71,66 @@ impl Television {
             ));
         }
         preview_title_spans.push(Span::styled(
-            shrink_with_ellipsis(
+            shrink_with_custom_ellipsis(
                 &preview.title,
                 layout.preview_window.width.saturating_sub(4) as usize,
+                &self.config.ui.ellipsis,
             ),
-            Style::default().fg(DEFAULT_PREVIEW_TITLE_FG).bold(),
+            Style::default().fg(self.theme.preview_title).bold(),
         ));
+        let mut block = Block::default()
+            .padding(Padding::horizontal(1))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.theme.border));
+        if let Some((sha256, md5)) = self.checksum_titles(selected_entry) {
+            let max_width = layout.preview_window.width.saturating_sub(4);
+            let title = |text: String| {
+                Line::from(shrink_with_custom_ellipsis(
+                    &text,
+                    max_width as usize,
+                    &self.config.ui.ellipsis,
+                ))
+                .alignment(Alignment::Right)
+                .style(Style::default().fg(self.theme.preview_title))
+            };
+            block = block.title_top(title(sha256));
+            if let Some(md5) = md5 {
+                block = block.title_bottom(title(md5));
+            }
+        }
         let preview_title = Paragraph::new(Line::from(preview_title_spans))
-            .block(
-                Block::default()
-                    .padding(Padding::horizontal(1))
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(BORDER_COLOR)),
-            )
+            .block(block)
             .alignment(Alignment::Left);
         f.render_widget(preview_title, layout.preview_title);
         Ok(())
     }
 
+    /// The titles displaying the checksums of the selected file, if they
+    /// were requested: the sha256 one (or the progress of the computation)
+    /// and the md5 one.
+    fn checksum_titles(
+        &self,
+        entry: &Entry,
+    ) -> Option<(String, Option<String>)> {
+        let checksum = self
+            .checksum
+            .as_ref()
+            .filter(|c| c.path == Path::new(&entry.name))?;
+        Some(match checksum.status() {
+            ChecksumStatus::Running(percent) => {
+                (format!(" sha256: {percent}% "), None)
+            }
+            ChecksumStatus::Done(checksums) => (
+                format!(" sha256 {} ", checksums.sha256),
+                Some(format!(" md5 {} ", checksums.md5)),
+            ),
+            ChecksumStatus::Failed(e) => {
+                (format!(" checksum failed: {e} "), None)
+            }
+        })
+    }
+
     pub(crate) fn draw_preview_content_block(
         &mut self,
         f: &mut Frame,
@@ -75,7 +142,7 @@ impl Television {
             .title_top(Line::from(" Preview ").alignment(Alignment::Center))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(BORDER_COLOR))
+            .border_style(Style::default().fg(self.theme.border))
             .style(Style::default())
             .padding(Padding::right(1));
 
@@ -89,27 +156,132 @@ impl Television {
         let inner = preview_outer_block.inner(layout.preview_window);
         f.render_widget(preview_outer_block, layout.preview_window);
 
-        //if let PreviewContent::Image(img) = &preview.content {
-        //    let image_component = StatefulImage::new(None);
-        //    frame.render_stateful_widget(
-        //        image_component,
-        //        inner,
-        //        &mut img.clone(),
-        //    );
-        //} else {
-        let preview_block = self.build_preview_paragraph(
-            preview_inner_block,
-            inner,
-            preview,
-            selected_entry
-                .line_number
-                .map(|l| u16::try_from(l).unwrap_or(0)),
-        );
-        f.render_widget(preview_block, inner);
-        //}
+        // the target line refers to the entry's source, not to the output of
+        // a preview command
+        let target_line = selected_entry
+            .line_number
+            .filter(|_| {
+                !matches!(selected_entry.preview_type, PreviewType::Command(_))
+            })
+            .map(|l| u16::try_from(l).unwrap_or(0));
+        self.maybe_init_preview_scroll(target_line, inner.height);
+
+        // large previews are costly to lay out, only do it when what's
+        // displayed changed
+        let key = PreviewPaneKey {
+            preview: preview.clone(),
+            scroll: self.preview_scroll.unwrap_or(0),
+            target_line,
+        };
+        let mut pane = std::mem::take(&mut self.preview_pane);
+        pane.draw(f.buffer_mut(), inner, key, |area, buf| {
+            if let PreviewContent::Image(image) = &preview.content {
+                let area = preview_inner_block.inner(area);
+                self.graphics.draw(self.graphics_protocol, image, area, buf);
+            } else {
+                // the previous image, if any, is replaced by text
+                self.graphics.clear();
+                self.build_preview_paragraph(
+                    preview_inner_block,
+                    area,
+                    preview,
+                    target_line,
+                )
+                .render(area, buf);
+            }
+        });
+        self.preview_pane = pane;
         Ok(())
     }
 
+    /// The secondary preview pane configured for the current channel, if any.
+    pub(crate) fn secondary_preview_config(
+        &self,
+    ) -> Option<SecondaryPreviewConfig> {
+        self.config
+            .ui
+            .secondary_previews
+            .get(&self.current_channel_name())
+            .cloned()
+    }
+
+    pub(crate) fn draw_secondary_preview_block(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        selected_entry: &Entry,
+        config: &SecondaryPreviewConfig,
+    ) {
+        let (title, preview) = match &config.command {
+            Some(command) => (
+                command.as_str(),
+                block_on(self.previewer.preview(&Entry::new(
+                    selected_entry.name.clone(),
+                    PreviewType::Command(
+                        PreviewCommand::new(command.replace(
+                            "{}",
+                            &shell_quote(&selected_entry.name),
+                        )),
+                    ),
+                ))),
+            ),
+            None => ("Metadata", meta::metadata(selected_entry)),
+        };
+        let block = Block::default()
+            .title_top(
+                Line::from(format!(
+                    " {} ",
+                    shrink_with_custom_ellipsis(
+                        title,
+                        area.width.saturating_sub(6) as usize,
+                        &self.config.ui.ellipsis,
+                    )
+                ))
+                .alignment(Alignment::Center),
+            )
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.theme.border))
+            .padding(Padding::horizontal(1));
+
+        // nothing to show for the placeholder entry
+        if selected_entry.name.is_empty() {
+            f.render_widget(block, area);
+            return;
+        }
+        let paragraph = match &preview.content {
+            PreviewContent::PlainText(content) => Paragraph::new(
+                content
+                    .iter()
+                    .map(|line| {
+                        Line::styled(
+                            line.to_string(),
+                            Style::default().fg(self.theme.preview_content),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            PreviewContent::AnsiText(content) => Paragraph::new(
+                content
+                    .iter()
+                    .map(|line| {
+                        Line::from(ansi_spans(
+                            &line.text,
+                            &line.styles,
+                            &[],
+                            Style::default().fg(self.theme.preview_content),
+                            Style::default(),
+                        ))
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            PreviewContent::Loading => Paragraph::new("Loading...")
+                .style(Style::default().add_modifier(Modifier::ITALIC)),
+            _ => Paragraph::new(Text::raw(EMPTY_STRING)),
+        };
+        f.render_widget(paragraph.block(block), area);
+    }
+
     const FILL_CHAR_SLANTED: char = '╱';
     const FILL_CHAR_EMPTY: char = ' ';
 
@@ -120,11 +292,19 @@ impl Television {
         preview: &Arc<Preview>,
         target_line: Option<u16>,
     ) -> Paragraph<'b> {
-        self.maybe_init_preview_scroll(target_line, inner.height);
+        // only the lines in view are laid out, previews can be huge
+        let scroll = self.preview_scroll.unwrap_or(0);
+        let visible_lines = usize::from(scroll)
+            ..usize::from(scroll) + usize::from(inner.height);
         match &preview.content {
             PreviewContent::PlainText(content) => {
                 let mut lines = Vec::new();
-                for (i, line) in content.iter().enumerate() {
+                for (i, line) in content
+                    .iter()
+                    .enumerate()
+                    .skip(visible_lines.start)
+                    .take(visible_lines.len())
+                {
                     lines.push(Line::from(vec![
                         build_line_number_span(i + 1).style(Style::default().fg(
                             if matches!(
@@ -132,18 +312,18 @@ impl Television {
                                 Some(l) if l == u16::try_from(i).unwrap_or(0) + 1
                             )
                             {
-                                DEFAULT_PREVIEW_GUTTER_SELECTED_FG
+                                self.theme.preview_gutter_selected
                             } else {
-                                DEFAULT_PREVIEW_GUTTER_FG
+                                self.theme.preview_gutter
                             },
                         )),
                         Span::styled(" │ ",
-                                     Style::default().fg(DEFAULT_PREVIEW_GUTTER_FG).dim()),
+                                     Style::default().fg(self.theme.preview_gutter).dim()),
                         Span::styled(
                             line.to_string(),
-                            Style::default().fg(DEFAULT_PREVIEW_CONTENT_FG).bg(
+                            Style::default().fg(self.theme.preview_content).bg(
                                 if matches!(target_line, Some(l) if l == u16::try_from(i).unwrap() + 1) {
-                                    DEFAULT_SELECTED_PREVIEW_BG
+                                    self.theme.preview_selection_bg
                                 } else {
                                     Color::Reset
                                 },
@@ -152,16 +332,14 @@ impl Television {
                     ]));
                 }
                 let text = Text::from(lines);
-                Paragraph::new(text)
-                    .block(preview_block)
-                    .scroll((self.preview_scroll.unwrap_or(0), 0))
+                Paragraph::new(text).block(preview_block)
             }
             PreviewContent::PlainTextWrapped(content) => {
                 let mut lines = Vec::new();
                 for line in content.lines() {
                     lines.push(Line::styled(
                         line.to_string(),
-                        Style::default().fg(DEFAULT_PREVIEW_CONTENT_FG),
+                        Style::default().fg(self.theme.preview_content),
                     ));
                 }
                 let text = Text::from(lines);
@@ -169,16 +347,65 @@ impl Television {
                     .block(preview_block)
                     .wrap(Wrap { trim: true })
             }
+            #[cfg(feature = "syntax-highlighting")]
             PreviewContent::SyntectHighlightedText(highlighted_lines) => {
                 compute_paragraph_from_highlighted_lines(
-                    highlighted_lines,
+                    highlighted_lines
+                        .get(visible_lines.start..)
+                        .unwrap_or_default()
+                        .iter()
+                        .take(visible_lines.len()),
+                    visible_lines.start,
                     target_line.map(|l| l as usize),
-                    self.preview_scroll.unwrap_or(0),
-                    self.preview_pane_height,
+                    &self.theme,
                 )
                 .block(preview_block)
                 .alignment(Alignment::Left)
-                .scroll((self.preview_scroll.unwrap_or(0), 0))
+            }
+            #[cfg(feature = "syntax-highlighting")]
+            PreviewContent::LazyHighlightedText(highlighter) => {
+                compute_paragraph_from_highlighted_lines(
+                    &highlighter.highlight(visible_lines.clone()),
+                    visible_lines.start,
+                    target_line.map(|l| l as usize),
+                    &self.theme,
+                )
+                .block(preview_block)
+                .alignment(Alignment::Left)
+            }
+            PreviewContent::Hexdump(hexdump) => {
+                build_hexdump_paragraph(hexdump, visible_lines, &self.theme)
+                    .block(preview_block)
+            }
+            PreviewContent::AnsiText(content) => {
+                let lines: Vec<Line> = content
+                    .iter()
+                    .enumerate()
+                    .skip(visible_lines.start)
+                    .take(visible_lines.len())
+                    .map(|(i, line)| {
+                        let mut spans = vec![
+                            build_line_number_span(i + 1).style(
+                                Style::default().fg(self.theme.preview_gutter),
+                            ),
+                            Span::styled(
+                                " │ ",
+                                Style::default()
+                                    .fg(self.theme.preview_gutter)
+                                    .dim(),
+                            ),
+                        ];
+                        spans.extend(ansi_spans(
+                            &line.text,
+                            &line.styles,
+                            &[],
+                            Style::default().fg(self.theme.preview_content),
+                            Style::default(),
+                        ));
+                        Line::from(spans)
+                    })
+                    .collect();
+                Paragraph::new(Text::from(lines)).block(preview_block)
             }
             // meta
             PreviewContent::Loading => self
@@ -298,36 +525,87 @@ fn build_line_number_span<'a>(line_number: usize) -> Span<'a> {
     Span::from(format!("{line_number:5} "))
 }
 
-fn compute_paragraph_from_highlighted_lines(
-    highlighted_lines: &[Vec<(syntect::highlighting::Style, String)>],
+/// The paragraph of the given lines of a hexdump, with the offsets in the
+/// gutter.
+fn build_hexdump_paragraph(
+    hexdump: &Hexdump,
+    visible_lines: std::ops::Range<usize>,
+    theme: &Theme,
+) -> Paragraph<'static> {
+    let mut lines: Vec<Line> = hexdump
+        .lines(visible_lines.clone())
+        .into_iter()
+        .map(|line| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:08x}", line.offset),
+                    Style::default().fg(theme.preview_gutter),
+                ),
+                Span::styled(
+                    " │ ",
+                    Style::default().fg(theme.preview_gutter).dim(),
+                ),
+                Span::styled(
+                    line.hex,
+                    Style::default().fg(theme.preview_content),
+                ),
+                Span::styled(
+                    " │ ",
+                    Style::default().fg(theme.preview_gutter).dim(),
+                ),
+                Span::styled(
+                    line.ascii,
+                    Style::default().fg(theme.preview_content).dim(),
+                ),
+            ])
+        })
+        .collect();
+    if hexdump.is_truncated() && visible_lines.contains(&hexdump.len()) {
+        lines.push(
+            Line::from(format!(
+                "(only the first {} of {} are shown)",
+                format::size(hexdump.size()),
+                format::size(hexdump.file_size())
+            ))
+            .style(
+                Style::default()
+                    .fg(theme.preview_gutter)
+                    .add_modifier(Modifier::ITALIC),
+            ),
+        );
+    }
+    Paragraph::new(Text::from(lines))
+}
+
+#[cfg(feature = "syntax-highlighting")]
+/// The paragraph of the given highlighted lines, the first of which is the
+/// line at the given index in the preview.
+fn compute_paragraph_from_highlighted_lines<'a>(
+    highlighted_lines: impl IntoIterator<Item = &'a HighlightedLine>,
+    first_line: usize,
     line_specifier: Option<usize>,
-    scroll: u16,
-    preview_pane_height: u16,
+    theme: &Theme,
 ) -> Paragraph<'static> {
     let preview_lines: Vec<Line> = highlighted_lines
-        .iter()
+        .into_iter()
         .enumerate()
         .map(|(i, l)| {
-            if i < scroll as usize
-                || i >= (scroll + preview_pane_height) as usize
-            {
-                return Line::from(Span::raw(EMPTY_STRING));
-            }
+            let i = first_line + i;
             let line_number =
                 build_line_number_span(i + 1).style(Style::default().fg(
                     if line_specifier.is_some()
                         && i == line_specifier.unwrap() - 1
                     {
-                        DEFAULT_PREVIEW_GUTTER_SELECTED_FG
+                        theme.preview_gutter_selected
                     } else {
-                        DEFAULT_PREVIEW_GUTTER_FG
+                        theme.preview_gutter
                     },
                 ));
             Line::from_iter(
                 std::iter::once(line_number)
                     .chain(std::iter::once(Span::styled(
                         " │ ",
-                        Style::default().fg(DEFAULT_PREVIEW_GUTTER_FG).dim(),
+                        Style::default().fg(theme.preview_gutter).dim(),
                     )))
                     .chain(l.iter().cloned().map(|sr| {
                         convert_syn_region_to_span(
@@ -335,12 +613,7 @@ fn compute_paragraph_from_highlighted_lines(
                             if line_specifier.is_some()
                                 && i == line_specifier.unwrap() - 1
                             {
-                                Some(SyntectColor {
-                                    r: 50,
-                                    g: 50,
-                                    b: 50,
-                                    a: 255,
-                                })
+                                Some(theme.preview_selection_bg)
                             } else {
                                 None
                             },
@@ -353,14 +626,15 @@ fn compute_paragraph_from_highlighted_lines(
     Paragraph::new(preview_lines)
 }
 
+#[cfg(feature = "syntax-highlighting")]
 pub fn convert_syn_region_to_span<'a>(
     syn_region: &(syntect::highlighting::Style, String),
-    background: Option<syntect::highlighting::Color>,
+    background: Option<Color>,
 ) -> Span<'a> {
     let mut style = Style::default()
         .fg(convert_syn_color_to_ratatui_color(syn_region.0.foreground));
     if let Some(background) = background {
-        style = style.bg(convert_syn_color_to_ratatui_color(background));
+        style = style.bg(background);
     }
     style = match syn_region.0.font_style {
         syntect::highlighting::FontStyle::BOLD => style.bold(),
@@ -371,6 +645,7 @@ pub fn convert_syn_region_to_span<'a>(
     Span::styled(syn_region.1.clone(), style)
 }
 
+#[cfg(feature = "syntax-highlighting")]
 fn convert_syn_color_to_ratatui_color(
     color: syntect::highlighting::Color,
 ) -> Color {
@@ -0,0 +1,193 @@
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Clear, Padding, Paragraph,
+};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::ui::input::actions::InputActionHandler;
+use crate::ui::input::Input;
+
+const PROMPT_COLOR: Color = Color::LightBlue;
+const PROMPT_WIDTH: u16 = 60;
+/// How many completion candidates are listed below the input.
+const MAX_DISPLAYED_CANDIDATES: usize = 5;
+
+/// Lists the completion candidates of a partial value.
+pub type Completer = fn(&str) -> Vec<String>;
+
+/// A one-line input popup asking for the value needed by an action of type
+/// `T` (e.g. the new name of a renamed file), which is carried out with the
+/// submitted value.
+#[derive(Debug, Clone)]
+pub struct Prompt<T> {
+    pub title: String,
+    pub input: Input,
+    pub action: T,
+    completer: Option<Completer>,
+    /// The candidates of the last completion, when there were several.
+    candidates: Vec<String>,
+}
+
+impl<T> Prompt<T> {
+    pub fn new(title: String, value: String, action: T) -> Self {
+        Self {
+            title,
+            input: Input::new(value),
+            action,
+            completer: None,
+            candidates: Vec::new(),
+        }
+    }
+
+    /// Complete the value with the candidates listed by the given function
+    /// (on tab).
+    #[must_use]
+    pub fn with_completion(mut self, completer: Completer) -> Self {
+        self.completer = Some(completer);
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        self.input.value()
+    }
+
+    /// Apply an input action (typing, moving the cursor, ...) to the value.
+    pub fn handle_action(&mut self, action: &Action) {
+        if self.input.handle_action(action).is_some() {
+            self.candidates.clear();
+        }
+    }
+
+    /// Complete the value up to the longest prefix shared by its candidates,
+    /// listing them if there are several.
+    pub fn complete(&mut self) {
+        let Some(completer) = self.completer else {
+            return;
+        };
+        let candidates = completer(self.value());
+        if let Some(prefix) = common_prefix(&candidates) {
+            if prefix.len() > self.value().len() {
+                self.input = Input::new(prefix.to_string());
+            }
+        }
+        self.candidates = if candidates.len() > 1 {
+            candidates
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Draw the prompt in the middle of the given area.
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let width = PROMPT_WIDTH.min(area.width);
+        let displayed_candidates =
+            self.candidates.len().min(MAX_DISPLAYED_CANDIDATES);
+        // the input, the candidates (and how many more there are) and the
+        // borders
+        let height = (u16::try_from(displayed_candidates).unwrap_or(0)
+            + u16::from(self.candidates.len() > MAX_DISPLAYED_CANDIDATES)
+            + 3)
+        .min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        let block = Block::default()
+            .title_top(
+                Line::from(format!(" {} ", self.title))
+                    .alignment(Alignment::Center),
+            )
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(PROMPT_COLOR))
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(popup);
+        let input_width = usize::from(inner.width.max(1) - 1);
+        let scroll = self.input.visual_scroll(input_width);
+        let mut lines = vec![Line::from(
+            self.value().chars().skip(scroll).collect::<String>(),
+        )
+        .bold()];
+        lines.extend(
+            self.candidates.iter().take(MAX_DISPLAYED_CANDIDATES).map(
+                |candidate| Line::from(Span::raw(candidate.as_str()).dim()),
+            ),
+        );
+        if self.candidates.len() > MAX_DISPLAYED_CANDIDATES {
+            lines.push(
+                Line::from(format!(
+                    "({} more)",
+                    self.candidates.len() - MAX_DISPLAYED_CANDIDATES
+                ))
+                .dim()
+                .italic(),
+            );
+        }
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+        f.set_cursor_position((
+            inner.x
+                + u16::try_from(self.input.visual_cursor() - scroll)
+                    .unwrap_or(0),
+            inner.y,
+        ));
+    }
+}
+
+/// The longest prefix shared by the given strings, if any.
+fn common_prefix(strings: &[String]) -> Option<&str> {
+    let (first, rest) = strings.split_first()?;
+    let mut len = first.len();
+    for s in rest {
+        len = first
+            .char_indices()
+            .zip(s.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, c), _)| i + c.len_utf8())
+            .min(len);
+    }
+    Some(&first[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planets(partial: &str) -> Vec<String> {
+        ["mars", "mercury", "venus"]
+            .iter()
+            .filter(|planet| planet.starts_with(partial))
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_complete() {
+        let mut prompt =
+            Prompt::new("Planet".to_string(), "m".to_string(), ())
+                .with_completion(planets);
+        prompt.complete();
+        assert_eq!(prompt.value(), "m");
+        assert_eq!(prompt.candidates, ["mars", "mercury"]);
+
+        prompt.handle_action(&Action::AddInputChar('e'));
+        assert!(prompt.candidates.is_empty());
+        prompt.complete();
+        assert_eq!(prompt.value(), "mercury");
+        assert!(prompt.candidates.is_empty());
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        let strings = ["ménage", "ménagerie", "mé"].map(String::from);
+        assert_eq!(common_prefix(&strings), Some("mé"));
+        assert_eq!(common_prefix(&strings[..2]), Some("ménage"));
+        assert_eq!(common_prefix(&[]), None);
+    }
+}
@@ -1,8 +1,7 @@
-use crate::television::Television;
+use crate::television::{Mode, Television};
 use crate::ui::logo::build_remote_logo_paragraph;
 use crate::ui::mode::mode_color;
-use crate::ui::results::{build_results_list, ResultsListColors};
-use crate::ui::BORDER_COLOR;
+use crate::ui::results::{build_results_list, ResultLines, ResultsListColors};
 use color_eyre::eyre::Result;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::prelude::Style;
@@ -33,7 +32,7 @@ impl Television {
             .split(*area);
         self.draw_rc_channels(f, &layout[0])?;
         self.draw_rc_input(f, &layout[1])?;
-        draw_rc_logo(f, layout[2], mode_color(self.mode));
+        draw_rc_logo(f, layout[2], mode_color(self.mode()));
         Ok(())
     }
 
@@ -41,7 +40,7 @@ impl Television {
         let rc_block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(BORDER_COLOR))
+            .border_style(Style::default().fg(self.theme.border))
             .style(Style::default())
             .padding(Padding::right(1));
 
@@ -51,20 +50,24 @@ impl Television {
             self.rc_picker.relative_select(Some(0));
         }
 
-        let entries = self.remote_control.results(
-            area.height.saturating_sub(2).into(),
-            u32::try_from(self.rc_picker.view_offset)?,
+        let result_lines = ResultLines::new(
+            ResultsListColors::from(&self.theme)
+                .result_name_fg(mode_color(self.mode())),
+            self.config.ui.use_nerd_font_icons,
+        );
+        let offset = u32::try_from(self.rc_picker.view_offset)?;
+        let mut lines = Vec::new();
+        self.remote_control.for_each_result(
+            offset..offset + u32::from(area.height.saturating_sub(2)),
+            &mut |entry| lines.push(result_lines.line(entry)),
         );
 
         let channel_list = build_results_list(
             rc_block,
-            &entries,
+            lines,
             ListDirection::TopToBottom,
-            Some(
-                ResultsListColors::default()
-                    .result_name_fg(mode_color(self.mode)),
-            ),
-            self.config.ui.use_nerd_font_icons,
+            self.theme.selection_bg,
+            &self.config.ui.pointer,
         );
 
         f.render_stateful_widget(
@@ -76,38 +79,40 @@ impl Television {
     }
 
     fn draw_rc_input(&mut self, f: &mut Frame, area: &Rect) -> Result<()> {
+        let title = match self.mode() {
+            Mode::OpenWith => "Open with",
+            Mode::FileTypes => "File types",
+            _ => "Remote Control",
+        };
         let input_block = Block::default()
-            .title_top(
-                Line::from("Remote Control").alignment(Alignment::Center),
-            )
+            .title_top(Line::from(title).alignment(Alignment::Center))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(BORDER_COLOR))
+            .border_style(Style::default().fg(self.theme.border))
             .style(Style::default());
 
         let input_block_inner = input_block.inner(*area);
 
         f.render_widget(input_block, *area);
 
+        let prompt = Span::styled(
+            self.config.ui.prompt.clone(),
+            Style::default().fg(self.theme.prompt).bold(),
+        );
+
         // split input block into 2 parts: prompt symbol, input
         let inner_input_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([
                 // prompt symbol
-                Constraint::Length(2),
+                Constraint::Length(u16::try_from(prompt.width())?),
                 // input field
                 Constraint::Fill(1),
             ])
             .split(input_block_inner);
 
         let prompt_symbol_block = Block::default();
-        let arrow = Paragraph::new(Span::styled(
-            "> ",
-            Style::default()
-                .fg(crate::television::DEFAULT_INPUT_FG)
-                .bold(),
-        ))
-        .block(prompt_symbol_block);
+        let arrow = Paragraph::new(prompt).block(prompt_symbol_block);
         f.render_widget(arrow, inner_input_chunks[0]);
 
         let interactive_input_block = Block::default();
@@ -117,12 +122,7 @@ impl Television {
         let input = Paragraph::new(self.rc_picker.input.value())
             .scroll((0, u16::try_from(scroll)?))
             .block(interactive_input_block)
-            .style(
-                Style::default()
-                    .fg(crate::television::DEFAULT_INPUT_FG)
-                    .bold()
-                    .italic(),
-            )
+            .style(Style::default().fg(self.theme.prompt).bold().italic())
             .alignment(Alignment::Left);
         f.render_widget(input, inner_input_chunks[1]);
 
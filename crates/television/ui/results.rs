@@ -1,40 +1,50 @@
 use crate::television::Television;
+use crate::ui::ansi::ansi_spans;
 use crate::ui::layout::Layout;
-use crate::ui::BORDER_COLOR;
+use crate::ui::theme::Theme;
 use color_eyre::eyre::Result;
 use ratatui::layout::Alignment;
-use ratatui::prelude::{Color, Line, Span, Style};
+use ratatui::prelude::{Color, Line, Span, Style, Stylize};
 use ratatui::widgets::{
     Block, BorderType, Borders, List, ListDirection, Padding,
 };
 use ratatui::Frame;
 use std::str::FromStr;
+use television_channels::annotations::Annotations;
 use television_channels::channels::OnAir;
-use television_channels::entry::Entry;
-use television_utils::strings::{
-    next_char_boundary, slice_at_char_boundaries,
-};
+use television_channels::entry::EntryRef;
+use television_channels::pins::Pins;
+use television_channels::selection::Selection;
+use television_utils::indices::byte_ranges;
 
 // Styles
-const DEFAULT_RESULT_NAME_FG: Color = Color::Blue;
-const DEFAULT_RESULT_PREVIEW_FG: Color = Color::Rgb(150, 150, 150);
-const DEFAULT_RESULT_LINE_NUMBER_FG: Color = Color::Yellow;
-const DEFAULT_RESULT_SELECTED_BG: Color = Color::Rgb(50, 50, 50);
+const SKIPPED_ENTRIES_FG: Color = Color::Yellow;
+
+/// Displayed before pinned entries.
+const PIN_MARKER: &str = "📌 ";
 
 pub struct ResultsListColors {
     pub result_name_fg: Color,
     pub result_preview_fg: Color,
     pub result_line_number_fg: Color,
     pub result_selected_bg: Color,
+    pub result_match: Style,
 }
 
 impl Default for ResultsListColors {
     fn default() -> Self {
+        Self::from(&Theme::default())
+    }
+}
+
+impl From<&Theme> for ResultsListColors {
+    fn from(theme: &Theme) -> Self {
         Self {
-            result_name_fg: DEFAULT_RESULT_NAME_FG,
-            result_preview_fg: DEFAULT_RESULT_PREVIEW_FG,
-            result_line_number_fg: DEFAULT_RESULT_LINE_NUMBER_FG,
-            result_selected_bg: DEFAULT_RESULT_SELECTED_BG,
+            result_name_fg: theme.result_name,
+            result_preview_fg: theme.result_value,
+            result_line_number_fg: theme.result_line_number,
+            result_selected_bg: theme.selection_bg,
+            result_match: theme.matched,
         }
     }
 }
@@ -62,22 +72,55 @@ impl ResultsListColors {
     }
 }
 
-pub fn build_results_list<'a, 'b>(
-    results_block: Block<'b>,
-    entries: &'a [Entry],
-    list_direction: ListDirection,
-    results_list_colors: Option<ResultsListColors>,
-    use_icons: bool,
-) -> List<'a>
-where
-    'b: 'a,
-{
-    let results_list_colors = results_list_colors.unwrap_or_default();
-    List::new(entries.iter().map(|entry| {
+/// Builds the lines of a results list, one per entry.
+pub struct ResultLines<'a> {
+    pub colors: ResultsListColors,
+    pub use_icons: bool,
+    /// Whether the values of the entries are displayed after their name.
+    pub with_values: bool,
+    pub annotations: Option<&'a Annotations>,
+    pub pins: Option<&'a Pins>,
+    /// The selected entries and the marker displayed before them.
+    pub selection: Option<(&'a Selection, &'a str)>,
+}
+
+impl ResultLines<'_> {
+    pub fn new(colors: ResultsListColors, use_icons: bool) -> Self {
+        Self {
+            colors,
+            use_icons,
+            with_values: true,
+            annotations: None,
+            pins: None,
+            selection: None,
+        }
+    }
+
+    /// The line displaying the given entry, which doesn't borrow from it so
+    /// that entries lent by the channels (see `OnAir::for_each_result`) can
+    /// be displayed.
+    pub fn line(&self, entry: &EntryRef<'_>) -> Line<'static> {
+        let colors = &self.colors;
         let mut spans = Vec::new();
+        // selection marker
+        if let Some((_, marker)) = self
+            .selection
+            .filter(|(selection, _)| selection.contains(*entry))
+        {
+            spans.push(Span::styled(
+                marker.to_string(),
+                Style::default().fg(colors.result_line_number_fg),
+            ));
+        }
+        // pin marker
+        if self.pins.is_some_and(|pins| pins.contains(*entry)) {
+            spans.push(Span::styled(
+                PIN_MARKER,
+                Style::default().fg(colors.result_line_number_fg),
+            ));
+        }
         // optional icon
-        if entry.icon.is_some() && use_icons {
-            let icon = entry.icon.as_ref().unwrap();
+        if let Some(icon) = entry.icon.as_ref().filter(|_| self.use_icons) {
             spans.push(Span::styled(
                 icon.to_string(),
                 Style::default().fg(Color::from_str(icon.color).unwrap()),
@@ -85,93 +128,98 @@ where
             spans.push(Span::raw(" "));
         }
         // entry name
-        if let Some(name_match_ranges) = &entry.name_match_ranges {
-            let mut last_match_end = 0;
-            for (start, end) in name_match_ranges
-                .iter()
-                .map(|(s, e)| (*s as usize, *e as usize))
-            {
-                spans.push(Span::styled(
-                    slice_at_char_boundaries(
-                        &entry.name,
-                        last_match_end,
-                        start,
-                    ),
-                    Style::default().fg(results_list_colors.result_name_fg),
-                ));
-                spans.push(Span::styled(
-                    slice_at_char_boundaries(&entry.name, start, end),
-                    Style::default().fg(Color::Red),
-                ));
-                last_match_end = end;
-            }
-            spans.push(Span::styled(
-                &entry.name[next_char_boundary(&entry.name, last_match_end)..],
-                Style::default().fg(results_list_colors.result_name_fg),
+        if let Some(styles) = entry.name_styles {
+            spans.extend(ansi_spans(
+                entry.name,
+                styles,
+                entry.name_match_ranges.unwrap_or_default(),
+                Style::default().fg(colors.result_name_fg),
+                colors.result_match,
             ));
+        } else if let Some(name_match_ranges) = entry.name_match_ranges {
+            push_matched_spans(
+                &mut spans,
+                entry.name,
+                name_match_ranges,
+                Style::default().fg(colors.result_name_fg),
+                colors.result_match,
+            );
         } else {
             spans.push(Span::styled(
-                entry.display_name(),
-                Style::default().fg(results_list_colors.result_name_fg),
+                entry.display_name().to_string(),
+                Style::default().fg(colors.result_name_fg),
             ));
         }
         // optional line number
         if let Some(line_number) = entry.line_number {
             spans.push(Span::styled(
                 format!(":{line_number}"),
-                Style::default().fg(results_list_colors.result_line_number_fg),
+                Style::default().fg(colors.result_line_number_fg),
             ));
         }
         // optional preview
-        if let Some(preview) = &entry.value {
+        if let Some(preview) = entry.value.filter(|_| self.with_values) {
             spans.push(Span::raw(": "));
-
-            if let Some(preview_match_ranges) = &entry.value_match_ranges {
-                if !preview_match_ranges.is_empty() {
-                    let mut last_match_end = 0;
-                    for (start, end) in preview_match_ranges
-                        .iter()
-                        .map(|(s, e)| (*s as usize, *e as usize))
-                    {
-                        spans.push(Span::styled(
-                            slice_at_char_boundaries(
-                                preview,
-                                last_match_end,
-                                start,
-                            ),
-                            Style::default()
-                                .fg(results_list_colors.result_preview_fg),
-                        ));
-                        spans.push(Span::styled(
-                            slice_at_char_boundaries(preview, start, end),
-                            Style::default().fg(Color::Red),
-                        ));
-                        last_match_end = end;
-                    }
-                    spans.push(Span::styled(
-                        &preview[next_char_boundary(
-                            preview,
-                            preview_match_ranges.last().unwrap().1 as usize,
-                        )..],
-                        Style::default()
-                            .fg(results_list_colors.result_preview_fg),
-                    ));
-                }
-            } else {
-                spans.push(Span::styled(
+            let style = Style::default().fg(colors.result_preview_fg);
+            match entry.value_match_ranges {
+                Some([]) => {}
+                Some(ranges) => push_matched_spans(
+                    &mut spans,
                     preview,
-                    Style::default().fg(results_list_colors.result_preview_fg),
-                ));
+                    ranges,
+                    style,
+                    colors.result_match,
+                ),
+                None => spans.push(Span::styled(preview.to_string(), style)),
             }
         }
+        // optional note
+        if let Some(note) = self.annotations.and_then(|a| a.get(*entry)) {
+            spans.push(Span::raw(format!("  # {note}")).dim());
+        }
         Line::from(spans)
-    }))
-    .direction(list_direction)
-    .highlight_style(
-        Style::default().bg(results_list_colors.result_selected_bg),
-    )
-    .highlight_symbol("> ")
-    .block(results_block)
+    }
+}
+
+/// Push the spans of the given text, where the characters in the given
+/// ranges are highlighted.
+fn push_matched_spans(
+    spans: &mut Vec<Span<'static>>,
+    text: &str,
+    match_ranges: &[(u32, u32)],
+    style: Style,
+    match_style: Style,
+) {
+    let mut last_match_end = 0;
+    for range in byte_ranges(text, match_ranges) {
+        // the ranges overlapping the previous ones are cut short
+        let start = range.start.max(last_match_end);
+        let end = range.end.max(start);
+        spans.push(Span::styled(
+            text[last_match_end..start].to_string(),
+            style,
+        ));
+        spans.push(Span::styled(text[start..end].to_string(), match_style));
+        last_match_end = end;
+    }
+    spans.push(Span::styled(text[last_match_end..].to_string(), style));
+}
+
+pub fn build_results_list<'a, 'b>(
+    results_block: Block<'b>,
+    lines: Vec<Line<'a>>,
+    list_direction: ListDirection,
+    selected_bg: Color,
+    pointer: &'a str,
+) -> List<'a>
+where
+    'b: 'a,
+{
+    List::new(lines)
+        .direction(list_direction)
+        .highlight_style(Style::default().bg(selected_bg))
+        .highlight_symbol(pointer)
+        .block(results_block)
 }
 
 impl Television {
@@ -180,31 +228,78 @@ impl Television {
         f: &mut Frame,
         layout: &Layout,
     ) -> Result<()> {
-        let results_block = Block::default()
-            .title_top(Line::from(" Results ").alignment(Alignment::Center))
+        let mut title = String::from(" Results ");
+        if !self.selection.is_empty() {
+            title.push_str(&format!("({} selected) ", self.selection.len()));
+        }
+        if self.tail_mode() {
+            title.push_str("(tail) ");
+        }
+        let mut results_block = Block::default()
+            .title_top(Line::from(title).alignment(Alignment::Center))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(BORDER_COLOR))
+            .border_style(Style::default().fg(self.theme.border))
             .style(Style::default())
             .padding(Padding::right(1));
+        // the entries that couldn't be loaded, e.g. unreadable directories
+        if let Some(warnings) = self.channel.warnings() {
+            results_block = results_block.title_bottom(
+                Line::from(format!(" skipped: {warnings} "))
+                    .alignment(Alignment::Right)
+                    .fg(SKIPPED_ENTRIES_FG),
+            );
+        }
 
         let result_count = self.channel.result_count();
         if result_count > 0 && self.results_picker.selected().is_none() {
             self.results_picker.select(Some(0));
             self.results_picker.relative_select(Some(0));
         }
-
-        let entries = self.channel.results(
-            layout.results.height.saturating_sub(2).into(),
-            u32::try_from(self.results_picker.view_offset)?,
-        );
+        self.update_promoted_results();
+        if self.tail_mode() {
+            self.select_newest_entry();
+        } else {
+            self.keep_selected_entry();
+        }
+        let num_entries: u32 = layout.results.height.saturating_sub(2).into();
+        let offset = u32::try_from(self.results_picker.view_offset)?;
+        // the channel lends the results to display, unless pinned and
+        // recent entries are moved first
+        let promoted_entries = self
+            .promotes_results()
+            .then(|| self.displayed_results(num_entries, offset));
+        let result_lines = ResultLines {
+            // values are dropped on narrow terminals
+            with_values: !self.narrow,
+            annotations: Some(&self.annotations),
+            pins: Some(&self.pins),
+            selection: Some((&self.selection, &self.config.ui.marker)),
+            ..ResultLines::new(
+                ResultsListColors::from(&self.theme),
+                self.config.ui.use_nerd_font_icons,
+            )
+        };
+        let mut lines = Vec::with_capacity(num_entries as usize);
+        if let Some(entries) = &promoted_entries {
+            lines.extend(
+                entries.iter().map(|entry| result_lines.line(&entry.into())),
+            );
+        } else {
+            self.channel.for_each_result(
+                offset..offset + num_entries,
+                &mut |entry| {
+                    lines.push(result_lines.line(entry));
+                },
+            );
+        }
 
         let results_list = build_results_list(
             results_block,
-            &entries,
+            lines,
             ListDirection::BottomToTop,
-            None,
-            self.config.ui.use_nerd_font_icons,
+            self.theme.selection_bg,
+            &self.config.ui.pointer,
         );
 
         f.render_stateful_widget(
@@ -215,3 +310,45 @@ impl Television {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_push_matched_spans(
+            text in "\\PC{0,16}",
+            indices in proptest::collection::btree_set(0u32..20, 0..8),
+        ) {
+            let match_style = Style::default().fg(Color::Red);
+            let match_ranges: Vec<(u32, u32)> =
+                indices.iter().map(|&i| (i, i + 1)).collect();
+            let mut spans = Vec::new();
+            push_matched_spans(
+                &mut spans,
+                &text,
+                &match_ranges,
+                Style::default(),
+                match_style,
+            );
+            let rendered: String =
+                spans.iter().map(|span| span.content.as_ref()).collect();
+            prop_assert_eq!(&rendered, &text);
+            // exactly the characters at the matched indices are highlighted
+            let highlighted: String = spans
+                .iter()
+                .filter(|span| span.style == match_style)
+                .map(|span| span.content.as_ref())
+                .collect();
+            let expected: String = text
+                .chars()
+                .enumerate()
+                .filter(|(i, _)| indices.contains(&(*i as u32)))
+                .map(|(_, c)| c)
+                .collect();
+            prop_assert_eq!(highlighted, expected);
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::config::ThemeConfig;
+
+/// The theme used when none is configured, and the base of the user
+/// defined themes that don't name one.
+pub const DEFAULT_THEME: &str = "default";
+
+/// The names of the built-in themes.
+pub const BUILTIN_THEMES: &[&str] =
+    &["default", "light", "gruvbox", "catppuccin"];
+
+/// The colors and styles of the UI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub border: Color,
+    /// The input prompt and the text typed in it.
+    pub prompt: Color,
+    pub results_count: Color,
+    pub result_name: Color,
+    pub result_value: Color,
+    pub result_line_number: Color,
+    pub selection_bg: Color,
+    /// The parts of the entries matching the pattern.
+    pub matched: Style,
+    pub preview_title: Color,
+    pub preview_content: Color,
+    pub preview_gutter: Color,
+    pub preview_gutter_selected: Color,
+    pub preview_selection_bg: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            border: Color::Blue,
+            prompt: Color::LightRed,
+            results_count: Color::LightRed,
+            result_name: Color::Blue,
+            result_value: Color::Rgb(150, 150, 150),
+            result_line_number: Color::Yellow,
+            selection_bg: Color::Rgb(50, 50, 50),
+            matched: Style::default().fg(Color::Red),
+            preview_title: Color::Blue,
+            preview_content: Color::Rgb(150, 150, 180),
+            preview_gutter: Color::Rgb(70, 70, 70),
+            preview_gutter_selected: Color::Rgb(255, 150, 150),
+            preview_selection_bg: Color::Rgb(50, 50, 50),
+        }
+    }
+}
+
+impl Theme {
+    /// The built-in theme with the given name, if any.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "light" => Some(Self {
+                border: Color::Blue,
+                prompt: Color::Red,
+                results_count: Color::Red,
+                result_name: Color::Blue,
+                result_value: Color::Rgb(90, 90, 90),
+                result_line_number: Color::Rgb(170, 110, 0),
+                selection_bg: Color::Rgb(220, 220, 220),
+                matched: Style::default()
+                    .fg(Color::Red)
+                    .add_modifier(Modifier::BOLD),
+                preview_title: Color::Blue,
+                preview_content: Color::Rgb(60, 60, 80),
+                preview_gutter: Color::Rgb(170, 170, 170),
+                preview_gutter_selected: Color::Rgb(200, 60, 60),
+                preview_selection_bg: Color::Rgb(225, 225, 225),
+            }),
+            "gruvbox" => Some(Self {
+                border: Color::Rgb(131, 165, 152),
+                prompt: Color::Rgb(254, 128, 25),
+                results_count: Color::Rgb(254, 128, 25),
+                result_name: Color::Rgb(131, 165, 152),
+                result_value: Color::Rgb(168, 153, 132),
+                result_line_number: Color::Rgb(250, 189, 47),
+                selection_bg: Color::Rgb(60, 56, 54),
+                matched: Style::default()
+                    .fg(Color::Rgb(251, 73, 52))
+                    .add_modifier(Modifier::BOLD),
+                preview_title: Color::Rgb(142, 192, 124),
+                preview_content: Color::Rgb(235, 219, 178),
+                preview_gutter: Color::Rgb(102, 92, 84),
+                preview_gutter_selected: Color::Rgb(251, 73, 52),
+                preview_selection_bg: Color::Rgb(60, 56, 54),
+            }),
+            "catppuccin" => Some(Self {
+                border: Color::Rgb(137, 180, 250),
+                prompt: Color::Rgb(243, 139, 168),
+                results_count: Color::Rgb(243, 139, 168),
+                result_name: Color::Rgb(137, 180, 250),
+                result_value: Color::Rgb(166, 173, 200),
+                result_line_number: Color::Rgb(249, 226, 175),
+                selection_bg: Color::Rgb(49, 50, 68),
+                matched: Style::default()
+                    .fg(Color::Rgb(250, 179, 135))
+                    .add_modifier(Modifier::BOLD),
+                preview_title: Color::Rgb(203, 166, 247),
+                preview_content: Color::Rgb(205, 214, 244),
+                preview_gutter: Color::Rgb(108, 112, 134),
+                preview_gutter_selected: Color::Rgb(243, 139, 168),
+                preview_selection_bg: Color::Rgb(49, 50, 68),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The theme with the given name: one of the given user defined themes
+    /// (on top of the built-in theme it's based on), else a built-in theme.
+    pub fn from_config(
+        name: &str,
+        themes: &HashMap<String, ThemeConfig>,
+    ) -> Result<Self, String> {
+        let Some(config) = themes.get(name) else {
+            return Self::builtin(name).ok_or_else(|| {
+                let mut names: Vec<&str> = BUILTIN_THEMES.to_vec();
+                names.extend(themes.keys().map(String::as_str));
+                format!(
+                    "unknown theme `{name}` (expected one of: {})",
+                    names.join(", ")
+                )
+            });
+        };
+        let base = config.base.as_deref().unwrap_or(DEFAULT_THEME);
+        let theme = Self::builtin(base).ok_or_else(|| {
+            format!(
+                "the `{name}` theme is based on the unknown `{base}` theme \
+                 (expected one of: {})",
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+        config
+            .apply(theme)
+            .map_err(|e| format!("invalid `{name}` theme: {e}"))
+    }
+}
+
+impl ThemeConfig {
+    /// The given theme with the colors and styles set by this config.
+    fn apply(&self, mut theme: Theme) -> Result<Theme, String> {
+        macro_rules! set_colors {
+            ($($field:ident),* $(,)?) => {
+                $(
+                    if let Some(value) = &self.$field {
+                        theme.$field = parse_color(stringify!($field), value)?;
+                    }
+                )*
+            };
+        }
+        set_colors!(
+            border,
+            prompt,
+            results_count,
+            result_name,
+            result_value,
+            result_line_number,
+            selection_bg,
+            preview_title,
+            preview_content,
+            preview_gutter,
+            preview_gutter_selected,
+            preview_selection_bg,
+        );
+        if let Some(value) = &self.matched {
+            theme.matched =
+                parse_style(value).map_err(|e| format!("`matched`: {e}"))?;
+        }
+        Ok(theme)
+    }
+}
+
+fn parse_color(field: &str, value: &str) -> Result<Color, String> {
+    Color::from_str(value)
+        .map_err(|_| format!("`{field}`: unknown color `{value}`"))
+}
+
+/// Parse a style made of a color and modifiers, e.g. `bold red` or
+/// `#ff8800 underlined`.
+fn parse_style(value: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    for word in value.split_whitespace() {
+        let modifier = match word {
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            _ if style.fg.is_none() => {
+                style = style.fg(Color::from_str(word).map_err(|_| {
+                    format!("unknown color or modifier `{word}`")
+                })?);
+                continue;
+            }
+            _ => return Err(format!("unknown modifier `{word}`")),
+        };
+        style = style.add_modifier(modifier);
+    }
+    Ok(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        for name in BUILTIN_THEMES {
+            assert!(Theme::builtin(name).is_some(), "{name}");
+        }
+        assert_eq!(Theme::builtin("nope"), None);
+    }
+
+    #[test]
+    fn test_theme_from_config() {
+        let themes: HashMap<String, ThemeConfig> = toml::from_str(
+            r##"
+            [mine]
+            base = "gruvbox"
+            border = "#102030"
+            prompt = "light-green"
+            matched = "italic 208 underlined"
+
+            [broken]
+            border = "not a color"
+            "##,
+        )
+        .unwrap();
+
+        let theme = Theme::from_config("mine", &themes).unwrap();
+        assert_eq!(theme.border, Color::Rgb(16, 32, 48));
+        assert_eq!(theme.prompt, Color::LightGreen);
+        assert_eq!(
+            theme.matched,
+            Style::default()
+                .fg(Color::Indexed(208))
+                .add_modifier(Modifier::ITALIC | Modifier::UNDERLINED)
+        );
+        // the other colors come from the base theme
+        assert_eq!(
+            theme.result_name,
+            Theme::builtin("gruvbox").unwrap().result_name
+        );
+
+        assert_eq!(
+            Theme::from_config("light", &themes),
+            Ok(Theme::builtin("light").unwrap())
+        );
+        assert_eq!(
+            Theme::from_config("broken", &themes),
+            Err("invalid `broken` theme: `border`: unknown color `not a \
+                 color`"
+                .to_string())
+        );
+        assert!(Theme::from_config("nope", &themes)
+            .unwrap_err()
+            .starts_with("unknown theme `nope`"));
+    }
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(parse_style(""), Ok(Style::default()));
+        assert_eq!(
+            parse_style("bold red"),
+            Ok(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        );
+        assert!(parse_style("red blue").is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Block, BorderType, Borders, Clear, Gauge, Padding, Paragraph,
+};
+use ratatui::Frame;
+
+/// How long finished toasts stay on screen once displayed.
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+/// How many toasts are displayed at once, the others waiting for their turn.
+const MAX_DISPLAYED_TOASTS: usize = 3;
+/// How many toasts can wait for their turn, the oldest ones being dropped.
+const MAX_QUEUED_TOASTS: usize = 20;
+const TOAST_WIDTH: u16 = 44;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Success,
+    Error,
+}
+
+impl ToastLevel {
+    fn color(self) -> Color {
+        match self {
+            ToastLevel::Info => Color::LightBlue,
+            ToastLevel::Success => Color::LightGreen,
+            ToastLevel::Error => Color::LightRed,
+        }
+    }
+}
+
+/// A notification, optionally reporting the progress of a long-running
+/// action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Toast {
+    /// Identifies the action reported by the toast, so that its progress
+    /// and completion update it instead of stacking new toasts.
+    pub key: Option<String>,
+    pub message: String,
+    pub level: ToastLevel,
+    /// The progress (in percent) of the action, while it is running.
+    pub progress: Option<u8>,
+    /// When the toast was displayed (or its action finished).
+    shown_at: Option<Instant>,
+}
+
+impl Toast {
+    fn new(message: String, level: ToastLevel) -> Self {
+        Self {
+            key: None,
+            message,
+            level,
+            progress: None,
+            shown_at: None,
+        }
+    }
+
+    fn height(&self) -> u16 {
+        // the message, the progress bar and the borders
+        1 + u16::from(self.progress.is_some()) + 2
+    }
+}
+
+/// The queue of the toasts displayed in the corner of the screen, dismissed
+/// automatically once their action is over.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+}
+
+impl Toasts {
+    pub fn info(&mut self, message: String) {
+        self.push(Toast::new(message, ToastLevel::Info));
+    }
+
+    pub fn success(&mut self, message: String) {
+        self.push(Toast::new(message, ToastLevel::Success));
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.push(Toast::new(message, ToastLevel::Error));
+    }
+
+    fn push(&mut self, toast: Toast) {
+        if self.queue.len() == MAX_QUEUED_TOASTS {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(toast);
+    }
+
+    /// Report the progress of the action with the given key, the toast
+    /// staying on screen until the action finishes.
+    pub fn progress(&mut self, key: &str, message: String, percent: u8) {
+        match self.get_mut(key) {
+            Some(toast) => {
+                toast.message = message;
+                toast.progress = Some(percent.min(100));
+                // e.g. the action was run again before the toast reporting
+                // its completion was dismissed
+                toast.level = ToastLevel::Info;
+                toast.shown_at = None;
+            }
+            None => {
+                let mut toast = Toast::new(message, ToastLevel::Info);
+                toast.key = Some(key.to_string());
+                toast.progress = Some(percent.min(100));
+                self.push(toast);
+            }
+        }
+    }
+
+    /// Report the completion of the action with the given key.
+    pub fn finish(&mut self, key: &str, message: String, level: ToastLevel) {
+        match self.get_mut(key) {
+            Some(toast) => {
+                toast.message = message;
+                toast.level = level;
+                toast.progress = None;
+                toast.shown_at = None;
+            }
+            None => self.push(Toast::new(message, level)),
+        }
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Toast> {
+        self.queue
+            .iter_mut()
+            .find(|toast| toast.key.as_deref() == Some(key))
+    }
+
+    /// Dismiss the displayed toasts whose time is up, making room for the
+    /// next ones.
+    pub fn tick(&mut self, now: Instant) {
+        self.queue.retain(|toast| {
+            toast.shown_at.is_none_or(|shown_at| {
+                now.duration_since(shown_at) < TOAST_DURATION
+            })
+        });
+        for toast in self.queue.iter_mut().take(MAX_DISPLAYED_TOASTS) {
+            if toast.progress.is_none() && toast.shown_at.is_none() {
+                toast.shown_at = Some(now);
+            }
+        }
+    }
+
+    /// The toasts currently displayed.
+    fn displayed(&self) -> impl Iterator<Item = &Toast> {
+        self.queue.iter().take(MAX_DISPLAYED_TOASTS)
+    }
+
+    /// Draw the displayed toasts in the bottom right corner of the given
+    /// area, the oldest one at the bottom.
+    pub fn draw(&self, f: &mut Frame, area: Rect) {
+        let width = TOAST_WIDTH.min(area.width);
+        let mut bottom = area.bottom();
+        for toast in self.displayed() {
+            let height = toast.height();
+            if bottom < area.y + height {
+                break;
+            }
+            bottom -= height;
+            let toast_area =
+                Rect::new(area.right() - width, bottom, width, height);
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(toast.level.color()))
+                .padding(Padding::horizontal(1));
+            let inner = block.inner(toast_area);
+            f.render_widget(Clear, toast_area);
+            f.render_widget(block, toast_area);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Length(1)])
+                .split(inner);
+            f.render_widget(
+                Paragraph::new(Line::from(toast.message.as_str())),
+                chunks[0],
+            );
+            if let Some(percent) = toast.progress {
+                f.render_widget(
+                    Gauge::default()
+                        .gauge_style(Style::default().fg(toast.level.color()))
+                        .percent(percent.into()),
+                    chunks[1],
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_and_dismiss() {
+        let mut toasts = Toasts::default();
+        let now = Instant::now();
+        toasts.progress("sum", "Computing".to_string(), 10);
+        toasts.progress("sum", "Computing".to_string(), 50);
+        assert_eq!(toasts.queue.len(), 1);
+        // running actions aren't dismissed
+        toasts.tick(now);
+        toasts.tick(now + TOAST_DURATION * 2);
+        assert_eq!(toasts.queue[0].progress, Some(50));
+
+        toasts.finish("sum", "Done".to_string(), ToastLevel::Success);
+        toasts.tick(now + TOAST_DURATION * 2);
+        assert_eq!(toasts.queue[0].message, "Done");
+        toasts.tick(now + TOAST_DURATION * 3);
+        assert!(toasts.queue.is_empty());
+    }
+
+    #[test]
+    fn test_queue() {
+        let mut toasts = Toasts::default();
+        let now = Instant::now();
+        for i in 0..MAX_DISPLAYED_TOASTS + 1 {
+            toasts.info(i.to_string());
+        }
+        toasts.tick(now);
+        assert_eq!(toasts.displayed().count(), MAX_DISPLAYED_TOASTS);
+        // the queued toast is displayed once the others are dismissed
+        toasts.tick(now + TOAST_DURATION);
+        assert_eq!(toasts.queue.len(), 1);
+        toasts.tick(now + TOAST_DURATION * 2 - Duration::from_millis(1));
+        assert_eq!(toasts.queue.len(), 1);
+        toasts.tick(now + TOAST_DURATION * 2);
+        assert!(toasts.queue.is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! Undoing the reversible actions of the session (hiding entries, renaming
+//! and deleting files).
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+use television_utils::dirs::get_data_dir;
+use tracing::{debug, warn};
+
+/// How many actions can be undone.
+const MAX_UNDO_STEPS: usize = 50;
+
+/// An action that was carried out and can be reverted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Undoable {
+    /// An entry was hidden from the files and text channels.
+    Hide(String),
+    /// An entry was un-hidden from the hidden entries channel.
+    Unhide(String),
+    /// A file was renamed.
+    Rename { from: PathBuf, to: PathBuf },
+    /// A file was deleted, i.e. moved to the trash of the session.
+    Delete { path: PathBuf, trashed: PathBuf },
+}
+
+impl Undoable {
+    /// What undoing the action does, e.g. to report it.
+    pub fn description(&self) -> String {
+        match self {
+            Undoable::Hide(entry) => format!("Un-hid {entry}"),
+            Undoable::Unhide(entry) => format!("Hid {entry} again"),
+            Undoable::Rename { from, to } => {
+                format!("Renamed {} back to {}", to.display(), from.display())
+            }
+            Undoable::Delete { path, .. } => {
+                format!("Restored {}", path.display())
+            }
+        }
+    }
+}
+
+/// The actions of the session that can be undone, most recent last.
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    actions: Vec<Undoable>,
+}
+
+impl UndoStack {
+    pub fn push(&mut self, action: Undoable) {
+        if self.actions.len() == MAX_UNDO_STEPS {
+            let forgotten = self.actions.remove(0);
+            // there is no restoring it anymore
+            if let Undoable::Delete { trashed, .. } = forgotten {
+                let _ = std::fs::remove_file(trashed);
+            }
+        }
+        self.actions.push(action);
+    }
+
+    pub fn pop(&mut self) -> Option<Undoable> {
+        self.actions.pop()
+    }
+}
+
+/// The directory deleted files are moved to, so that their deletion can be
+/// undone until `tv` exits.
+///
+/// It lives in the data directory rather than in the temporary one, which is
+/// often on another file system, so that files are moved rather than copied.
+fn trash_dir() -> PathBuf {
+    get_data_dir()
+        .join("trash")
+        .join(std::process::id().to_string())
+}
+
+/// Move the given file to the trash of the session, returning its new path,
+/// or delete it right away (returning `None`) if it can't be moved there.
+pub fn trash(path: &Path) -> Result<Option<PathBuf>> {
+    let dir = trash_dir();
+    std::fs::create_dir_all(&dir)?;
+    // files with the same name may be deleted from different directories
+    let trashed = (0..)
+        .map(|i| {
+            dir.join(format!(
+                "{i}-{}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ))
+        })
+        .find(|trashed| !trashed.exists())
+        .expect("an unused trash path");
+    match std::fs::rename(path, &trashed) {
+        Ok(()) => {
+            debug!("Moved {:?} to {:?}", path, trashed);
+            Ok(Some(trashed))
+        }
+        Err(e) => {
+            warn!("Failed to move {:?} to the trash: {:?}", path, e);
+            std::fs::remove_file(path)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Permanently delete the files of the session's trash.
+pub fn empty_trash() {
+    let dir = trash_dir();
+    if dir.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            warn!("Failed to empty the trash {:?}: {:?}", dir, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_stack() {
+        let mut stack = UndoStack::default();
+        for i in 0..=MAX_UNDO_STEPS {
+            stack.push(Undoable::Hide(i.to_string()));
+        }
+        assert_eq!(stack.actions.len(), MAX_UNDO_STEPS);
+        assert_eq!(
+            stack.pop(),
+            Some(Undoable::Hide(MAX_UNDO_STEPS.to_string()))
+        );
+        // the oldest action was forgotten
+        assert_eq!(stack.actions[0], Undoable::Hide("1".to_string()));
+    }
+}
@@ -0,0 +1,289 @@
+//! Updating the `tv` binary to the latest GitHub release (see `tv update`).
+//!
+//! The release archives and their `.sha256` checksums are downloaded with
+//! `curl`, the checksum is verified and the binary extracted next to the
+//! running one before being renamed over it, so an interrupted update never
+//! leaves a broken binary behind. Builds installed by a package manager are
+//! left to it.
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+
+use color_eyre::eyre::{bail, eyre, Result};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use television_utils::checksum::sha256;
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/alexpasmantier/television/releases/latest";
+
+#[cfg(windows)]
+const BINARY_NAME: &str = "tv.exe";
+#[cfg(not(windows))]
+const BINARY_NAME: &str = "tv";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Check for a newer release and install it, or only report it if `check`
+/// is set.
+pub fn run(check: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release: Release = serde_json::from_slice(&curl(LATEST_RELEASE_URL)?)?;
+    if !is_newer(&release.tag_name, current) {
+        println!("tv {current} is up to date");
+        return Ok(());
+    }
+    println!(
+        "tv {} is available (installed: {current})",
+        release.tag_name
+    );
+    if check {
+        return Ok(());
+    }
+
+    let exe = std::env::current_exe()?.canonicalize()?;
+    if let Some(hint) = package_manager_hint(&exe) {
+        bail!(
+            "{} was installed by a package manager, update it with {hint}",
+            exe.display()
+        );
+    }
+    let name = asset_name(
+        &release.tag_name,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    )
+    .ok_or_else(|| eyre!("no release is built for this platform"))?;
+    let url = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .map(|asset| asset.browser_download_url.as_str())
+            .ok_or_else(|| eyre!("the release has no {name} asset"))
+    };
+    let archive = curl(url(&name)?)?;
+    let checksum = curl(url(&checksum_name(&name))?)?;
+    verify_checksum(&archive, &String::from_utf8_lossy(&checksum))?;
+    let binary = extract_binary(&archive)?;
+    replace(&exe, &binary)?;
+    println!("Updated {} to {}", exe.display(), release.tag_name);
+    Ok(())
+}
+
+/// Download the given url.
+fn curl(url: &str) -> Result<Vec<u8>> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "-H", "User-Agent: television", url])
+        .output()
+        .map_err(|e| eyre!("could not run curl: {e}"))?;
+    if !output.status.success() {
+        bail!(
+            "could not download {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Whether the release of the given tag is newer than the given version.
+fn is_newer(tag: &str, version: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| {
+                part.chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0)
+            })
+            .collect()
+    };
+    parse(tag) > parse(version)
+}
+
+/// The name of the release archive of the given tag for the given platform
+/// (as named by `std::env::consts`), if one is built for it.
+fn asset_name(tag: &str, os: &str, arch: &str) -> Option<String> {
+    let os = match os {
+        "linux" | "macos" | "windows" => os,
+        _ => return None,
+    };
+    let arch = match arch {
+        "x86_64" => "x86_64",
+        "aarch64" => "arm64",
+        "x86" => "i686",
+        _ => return None,
+    };
+    Some(format!("tv-{tag}-{os}-{arch}.tar.gz"))
+}
+
+/// The name of the checksum asset published alongside the given release
+/// archive (`tv-<tag>-<os>-<arch>.sha256`).
+fn checksum_name(archive: &str) -> String {
+    format!("{}.sha256", archive.trim_end_matches(".tar.gz"))
+}
+
+/// How to update a binary installed at the given path by a package manager,
+/// if it was.
+fn package_manager_hint(path: &Path) -> Option<&'static str> {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let hints = [
+        ("/nix/store/", "your nix configuration"),
+        ("/Cellar/", "`brew upgrade television`"),
+        ("/linuxbrew/", "`brew upgrade television`"),
+        ("/scoop/", "`scoop update television`"),
+        ("/snap/", "`snap refresh television`"),
+        ("/.cargo/bin/", "`cargo install --locked television`"),
+        ("/usr/bin/", "your system's package manager"),
+    ];
+    hints
+        .into_iter()
+        .find(|(pattern, _)| path.contains(pattern))
+        .map(|(_, hint)| hint)
+}
+
+/// Check the archive against the contents of its `.sha256` file.
+fn verify_checksum(archive: &[u8], checksum_file: &str) -> Result<()> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| eyre!("the checksum file is empty"))?;
+    let actual = sha256(archive);
+    if !actual.eq_ignore_ascii_case(expected) {
+        bail!("checksum mismatch: expected {expected}, got {actual}");
+    }
+    Ok(())
+}
+
+/// Extract the `tv` binary from the given release archive.
+fn extract_binary(archive: &[u8]) -> Result<Vec<u8>> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.file_name() == Some(BINARY_NAME.as_ref()) {
+            let mut binary = Vec::new();
+            entry.read_to_end(&mut binary)?;
+            return Ok(binary);
+        }
+    }
+    bail!("the release archive has no {BINARY_NAME} binary")
+}
+
+/// Replace the binary at the given path, writing the new one next to it
+/// first so that the final rename is atomic.
+fn replace(exe: &Path, binary: &[u8]) -> Result<()> {
+    let dir = exe.parent().ok_or_else(|| eyre!("invalid binary path"))?;
+    let staged = dir.join(".tv-update");
+    fs::write(&staged, binary)
+        .map_err(|e| eyre!("could not write to {}: {e}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    // a running executable can't be overwritten on windows, but it can be
+    // renamed out of the way (and back if the new one can't take its place)
+    let old = cfg!(windows).then(|| dir.join(".tv-old.exe"));
+    if let Some(old) = &old {
+        let _ = fs::remove_file(old);
+        fs::rename(exe, old)?;
+    }
+    fs::rename(&staged, exe).inspect_err(|_| {
+        let _ = fs::remove_file(&staged);
+        if let Some(old) = &old {
+            let _ = fs::rename(old, exe);
+        }
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.5.0", "0.4.19"));
+        assert!(is_newer("0.4.20", "0.4.19"));
+        assert!(!is_newer("v0.4.19", "0.4.19"));
+        assert!(!is_newer("0.4.3", "0.4.19"));
+        assert!(!is_newer("v0.4.19-rc1", "0.4.19"));
+    }
+
+    #[test]
+    fn test_asset_name() {
+        assert_eq!(
+            asset_name("0.5.0", "linux", "x86_64").as_deref(),
+            Some("tv-0.5.0-linux-x86_64.tar.gz")
+        );
+        assert_eq!(
+            asset_name("0.5.0", "macos", "aarch64").as_deref(),
+            Some("tv-0.5.0-macos-arm64.tar.gz")
+        );
+        assert_eq!(asset_name("0.5.0", "freebsd", "x86_64"), None);
+    }
+
+    #[test]
+    fn test_checksum_name_matches_release_workflow() {
+        // cd.yml publishes `$RELEASE_NAME.tar.gz` and `$RELEASE_NAME.sha256`
+        let workflow = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/.github/workflows/cd.yml"
+        ));
+        assert!(workflow.contains("tar czvf $RELEASE_NAME.tar.gz"));
+        assert!(workflow.contains("> $RELEASE_NAME.sha256"));
+        assert_eq!(
+            checksum_name("tv-0.5.0-linux-x86_64.tar.gz"),
+            "tv-0.5.0-linux-x86_64.sha256"
+        );
+    }
+
+    #[test]
+    fn test_package_manager_hint() {
+        for path in [
+            "/nix/store/abc-television-0.4.19/bin/tv",
+            "/opt/homebrew/Cellar/television/0.4.19/bin/tv",
+            "/usr/bin/tv",
+            "/home/me/.cargo/bin/tv",
+        ] {
+            assert!(package_manager_hint(Path::new(path)).is_some(), "{path}");
+        }
+        assert_eq!(package_manager_hint(Path::new("/home/me/bin/tv")), None);
+        assert_eq!(package_manager_hint(Path::new("/usr/local/bin/tv")), None);
+    }
+
+    #[test]
+    fn test_verify_and_extract() {
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            Vec::new(),
+            Compression::default(),
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(6);
+        header.set_mode(0o755);
+        builder
+            .append_data(&mut header, BINARY_NAME, &b"binary"[..])
+            .unwrap();
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+
+        let checksum = format!("{}  tv.tar.gz\n", sha256(&archive));
+        verify_checksum(&archive, &checksum).unwrap();
+        assert!(verify_checksum(b"tampered", &checksum).is_err());
+        assert_eq!(extract_binary(&archive).unwrap(), b"binary");
+    }
+}
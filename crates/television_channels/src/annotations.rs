@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use color_eyre::Result;
+use tracing::debug;
+
+use crate::entry::{Entry, EntryRef};
+use crate::store::write_lines;
+use television_utils::dirs::get_data_dir;
+
+/// The directory of the data directory the notes are stored in.
+pub const ANNOTATIONS_DIR: &str = "annotations";
+
+/// Short notes the user attached to the entries of a given channel.
+///
+/// Annotations are stored in the data directory, in a file named after the
+/// channel, with one `<note>\t<entry>` line per annotated entry.
+#[derive(Debug, Clone, Default)]
+pub struct Annotations {
+    store: PathBuf,
+    notes: BTreeMap<String, String>,
+}
+
+impl Annotations {
+    /// Load the annotations of the given (lowercase) channel.
+    pub fn for_channel(channel: &str) -> Self {
+        Self::load(get_data_dir().join(ANNOTATIONS_DIR).join(channel))
+    }
+
+    fn load(store: PathBuf) -> Self {
+        let notes = std::fs::read_to_string(&store)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| line.split_once('\t'))
+                    .map(|(note, entry)| (entry.to_string(), note.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        debug!("Loaded annotations from {:?}: {:?}", store, notes);
+        Annotations { store, notes }
+    }
+
+    /// The note attached to the given entry, if any.
+    pub fn get<'a>(&self, entry: impl Into<EntryRef<'a>>) -> Option<&str> {
+        self.notes.get(&entry.into().key()).map(String::as_str)
+    }
+
+    /// Attach a note to the given entry (or remove its note if empty) and
+    /// persist the change.
+    pub fn set(&mut self, entry: &Entry, note: &str) -> Result<()> {
+        // notes are stored on a single line, before a tab
+        let note = note.replace(['\t', '\n', '\r'], " ").trim().to_string();
+        let changed = if note.is_empty() {
+            self.notes.remove(&entry.key()).is_some()
+        } else {
+            self.notes.insert(entry.key(), note.clone()) != Some(note)
+        };
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        write_lines(
+            &self.store,
+            self.notes
+                .iter()
+                .map(|(entry, note)| format!("{note}\t{entry}")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::PreviewType;
+
+    #[test]
+    fn test_annotations() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("files");
+        let readme = Entry::new("README.md".to_string(), PreviewType::Files);
+        let line = Entry::new("src/main.rs".to_string(), PreviewType::Files)
+            .with_line_number(12);
+
+        let mut annotations = Annotations::load(store.clone());
+        annotations.set(&readme, "  needs\tproofreading\n").unwrap();
+        annotations.set(&line, "TODO").unwrap();
+
+        let mut annotations = Annotations::load(store.clone());
+        assert_eq!(annotations.get(&readme), Some("needs proofreading"));
+        assert_eq!(annotations.get(&line), Some("TODO"));
+        assert_eq!(
+            annotations.get(&Entry::new(
+                "src/main.rs".to_string(),
+                PreviewType::Files
+            )),
+            None
+        );
+
+        annotations.set(&line, "").unwrap();
+        let annotations = Annotations::load(store.clone());
+        assert_eq!(annotations.get(&line), None);
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc;
+
+/// A message sent by the workers running in the background (previewers,
+/// long running tasks, ...) to the UI, which would otherwise have to poll
+/// them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// The preview of the entry with the given name was computed.
+    PreviewReady(String),
+    /// The progress of the task with the given key.
+    Progress {
+        key: String,
+        text: String,
+        percent: u8,
+    },
+    /// The end of the task with the given key.
+    Finished {
+        key: String,
+        text: String,
+        failed: bool,
+    },
+}
+
+/// The bus the workers of a picker send their messages to its UI through.
+///
+/// Clones share the same bus, while each picker (i.e. each `App`) has its
+/// own, which its channels and previewers are handed along with their
+/// context.
+#[derive(Debug, Clone, Default)]
+pub struct Bus {
+    /// The sending end of the bus, once the UI is connected to it.
+    tx: Arc<Mutex<Option<mpsc::UnboundedSender<Message>>>>,
+}
+
+impl Bus {
+    /// Connect to the bus, receiving the messages sent from now on (instead
+    /// of any previously connected receiver).
+    pub fn connect(&self) -> mpsc::UnboundedReceiver<Message> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        *self.tx.lock().unwrap() = Some(tx);
+        rx
+    }
+
+    /// Send the given message to the UI, if it's connected to the bus.
+    pub fn send(&self, message: Message) {
+        if let Some(tx) = self.tx.lock().unwrap().as_ref() {
+            let _ = tx.send(message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus() {
+        let bus = Bus::default();
+        let mut old_rx = bus.connect();
+        let mut rx = bus.clone().connect();
+        bus.send(Message::PreviewReady("a".to_string()));
+        assert_eq!(rx.try_recv(), Ok(Message::PreviewReady("a".to_string())));
+        // the previous receiver was disconnected
+        assert!(old_rx.try_recv().is_err());
+        // other buses are left alone
+        let mut other_rx = Bus::default().connect();
+        bus.send(Message::PreviewReady("b".to_string()));
+        assert!(other_rx.try_recv().is_err());
+    }
+}
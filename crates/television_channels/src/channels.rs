@@ -1,12 +1,45 @@
-use crate::entry::Entry;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, EntryRef, PreviewType};
 use color_eyre::eyre::Result;
+use std::ops::Range;
+use std::path::PathBuf;
 use television_derive::{Broadcast, ToCliChannel, ToUnitChannel};
 
-mod alias;
+pub mod alias;
+pub mod builder;
+pub mod cable;
+#[cfg(feature = "cron")]
+mod cron;
+#[cfg(feature = "dns")]
+mod dns;
+#[cfg(feature = "docker")]
+mod docker_containers;
+#[cfg(feature = "docker")]
+mod docker_images;
 mod env;
-mod files;
-mod git_repos;
+pub mod external;
+pub mod files;
+#[cfg(feature = "fonts")]
+mod fonts;
+mod git_branches;
+mod git_log;
+pub mod git_repos;
+mod hidden_entries;
+mod history;
+#[cfg(feature = "http-requests")]
+pub mod http_requests;
+#[cfg(feature = "journal")]
+mod journal;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+#[cfg(feature = "processes")]
+pub mod processes;
+mod reload;
 pub mod remote_control;
+#[cfg(feature = "scripting")]
+pub mod script;
 pub mod stdin;
 mod text;
 
@@ -34,6 +67,11 @@ mod text;
 ///   ```ignore
 ///   fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry>;
 ///   ```
+/// - `for_each_result`: Visit the results in the given range without copying
+///   them (optional, goes through `results` by default).
+///   ```ignore
+///   fn for_each_result(&mut self, range: Range<u32>, f: &mut dyn FnMut(&EntryRef));
+///   ```
 /// - `get_result`: Get a specific result by its index.
 ///   ```ignore
 ///   fn get_result(&self, index: u32) -> Option<Entry>;
@@ -47,6 +85,22 @@ mod text;
 ///   ```ignore
 ///   fn total_count(&self) -> u32;
 ///   ```
+/// - `loading`: Check if the channel is still loading its entries (optional).
+///   ```ignore
+///   fn loading(&self) -> bool;
+///   ```
+/// - `refresh`: Reload the channel's entries in the background (optional).
+///   ```ignore
+///   fn refresh(&mut self);
+///   ```
+/// - `warnings`: Summarize the entries skipped while loading (optional).
+///   ```ignore
+///   fn warnings(&self) -> Option<String>;
+///   ```
+/// - `memory_usage`: Estimate the memory taken by the entries (optional).
+///   ```ignore
+///   fn memory_usage(&self) -> usize;
+///   ```
 ///
 pub trait OnAir: Send {
     /// Find entries that match the given pattern.
@@ -60,6 +114,23 @@ pub trait OnAir: Send {
     /// Get the results of the search (that are currently available).
     fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry>;
 
+    /// Visit the results of the search in the given range, e.g. to display
+    /// them.
+    ///
+    /// Channels able to lend their entries (e.g. straight from their
+    /// matcher's snapshot) override this to avoid building an `Entry` for
+    /// each result, the default goes through `results`.
+    fn for_each_result(
+        &mut self,
+        range: Range<u32>,
+        f: &mut dyn FnMut(&EntryRef<'_>),
+    ) {
+        let num_entries = range.end.saturating_sub(range.start);
+        for entry in self.results(num_entries, range.start) {
+            f(&EntryRef::from(&entry));
+        }
+    }
+
     /// Get a specific result by its index.
     fn get_result(&self, index: u32) -> Option<Entry>;
 
@@ -72,8 +143,34 @@ pub trait OnAir: Send {
     /// Check if the channel is currently running.
     fn running(&self) -> bool;
 
+    /// Whether the channel is still loading its entries (e.g. walking
+    /// directories or running a command), i.e. whether more may show up.
+    ///
+    /// Channels loading their entries up front are never loading.
+    fn loading(&self) -> bool {
+        false
+    }
+
     /// Turn off
     fn shutdown(&self);
+
+    /// Reload the channel's entries in the background (e.g. by re-running
+    /// the command backing it), without resetting the current pattern.
+    ///
+    /// This does nothing for channels that don't support it.
+    fn refresh(&mut self) {}
+
+    /// A summary of the entries skipped while loading the channel (e.g.
+    /// unreadable directories), if any, to show to the user.
+    fn warnings(&self) -> Option<String> {
+        None
+    }
+
+    /// The approximate number of bytes taken by the entries of the channel
+    /// (e.g. the haystack of its matcher), to show to the user.
+    fn memory_usage(&self) -> usize {
+        0
+    }
 }
 
 /// The available television channels.
@@ -83,7 +180,8 @@ pub trait OnAir: Send {
 ///
 /// # Important
 /// When adding a new channel, make sure to add a new variant to this enum and
-/// implement the `OnAir` trait for it.
+/// implement the `OnAir` trait for it. Simple channels can be described with a
+/// `ChannelBuilder` instead (see the `builder` module).
 ///
 /// # Derive
 /// ## `CliChannel`
@@ -119,6 +217,16 @@ pub enum TelevisionChannel {
     ///
     /// This channel allows to search through git repositories.
     GitRepos(git_repos::Channel),
+    /// The git log channel.
+    ///
+    /// This channel allows to search through the commits of the current
+    /// repository.
+    GitLog(git_log::Channel),
+    /// The git branches channel.
+    ///
+    /// This channel allows to search through the local and remote branches
+    /// of the current repository.
+    GitBranches(git_branches::Channel),
     /// The text channel.
     ///
     /// This channel allows to search through the contents of text files.
@@ -132,6 +240,91 @@ pub enum TelevisionChannel {
     ///
     /// This channel allows to search through aliases.
     Alias(alias::Channel),
+    /// The http requests channel.
+    ///
+    /// This channel allows to search through the requests defined in http
+    /// request collection files (`.http`, `.rest` and `.hurl`).
+    #[cfg(feature = "http-requests")]
+    HttpRequests(http_requests::Channel),
+    /// The OpenAPI channel.
+    ///
+    /// This channel allows to search through the operations defined in an
+    /// OpenAPI (or Swagger) spec.
+    #[cfg(feature = "openapi")]
+    Openapi(openapi::Channel),
+    /// The DNS channel.
+    ///
+    /// This channel allows to search through the hosts file, the configured
+    /// name servers and recent DNS queries.
+    #[cfg(feature = "dns")]
+    Dns(dns::Channel),
+    /// The cron channel.
+    ///
+    /// This channel allows to search through crontab entries and systemd
+    /// timers.
+    #[cfg(feature = "cron")]
+    Cron(cron::Channel),
+    /// The fonts channel.
+    ///
+    /// This channel allows to search through installed fonts.
+    #[cfg(feature = "fonts")]
+    Fonts(fonts::Channel),
+    /// The journal channel.
+    ///
+    /// This channel allows to search through the most recent entries of the
+    /// systemd journal, within a time range.
+    #[cfg(feature = "journal")]
+    Journal(journal::Channel),
+    /// The processes channel.
+    ///
+    /// This channel allows to search through running processes.
+    #[cfg(feature = "processes")]
+    Processes(processes::Channel),
+    /// The docker containers channel.
+    ///
+    /// This channel allows to search through docker containers, running or
+    /// not.
+    #[cfg(feature = "docker")]
+    DockerContainers(docker_containers::Channel),
+    /// The docker images channel.
+    ///
+    /// This channel allows to search through docker images.
+    #[cfg(feature = "docker")]
+    DockerImages(docker_images::Channel),
+    /// The hidden entries channel.
+    ///
+    /// This channel allows to search through the entries hidden from the
+    /// files and text channels of the current project, and to un-hide them.
+    HiddenEntries(hidden_entries::Channel),
+    /// A cable channel.
+    ///
+    /// This channel allows to search through the lines printed by a command
+    /// defined in the config file.
+    #[exclude_from_cli]
+    Cable(cable::Channel),
+    /// A script channel.
+    ///
+    /// This channel allows to search through the entries listed by a script
+    /// of the config directory.
+    #[cfg(feature = "scripting")]
+    #[exclude_from_cli]
+    Script(script::Channel),
+    /// A plugin channel.
+    ///
+    /// This channel allows to search through the entries listed by a
+    /// WebAssembly plugin of the config directory.
+    #[cfg(feature = "plugins")]
+    #[exclude_from_cli]
+    Plugin(plugin::Channel),
+    /// The external channel.
+    ///
+    /// This channel allows to search through the lines printed by a command
+    /// given on the command line, while it is running.
+    External(external::Channel),
+    /// The history channel.
+    ///
+    /// This channel allows to search through the shell's command history.
+    History(history::Channel),
     /// The remote control channel.
     ///
     /// This channel allows to switch between different channels.
@@ -140,25 +333,273 @@ pub enum TelevisionChannel {
     RemoteControl(remote_control::RemoteControl),
 }
 
-/// NOTE: this could/should be generated by a macro
-impl TryFrom<&Entry> for TelevisionChannel {
-    type Error = String;
-
-    fn try_from(entry: &Entry) -> Result<Self, Self::Error> {
+impl TelevisionChannel {
+    /// Create the channel named after the given entry (e.g. picked from the
+    /// remote control), with its default settings.
+    ///
+    /// NOTE: this could/should be generated by a macro
+    pub fn from_entry(ctx: &Context, entry: &Entry) -> Result<Self, String> {
         match entry.name.to_ascii_lowercase().as_ref() {
-            "env" => Ok(TelevisionChannel::Env(env::Channel::default())),
-            "files" => Ok(TelevisionChannel::Files(files::Channel::default())),
-            "gitrepos" => {
-                Ok(TelevisionChannel::GitRepos(git_repos::Channel::default()))
+            "env" => {
+                Ok(TelevisionChannel::Env(env::Channel::from_context(ctx)))
+            }
+            "files" => {
+                Ok(TelevisionChannel::Files(files::Channel::from_context(ctx)))
+            }
+            "gitrepos" => Ok(TelevisionChannel::GitRepos(
+                git_repos::Channel::from_context(ctx),
+            )),
+            "gitlog" => Ok(TelevisionChannel::GitLog(
+                git_log::Channel::from_context(ctx),
+            )),
+            "gitbranches" => Ok(TelevisionChannel::GitBranches(
+                git_branches::Channel::from_context(ctx),
+            )),
+            "text" => {
+                Ok(TelevisionChannel::Text(text::Channel::from_context(ctx)))
+            }
+            "stdin" => {
+                Ok(TelevisionChannel::Stdin(stdin::Channel::from_context(ctx)))
+            }
+            "alias" => {
+                Ok(TelevisionChannel::Alias(alias::Channel::from_context(ctx)))
+            }
+            #[cfg(feature = "http-requests")]
+            "httprequests" => Ok(TelevisionChannel::HttpRequests(
+                http_requests::Channel::from_context(ctx),
+            )),
+            #[cfg(feature = "openapi")]
+            "openapi" => Ok(TelevisionChannel::Openapi(
+                openapi::Channel::from_context(ctx),
+            )),
+            #[cfg(feature = "dns")]
+            "dns" => {
+                Ok(TelevisionChannel::Dns(dns::Channel::from_context(ctx)))
+            }
+            #[cfg(feature = "cron")]
+            "cron" => {
+                Ok(TelevisionChannel::Cron(cron::Channel::from_context(ctx)))
+            }
+            #[cfg(feature = "fonts")]
+            "fonts" => {
+                Ok(TelevisionChannel::Fonts(fonts::Channel::from_context(ctx)))
             }
-            "text" => Ok(TelevisionChannel::Text(text::Channel::default())),
-            "stdin" => Ok(TelevisionChannel::Stdin(stdin::Channel::default())),
-            "alias" => Ok(TelevisionChannel::Alias(alias::Channel::default())),
-            _ => Err(format!("Unknown channel: {}", entry.name)),
+            #[cfg(feature = "journal")]
+            "journal" => Ok(TelevisionChannel::Journal(
+                journal::Channel::from_context(ctx),
+            )),
+            #[cfg(feature = "processes")]
+            "processes" => Ok(TelevisionChannel::Processes(
+                processes::Channel::from_context(ctx),
+            )),
+            #[cfg(feature = "docker")]
+            "dockercontainers" => Ok(TelevisionChannel::DockerContainers(
+                docker_containers::Channel::from_context(ctx),
+            )),
+            #[cfg(feature = "docker")]
+            "dockerimages" => Ok(TelevisionChannel::DockerImages(
+                docker_images::Channel::from_context(ctx),
+            )),
+            "hiddenentries" => Ok(TelevisionChannel::HiddenEntries(
+                hidden_entries::Channel::from_context(ctx),
+            )),
+            "external" => Ok(TelevisionChannel::External(
+                external::Channel::from_context(ctx),
+            )),
+            "history" => Ok(TelevisionChannel::History(
+                history::Channel::from_context(ctx),
+            )),
+            name => ctx
+                .cable_channel(name)
+                .map(|prototype| {
+                    TelevisionChannel::Cable(cable::Channel::new(
+                        ctx, prototype,
+                    ))
+                })
+                .or_else(|| script_channel(ctx, name))
+                .or_else(|| plugin_channel(ctx, name))
+                .ok_or_else(|| match missing_channel_feature(name) {
+                    Some(feature) => compiled_out_error(&entry.name, feature),
+                    None => format!("Unknown channel: {}", entry.name),
+                }),
         }
     }
 }
 
+impl TelevisionChannel {
+    /// The name of the channel, as displayed in the UI.
+    ///
+    /// Its lowercase version is used to configure channel specific settings.
+    pub fn name(&self) -> String {
+        match self {
+            TelevisionChannel::Cable(channel) => channel.name().to_string(),
+            #[cfg(feature = "scripting")]
+            TelevisionChannel::Script(channel) => channel.name().to_string(),
+            #[cfg(feature = "plugins")]
+            TelevisionChannel::Plugin(channel) => channel.name().to_string(),
+            _ => UnitChannel::from(self).to_string(),
+        }
+    }
+
+    /// Create the channel with the given (case insensitive) name from the
+    /// value of its parameter, e.g. when it is picked from the remote
+    /// control.
+    ///
+    /// An empty value creates the channel with its default settings.
+    pub fn with_parameter(
+        ctx: &Context,
+        name: &str,
+        value: &str,
+    ) -> Result<Self, String> {
+        let value = value.trim();
+        if value.is_empty() || channel_parameter(name).is_none() {
+            return Self::from_entry(
+                ctx,
+                &Entry::new(name.to_string(), PreviewType::Basic),
+            );
+        }
+        let path = PathBuf::from(value);
+        Ok(match name.to_ascii_lowercase().as_ref() {
+            "files" => {
+                TelevisionChannel::Files(files::Channel::new(ctx, vec![path]))
+            }
+            "text" => {
+                TelevisionChannel::Text(text::Channel::new(ctx, vec![path]))
+            }
+            #[cfg(feature = "http-requests")]
+            "httprequests" => TelevisionChannel::HttpRequests(
+                http_requests::Channel::new(ctx, vec![path], false),
+            ),
+            #[cfg(feature = "openapi")]
+            "openapi" => TelevisionChannel::Openapi(openapi::Channel::new(
+                ctx,
+                Some(path),
+            )),
+            "external" => TelevisionChannel::External(external::Channel::new(
+                ctx,
+                value.to_string(),
+            )),
+            _ => unreachable!("channel {name} has no parameter"),
+        })
+    }
+}
+
+/// The parameter a channel can be created from, instead of the one given on
+/// the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelParameter {
+    /// What the parameter is, e.g. to prompt for it.
+    pub description: &'static str,
+    /// Whether the parameter is a path, which can be completed.
+    pub is_path: bool,
+}
+
+/// The parameter of the channel with the given (case insensitive) name, if
+/// it takes one.
+pub fn channel_parameter(name: &str) -> Option<ChannelParameter> {
+    let (description, is_path) = match name.to_ascii_lowercase().as_ref() {
+        "files" => ("Root directory", true),
+        "text" => ("Directory to search", true),
+        #[cfg(feature = "http-requests")]
+        "httprequests" => ("Directory of the .http files", true),
+        #[cfg(feature = "openapi")]
+        "openapi" => ("Path of the spec", true),
+        "external" => ("Source command", false),
+        _ => return None,
+    };
+    Some(ChannelParameter {
+        description,
+        is_path,
+    })
+}
+
+/// The optional channels (by CLI name), the cargo feature they're gated
+/// behind and whether it is enabled in this build.
+const OPTIONAL_CHANNELS: [(&str, &str, bool); 9] = [
+    (
+        "http-requests",
+        "http-requests",
+        cfg!(feature = "http-requests"),
+    ),
+    ("openapi", "openapi", cfg!(feature = "openapi")),
+    ("dns", "dns", cfg!(feature = "dns")),
+    ("cron", "cron", cfg!(feature = "cron")),
+    ("fonts", "fonts", cfg!(feature = "fonts")),
+    ("journal", "journal", cfg!(feature = "journal")),
+    ("processes", "processes", cfg!(feature = "processes")),
+    ("docker-containers", "docker", cfg!(feature = "docker")),
+    ("docker-images", "docker", cfg!(feature = "docker")),
+];
+
+/// The channel listing the entries of the registered script with the given
+/// (case insensitive) name, if it lists any.
+#[cfg(feature = "scripting")]
+pub fn script_channel(ctx: &Context, name: &str) -> Option<TelevisionChannel> {
+    ctx.script(name)
+        .filter(script::Script::is_channel)
+        .map(|script| {
+            TelevisionChannel::Script(script::Channel::new(ctx, script))
+        })
+}
+
+#[cfg(not(feature = "scripting"))]
+pub fn script_channel(
+    _ctx: &Context,
+    _name: &str,
+) -> Option<TelevisionChannel> {
+    None
+}
+
+/// The channel listing the entries of the registered plugin with the given
+/// (case insensitive) name.
+#[cfg(feature = "plugins")]
+pub fn plugin_channel(ctx: &Context, name: &str) -> Option<TelevisionChannel> {
+    ctx.plugin(name).map(|plugin| {
+        TelevisionChannel::Plugin(plugin::Channel::new(ctx, plugin))
+    })
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn plugin_channel(
+    _ctx: &Context,
+    _name: &str,
+) -> Option<TelevisionChannel> {
+    None
+}
+
+/// If the channel with the given (case insensitive) name was compiled out
+/// of this build, the cargo feature required to build it.
+///
+/// Dashes are ignored, so that both the CLI name (e.g. `docker-images`) and
+/// the remote control one (e.g. `DockerImages`) are recognized.
+pub fn missing_channel_feature(name: &str) -> Option<&'static str> {
+    let normalize = |name: &str| name.replace('-', "").to_lowercase();
+    OPTIONAL_CHANNELS
+        .iter()
+        .find(|(channel, _, enabled)| {
+            !enabled && normalize(channel) == normalize(name)
+        })
+        .map(|(_, feature, _)| *feature)
+}
+
+/// The optional channels compiled out of this build (by CLI name), e.g. to
+/// tell users about them instead of leaving them wondering.
+pub fn compiled_out_channels() -> impl Iterator<Item = &'static str> {
+    OPTIONAL_CHANNELS
+        .into_iter()
+        .filter(|(_, _, enabled)| !enabled)
+        .map(|(channel, _, _)| channel)
+}
+
+/// The error reported when the given channel was compiled out of this
+/// build, naming the cargo feature required to build it.
+pub fn compiled_out_error(name: &str, feature: &str) -> String {
+    format!(
+        "the `{name}` channel is not available in this build (it requires \
+         the `{feature}` cargo feature)"
+    )
+}
+
 macro_rules! variant_to_module {
     (Files) => {
         files::Channel
@@ -169,6 +610,12 @@ macro_rules! variant_to_module {
     (GitRepos) => {
         git_repos::Channel
     };
+    (GitLog) => {
+        git_log::Channel
+    };
+    (GitBranches) => {
+        git_branches::Channel
+    };
     (Env) => {
         env::Channel
     };
@@ -178,6 +625,51 @@ macro_rules! variant_to_module {
     (Alias) => {
         alias::Channel
     };
+    (HttpRequests) => {
+        http_requests::Channel
+    };
+    (Openapi) => {
+        openapi::Channel
+    };
+    (Dns) => {
+        dns::Channel
+    };
+    (Cron) => {
+        cron::Channel
+    };
+    (Fonts) => {
+        fonts::Channel
+    };
+    (Journal) => {
+        journal::Channel
+    };
+    (Processes) => {
+        processes::Channel
+    };
+    (DockerContainers) => {
+        docker_containers::Channel
+    };
+    (DockerImages) => {
+        docker_images::Channel
+    };
+    (HiddenEntries) => {
+        hidden_entries::Channel
+    };
+    (Cable) => {
+        cable::Channel
+    };
+    (Script) => {
+        script::Channel
+    };
+    (Plugin) => {
+        plugin::Channel
+    };
+    (External) => {
+        external::Channel
+    };
+    (History) => {
+        history::Channel
+    };
     (RemoteControl) => {
         remote_control::RemoteControl
     };
@@ -214,16 +706,16 @@ macro_rules! variant_to_module {
 ///         }
 ///     }
 ///
-///     pub fn transition_to(self, target: UnitChannel) -> TelevisionChannel {
+///     pub fn transition_to(&mut self, ctx: &Context, target: UnitChannel) -> TelevisionChannel {
 ///         match (self, target) {
 ///             (tv_channel @ TelevisionChannel::Files(_), UnitChannel::Text) => {
-///                 TelevisionChannel::Text(text::Channel::from(tv_channel))
+///                 TelevisionChannel::Text(text::Channel::from_channel(ctx, tv_channel))
 ///             },
 ///             (tv_channel @ TelevisionChannel::GitRepos(_), UnitChannel::Files) => {
-///                 TelevisionChannel::Files(files::Channel::from(tv_channel))
+///                 TelevisionChannel::Files(files::Channel::from_channel(ctx, tv_channel))
 ///             },
 ///             (tv_channel @ TelevisionChannel::GitRepos(_), UnitChannel::Text) => {
-///                 TelevisionChannel::Text(text::Channel::from(tv_channel))
+///                 TelevisionChannel::Text(text::Channel::from_channel(ctx, tv_channel))
 ///             },
 ///             _ => unreachable!(),
 ///         }
@@ -249,13 +741,13 @@ macro_rules! define_transitions {
                 }
             }
 
-            pub fn transition_to(&mut self, target: UnitChannel) -> TelevisionChannel {
+            pub fn transition_to(&mut self, ctx: &Context, target: UnitChannel) -> TelevisionChannel {
                 match (self, target) {
                     $(
                         $(
                             (tv_channel @ TelevisionChannel::$from_variant(_), UnitChannel::$to_variant) => {
                                 TelevisionChannel::$to_variant(
-                                    <variant_to_module!($to_variant)>::from(tv_channel)
+                                    <variant_to_module!($to_variant)>::from_channel(ctx, tv_channel)
                                 )
                             },
                         )*
@@ -277,3 +769,123 @@ define_transitions! {
     Files => [Files, Text],
     GitRepos => [Files, Text],
 }
+
+/// A macro that generates the `zap_target` and `zap` methods of the
+/// `TelevisionChannel` enum based on the zaps defined in the macro call.
+///
+/// Unlike transitions, which send all the results of a channel to another
+/// one, a zap follows the selected entry to the channel it leads to, created
+/// from the entry's output (or name) as its parameter: e.g. zapping from a
+/// repository of the `GitRepos` channel opens the `Files` channel rooted at
+/// that repository. The target channels must therefore take a parameter (see
+/// `channel_parameter`).
+///
+/// # Example
+/// ```ignore
+/// define_zaps! {
+///     // The `GitRepos` channel zaps into the `Files` channel.
+///     GitRepos => Files,
+/// }
+/// ```
+macro_rules! define_zaps {
+    (
+        $(
+            $from_variant:ident => $to_variant:ident,
+        )*
+    ) => {
+        impl TelevisionChannel {
+            /// The channel the selected entry of this channel leads to, if
+            /// any.
+            pub fn zap_target(&self) -> Option<UnitChannel> {
+                match self {
+                    $(
+                        TelevisionChannel::$from_variant(_) => {
+                            Some(UnitChannel::$to_variant)
+                        }
+                    )*
+                    _ => None,
+                }
+            }
+
+            /// Create the channel the given entry of this channel leads to,
+            /// if any and if it is available.
+            pub fn zap(
+                &self,
+                ctx: &Context,
+                entry: &Entry,
+            ) -> Option<TelevisionChannel> {
+                let target = self.zap_target()?.to_string();
+                if !ctx.is_channel_available(&target) {
+                    return None;
+                }
+                Self::with_parameter(
+                    ctx,
+                    &target,
+                    entry.output.as_deref().unwrap_or(&entry.name),
+                )
+                .ok()
+            }
+        }
+    }
+}
+
+// Define the zaps from the entries of a channel to another channel.
+define_zaps! {
+    GitRepos => Files,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_channel_feature() {
+        assert_eq!(missing_channel_feature("files"), None);
+        assert_eq!(
+            missing_channel_feature("HTTP-Requests"),
+            (!cfg!(feature = "http-requests")).then_some("http-requests")
+        );
+        assert_eq!(
+            missing_channel_feature("DockerImages"),
+            (!cfg!(feature = "docker")).then_some("docker")
+        );
+        assert!(compiled_out_channels()
+            .all(|channel| missing_channel_feature(channel).is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_with_parameter() {
+        assert!(channel_parameter("Files").is_some_and(|p| p.is_path));
+        assert_eq!(channel_parameter("env"), None);
+
+        let ctx = Context::default();
+        let channel =
+            TelevisionChannel::with_parameter(&ctx, "files", "src").unwrap();
+        assert!(matches!(channel, TelevisionChannel::Files(_)));
+        // channels without parameter ignore it
+        let channel =
+            TelevisionChannel::with_parameter(&ctx, "env", "src").unwrap();
+        assert!(matches!(channel, TelevisionChannel::Env(_)));
+        assert!(TelevisionChannel::with_parameter(&ctx, "nope", "").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_zap() {
+        let mut ctx = Context::default();
+        let repos = TelevisionChannel::GitRepos(git_repos::Channel::new(&ctx));
+        assert_eq!(repos.zap_target(), Some(UnitChannel::Files));
+        let entry = Entry::new("src".to_string(), PreviewType::Directory);
+        assert!(matches!(
+            repos.zap(&ctx, &entry),
+            Some(TelevisionChannel::Files(_))
+        ));
+        // unless the target channel isn't available
+        ctx.set_available_channels(vec!["gitrepos".to_string()]);
+        assert!(repos.zap(&ctx, &entry).is_none());
+        repos.shutdown();
+
+        let env = TelevisionChannel::with_parameter(&ctx, "env", "").unwrap();
+        assert_eq!(env.zap_target(), None);
+        assert!(env.zap(&ctx, &entry).is_none());
+    }
+}
@@ -1,13 +1,15 @@
-use crate::channels::OnAir;
-use crate::entry::Entry;
-use crate::entry::PreviewType;
-use devicons::FileIcon;
-use television_fuzzy::matcher::{config::Config, injector::Injector, Matcher};
-use television_utils::indices::sep_name_and_value_indices;
+use std::process::{Command, Stdio};
+
+use television_derive::FromBuilder;
+use television_utils::cancel::{output, CancellationToken};
+use television_utils::shell::find_executable;
 use television_utils::strings::preprocess_line;
 use tracing::debug;
 
-#[derive(Debug, Clone)]
+use super::builder::{BuiltChannel, ChannelBuilder};
+use crate::entry::PreviewType;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Alias {
     name: String,
     value: String,
@@ -19,143 +21,236 @@ impl Alias {
     }
 }
 
-pub struct Channel {
-    matcher: Matcher<Alias>,
-    file_icon: FileIcon,
-}
-
-const NUM_THREADS: usize = 1;
+#[derive(FromBuilder)]
+pub struct Channel(BuiltChannel<Alias>);
 
 const FILE_ICON_STR: &str = "nu";
 const SHELL_ENV_VAR: &str = "SHELL";
 
-fn get_current_shell() -> Option<String> {
-    std::env::var(SHELL_ENV_VAR).ok()
+/// The shells whose aliases can be listed, each printing them in its own
+/// format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shell {
+    /// `alias name='value'`
+    Bash,
+    /// `name=value` or `name='value'`
+    Zsh,
+    /// `alias name 'value'` or `alias name value`
+    Fish,
+    /// `name=value`, as printed by `POWERSHELL_ALIASES`
+    Powershell,
+    /// `name=value`, as printed by `doskey /macros`
+    Cmd,
 }
 
-fn get_raw_aliases(shell: &str) -> Vec<String> {
-    let output = std::process::Command::new(shell)
-        .arg("-i")
-        .arg("-c")
-        .arg("alias")
-        .output()
-        .expect("failed to execute process");
-    let aliases = String::from_utf8(output.stdout).unwrap();
-    aliases.lines().map(ToString::to_string).collect()
-}
+/// Print the aliases of the powershell session, with their definitions.
+const POWERSHELL_ALIASES: &str =
+    "Get-Alias | ForEach-Object { $_.Name + '=' + $_.Definition }";
 
-impl Channel {
-    pub fn new() -> Self {
-        let matcher = Matcher::new(Config::default().n_threads(NUM_THREADS));
-        let injector = matcher.injector();
-        tokio::spawn(load_aliases(injector));
-
-        Self {
-            matcher,
-            file_icon: FileIcon::from(FILE_ICON_STR),
+impl Shell {
+    /// The shell with the given name, or path (e.g. `/bin/zsh` or
+    /// `C:\...\pwsh.exe`), if its aliases can be listed.
+    fn from_path(path: &str) -> Option<Self> {
+        let name = path.rsplit(['/', '\\']).next()?.to_lowercase();
+        match name.strip_suffix(".exe").unwrap_or(&name) {
+            "bash" | "sh" => Some(Shell::Bash),
+            "zsh" => Some(Shell::Zsh),
+            "fish" => Some(Shell::Fish),
+            "pwsh" | "powershell" => Some(Shell::Powershell),
+            "cmd" => Some(Shell::Cmd),
+            _ => None,
         }
     }
-}
 
-impl Default for Channel {
-    fn default() -> Self {
-        Self::new()
+    /// The shell of the platform, when `$SHELL` doesn't name a known one:
+    /// powershell (or cmd if it isn't installed) on windows, bash elsewhere.
+    fn platform_default() -> Self {
+        if !cfg!(windows) {
+            Shell::Bash
+        } else if find_executable("pwsh").is_some()
+            || find_executable("powershell").is_some()
+        {
+            Shell::Powershell
+        } else {
+            Shell::Cmd
+        }
     }
-}
 
-impl OnAir for Channel {
-    fn find(&mut self, pattern: &str) {
-        self.matcher.find(pattern);
+    /// The command printing the shell's aliases, one per line.
+    fn aliases_command(self) -> Command {
+        let mut command = Command::new(self.executable());
+        match self {
+            Shell::Bash | Shell::Zsh | Shell::Fish => {
+                command.args(["-i", "-c", "alias"]);
+            }
+            Shell::Powershell => {
+                command.args(["-NoLogo", "-Command", POWERSHELL_ALIASES]);
+            }
+            Shell::Cmd => {
+                command.args(["/C", "doskey", "/macros"]);
+            }
+        }
+        command.stdin(Stdio::null()).stderr(Stdio::null());
+        command
     }
 
-    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
-        self.matcher.tick();
-        self.matcher
-            .results(num_entries, offset)
-            .into_iter()
-            .map(|item| {
-                let (
-                    name_indices,
-                    value_indices,
-                    should_add_name_indices,
-                    should_add_value_indices,
-                ) = sep_name_and_value_indices(
-                    &mut item.match_indices.iter().map(|i| i.0).collect(),
-                    u32::try_from(item.inner.name.len()).unwrap(),
-                );
-
-                let mut entry =
-                    Entry::new(item.inner.name.clone(), PreviewType::EnvVar)
-                        .with_value(item.inner.value.clone())
-                        .with_icon(self.file_icon);
-
-                if should_add_name_indices {
-                    entry = entry.with_name_match_ranges(
-                        name_indices.into_iter().map(|i| (i, i + 1)).collect(),
-                    );
-                }
-
-                if should_add_value_indices {
-                    entry = entry.with_value_match_ranges(
-                        value_indices
-                            .into_iter()
-                            .map(|i| (i, i + 1))
-                            .collect(),
-                    );
-                }
-
-                entry
-            })
-            .collect()
+    fn executable(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            // the cross-platform powershell, or the one bundled with windows
+            Shell::Powershell if find_executable("pwsh").is_some() => "pwsh",
+            Shell::Powershell => "powershell",
+            Shell::Cmd => "cmd",
+        }
     }
 
-    fn get_result(&self, index: u32) -> Option<Entry> {
-        self.matcher.get_result(index).map(|item| {
-            Entry::new(item.inner.name.clone(), PreviewType::EnvVar)
-                .with_value(item.inner.value.clone())
-                .with_icon(self.file_icon)
-        })
+    /// Parse a line of the shell's `alias` output.
+    fn parse_alias(self, line: &str) -> Option<Alias> {
+        let (name, value) = match self {
+            Shell::Bash => line.strip_prefix("alias ")?.split_once('=')?,
+            Shell::Zsh | Shell::Powershell | Shell::Cmd => {
+                line.split_once('=')?
+            }
+            Shell::Fish => line.strip_prefix("alias ")?.split_once(' ')?,
+        };
+        let value = match self {
+            Shell::Bash | Shell::Zsh | Shell::Fish => self.unquote(value),
+            // printed as is
+            Shell::Powershell | Shell::Cmd => value.to_string(),
+        };
+        // the name may be left empty by `preprocess_line`, e.g. when it is
+        // only a carriage return
+        let name = preprocess_line(name);
+        if name.is_empty() {
+            return None;
+        }
+        Some(Alias::new(name, preprocess_line(&value)))
     }
 
-    fn result_count(&self) -> u32 {
-        self.matcher.matched_item_count
+    /// Remove the quoting of an alias value.
+    fn unquote(self, value: &str) -> String {
+        let mut unquoted = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => quoted = !quoted,
+                // fish escapes quotes and backslashes inside quotes
+                '\\' if quoted && self == Shell::Fish => {
+                    unquoted.extend(chars.next());
+                }
+                // posix shells close the quotes to escape them: `'\''`
+                '\\' if !quoted => unquoted.extend(chars.next()),
+                c => unquoted.push(c),
+            }
+        }
+        unquoted
     }
+}
 
-    fn total_count(&self) -> u32 {
-        self.matcher.total_item_count
-    }
+/// Parse a line of the `alias` output of the shell with the given path into
+/// the name and value of the alias, for fuzzing.
+#[doc(hidden)]
+pub fn parse_alias_line(shell: &str, line: &str) -> Option<(String, String)> {
+    Shell::from_path(shell)?
+        .parse_alias(line)
+        .map(|alias| (alias.name, alias.value))
+}
+
+fn get_current_shell() -> Shell {
+    std::env::var(SHELL_ENV_VAR)
+        .ok()
+        .and_then(|shell| Shell::from_path(&shell))
+        .unwrap_or_else(Shell::platform_default)
+}
 
-    fn running(&self) -> bool {
-        self.matcher.status.running
+fn get_raw_aliases(shell: Shell, token: &CancellationToken) -> Vec<String> {
+    match output(&mut shell.aliases_command(), token) {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(ToString::to_string)
+            .collect(),
+        Err(e) => {
+            debug!("Failed to list the aliases of {:?}: {}", shell, e);
+            Vec::new()
+        }
     }
+}
 
-    fn shutdown(&self) {}
+impl Channel {
+    fn builder() -> ChannelBuilder<Alias> {
+        ChannelBuilder::new("alias", aliases, |alias| {
+            (alias.name.clone(), Some(alias.value.clone()))
+        })
+        .preview(PreviewType::EnvVar)
+        .icon(FILE_ICON_STR)
+    }
 }
 
-#[allow(clippy::unused_async)]
-async fn load_aliases(injector: Injector<Alias>) {
-    let raw_shell = get_current_shell().unwrap_or("bash".to_string());
-    let shell = raw_shell.split('/').last().unwrap();
-    debug!("Current shell: {}", shell);
-    let raw_aliases = get_raw_aliases(shell);
+fn aliases(token: &CancellationToken) -> Vec<Alias> {
+    let shell = get_current_shell();
+    debug!("Current shell: {:?}", shell);
 
-    raw_aliases
+    get_raw_aliases(shell, token)
         .iter()
-        .filter_map(|alias| {
-            let mut parts = alias.split('=');
-            if let Some(name) = parts.next() {
-                if let Some(value) = parts.next() {
-                    return Some(Alias::new(
-                        preprocess_line(name),
-                        preprocess_line(value),
-                    ));
-                }
-            }
-            None
-        })
-        .for_each(|alias| {
-            let () = injector.push(alias.clone(), |_, cols| {
-                cols[0] = (alias.name.clone() + &alias.value).into();
-            });
-        });
+        .filter_map(|line| shell.parse_alias(line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(name: &str, value: &str) -> Option<Alias> {
+        Some(Alias::new(name.to_string(), value.to_string()))
+    }
+
+    #[test]
+    fn test_parse_alias() {
+        assert_eq!(
+            Shell::Bash.parse_alias(r"alias say='echo '\''hi'\'''"),
+            alias("say", "echo 'hi'")
+        );
+        assert_eq!(
+            Shell::Zsh.parse_alias("gs='git status --short'"),
+            alias("gs", "git status --short")
+        );
+        assert_eq!(
+            Shell::Zsh.parse_alias("e=nvim=0.10"),
+            alias("e", "nvim=0.10")
+        );
+        assert_eq!(
+            Shell::Fish.parse_alias(r"alias say 'echo \'hi\''"),
+            alias("say", "echo 'hi'")
+        );
+        assert_eq!(Shell::Fish.parse_alias("alias g git"), alias("g", "git"));
+        assert_eq!(Shell::Fish.parse_alias("g git"), None);
+        assert_eq!(
+            Shell::Powershell.parse_alias("gci=Get-ChildItem"),
+            alias("gci", "Get-ChildItem")
+        );
+        assert_eq!(
+            Shell::Cmd.parse_alias("ls=dir /b $*"),
+            alias("ls", "dir /b $*")
+        );
+        assert_eq!(Shell::Cmd.parse_alias("=oops"), None);
+        assert_eq!(Shell::Zsh.parse_alias("\r=oops"), None);
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Shell::from_path("/usr/bin/zsh"), Some(Shell::Zsh));
+        assert_eq!(Shell::from_path("fish"), Some(Shell::Fish));
+        assert_eq!(
+            Shell::from_path(r"C:\Program Files\PowerShell\7\pwsh.exe"),
+            Some(Shell::Powershell)
+        );
+        assert_eq!(
+            Shell::from_path(r"C:\Windows\System32\CMD.EXE"),
+            Some(Shell::Cmd)
+        );
+        assert_eq!(Shell::from_path("/usr/bin/nu"), None);
+    }
 }
@@ -0,0 +1,369 @@
+//! A declarative way of writing simple built-in channels.
+//!
+//! Most channels boil down to the same plumbing: load some items in the
+//! background, push them into a fuzzy matcher, and turn the matched items
+//! into entries. A [`ChannelBuilder`] takes care of all of it, so that a new
+//! channel only has to describe:
+//! - its **source**: a function listing the channel's items. It is run in the
+//!   background once the UI is up, and again whenever the channel is
+//!   refreshed, and should give up once the token it's handed is cancelled
+//!   (e.g. by running its commands with `cancel::output`);
+//! - its **entry mapper**: a function giving the name and optional value of
+//!   the entry an item is displayed as (both are matched against the
+//!   pattern);
+//! - its **preview**: the type of preview of the entries, or the command
+//!   previewing each of them;
+//! - its **actions**: what selecting an entry outputs and how it can be
+//!   edited.
+//!
+//! The channel itself is a newtype around [`BuiltChannel`] deriving
+//! `FromBuilder`, which generates its constructors and its `OnAir`
+//! implementation from a `builder` associated function:
+//! ```ignore
+//! use television_derive::FromBuilder;
+//!
+//! use crate::channels::builder::{BuiltChannel, ChannelBuilder};
+//! use crate::entry::PreviewType;
+//!
+//! #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+//! struct Service {
+//!     name: String,
+//!     description: String,
+//! }
+//!
+//! #[derive(FromBuilder)]
+//! pub struct Channel(BuiltChannel<Service>);
+//!
+//! impl Channel {
+//!     fn builder() -> ChannelBuilder<Service> {
+//!         ChannelBuilder::new("services", services, |service| {
+//!             (service.name.clone(), Some(service.description.clone()))
+//!         })
+//!         .preview(PreviewType::EnvVar)
+//!         .icon("service")
+//!         .output(|service| service.name.clone())
+//!     }
+//! }
+//!
+//! fn services(token: &CancellationToken) -> Vec<Service> {
+//!     // ...
+//! }
+//! ```
+//! Such a channel, wired up into `TelevisionChannel` and with a test loading
+//! its entries, is scaffolded by `make channel CHANNEL=<name>` (see the
+//! `television_scaffold` crate).
+//!
+//! Channels needing more than this (e.g. transitions from other channels or
+//! several sources) implement `OnAir` by hand.
+use std::hash::Hash;
+
+use devicons::FileIcon;
+use television_fuzzy::matcher::{config::Config, Matcher, Utf32String};
+use television_utils::cancel::CancellationToken;
+use television_utils::indices::sep_name_and_value_indices;
+
+use super::reload::Reloader;
+use super::OnAir;
+use crate::context::Context;
+use crate::entry::{Entry, PreviewCommand, PreviewType};
+
+const NUM_THREADS: usize = 1;
+
+/// Describes a channel, see the module documentation.
+pub struct ChannelBuilder<T> {
+    name: &'static str,
+    source: fn(&CancellationToken) -> Vec<T>,
+    entry: fn(&T) -> (String, Option<String>),
+    preview: PreviewType,
+    preview_command: Option<fn(&T) -> PreviewCommand>,
+    icon: Option<FileIcon>,
+    threads: usize,
+    output: Option<fn(&T) -> String>,
+    edit_command: Option<fn(&T) -> String>,
+}
+
+impl<T> ChannelBuilder<T>
+where
+    T: Sync + Send + Clone + Eq + Hash + 'static,
+{
+    /// Describe a channel listing the items returned by `source`, displayed
+    /// as the entries returned by `entry` (their name and optional value).
+    ///
+    /// The `name` is the lowercase channel name used in the channel specific
+    /// settings (e.g. `[channels.<name>]`).
+    pub fn new(
+        name: &'static str,
+        source: fn(&CancellationToken) -> Vec<T>,
+        entry: fn(&T) -> (String, Option<String>),
+    ) -> Self {
+        Self {
+            name,
+            source,
+            entry,
+            preview: PreviewType::default(),
+            preview_command: None,
+            icon: None,
+            threads: NUM_THREADS,
+            output: None,
+            edit_command: None,
+        }
+    }
+
+    /// The type of preview of the entries.
+    pub fn preview(mut self, preview: PreviewType) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// The command previewing an entry, instead of the type of preview of
+    /// the entries.
+    pub fn preview_command(
+        mut self,
+        preview_command: fn(&T) -> PreviewCommand,
+    ) -> Self {
+        self.preview_command = Some(preview_command);
+        self
+    }
+
+    /// The icon of the entries, as the name of a file having this icon (e.g.
+    /// `"config"` or `"font.ttf"`).
+    pub fn icon(mut self, icon: &str) -> Self {
+        self.icon = Some(FileIcon::from(icon));
+        self
+    }
+
+    /// The default number of matcher threads, which can be overridden in the
+    /// config.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// What selecting an entry outputs, instead of its name and value.
+    pub fn output(mut self, output: fn(&T) -> String) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// The shell command used to edit an entry.
+    pub fn edit_command(mut self, edit_command: fn(&T) -> String) -> Self {
+        self.edit_command = Some(edit_command);
+        self
+    }
+
+    /// Create the channel and start loading its items.
+    pub fn build(self, ctx: &Context) -> BuiltChannel<T> {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(ctx.matcher_threads(self.name, self.threads)),
+        );
+        let injector = matcher.injector();
+        let (source, entry) = (self.source, self.entry);
+        let token = CancellationToken::new();
+        let load_token = token.clone();
+        let startup = ctx.startup.clone();
+        let load_handle = tokio::spawn(async move {
+            startup.ready().await;
+            for item in source(&load_token) {
+                let () = injector.push(item, |item, cols| {
+                    fill_columns(entry, item, cols);
+                });
+            }
+        });
+        BuiltChannel {
+            matcher,
+            builder: self,
+            load_handle,
+            token,
+            reloader: Reloader::default(),
+        }
+    }
+}
+
+/// Match against the name and value of the entries, one after the other.
+fn fill_columns<T>(
+    entry: fn(&T) -> (String, Option<String>),
+    item: &T,
+    cols: &mut [Utf32String],
+) {
+    let (name, value) = entry(item);
+    cols[0] = (name + value.as_deref().unwrap_or_default()).into();
+}
+
+/// A channel created by a [`ChannelBuilder`].
+pub struct BuiltChannel<T>
+where
+    T: Sync + Send + Clone + 'static,
+{
+    matcher: Matcher<T>,
+    builder: ChannelBuilder<T>,
+    load_handle: tokio::task::JoinHandle<()>,
+    /// Cancels the loading of the items, and their reloads.
+    token: CancellationToken,
+    reloader: Reloader<T>,
+}
+
+impl<T> BuiltChannel<T>
+where
+    T: Sync + Send + Clone + Eq + Hash + 'static,
+{
+    fn entry(&self, item: &T) -> Entry {
+        let (name, value) = (self.builder.entry)(item);
+        let preview = match self.builder.preview_command {
+            Some(preview_command) => {
+                PreviewType::Command(preview_command(item))
+            }
+            None => self.builder.preview.clone(),
+        };
+        let mut entry = Entry::new(name, preview);
+        if let Some(value) = value {
+            entry = entry.with_value(value);
+        }
+        if let Some(icon) = self.builder.icon {
+            entry = entry.with_icon(icon);
+        }
+        if let Some(output) = self.builder.output {
+            entry = entry.with_output(output(item));
+        }
+        if let Some(edit_command) = self.builder.edit_command {
+            entry = entry.with_edit_command(edit_command(item));
+        }
+        entry
+    }
+}
+
+impl<T> OnAir for BuiltChannel<T>
+where
+    T: Sync + Send + Clone + Eq + Hash + 'static,
+{
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(items) = self.reloader.take() {
+            let entry = self.builder.entry;
+            self.matcher.update_items(items, |item, cols| {
+                fill_columns(entry, item, cols);
+            });
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                let mut entry = self.entry(&item.inner);
+                let (
+                    name_indices,
+                    value_indices,
+                    should_add_name_indices,
+                    should_add_value_indices,
+                ) = sep_name_and_value_indices(
+                    &mut item.match_indices.iter().map(|i| i.0).collect(),
+                    u32::try_from(entry.name.chars().count()).unwrap(),
+                );
+
+                if should_add_name_indices {
+                    entry = entry.with_name_match_ranges(
+                        name_indices.into_iter().map(|i| (i, i + 1)).collect(),
+                    );
+                }
+
+                if should_add_value_indices {
+                    entry = entry.with_value_match_ranges(
+                        value_indices
+                            .into_iter()
+                            .map(|i| (i, i + 1))
+                            .collect(),
+                    );
+                }
+
+                entry
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher
+            .get_result(index)
+            .map(|item| self.entry(&item.inner))
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.token.cancel();
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        let (source, token) = (self.builder.source, self.token.clone());
+        self.reloader.start(move || source(&token));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planets(_token: &CancellationToken) -> Vec<(String, String)> {
+        vec![
+            ("mercury".to_string(), "closest".to_string()),
+            ("neptune".to_string(), "farthest".to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_built_channel() {
+        let mut channel = ChannelBuilder::new("planets", planets, |p| {
+            (p.0.clone(), Some(p.1.clone()))
+        })
+        .preview(PreviewType::EnvVar)
+        .output(|p| p.0.to_uppercase())
+        .build(&Context::default());
+        channel.find("nefar");
+        let results = loop {
+            tokio::task::yield_now().await;
+            let results = channel.results(10, 0);
+            if !results.is_empty() && !channel.running() {
+                break results;
+            }
+        };
+        assert_eq!(results.len(), 1);
+        let entry = &results[0];
+        assert_eq!(entry.name, "neptune");
+        assert_eq!(entry.value.as_deref(), Some("farthest"));
+        assert_eq!(entry.name_match_ranges, Some(vec![(0, 1), (1, 2)]));
+        assert_eq!(
+            entry.value_match_ranges,
+            Some(vec![(0, 1), (1, 2), (2, 3)])
+        );
+        assert_eq!(entry.preview_type, PreviewType::EnvVar);
+        assert_eq!(entry.stdout_repr(), "NEPTUNE");
+
+        // channels that can't lend their entries go through `results`
+        let mut lent = Vec::new();
+        channel.for_each_result(0..10, &mut |entry| {
+            lent.push(entry.name.to_string());
+        });
+        assert_eq!(lent, ["neptune"]);
+    }
+}
@@ -0,0 +1,273 @@
+use devicons::FileIcon;
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use super::external::stream_lines;
+use super::reload::Reloader;
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewCommand, PreviewType};
+use crate::time_range::TimeRange;
+use television_fuzzy::matcher::{config::Config, Matcher};
+use television_utils::cancel::{output, CancellationToken};
+use television_utils::strings::{preprocess_line, shell_quote};
+
+/// A channel defined in the config file, listing the lines printed by a
+/// shell command.
+///
+/// In the preview command and the output template, `{}` is replaced by the
+/// selected line (shell-quoted in the preview command).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CableChannelPrototype {
+    pub name: String,
+    pub source_command: String,
+    #[serde(default)]
+    pub preview_command: Option<String>,
+    #[serde(default)]
+    pub output: Option<String>,
+}
+
+impl Context {
+    /// Register the cable channels defined in the config file, so that they
+    /// can be picked from the remote control.
+    pub fn set_cable_channels(
+        &mut self,
+        prototypes: Vec<CableChannelPrototype>,
+    ) {
+        self.cable_channels = prototypes;
+    }
+
+    /// The registered cable channel with the given (case insensitive) name.
+    pub fn cable_channel(&self, name: &str) -> Option<CableChannelPrototype> {
+        self.cable_channels
+            .iter()
+            .find(|prototype| prototype.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    /// The names of the registered cable channels.
+    pub fn cable_channel_names(&self) -> Vec<String> {
+        self.cable_channels
+            .iter()
+            .map(|prototype| prototype.name.clone())
+            .collect()
+    }
+}
+
+pub struct Channel {
+    prototype: CableChannelPrototype,
+    time_range: TimeRange,
+    matcher: Matcher<String>,
+    icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+    /// Kills the source command when reloading the channel (the streamed
+    /// one is killed along with the loading task).
+    token: CancellationToken,
+    reloader: Reloader<String>,
+}
+
+const NUM_THREADS: usize = 1;
+const FILE_ICON_STR: &str = "sh";
+
+impl Channel {
+    pub fn new(ctx: &Context, prototype: CableChannelPrototype) -> Self {
+        let matcher = Matcher::new(Config::default().n_threads(
+            ctx.matcher_threads(&prototype.name.to_lowercase(), NUM_THREADS),
+        ));
+        let load_handle = tokio::spawn(stream_lines(
+            matcher.injector(),
+            prototype.source_command.clone(),
+            ctx.time_range().clone(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            prototype,
+            time_range: ctx.time_range().clone(),
+            matcher,
+            icon: FileIcon::from(FILE_ICON_STR),
+            load_handle,
+            token: CancellationToken::new(),
+            reloader: Reloader::default(),
+        }
+    }
+
+    /// The name of the channel, as defined in the config file.
+    pub fn name(&self) -> &str {
+        &self.prototype.name
+    }
+
+    /// What the channel was created from.
+    pub fn prototype(&self) -> &CableChannelPrototype {
+        &self.prototype
+    }
+
+    fn entry(&self, line: &str) -> Entry {
+        let preview = match &self.prototype.preview_command {
+            Some(command) => PreviewType::Command(PreviewCommand::new(
+                command.replace("{}", &shell_quote(line)),
+            )),
+            None => PreviewType::Basic,
+        };
+        let mut entry =
+            Entry::new(line.to_string(), preview).with_icon(self.icon);
+        if let Some(output) = &self.prototype.output {
+            entry = entry.with_output(output.replace("{}", line));
+        }
+        entry
+    }
+}
+
+/// An empty channel, cable channels being created from their prototype.
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(
+            ctx,
+            CableChannelPrototype {
+                name: "cable".to_string(),
+                source_command: String::new(),
+                preview_command: None,
+                output: None,
+            },
+        )
+    }
+}
+
+/// The non-empty lines printed by the given shell command, run within the
+/// given time range.
+fn lines(
+    command: &str,
+    time_range: &TimeRange,
+    token: &CancellationToken,
+) -> Vec<String> {
+    if command.is_empty() {
+        return Vec::new();
+    }
+    debug!("Running cable channel source command {:?}", command);
+    match output(&mut time_range.shell_command(command), token) {
+        Ok(output) => {
+            if !output.status.success() {
+                warn!("Command {:?} exited with {}", command, output.status);
+            }
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(preprocess_line)
+                .collect()
+        }
+        Err(e) => {
+            warn!("Failed to run command {:?}: {:?}", command, e);
+            Vec::new()
+        }
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(lines) = self.reloader.take() {
+            self.matcher.update_items(lines, |line, cols| {
+                cols[0] = line.clone().into();
+            });
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                self.entry(&item.inner)
+                    .with_name_match_ranges(item.match_indices)
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher
+            .get_result(index)
+            .map(|item| self.entry(&item.inner))
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.token.cancel();
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        let command = self.prototype.source_command.clone();
+        let time_range = self.time_range.clone();
+        let token = self.token.clone();
+        self.reloader
+            .start(move || lines(&command, &time_range, &token));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prototype(name: &str) -> CableChannelPrototype {
+        CableChannelPrototype {
+            name: name.to_string(),
+            source_command: "printf 'alpha\\n\\nbeta\\n'".to_string(),
+            preview_command: Some("echo {}".to_string()),
+            output: Some("got {}".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut ctx = Context::default();
+        ctx.set_cable_channels(vec![prototype("Planets")]);
+        assert_eq!(ctx.cable_channel("planets"), Some(prototype("Planets")));
+        assert_eq!(ctx.cable_channel("stars"), None);
+        assert_eq!(ctx.cable_channel_names(), vec!["Planets".to_string()]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cable_channel() {
+        let mut channel =
+            Channel::new(&Context::default(), prototype("planets"));
+        channel.find("bet");
+        let results = loop {
+            tokio::task::yield_now().await;
+            let results = channel.results(10, 0);
+            if !results.is_empty() && !channel.running() {
+                break results;
+            }
+        };
+        assert_eq!(channel.total_count(), 2);
+        let entry = &results[0];
+        assert_eq!(entry.name, "beta");
+        assert_eq!(
+            entry.preview_type,
+            PreviewType::Command(PreviewCommand::new(format!(
+                "echo {}",
+                shell_quote("beta")
+            )))
+        );
+        assert_eq!(entry.stdout_repr(), "got beta");
+    }
+}
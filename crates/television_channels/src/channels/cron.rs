@@ -0,0 +1,841 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::Path;
+use std::process::Command;
+
+use devicons::FileIcon;
+use jiff::civil::{self, DateTime};
+use jiff::Zoned;
+use tracing::debug;
+
+use super::reload::Reloader;
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewCommand, PreviewType};
+use crate::startup::Startup;
+use television_fuzzy::matcher::{
+    config::Config, injector::Injector, Matcher, Utf32String,
+};
+use television_utils::cancel::{output, CancellationToken};
+use television_utils::indices::sep_name_and_value_indices;
+use television_utils::strings::{preprocess_line, shell_quote};
+
+/// A scheduled job: either a crontab entry or a systemd timer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Job {
+    Cron(Box<CronJob>),
+    Timer(Timer),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CronJob {
+    schedule: Schedule,
+    /// The raw schedule as written in the crontab (e.g. `0 3 * * *`).
+    raw_schedule: String,
+    command: String,
+    /// The user the job runs as (only specified in system crontabs).
+    user: Option<String>,
+    /// The crontab file the job comes from (`None` for the user's crontab).
+    source: Option<String>,
+    /// The crontab line the job was parsed from.
+    line: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Timer {
+    unit: String,
+    description: String,
+    user: bool,
+}
+
+impl Job {
+    /// The name of the job, displayed in the results list.
+    fn name(&self) -> &str {
+        match self {
+            Job::Cron(job) => &job.command,
+            Job::Timer(timer) => &timer.unit,
+        }
+    }
+
+    /// A short description of the job, displayed next to its name.
+    fn description(&self) -> String {
+        match self {
+            Job::Cron(job) => format!(
+                "{} ({})",
+                job.schedule.describe(),
+                job.source.as_deref().unwrap_or("crontab")
+            ),
+            Job::Timer(timer) => format!(
+                "{} ({} timer)",
+                timer.description,
+                if timer.user { "user" } else { "system" }
+            ),
+        }
+    }
+
+    fn edit_command(&self) -> String {
+        match self {
+            Job::Cron(job) => match &job.source {
+                Some(source) => format!("sudoedit {}", shell_quote(source)),
+                None => "crontab -e".to_string(),
+            },
+            // system units can only be edited by root
+            Job::Timer(timer) => format!(
+                "{}{} edit --full {}",
+                if timer.user { "" } else { "sudo " },
+                systemctl(timer.user),
+                shell_quote(&timer.unit)
+            ),
+        }
+    }
+}
+
+impl CronJob {
+    fn details(&self, now: &Zoned) -> String {
+        let mut details = format!(
+            "Schedule: {}\n          {}",
+            self.raw_schedule,
+            self.schedule.describe()
+        );
+        if let Some(next) = self.schedule.next_run(now.datetime()) {
+            let next = next.to_zoned(now.time_zone().clone()).map_or_else(
+                |_| next.to_string(),
+                |z| z.strftime("%a %Y-%m-%d %H:%M %Z").to_string(),
+            );
+            let _ = write!(details, "\nNext run: {next}");
+        }
+        if let Some(user) = &self.user {
+            let _ = write!(details, "\nUser:     {user}");
+        }
+        let _ = write!(
+            details,
+            "\nSource:   {}\n\nCommand:\n  {}",
+            self.source.as_deref().unwrap_or("crontab (current user)"),
+            self.command
+        );
+        details
+    }
+}
+
+impl Timer {
+    /// Show the timer's next and last runs and the timer and service units.
+    fn preview_command(&self) -> PreviewCommand {
+        let systemctl = systemctl(self.user);
+        let unit = shell_quote(&self.unit);
+        PreviewCommand::new(format!(
+            "{systemctl} list-timers --all --no-pager {unit}; echo; \
+             {systemctl} cat --no-pager {unit} \
+             \"$({systemctl} show -p Unit --value {unit})\""
+        ))
+    }
+}
+
+fn systemctl(user: bool) -> &'static str {
+    if user {
+        "systemctl --user"
+    } else {
+        "systemctl"
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct Channel {
+    matcher: Matcher<Job>,
+    file_icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+    /// Kills the commands listing the jobs, when loading or reloading them.
+    token: CancellationToken,
+    reloader: Reloader<Job>,
+}
+
+const NUM_THREADS: usize = 1;
+const FILE_ICON_STR: &str = "config";
+
+const SYSTEM_CRONTAB: &str = "/etc/crontab";
+const SYSTEM_CRONTAB_DIR: &str = "/etc/cron.d";
+
+impl Channel {
+    pub fn new(ctx: &Context) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(ctx.matcher_threads("cron", NUM_THREADS)),
+        );
+        let token = CancellationToken::new();
+        let load_handle = tokio::spawn(load_jobs(
+            matcher.injector(),
+            token.clone(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            matcher,
+            file_icon: FileIcon::from(FILE_ICON_STR),
+            load_handle,
+            token,
+            reloader: Reloader::default(),
+        }
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx)
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(jobs) = self.reloader.take() {
+            self.matcher.update_items(jobs, job_columns);
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                let name = item.inner.name();
+                let (
+                    name_indices,
+                    value_indices,
+                    should_add_name_indices,
+                    should_add_value_indices,
+                ) = sep_name_and_value_indices(
+                    &mut item.match_indices.iter().map(|i| i.0).collect(),
+                    u32::try_from(name.len()).unwrap(),
+                );
+
+                let mut entry =
+                    Entry::new(name.to_string(), PreviewType::PlainText)
+                        .with_value(item.inner.description())
+                        .with_icon(self.file_icon);
+
+                if should_add_name_indices {
+                    entry = entry.with_name_match_ranges(
+                        name_indices.into_iter().map(|i| (i, i + 1)).collect(),
+                    );
+                }
+
+                if should_add_value_indices {
+                    entry = entry.with_value_match_ranges(
+                        value_indices
+                            .into_iter()
+                            .map(|i| (i, i + 1))
+                            .collect(),
+                    );
+                }
+
+                entry
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            let job = &item.inner;
+            let entry = match job {
+                Job::Cron(cron_job) => {
+                    Entry::new(job.name().to_string(), PreviewType::PlainText)
+                        .with_value(cron_job.details(&Zoned::now()))
+                        .with_output(cron_job.line.clone())
+                }
+                Job::Timer(timer) => Entry::new(
+                    job.name().to_string(),
+                    PreviewType::Command(timer.preview_command()),
+                ),
+            };
+            entry
+                .with_icon(self.file_icon)
+                .with_edit_command(job.edit_command())
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.token.cancel();
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        let token = self.token.clone();
+        self.reloader.start(move || jobs(&token));
+    }
+}
+
+async fn load_jobs(
+    injector: Injector<Job>,
+    token: CancellationToken,
+    startup: Startup,
+) {
+    startup.ready().await;
+    inject_jobs(&injector, jobs(&token));
+}
+
+fn jobs(token: &CancellationToken) -> Vec<Job> {
+    let mut jobs = Vec::new();
+    if let Some(crontab) =
+        command_output(Command::new("crontab").arg("-l"), token)
+    {
+        jobs.extend(
+            parse_crontab(&crontab, None)
+                .into_iter()
+                .map(|job| Job::Cron(Box::new(job))),
+        );
+    }
+    let mut system_crontabs = vec![Path::new(SYSTEM_CRONTAB).to_path_buf()];
+    if let Ok(entries) = std::fs::read_dir(SYSTEM_CRONTAB_DIR) {
+        system_crontabs.extend(entries.filter_map(|e| Some(e.ok()?.path())));
+    }
+    for path in system_crontabs {
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            let source = path.to_string_lossy().to_string();
+            jobs.extend(
+                parse_crontab(&content, Some(&source))
+                    .into_iter()
+                    .map(|job| Job::Cron(Box::new(job))),
+            );
+        }
+    }
+    for user in [false, true] {
+        let mut cmd = Command::new("systemctl");
+        if user {
+            cmd.arg("--user");
+        }
+        cmd.args([
+            "list-units",
+            "--type=timer",
+            "--all",
+            "--no-legend",
+            "--plain",
+            "--no-pager",
+        ]);
+        if let Some(output) = command_output(&mut cmd, token) {
+            jobs.extend(
+                parse_timers(&output, user).into_iter().map(Job::Timer),
+            );
+        }
+    }
+    jobs
+}
+
+fn inject_jobs(injector: &Injector<Job>, jobs: Vec<Job>) {
+    for job in jobs {
+        let () = injector.push(job, job_columns);
+    }
+}
+
+fn job_columns(job: &Job, cols: &mut [Utf32String]) {
+    cols[0] = (job.name().to_string() + job.description().as_str()).into();
+}
+
+fn command_output(
+    command: &mut Command,
+    token: &CancellationToken,
+) -> Option<String> {
+    match output(command, token) {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).to_string())
+        }
+        Ok(output) => {
+            debug!("{:?} exited with {}", command, output.status);
+            None
+        }
+        Err(e) => {
+            debug!("Failed to run {:?}: {:?}", command, e);
+            None
+        }
+    }
+}
+
+/// Parse the jobs of a crontab.
+///
+/// System crontabs (those with a `source`) have an additional user field
+/// between the schedule and the command.
+fn parse_crontab(content: &str, source: Option<&str>) -> Vec<CronJob> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let first = fields.next()?;
+            let (schedule, raw_schedule) = if first.starts_with('@') {
+                (Schedule::parse_nickname(first)?, first.to_string())
+            } else {
+                let raw_fields = std::iter::once(first)
+                    .chain(fields.by_ref().take(4))
+                    .collect::<Vec<_>>();
+                (Schedule::parse_fields(&raw_fields)?, raw_fields.join(" "))
+            };
+            let user = if source.is_some() {
+                Some(fields.next()?.to_string())
+            } else {
+                None
+            };
+            let command = fields.collect::<Vec<_>>().join(" ");
+            if command.is_empty() {
+                return None;
+            }
+            Some(CronJob {
+                schedule,
+                raw_schedule,
+                command: preprocess_line(&command),
+                user,
+                source: source.map(String::from),
+                line: line.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse the output of `systemctl list-units --type=timer --plain
+/// --no-legend`, e.g.:
+/// ```text
+/// logrotate.timer loaded active waiting Daily rotation of log files
+/// ```
+fn parse_timers(output: &str, user: bool) -> Vec<Timer> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let unit = fields.next()?;
+            // skip the LOAD, ACTIVE and SUB columns
+            let description = fields.skip(3).collect::<Vec<_>>().join(" ");
+            Some(Timer {
+                unit: preprocess_line(unit),
+                description: preprocess_line(&description),
+                user,
+            })
+        })
+        .collect()
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// A cron schedule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Schedule {
+    /// `@reboot`
+    AtStartup,
+    Fields {
+        minutes: Field,
+        hours: Field,
+        days_of_month: Field,
+        months: Field,
+        days_of_week: Field,
+    },
+}
+
+/// A cron field: the set of values it matches, and whether it was written as
+/// a wildcard (`*` or `*/n`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Field {
+    values: BTreeSet<u8>,
+    /// The step of a `*/n` field, `Some(1)` for `*`.
+    wildcard_step: Option<u8>,
+}
+
+impl Field {
+    /// Parse a cron field such as `*`, `*/5`, `1-5`, `1,15` or `mon-fri`.
+    fn parse(field: &str, min: u8, max: u8, names: &[&str]) -> Option<Self> {
+        let parse_value = |value: &str| -> Option<u8> {
+            value.parse().ok().or_else(|| {
+                names
+                    .iter()
+                    .position(|name| name[..3].eq_ignore_ascii_case(value))
+                    .and_then(|i| u8::try_from(i).ok())
+                    .map(|i| i + min)
+            })
+        };
+        let mut values = BTreeSet::new();
+        let mut wildcard_step = None;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<u8>().ok()?),
+                None => (part, 1),
+            };
+            if step == 0 {
+                return None;
+            }
+            let (start, end) = if range == "*" {
+                if field.contains(',') {
+                    return None;
+                }
+                wildcard_step = Some(step);
+                (min, max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                (parse_value(start)?, parse_value(end)?)
+            } else {
+                let value = parse_value(range)?;
+                // `5/10` means every 10 starting from 5
+                (value, if step > 1 { max } else { value })
+            };
+            if start < min || end > max || start > end {
+                return None;
+            }
+            values.extend((start..=end).step_by(step as usize));
+        }
+        Some(Field {
+            values,
+            wildcard_step,
+        })
+    }
+
+    fn is_any(&self) -> bool {
+        self.wildcard_step == Some(1)
+    }
+
+    fn matches(&self, value: u8) -> bool {
+        self.values.contains(&value)
+    }
+
+    fn single(&self) -> Option<u8> {
+        if self.values.len() == 1 {
+            self.values.first().copied()
+        } else {
+            None
+        }
+    }
+
+    fn describe(&self, names: Option<&[&str]>, min: u8) -> String {
+        let values = self
+            .values
+            .iter()
+            .map(|v| match names {
+                Some(names) => names[usize::from(v - min)].to_string(),
+                None => v.to_string(),
+            })
+            .collect::<Vec<_>>();
+        join_list(&values)
+    }
+}
+
+fn join_list(values: &[String]) -> String {
+    match values {
+        [] => String::new(),
+        [value] => value.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+impl Schedule {
+    fn parse_nickname(nickname: &str) -> Option<Self> {
+        let fields = match nickname {
+            "@reboot" => return Some(Schedule::AtStartup),
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            _ => return None,
+        };
+        Self::parse_fields(&fields.split(' ').collect::<Vec<_>>())
+    }
+
+    fn parse_fields(fields: &[&str]) -> Option<Self> {
+        let [minutes, hours, days_of_month, months, days_of_week] = fields
+        else {
+            return None;
+        };
+        let mut days_of_week = Field::parse(days_of_week, 0, 7, &WEEKDAYS)?;
+        // both 0 and 7 are sunday
+        if days_of_week.values.remove(&7) {
+            days_of_week.values.insert(0);
+        }
+        Some(Schedule::Fields {
+            minutes: Field::parse(minutes, 0, 59, &[])?,
+            hours: Field::parse(hours, 0, 23, &[])?,
+            days_of_month: Field::parse(days_of_month, 1, 31, &[])?,
+            months: Field::parse(months, 1, 12, &MONTHS)?,
+            days_of_week,
+        })
+    }
+
+    /// Describe the schedule in human terms (e.g. `every day at 03:00`).
+    fn describe(&self) -> String {
+        let Schedule::Fields {
+            minutes,
+            hours,
+            days_of_month,
+            months,
+            days_of_week,
+        } = self
+        else {
+            return "at startup".to_string();
+        };
+
+        let time = match (minutes.single(), hours.single()) {
+            (Some(minute), Some(hour)) => format!("at {hour:02}:{minute:02}"),
+            (Some(minute), None) if hours.values.len() <= 4 => format!(
+                "at {}",
+                join_list(
+                    &hours
+                        .values
+                        .iter()
+                        .map(|hour| format!("{hour:02}:{minute:02}"))
+                        .collect::<Vec<_>>()
+                )
+            ),
+            (Some(minute), None) if hours.is_any() => {
+                format!("every hour at minute {minute}")
+            }
+            _ => {
+                let minutes = match minutes.wildcard_step {
+                    Some(1) => "every minute".to_string(),
+                    Some(step) => format!("every {step} minutes"),
+                    None => format!("at minute {}", minutes.describe(None, 0)),
+                };
+                match hours.wildcard_step {
+                    Some(1) => minutes,
+                    Some(step) => format!("{minutes} of every {step} hours"),
+                    None => format!(
+                        "{minutes} past hour {}",
+                        hours.describe(None, 0)
+                    ),
+                }
+            }
+        };
+
+        let days = match (days_of_month.is_any(), days_of_week.is_any()) {
+            (true, true) => None,
+            (true, false) => Some(format!(
+                "on {}",
+                days_of_week.describe(Some(&WEEKDAYS), 0)
+            )),
+            (false, true) => Some(format!(
+                "on day {} of the month",
+                days_of_month.describe(None, 1)
+            )),
+            (false, false) => Some(format!(
+                "on day {} of the month or on {}",
+                days_of_month.describe(None, 1),
+                days_of_week.describe(Some(&WEEKDAYS), 0)
+            )),
+        };
+
+        let mut description = match days {
+            Some(days) => format!("{time} {days}"),
+            None if time.starts_with("every") => time,
+            None => format!("every day {time}"),
+        };
+        if !months.is_any() {
+            let _ = write!(
+                description,
+                " in {}",
+                months.describe(Some(&MONTHS), 1)
+            );
+        }
+        description
+    }
+
+    /// Compute the next time (strictly after `after`) the schedule runs at.
+    fn next_run(&self, after: DateTime) -> Option<DateTime> {
+        let Schedule::Fields {
+            minutes,
+            hours,
+            days_of_month,
+            months,
+            days_of_week,
+        } = self
+        else {
+            return None;
+        };
+        let mut date = after.date();
+        // look far enough ahead to find schedules such as the 29th of
+        // february
+        for _ in 0..(366 * 8) {
+            let month = u8::try_from(date.month()).ok()?;
+            let day = u8::try_from(date.day()).ok()?;
+            let weekday =
+                u8::try_from(date.weekday().to_sunday_zero_offset()).ok()?;
+            // when both the day of month and the day of week are
+            // restricted, cron runs the job when either matches
+            let day_matches =
+                match (days_of_month.is_any(), days_of_week.is_any()) {
+                    (true, true) => true,
+                    (true, false) => days_of_week.matches(weekday),
+                    (false, true) => days_of_month.matches(day),
+                    (false, false) => {
+                        days_of_month.matches(day)
+                            || days_of_week.matches(weekday)
+                    }
+                };
+            if months.matches(month) && day_matches {
+                for hour in &hours.values {
+                    for minute in &minutes.values {
+                        let candidate = date.to_datetime(civil::time(
+                            i8::try_from(*hour).ok()?,
+                            i8::try_from(*minute).ok()?,
+                            0,
+                            0,
+                        ));
+                        if candidate > after {
+                            return Some(candidate);
+                        }
+                    }
+                }
+            }
+            date = date.tomorrow().ok()?;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(fields: &str) -> Schedule {
+        if fields.starts_with('@') {
+            Schedule::parse_nickname(fields).unwrap()
+        } else {
+            Schedule::parse_fields(&fields.split(' ').collect::<Vec<_>>())
+                .unwrap()
+        }
+    }
+
+    #[test]
+    fn test_describe_schedule() {
+        for (fields, description) in [
+            ("0 3 * * *", "every day at 03:00"),
+            ("@daily", "every day at 00:00"),
+            ("@hourly", "every hour at minute 0"),
+            ("@reboot", "at startup"),
+            ("* * * * *", "every minute"),
+            ("*/5 * * * *", "every 5 minutes"),
+            ("30 8,20 * * *", "every day at 08:30 and 20:30"),
+            (
+                "0 9 * * mon-fri",
+                "at 09:00 on Monday, Tuesday, Wednesday, Thursday and Friday",
+            ),
+            ("15 0 1,15 * *", "at 00:15 on day 1 and 15 of the month"),
+            ("0 0 1 1 *", "at 00:00 on day 1 of the month in January"),
+            ("0 12 * * 7", "at 12:00 on Sunday"),
+        ] {
+            assert_eq!(schedule(fields).describe(), description, "{fields}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_schedules() {
+        for fields in ["60 * * * *", "* 24 * * *", "* * 0 * *", "*/0 * * * *"]
+        {
+            assert!(
+                Schedule::parse_fields(&fields.split(' ').collect::<Vec<_>>())
+                    .is_none(),
+                "{fields}"
+            );
+        }
+        assert!(Schedule::parse_nickname("@sometimes").is_none());
+    }
+
+    #[test]
+    fn test_next_run() {
+        // a thursday
+        let now = civil::date(2024, 11, 14).at(10, 30, 0, 0);
+        for (fields, next) in [
+            ("0 3 * * *", civil::date(2024, 11, 15).at(3, 0, 0, 0)),
+            ("*/15 * * * *", civil::date(2024, 11, 14).at(10, 45, 0, 0)),
+            ("0 9 * * mon", civil::date(2024, 11, 18).at(9, 0, 0, 0)),
+            ("0 0 29 2 *", civil::date(2028, 2, 29).at(0, 0, 0, 0)),
+            ("30 10 14 11 *", civil::date(2025, 11, 14).at(10, 30, 0, 0)),
+        ] {
+            assert_eq!(schedule(fields).next_run(now), Some(next), "{fields}");
+        }
+        assert_eq!(schedule("@reboot").next_run(now), None);
+    }
+
+    #[test]
+    fn test_parse_crontab() {
+        let content = "\
+# m h dom mon dow command
+SHELL=/bin/sh
+0 3 * * * /usr/local/bin/backup.sh --full
+@reboot   ~/bin/start-agent
+";
+        let jobs = parse_crontab(content, None);
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].command, "/usr/local/bin/backup.sh --full");
+        assert_eq!(jobs[0].raw_schedule, "0 3 * * *");
+        assert_eq!(jobs[1].command, "~/bin/start-agent");
+        assert_eq!(jobs[1].schedule, Schedule::AtStartup);
+
+        let system_content =
+            "17 * * * * root cd / && run-parts --report /etc/cron.hourly";
+        let jobs = parse_crontab(system_content, Some("/etc/crontab"));
+        assert_eq!(jobs[0].user.as_deref(), Some("root"));
+        assert_eq!(
+            jobs[0].command,
+            "cd / && run-parts --report /etc/cron.hourly"
+        );
+        assert_eq!(
+            Job::Cron(Box::new(jobs[0].clone())).edit_command(),
+            "sudoedit '/etc/crontab'"
+        );
+    }
+
+    #[test]
+    fn test_parse_timers() {
+        let output = "\
+logrotate.timer     loaded active waiting Daily rotation of log files
+fstrim.timer        loaded active waiting Discard unused blocks once a week
+";
+        let timers = parse_timers(output, false);
+        assert_eq!(timers.len(), 2);
+        assert_eq!(timers[0].unit, "logrotate.timer");
+        assert_eq!(timers[0].description, "Daily rotation of log files");
+        assert_eq!(
+            Job::Timer(timers[1].clone()).edit_command(),
+            "sudo systemctl edit --full 'fstrim.timer'"
+        );
+        let user_timers = parse_timers(output, true);
+        assert_eq!(
+            Job::Timer(user_timers[0].clone()).edit_command(),
+            "systemctl --user edit --full 'logrotate.timer'"
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use devicons::FileIcon;
+use tracing::debug;
+
+use super::reload::Reloader;
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewCommand, PreviewType};
+use crate::startup::Startup;
+use television_fuzzy::matcher::{
+    config::Config, injector::Injector, Matcher, Utf32String,
+};
+use television_utils::indices::sep_name_and_value_indices;
+use television_utils::strings::{preprocess_line, shell_quote};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DnsEntry {
+    /// The host name (or address, for name servers).
+    host: String,
+    /// Where the entry comes from and what it resolves to.
+    description: String,
+    kind: DnsEntryKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum DnsEntryKind {
+    /// A host name mapped in the hosts file.
+    Host,
+    /// A name server from `resolv.conf`.
+    NameServer,
+    /// A search domain from `resolv.conf`.
+    SearchDomain,
+    /// A query found in a local resolver's log.
+    Query,
+}
+
+impl DnsEntry {
+    fn dig_command(&self) -> PreviewCommand {
+        let host = shell_quote(&self.host);
+        PreviewCommand::new(match self.kind {
+            DnsEntryKind::NameServer => format!("dig -x {host}"),
+            DnsEntryKind::SearchDomain => format!("dig {host} SOA"),
+            DnsEntryKind::Host | DnsEntryKind::Query => format!("dig {host}"),
+        })
+    }
+}
+
+#[cfg(unix)]
+const HOSTS_FILE: &str = "/etc/hosts";
+#[cfg(windows)]
+const HOSTS_FILE: &str = r"C:\Windows\System32\drivers\etc\hosts";
+const RESOLV_CONF_FILE: &str = "/etc/resolv.conf";
+/// Log files of local resolvers (dnsmasq and pi-hole) that might contain
+/// recent DNS queries.
+const RESOLVER_LOG_FILES: [&str; 2] =
+    ["/var/log/dnsmasq.log", "/var/log/pihole/pihole.log"];
+/// The maximum number of distinct recent queries to load from resolver logs.
+const MAX_RECENT_QUERIES: usize = 500;
+/// How much of the end of the resolver logs is read, as they can grow large.
+const RESOLVER_LOG_TAIL_SIZE: u64 = 4 * 1024 * 1024;
+
+#[allow(clippy::module_name_repetitions)]
+pub struct Channel {
+    matcher: Matcher<DnsEntry>,
+    file_icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+    reloader: Reloader<DnsEntry>,
+}
+
+const NUM_THREADS: usize = 1;
+const FILE_ICON_STR: &str = "config";
+
+impl Channel {
+    pub fn new(ctx: &Context) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(ctx.matcher_threads("dns", NUM_THREADS)),
+        );
+        let load_handle = tokio::spawn(load_dns_entries(
+            matcher.injector(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            matcher,
+            file_icon: FileIcon::from(FILE_ICON_STR),
+            load_handle,
+            reloader: Reloader::default(),
+        }
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx)
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(entries) = self.reloader.take() {
+            self.matcher.update_items(entries, dns_entry_columns);
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                let (
+                    name_indices,
+                    value_indices,
+                    should_add_name_indices,
+                    should_add_value_indices,
+                ) = sep_name_and_value_indices(
+                    &mut item.match_indices.iter().map(|i| i.0).collect(),
+                    u32::try_from(item.inner.host.len()).unwrap(),
+                );
+
+                let mut entry = Entry::new(
+                    item.inner.host.clone(),
+                    PreviewType::Command(item.inner.dig_command()),
+                )
+                .with_value(item.inner.description.clone())
+                .with_icon(self.file_icon);
+
+                if should_add_name_indices {
+                    entry = entry.with_name_match_ranges(
+                        name_indices.into_iter().map(|i| (i, i + 1)).collect(),
+                    );
+                }
+
+                if should_add_value_indices {
+                    entry = entry.with_value_match_ranges(
+                        value_indices
+                            .into_iter()
+                            .map(|i| (i, i + 1))
+                            .collect(),
+                    );
+                }
+
+                entry
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            Entry::new(
+                item.inner.host.clone(),
+                PreviewType::Command(item.inner.dig_command()),
+            )
+            .with_icon(self.file_icon)
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        self.reloader.start(dns_entries);
+    }
+}
+
+async fn load_dns_entries(injector: Injector<DnsEntry>, startup: Startup) {
+    startup.ready().await;
+    let entries = tokio::task::spawn_blocking(dns_entries)
+        .await
+        .unwrap_or_default();
+    inject_dns_entries(&injector, entries);
+}
+
+fn dns_entries() -> Vec<DnsEntry> {
+    let mut entries = Vec::new();
+    if let Ok(content) = std::fs::read_to_string(HOSTS_FILE) {
+        entries.extend(parse_hosts(&content));
+    }
+    if let Ok(content) = std::fs::read_to_string(RESOLV_CONF_FILE) {
+        entries.extend(parse_resolv_conf(&content));
+    }
+    for log_file in RESOLVER_LOG_FILES {
+        if Path::new(log_file).exists() {
+            debug!("Loading recent DNS queries from {}", log_file);
+            if let Ok(content) = read_tail(log_file, RESOLVER_LOG_TAIL_SIZE) {
+                entries.extend(parse_resolver_log(&content));
+            }
+        }
+    }
+    entries
+}
+
+/// Read the last lines of the given file, up to the given number of bytes.
+fn read_tail(path: impl AsRef<Path>, max_bytes: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let start = file.metadata()?.len().saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::new();
+    file.take(max_bytes).read_to_end(&mut bytes)?;
+    let content = String::from_utf8_lossy(&bytes);
+    // the first line is likely cut
+    Ok(match content.split_once('\n') {
+        Some((_, rest)) if start > 0 => rest.to_string(),
+        _ => content.into_owned(),
+    })
+}
+
+fn inject_dns_entries(injector: &Injector<DnsEntry>, entries: Vec<DnsEntry>) {
+    for entry in entries {
+        let () = injector.push(entry, dns_entry_columns);
+    }
+}
+
+fn dns_entry_columns(entry: &DnsEntry, cols: &mut [Utf32String]) {
+    cols[0] = (entry.host.clone() + entry.description.as_str()).into();
+}
+
+fn parse_hosts(content: &str) -> Vec<DnsEntry> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| {
+            let mut fields = line.split_whitespace();
+            let address = fields.next().map(preprocess_line);
+            fields.filter_map(move |host| {
+                Some(DnsEntry {
+                    host: preprocess_line(host),
+                    description: format!("{} (hosts)", address.as_ref()?),
+                    kind: DnsEntryKind::Host,
+                })
+            })
+        })
+        .collect()
+}
+
+fn parse_resolv_conf(content: &str) -> Vec<DnsEntry> {
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("nameserver") => {
+                entries.extend(fields.next().map(|address| DnsEntry {
+                    host: preprocess_line(address),
+                    description: "nameserver (resolv.conf)".to_string(),
+                    kind: DnsEntryKind::NameServer,
+                }));
+            }
+            Some("search" | "domain") => {
+                entries.extend(fields.map(|domain| DnsEntry {
+                    host: preprocess_line(domain),
+                    description: "search domain (resolv.conf)".to_string(),
+                    kind: DnsEntryKind::SearchDomain,
+                }));
+            }
+            _ => {}
+        }
+    }
+    entries
+}
+
+/// Parse the queries out of a dnsmasq style log, e.g.:
+/// ```text
+/// Nov 10 10:00:00 dnsmasq[42]: query[A] example.com from 192.168.1.10
+/// ```
+///
+/// The most recent queries come first.
+fn parse_resolver_log(content: &str) -> Vec<DnsEntry> {
+    let mut seen = HashSet::new();
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| {
+            let (_, query) = line.split_once(": query[")?;
+            let (record_type, rest) = query.split_once("] ")?;
+            let (host, client) = rest.split_once(" from ")?;
+            Some((record_type, host, client))
+        })
+        .filter(|(_, host, _)| seen.insert(host.to_string()))
+        .take(MAX_RECENT_QUERIES)
+        .map(|(record_type, host, client)| DnsEntry {
+            host: preprocess_line(host),
+            description: format!(
+                "{record_type} query from {} (resolver log)",
+                preprocess_line(client)
+            ),
+            kind: DnsEntryKind::Query,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hosts() {
+        let content = "\
+# comment
+127.0.0.1   localhost
+::1         localhost ip6-localhost # trailing comment
+
+192.168.1.2 nas.lan nas
+";
+        let hosts: Vec<_> = parse_hosts(content)
+            .into_iter()
+            .map(|e| (e.host, e.description))
+            .collect();
+        assert_eq!(
+            hosts,
+            vec![
+                ("localhost".to_string(), "127.0.0.1 (hosts)".to_string()),
+                ("localhost".to_string(), "::1 (hosts)".to_string()),
+                ("ip6-localhost".to_string(), "::1 (hosts)".to_string()),
+                ("nas.lan".to_string(), "192.168.1.2 (hosts)".to_string()),
+                ("nas".to_string(), "192.168.1.2 (hosts)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_resolv_conf() {
+        let content = "\
+nameserver 1.1.1.1
+nameserver 9.9.9.9
+search lan example.com
+options edns0
+";
+        let entries = parse_resolv_conf(content);
+        let hosts: Vec<_> = entries.iter().map(|e| e.host.as_str()).collect();
+        assert_eq!(hosts, vec!["1.1.1.1", "9.9.9.9", "lan", "example.com"]);
+        assert_eq!(entries[0].kind, DnsEntryKind::NameServer);
+        assert_eq!(entries[0].dig_command().command, "dig -x '1.1.1.1'");
+        assert_eq!(entries[2].kind, DnsEntryKind::SearchDomain);
+    }
+
+    #[test]
+    fn test_parse_resolver_log() {
+        let content = "\
+Nov 10 10:00:00 dnsmasq[42]: query[A] example.com from 192.168.1.10
+Nov 10 10:00:00 dnsmasq[42]: forwarded example.com to 1.1.1.1
+Nov 10 10:00:01 dnsmasq[42]: query[AAAA] rust-lang.org from 192.168.1.11
+Nov 10 10:00:02 dnsmasq[42]: query[A] example.com from 192.168.1.10
+";
+        let queries: Vec<_> = parse_resolver_log(content)
+            .into_iter()
+            .map(|e| (e.host, e.description))
+            .collect();
+        assert_eq!(
+            queries,
+            vec![
+                (
+                    "example.com".to_string(),
+                    "A query from 192.168.1.10 (resolver log)".to_string()
+                ),
+                (
+                    "rust-lang.org".to_string(),
+                    "AAAA query from 192.168.1.11 (resolver log)".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dnsmasq.log");
+        std::fs::write(&path, "first line\nsecond\nthird\n").unwrap();
+        assert_eq!(
+            read_tail(&path, 100).unwrap(),
+            "first line\nsecond\nthird\n"
+        );
+        // the cut line is dropped
+        assert_eq!(read_tail(&path, 10).unwrap(), "third\n");
+    }
+}
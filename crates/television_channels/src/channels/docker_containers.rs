@@ -0,0 +1,103 @@
+use std::process::Command;
+
+use television_derive::FromBuilder;
+use television_utils::cancel::{output, CancellationToken};
+use television_utils::strings::{preprocess_line, shell_quote};
+use tracing::debug;
+
+use super::builder::{BuiltChannel, ChannelBuilder};
+use crate::entry::PreviewCommand;
+
+/// A running or stopped docker container.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Container {
+    id: String,
+    name: String,
+    image: String,
+    /// The status of the container, e.g. `Up 2 hours`.
+    status: String,
+}
+
+#[derive(FromBuilder)]
+pub struct Channel(BuiltChannel<Container>);
+
+const FILE_ICON_STR: &str = "Dockerfile";
+/// Separates the fields of a container in the output of `docker ps`.
+const FIELD_SEPARATOR: char = '\x1f';
+/// Prints the fields of a container, separated by `FIELD_SEPARATOR`.
+const PS_FORMAT: &str = "{{.ID}}\x1f{{.Names}}\x1f{{.Image}}\x1f{{.Status}}";
+
+impl Channel {
+    fn builder() -> ChannelBuilder<Container> {
+        ChannelBuilder::new("dockercontainers", containers, |container| {
+            (
+                container.name.clone(),
+                Some(format!("{}, {}", container.image, container.status)),
+            )
+        })
+        .preview_command(|container| {
+            PreviewCommand::new(format!(
+                "docker inspect --type container {}",
+                shell_quote(&container.id)
+            ))
+            .highlighted_as("inspect.json")
+        })
+        .icon(FILE_ICON_STR)
+        .output(|container| container.id.clone())
+    }
+}
+
+fn containers(token: &CancellationToken) -> Vec<Container> {
+    let output = match output(
+        Command::new("docker").args([
+            "ps",
+            "--all",
+            "--no-trunc",
+            "--format",
+            PS_FORMAT,
+        ]),
+        token,
+    ) {
+        Ok(output) => output,
+        Err(e) => {
+            debug!("Failed to run docker ps: {}", e);
+            return Vec::new();
+        }
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_container)
+        .collect()
+}
+
+/// Parse a line of `docker ps` output, printed with `PS_FORMAT`.
+fn parse_container(line: &str) -> Option<Container> {
+    let mut fields = line.split(FIELD_SEPARATOR);
+    Some(Container {
+        id: fields.next()?.to_string(),
+        name: preprocess_line(fields.next()?),
+        image: preprocess_line(fields.next()?),
+        status: preprocess_line(fields.next()?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_container() {
+        assert_eq!(
+            parse_container(
+                "3f4e1c2a9b\x1fweb\x1fnginx:1.27\x1fExited (0) 2 hours ago"
+            ),
+            Some(Container {
+                id: "3f4e1c2a9b".to_string(),
+                name: "web".to_string(),
+                image: "nginx:1.27".to_string(),
+                status: "Exited (0) 2 hours ago".to_string(),
+            })
+        );
+        assert_eq!(parse_container("3f4e1c2a9b\x1fweb"), None);
+    }
+}
@@ -0,0 +1,120 @@
+use std::process::Command;
+
+use television_derive::FromBuilder;
+use television_utils::cancel::{output, CancellationToken};
+use television_utils::strings::{preprocess_line, shell_quote};
+use tracing::debug;
+
+use super::builder::{BuiltChannel, ChannelBuilder};
+use crate::entry::PreviewCommand;
+
+/// A docker image.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Image {
+    id: String,
+    repository: String,
+    tag: String,
+    size: String,
+    /// When the image was created, e.g. `3 weeks ago`.
+    created: String,
+}
+
+/// What docker displays in place of the repository or tag of untagged
+/// images.
+const NONE: &str = "<none>";
+
+impl Image {
+    /// The `repository:tag` reference of the image, or its ID when it is
+    /// untagged.
+    fn reference(&self) -> String {
+        if self.repository == NONE {
+            self.id.clone()
+        } else if self.tag == NONE {
+            self.repository.clone()
+        } else {
+            format!("{}:{}", self.repository, self.tag)
+        }
+    }
+}
+
+#[derive(FromBuilder)]
+pub struct Channel(BuiltChannel<Image>);
+
+const FILE_ICON_STR: &str = "Dockerfile";
+/// Separates the fields of an image in the output of `docker images`.
+const FIELD_SEPARATOR: char = '\x1f';
+/// Prints the fields of an image, separated by `FIELD_SEPARATOR`.
+const IMAGES_FORMAT: &str =
+    "{{.ID}}\x1f{{.Repository}}\x1f{{.Tag}}\x1f{{.Size}}\x1f{{.CreatedSince}}";
+
+impl Channel {
+    fn builder() -> ChannelBuilder<Image> {
+        ChannelBuilder::new("dockerimages", images, |image| {
+            (
+                image.reference(),
+                Some(format!(
+                    "{}, {}, created {}",
+                    image.id, image.size, image.created
+                )),
+            )
+        })
+        .preview_command(|image| {
+            PreviewCommand::new(format!(
+                "docker inspect --type image {}",
+                shell_quote(&image.id)
+            ))
+            .highlighted_as("inspect.json")
+        })
+        .icon(FILE_ICON_STR)
+        .output(Image::reference)
+    }
+}
+
+fn images(token: &CancellationToken) -> Vec<Image> {
+    let output = match output(
+        Command::new("docker").args(["images", "--format", IMAGES_FORMAT]),
+        token,
+    ) {
+        Ok(output) => output,
+        Err(e) => {
+            debug!("Failed to run docker images: {}", e);
+            return Vec::new();
+        }
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_image)
+        .collect()
+}
+
+/// Parse a line of `docker images` output, printed with `IMAGES_FORMAT`.
+fn parse_image(line: &str) -> Option<Image> {
+    let mut fields = line.split(FIELD_SEPARATOR);
+    Some(Image {
+        id: fields.next()?.to_string(),
+        repository: preprocess_line(fields.next()?),
+        tag: preprocess_line(fields.next()?),
+        size: fields.next()?.to_string(),
+        created: fields.next()?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_image() {
+        let image =
+            parse_image("a1b2c3d4\x1fnginx\x1f1.27\x1f192MB\x1f3 weeks ago")
+                .unwrap();
+        assert_eq!(image.reference(), "nginx:1.27");
+        assert_eq!(image.size, "192MB");
+        assert_eq!(image.created, "3 weeks ago");
+        let untagged =
+            parse_image("a1b2c3d4\x1f<none>\x1f<none>\x1f1GB\x1f2 days ago")
+                .unwrap();
+        assert_eq!(untagged.reference(), "a1b2c3d4");
+        assert_eq!(parse_image("a1b2c3d4\x1fnginx"), None);
+    }
+}
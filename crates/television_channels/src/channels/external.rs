@@ -0,0 +1,202 @@
+use std::path::Path;
+use std::process::Stdio;
+
+use devicons::FileIcon;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tracing::{debug, warn};
+
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewType};
+use crate::startup::Startup;
+use crate::threads::large_channel_default;
+use crate::time_range::TimeRange;
+use television_fuzzy::matcher::{config::Config, injector::Injector, Matcher};
+use television_utils::strings::preprocess_line;
+
+/// A channel listing the lines printed by a command, as they are printed.
+///
+/// The lines of long-running commands (e.g. `rg --files` on a huge tree or
+/// `journalctl -f`) show up while they are still running. The command is
+/// killed when leaving the channel.
+pub struct Channel {
+    matcher: Matcher<String>,
+    icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+}
+
+const FILE_ICON_STR: &str = "sh";
+
+impl Channel {
+    pub fn new(ctx: &Context, command: String) -> Self {
+        let matcher = Matcher::new(Config::default().n_threads(
+            ctx.matcher_threads("external", large_channel_default()),
+        ));
+        let load_handle = tokio::spawn(stream_lines(
+            matcher.injector(),
+            command,
+            ctx.time_range().clone(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            matcher,
+            icon: FileIcon::from(FILE_ICON_STR),
+            load_handle,
+        }
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx, ctx.source_command().to_string())
+    }
+}
+
+/// Run the given shell command within the given time range and push the
+/// non-empty lines it prints to the injector as soon as they are printed,
+/// once the given startup is ready.
+///
+/// The command is killed if the returned future is dropped (e.g. when its
+/// task is aborted).
+pub(crate) async fn stream_lines(
+    injector: Injector<String>,
+    command: String,
+    time_range: TimeRange,
+    startup: Startup,
+) {
+    if command.is_empty() {
+        return;
+    }
+    startup.ready().await;
+    debug!("Streaming the output of {:?}", command);
+    let mut child = match tokio::process::Command::from(
+        time_range.shell_command(&command),
+    )
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .kill_on_drop(true)
+    .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("Failed to run command {:?}: {:?}", command, e);
+            return;
+        }
+    };
+    let Some(stdout) = child.stdout.take() else {
+        return;
+    };
+    // lines aren't necessarily valid UTF-8
+    let mut lines = BufReader::new(stdout).split(b'\n');
+    while let Ok(Some(line)) = lines.next_segment().await {
+        let line = String::from_utf8_lossy(&line);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let () = injector.push(preprocess_line(&line), |line, cols| {
+            cols[0] = line.clone().into();
+        });
+    }
+    match child.wait().await {
+        Ok(status) if !status.success() => {
+            warn!("Command {:?} exited with {}", command, status);
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Failed to wait for command {:?}: {:?}", command, e),
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                let path = Path::new(&item.matched_string);
+                let icon = if path.try_exists().unwrap_or(false) {
+                    FileIcon::from(path)
+                } else {
+                    self.icon
+                };
+                Entry::new(item.matched_string, PreviewType::Basic)
+                    .with_name_match_ranges(item.match_indices)
+                    .with_icon(icon)
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            let path = Path::new(&item.matched_string);
+            // commands like `rg --files` or `fd` print paths
+            if path.is_file() {
+                Entry::new(item.matched_string.clone(), PreviewType::Files)
+                    .with_icon(FileIcon::from(path))
+            } else if path.is_dir() {
+                Entry::new(item.matched_string.clone(), PreviewType::Directory)
+                    .with_icon(FileIcon::from(path))
+            } else {
+                Entry::new(item.matched_string.clone(), PreviewType::Basic)
+                    .with_icon(self.icon)
+            }
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running || !self.load_handle.is_finished()
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.load_handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_streamed_lines() {
+        // the first lines show up while the command is still running
+        let mut channel = Channel::new(
+            &Context::default(),
+            "printf 'alpha\\n\\nbeta\\n'; sleep 10".to_string(),
+        );
+        let results = loop {
+            tokio::task::yield_now().await;
+            let results = channel.results(10, 0);
+            if results.len() == 2 {
+                break results;
+            }
+        };
+        assert!(channel.running());
+        assert_eq!(
+            results.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(),
+            ["alpha", "beta"]
+        );
+        channel.shutdown();
+    }
+}
@@ -1,43 +1,259 @@
 use crate::channels::{OnAir, TelevisionChannel};
-use crate::entry::{Entry, PreviewType};
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, EntryRef, PreviewType};
+use crate::filters::MetadataFilters;
+use crate::hidden::HiddenEntries;
+use crate::threads::large_channel_default;
 use devicons::FileIcon;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
 use television_fuzzy::matcher::{config::Config, injector::Injector, Matcher};
-use television_utils::files::{walk_builder, DEFAULT_NUM_THREADS};
+use television_utils::cancel::CancellationToken;
+use television_utils::files::{
+    add_git_excludes_file, walk_builder, WalkWarnings, DEFAULT_NUM_THREADS,
+};
+use television_utils::project::relative_path;
 use television_utils::strings::preprocess_line;
 
 pub struct Channel {
     matcher: Matcher<String>,
+    filters: MetadataFilters<String>,
+    /// The number of files found by extension, whatever the file types
+    /// listed.
+    extension_counts: Arc<Mutex<HashMap<String, usize>>>,
+    walk_warnings: Arc<WalkWarnings>,
     crawl_handle: tokio::task::JoinHandle<()>,
+    /// Stops the walk, which aborting the crawl task doesn't.
+    token: CancellationToken,
     // PERF: cache results (to make deleting characters smoother) with
     // a shallow stack of sub-patterns as keys (e.g. "a", "ab", "abc")
 }
 
 impl Channel {
-    pub fn new(paths: Vec<PathBuf>) -> Self {
-        let matcher = Matcher::new(Config::default().match_paths(true));
-        // start loading files in the background
-        let crawl_handle = tokio::spawn(load_files(paths, matcher.injector()));
+    pub fn new(ctx: &Context, paths: Vec<PathBuf>) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(
+                    ctx.matcher_threads("files", large_channel_default()),
+                )
+                .match_paths(true)
+                .chunking(ctx.chunking("files")),
+        );
+        let extension_counts = Arc::default();
+        let walk_warnings = Arc::default();
+        let token = CancellationToken::new();
+        // start loading files in the background, once the picker is ready
+        let startup = ctx.startup.clone();
+        let load = load_files(
+            paths,
+            matcher.injector(),
+            ctx.file_types().to_vec(),
+            ctx.changed_within(),
+            Arc::clone(&extension_counts),
+            Arc::clone(&walk_warnings),
+            token.clone(),
+        );
+        let crawl_handle = tokio::spawn(async move {
+            startup.ready().await;
+            load.await;
+        });
         Channel {
             matcher,
+            filters: metadata_filters(),
+            extension_counts,
+            walk_warnings,
             crawl_handle,
+            token,
+        }
+    }
+
+    /// The extensions of the files found so far along with how many files
+    /// have them, most common first.
+    pub fn extension_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = self
+            .extension_counts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(ext, count)| (ext.clone(), *count))
+            .collect();
+        counts.sort_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| a.cmp(b))
+        });
+        counts
+    }
+}
+
+/// Whether the given file was modified within the given duration.
+fn modified_within(entry: &ignore::DirEntry, duration: Duration) -> bool {
+    entry
+        .metadata()
+        .ok()
+        .and_then(|metadata| metadata.modified().ok())
+        .is_some_and(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or(Duration::ZERO)
+                <= duration
+        })
+}
+
+/// The (lowercase) extension of the given file, if any.
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+/// How many files with a given extension were counted by a walker thread
+/// before the counts are added to the shared ones.
+const EXTENSION_COUNTS_BATCH: usize = 1024;
+
+/// Counts the extensions of the files found by a walker thread, adding them
+/// to the shared counts by batches (and when the thread is done) to keep
+/// the threads from contending for them.
+struct ExtensionCounter {
+    counts: HashMap<String, usize>,
+    pending: usize,
+    shared: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl ExtensionCounter {
+    fn new(shared: Arc<Mutex<HashMap<String, usize>>>) -> Self {
+        Self {
+            counts: HashMap::new(),
+            pending: 0,
+            shared,
+        }
+    }
+
+    fn count(&mut self, extension: String) {
+        *self.counts.entry(extension).or_default() += 1;
+        self.pending += 1;
+        if self.pending == EXTENSION_COUNTS_BATCH {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        let mut shared = self.shared.lock().unwrap();
+        for (extension, count) in self.counts.drain() {
+            *shared.entry(extension).or_default() += count;
+        }
+        self.pending = 0;
+    }
+}
+
+impl Drop for ExtensionCounter {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// The size and modification time of a file.
+type FileMetadata = (u64, SystemTime);
+
+/// The metadata filters supported by the files channel: `ext`, `size`,
+/// `mtime` and `status` (the git status of the file, e.g. `modified` or
+/// `untracked`).
+fn metadata_filters() -> MetadataFilters<String> {
+    // files are only looked up once, however many times the query changes
+    let metadata: Arc<Mutex<HashMap<String, Option<FileMetadata>>>> =
+        Arc::default();
+    let lookup =
+        move |path: &String| {
+            *metadata.lock().unwrap().entry(path.clone()).or_insert_with(
+                || {
+                    let metadata = std::fs::metadata(path).ok()?;
+                    Some((metadata.len(), metadata.modified().ok()?))
+                },
+            )
+        };
+    let statuses: Arc<OnceLock<HashMap<String, &'static str>>> =
+        Arc::default();
+    let size_lookup = lookup.clone();
+    MetadataFilters::new()
+        .text("ext", |path: &String| extension(Path::new(path)))
+        .size("size", move |path| size_lookup(path).map(|(size, _)| size))
+        .age("mtime", move |path| {
+            let (_, modified) = lookup(path)?;
+            Some(
+                SystemTime::now()
+                    .duration_since(modified)
+                    .unwrap_or(Duration::ZERO),
+            )
+        })
+        .text("status", move |path| {
+            statuses
+                .get_or_init(git_statuses)
+                .get(path)
+                .map(ToString::to_string)
+        })
+}
+
+/// The git status of the changed files of the repository the current
+/// directory belongs to, by path (relative to the current directory).
+fn git_statuses() -> HashMap<String, &'static str> {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    let Some(prefix) = git(&["rev-parse", "--show-prefix"]) else {
+        return HashMap::new();
+    };
+    let Some(output) =
+        git(&["status", "--porcelain=v1", "-z", "--untracked-files=all"])
+    else {
+        return HashMap::new();
+    };
+    let prefix = prefix.trim_end();
+    let mut statuses = HashMap::new();
+    let mut records = output.split('\0');
+    while let Some(record) = records.next() {
+        let Some((code, path)) = record.split_at_checked(3) else {
+            continue;
+        };
+        let status = match code.trim() {
+            "??" => "untracked",
+            c if c.contains('U') => "conflicted",
+            c if c.contains('R') => {
+                // the record is followed by the original path
+                records.next();
+                "renamed"
+            }
+            c if c.contains('A') => "added",
+            c if c.contains('D') => "deleted",
+            _ => "modified",
+        };
+        if let Some(path) = path.strip_prefix(prefix) {
+            statuses.insert(path.to_string(), status);
         }
     }
+    statuses
 }
 
-impl Default for Channel {
-    fn default() -> Self {
-        Self::new(vec![std::env::current_dir().unwrap()])
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx, vec![ctx.search_root()])
     }
 }
 
-impl From<&mut TelevisionChannel> for Channel {
-    fn from(value: &mut TelevisionChannel) -> Self {
+impl Channel {
+    /// List the files among the results of the given channel (e.g. in the
+    /// repositories it lists), when transitioning from it.
+    pub fn from_channel(ctx: &Context, value: &mut TelevisionChannel) -> Self {
         match value {
             c @ TelevisionChannel::GitRepos(_) => {
                 let entries = c.results(c.result_count(), 0);
                 Self::new(
+                    ctx,
                     entries
                         .iter()
                         .map(|entry| PathBuf::from(entry.name.clone()))
@@ -47,6 +263,7 @@ impl From<&mut TelevisionChannel> for Channel {
             c @ TelevisionChannel::Files(_) => {
                 let entries = c.results(c.result_count(), 0);
                 Self::new(
+                    ctx,
                     entries
                         .iter()
                         .map(|entry| PathBuf::from(entry.name.clone()))
@@ -56,6 +273,7 @@ impl From<&mut TelevisionChannel> for Channel {
             c @ TelevisionChannel::Text(_) => {
                 let entries = c.results(c.result_count(), 0);
                 Self::new(
+                    ctx,
                     entries
                         .iter()
                         .map(|entry| PathBuf::from(entry.display_name()))
@@ -71,7 +289,9 @@ impl From<&mut TelevisionChannel> for Channel {
 
 impl OnAir for Channel {
     fn find(&mut self, pattern: &str) {
-        self.matcher.find(pattern);
+        let (pattern, filter) = self.filters.parse(pattern);
+        self.matcher.set_filter(filter);
+        self.matcher.find(&pattern);
     }
 
     fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
@@ -88,6 +308,19 @@ impl OnAir for Channel {
             .collect()
     }
 
+    fn for_each_result(
+        &mut self,
+        range: Range<u32>,
+        f: &mut dyn FnMut(&EntryRef<'_>),
+    ) {
+        self.matcher.tick();
+        self.matcher.for_each_result(range, |item| {
+            f(&EntryRef::new(item.matched_string)
+                .with_name_match_ranges(item.match_indices)
+                .with_icon(FileIcon::from(item.matched_string)));
+        });
+    }
+
     fn get_result(&self, index: u32) -> Option<Entry> {
         self.matcher.get_result(index).map(|item| {
             let path = item.matched_string;
@@ -104,47 +337,196 @@ impl OnAir for Channel {
         self.matcher.total_item_count
     }
 
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
     fn running(&self) -> bool {
         self.matcher.status.running
     }
 
+    fn loading(&self) -> bool {
+        !self.crawl_handle.is_finished()
+    }
+
     fn shutdown(&self) {
+        self.token.cancel();
         self.crawl_handle.abort();
     }
+
+    fn warnings(&self) -> Option<String> {
+        self.walk_warnings.summary()
+    }
 }
 
-#[allow(clippy::unused_async)]
-async fn load_files(paths: Vec<PathBuf>, injector: Injector<String>) {
+async fn load_files(
+    paths: Vec<PathBuf>,
+    injector: Injector<String>,
+    file_types: Vec<String>,
+    changed_within: Option<Duration>,
+    extension_counts: Arc<Mutex<HashMap<String, usize>>>,
+    walk_warnings: Arc<WalkWarnings>,
+    token: CancellationToken,
+) {
     if paths.is_empty() {
         return;
     }
     let current_dir = std::env::current_dir().unwrap();
+    // entries the user chose to hide from this project
+    let hidden_paths = Some(HiddenEntries::for_current_dir().paths())
+        .filter(|p| !p.is_empty());
     let mut builder =
-        walk_builder(&paths[0], *DEFAULT_NUM_THREADS, None, None);
+        walk_builder(&paths[0], *DEFAULT_NUM_THREADS, None, hidden_paths);
+    add_git_excludes_file(&mut builder, &paths[0]);
     paths[1..].iter().for_each(|path| {
         builder.add(path);
     });
     let walker = builder.build_parallel();
 
     walker.run(|| {
-        let injector = injector.clone();
+        let mut injector = injector.clone().chunked();
         let current_dir = current_dir.clone();
+        let file_types = file_types.clone();
+        let mut counter = ExtensionCounter::new(Arc::clone(&extension_counts));
+        let walk_warnings = Arc::clone(&walk_warnings);
+        let token = token.clone();
         Box::new(move |result| {
-            if let Ok(entry) = result {
-                if entry.file_type().unwrap().is_file() {
-                    let file_path = preprocess_line(
-                        &entry
-                            .path()
-                            .strip_prefix(&current_dir)
-                            .unwrap_or(entry.path())
-                            .to_string_lossy(),
-                    );
-                    let () = injector.push(file_path, |e, cols| {
-                        cols[0] = e.clone().into();
-                    });
+            if token.is_cancelled() {
+                return ignore::WalkState::Quit;
+            }
+            let entry = match result {
+                Ok(entry) => entry,
+                Err(e) => {
+                    walk_warnings.record(&e);
+                    return ignore::WalkState::Continue;
                 }
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                return ignore::WalkState::Continue;
+            }
+            if changed_within
+                .is_some_and(|within| !modified_within(&entry, within))
+            {
+                return ignore::WalkState::Continue;
             }
+            let extension = extension(entry.path());
+            if let Some(extension) = &extension {
+                counter.count(extension.clone());
+            }
+            if !file_types.is_empty()
+                && !extension.is_some_and(|ext| file_types.contains(&ext))
+            {
+                return ignore::WalkState::Continue;
+            }
+            // relative to the current directory even when searching the
+            // project root, so that the output is a valid path
+            let file_path = preprocess_line(
+                &relative_path(entry.path(), &current_dir).to_string_lossy(),
+            );
+            injector.push(file_path, |e, cols| {
+                cols[0] = e.clone().into();
+            });
             ignore::WalkState::Continue
         })
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_extension_counts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        for file in ["src/main.rs", "src/lib.rs", "Cargo.TOML", "LICENSE"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+
+        let channel = Channel::new(&Context::default(), vec![dir.clone()]);
+        while channel.loading() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            channel.extension_counts(),
+            [("rs".to_string(), 2), ("toml".to_string(), 1)]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_walk_resilience() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        std::fs::create_dir_all(dir.join("src/nested")).unwrap();
+        std::fs::create_dir_all(dir.join("locked")).unwrap();
+        std::fs::write(dir.join("src/main.rs"), "").unwrap();
+        std::fs::write(dir.join("locked/secret.rs"), "").unwrap();
+        // a link to an ancestor, a link to itself and a dangling link
+        symlink(&dir, dir.join("src/nested/up")).unwrap();
+        symlink(dir.join("self"), dir.join("self")).unwrap();
+        symlink(dir.join("gone"), dir.join("dangling")).unwrap();
+        std::fs::set_permissions(
+            dir.join("locked"),
+            std::fs::Permissions::from_mode(0o000),
+        )
+        .unwrap();
+        // root can read anything
+        let locked = std::fs::read_dir(dir.join("locked")).is_err();
+
+        let mut channel = Channel::new(&Context::default(), vec![dir.clone()]);
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while channel.loading() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the walk should not hang");
+        // for the temporary directory to be removed
+        std::fs::set_permissions(
+            dir.join("locked"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        channel.find("");
+        while channel.running() {
+            channel.results(0, 0);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let mut names: Vec<String> = channel
+            .results(10, 0)
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        names.sort();
+        let mut expected = vec!["src/main.rs"];
+        if locked {
+            assert_eq!(channel.warnings().as_deref(), Some("1 unreadable"));
+        } else {
+            expected.push("locked/secret.rs");
+            assert_eq!(channel.warnings(), None);
+        }
+        let current_dir = std::env::current_dir().unwrap();
+        let mut expected: Vec<String> = expected
+            .iter()
+            .map(|name| {
+                relative_path(&dir.join(name), &current_dir)
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        expected.sort();
+        assert_eq!(names, expected);
+
+        // the entries lent for display are the ones `results` returns
+        let mut lent = Vec::new();
+        channel.for_each_result(0..10, &mut |entry| {
+            lent.push(entry.name.to_string());
+        });
+        lent.sort();
+        assert_eq!(lent, expected);
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::process::Command;
+
+use devicons::FileIcon;
+use tracing::debug;
+
+use super::reload::Reloader;
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewType};
+use crate::startup::Startup;
+use television_fuzzy::matcher::{
+    config::Config, injector::Injector, Matcher, Utf32String,
+};
+use television_utils::cancel::{output, CancellationToken};
+use television_utils::strings::preprocess_line;
+
+/// A font family and the styles and files it is available in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FontFamily {
+    name: String,
+    styles: BTreeSet<String>,
+    files: BTreeSet<String>,
+}
+
+impl FontFamily {
+    fn details(&self) -> String {
+        let mut details = format!(
+            "{PANGRAM}\n{}\n0123456789\n\nFamily: {}\nStyles: {}\n\nFiles:",
+            PANGRAM.to_uppercase(),
+            self.name,
+            self.styles.iter().cloned().collect::<Vec<_>>().join(", ")
+        );
+        for file in &self.files {
+            let _ = write!(details, "\n  {file}");
+        }
+        details
+    }
+}
+
+const PANGRAM: &str = "The quick brown fox jumps over the lazy dog";
+
+/// The `fc-list` output format: one font per line, with its family, style
+/// and file separated by tabs.
+const FC_LIST_FORMAT: &str = "%{family}\t%{style}\t%{file}\n";
+
+#[allow(clippy::module_name_repetitions)]
+pub struct Channel {
+    matcher: Matcher<FontFamily>,
+    file_icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+    /// Kills `fc-list`, when loading or reloading the fonts.
+    token: CancellationToken,
+    reloader: Reloader<FontFamily>,
+}
+
+const NUM_THREADS: usize = 1;
+const FILE_ICON_STR: &str = "font.ttf";
+
+impl Channel {
+    pub fn new(ctx: &Context) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(ctx.matcher_threads("fonts", NUM_THREADS)),
+        );
+        let token = CancellationToken::new();
+        let load_handle = tokio::spawn(load_fonts(
+            matcher.injector(),
+            token.clone(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            matcher,
+            file_icon: FileIcon::from(FILE_ICON_STR),
+            load_handle,
+            token,
+            reloader: Reloader::default(),
+        }
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx)
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(families) = self.reloader.take() {
+            self.matcher.update_items(families, font_family_columns);
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                Entry::new(item.matched_string, PreviewType::PlainText)
+                    .with_name_match_ranges(item.match_indices)
+                    .with_icon(self.file_icon)
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            Entry::new(item.inner.name.clone(), PreviewType::PlainText)
+                .with_value(item.inner.details())
+                .with_icon(self.file_icon)
+                .with_output(item.inner.name.clone())
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.token.cancel();
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        let token = self.token.clone();
+        self.reloader.start(move || font_families(&token));
+    }
+}
+
+async fn load_fonts(
+    injector: Injector<FontFamily>,
+    token: CancellationToken,
+    startup: Startup,
+) {
+    startup.ready().await;
+    inject_font_families(&injector, font_families(&token));
+}
+
+fn font_families(token: &CancellationToken) -> Vec<FontFamily> {
+    match output(
+        Command::new("fc-list").args(["--format", FC_LIST_FORMAT]),
+        token,
+    ) {
+        Ok(output) => parse_fc_list(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => {
+            debug!("Failed to run fc-list: {:?}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn inject_font_families(
+    injector: &Injector<FontFamily>,
+    families: Vec<FontFamily>,
+) {
+    for family in families {
+        let () = injector.push(family, font_family_columns);
+    }
+}
+
+fn font_family_columns(family: &FontFamily, cols: &mut [Utf32String]) {
+    cols[0] = family.name.clone().into();
+}
+
+/// Group the fonts listed by `fc-list` by family.
+///
+/// Families and styles may have several (localized) names separated by
+/// commas, in which case only the first one is kept.
+fn parse_fc_list(output: &str) -> Vec<FontFamily> {
+    let mut families: BTreeMap<String, FontFamily> = BTreeMap::new();
+    for line in output.lines() {
+        let mut fields = line.split('\t');
+        let (Some(family), Some(style), Some(file)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let Some(family) = family.split(',').next().filter(|f| !f.is_empty())
+        else {
+            continue;
+        };
+        let family =
+            families
+                .entry(family.to_string())
+                .or_insert_with(|| FontFamily {
+                    name: preprocess_line(family),
+                    styles: BTreeSet::new(),
+                    files: BTreeSet::new(),
+                });
+        if let Some(style) = style.split(',').next().filter(|s| !s.is_empty())
+        {
+            family.styles.insert(preprocess_line(style));
+        }
+        family.files.insert(preprocess_line(file));
+    }
+    families.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fc_list() {
+        let output = "\
+DejaVu Sans\tBook\t/usr/share/fonts/TTF/DejaVuSans.ttf
+DejaVu Sans,DejaVu Sans Light\tExtraLight,Light\t/usr/share/fonts/TTF/DejaVuSans-ExtraLight.ttf
+Fira Code\tBold\t/usr/share/fonts/TTF/FiraCode-Bold.ttf
+DejaVu Sans\tBold\t/usr/share/fonts/TTF/DejaVuSans-Bold.ttf
+";
+        let families = parse_fc_list(output);
+        assert_eq!(families.len(), 2);
+        assert_eq!(families[0].name, "DejaVu Sans");
+        assert_eq!(
+            families[0].styles.iter().collect::<Vec<_>>(),
+            vec!["Bold", "Book", "ExtraLight"]
+        );
+        assert_eq!(families[0].files.len(), 3);
+        assert_eq!(families[1].name, "Fira Code");
+        assert!(families[1]
+            .details()
+            .ends_with("Files:\n  /usr/share/fonts/TTF/FiraCode-Bold.ttf"));
+    }
+}
@@ -0,0 +1,199 @@
+use std::process::Command;
+
+use television_derive::FromBuilder;
+use television_utils::cancel::{output, CancellationToken};
+use television_utils::strings::{preprocess_line, shell_quote};
+use tracing::debug;
+
+use super::builder::{BuiltChannel, ChannelBuilder};
+use crate::entry::PreviewCommand;
+
+/// A local or remote branch of the current repository.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Branch {
+    /// The short name of the branch, e.g. `main` or `origin/main`.
+    name: String,
+    remote: bool,
+    /// Whether the branch is checked out.
+    current: bool,
+    /// How the branch compares to its upstream, e.g. `↑1 ↓2` (empty when
+    /// they are in sync or the branch has no upstream).
+    tracking: String,
+    /// When the branch was last committed to, e.g. `3 days ago`.
+    date: String,
+    /// The subject of the last commit of the branch.
+    subject: String,
+}
+
+impl Branch {
+    /// A description of the branch, displayed next to its name.
+    fn description(&self) -> String {
+        let mut parts = Vec::new();
+        if self.current {
+            parts.push("current");
+        }
+        if !self.tracking.is_empty() {
+            parts.push(&self.tracking);
+        }
+        parts.push(&self.date);
+        parts.push(&self.subject);
+        parts.join(", ")
+    }
+
+    /// Check the branch out, creating a local branch tracking it for
+    /// remote branches.
+    fn checkout_command(&self) -> String {
+        if self.remote {
+            format!("git switch --track {}", shell_quote(&self.name))
+        } else {
+            format!("git switch {}", shell_quote(&self.name))
+        }
+    }
+}
+
+#[derive(FromBuilder)]
+pub struct Channel(BuiltChannel<Branch>);
+
+const FILE_ICON_STR: &str = "git";
+/// Separates the fields of a branch in the output of `git for-each-ref`.
+const FIELD_SEPARATOR: char = '\x1f';
+/// Prints the fields of a branch, separated by `FIELD_SEPARATOR`.
+const REF_FORMAT: &str = "--format=%(refname)%1f%(refname:short)%1f\
+    %(symref)%1f%(HEAD)%1f%(upstream:track,nobracket)%1f\
+    %(committerdate:relative)%1f%(subject)";
+/// How many commits of a branch are previewed.
+const PREVIEWED_COMMITS: usize = 100;
+
+impl Channel {
+    fn builder() -> ChannelBuilder<Branch> {
+        ChannelBuilder::new("gitbranches", branches, |branch| {
+            (branch.name.clone(), Some(branch.description()))
+        })
+        .preview_command(|branch| {
+            PreviewCommand::new(format!(
+                "git log --no-color --format='%h %as %an: %s' -n {} {} --",
+                PREVIEWED_COMMITS,
+                shell_quote(&branch.name)
+            ))
+        })
+        .icon(FILE_ICON_STR)
+        .output(|branch| branch.name.clone())
+        .edit_command(Branch::checkout_command)
+    }
+}
+
+fn branches(token: &CancellationToken) -> Vec<Branch> {
+    let output = match output(
+        Command::new("git")
+            .args(["for-each-ref", "--sort=-committerdate", REF_FORMAT])
+            .args(["refs/heads", "refs/remotes"]),
+        token,
+    ) {
+        Ok(output) => output,
+        Err(e) => {
+            debug!("Failed to run git for-each-ref: {}", e);
+            return Vec::new();
+        }
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_branch)
+        .collect()
+}
+
+/// Parse a line of `git for-each-ref` output, printed with `REF_FORMAT`,
+/// skipping symbolic refs (e.g. `origin/HEAD`).
+fn parse_branch(line: &str) -> Option<Branch> {
+    let mut fields = line.split(FIELD_SEPARATOR);
+    let refname = fields.next()?;
+    let name = fields.next()?;
+    if !fields.next()?.is_empty() {
+        return None;
+    }
+    Some(Branch {
+        name: name.to_string(),
+        remote: refname.starts_with("refs/remotes/"),
+        current: fields.next()? == "*",
+        tracking: tracking(fields.next()?),
+        date: fields.next()?.to_string(),
+        subject: preprocess_line(fields.next()?),
+    })
+}
+
+/// Shorten the tracking info printed by git, e.g. `ahead 1, behind 2` to
+/// `↑1 ↓2`.
+fn tracking(track: &str) -> String {
+    if track == "gone" {
+        return "upstream gone".to_string();
+    }
+    track
+        .split(", ")
+        .filter_map(|part| {
+            let (direction, count) = part.split_once(' ')?;
+            match direction {
+                "ahead" => Some(format!("↑{count}")),
+                "behind" => Some(format!("↓{count}")),
+                _ => None,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_branch() {
+        let line = |fields: [&str; 7]| fields.join("\x1f");
+        assert_eq!(
+            parse_branch(&line([
+                "refs/heads/main",
+                "main",
+                "",
+                "*",
+                "ahead 1, behind 12",
+                "2 hours ago",
+                "Fix the preview",
+            ])),
+            Some(Branch {
+                name: "main".to_string(),
+                remote: false,
+                current: true,
+                tracking: "↑1 ↓12".to_string(),
+                date: "2 hours ago".to_string(),
+                subject: "Fix the preview".to_string(),
+            })
+        );
+        let remote = parse_branch(&line([
+            "refs/remotes/origin/feature",
+            "origin/feature",
+            "",
+            " ",
+            "",
+            "3 days ago",
+            "Add a feature",
+        ]))
+        .unwrap();
+        assert!(remote.remote && !remote.current);
+        assert_eq!(remote.description(), "3 days ago, Add a feature");
+        assert_eq!(
+            remote.checkout_command(),
+            format!("git switch --track {}", shell_quote("origin/feature"))
+        );
+        assert_eq!(
+            parse_branch(&line([
+                "refs/remotes/origin/HEAD",
+                "origin",
+                "refs/remotes/origin/main",
+                " ",
+                "",
+                "3 days ago",
+                "Add a feature",
+            ])),
+            None
+        );
+        assert_eq!(tracking("gone"), "upstream gone");
+    }
+}
@@ -0,0 +1,98 @@
+use std::process::Command;
+
+use television_derive::FromBuilder;
+use television_utils::cancel::{output, CancellationToken};
+use television_utils::strings::preprocess_line;
+use tracing::debug;
+
+use super::builder::{BuiltChannel, ChannelBuilder};
+use crate::entry::PreviewCommand;
+
+/// A commit of the current repository.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Commit {
+    hash: String,
+    short_hash: String,
+    subject: String,
+    author: String,
+    /// The author date, e.g. `2024-11-10`.
+    date: String,
+}
+
+#[derive(FromBuilder)]
+pub struct Channel(BuiltChannel<Commit>);
+
+const FILE_ICON_STR: &str = "git";
+/// Separates the fields of a commit in the output of `git log`.
+const FIELD_SEPARATOR: char = '\x1f';
+/// Prints the fields of a commit, separated by `FIELD_SEPARATOR`.
+const LOG_FORMAT: &str = "--format=%H%x1f%h%x1f%s%x1f%an%x1f%as";
+
+impl Channel {
+    fn builder() -> ChannelBuilder<Commit> {
+        ChannelBuilder::new("gitlog", commits, |commit| {
+            (
+                format!("{} {}", commit.short_hash, commit.subject),
+                Some(format!("{}, {}", commit.author, commit.date)),
+            )
+        })
+        .preview_command(|commit| {
+            PreviewCommand::new(format!(
+                "git show --stat --patch --no-color {}",
+                commit.hash
+            ))
+            .highlighted_as("commit.diff")
+        })
+        .icon(FILE_ICON_STR)
+        .output(|commit| commit.hash.clone())
+    }
+}
+
+fn commits(token: &CancellationToken) -> Vec<Commit> {
+    let output =
+        match output(Command::new("git").arg("log").arg(LOG_FORMAT), token) {
+            Ok(output) => output,
+            Err(e) => {
+                debug!("Failed to run git log: {}", e);
+                return Vec::new();
+            }
+        };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_commit)
+        .collect()
+}
+
+/// Parse a line of `git log` output, printed with `LOG_FORMAT`.
+fn parse_commit(line: &str) -> Option<Commit> {
+    let mut fields = line.split(FIELD_SEPARATOR);
+    Some(Commit {
+        hash: fields.next()?.to_string(),
+        short_hash: fields.next()?.to_string(),
+        subject: preprocess_line(fields.next()?),
+        author: preprocess_line(fields.next()?),
+        date: fields.next()?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commit() {
+        assert_eq!(
+            parse_commit(
+                "8f3e1c2a9b\x1f8f3e1c2\x1fFix the\tpreview\x1fJane Doe\x1f2024-11-10"
+            ),
+            Some(Commit {
+                hash: "8f3e1c2a9b".to_string(),
+                short_hash: "8f3e1c2".to_string(),
+                subject: "Fix the    preview".to_string(),
+                author: "Jane Doe".to_string(),
+                date: "2024-11-10".to_string(),
+            })
+        );
+        assert_eq!(parse_commit("8f3e1c2a9b\x1f8f3e1c2"), None);
+    }
+}
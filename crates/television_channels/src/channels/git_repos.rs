@@ -2,40 +2,66 @@ use devicons::FileIcon;
 use directories::BaseDirs;
 use ignore::overrides::OverrideBuilder;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 use tracing::debug;
 
 use crate::channels::OnAir;
+use crate::context::{Context, FromContext};
 use crate::entry::{Entry, PreviewType};
+use crate::startup::Startup;
+use crate::threads::large_channel_default;
 use television_fuzzy::matcher::{config::Config, injector::Injector, Matcher};
-use television_utils::files::{walk_builder, DEFAULT_NUM_THREADS};
+use television_utils::cancel::CancellationToken;
+use television_utils::files::{
+    walk_builder, WalkWarnings, DEFAULT_NUM_THREADS,
+};
 use television_utils::strings::preprocess_line;
 
 pub struct Channel {
     matcher: Matcher<String>,
     icon: FileIcon,
+    walk_warnings: Arc<WalkWarnings>,
     crawl_handle: JoinHandle<()>,
+    /// Stops the walk, which aborting the crawl task doesn't.
+    token: CancellationToken,
 }
 
 impl Channel {
-    pub fn new() -> Self {
-        let matcher = Matcher::new(Config::default().match_paths(true));
-        let base_dirs = BaseDirs::new().unwrap();
+    pub fn new(ctx: &Context) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(
+                    ctx.matcher_threads("gitrepos", large_channel_default()),
+                )
+                .match_paths(true),
+        );
+        let mut roots = ctx.repo_roots().to_vec();
+        if roots.is_empty() {
+            roots.push(BaseDirs::new().unwrap().home_dir().to_path_buf());
+        }
+        let walk_warnings = Arc::default();
+        let token = CancellationToken::new();
         let crawl_handle = tokio::spawn(crawl_for_repos(
-            base_dirs.home_dir().to_path_buf(),
+            roots,
             matcher.injector(),
+            Arc::clone(&walk_warnings),
+            token.clone(),
+            ctx.startup.clone(),
         ));
         Channel {
             matcher,
             icon: FileIcon::from("git"),
+            walk_warnings,
             crawl_handle,
+            token,
         }
     }
 }
 
-impl Default for Channel {
-    fn default() -> Self {
-        Self::new()
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx)
     }
 }
 
@@ -74,14 +100,27 @@ impl OnAir for Channel {
         self.matcher.total_item_count
     }
 
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
     fn running(&self) -> bool {
         self.matcher.status.running
     }
 
+    fn loading(&self) -> bool {
+        !self.crawl_handle.is_finished()
+    }
+
     fn shutdown(&self) {
         debug!("Shutting down git repos channel");
+        self.token.cancel();
         self.crawl_handle.abort();
     }
+
+    fn warnings(&self) -> Option<String> {
+        self.walk_warnings.summary()
+    }
 }
 
 fn get_ignored_paths() -> Vec<PathBuf> {
@@ -131,23 +170,39 @@ fn get_ignored_paths() -> Vec<PathBuf> {
 
     ignored_paths
 }
-#[allow(clippy::unused_async)]
-async fn crawl_for_repos(starting_point: PathBuf, injector: Injector<String>) {
-    let mut walker_overrides_builder = OverrideBuilder::new(&starting_point);
+async fn crawl_for_repos(
+    roots: Vec<PathBuf>,
+    injector: Injector<String>,
+    walk_warnings: Arc<WalkWarnings>,
+    token: CancellationToken,
+    startup: Startup,
+) {
+    startup.ready().await;
+    let mut walker_overrides_builder = OverrideBuilder::new(&roots[0]);
     walker_overrides_builder.add(".git").unwrap();
-    let walker = walk_builder(
-        &starting_point,
+    let mut builder = walk_builder(
+        &roots[0],
         *DEFAULT_NUM_THREADS,
         Some(walker_overrides_builder.build().unwrap()),
         Some(get_ignored_paths()),
-    )
-    .build_parallel();
+    );
+    roots[1..].iter().for_each(|root| {
+        builder.add(root);
+    });
+    let walker = builder.build_parallel();
 
     walker.run(|| {
         let injector = injector.clone();
+        let walk_warnings = walk_warnings.clone();
+        let token = token.clone();
         Box::new(move |result| {
-            if let Ok(entry) = result {
-                if entry.file_type().unwrap().is_dir() {
+            if token.is_cancelled() {
+                return ignore::WalkState::Quit;
+            }
+            match result {
+                Ok(entry)
+                    if entry.file_type().is_some_and(|ft| ft.is_dir()) =>
+                {
                     // if the entry is a .git directory, add its parent to the list of git repos
                     if entry.path().ends_with(".git") {
                         let parent_path = preprocess_line(
@@ -160,6 +215,8 @@ async fn crawl_for_repos(starting_point: PathBuf, injector: Injector<String>) {
                         return ignore::WalkState::Skip;
                     }
                 }
+                Ok(_) => {}
+                Err(e) => walk_warnings.record(&e),
             }
             ignore::WalkState::Continue
         })
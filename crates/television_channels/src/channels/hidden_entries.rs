@@ -0,0 +1,85 @@
+use devicons::FileIcon;
+
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewType};
+use crate::hidden::HiddenEntries;
+use television_fuzzy::matcher::{config::Config, Matcher};
+
+/// A channel listing the entries hidden from the files and text channels of
+/// the current project, so that they can be reviewed and un-hidden.
+#[allow(clippy::module_name_repetitions)]
+pub struct Channel {
+    matcher: Matcher<String>,
+}
+
+const NUM_THREADS: usize = 1;
+
+impl Channel {
+    pub fn new(ctx: &Context) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(ctx.matcher_threads("hiddenentries", NUM_THREADS))
+                .match_paths(true),
+        );
+        let injector = matcher.injector();
+        for entry in HiddenEntries::for_current_dir().iter() {
+            let () = injector.push(entry, |e, cols| {
+                cols[0] = e.clone().into();
+            });
+        }
+        Channel { matcher }
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx)
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                let path = item.matched_string;
+                Entry::new(path.clone(), PreviewType::Files)
+                    .with_name_match_ranges(item.match_indices)
+                    .with_icon(FileIcon::from(&path))
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            let path = item.matched_string;
+            Entry::new(path.clone(), PreviewType::Files)
+                .with_icon(FileIcon::from(&path))
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn shutdown(&self) {}
+}
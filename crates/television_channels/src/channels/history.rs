@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use television_derive::FromBuilder;
+use television_utils::cancel::CancellationToken;
+use television_utils::strings::preprocess_line;
+use tracing::debug;
+
+use super::builder::{BuiltChannel, ChannelBuilder};
+use crate::entry::PreviewType;
+
+/// A command from the shell history.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct HistoryCommand {
+    command: String,
+}
+
+#[derive(FromBuilder)]
+pub struct Channel(BuiltChannel<HistoryCommand>);
+
+const FILE_ICON_STR: &str = "history.sh";
+const SHELL_ENV_VAR: &str = "SHELL";
+const HISTFILE_ENV_VAR: &str = "HISTFILE";
+
+impl Channel {
+    fn builder() -> ChannelBuilder<HistoryCommand> {
+        ChannelBuilder::new("history", history, |command| {
+            // multi-line commands are displayed on a single line
+            (preprocess_line(&command.command.replace('\n', " ")), None)
+        })
+        .preview(PreviewType::Basic)
+        .icon(FILE_ICON_STR)
+        .output(|command| command.command.clone())
+    }
+}
+
+/// The shells whose history can be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    fn current() -> Self {
+        let shell = std::env::var(SHELL_ENV_VAR).unwrap_or_default();
+        match shell.rsplit('/').next().unwrap_or_default() {
+            "zsh" => Shell::Zsh,
+            "fish" => Shell::Fish,
+            _ => Shell::Bash,
+        }
+    }
+
+    /// The history file of the shell, `HISTFILE` taking precedence for the
+    /// shells that use it.
+    fn history_file(self) -> Option<PathBuf> {
+        let home = || std::env::var_os("HOME").map(PathBuf::from);
+        match self {
+            Shell::Bash | Shell::Zsh => {
+                if let Some(histfile) = std::env::var_os(HISTFILE_ENV_VAR)
+                    .filter(|histfile| !histfile.is_empty())
+                {
+                    return Some(PathBuf::from(histfile));
+                }
+                if self == Shell::Bash {
+                    home().map(|home| home.join(".bash_history"))
+                } else {
+                    std::env::var_os("ZDOTDIR")
+                        .map(PathBuf::from)
+                        .or_else(home)
+                        .map(|dir| dir.join(".zsh_history"))
+                }
+            }
+            Shell::Fish => std::env::var_os("XDG_DATA_HOME")
+                .map(PathBuf::from)
+                .or_else(|| home().map(|home| home.join(".local/share")))
+                .map(|dir| dir.join("fish/fish_history")),
+        }
+    }
+
+    /// Parse the content of a history file, oldest command first.
+    fn parse(self, content: &[u8]) -> Vec<String> {
+        match self {
+            Shell::Bash => parse_bash(&String::from_utf8_lossy(content)),
+            Shell::Zsh => parse_zsh(&unmetafy(content)),
+            Shell::Fish => parse_fish(&String::from_utf8_lossy(content)),
+        }
+    }
+}
+
+fn history(_token: &CancellationToken) -> Vec<HistoryCommand> {
+    let shell = Shell::current();
+    let Some(path) = shell.history_file() else {
+        return Vec::new();
+    };
+    debug!("Reading {:?} history from {:?}", shell, path);
+    let Ok(content) = std::fs::read(&path) else {
+        return Vec::new();
+    };
+    dedup_recent_first(shell.parse(&content))
+        .into_iter()
+        .map(|command| HistoryCommand { command })
+        .collect()
+}
+
+/// Keep the most recent occurrence of each command, most recent first.
+fn dedup_recent_first(commands: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    commands
+        .into_iter()
+        .rev()
+        .filter(|command| seen.insert(command.clone()))
+        .collect()
+}
+
+/// Bash history: one command per line, optionally preceded by a `#<time>`
+/// comment line when `HISTTIMEFORMAT` is set.
+fn parse_bash(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !is_bash_timestamp(line))
+        .map(String::from)
+        .collect()
+}
+
+fn is_bash_timestamp(line: &str) -> bool {
+    line.strip_prefix('#').is_some_and(|time| {
+        !time.is_empty() && time.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+/// Zsh history: commands optionally prefixed by `: <time>:<duration>;`
+/// (extended history), lines ending with a backslash being continued on the
+/// next one.
+fn parse_zsh(content: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut current: Option<String> = None;
+    for line in content.lines() {
+        let line = match current.take() {
+            Some(mut command) => {
+                command.push('\n');
+                command.push_str(line);
+                command
+            }
+            None => strip_zsh_extended_prefix(line).to_string(),
+        };
+        match line.strip_suffix('\\') {
+            Some(continued) => current = Some(continued.to_string()),
+            None if !line.trim().is_empty() => commands.push(line),
+            None => {}
+        }
+    }
+    commands.extend(current);
+    commands
+}
+
+fn strip_zsh_extended_prefix(line: &str) -> &str {
+    line.strip_prefix(": ")
+        .and_then(|rest| rest.split_once(';'))
+        .filter(|(time, _)| {
+            time.chars().all(|c| c.is_ascii_digit() || c == ':')
+        })
+        .map_or(line, |(_, command)| command)
+}
+
+/// Zsh "metafies" the bytes of its history file: special bytes are written
+/// as `0x83` followed by the byte xor-ed with `0x20`.
+fn unmetafy(content: &[u8]) -> String {
+    const META: u8 = 0x83;
+    let mut bytes = Vec::with_capacity(content.len());
+    let mut iter = content.iter();
+    while let Some(&byte) = iter.next() {
+        if byte == META {
+            if let Some(&next) = iter.next() {
+                bytes.push(next ^ 0x20);
+            }
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Fish history: a YAML-like list of `- cmd: <command>` items, with escaped
+/// newlines and backslashes.
+fn parse_fish(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| line.strip_prefix("- cmd: "))
+        .map(|command| {
+            let mut unescaped = String::with_capacity(command.len());
+            let mut chars = command.chars();
+            while let Some(c) = chars.next() {
+                match (c, chars.clone().next()) {
+                    ('\\', Some('n')) => {
+                        unescaped.push('\n');
+                        chars.next();
+                    }
+                    ('\\', Some('\\')) => {
+                        unescaped.push('\\');
+                        chars.next();
+                    }
+                    _ => unescaped.push(c),
+                }
+            }
+            unescaped
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bash() {
+        let content = "#1700000000\nls -la\n\ncd /tmp\n#not a timestamp\n";
+        assert_eq!(
+            parse_bash(content),
+            ["ls -la", "cd /tmp", "#not a timestamp"]
+        );
+    }
+
+    #[test]
+    fn test_parse_zsh() {
+        let content = ": 1700000000:0;git status\n\
+                       : 1700000001:3;for f in *; do\\\n  echo $f\\\ndone\n\
+                       make\n";
+        assert_eq!(
+            parse_zsh(content),
+            ["git status", "for f in *; do\n  echo $f\ndone", "make"]
+        );
+        // `ă` is `0xc4 0x83`
+        assert_eq!(unmetafy(b"echo \xc4\x83\xa3"), "echo ă");
+    }
+
+    #[test]
+    fn test_parse_fish() {
+        let content = "- cmd: echo a\\nb\n  when: 1700000000\n\
+                       - cmd: printf '\\\\n'\n  when: 1700000001\n  \
+                       paths:\n    - /tmp\n";
+        assert_eq!(parse_fish(content), ["echo a\nb", "printf '\\n'"]);
+    }
+
+    #[test]
+    fn test_dedup_recent_first() {
+        let commands = ["ls", "make", "ls", "cd"].map(String::from).to_vec();
+        assert_eq!(dedup_recent_first(commands), ["cd", "ls", "make"]);
+    }
+}
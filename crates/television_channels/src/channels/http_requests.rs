@@ -0,0 +1,432 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use devicons::FileIcon;
+use ignore::WalkState;
+use tracing::{debug, warn};
+
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewCommand, PreviewType};
+use crate::startup::Startup;
+use crate::threads::large_channel_default;
+use television_fuzzy::matcher::{config::Config, injector::Injector, Matcher};
+use television_utils::cancel::CancellationToken;
+use television_utils::files::{
+    walk_builder, WalkWarnings, DEFAULT_NUM_THREADS,
+};
+use television_utils::strings::{preprocess_line, shell_quote};
+
+/// A single request found in an `.http`/`.rest` or `.hurl` file.
+#[derive(Debug, Clone)]
+struct HttpRequest {
+    path: PathBuf,
+    method: String,
+    url: String,
+    /// The line number of the request line (1-based).
+    line_number: usize,
+    /// The index of the request in its file (1-based).
+    index: usize,
+}
+
+impl HttpRequest {
+    fn request_line(&self) -> String {
+        format!("{} {}", self.method, self.url)
+    }
+
+    /// The command used to execute the request and display its response.
+    fn execute_command(&self) -> Option<PreviewCommand> {
+        let path = shell_quote(&self.path.to_string_lossy());
+        match RequestFileKind::from_path(&self.path)? {
+            RequestFileKind::Hurl => Some(PreviewCommand::new(format!(
+                "hurl --include --from-entry {} --to-entry {} {path}",
+                self.index, self.index
+            ))),
+            RequestFileKind::Http => Some(PreviewCommand::new(format!(
+                "httpyac send {path} --line {}",
+                self.line_number
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestFileKind {
+    /// REST Client / JetBrains style `.http` and `.rest` files.
+    Http,
+    /// Hurl files.
+    Hurl,
+}
+
+impl RequestFileKind {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "http" | "rest" => Some(Self::Http),
+            "hurl" => Some(Self::Hurl),
+            _ => None,
+        }
+    }
+}
+
+const HTTP_METHODS: [&str; 9] = [
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE",
+    "CONNECT",
+];
+
+/// The separator between requests in `.http` files.
+const HTTP_REQUEST_SEPARATOR: &str = "###";
+
+pub struct Channel {
+    matcher: Matcher<HttpRequest>,
+    walk_warnings: Arc<WalkWarnings>,
+    crawl_handle: tokio::task::JoinHandle<()>,
+    /// Stops the walk, which aborting the crawl task doesn't.
+    token: CancellationToken,
+    execute_requests: bool,
+}
+
+impl Channel {
+    pub fn new(
+        ctx: &Context,
+        directories: Vec<PathBuf>,
+        execute_requests: bool,
+    ) -> Self {
+        let matcher = Matcher::new(Config::default().n_threads(
+            ctx.matcher_threads("httprequests", large_channel_default()),
+        ));
+        let walk_warnings = Arc::default();
+        let token = CancellationToken::new();
+        let crawl_handle = tokio::spawn(crawl_for_requests(
+            directories,
+            matcher.injector(),
+            Arc::clone(&walk_warnings),
+            token.clone(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            matcher,
+            walk_warnings,
+            crawl_handle,
+            token,
+            execute_requests,
+        }
+    }
+
+    /// Create a channel that previews requests by executing them (using
+    /// `hurl` for `.hurl` files and `httpyac` for `.http` files) and
+    /// displaying their response.
+    pub fn with_execution(ctx: &Context) -> Self {
+        Self::new(ctx, vec![std::env::current_dir().unwrap()], true)
+    }
+
+    fn preview_type(&self, request: &HttpRequest) -> PreviewType {
+        if self.execute_requests {
+            if let Some(command) = request.execute_command() {
+                return PreviewType::Command(command);
+            }
+        }
+        PreviewType::Files
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx, vec![std::env::current_dir().unwrap()], false)
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                let display_path =
+                    item.inner.path.to_string_lossy().to_string();
+                Entry::new(
+                    display_path.clone()
+                        + item.inner.line_number.to_string().as_str(),
+                    self.preview_type(&item.inner),
+                )
+                .with_display_name(display_path)
+                .with_value(item.matched_string)
+                .with_value_match_ranges(item.match_indices)
+                .with_icon(FileIcon::from(item.inner.path.as_path()))
+                .with_line_number(item.inner.line_number)
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            let display_path = item.inner.path.to_string_lossy().to_string();
+            Entry::new(display_path, self.preview_type(&item.inner))
+                .with_value(item.inner.request_line())
+                .with_icon(FileIcon::from(item.inner.path.as_path()))
+                .with_line_number(item.inner.line_number)
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.crawl_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.token.cancel();
+        self.crawl_handle.abort();
+    }
+
+    fn warnings(&self) -> Option<String> {
+        self.walk_warnings.summary()
+    }
+}
+
+async fn crawl_for_requests(
+    directories: Vec<PathBuf>,
+    injector: Injector<HttpRequest>,
+    walk_warnings: Arc<WalkWarnings>,
+    token: CancellationToken,
+    startup: Startup,
+) {
+    startup.ready().await;
+    if directories.is_empty() {
+        return;
+    }
+    let current_dir = std::env::current_dir().unwrap();
+    let mut walker =
+        walk_builder(&directories[0], *DEFAULT_NUM_THREADS, None, None);
+    directories[1..].iter().for_each(|path| {
+        walker.add(path);
+    });
+
+    walker.build_parallel().run(|| {
+        let injector = injector.clone();
+        let current_dir = current_dir.clone();
+        let walk_warnings = walk_warnings.clone();
+        let token = token.clone();
+        Box::new(move |result| {
+            if token.is_cancelled() {
+                return WalkState::Quit;
+            }
+            let entry = match result {
+                Ok(entry) => entry,
+                Err(e) => {
+                    walk_warnings.record(&e);
+                    return WalkState::Continue;
+                }
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                return WalkState::Continue;
+            }
+            if let Some(kind) = RequestFileKind::from_path(entry.path()) {
+                inject_requests(
+                    &injector,
+                    entry
+                        .path()
+                        .strip_prefix(&current_dir)
+                        .unwrap_or(entry.path()),
+                    kind,
+                    &walk_warnings,
+                );
+            }
+            WalkState::Continue
+        })
+    });
+}
+
+fn inject_requests(
+    injector: &Injector<HttpRequest>,
+    path: &Path,
+    kind: RequestFileKind,
+    walk_warnings: &WalkWarnings,
+) {
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            for request in parse_requests(path, &content, kind) {
+                debug!("Found request: {:?}", request);
+                let () = injector.push(request, |r, cols| {
+                    cols[0] = r.request_line().into();
+                });
+            }
+        }
+        Err(e) => {
+            warn!("Error reading file {:?}: {:?}", path, e);
+            walk_warnings.record_io(&e);
+        }
+    }
+}
+
+fn parse_requests(
+    path: &Path,
+    content: &str,
+    kind: RequestFileKind,
+) -> Vec<HttpRequest> {
+    let mut requests = Vec::new();
+    // in `.http` files, the first meaningful line of each block is the
+    // request line, which may omit the method (defaulting to GET)
+    let mut expecting_request_line = true;
+    for (i, raw_line) in content.lines().enumerate() {
+        let line = raw_line.trim();
+        if kind == RequestFileKind::Http {
+            if line.starts_with(HTTP_REQUEST_SEPARATOR) {
+                expecting_request_line = true;
+                continue;
+            }
+            if line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with("//")
+                || line.starts_with('@')
+            {
+                continue;
+            }
+        }
+        // hurl entries start with their request line, wherever they are,
+        // while the headers and bodies of `.http` requests may also start
+        // with a method (e.g. a plain text body)
+        let request_line =
+            kind == RequestFileKind::Hurl || expecting_request_line;
+        let mut parts = line.split_whitespace();
+        let (method, url) = match (parts.next(), parts.next()) {
+            (Some(method), Some(url))
+                if request_line && HTTP_METHODS.contains(&method) =>
+            {
+                (method, url)
+            }
+            (Some(url), _)
+                if kind == RequestFileKind::Http
+                    && request_line
+                    && url.starts_with("http") =>
+            {
+                ("GET", url)
+            }
+            _ => {
+                expecting_request_line = false;
+                continue;
+            }
+        };
+        expecting_request_line = false;
+        requests.push(HttpRequest {
+            path: path.to_path_buf(),
+            method: method.to_string(),
+            url: preprocess_line(url),
+            line_number: i + 1,
+            index: requests.len() + 1,
+        });
+    }
+    requests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_http_requests() {
+        let content = "\
+@host = https://example.com
+
+### list users
+GET {{host}}/users
+Accept: application/json
+
+###
+https://example.com/health
+
+### create user
+POST {{host}}/users HTTP/1.1
+Content-Type: application/json
+
+{\"name\": \"GET me\"}
+";
+        let requests = parse_requests(
+            Path::new("api.http"),
+            content,
+            RequestFileKind::Http,
+        );
+        let lines: Vec<_> = requests
+            .iter()
+            .map(|r| (r.request_line(), r.line_number, r.index))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("GET {{host}}/users".to_string(), 4, 1),
+                ("GET https://example.com/health".to_string(), 8, 2),
+                ("POST {{host}}/users".to_string(), 11, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_http_request_bodies() {
+        let content = "\
+POST https://example.com/notes
+Content-Type: text/plain
+
+DELETE https://example.com/users/1 was called twice
+";
+        let requests = parse_requests(
+            Path::new("notes.http"),
+            content,
+            RequestFileKind::Http,
+        );
+        let lines: Vec<_> = requests
+            .iter()
+            .map(|r| (r.request_line(), r.line_number))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![("POST https://example.com/notes".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_parse_hurl_requests() {
+        let content = "\
+GET https://example.com/api/users
+HTTP 200
+[Asserts]
+jsonpath \"$.users\" count > 0
+
+DELETE https://example.com/api/users/1
+HTTP 204
+";
+        let requests = parse_requests(
+            Path::new("api.hurl"),
+            content,
+            RequestFileKind::Hurl,
+        );
+        let lines: Vec<_> = requests
+            .iter()
+            .map(|r| (r.request_line(), r.line_number, r.index))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ("GET https://example.com/api/users".to_string(), 1, 1),
+                ("DELETE https://example.com/api/users/1".to_string(), 6, 2),
+            ]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use devicons::FileIcon;
+use tracing::{debug, warn};
+
+use super::reload::Reloader;
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewType};
+use crate::startup::Startup;
+use crate::time_range::TimeRange;
+use television_fuzzy::matcher::{config::Config, injector::Injector, Matcher};
+use television_utils::strings::preprocess_line;
+
+/// The command listing the most recent entries of the systemd journal,
+/// newest first.
+const JOURNAL_COMMAND: &str =
+    "journalctl --no-pager --quiet --reverse --output short-iso --lines 10000";
+
+#[allow(clippy::module_name_repetitions)]
+pub struct Channel {
+    matcher: Matcher<String>,
+    /// The time range the entries are restricted to.
+    time_range: TimeRange,
+    file_icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+    reloader: Reloader<String>,
+}
+
+const NUM_THREADS: usize = 1;
+const FILE_ICON_STR: &str = "log";
+
+impl Channel {
+    pub fn new(ctx: &Context) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(ctx.matcher_threads("journal", NUM_THREADS)),
+        );
+        let load_handle = tokio::spawn(load_lines(
+            matcher.injector(),
+            ctx.time_range().clone(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            matcher,
+            time_range: ctx.time_range().clone(),
+            file_icon: FileIcon::from(FILE_ICON_STR),
+            load_handle,
+            reloader: Reloader::default(),
+        }
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx)
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(lines) = self.reloader.take() {
+            self.matcher.update_items(lines, |line, cols| {
+                cols[0] = line.clone().into();
+            });
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                Entry::new(item.matched_string, PreviewType::Basic)
+                    .with_name_match_ranges(item.match_indices)
+                    .with_icon(self.file_icon)
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            Entry::new(item.inner.clone(), PreviewType::Basic)
+                .with_icon(self.file_icon)
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        let time_range = self.time_range.clone();
+        self.reloader.start(move || journal_lines(&time_range));
+    }
+}
+
+async fn load_lines(
+    injector: Injector<String>,
+    time_range: TimeRange,
+    startup: Startup,
+) {
+    startup.ready().await;
+    for line in journal_lines(&time_range) {
+        let () = injector.push(line, |line, cols| {
+            cols[0] = line.clone().into();
+        });
+    }
+}
+
+/// The lines of the most recent journal entries within the given time
+/// range.
+fn journal_lines(time_range: &TimeRange) -> Vec<String> {
+    debug!("Listing the journal entries {}", time_range);
+    match time_range.shell_command(JOURNAL_COMMAND).output() {
+        Ok(output) => {
+            if !output.status.success() {
+                warn!("journalctl exited with {}", output.status);
+            }
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(preprocess_line)
+                .collect()
+        }
+        Err(e) => {
+            debug!("Failed to run journalctl: {:?}", e);
+            Vec::new()
+        }
+    }
+}
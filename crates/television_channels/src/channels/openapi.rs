@@ -0,0 +1,688 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{eyre, Result};
+use devicons::FileIcon;
+use serde_json::Value;
+use tracing::warn;
+
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewType};
+use crate::startup::Startup;
+use television_fuzzy::matcher::{config::Config, injector::Injector, Matcher};
+use television_utils::indices::sep_name_and_value_indices;
+use television_utils::strings::preprocess_line;
+
+/// An operation (e.g. `GET /pets/{petId}`) defined in an OpenAPI spec.
+#[derive(Debug, Clone)]
+struct Operation {
+    /// The method and path of the operation (e.g. `GET /pets/{petId}`).
+    name: String,
+    summary: String,
+    /// A description of the operation's parameters, request body and
+    /// responses.
+    details: String,
+    /// A `curl` command template to call the operation.
+    curl: String,
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct Channel {
+    matcher: Matcher<Operation>,
+    file_icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+}
+
+const NUM_THREADS: usize = 1;
+const FILE_ICON_STR: &str = "json";
+
+/// File names that are looked up in the current directory when no spec path
+/// is given.
+const DEFAULT_SPEC_FILE_NAMES: [&str; 6] = [
+    "openapi.yaml",
+    "openapi.yml",
+    "openapi.json",
+    "swagger.yaml",
+    "swagger.yml",
+    "swagger.json",
+];
+
+const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// How deep nested schemas are expanded in the preview.
+const MAX_SCHEMA_DEPTH: usize = 3;
+
+impl Channel {
+    pub fn new(ctx: &Context, spec_path: Option<PathBuf>) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(ctx.matcher_threads("openapi", NUM_THREADS)),
+        );
+        let load_handle = tokio::spawn(load_spec(
+            spec_path,
+            matcher.injector(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            matcher,
+            file_icon: FileIcon::from(FILE_ICON_STR),
+            load_handle,
+        }
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx, None)
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                let (
+                    name_indices,
+                    value_indices,
+                    should_add_name_indices,
+                    should_add_value_indices,
+                ) = sep_name_and_value_indices(
+                    &mut item.match_indices.iter().map(|i| i.0).collect(),
+                    u32::try_from(item.inner.name.len()).unwrap(),
+                );
+
+                let mut entry = Entry::new(
+                    item.inner.name.clone(),
+                    PreviewType::PlainText,
+                )
+                .with_icon(self.file_icon);
+                if !item.inner.summary.is_empty() {
+                    entry = entry.with_value(item.inner.summary.clone());
+                }
+
+                if should_add_name_indices {
+                    entry = entry.with_name_match_ranges(
+                        name_indices.into_iter().map(|i| (i, i + 1)).collect(),
+                    );
+                }
+
+                if should_add_value_indices {
+                    entry = entry.with_value_match_ranges(
+                        value_indices
+                            .into_iter()
+                            .map(|i| (i, i + 1))
+                            .collect(),
+                    );
+                }
+
+                entry
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            Entry::new(item.inner.name.clone(), PreviewType::PlainText)
+                .with_value(item.inner.details.clone())
+                .with_icon(self.file_icon)
+                .with_output(item.inner.curl.clone())
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.load_handle.abort();
+    }
+}
+
+async fn load_spec(
+    spec_path: Option<PathBuf>,
+    injector: Injector<Operation>,
+    startup: Startup,
+) {
+    startup.ready().await;
+    match spec_path
+        .or_else(find_spec_in_current_dir)
+        .ok_or_else(|| eyre!("No OpenAPI spec found"))
+        .and_then(|path| load_operations(&path))
+    {
+        Ok(operations) => {
+            for operation in operations {
+                let () = injector.push(operation, |o, cols| {
+                    cols[0] = (o.name.clone() + o.summary.as_str()).into();
+                });
+            }
+        }
+        Err(e) => warn!("Error loading OpenAPI spec: {:?}", e),
+    }
+}
+
+fn find_spec_in_current_dir() -> Option<PathBuf> {
+    let current_dir = std::env::current_dir().ok()?;
+    DEFAULT_SPEC_FILE_NAMES
+        .iter()
+        .map(|name| current_dir.join(name))
+        .find(|path| path.is_file())
+}
+
+fn load_operations(path: &Path) -> Result<Vec<Operation>> {
+    let content = std::fs::read_to_string(path)?;
+    let spec: Value = if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+    {
+        serde_json::from_str(&content)?
+    } else {
+        serde_yaml::from_str(&content)?
+    };
+    Ok(parse_operations(&spec))
+}
+
+fn parse_operations(spec: &Value) -> Vec<Operation> {
+    let base_url = base_url(spec);
+    let mut operations = Vec::new();
+    let Some(paths) = spec.get("paths").and_then(Value::as_object) else {
+        return operations;
+    };
+    for (path, path_item) in paths {
+        let path_item = resolve(spec, path_item);
+        let path_parameters = parameters(spec, path_item);
+        for method in HTTP_METHODS {
+            let Some(operation) = path_item.get(method) else {
+                continue;
+            };
+            // operation level parameters override path level ones
+            let mut parameters = parameters(spec, operation);
+            for parameter in &path_parameters {
+                if !parameters.iter().any(|p| {
+                    p.name == parameter.name
+                        && p.location == parameter.location
+                }) {
+                    parameters.push(parameter.clone());
+                }
+            }
+            let summary = operation
+                .get("summary")
+                .or_else(|| operation.get("operationId"))
+                .and_then(Value::as_str)
+                .map(preprocess_line)
+                .unwrap_or_default();
+            let name = format!("{} {path}", method.to_uppercase());
+            let body = request_body(spec, operation);
+            operations.push(Operation {
+                details: details(
+                    spec,
+                    &name,
+                    operation,
+                    &parameters,
+                    body.as_ref(),
+                ),
+                curl: curl_template(
+                    &base_url,
+                    method,
+                    path,
+                    &parameters,
+                    body.as_ref(),
+                ),
+                name,
+                summary,
+            });
+        }
+    }
+    operations
+}
+
+#[derive(Debug, Clone)]
+struct Parameter<'a> {
+    name: String,
+    /// Where the parameter goes (`path`, `query`, `header`, `cookie`).
+    location: String,
+    required: bool,
+    description: Option<String>,
+    schema: Option<&'a Value>,
+}
+
+/// A request body: its media type and schema.
+struct RequestBody<'a> {
+    media_type: String,
+    schema: Option<&'a Value>,
+}
+
+fn parameters<'a>(spec: &'a Value, item: &'a Value) -> Vec<Parameter<'a>> {
+    item.get("parameters")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|p| resolve(spec, p))
+        // swagger 2 body parameters are handled as request bodies
+        .filter(|p| p.get("in").and_then(Value::as_str) != Some("body"))
+        .filter_map(|p| {
+            Some(Parameter {
+                name: p.get("name")?.as_str()?.to_string(),
+                location: p.get("in")?.as_str()?.to_string(),
+                required: p
+                    .get("required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                description: p
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(preprocess_line),
+                // swagger 2 parameters carry their type directly
+                schema: p.get("schema").or(Some(p)),
+            })
+        })
+        .collect()
+}
+
+fn request_body<'a>(
+    spec: &'a Value,
+    operation: &'a Value,
+) -> Option<RequestBody<'a>> {
+    // openapi 3
+    if let Some(body) = operation.get("requestBody") {
+        let (media_type, content) = resolve(spec, body)
+            .get("content")?
+            .as_object()?
+            .iter()
+            .next()?;
+        return Some(RequestBody {
+            media_type: media_type.clone(),
+            schema: content.get("schema"),
+        });
+    }
+    // swagger 2
+    operation
+        .get("parameters")
+        .and_then(Value::as_array)?
+        .iter()
+        .map(|p| resolve(spec, p))
+        .find(|p| p.get("in").and_then(Value::as_str) == Some("body"))
+        .map(|p| RequestBody {
+            media_type: operation
+                .get("consumes")
+                .or_else(|| spec.get("consumes"))
+                .and_then(|c| c.get(0))
+                .and_then(Value::as_str)
+                .unwrap_or("application/json")
+                .to_string(),
+            schema: p.get("schema"),
+        })
+}
+
+fn base_url(spec: &Value) -> String {
+    // openapi 3
+    if let Some(url) = spec
+        .get("servers")
+        .and_then(|s| s.get(0))
+        .and_then(|s| s.get("url"))
+        .and_then(Value::as_str)
+    {
+        return url.trim_end_matches('/').to_string();
+    }
+    // swagger 2
+    let scheme = spec
+        .get("schemes")
+        .and_then(|s| s.get(0))
+        .and_then(Value::as_str)
+        .unwrap_or("https");
+    let host = spec
+        .get("host")
+        .and_then(Value::as_str)
+        .unwrap_or("localhost");
+    let base_path = spec.get("basePath").and_then(Value::as_str).unwrap_or("");
+    format!("{scheme}://{host}{}", base_path.trim_end_matches('/'))
+}
+
+/// Follow a local `$ref` (e.g. `#/components/schemas/Pet`) if there is one.
+fn resolve<'a>(spec: &'a Value, value: &'a Value) -> &'a Value {
+    value
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix('#'))
+        .and_then(|pointer| spec.pointer(pointer))
+        .unwrap_or(value)
+}
+
+fn ref_name(value: &Value) -> Option<&str> {
+    value
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.rsplit('/').next())
+}
+
+fn details(
+    spec: &Value,
+    name: &str,
+    operation: &Value,
+    parameters: &[Parameter],
+    body: Option<&RequestBody>,
+) -> String {
+    let mut details = name.to_string();
+    for key in ["summary", "description"] {
+        if let Some(text) = operation.get(key).and_then(Value::as_str) {
+            details.push_str("\n\n");
+            details.push_str(&preprocess_line(text));
+        }
+    }
+
+    if !parameters.is_empty() {
+        details.push_str("\n\nParameters:");
+        for parameter in parameters {
+            let _ = write!(
+                details,
+                "\n  {} ({}{}): {}",
+                parameter.name,
+                parameter.location,
+                if parameter.required { ", required" } else { "" },
+                parameter
+                    .schema
+                    .map_or_else(|| "any".to_string(), schema_type)
+            );
+            if let Some(description) = &parameter.description {
+                let _ = write!(details, " - {description}");
+            }
+        }
+    }
+
+    if let Some(body) = body {
+        let _ = write!(details, "\n\nRequest body ({}):", body.media_type);
+        if let Some(schema) = body.schema {
+            render_schema(spec, schema, 1, 0, &mut details);
+        }
+    }
+
+    if let Some(responses) =
+        operation.get("responses").and_then(Value::as_object)
+    {
+        details.push_str("\n\nResponses:");
+        for (code, response) in responses {
+            let response = resolve(spec, response);
+            let _ = write!(
+                details,
+                "\n  {code}: {}",
+                response
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(preprocess_line)
+                    .unwrap_or_default()
+            );
+            let schema = response
+                .get("content")
+                .and_then(Value::as_object)
+                .and_then(|c| c.values().next())
+                .and_then(|c| c.get("schema"))
+                .or_else(|| response.get("schema"));
+            if let Some(schema) = schema {
+                render_schema(spec, schema, 2, 0, &mut details);
+            }
+        }
+    }
+    details
+}
+
+/// A short description of a schema's type (e.g. `string`, `array of Pet`).
+fn schema_type(schema: &Value) -> String {
+    if let Some(name) = ref_name(schema) {
+        return name.to_string();
+    }
+    match schema.get("type").and_then(Value::as_str) {
+        Some("array") => format!(
+            "array of {}",
+            schema
+                .get("items")
+                .map_or_else(|| "any".to_string(), schema_type)
+        ),
+        Some(t) => schema
+            .get("format")
+            .and_then(Value::as_str)
+            .map_or_else(|| t.to_string(), |f| format!("{t} ({f})")),
+        None if schema.get("properties").is_some() => "object".to_string(),
+        None => "any".to_string(),
+    }
+}
+
+/// Render a schema and its properties (down to `MAX_SCHEMA_DEPTH`).
+fn render_schema(
+    spec: &Value,
+    schema: &Value,
+    indent: usize,
+    depth: usize,
+    out: &mut String,
+) {
+    let _ = write!(out, "\n{}{}", "  ".repeat(indent), schema_type(schema));
+    render_properties(spec, schema, indent + 1, depth, out);
+}
+
+fn render_properties(
+    spec: &Value,
+    schema: &Value,
+    indent: usize,
+    depth: usize,
+    out: &mut String,
+) {
+    if depth >= MAX_SCHEMA_DEPTH {
+        return;
+    }
+    let mut schema = resolve(spec, schema);
+    if schema.get("type").and_then(Value::as_str) == Some("array") {
+        if let Some(items) = schema.get("items") {
+            schema = resolve(spec, items);
+        }
+    }
+    let Some(properties) = schema.get("properties").and_then(Value::as_object)
+    else {
+        return;
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    for (name, property) in properties {
+        let _ = write!(
+            out,
+            "\n{}{name}: {}{}",
+            "  ".repeat(indent),
+            schema_type(property),
+            if required.contains(&name.as_str()) {
+                " (required)"
+            } else {
+                ""
+            }
+        );
+        render_properties(spec, property, indent + 1, depth + 1, out);
+    }
+}
+
+fn curl_template(
+    base_url: &str,
+    method: &str,
+    path: &str,
+    parameters: &[Parameter],
+    body: Option<&RequestBody>,
+) -> String {
+    let mut url = format!("{base_url}{path}");
+    let query: Vec<String> = parameters
+        .iter()
+        .filter(|p| p.location == "query")
+        .map(|p| format!("{}={{{}}}", p.name, p.name))
+        .collect();
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(&query.join("&"));
+    }
+
+    let mut curl = format!("curl -X {} '{url}'", method.to_uppercase());
+    for parameter in parameters.iter().filter(|p| p.location == "header") {
+        let _ =
+            write!(curl, " -H '{}: {{{}}}'", parameter.name, parameter.name);
+    }
+    if let Some(body) = body {
+        let _ =
+            write!(curl, " -H 'Content-Type: {}' -d '{{}}'", body.media_type);
+    }
+    curl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r"
+openapi: 3.0.0
+servers:
+  - url: https://petstore.example.com/v1/
+paths:
+  /pets:
+    get:
+      summary: List all pets
+      parameters:
+        - name: limit
+          in: query
+          schema:
+            type: integer
+            format: int32
+      responses:
+        '200':
+          description: A list of pets
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: '#/components/schemas/Pet'
+    post:
+      summary: Create a pet
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+      responses:
+        '201':
+          description: Created
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        schema:
+          type: string
+    get:
+      operationId: showPetById
+      parameters:
+        - name: X-Request-Id
+          in: header
+          schema:
+            type: string
+      responses:
+        default:
+          description: unexpected error
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [id]
+      properties:
+        id:
+          type: integer
+        name:
+          type: string
+";
+
+    fn operations() -> Vec<Operation> {
+        parse_operations(&serde_yaml::from_str(SPEC).unwrap())
+    }
+
+    #[test]
+    fn test_parse_operations() {
+        let operations = operations();
+        let names: Vec<_> = operations
+            .iter()
+            .map(|o| (o.name.as_str(), o.summary.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("GET /pets", "List all pets"),
+                ("POST /pets", "Create a pet"),
+                ("GET /pets/{petId}", "showPetById"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_curl_template() {
+        let operations = operations();
+        assert_eq!(
+            operations[0].curl,
+            "curl -X GET 'https://petstore.example.com/v1/pets?limit={limit}'"
+        );
+        assert_eq!(
+            operations[1].curl,
+            "curl -X POST 'https://petstore.example.com/v1/pets' \
+             -H 'Content-Type: application/json' -d '{}'"
+        );
+        assert_eq!(
+            operations[2].curl,
+            "curl -X GET 'https://petstore.example.com/v1/pets/{petId}' \
+             -H 'X-Request-Id: {X-Request-Id}'"
+        );
+    }
+
+    #[test]
+    fn test_details() {
+        let operations = operations();
+        assert_eq!(
+            operations[0].details,
+            "\
+GET /pets
+
+List all pets
+
+Parameters:
+  limit (query): integer (int32)
+
+Responses:
+  200: A list of pets
+    array of Pet
+      id: integer (required)
+      name: string"
+        );
+        assert!(operations[2]
+            .details
+            .contains("petId (path, required): string"));
+    }
+}
@@ -0,0 +1,500 @@
+//! Channels shipped as precompiled WebAssembly modules, loaded from the
+//! `plugins` directory of the config directory.
+//!
+//! A plugin is a core WebAssembly module (e.g. built for the
+//! `wasm32-unknown-unknown` target) named after its file, which imports
+//! nothing and exports its `memory` along with the following functions:
+//! - `alloc(len: i32) -> i32`: the address of a buffer of `len` bytes, which
+//!   the string argument of the functions below is written to;
+//! - `entries() -> i64`: the entries of the channel, one per line;
+//! - `preview(ptr: i32, len: i32) -> i64` (optional): the preview of an
+//!   entry, colored by ANSI escape sequences or not;
+//! - `on_select(ptr: i32, len: i32) -> i64` (optional): the text output when
+//!   an entry is confirmed, instead of the entry itself (unless empty).
+//!
+//! Strings are UTF-8 encoded, and returned as their address in the upper 32
+//! bits and their length in the lower 32 bits. Each call is made on a fresh
+//! instance of the module, with a bounded amount of fuel so that a plugin
+//! can't hang the picker.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use color_eyre::eyre::{bail, eyre, Result};
+use devicons::FileIcon;
+use tracing::{debug, warn};
+use wasmi::{Engine, ExternType, Linker, Module, Store};
+
+use super::reload::Reloader;
+use super::OnAir;
+use crate::bus::{Bus, Message};
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewType};
+use television_fuzzy::matcher::{config::Config, Matcher};
+use television_utils::strings::preprocess_line;
+
+/// The extension of the plugin files.
+const PLUGIN_EXTENSION: &str = "wasm";
+
+/// The exports of a plugin.
+const MEMORY: &str = "memory";
+const ALLOC_FN: &str = "alloc";
+const ENTRIES_FN: &str = "entries";
+const PREVIEW_FN: &str = "preview";
+const ON_SELECT_FN: &str = "on_select";
+
+/// The key of the notifications about the plugins.
+const PLUGIN_TOAST: &str = "plugin";
+
+/// How many instructions (roughly) a call to a plugin may run.
+const FUEL: u64 = 1_000_000_000;
+
+/// A plugin, named after its file.
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub name: String,
+    engine: Engine,
+    module: Arc<Module>,
+}
+
+impl Plugin {
+    /// Compile the plugin at the given path.
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| eyre!("{} isn't a file", path.display()))?;
+        let wasm = std::fs::read(path)?;
+        Self::compile(&name, &wasm)
+            .map_err(|e| eyre!("{}: {e}", path.display()))
+    }
+
+    /// Compile the given WebAssembly module, checking that it exports what
+    /// plugins must.
+    pub fn compile(name: &str, wasm: &[u8]) -> Result<Self> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm)?;
+        if module.imports().len() > 0 {
+            bail!("plugins can't import anything");
+        }
+        let plugin = Self {
+            name: name.to_string(),
+            engine,
+            module: Arc::new(module),
+        };
+        if !plugin.exports_memory() {
+            bail!("missing `{MEMORY}` export");
+        }
+        for (function, arity) in [(ALLOC_FN, 1), (ENTRIES_FN, 0)] {
+            if !plugin.has_function(function, arity) {
+                bail!("missing `{function}` function");
+            }
+        }
+        Ok(plugin)
+    }
+
+    /// Whether the plugin lists entries, which every valid plugin does.
+    fn lists_entries(&self) -> bool {
+        self.has_function(ENTRIES_FN, 0)
+    }
+
+    /// Whether the plugin previews the entries it lists.
+    pub fn has_preview(&self) -> bool {
+        self.has_function(PREVIEW_FN, 2)
+    }
+
+    /// The entries listed by the plugin, skipping the blank ones.
+    pub fn entries(&self) -> Result<Vec<String>> {
+        Ok(self
+            .call(ENTRIES_FN, None)?
+            .lines()
+            .filter(|entry| !entry.trim().is_empty())
+            .map(preprocess_line)
+            .collect())
+    }
+
+    /// The preview of the given entry.
+    pub fn preview(&self, entry: &str) -> Result<String> {
+        self.call(PREVIEW_FN, Some(entry))
+    }
+
+    /// The text output when the given entry is confirmed, if the plugin
+    /// replaces it.
+    pub fn on_select(&self, entry: &str) -> Result<Option<String>> {
+        if !self.has_function(ON_SELECT_FN, 2) {
+            return Ok(None);
+        }
+        let output = self.call(ON_SELECT_FN, Some(entry))?;
+        Ok((!output.is_empty()).then_some(output))
+    }
+
+    fn exports_memory(&self) -> bool {
+        self.module.exports().any(|export| {
+            export.name() == MEMORY
+                && matches!(export.ty(), ExternType::Memory(_))
+        })
+    }
+
+    fn has_function(&self, name: &str, arity: usize) -> bool {
+        self.module.exports().any(|export| {
+            export.name() == name
+                && matches!(
+                    export.ty(),
+                    ExternType::Func(f) if f.params().len() == arity
+                )
+        })
+    }
+
+    fn call(&self, name: &str, arg: Option<&str>) -> Result<String> {
+        debug!("Calling {}::{}", self.name, name);
+        self.try_call(name, arg)
+            .map_err(|e| eyre!("{}::{name}: {e}", self.name))
+    }
+
+    fn try_call(&self, name: &str, arg: Option<&str>) -> Result<String> {
+        let mut store = Store::new(&self.engine, ());
+        store.set_fuel(FUEL).map_err(|e| eyre!("{e}"))?;
+        let instance = Linker::new(&self.engine)
+            .instantiate(&mut store, &self.module)?
+            .start(&mut store)?;
+        let memory = instance
+            .get_memory(&store, MEMORY)
+            .ok_or_else(|| eyre!("missing `{MEMORY}` export"))?;
+        let packed = match arg {
+            None => instance
+                .get_typed_func::<(), i64>(&store, name)?
+                .call(&mut store, ())?,
+            Some(arg) => {
+                let len = i32::try_from(arg.len())?;
+                let ptr = instance
+                    .get_typed_func::<i32, i32>(&store, ALLOC_FN)?
+                    .call(&mut store, len)?;
+                memory
+                    .write(&mut store, usize::try_from(ptr)?, arg.as_bytes())
+                    .map_err(|e| eyre!("{e}"))?;
+                instance
+                    .get_typed_func::<(i32, i32), i64>(&store, name)?
+                    .call(&mut store, (ptr, len))?
+            }
+        };
+        let packed = packed.cast_unsigned();
+        let mut bytes = vec![0; usize::try_from(packed & 0xffff_ffff)?];
+        memory
+            .read(&store, usize::try_from(packed >> 32)?, &mut bytes)
+            .map_err(|e| eyre!("{e}"))?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Compile the plugins of the given directory, in the order of their names.
+pub fn load_plugins(dir: &Path) -> Vec<Result<Plugin>> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = read_dir
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|ext| ext == PLUGIN_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+    paths.iter().map(|path| Plugin::load(path)).collect()
+}
+
+impl Context {
+    /// Register the plugins found in the config directory, so that their
+    /// channels can be picked from the remote control.
+    pub fn set_plugins(&mut self, plugins: Vec<Plugin>) {
+        self.plugins = plugins;
+    }
+
+    /// The registered plugin with the given (case insensitive) name.
+    pub fn plugin(&self, name: &str) -> Option<Plugin> {
+        self.plugins
+            .iter()
+            .find(|plugin| plugin.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    /// The names of the registered plugins.
+    pub fn plugin_channel_names(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .map(|plugin| plugin.name.clone())
+            .collect()
+    }
+}
+
+/// Log the error of a plugin and notify the user about it through the given
+/// bus.
+fn report_error(bus: &Bus, error: &color_eyre::Report) {
+    warn!("{error}");
+    bus.send(Message::Finished {
+        key: PLUGIN_TOAST.to_string(),
+        text: error.to_string(),
+        failed: true,
+    });
+}
+
+pub struct Channel {
+    plugin: Plugin,
+    bus: Bus,
+    matcher: Matcher<String>,
+    icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+    reloader: Reloader<String>,
+}
+
+const NUM_THREADS: usize = 1;
+
+impl Channel {
+    pub fn new(ctx: &Context, plugin: Plugin) -> Self {
+        let matcher = Matcher::new(Config::default().n_threads(
+            ctx.matcher_threads(&plugin.name.to_lowercase(), NUM_THREADS),
+        ));
+        let injector = matcher.injector();
+        let (loaded, bus) = (plugin.clone(), ctx.bus.clone());
+        let load_handle = tokio::task::spawn_blocking(move || {
+            for entry in entries(&loaded, &bus) {
+                let () = injector.push(entry, |e, cols| {
+                    cols[0] = e.clone().into();
+                });
+            }
+        });
+        Channel {
+            plugin,
+            bus: ctx.bus.clone(),
+            matcher,
+            icon: FileIcon::from(PLUGIN_EXTENSION),
+            load_handle,
+            reloader: Reloader::default(),
+        }
+    }
+
+    /// The name of the channel, i.e. of its plugin.
+    pub fn name(&self) -> &str {
+        &self.plugin.name
+    }
+
+    /// The entries output when the given ones are confirmed, as replaced by
+    /// the plugin's `on_select` function.
+    pub fn on_select(&self, entries: Vec<Entry>) -> Vec<Entry> {
+        entries
+            .into_iter()
+            .map(|entry| match self.plugin.on_select(&entry.stdout_repr()) {
+                Ok(Some(output)) => entry.with_output(output),
+                Ok(None) => entry,
+                Err(e) => {
+                    report_error(&self.bus, &e);
+                    entry
+                }
+            })
+            .collect()
+    }
+
+    fn entry(&self, line: &str) -> Entry {
+        let preview = if self.plugin.has_preview() {
+            PreviewType::Plugin(self.plugin.name.clone())
+        } else {
+            PreviewType::Basic
+        };
+        Entry::new(line.to_string(), preview).with_icon(self.icon)
+    }
+}
+
+/// An empty channel, plugin channels being created from their plugin.
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        let engine = Engine::default();
+        let module = Module::new(&engine, EMPTY_MODULE)
+            .expect("the empty module is valid");
+        Self::new(
+            ctx,
+            Plugin {
+                name: "plugin".to_string(),
+                engine,
+                module: Arc::new(module),
+            },
+        )
+    }
+}
+
+/// The binary encoding of a module without anything in it.
+const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+/// The entries listed by the given plugin, none if it failed.
+fn entries(plugin: &Plugin, bus: &Bus) -> Vec<String> {
+    if !plugin.lists_entries() {
+        return Vec::new();
+    }
+    plugin.entries().unwrap_or_else(|e| {
+        report_error(bus, &e);
+        Vec::new()
+    })
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(lines) = self.reloader.take() {
+            self.matcher.update_items(lines, |line, cols| {
+                cols[0] = line.clone().into();
+            });
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                self.entry(&item.inner)
+                    .with_name_match_ranges(item.match_indices)
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher
+            .get_result(index)
+            .map(|item| self.entry(&item.inner))
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        let (plugin, bus) = (self.plugin.clone(), self.bus.clone());
+        self.reloader.start(move || entries(&plugin, &bus));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lists `stash@{0}: wip` and `stash@{1}: fix`, previews an entry as
+    /// `diff of <entry>` and outputs its first 8 bytes when it's selected.
+    const STASHES: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 0) "stash@{0}: wip\n\n stash@{1}: fix\n")
+          (data (i32.const 64) "diff of ")
+          (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+          (func (export "entries") (result i64) (i64.const 32))
+          (func (export "preview") (param $ptr i32) (param $len i32)
+            (result i64)
+            (memory.copy (i32.const 72) (local.get $ptr) (local.get $len))
+            (i64.or
+              (i64.const 274877906944)
+              (i64.extend_i32_u (i32.add (local.get $len) (i32.const 8)))))
+          (func (export "on_select") (param $ptr i32) (param $len i32)
+            (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.const 8))))
+    "#;
+
+    #[test]
+    fn test_plugin() {
+        let plugin =
+            Plugin::compile("stashes", &wat::parse_str(STASHES).unwrap())
+                .unwrap();
+        assert!(plugin.has_preview());
+        assert_eq!(
+            plugin.entries().unwrap(),
+            vec!["stash@{0}: wip", " stash@{1}: fix"]
+        );
+        assert_eq!(plugin.preview("a").unwrap(), "diff of a");
+        assert_eq!(
+            plugin.on_select("stash@{1}: fix").unwrap(),
+            Some("stash@{1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_plugins() {
+        let compile = |wat: &str| {
+            Plugin::compile("invalid", &wat::parse_str(wat).unwrap())
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(compile("(module)"), "missing `memory` export");
+        assert_eq!(
+            compile(r#"(module (memory (export "memory") 1))"#),
+            "missing `alloc` function"
+        );
+        assert_eq!(
+            compile(r#"(module (import "env" "run" (func)))"#),
+            "plugins can't import anything"
+        );
+        assert!(Plugin::compile("invalid", b"not wasm").is_err());
+
+        // a plugin without `preview` nor `on_select` functions
+        let minimal = Plugin::compile(
+            "minimal",
+            &wat::parse_str(
+                r#"(module
+                  (memory (export "memory") 1)
+                  (func (export "alloc") (param i32) (result i32)
+                    (i32.const 0))
+                  (func (export "entries") (result i64)
+                    (i64.const 131072)))"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(!minimal.has_preview());
+        assert_eq!(minimal.on_select("a").unwrap(), None);
+        // the string it returns is out of its memory
+        assert!(minimal
+            .entries()
+            .unwrap_err()
+            .to_string()
+            .starts_with("minimal::entries: "));
+    }
+
+    #[test]
+    fn test_load_plugins() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &[u8]| {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        };
+        write("b.wasm", &wat::parse_str(STASHES).unwrap());
+        write("a.wasm", b"not wasm");
+        write("notes.txt", b"");
+        let plugins = load_plugins(dir.path());
+        assert_eq!(plugins.len(), 2);
+        assert!(plugins[0].is_err());
+        assert_eq!(plugins[1].as_ref().unwrap().name, "b");
+
+        let mut ctx = Context::default();
+        ctx.set_plugins(plugins.into_iter().flatten().collect());
+        assert_eq!(ctx.plugin_channel_names(), vec!["b"]);
+        assert!(ctx.plugin("B").is_some());
+    }
+}
@@ -0,0 +1,447 @@
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use devicons::FileIcon;
+use sysinfo::{
+    Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, System, ThreadKind,
+    UpdateKind, Users, MINIMUM_CPU_UPDATE_INTERVAL,
+};
+
+use super::reload::Reloader;
+use super::OnAir;
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewType};
+use crate::startup::Startup;
+use television_fuzzy::matcher::{
+    config::Config, injector::Injector, Matcher, Utf32String,
+};
+use television_utils::format;
+use television_utils::indices::sep_name_and_value_indices;
+use television_utils::strings::preprocess_line;
+
+/// A running process.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Process {
+    pid: u32,
+    parent: Option<u32>,
+    user: String,
+    /// The command line, or the bracketed process name when it has none
+    /// (e.g. kernel threads), like `ps` does.
+    command: String,
+    executable: Option<String>,
+    working_dir: Option<String>,
+    status: String,
+    /// The CPU usage, in tenths of a percent.
+    cpu: u32,
+    /// The resident memory, in bytes.
+    memory: u64,
+    /// How long the process has been running, in seconds.
+    run_time: u64,
+    environment: Vec<String>,
+}
+
+impl Process {
+    fn name(&self) -> String {
+        format!("{} {}", self.pid, self.command)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{}, {}.{}% cpu, {}",
+            self.user,
+            self.cpu / 10,
+            self.cpu % 10,
+            format::size(self.memory)
+        )
+    }
+
+    /// The details of the process, previewed along with its open files.
+    fn details(&self, open_files: &[String]) -> String {
+        let mut details = format!("Command: {}\n", self.command);
+        let optional = |value: &Option<String>| {
+            value.clone().unwrap_or_else(|| "unknown".to_string())
+        };
+        let _ =
+            writeln!(details, "Executable: {}", optional(&self.executable));
+        let _ = writeln!(
+            details,
+            "Working directory: {}",
+            optional(&self.working_dir)
+        );
+        if let Some(parent) = self.parent {
+            let _ = writeln!(details, "Parent: {parent}");
+        }
+        let _ = writeln!(details, "User: {}", self.user);
+        let _ = writeln!(details, "Status: {}", self.status);
+        let _ = writeln!(details, "CPU: {}.{}%", self.cpu / 10, self.cpu % 10);
+        let _ = writeln!(details, "Memory: {}", format::size(self.memory));
+        let _ = writeln!(
+            details,
+            "Running for: {}",
+            format_run_time(self.run_time)
+        );
+        details.push_str("\nEnvironment:");
+        if self.environment.is_empty() {
+            details.push_str(" unknown");
+        }
+        for variable in &self.environment {
+            let _ = write!(details, "\n  {variable}");
+        }
+        details.push_str("\n\nOpen files:");
+        if open_files.is_empty() {
+            details.push_str(" unknown");
+        }
+        for file in open_files {
+            let _ = write!(details, "\n  {file}");
+        }
+        details
+    }
+}
+
+/// Format a run time like `ps` does, e.g. `2-03:04:05` for 2 days, 3 hours,
+/// 4 minutes and 5 seconds.
+fn format_run_time(seconds: u64) -> String {
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    );
+    if days > 0 {
+        format!("{days}-{time}")
+    } else {
+        time
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct Channel {
+    matcher: Matcher<Process>,
+    file_icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+    reloader: Reloader<Process>,
+    /// The open files of the last previewed process, which are listed on
+    /// every frame otherwise.
+    open_files: Mutex<Option<(u32, Vec<String>)>>,
+}
+
+const NUM_THREADS: usize = 1;
+const FILE_ICON_STR: &str = "exe";
+
+impl Channel {
+    pub fn new(ctx: &Context) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(ctx.matcher_threads("processes", NUM_THREADS)),
+        );
+        let load_handle = tokio::spawn(load_processes(
+            matcher.injector(),
+            ctx.startup.clone(),
+        ));
+        Channel {
+            matcher,
+            file_icon: FileIcon::from(FILE_ICON_STR),
+            load_handle,
+            reloader: Reloader::default(),
+            open_files: Mutex::default(),
+        }
+    }
+
+    fn open_files(&self, pid: u32) -> Vec<String> {
+        let mut cached = self.open_files.lock().unwrap();
+        match cached.as_ref() {
+            Some((cached_pid, files)) if *cached_pid == pid => files.clone(),
+            _ => {
+                let files = open_files(pid);
+                *cached = Some((pid, files.clone()));
+                files
+            }
+        }
+    }
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx)
+    }
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(processes) = self.reloader.take() {
+            self.matcher.update_items(processes, process_columns);
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                let name = item.inner.name();
+                let (
+                    name_indices,
+                    value_indices,
+                    should_add_name_indices,
+                    should_add_value_indices,
+                ) = sep_name_and_value_indices(
+                    &mut item.match_indices.iter().map(|i| i.0).collect(),
+                    u32::try_from(name.len()).unwrap(),
+                );
+
+                let mut entry = Entry::new(name, PreviewType::PlainText)
+                    .with_value(item.inner.description())
+                    .with_icon(self.file_icon);
+
+                if should_add_name_indices {
+                    entry = entry.with_name_match_ranges(
+                        name_indices.into_iter().map(|i| (i, i + 1)).collect(),
+                    );
+                }
+
+                if should_add_value_indices {
+                    entry = entry.with_value_match_ranges(
+                        value_indices
+                            .into_iter()
+                            .map(|i| (i, i + 1))
+                            .collect(),
+                    );
+                }
+
+                entry
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher.get_result(index).map(|item| {
+            let process = &item.inner;
+            Entry::new(process.name(), PreviewType::PlainText)
+                .with_value(process.details(&self.open_files(process.pid)))
+                .with_icon(self.file_icon)
+                .with_output(process.pid.to_string())
+        })
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        *self.open_files.lock().unwrap() = None;
+        self.reloader.start(processes);
+    }
+}
+
+async fn load_processes(injector: Injector<Process>, startup: Startup) {
+    startup.ready().await;
+    // measuring the CPU usage takes a while
+    let Ok(processes) = tokio::task::spawn_blocking(processes).await else {
+        return;
+    };
+    for process in processes {
+        let () = injector.push(process, process_columns);
+    }
+}
+
+fn process_columns(process: &Process, cols: &mut [Utf32String]) {
+    cols[0] = (process.name() + process.description().as_str()).into();
+}
+
+/// The running processes (but not their threads), the busiest first.
+fn processes() -> Vec<Process> {
+    let refresh_kind = ProcessRefreshKind::new()
+        .with_cpu()
+        .with_memory()
+        .with_user(UpdateKind::OnlyIfNotSet)
+        .with_cmd(UpdateKind::OnlyIfNotSet)
+        .with_exe(UpdateKind::OnlyIfNotSet)
+        .with_cwd(UpdateKind::OnlyIfNotSet)
+        .with_environ(UpdateKind::OnlyIfNotSet);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        refresh_kind,
+    );
+    // the CPU usage is measured between two refreshes
+    std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::new().with_cpu(),
+    );
+    let users = Users::new_with_refreshed_list();
+    let mut processes: Vec<Process> = system
+        .processes()
+        .values()
+        .filter(|process| process.thread_kind() != Some(ThreadKind::Userland))
+        .map(|process| {
+            let command = process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let command = if command.is_empty() {
+                format!("[{}]", process.name().to_string_lossy())
+            } else {
+                command
+            };
+            Process {
+                pid: process.pid().as_u32(),
+                parent: process.parent().map(Pid::as_u32),
+                user: process
+                    .user_id()
+                    .and_then(|uid| users.get_user_by_id(uid))
+                    .map_or_else(
+                        || "unknown".to_string(),
+                        |user| user.name().to_string(),
+                    ),
+                command: preprocess_line(&command),
+                executable: process
+                    .exe()
+                    .map(|path| path.to_string_lossy().into_owned()),
+                working_dir: process
+                    .cwd()
+                    .map(|path| path.to_string_lossy().into_owned()),
+                status: process.status().to_string(),
+                #[allow(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss
+                )]
+                cpu: (process.cpu_usage() * 10.0).round() as u32,
+                memory: process.memory(),
+                run_time: process.run_time(),
+                environment: process
+                    .environ()
+                    .iter()
+                    .map(|variable| {
+                        preprocess_line(&variable.to_string_lossy())
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    processes.sort_by(|a, b| b.cpu.cmp(&a.cpu).then(b.memory.cmp(&a.memory)));
+    processes
+}
+
+/// The files the given process has open, as `fd -> target` lines.
+#[cfg(target_os = "linux")]
+fn open_files(pid: u32) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{pid}/fd")) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(u32, String)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let fd = entry.file_name().to_string_lossy().parse().ok()?;
+            let target = std::fs::read_link(entry.path()).ok()?;
+            Some((fd, preprocess_line(&target.to_string_lossy())))
+        })
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .map(|(fd, target)| format!("{fd} -> {target}"))
+        .collect()
+}
+
+/// The open files of a process aren't listed on this platform.
+#[cfg(not(target_os = "linux"))]
+fn open_files(_pid: u32) -> Vec<String> {
+    Vec::new()
+}
+
+/// Ask the process with the given PID to terminate (`SIGTERM` on unix).
+pub fn terminate(pid: u32) -> Result<(), String> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::new(),
+    );
+    let process = system
+        .process(pid)
+        .ok_or_else(|| "no such process".to_string())?;
+    // signals other than kill aren't supported on every platform
+    match process.kill_with(Signal::Term) {
+        Some(true) => Ok(()),
+        Some(false) => Err("permission denied".to_string()),
+        None if process.kill() => Ok(()),
+        None => Err("permission denied".to_string()),
+    }
+}
+
+/// The PID of a process entry, named `<pid> <command>`.
+pub fn entry_pid(entry: &Entry) -> Option<u32> {
+    entry.name.split(' ').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_details() {
+        let process = Process {
+            pid: 4242,
+            parent: Some(1),
+            user: "alice".to_string(),
+            command: "nvim src/main.rs".to_string(),
+            executable: Some("/usr/bin/nvim".to_string()),
+            working_dir: None,
+            status: "Sleeping".to_string(),
+            cpu: 25,
+            memory: 3 * 1024 * 1024,
+            run_time: 7205,
+            environment: vec!["HOME=/home/alice".to_string()],
+        };
+        assert_eq!(process.name(), "4242 nvim src/main.rs");
+        assert_eq!(process.description(), "alice, 2.5% cpu, 3.0 MiB");
+        let details = process.details(&["0 -> /dev/pts/1".to_string()]);
+        assert!(details.starts_with("Command: nvim src/main.rs\n"));
+        assert!(details.contains("Working directory: unknown\n"));
+        assert!(details.contains("Running for: 02:00:05\n"));
+        assert_eq!(format_run_time(2 * 86400 + 3 * 3600 + 245), "2-03:04:05");
+        assert!(details.ends_with(
+            "Environment:\n  HOME=/home/alice\n\nOpen files:\n  0 -> /dev/pts/1"
+        ));
+        let entry = Entry::new(process.name(), PreviewType::PlainText);
+        assert_eq!(entry_pid(&entry), Some(4242));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_open_files() {
+        let file = std::fs::File::open("Cargo.toml").unwrap();
+        let files = open_files(std::process::id());
+        assert!(files.iter().any(|f| f.ends_with("/Cargo.toml")));
+        drop(file);
+    }
+}
@@ -0,0 +1,77 @@
+use tokio::sync::oneshot;
+
+/// Reloads the entries of a channel in the background (e.g. by re-running
+/// the command backing it).
+///
+/// The reloaded entries are meant to be polled with `take` whenever the
+/// channel's results are requested, and diffed into the channel's matcher
+/// (see `Matcher::update_items`) so that they replace the current entries
+/// all at once.
+pub(crate) struct Reloader<T> {
+    pending: Option<oneshot::Receiver<Vec<T>>>,
+}
+
+impl<T> Default for Reloader<T> {
+    fn default() -> Self {
+        Self { pending: None }
+    }
+}
+
+impl<T> Reloader<T>
+where
+    T: Send + 'static,
+{
+    /// Start reloading the entries with the given function, unless a reload
+    /// is already pending.
+    pub(crate) fn start<F>(&mut self, load: F)
+    where
+        F: FnOnce() -> Vec<T> + Send + 'static,
+    {
+        if self.pending.is_some() {
+            return;
+        }
+        let (tx, rx) = oneshot::channel();
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send(load());
+        });
+        self.pending = Some(rx);
+    }
+
+    /// The reloaded entries, once the pending reload has completed.
+    pub(crate) fn take(&mut self) -> Option<Vec<T>> {
+        let result = self.pending.as_mut()?.try_recv();
+        match result {
+            Ok(entries) => {
+                self.pending = None;
+                Some(entries)
+            }
+            Err(oneshot::error::TryRecvError::Empty) => None,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                self.pending = None;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reloader() {
+        let mut reloader = Reloader::default();
+        assert!(reloader.take().is_none());
+        reloader.start(|| vec![1, 2, 3]);
+        // a second reload is ignored while the first one is pending
+        reloader.start(|| vec![4]);
+        let entries = loop {
+            if let Some(entries) = reloader.take() {
+                break entries;
+            }
+            tokio::task::yield_now().await;
+        };
+        assert_eq!(entries, vec![1, 2, 3]);
+        assert!(reloader.take().is_none());
+    }
+}
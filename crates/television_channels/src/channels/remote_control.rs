@@ -1,4 +1,7 @@
-use crate::channels::{CliTvChannel, OnAir, TelevisionChannel, UnitChannel};
+use crate::channels::{
+    compiled_out_channels, CliTvChannel, OnAir, TelevisionChannel, UnitChannel,
+};
+use crate::context::{Context, FromContext};
 use crate::entry::{Entry, PreviewType};
 use clap::ValueEnum;
 use devicons::FileIcon;
@@ -6,37 +9,101 @@ use television_fuzzy::matcher::{config::Config, Matcher};
 
 pub struct RemoteControl {
     matcher: Matcher<String>,
+    icon: FileIcon,
+    /// The listed channels that were compiled out of this build, picking
+    /// them tells which cargo feature they require.
+    compiled_out: Vec<String>,
 }
 
 const NUM_THREADS: usize = 1;
 
 impl RemoteControl {
-    pub fn new(channels: Vec<UnitChannel>) -> Self {
+    pub fn new(ctx: &Context, channels: Vec<UnitChannel>) -> Self {
+        Self::with_entries(
+            channels
+                .iter()
+                .map(ToString::to_string)
+                .filter(|name| ctx.is_channel_available(name))
+                .collect(),
+            TV_ICON,
+        )
+    }
+
+    /// A remote control listing applications (e.g. to open a file with)
+    /// instead of channels.
+    pub fn with_applications(names: Vec<String>) -> Self {
+        Self::with_entries(names, APPLICATION_ICON)
+    }
+
+    /// A remote control listing file types (e.g. `rs (12)`) instead of
+    /// channels.
+    pub fn with_file_types(labels: Vec<String>) -> Self {
+        Self::with_entries(labels, FILE_TYPE_ICON)
+    }
+
+    fn with_entries(names: Vec<String>, icon: FileIcon) -> Self {
         let matcher = Matcher::new(Config::default().n_threads(NUM_THREADS));
         let injector = matcher.injector();
-        for channel in channels {
-            let () = injector.push(channel.to_string(), |e, cols| {
+        for name in names {
+            let () = injector.push(name, |e, cols| {
                 cols[0] = e.clone().into();
             });
         }
-        RemoteControl { matcher }
+        RemoteControl {
+            matcher,
+            icon,
+            compiled_out: Vec::new(),
+        }
+    }
+
+    /// The icon of the entry with the given name.
+    fn icon_of(&self, name: &str) -> FileIcon {
+        if self.compiled_out.iter().any(|n| n == name) {
+            COMPILED_OUT_ICON
+        } else {
+            self.icon
+        }
     }
 
     pub fn with_transitions_from(
+        ctx: &Context,
         television_channel: &TelevisionChannel,
     ) -> Self {
-        Self::new(television_channel.available_transitions())
+        Self::new(ctx, television_channel.available_transitions())
     }
 }
 
-impl Default for RemoteControl {
-    fn default() -> Self {
-        Self::new(
-            CliTvChannel::value_variants()
-                .iter()
-                .map(|v| v.to_string().as_str().into())
-                .collect(),
-        )
+impl FromContext for RemoteControl {
+    /// A remote control listing the available built-in channels, the cable
+    /// channels defined in the config file, the channels and actions of the
+    /// scripts and the channels of the plugins, along with the built-in
+    /// channels compiled out of this build.
+    fn from_context(ctx: &Context) -> Self {
+        #[cfg_attr(
+            not(any(feature = "scripting", feature = "plugins")),
+            allow(unused_mut)
+        )]
+        let mut names: Vec<String> = CliTvChannel::value_variants()
+            .iter()
+            .map(ToString::to_string)
+            .chain(ctx.cable_channel_names())
+            .collect();
+        #[cfg(feature = "scripting")]
+        names.extend(ctx.script_channel_names());
+        #[cfg(feature = "plugins")]
+        names.extend(ctx.plugin_channel_names());
+        names.retain(|name| ctx.is_channel_available(name));
+        #[cfg(feature = "scripting")]
+        names.extend(ctx.script_actions());
+        let compiled_out: Vec<String> = compiled_out_channels()
+            .filter(|name| ctx.is_channel_available(name))
+            .map(ToString::to_string)
+            .collect();
+        names.extend(compiled_out.iter().cloned());
+        Self {
+            compiled_out,
+            ..Self::with_entries(names, TV_ICON)
+        }
     }
 }
 
@@ -45,6 +112,21 @@ const TV_ICON: FileIcon = FileIcon {
     color: "#000000",
 };
 
+const APPLICATION_ICON: FileIcon = FileIcon {
+    icon: '🚀',
+    color: "#000000",
+};
+
+const FILE_TYPE_ICON: FileIcon = FileIcon {
+    icon: '📄',
+    color: "#000000",
+};
+
+const COMPILED_OUT_ICON: FileIcon = FileIcon {
+    icon: '🚫',
+    color: "#000000",
+};
+
 impl OnAir for RemoteControl {
     fn find(&mut self, pattern: &str) {
         self.matcher.find(pattern);
@@ -59,7 +141,7 @@ impl OnAir for RemoteControl {
                 let path = item.matched_string;
                 Entry::new(path.clone(), PreviewType::Basic)
                     .with_name_match_ranges(item.match_indices)
-                    .with_icon(TV_ICON)
+                    .with_icon(self.icon_of(&path))
             })
             .collect()
     }
@@ -67,7 +149,8 @@ impl OnAir for RemoteControl {
     fn get_result(&self, index: u32) -> Option<Entry> {
         self.matcher.get_result(index).map(|item| {
             let path = item.matched_string;
-            Entry::new(path.clone(), PreviewType::Basic).with_icon(TV_ICON)
+            Entry::new(path.clone(), PreviewType::Basic)
+                .with_icon(self.icon_of(&path))
         })
     }
 
@@ -79,6 +162,10 @@ impl OnAir for RemoteControl {
         self.matcher.total_item_count
     }
 
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
     fn running(&self) -> bool {
         self.matcher.status.running
     }
@@ -0,0 +1,469 @@
+//! Channels and actions written in [Rhai](https://rhai.rs), loaded from the
+//! `scripts` directory of the config directory.
+//!
+//! A script defining an `entries` function is a channel named after its
+//! file, listing the strings it returns. It may preview them with a
+//! `preview` function returning some text (colored by ANSI escape sequences
+//! or not):
+//! ```text
+//! // stashes.rhai
+//! fn entries() { run("git stash list").split("\n") }
+//! fn preview(entry) { run(`git stash show -p ${entry.split(":")[0]}`) }
+//! fn drop(entry) { run(`git stash drop ${entry.split(":")[0]}`); }
+//! ```
+//! Its other public functions taking one argument are actions, listed in
+//! the remote control (e.g. `stashes: drop`) and run on the entry selected
+//! in the current channel. An action returning a string has it run as a
+//! shell command in the foreground (e.g. to open an editor).
+//!
+//! Besides Rhai's standard library, scripts can call `run(command)` (the
+//! standard output of a shell command), `env(name)` and `read_file(path)`.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use color_eyre::eyre::{eyre, Result};
+use devicons::FileIcon;
+use rhai::{
+    Array, Dynamic, Engine, EvalAltResult, FnAccess, FuncArgs, Scope, AST,
+};
+use tracing::{debug, warn};
+
+use super::reload::Reloader;
+use super::OnAir;
+use crate::bus::{Bus, Message};
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, PreviewType};
+use television_fuzzy::matcher::{config::Config, Matcher};
+use television_utils::shell::shell_command;
+use television_utils::strings::preprocess_line;
+
+/// The extension of the script files.
+const SCRIPT_EXTENSION: &str = "rhai";
+
+/// The functions of a script that aren't actions.
+const ENTRIES_FN: &str = "entries";
+const PREVIEW_FN: &str = "preview";
+
+/// The key of the notifications about the scripts.
+const SCRIPT_TOAST: &str = "script";
+
+/// A script, named after its file.
+#[derive(Debug, Clone)]
+pub struct Script {
+    pub name: String,
+    ast: Arc<AST>,
+}
+
+impl Script {
+    /// Compile the script at the given path.
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| eyre!("{} isn't a file", path.display()))?;
+        let ast = engine()
+            .compile_file(path.to_path_buf())
+            .map_err(|e| eyre!("{}: {e}", path.display()))?;
+        Ok(Self {
+            name,
+            ast: Arc::new(ast),
+        })
+    }
+
+    /// Compile the given source code, e.g. in tests.
+    pub fn compile(name: &str, source: &str) -> Result<Self> {
+        let ast =
+            engine().compile(source).map_err(|e| eyre!("{name}: {e}"))?;
+        Ok(Self {
+            name: name.to_string(),
+            ast: Arc::new(ast),
+        })
+    }
+
+    /// Whether the script is a channel, i.e. lists entries.
+    pub fn is_channel(&self) -> bool {
+        self.has_function(ENTRIES_FN, 0)
+    }
+
+    /// Whether the script previews the entries it lists.
+    pub fn has_preview(&self) -> bool {
+        self.has_function(PREVIEW_FN, 1)
+    }
+
+    /// The names of the actions defined by the script, sorted.
+    pub fn actions(&self) -> Vec<String> {
+        let mut actions: Vec<String> = self
+            .ast
+            .iter_functions()
+            .filter(|f| {
+                f.access == FnAccess::Public
+                    && f.params.len() == 1
+                    && f.name != PREVIEW_FN
+            })
+            .map(|f| f.name.to_string())
+            .collect();
+        actions.sort();
+        actions
+    }
+
+    /// The entries listed by the script, skipping the blank ones.
+    pub fn entries(&self) -> Result<Vec<String>> {
+        let entries: Array =
+            self.call(ENTRIES_FN, ())?.into_array().map_err(|t| {
+                eyre!(
+                    "{}::{ENTRIES_FN} returned a {t}, not an array",
+                    self.name
+                )
+            })?;
+        Ok(entries
+            .iter()
+            .map(ToString::to_string)
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| preprocess_line(&entry))
+            .collect())
+    }
+
+    /// The preview of the given entry.
+    pub fn preview(&self, entry: &str) -> Result<String> {
+        Ok(self.call(PREVIEW_FN, (entry.to_string(),))?.to_string())
+    }
+
+    /// Run the given action on the given entry, returning the shell command
+    /// to run in the foreground, if any.
+    pub fn run_action(
+        &self,
+        action: &str,
+        entry: &str,
+    ) -> Result<Option<String>> {
+        Ok(self.call(action, (entry.to_string(),))?.into_string().ok())
+    }
+
+    fn has_function(&self, name: &str, arity: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == arity)
+    }
+
+    fn call(&self, name: &str, args: impl FuncArgs) -> Result<Dynamic> {
+        debug!("Calling {}::{}", self.name, name);
+        engine()
+            .call_fn(&mut Scope::new(), &self.ast, name, args)
+            .map_err(|e| eyre!("{}::{name}: {e}", self.name))
+    }
+}
+
+/// The engine running the scripts, with the functions they can call.
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .register_fn("run", run)
+        .register_fn("env", |name: &str| {
+            std::env::var(name).unwrap_or_default()
+        })
+        .register_fn("read_file", |path: &str| {
+            std::fs::read_to_string(path).map_err(|e| -> Box<EvalAltResult> {
+                format!("{path}: {e}").into()
+            })
+        })
+        .on_print(|text| debug!("script: {text}"));
+    engine
+}
+
+/// The standard output of the given shell command.
+fn run(command: &str) -> Result<String, Box<EvalAltResult>> {
+    debug!("Running script command {:?}", command);
+    let output = shell_command(command).output().map_err(
+        |e| -> Box<EvalAltResult> { format!("`{command}`: {e}").into() },
+    )?;
+    if !output.status.success() {
+        warn!("Command {:?} exited with {}", command, output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Compile the scripts of the given directory, in the order of their names.
+pub fn load_scripts(dir: &Path) -> Vec<Result<Script>> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = read_dir
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+    paths.iter().map(|path| Script::load(path)).collect()
+}
+
+impl Context {
+    /// Register the scripts found in the config directory, so that their
+    /// channels and actions can be picked from the remote control.
+    pub fn set_scripts(&mut self, scripts: Vec<Script>) {
+        self.scripts = scripts;
+    }
+
+    /// The registered script with the given (case insensitive) name.
+    pub fn script(&self, name: &str) -> Option<Script> {
+        self.scripts
+            .iter()
+            .find(|script| script.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    /// The names of the registered scripts that are channels.
+    pub fn script_channel_names(&self) -> Vec<String> {
+        self.scripts
+            .iter()
+            .filter(|script| script.is_channel())
+            .map(|script| script.name.clone())
+            .collect()
+    }
+
+    /// The actions of the registered scripts, as listed in the remote
+    /// control (e.g. `stashes: drop`).
+    pub fn script_actions(&self) -> Vec<String> {
+        self.scripts
+            .iter()
+            .flat_map(|script| {
+                script
+                    .actions()
+                    .into_iter()
+                    .map(|action| format!("{}: {action}", script.name))
+            })
+            .collect()
+    }
+
+    /// The script and the name of the action listed as given in the remote
+    /// control.
+    pub fn script_action(&self, label: &str) -> Option<(Script, String)> {
+        let (name, action) = label.split_once(": ")?;
+        let script = self.script(name)?;
+        script
+            .actions()
+            .contains(&action.to_string())
+            .then(|| (script, action.to_string()))
+    }
+}
+
+/// Log the error of a script and notify the user about it through the given
+/// bus.
+pub fn report_error(bus: &Bus, error: &color_eyre::Report) {
+    warn!("{error}");
+    bus.send(Message::Finished {
+        key: SCRIPT_TOAST.to_string(),
+        text: error.to_string(),
+        failed: true,
+    });
+}
+
+pub struct Channel {
+    script: Script,
+    bus: Bus,
+    matcher: Matcher<String>,
+    icon: FileIcon,
+    load_handle: tokio::task::JoinHandle<()>,
+    reloader: Reloader<String>,
+}
+
+const NUM_THREADS: usize = 1;
+
+impl Channel {
+    pub fn new(ctx: &Context, script: Script) -> Self {
+        let matcher = Matcher::new(Config::default().n_threads(
+            ctx.matcher_threads(&script.name.to_lowercase(), NUM_THREADS),
+        ));
+        let injector = matcher.injector();
+        let (loaded, bus) = (script.clone(), ctx.bus.clone());
+        let load_handle = tokio::task::spawn_blocking(move || {
+            for entry in entries(&loaded, &bus) {
+                let () = injector.push(entry, |e, cols| {
+                    cols[0] = e.clone().into();
+                });
+            }
+        });
+        Channel {
+            script,
+            bus: ctx.bus.clone(),
+            matcher,
+            icon: FileIcon::from(SCRIPT_EXTENSION),
+            load_handle,
+            reloader: Reloader::default(),
+        }
+    }
+
+    /// The name of the channel, i.e. of its script.
+    pub fn name(&self) -> &str {
+        &self.script.name
+    }
+
+    fn entry(&self, line: &str) -> Entry {
+        let preview = if self.script.has_preview() {
+            PreviewType::Script(self.script.name.clone())
+        } else {
+            PreviewType::Basic
+        };
+        Entry::new(line.to_string(), preview).with_icon(self.icon)
+    }
+}
+
+/// An empty channel, script channels being created from their script.
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(
+            ctx,
+            Script {
+                name: "script".to_string(),
+                ast: Arc::new(AST::empty()),
+            },
+        )
+    }
+}
+
+/// The entries listed by the given script, none if it failed.
+fn entries(script: &Script, bus: &Bus) -> Vec<String> {
+    if !script.is_channel() {
+        return Vec::new();
+    }
+    script.entries().unwrap_or_else(|e| {
+        report_error(bus, &e);
+        Vec::new()
+    })
+}
+
+impl OnAir for Channel {
+    fn find(&mut self, pattern: &str) {
+        self.matcher.find(pattern);
+    }
+
+    fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
+        if let Some(lines) = self.reloader.take() {
+            self.matcher.update_items(lines, |line, cols| {
+                cols[0] = line.clone().into();
+            });
+        }
+        self.matcher.tick();
+        self.matcher
+            .results(num_entries, offset)
+            .into_iter()
+            .map(|item| {
+                self.entry(&item.inner)
+                    .with_name_match_ranges(item.match_indices)
+            })
+            .collect()
+    }
+
+    fn get_result(&self, index: u32) -> Option<Entry> {
+        self.matcher
+            .get_result(index)
+            .map(|item| self.entry(&item.inner))
+    }
+
+    fn result_count(&self) -> u32 {
+        self.matcher.matched_item_count
+    }
+
+    fn total_count(&self) -> u32 {
+        self.matcher.total_item_count
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
+    fn running(&self) -> bool {
+        self.matcher.status.running
+    }
+
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.load_handle.abort();
+    }
+
+    fn refresh(&mut self) {
+        let (script, bus) = (self.script.clone(), self.bus.clone());
+        self.reloader.start(move || entries(&script, &bus));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STASHES: &str = r#"
+        fn entries() { ["stash@{0}: wip", "", "stash@{1}: fix"] }
+        fn preview(entry) { `diff of ${entry}` }
+        fn drop(entry) { `git stash drop ${entry.split(":")[0]}` }
+        fn count(entry) { entry.len() }
+        private fn helper(entry) { entry }
+    "#;
+
+    #[test]
+    fn test_script() {
+        let script = Script::compile("stashes", STASHES).unwrap();
+        assert!(script.is_channel());
+        assert!(script.has_preview());
+        assert_eq!(script.actions(), vec!["count", "drop"]);
+        assert_eq!(
+            script.entries().unwrap(),
+            vec!["stash@{0}: wip", "stash@{1}: fix"]
+        );
+        assert_eq!(script.preview("a").unwrap(), "diff of a");
+        assert_eq!(
+            script.run_action("drop", "stash@{1}: fix").unwrap(),
+            Some("git stash drop stash@{1}".to_string())
+        );
+        // only strings are run
+        assert_eq!(script.run_action("count", "abc").unwrap(), None);
+        assert!(script.run_action("nope", "abc").is_err());
+        assert!(Script::compile("broken", "fn entries( {").is_err());
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut ctx = Context::default();
+        ctx.set_scripts(vec![
+            Script::compile("stashes", STASHES).unwrap(),
+            Script::compile("tools", "fn open(entry) { entry }").unwrap(),
+        ]);
+        assert_eq!(ctx.script_channel_names(), vec!["stashes"]);
+        assert_eq!(
+            ctx.script_actions(),
+            vec!["stashes: count", "stashes: drop", "tools: open"]
+        );
+        let (script, action) = ctx.script_action("tools: open").unwrap();
+        assert_eq!((script.name.as_str(), action.as_str()), ("tools", "open"));
+        assert!(ctx.script_action("tools: helper").is_none());
+        assert!(ctx.script_action("stashes").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        let script = Script::compile(
+            "env",
+            r#"fn entries() { run("echo a; echo b").split("\n") }"#,
+        )
+        .unwrap();
+        assert_eq!(script.entries().unwrap(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_load_scripts() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        };
+        write("b.rhai", "fn entries() { [] }");
+        write("a.rhai", "fn entries( {");
+        write("notes.txt", "");
+        let scripts = load_scripts(dir.path());
+        assert_eq!(scripts.len(), 2);
+        assert!(scripts[0].is_err());
+        assert_eq!(scripts[1].as_ref().unwrap().name, "b");
+    }
+}
@@ -1,43 +1,194 @@
+use std::borrow::Cow;
 use std::io::BufRead;
+use std::ops::Range;
 use std::path::Path;
+use std::thread::JoinHandle;
 
 use devicons::FileIcon;
 
 use super::OnAir;
-use crate::entry::{Entry, PreviewType};
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, EntryRef, PreviewType};
+use crate::threads::large_channel_default;
 use television_fuzzy::matcher::{config::Config, Matcher};
-use television_utils::strings::preprocess_line;
+use television_utils::ansi::{has_escapes, preprocess_ansi_line, AnsiStyle};
+use television_utils::cancel::CancellationToken;
+use television_utils::fields::{Delimiter, Fields, Picked};
+use television_utils::strings::{
+    replace_non_printable, truncate_line, TAB_WIDTH,
+};
 
-pub struct Channel {
-    matcher: Matcher<String>,
-    icon: FileIcon,
+/// How the lines read from stdin are split into fields (e.g. the columns of
+/// `ps aux` or of a CSV file), and which ones are displayed, matched and
+/// output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldOptions {
+    pub delimiter: Delimiter,
+    /// The fields displayed, all of them if `None`.
+    pub with_nth: Option<Fields>,
+    /// The displayed fields matched against, all of them if `None`.
+    pub nth: Option<Fields>,
+    /// The fields output when a line is selected, instead of the whole
+    /// line.
+    pub accept_nth: Option<Fields>,
 }
 
-const NUM_THREADS: usize = 2;
+/// A line read from stdin, along with the styles of its parts if it was
+/// colored (e.g. `rg --color=always | tv`).
+#[derive(Clone)]
+struct StdinLine {
+    /// The displayed text.
+    text: String,
+    styles: Option<Vec<(Range<usize>, AnsiStyle)>>,
+    /// The displayed fields matched against, if not the whole text.
+    matched: Option<Picked>,
+    /// What's output when the line is selected, if not the text.
+    output: Option<String>,
+}
 
-impl Channel {
-    pub fn new() -> Self {
-        let mut lines = Vec::new();
-        for line in std::io::stdin().lock().lines().map_while(Result::ok) {
-            lines.push(preprocess_line(&line));
+impl StdinLine {
+    fn new(line: &str, options: &FieldOptions) -> Self {
+        let (line, styles) = if has_escapes(line) {
+            let line = preprocess_ansi_line(line);
+            (line.text, Some(line.styles))
+        } else {
+            // the fields are picked before the tabs are replaced
+            (truncate_line(line).to_string(), None)
+        };
+        if *options == FieldOptions::default() {
+            let text = if styles.is_some() {
+                line.clone()
+            } else {
+                replace_non_printable(line.as_bytes(), TAB_WIDTH)
+            };
+            // e.g. the tabs, or the newlines of the paths read with
+            // `--read0`, are output as they were read
+            let output = (text != line).then_some(line);
+            return Self {
+                text,
+                styles,
+                matched: None,
+                output,
+            };
         }
-        let matcher = Matcher::new(Config::default().n_threads(NUM_THREADS));
-        let injector = matcher.injector();
-        for line in &lines {
-            let () = injector.push(line.clone(), |e, cols| {
-                cols[0] = e.clone().into();
-            });
+        let fields = options.delimiter.split(&line);
+        let displayed = match &options.with_nth {
+            Some(with_nth) => Picked::new(
+                &line,
+                &fields,
+                &with_nth.indices(fields.len()),
+                &options.delimiter,
+            ),
+            None => Picked::all(&line, &fields),
+        };
+        let matched = options.nth.as_ref().map(|nth| {
+            Picked::new(
+                &displayed.text,
+                &displayed.fields,
+                &nth.indices(displayed.fields.len()),
+                &options.delimiter,
+            )
+        });
+        let output = match &options.accept_nth {
+            Some(accept_nth) => Some(
+                Picked::new(
+                    &line,
+                    &fields,
+                    &accept_nth.indices(fields.len()),
+                    &options.delimiter,
+                )
+                .text,
+            ),
+            None => (displayed.text != line).then_some(line),
+        };
+        Self {
+            styles: styles.map(|styles| displayed.map_ranges(&styles)),
+            text: displayed.text,
+            matched,
+            output,
         }
+    }
+
+    /// The text matched against.
+    fn matched_text(&self) -> &str {
+        self.matched
+            .as_ref()
+            .map_or(&self.text, |matched| &matched.text)
+    }
+
+    /// The matched characters of the text, given those of the text matched
+    /// against.
+    fn match_ranges<'a>(
+        &self,
+        match_indices: &'a [(u32, u32)],
+    ) -> Cow<'a, [(u32, u32)]> {
+        match &self.matched {
+            Some(matched) => {
+                Cow::Owned(matched.line_char_ranges(&self.text, match_indices))
+            }
+            None => Cow::Borrowed(match_indices),
+        }
+    }
+
+    fn entry(&self, preview_type: PreviewType) -> Entry {
+        let entry = Entry::new(self.text.clone(), preview_type);
+        match &self.output {
+            Some(output) => entry.with_output(output.clone()),
+            None => entry,
+        }
+    }
+}
+
+pub struct Channel {
+    matcher: Matcher<StdinLine>,
+    icon: FileIcon,
+    load_handle: JoinHandle<()>,
+    token: CancellationToken,
+}
+
+impl Channel {
+    /// Read the lines of stdin, ended by the given separator (`b'\n'`, or
+    /// `b'\0'` for the output of e.g. `find -print0`).
+    pub fn new(ctx: &Context, options: &FieldOptions, separator: u8) -> Self {
+        let matcher = Matcher::new(
+            Config::default()
+                .n_threads(
+                    ctx.matcher_threads("stdin", large_channel_default()),
+                )
+                .chunking(ctx.chunking("stdin")),
+        );
+        // the lines are matched as they are read, e.g. from a slow `find /`,
+        // the UI starting meanwhile
+        let mut injector = matcher.injector().chunked();
+        let token = CancellationToken::new();
+        let cancelled = token.clone();
+        let options = options.clone();
+        let load_handle = std::thread::spawn(move || {
+            let stdin = std::io::stdin().lock();
+            // the lines that aren't valid UTF-8 are kept, like the others
+            for line in stdin.split(separator).map_while(Result::ok) {
+                if cancelled.is_cancelled() {
+                    return;
+                }
+                let line =
+                    StdinLine::new(&String::from_utf8_lossy(&line), &options);
+                injector.push(line, |e, cols| {
+                    cols[0] = e.matched_text().into();
+                });
+            }
+        });
         Self {
             matcher,
             icon: FileIcon::from("nu"),
+            load_handle,
+            token,
         }
     }
 }
 
-impl Default for Channel {
-    fn default() -> Self {
-        Self::new()
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx, &FieldOptions::default(), b'\n')
     }
 }
 
@@ -52,33 +203,67 @@ impl OnAir for Channel {
             .results(num_entries, offset)
             .into_iter()
             .map(|item| {
-                let path = Path::new(&item.matched_string);
+                let line = &item.inner;
+                let path = Path::new(&line.text);
                 let icon = if path.try_exists().unwrap_or(false) {
                     FileIcon::from(path)
                 } else {
                     self.icon
                 };
-                Entry::new(item.matched_string, PreviewType::Basic)
-                    .with_name_match_ranges(item.match_indices)
-                    .with_icon(icon)
+                let mut entry = line
+                    .entry(PreviewType::Basic)
+                    .with_name_match_ranges(
+                        line.match_ranges(&item.match_indices).into_owned(),
+                    )
+                    .with_icon(icon);
+                if let Some(styles) = &line.styles {
+                    entry = entry.with_name_styles(styles.clone());
+                }
+                entry
             })
             .collect()
     }
 
+    fn for_each_result(
+        &mut self,
+        range: Range<u32>,
+        f: &mut dyn FnMut(&EntryRef<'_>),
+    ) {
+        self.matcher.tick();
+        let default_icon = self.icon;
+        self.matcher.for_each_result(range, |item| {
+            let line = item.inner;
+            let path = Path::new(&line.text);
+            let icon = if path.try_exists().unwrap_or(false) {
+                FileIcon::from(path)
+            } else {
+                default_icon
+            };
+            let match_ranges = line.match_ranges(item.match_indices);
+            let mut entry = EntryRef::new(&line.text)
+                .with_name_match_ranges(&match_ranges)
+                .with_icon(icon);
+            if let Some(styles) = &line.styles {
+                entry = entry.with_name_styles(styles);
+            }
+            f(&entry);
+        });
+    }
+
     fn get_result(&self, index: u32) -> Option<Entry> {
         self.matcher.get_result(index).map(|item| {
-            let path = Path::new(&item.matched_string);
+            let line = &item.inner;
+            let path = Path::new(&line.text);
             // if we recognize a file path, use a file icon
             // and set the preview type to "Files"
             if path.is_file() {
-                Entry::new(item.matched_string.clone(), PreviewType::Files)
+                line.entry(PreviewType::Files)
                     .with_icon(FileIcon::from(path))
             } else if path.is_dir() {
-                Entry::new(item.matched_string.clone(), PreviewType::Directory)
+                line.entry(PreviewType::Directory)
                     .with_icon(FileIcon::from(path))
             } else {
-                Entry::new(item.matched_string.clone(), PreviewType::Basic)
-                    .with_icon(self.icon)
+                line.entry(PreviewType::Basic).with_icon(self.icon)
             }
         })
     }
@@ -91,9 +276,65 @@ impl OnAir for Channel {
         self.matcher.total_item_count
     }
 
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
     fn running(&self) -> bool {
         self.matcher.status.running
     }
 
-    fn shutdown(&self) {}
+    fn loading(&self) -> bool {
+        !self.load_handle.is_finished()
+    }
+
+    fn shutdown(&self) {
+        self.token.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stdin_line_fields() {
+        let options = FieldOptions {
+            delimiter: Delimiter::Whitespace,
+            with_nth: "1,3..".parse().ok(),
+            nth: "2..".parse().ok(),
+            accept_nth: None,
+        };
+        let line = StdinLine::new("alex  42  vim été.rs\n", &options);
+        assert_eq!(line.text, "alex vim été.rs");
+        assert_eq!(line.matched_text(), "vim été.rs");
+        assert_eq!(line.output.as_deref(), Some("alex  42  vim été.rs"));
+        // the `t` of `été.rs`
+        assert_eq!(line.match_ranges(&[(5, 6)]).as_ref(), &[(10, 11)]);
+
+        let options = FieldOptions {
+            delimiter: Delimiter::new(","),
+            accept_nth: "-1".parse().ok(),
+            ..FieldOptions::default()
+        };
+        let line = StdinLine::new("\x1b[31ma\x1b[0m,b,c", &options);
+        assert_eq!(line.text, "a,b,c");
+        assert_eq!(line.styles.as_ref().unwrap()[0].0, 0..1);
+        assert_eq!(line.match_ranges(&[(0, 1)]).as_ref(), &[(0, 1)]);
+        assert_eq!(line.output.as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn test_stdin_line_output() {
+        let options = FieldOptions::default();
+        let line = StdinLine::new("a b.rs", &options);
+        assert_eq!(line.output, None);
+        // e.g. a path read with `--read0`
+        let line = StdinLine::new("a\nb\t.rs", &options);
+        assert_eq!(line.text, "ab    .rs");
+        assert_eq!(line.output.as_deref(), Some("a\nb\t.rs"));
+        let line = StdinLine::new("\x1b[1ma\tb\x1b[0m", &options);
+        assert_eq!(line.text, "a    b");
+        assert_eq!(line.output, None);
+    }
 }
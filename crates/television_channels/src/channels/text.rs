@@ -1,17 +1,26 @@
 use super::{OnAir, TelevisionChannel};
-use crate::entry::{Entry, PreviewType};
+use crate::context::{Context, FromContext};
+use crate::entry::{Entry, EntryRef, PreviewType};
+use crate::filters::MetadataFilters;
+use crate::hidden::HiddenEntries;
+use crate::startup::Startup;
+use crate::threads::large_channel_default;
 use devicons::FileIcon;
 use ignore::WalkState;
 use std::{
     fs::File,
     io::{BufRead, Read, Seek},
+    ops::Range,
     path::{Path, PathBuf},
     sync::{atomic::AtomicUsize, Arc},
 };
 use television_fuzzy::matcher::{config::Config, injector::Injector, Matcher};
+use television_utils::cancel::CancellationToken;
 use television_utils::files::{
-    is_not_text, walk_builder, DEFAULT_NUM_THREADS,
+    add_git_excludes_file, is_not_text, walk_builder, WalkWarnings,
+    DEFAULT_NUM_THREADS,
 };
+use television_utils::project::relative_path;
 use television_utils::strings::{
     preprocess_line, proportion_of_printable_ascii_characters,
     PRINTABLE_ASCII_THRESHOLD,
@@ -38,35 +47,52 @@ impl CandidateLine {
 #[allow(clippy::module_name_repetitions)]
 pub struct Channel {
     matcher: Matcher<CandidateLine>,
+    filters: MetadataFilters<CandidateLine>,
+    walk_warnings: Arc<WalkWarnings>,
     crawl_handle: tokio::task::JoinHandle<()>,
+    /// Stops the walk, which aborting the crawl task doesn't.
+    token: CancellationToken,
 }
 
 impl Channel {
-    pub fn new(directories: Vec<PathBuf>) -> Self {
-        let matcher = Matcher::new(Config::default());
+    pub fn new(ctx: &Context, directories: Vec<PathBuf>) -> Self {
+        let matcher = Matcher::new(matcher_config(ctx));
+        let walk_warnings = Arc::default();
+        let token = CancellationToken::new();
         // start loading files in the background
         let crawl_handle = tokio::spawn(crawl_for_candidates(
             directories,
             matcher.injector(),
+            Arc::clone(&walk_warnings),
+            token.clone(),
+            ctx.startup.clone(),
         ));
         Channel {
             matcher,
+            filters: metadata_filters(),
+            walk_warnings,
             crawl_handle,
+            token,
         }
     }
 
-    fn from_file_paths(file_paths: Vec<PathBuf>) -> Self {
-        let matcher = Matcher::new(Config::default());
+    fn from_file_paths(ctx: &Context, file_paths: Vec<PathBuf>) -> Self {
+        let matcher = Matcher::new(matcher_config(ctx));
         let injector = matcher.injector();
         let current_dir = std::env::current_dir().unwrap();
+        let walk_warnings = Arc::<WalkWarnings>::default();
+        let warnings = Arc::clone(&walk_warnings);
+        let token = CancellationToken::new();
+        let cancelled = token.clone();
         let crawl_handle = tokio::spawn(async move {
             let mut lines_in_mem = 0;
             for path in file_paths {
-                if lines_in_mem > MAX_LINES_IN_MEM {
+                if lines_in_mem > MAX_LINES_IN_MEM || cancelled.is_cancelled()
+                {
                     break;
                 }
                 if let Some(injected_lines) =
-                    try_inject_lines(&injector, &current_dir, &path)
+                    try_inject_lines(&injector, &current_dir, &path, &warnings)
                 {
                     lines_in_mem += injected_lines;
                 }
@@ -75,12 +101,15 @@ impl Channel {
 
         Channel {
             matcher,
+            filters: metadata_filters(),
+            walk_warnings,
             crawl_handle,
+            token,
         }
     }
 
-    fn from_text_entries(entries: Vec<Entry>) -> Self {
-        let matcher = Matcher::new(Config::default());
+    fn from_text_entries(ctx: &Context, entries: Vec<Entry>) -> Self {
+        let matcher = Matcher::new(matcher_config(ctx));
         let injector = matcher.injector();
         let load_handle = tokio::spawn(async move {
             for entry in entries.into_iter().take(MAX_LINES_IN_MEM) {
@@ -99,14 +128,22 @@ impl Channel {
 
         Channel {
             matcher,
+            filters: metadata_filters(),
+            walk_warnings: Arc::default(),
             crawl_handle: load_handle,
+            token: CancellationToken::new(),
         }
     }
 }
 
-impl Default for Channel {
-    fn default() -> Self {
-        Self::new(vec![std::env::current_dir().unwrap()])
+fn matcher_config(ctx: &Context) -> Config {
+    Config::default()
+        .n_threads(ctx.matcher_threads("text", large_channel_default()))
+}
+
+impl FromContext for Channel {
+    fn from_context(ctx: &Context) -> Self {
+        Self::new(ctx, vec![ctx.search_root()])
     }
 }
 
@@ -118,8 +155,10 @@ impl Default for Channel {
 /// `MAX_LINES_IN_MEM / 100` (assuming 100 lines per file on average).
 const MAX_PIPED_FILES: usize = MAX_LINES_IN_MEM / 200;
 
-impl From<&mut TelevisionChannel> for Channel {
-    fn from(value: &mut TelevisionChannel) -> Self {
+impl Channel {
+    /// Search the text of the results of the given channel (e.g. of the
+    /// files it lists), when transitioning from it.
+    pub fn from_channel(ctx: &Context, value: &mut TelevisionChannel) -> Self {
         match value {
             c @ TelevisionChannel::Files(_) => {
                 let entries = c.results(
@@ -129,6 +168,7 @@ impl From<&mut TelevisionChannel> for Channel {
                     0,
                 );
                 Self::from_file_paths(
+                    ctx,
                     entries
                         .iter()
                         .flat_map(|entry| {
@@ -140,6 +180,7 @@ impl From<&mut TelevisionChannel> for Channel {
             c @ TelevisionChannel::GitRepos(_) => {
                 let entries = c.results(c.result_count(), 0);
                 Self::new(
+                    ctx,
                     entries
                         .iter()
                         .flat_map(|entry| {
@@ -150,16 +191,28 @@ impl From<&mut TelevisionChannel> for Channel {
             }
             c @ TelevisionChannel::Text(_) => {
                 let entries = c.results(c.result_count(), 0);
-                Self::from_text_entries(entries)
+                Self::from_text_entries(ctx, entries)
             }
             _ => unreachable!(),
         }
     }
 }
 
+/// The metadata filters supported by the text channel: `ext`, the extension
+/// of the file the line belongs to.
+fn metadata_filters() -> MetadataFilters<CandidateLine> {
+    MetadataFilters::new().text("ext", |line: &CandidateLine| {
+        line.path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+    })
+}
+
 impl OnAir for Channel {
     fn find(&mut self, pattern: &str) {
-        self.matcher.find(pattern);
+        let (pattern, filter) = self.filters.parse(pattern);
+        self.matcher.set_filter(filter);
+        self.matcher.find(&pattern);
     }
 
     fn results(&mut self, num_entries: u32, offset: u32) -> Vec<Entry> {
@@ -172,7 +225,8 @@ impl OnAir for Channel {
                 let display_path =
                     item.inner.path.to_string_lossy().to_string();
                 Entry::new(
-                    display_path.clone() + &item.inner.line_number.to_string(),
+                    display_path.clone()
+                        + item.inner.line_number.to_string().as_str(),
                     PreviewType::Files,
                 )
                 .with_display_name(display_path)
@@ -184,6 +238,26 @@ impl OnAir for Channel {
             .collect()
     }
 
+    fn for_each_result(
+        &mut self,
+        range: Range<u32>,
+        f: &mut dyn FnMut(&EntryRef<'_>),
+    ) {
+        self.matcher.tick();
+        self.matcher.for_each_result(range, |item| {
+            let display_path = item.inner.path.to_string_lossy();
+            // same as the entries built by `results`
+            let name = format!("{display_path}{}", item.inner.line_number);
+            let mut entry = EntryRef::new(&name)
+                .with_value(item.matched_string)
+                .with_value_match_ranges(item.match_indices)
+                .with_icon(FileIcon::from(item.inner.path.as_path()))
+                .with_line_number(item.inner.line_number);
+            entry.display_name = Some(&display_path);
+            f(&entry);
+        });
+    }
+
     fn get_result(&self, index: u32) -> Option<Entry> {
         self.matcher.get_result(index).map(|item| {
             let display_path = item.inner.path.to_string_lossy().to_string();
@@ -191,7 +265,7 @@ impl OnAir for Channel {
                 .with_display_name(
                     display_path.clone()
                         + ":"
-                        + &item.inner.line_number.to_string(),
+                        + item.inner.line_number.to_string().as_str(),
                 )
                 .with_icon(FileIcon::from(item.inner.path.as_path()))
                 .with_line_number(item.inner.line_number)
@@ -206,13 +280,26 @@ impl OnAir for Channel {
         self.matcher.total_item_count
     }
 
+    fn memory_usage(&self) -> usize {
+        self.matcher.memory_usage()
+    }
+
     fn running(&self) -> bool {
         self.matcher.status.running
     }
 
+    fn loading(&self) -> bool {
+        !self.crawl_handle.is_finished()
+    }
+
     fn shutdown(&self) {
+        self.token.cancel();
         self.crawl_handle.abort();
     }
+
+    fn warnings(&self) -> Option<String> {
+        self.walk_warnings.summary()
+    }
 }
 
 /// The maximum file size we're willing to search in.
@@ -235,17 +322,28 @@ const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
 /// so this should take around 100 x `5_000_000` = 500MB of memory.
 const MAX_LINES_IN_MEM: usize = 5_000_000;
 
-#[allow(clippy::unused_async)]
 async fn crawl_for_candidates(
     directories: Vec<PathBuf>,
     injector: Injector<CandidateLine>,
+    walk_warnings: Arc<WalkWarnings>,
+    token: CancellationToken,
+    startup: Startup,
 ) {
+    startup.ready().await;
     if directories.is_empty() {
         return;
     }
     let current_dir = std::env::current_dir().unwrap();
-    let mut walker =
-        walk_builder(&directories[0], *DEFAULT_NUM_THREADS, None, None);
+    // entries the user chose to hide from this project
+    let hidden_paths = Some(HiddenEntries::for_current_dir().paths())
+        .filter(|p| !p.is_empty());
+    let mut walker = walk_builder(
+        &directories[0],
+        *DEFAULT_NUM_THREADS,
+        None,
+        hidden_paths,
+    );
+    add_git_excludes_file(&mut walker, &directories[0]);
     directories[1..].iter().for_each(|path| {
         walker.add(path);
     });
@@ -256,29 +354,41 @@ async fn crawl_for_candidates(
         let injector = injector.clone();
         let current_dir = current_dir.clone();
         let lines_in_mem = lines_in_mem.clone();
+        let walk_warnings = walk_warnings.clone();
+        let token = token.clone();
         Box::new(move |result| {
             if lines_in_mem.load(std::sync::atomic::Ordering::Relaxed)
                 > MAX_LINES_IN_MEM
+                || token.is_cancelled()
             {
                 return WalkState::Quit;
             }
-            if let Ok(entry) = result {
-                if entry.file_type().unwrap().is_file() {
-                    if let Ok(m) = entry.metadata() {
-                        if m.len() > MAX_FILE_SIZE {
-                            return WalkState::Continue;
-                        }
-                    }
-                    // try to inject the lines of the file
-                    if let Some(injected_lines) =
-                        try_inject_lines(&injector, &current_dir, entry.path())
-                    {
-                        lines_in_mem.fetch_add(
-                            injected_lines,
-                            std::sync::atomic::Ordering::Relaxed,
-                        );
-                    }
+            let entry = match result {
+                Ok(entry) => entry,
+                Err(e) => {
+                    walk_warnings.record(&e);
+                    return WalkState::Continue;
                 }
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                return WalkState::Continue;
+            }
+            if let Ok(m) = entry.metadata() {
+                if m.len() > MAX_FILE_SIZE {
+                    return WalkState::Continue;
+                }
+            }
+            // try to inject the lines of the file
+            if let Some(injected_lines) = try_inject_lines(
+                &injector,
+                &current_dir,
+                entry.path(),
+                &walk_warnings,
+            ) {
+                lines_in_mem.fetch_add(
+                    injected_lines,
+                    std::sync::atomic::Ordering::Relaxed,
+                );
             }
             WalkState::Continue
         })
@@ -287,8 +397,9 @@ async fn crawl_for_candidates(
 
 fn try_inject_lines(
     injector: &Injector<CandidateLine>,
-    current_dir: &PathBuf,
+    current_dir: &Path,
     path: &Path,
+    walk_warnings: &WalkWarnings,
 ) -> Option<usize> {
     match File::open(path) {
         Ok(file) => {
@@ -304,7 +415,7 @@ fn try_inject_lines(
                     {
                         return None;
                     }
-                    reader.seek(std::io::SeekFrom::Start(0)).unwrap();
+                    reader.seek(std::io::SeekFrom::Start(0)).ok()?;
                 }
                 Err(_) => {
                     return None;
@@ -323,9 +434,7 @@ fn try_inject_lines(
                             continue;
                         }
                         let candidate = CandidateLine::new(
-                            path.strip_prefix(current_dir)
-                                .unwrap_or(path)
-                                .to_path_buf(),
+                            relative_path(path, current_dir),
                             line,
                             line_number,
                         );
@@ -344,6 +453,7 @@ fn try_inject_lines(
         }
         Err(e) => {
             warn!("Error opening file {:?}: {:?}", path, e);
+            walk_warnings.record_io(&e);
             None
         }
     }
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use television_fuzzy::matcher::config::Chunking;
+
+use crate::bus::Bus;
+use crate::channels::cable::CableChannelPrototype;
+#[cfg(feature = "plugins")]
+use crate::channels::plugin::Plugin;
+#[cfg(feature = "scripting")]
+use crate::channels::script::Script;
+use crate::recent::RecentLimits;
+use crate::scope::Scope;
+use crate::startup::Startup;
+use crate::time_range::TimeRange;
+
+/// What the channels of a picker are created with: the settings they read
+/// (e.g. the number of worker threads of their fuzzy matcher) and the
+/// handles they report back through (its startup and its bus).
+///
+/// Each picker has its own context, so that settings don't leak between
+/// them. Settings only apply to the channels created afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    /// The number of worker threads of the fuzzy matchers, by (lowercase)
+    /// channel name.
+    matcher_threads: HashMap<String, usize>,
+    /// How the channels push their entries in bulk into their fuzzy
+    /// matcher, by (lowercase) channel name.
+    chunking: HashMap<String, Chunking>,
+    /// Where the files and text channels search by default.
+    scope: Scope,
+    /// The directories to look for repositories in, the home directory if
+    /// empty.
+    repo_roots: Vec<PathBuf>,
+    /// The file types (extensions) listed by the files channel, all of them
+    /// if empty.
+    file_types: Vec<String>,
+    /// How recently the files listed by the files channel must have been
+    /// modified, if they must.
+    changed_within: Option<Duration>,
+    /// The command of the external channels created from the remote
+    /// control.
+    source_command: String,
+    /// The (lowercase) names of the channels that can be watched, all of
+    /// them if empty.
+    available_channels: Vec<String>,
+    /// How the recent entries of each channel are bounded.
+    recent_limits: RecentLimits,
+    /// The time range the channels running a command restrict its lines
+    /// to.
+    time_range: TimeRange,
+    /// The cable channels defined in the config file.
+    pub(crate) cable_channels: Vec<CableChannelPrototype>,
+    /// The scripts found in the config directory.
+    #[cfg(feature = "scripting")]
+    pub(crate) scripts: Vec<Script>,
+    /// The plugins found in the config directory.
+    #[cfg(feature = "plugins")]
+    pub(crate) plugins: Vec<Plugin>,
+    /// Holds the heavy work of the channels, e.g. until the first frame has
+    /// been drawn.
+    pub startup: Startup,
+    /// The bus the channels and previewers send their messages to the UI
+    /// through.
+    pub bus: Bus,
+}
+
+/// Creating a channel with its default settings, as read from the context
+/// of the picker it is watched in.
+pub trait FromContext {
+    fn from_context(ctx: &Context) -> Self;
+}
+
+impl Context {
+    /// Set the number of worker threads the fuzzy matcher of the given
+    /// channel uses.
+    pub fn set_matcher_threads(&mut self, channel: &str, n_threads: usize) {
+        self.matcher_threads
+            .insert(channel.to_lowercase(), n_threads.max(1));
+    }
+
+    /// The number of worker threads the fuzzy matcher of the given channel
+    /// should use, or the given default if it wasn't configured.
+    pub fn matcher_threads(&self, channel: &str, default: usize) -> usize {
+        self.matcher_threads
+            .get(channel)
+            .copied()
+            .unwrap_or(default)
+    }
+
+    /// Set how the given channel pushes its entries in bulk into its fuzzy
+    /// matcher.
+    pub fn set_chunking(&mut self, channel: &str, chunking: Chunking) {
+        self.chunking.insert(
+            channel.to_lowercase(),
+            Chunking {
+                chunk_size: chunking.chunk_size.max(1),
+                ..chunking
+            },
+        );
+    }
+
+    /// How the given channel should push its entries in bulk into its fuzzy
+    /// matcher, the default if it wasn't configured.
+    pub fn chunking(&self, channel: &str) -> Chunking {
+        self.chunking.get(channel).copied().unwrap_or_default()
+    }
+
+    /// Set where the files and text channels search.
+    pub fn set_scope(&mut self, scope: Scope) {
+        self.scope = scope;
+    }
+
+    pub fn scope(&self) -> Scope {
+        self.scope
+    }
+
+    /// The directory the files and text channels search by default, given
+    /// the current scope.
+    pub fn search_root(&self) -> PathBuf {
+        self.scope.search_root()
+    }
+
+    /// Set the directories the git repos channels look for repositories in
+    /// (the home directory if empty).
+    pub fn set_repo_roots(&mut self, roots: Vec<PathBuf>) {
+        self.repo_roots = roots;
+    }
+
+    pub fn repo_roots(&self) -> &[PathBuf] {
+        &self.repo_roots
+    }
+
+    /// Only list the files with the given extensions (e.g. `rs`) in the
+    /// files channels, or all files if empty.
+    ///
+    /// Other files are skipped while walking the file system, so that they
+    /// never reach the matcher.
+    pub fn set_file_types(&mut self, types: Vec<String>) {
+        self.file_types = types
+            .into_iter()
+            .map(|t| t.trim().trim_start_matches('.').to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+    }
+
+    pub fn file_types(&self) -> &[String] {
+        &self.file_types
+    }
+
+    /// Only list the files modified within the given duration (e.g. the
+    /// last 2 days) in the files channels, or all files if `None`.
+    ///
+    /// Like file types, this is checked while walking the file system.
+    pub fn set_changed_within(&mut self, duration: Option<Duration>) {
+        self.changed_within = duration;
+    }
+
+    pub fn changed_within(&self) -> Option<Duration> {
+        self.changed_within
+    }
+
+    /// Set the command run by the external channels created from the remote
+    /// control (i.e. the one given on the command line).
+    pub fn set_source_command(&mut self, command: &str) {
+        command.clone_into(&mut self.source_command);
+    }
+
+    pub fn source_command(&self) -> &str {
+        &self.source_command
+    }
+
+    /// Restrict the channels that can be watched (e.g. listed by the remote
+    /// control) to the ones with the given names, all of them if empty.
+    pub fn set_available_channels(&mut self, names: Vec<String>) {
+        self.available_channels = names;
+    }
+
+    /// Whether the channel with the given (case insensitive) name can be
+    /// watched, where dashes are ignored (e.g. `git-repos` is `gitrepos`).
+    pub fn is_channel_available(&self, name: &str) -> bool {
+        let normalize = |name: &str| name.replace('-', "").to_lowercase();
+        self.available_channels.is_empty()
+            || self
+                .available_channels
+                .iter()
+                .any(|n| normalize(n) == normalize(name))
+    }
+
+    /// Set how the recent entries of each channel are bounded.
+    pub fn set_recent_limits(&mut self, limits: RecentLimits) {
+        self.recent_limits = limits;
+    }
+
+    pub fn recent_limits(&self) -> RecentLimits {
+        self.recent_limits
+    }
+
+    /// Restrict the lines of the commands run by the journal, external and
+    /// cable channels to the given time range.
+    pub fn set_time_range(&mut self, range: TimeRange) {
+        self.time_range = range;
+    }
+
+    pub fn time_range(&self) -> &TimeRange {
+        &self.time_range
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matcher_threads() {
+        let mut ctx = Context::default();
+        assert_eq!(ctx.matcher_threads("test-channel", 3), 3);
+        ctx.set_matcher_threads("Test-Channel", 8);
+        assert_eq!(ctx.matcher_threads("test-channel", 3), 8);
+        ctx.set_matcher_threads("test-channel", 0);
+        assert_eq!(ctx.matcher_threads("test-channel", 3), 1);
+        // contexts don't share their settings
+        assert_eq!(Context::default().matcher_threads("test-channel", 3), 3);
+    }
+
+    #[test]
+    fn test_chunking() {
+        let mut ctx = Context::default();
+        assert_eq!(ctx.chunking("test-channel"), Chunking::default());
+        ctx.set_chunking(
+            "Test-Channel",
+            Chunking {
+                chunk_size: 0,
+                high_watermark: 10,
+            },
+        );
+        assert_eq!(
+            ctx.chunking("test-channel"),
+            Chunking {
+                chunk_size: 1,
+                high_watermark: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_is_channel_available() {
+        let mut ctx = Context::default();
+        assert!(ctx.is_channel_available("git-repos"));
+        ctx.set_available_channels(vec![
+            "files".to_string(),
+            "gitrepos".into(),
+        ]);
+        assert!(ctx.is_channel_available("Files"));
+        assert!(ctx.is_channel_available("git-repos"));
+        assert!(!ctx.is_channel_available("env"));
+        ctx.set_available_channels(Vec::new());
+        assert!(ctx.is_channel_available("env"));
+    }
+}
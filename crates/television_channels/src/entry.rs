@@ -1,4 +1,7 @@
+use std::ops::Range;
+
 use devicons::FileIcon;
+use television_utils::ansi::AnsiStyle;
 
 // NOTE: having an enum for entry types would be nice since it would allow
 // having a nicer implementation for transitions between channels. This would
@@ -18,12 +21,22 @@ pub struct Entry {
     pub name_match_ranges: Option<Vec<(u32, u32)>>,
     /// The optional ranges for matching characters in the value.
     pub value_match_ranges: Option<Vec<(u32, u32)>>,
+    /// The optional styles of parts of the name (by byte range), e.g. the
+    /// colors of a line of colored input.
+    pub name_styles: Option<Vec<(Range<usize>, AnsiStyle)>>,
     /// The optional icon associated with the entry.
     pub icon: Option<FileIcon>,
     /// The optional line number associated with the entry.
     pub line_number: Option<usize>,
     /// The type of preview associated with the entry.
     pub preview_type: PreviewType,
+    /// An optional output that replaces the default representation of the
+    /// entry when it is selected (e.g. printed to stdout or copied to the
+    /// clipboard).
+    pub output: Option<String>,
+    /// An optional shell command used to edit the entry (e.g. open it in an
+    /// editor).
+    pub edit_command: Option<String>,
 }
 
 impl Entry {
@@ -40,7 +53,9 @@ impl Entry {
     ///                 .with_name_match_ranges(vec![(0, 1)])
     ///                 .with_value_match_ranges(vec![(0, 1)])
     ///                 .with_icon(FileIcon::default())
-    ///                 .with_line_number(0);
+    ///                 .with_line_number(0)
+    ///                 .with_output("output".to_string())
+    ///                 .with_edit_command("$EDITOR name".to_string());
     /// ```
     ///
     /// # Arguments
@@ -57,9 +72,12 @@ impl Entry {
             value: None,
             name_match_ranges: None,
             value_match_ranges: None,
+            name_styles: None,
             icon: None,
             line_number: None,
             preview_type,
+            output: None,
+            edit_command: None,
         }
     }
 
@@ -89,6 +107,14 @@ impl Entry {
         self
     }
 
+    pub fn with_name_styles(
+        mut self,
+        name_styles: Vec<(Range<usize>, AnsiStyle)>,
+    ) -> Self {
+        self.name_styles = Some(name_styles);
+        self
+    }
+
     pub fn with_icon(mut self, icon: FileIcon) -> Self {
         self.icon = Some(icon);
         self
@@ -99,11 +125,30 @@ impl Entry {
         self
     }
 
+    pub fn with_output(mut self, output: String) -> Self {
+        self.output = Some(output);
+        self
+    }
+
+    pub fn with_edit_command(mut self, edit_command: String) -> Self {
+        self.edit_command = Some(edit_command);
+        self
+    }
+
     pub fn display_name(&self) -> &str {
         self.display_name.as_ref().unwrap_or(&self.name)
     }
 
+    /// Identifies the entry across sessions (e.g. for annotations), by its
+    /// name and line number if any.
+    pub(crate) fn key(&self) -> String {
+        EntryRef::from(self).key()
+    }
+
     pub fn stdout_repr(&self) -> String {
+        if let Some(output) = &self.output {
+            return output.clone();
+        }
         let mut repr = self.name.clone();
         if let Some(line_number) = self.line_number {
             repr.push_str(&format!(":{line_number}"));
@@ -115,15 +160,117 @@ impl Entry {
     }
 }
 
+/// A borrowed view of the displayed parts of an entry, which channels can
+/// lend straight from their matcher's snapshot (see
+/// `OnAir::for_each_result`) instead of building an `Entry`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EntryRef<'a> {
+    pub name: &'a str,
+    pub display_name: Option<&'a str>,
+    pub value: Option<&'a str>,
+    pub name_match_ranges: Option<&'a [(u32, u32)]>,
+    pub value_match_ranges: Option<&'a [(u32, u32)]>,
+    pub name_styles: Option<&'a [(Range<usize>, AnsiStyle)]>,
+    pub icon: Option<FileIcon>,
+    pub line_number: Option<usize>,
+}
+
+impl<'a> EntryRef<'a> {
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            display_name: None,
+            value: None,
+            name_match_ranges: None,
+            value_match_ranges: None,
+            name_styles: None,
+            icon: None,
+            line_number: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_value(mut self, value: &'a str) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    #[must_use]
+    pub fn with_name_match_ranges(mut self, ranges: &'a [(u32, u32)]) -> Self {
+        self.name_match_ranges = Some(ranges);
+        self
+    }
+
+    #[must_use]
+    pub fn with_value_match_ranges(
+        mut self,
+        ranges: &'a [(u32, u32)],
+    ) -> Self {
+        self.value_match_ranges = Some(ranges);
+        self
+    }
+
+    #[must_use]
+    pub fn with_name_styles(
+        mut self,
+        styles: &'a [(Range<usize>, AnsiStyle)],
+    ) -> Self {
+        self.name_styles = Some(styles);
+        self
+    }
+
+    #[must_use]
+    pub fn with_icon(mut self, icon: FileIcon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    #[must_use]
+    pub fn with_line_number(mut self, line_number: usize) -> Self {
+        self.line_number = Some(line_number);
+        self
+    }
+
+    pub fn display_name(&self) -> &'a str {
+        self.display_name.unwrap_or(self.name)
+    }
+
+    /// See `Entry::key`.
+    pub(crate) fn key(&self) -> String {
+        match self.line_number {
+            Some(line_number) => format!("{}:{line_number}", self.name),
+            None => self.name.to_string(),
+        }
+    }
+}
+
+impl<'a> From<&'a Entry> for EntryRef<'a> {
+    fn from(entry: &'a Entry) -> Self {
+        Self {
+            name: &entry.name,
+            display_name: entry.display_name.as_deref(),
+            value: entry.value.as_deref(),
+            name_match_ranges: entry.name_match_ranges.as_deref(),
+            value_match_ranges: entry.value_match_ranges.as_deref(),
+            name_styles: entry.name_styles.as_deref(),
+            icon: entry.icon,
+            line_number: entry.line_number,
+        }
+    }
+}
+
 pub const ENTRY_PLACEHOLDER: Entry = Entry {
     name: String::new(),
     display_name: None,
     value: None,
     name_match_ranges: None,
     value_match_ranges: None,
+    name_styles: None,
     icon: None,
     line_number: None,
     preview_type: PreviewType::EnvVar,
+    output: None,
+    edit_command: None,
 };
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
@@ -133,4 +280,42 @@ pub enum PreviewType {
     Directory,
     EnvVar,
     Files,
+    /// The value of the entry is displayed as-is, line by line.
+    PlainText,
+    Command(PreviewCommand),
+    /// The text returned by the `preview` function of the script with the
+    /// given name.
+    Script(String),
+    /// The text returned by the `preview` function of the plugin with the
+    /// given name.
+    Plugin(String),
+}
+
+/// A shell command whose output is used as the preview of an entry.
+///
+/// The command is run through the platform's shell (`sh -c` on unix,
+/// `cmd /C` on windows) and its standard output is displayed as-is in the
+/// preview pane, or syntax highlighted when `syntax` is set.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PreviewCommand {
+    pub command: String,
+    /// The name of a file having the syntax of the output (e.g.
+    /// `commit.diff`), to highlight it.
+    pub syntax: Option<String>,
+}
+
+impl PreviewCommand {
+    pub fn new(command: String) -> Self {
+        Self {
+            command,
+            syntax: None,
+        }
+    }
+
+    /// Highlight the output with the syntax of the given file name.
+    #[must_use]
+    pub fn highlighted_as(mut self, file_name: &str) -> Self {
+        self.syntax = Some(file_name.to_string());
+        self
+    }
 }
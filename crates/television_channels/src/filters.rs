@@ -0,0 +1,310 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use television_fuzzy::matcher::query::split_terms;
+use television_fuzzy::matcher::Filter;
+
+type Extract<T, V> = Arc<dyn Fn(&T) -> Option<V> + Send + Sync>;
+type Condition<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// The metadata filters a channel supports, e.g. `ext:rs`, `size:>1M` or
+/// `mtime:<7d`.
+///
+/// Each channel registers the keys it supports along with how to extract the
+/// corresponding value from its items. Filters are then parsed out of the
+/// query before fuzzy matching: words of the form `<key>:<value>` whose key is
+/// supported are turned into a filter while the rest of the query is used as
+/// the fuzzy pattern.
+///
+/// Values are compared depending on the kind of the key:
+/// - text keys match any of the comma-separated values (case-insensitively),
+///   or none of them when the value starts with `!` (e.g. `ext:!md,txt`);
+/// - size keys compare a size in bytes with an optional `k`, `M`, `G` or `T`
+///   suffix (e.g. `size:>1M`);
+/// - age keys compare how long ago something happened, with an `s`, `m`,
+///   `h`, `d` or `w` suffix (e.g. `mtime:<7d` for less than 7 days ago).
+///
+/// Size and age values can be prefixed with `<`, `<=`, `>`, `>=` or `=`
+/// (the default).
+pub struct MetadataFilters<T> {
+    keys: Vec<(&'static str, Extractor<T>)>,
+}
+
+enum Extractor<T> {
+    Text(Extract<T, String>),
+    Size(Extract<T, u64>),
+    Age(Extract<T, Duration>),
+}
+
+impl<T> Default for MetadataFilters<T> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<T: 'static> MetadataFilters<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a key whose values are compared as text.
+    #[must_use]
+    pub fn text<F>(mut self, key: &'static str, extract: F) -> Self
+    where
+        F: Fn(&T) -> Option<String> + Send + Sync + 'static,
+    {
+        self.keys.push((key, Extractor::Text(Arc::new(extract))));
+        self
+    }
+
+    /// Register a key whose values are sizes in bytes.
+    #[must_use]
+    pub fn size<F>(mut self, key: &'static str, extract: F) -> Self
+    where
+        F: Fn(&T) -> Option<u64> + Send + Sync + 'static,
+    {
+        self.keys.push((key, Extractor::Size(Arc::new(extract))));
+        self
+    }
+
+    /// Register a key whose values are ages (how long ago something
+    /// happened).
+    #[must_use]
+    pub fn age<F>(mut self, key: &'static str, extract: F) -> Self
+    where
+        F: Fn(&T) -> Option<Duration> + Send + Sync + 'static,
+    {
+        self.keys.push((key, Extractor::Age(Arc::new(extract))));
+        self
+    }
+
+    /// Split the given query into the fuzzy pattern and the filter made of
+    /// its metadata filters, if any.
+    ///
+    /// Words that look like filters but have an unknown key or an invalid
+    /// value are left in the pattern.
+    pub fn parse(&self, query: &str) -> (String, Option<Filter<T>>) {
+        let mut pattern = Vec::new();
+        let mut filters = Vec::new();
+        let mut conditions = Vec::new();
+        for word in split_terms(query) {
+            match self.condition(word) {
+                Some(condition) => {
+                    filters.push(word);
+                    conditions.push(condition);
+                }
+                None => pattern.push(word),
+            }
+        }
+        if conditions.is_empty() {
+            return (query.to_string(), None);
+        }
+        let filter = Filter {
+            query: filters.join(" "),
+            predicate: Arc::new(move |item: &T| {
+                conditions.iter().all(|condition| condition(item))
+            }),
+        };
+        (pattern.join(" "), Some(filter))
+    }
+
+    fn condition(&self, word: &str) -> Option<Condition<T>> {
+        let (key, value) = word.split_once(':')?;
+        let (_, extractor) = self.keys.iter().find(|(k, _)| *k == key)?;
+        match extractor {
+            Extractor::Text(extract) => {
+                let (negated, value) = match value.strip_prefix('!') {
+                    Some(value) => (true, value),
+                    None => (false, value),
+                };
+                let values: Vec<String> = value
+                    .split(',')
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_lowercase)
+                    .collect();
+                if values.is_empty() {
+                    return None;
+                }
+                let extract = extract.clone();
+                Some(Box::new(move |item| {
+                    let found = extract(item)
+                        .is_some_and(|v| values.contains(&v.to_lowercase()));
+                    found != negated
+                }))
+            }
+            Extractor::Size(extract) => {
+                let (op, value) = parse_comparison(value);
+                let size = parse_size(value)?;
+                let extract = extract.clone();
+                Some(Box::new(move |item| {
+                    extract(item).is_some_and(|s| op.compare(s, size))
+                }))
+            }
+            Extractor::Age(extract) => {
+                let (op, value) = parse_comparison(value);
+                let age = parse_age(value)?;
+                let extract = extract.clone();
+                Some(Box::new(move |item| {
+                    extract(item).is_some_and(|a| op.compare(a, age))
+                }))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+impl Comparison {
+    fn compare<V: PartialOrd>(self, value: V, reference: V) -> bool {
+        match self {
+            Comparison::Lt => value < reference,
+            Comparison::Le => value <= reference,
+            Comparison::Gt => value > reference,
+            Comparison::Ge => value >= reference,
+            Comparison::Eq => value == reference,
+        }
+    }
+}
+
+fn parse_comparison(value: &str) -> (Comparison, &str) {
+    [
+        ("<=", Comparison::Le),
+        (">=", Comparison::Ge),
+        ("<", Comparison::Lt),
+        (">", Comparison::Gt),
+        ("=", Comparison::Eq),
+    ]
+    .into_iter()
+    .find_map(|(prefix, op)| value.strip_prefix(prefix).map(|v| (op, v)))
+    .unwrap_or((Comparison::Eq, value))
+}
+
+/// Split a value such as `1.5M` into its number and its unit.
+fn split_unit(value: &str) -> Option<(f64, &str)> {
+    let end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let number = value[..end].parse::<f64>().ok()?;
+    Some((number, &value[end..]))
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn parse_size(value: &str) -> Option<u64> {
+    let (number, unit) = split_unit(value)?;
+    let multiplier: u64 = match unit.trim_end_matches(['b', 'B']) {
+        "" => 1,
+        "k" | "K" => 1 << 10,
+        "m" | "M" => 1 << 20,
+        "g" | "G" => 1 << 30,
+        "t" | "T" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+/// Parse a duration such as `2d` (with an `s`, `m`, `h`, `d` or `w` unit),
+/// e.g. how long ago a file was modified.
+pub fn parse_age(value: &str) -> Option<Duration> {
+    let (number, unit) = split_unit(value)?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    Duration::try_from_secs_f64(number * f64::from(seconds)).ok()
+}
+
+/// Format a duration the way `parse_age` parses it, in the largest unit
+/// dividing it (e.g. `2d` or `36h`).
+pub fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    [
+        ("w", 7 * 24 * 60 * 60),
+        ("d", 24 * 60 * 60),
+        ("h", 60 * 60),
+        ("m", 60),
+    ]
+    .into_iter()
+    .find(|(_, unit)| seconds > 0 && seconds.is_multiple_of(*unit))
+    .map_or_else(
+        || format!("{seconds}s"),
+        |(suffix, unit)| format!("{}{suffix}", seconds / unit),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct File {
+        name: &'static str,
+        size: u64,
+        age: Duration,
+    }
+
+    fn filters() -> MetadataFilters<File> {
+        MetadataFilters::new()
+            .text("ext", |f: &File| {
+                f.name.rsplit_once('.').map(|(_, ext)| ext.to_string())
+            })
+            .size("size", |f| Some(f.size))
+            .age("mtime", |f| Some(f.age))
+    }
+
+    fn matches(query: &str, file: &File) -> bool {
+        let (_, filter) = filters().parse(query);
+        (filter.unwrap().predicate)(file)
+    }
+
+    #[test]
+    fn test_parse() {
+        let (pattern, filter) = filters().parse("src ext:rs size:>1k foo:bar");
+        assert_eq!(pattern, "src foo:bar");
+        assert_eq!(filter.unwrap().query, "ext:rs size:>1k");
+
+        // invalid values are left in the pattern
+        let (pattern, filter) = filters().parse("size:>1x ext:");
+        assert_eq!(pattern, "size:>1x ext:");
+        assert!(filter.is_none());
+
+        // escaped spaces don't separate words
+        let (pattern, _) = filters().parse("ext:rs my\\ file");
+        assert_eq!(pattern, "my\\ file");
+    }
+
+    #[test]
+    fn test_conditions() {
+        let file = File {
+            name: "main.RS",
+            size: 2048,
+            age: Duration::from_secs(3 * 24 * 60 * 60),
+        };
+        assert!(matches("ext:rs", &file));
+        assert!(matches("ext:toml,rs", &file));
+        assert!(!matches("ext:!rs", &file));
+        assert!(matches("size:>1k", &file));
+        assert!(matches("size:2k", &file));
+        assert!(!matches("size:<=1.5K", &file));
+        assert!(matches("mtime:<7d", &file));
+        assert!(!matches("mtime:<1w ext:md", &file));
+        assert!(matches("mtime:>=72h", &file));
+    }
+
+    #[test]
+    fn test_format_age() {
+        for age in ["2d", "36h", "90s", "1w", "0s"] {
+            assert_eq!(format_age(parse_age(age).unwrap()), age);
+        }
+        assert_eq!(format_age(parse_age("1.5h").unwrap()), "90m");
+    }
+}
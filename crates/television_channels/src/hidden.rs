@@ -0,0 +1,239 @@
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+use color_eyre::Result;
+use tracing::debug;
+
+use crate::store::write_lines;
+use television_utils::dirs::get_data_dir;
+use television_utils::project::{find_project_root, relative_path};
+
+/// The directory of the data directory the hidden entries are stored in.
+pub const HIDDEN_ENTRIES_DIR: &str = "hidden";
+
+/// Entries (file paths) the user chose to hide from the files and text
+/// channels of a given project.
+///
+/// Hidden entries are stored as a wordlist (one path relative to the
+/// project's root per line) in the data directory, in a file named after the
+/// project's root, so that they are shared by all of its directories. They
+/// are given and listed relative to the directory they were loaded from.
+#[derive(Debug, Clone, Default)]
+pub struct HiddenEntries {
+    /// The root of the project, which the stored entries are relative to.
+    root: PathBuf,
+    /// The directory the entries are given and listed relative to.
+    dir: PathBuf,
+    store: PathBuf,
+    entries: BTreeSet<String>,
+}
+
+impl HiddenEntries {
+    /// Load the hidden entries of the project the given directory belongs
+    /// to (see `find_project_root`), or of the directory itself if it isn't
+    /// part of one.
+    pub fn for_dir(dir: &Path) -> Self {
+        Self::from_stores(&get_data_dir().join(HIDDEN_ENTRIES_DIR), dir)
+    }
+
+    /// Load the hidden entries of the project in the current directory.
+    pub fn for_current_dir() -> Self {
+        Self::for_dir(&std::env::current_dir().unwrap_or_default())
+    }
+
+    fn from_stores(stores: &Path, dir: &Path) -> Self {
+        let root = find_project_root(dir).unwrap_or_else(|| dir.to_path_buf());
+        let store = stores.join(store_file_name(&root));
+        Self::load(&root, dir, store)
+    }
+
+    fn load(root: &Path, dir: &Path, store: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&store)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        debug!("Loaded hidden entries from {:?}: {:?}", store, entries);
+        HiddenEntries {
+            root: root.to_path_buf(),
+            dir: dir.to_path_buf(),
+            store,
+            entries,
+        }
+    }
+
+    /// The hidden entries, relative to the directory they were loaded from.
+    pub fn iter(&self) -> impl Iterator<Item = String> + '_ {
+        self.entries.iter().map(|entry| {
+            relative_path(&self.root.join(entry), &self.dir)
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+
+    pub fn contains(&self, entry: &str) -> bool {
+        self.entries.contains(&self.key(entry))
+    }
+
+    /// The paths to exclude when walking the project, both relative to the
+    /// directory the entries were loaded from and absolute.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .flat_map(|entry| {
+                let path = self.root.join(entry);
+                [relative_path(&path, &self.dir), path]
+            })
+            .collect()
+    }
+
+    /// Hide an entry and persist the change.
+    pub fn hide(&mut self, entry: &str) -> Result<()> {
+        if self.entries.insert(self.key(entry)) {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Un-hide an entry and persist the change.
+    pub fn unhide(&mut self, entry: &str) -> Result<()> {
+        if self.entries.remove(&self.key(entry)) {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// The stored form of an entry given relative to the directory the
+    /// entries were loaded from (or absolute): its path relative to the
+    /// project's root.
+    fn key(&self, entry: &str) -> String {
+        let path = normalize(&self.dir.join(entry));
+        relative_path(&path, &self.root)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn save(&self) -> Result<()> {
+        write_lines(&self.store, &self.entries)
+    }
+}
+
+/// Resolve the `.` and `..` components of a path, without touching the
+/// filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Turn a project path into a file name by percent-encoding the characters
+/// that can't appear in one (e.g. `/home/user/project` becomes
+/// `%2Fhome%2Fuser%2Fproject`), so that distinct paths never share a store.
+fn store_file_name(project: &Path) -> String {
+    let mut name = String::new();
+    for c in project.to_string_lossy().chars() {
+        match c {
+            '%' | '/' | '\\' | ':' => {
+                name.push_str(&format!("%{:02X}", c as u32))
+            }
+            c => name.push(c),
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_file_name() {
+        assert_eq!(
+            store_file_name(Path::new("/home/user/project")),
+            "%2Fhome%2Fuser%2Fproject"
+        );
+        for (a, b) in [("/a/b", "/a%b"), ("C:\\x", "C%/x")] {
+            assert_ne!(
+                store_file_name(Path::new(a)),
+                store_file_name(Path::new(b))
+            );
+        }
+    }
+
+    #[test]
+    fn test_hide_and_unhide() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("store");
+        let project = Path::new("/project");
+
+        let mut hidden = HiddenEntries::load(project, project, store.clone());
+        hidden.hide("target/debug.log").unwrap();
+        hidden.hide("secrets.env").unwrap();
+
+        let mut hidden = HiddenEntries::load(project, project, store.clone());
+        assert!(hidden.contains("secrets.env"));
+        assert_eq!(
+            hidden.paths(),
+            vec![
+                PathBuf::from("secrets.env"),
+                PathBuf::from("/project/secrets.env"),
+                PathBuf::from("target/debug.log"),
+                PathBuf::from("/project/target/debug.log"),
+            ]
+        );
+
+        hidden.unhide("secrets.env").unwrap();
+        let hidden = HiddenEntries::load(project, project, store.clone());
+        assert_eq!(
+            hidden.iter().collect::<Vec<_>>(),
+            vec!["target/debug.log"]
+        );
+    }
+
+    #[test]
+    fn test_hidden_entries_are_shared_by_the_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let stores = dir.path().join("hidden");
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::create_dir_all(repo.join("tests")).unwrap();
+
+        let mut hidden =
+            HiddenEntries::from_stores(&stores, &repo.join("src"));
+        hidden.hide("generated.rs").unwrap();
+        hidden.hide("../tests/./fixtures").unwrap();
+        assert!(hidden.contains("generated.rs"));
+
+        let mut hidden = HiddenEntries::from_stores(&stores, &repo);
+        assert!(hidden.contains("src/generated.rs"));
+        assert_eq!(
+            hidden.iter().collect::<Vec<_>>(),
+            vec!["src/generated.rs", "tests/fixtures"]
+        );
+        assert!(hidden.paths().contains(&repo.join("src/generated.rs")));
+
+        let hidden_from_tests =
+            HiddenEntries::from_stores(&stores, &repo.join("tests"));
+        assert_eq!(
+            hidden_from_tests.iter().collect::<Vec<_>>(),
+            vec!["../src/generated.rs", "fixtures"]
+        );
+
+        hidden.unhide("src/generated.rs").unwrap();
+        let hidden = HiddenEntries::from_stores(&stores, &repo.join("src"));
+        assert!(!hidden.contains("generated.rs"));
+    }
+}
@@ -1,2 +1,15 @@
+pub mod annotations;
+pub mod bus;
 pub mod channels;
+pub mod context;
 pub mod entry;
+pub mod filters;
+pub mod hidden;
+pub mod pins;
+pub mod recent;
+pub mod scope;
+pub mod selection;
+pub mod startup;
+pub mod store;
+pub mod threads;
+pub mod time_range;
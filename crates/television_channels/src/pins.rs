@@ -0,0 +1,99 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use color_eyre::Result;
+use tracing::debug;
+
+use crate::entry::{Entry, EntryRef};
+use crate::store::write_lines;
+use television_utils::dirs::get_data_dir;
+
+/// The directory of the data directory the pins are stored in.
+pub const PINS_DIR: &str = "pins";
+
+/// The entries the user pinned to the top of the results of a given
+/// channel.
+///
+/// Pins are stored as a wordlist (one entry per line) in the data directory,
+/// in a file named after the channel.
+#[derive(Debug, Clone, Default)]
+pub struct Pins {
+    store: PathBuf,
+    entries: BTreeSet<String>,
+}
+
+impl Pins {
+    /// Load the pins of the given (lowercase) channel.
+    pub fn for_channel(channel: &str) -> Self {
+        Self::load(get_data_dir().join(PINS_DIR).join(channel))
+    }
+
+    fn load(store: PathBuf) -> Self {
+        let entries = std::fs::read_to_string(&store)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        debug!("Loaded pins from {:?}: {:?}", store, entries);
+        Pins { store, entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains<'a>(&self, entry: impl Into<EntryRef<'a>>) -> bool {
+        self.entries.contains(&entry.into().key())
+    }
+
+    /// Pin an entry and persist the change.
+    pub fn pin(&mut self, entry: &Entry) -> Result<()> {
+        if self.entries.insert(entry.key()) {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Unpin an entry and persist the change.
+    pub fn unpin(&mut self, entry: &Entry) -> Result<()> {
+        if self.entries.remove(&entry.key()) {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        write_lines(&self.store, &self.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::PreviewType;
+
+    #[test]
+    fn test_pin_and_unpin() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("files");
+        let readme = Entry::new("README.md".to_string(), PreviewType::Files);
+        let license = Entry::new("LICENSE".to_string(), PreviewType::Files);
+
+        let mut pins = Pins::load(store.clone());
+        assert!(pins.is_empty());
+        pins.pin(&readme).unwrap();
+        pins.pin(&license).unwrap();
+
+        let mut pins = Pins::load(store.clone());
+        assert!(pins.contains(&readme));
+        pins.unpin(&readme).unwrap();
+
+        let pins = Pins::load(store.clone());
+        assert!(!pins.contains(&readme));
+        assert!(pins.contains(&license));
+    }
+}
@@ -0,0 +1,317 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use color_eyre::Result;
+use tracing::debug;
+
+use crate::entry::Entry;
+use crate::store::write_lines;
+use television_utils::dirs::get_data_dir;
+
+/// The directory of the data directory the recent entries are stored in.
+pub const RECENT_ENTRIES_DIR: &str = "recent";
+
+/// How many selected entries are remembered per channel by default.
+pub const DEFAULT_MAX_RECENT_ENTRIES: usize = 100;
+
+/// How the recent entries of each channel are bounded, applied whenever an
+/// entry is recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecentLimits {
+    /// How many entries are kept per channel.
+    pub max_entries: usize,
+    /// How long ago entries may have been selected to be kept, if bounded.
+    pub max_age: Option<Duration>,
+}
+
+impl Default for RecentLimits {
+    fn default() -> Self {
+        RecentLimits {
+            max_entries: DEFAULT_MAX_RECENT_ENTRIES,
+            max_age: None,
+        }
+    }
+}
+
+fn recent_entries_dir() -> PathBuf {
+    get_data_dir().join(RECENT_ENTRIES_DIR)
+}
+
+/// An entry recently selected in a channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentEntry {
+    pub key: String,
+    pub selected_at: SystemTime,
+}
+
+impl RecentEntry {
+    /// How long ago the entry was selected.
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.selected_at)
+            .unwrap_or_default()
+    }
+}
+
+/// The entries recently selected in a given channel, most recent first.
+///
+/// Recent entries are stored in the data directory, in a file named after
+/// the channel, as one `<unix timestamp>\t<entry>` line per entry (plain
+/// entry lines, as written by older versions, are dated by the file's
+/// modification time).
+#[derive(Debug, Clone, Default)]
+pub struct RecentEntries {
+    channel: String,
+    store: PathBuf,
+    entries: Vec<RecentEntry>,
+    limits: RecentLimits,
+}
+
+impl RecentEntries {
+    /// Load the recent entries of the given (lowercase) channel, bounded
+    /// by the given limits.
+    pub fn for_channel(channel: &str, limits: RecentLimits) -> Self {
+        Self::load(recent_entries_dir().join(channel), limits)
+    }
+
+    /// Load the recent entries of every channel that has any, by channel
+    /// name.
+    pub fn all(limits: RecentLimits) -> Vec<Self> {
+        Self::load_dir(&recent_entries_dir(), limits)
+    }
+
+    fn load_dir(dir: &Path, limits: RecentLimits) -> Vec<Self> {
+        let mut stores: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| Some(entry.ok()?.path()))
+                    .filter(|path| path.is_file())
+                    .collect()
+            })
+            .unwrap_or_default();
+        stores.sort();
+        stores
+            .into_iter()
+            .map(|store| Self::load(store, limits))
+            .collect()
+    }
+
+    fn load(store: PathBuf, limits: RecentLimits) -> Self {
+        let modified = std::fs::metadata(&store)
+            .and_then(|m| m.modified())
+            .unwrap_or_else(|_| SystemTime::now());
+        let entries = std::fs::read_to_string(&store)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(|line| parse_line(line, modified))
+                    .collect()
+            })
+            .unwrap_or_default();
+        debug!("Loaded recent entries from {:?}: {:?}", store, entries);
+        let channel = store
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut recent = RecentEntries {
+            channel,
+            store,
+            entries,
+            limits,
+        };
+        recent.retain(Some(limits.max_entries), limits.max_age);
+        recent
+    }
+
+    /// The name of the channel the entries were selected in.
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    /// The recent entries, most recent first.
+    pub fn entries(&self) -> &[RecentEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// How recently the given entry was selected (0 being the most recent
+    /// one), if it was.
+    pub fn rank(&self, entry: &Entry) -> Option<usize> {
+        let key = entry.key();
+        self.entries.iter().position(|e| e.key == key)
+    }
+
+    /// Record that the given entry was selected and persist the change,
+    /// dropping the entries beyond the configured limits.
+    pub fn record(&mut self, entry: &Entry) -> Result<()> {
+        let key = entry.key();
+        self.entries.retain(|e| e.key != key);
+        self.entries.insert(
+            0,
+            RecentEntry {
+                key,
+                selected_at: SystemTime::now(),
+            },
+        );
+        self.retain(Some(self.limits.max_entries), self.limits.max_age);
+        self.save()
+    }
+
+    /// Drop the duplicate entries, the ones beyond the given count and the
+    /// ones selected longer ago than the given age, then persist the
+    /// change. Returns how many entries were dropped.
+    pub fn prune(
+        &mut self,
+        max_entries: Option<usize>,
+        max_age: Option<Duration>,
+    ) -> Result<usize> {
+        let removed = self.retain(max_entries, max_age);
+        if removed > 0 {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Forget all the recent entries of the channel.
+    pub fn purge(&mut self) -> Result<()> {
+        self.entries.clear();
+        match std::fs::remove_file(&self.store) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e)?,
+            _ => Ok(()),
+        }
+    }
+
+    fn retain(
+        &mut self,
+        max_entries: Option<usize>,
+        max_age: Option<Duration>,
+    ) -> usize {
+        let before = self.entries.len();
+        let mut seen = std::collections::HashSet::new();
+        self.entries.retain(|e| {
+            seen.insert(e.key.clone())
+                && max_age.is_none_or(|max_age| e.age() <= max_age)
+        });
+        if let Some(max_entries) = max_entries {
+            self.entries.truncate(max_entries);
+        }
+        before - self.entries.len()
+    }
+
+    fn save(&self) -> Result<()> {
+        write_lines(
+            &self.store,
+            self.entries.iter().map(|entry| {
+                let timestamp = entry
+                    .selected_at
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                format!("{timestamp}\t{}", entry.key)
+            }),
+        )
+    }
+}
+
+/// Parse a line of a recent entries store, dating plain entry lines with
+/// the given time.
+fn parse_line(line: &str, default_time: SystemTime) -> RecentEntry {
+    line.split_once('\t')
+        .and_then(|(timestamp, key)| {
+            let seconds = timestamp.parse().ok()?;
+            Some(RecentEntry {
+                key: key.to_string(),
+                selected_at: UNIX_EPOCH + Duration::from_secs(seconds),
+            })
+        })
+        .unwrap_or_else(|| RecentEntry {
+            key: line.to_string(),
+            selected_at: default_time,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::PreviewType;
+
+    fn entry(name: &str) -> Entry {
+        Entry::new(name.to_string(), PreviewType::Files)
+    }
+
+    #[test]
+    fn test_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("files");
+
+        let mut recent =
+            RecentEntries::load(store.clone(), RecentLimits::default());
+        assert!(recent.is_empty());
+        recent.record(&entry("a")).unwrap();
+        recent.record(&entry("b")).unwrap();
+        recent.record(&entry("a")).unwrap();
+
+        let recent =
+            RecentEntries::load(store.clone(), RecentLimits::default());
+        assert_eq!(recent.channel(), "files");
+        assert_eq!(recent.rank(&entry("a")), Some(0));
+        assert_eq!(recent.rank(&entry("b")), Some(1));
+        assert_eq!(recent.rank(&entry("c")), None);
+    }
+
+    #[test]
+    fn test_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let day = 24 * 60 * 60;
+        std::fs::write(
+            dir.path().join("text"),
+            format!(
+                "{now}\ta\n{}\tb\n{}\ta\n{}\tc\nlegacy\n",
+                now - day,
+                now - 2 * day,
+                now - 10 * day
+            ),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("env"), "x\n").unwrap();
+
+        let mut all =
+            RecentEntries::load_dir(dir.path(), RecentLimits::default());
+        let names: Vec<&str> =
+            all.iter().map(RecentEntries::channel).collect();
+        assert_eq!(names, ["env", "text"]);
+        let text = &mut all[1];
+        let keys = |r: &RecentEntries| {
+            r.entries()
+                .iter()
+                .map(|e| e.key.clone())
+                .collect::<Vec<_>>()
+        };
+        // duplicates are dropped on load
+        assert_eq!(keys(text), ["a", "b", "c", "legacy"]);
+        assert!(text.entries()[3].age() < Duration::from_secs(60));
+
+        let removed = text
+            .prune(None, Some(Duration::from_secs(5 * day)))
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(text.prune(Some(2), None).unwrap(), 1);
+        let text = RecentEntries::load(
+            dir.path().join("text"),
+            RecentLimits::default(),
+        );
+        assert_eq!(keys(&text), ["a", "b"]);
+
+        all[0].purge().unwrap();
+        assert!(!dir.path().join("env").exists());
+        all[0].purge().unwrap();
+    }
+}
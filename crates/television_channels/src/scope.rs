@@ -0,0 +1,84 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use television_utils::project::{find_package_root, find_project_root};
+
+/// Where the files and text channels search by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    /// The root of the project the current directory belongs to (see
+    /// `find_project_root`), e.g. a whole monorepo.
+    #[default]
+    ProjectRoot,
+    /// The package of the project the current directory belongs to (see
+    /// `find_package_root`), e.g. a crate of a cargo workspace.
+    Package,
+    /// The current directory, as is.
+    CurrentDir,
+}
+
+impl Scope {
+    /// The scope after this one, from the widest to the narrowest.
+    pub fn next(self) -> Self {
+        match self {
+            Scope::ProjectRoot => Scope::Package,
+            Scope::Package => Scope::CurrentDir,
+            Scope::CurrentDir => Scope::ProjectRoot,
+        }
+    }
+
+    /// The directory the files and text channels search by default within
+    /// this scope, falling back to the current directory outside of
+    /// projects.
+    pub fn search_root(self) -> PathBuf {
+        let current_dir = std::env::current_dir().unwrap();
+        let project_root = find_project_root(&current_dir);
+        match self {
+            Scope::ProjectRoot => project_root,
+            // never wider than the project (e.g. below a `.tv-root` marker)
+            Scope::Package => find_package_root(&current_dir)
+                .filter(|package| {
+                    project_root
+                        .as_ref()
+                        .is_none_or(|project| package.starts_with(project))
+                })
+                .or(project_root),
+            Scope::CurrentDir => None,
+        }
+        .unwrap_or(current_dir)
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scope::ProjectRoot => write!(f, "project"),
+            Scope::Package => write!(f, "package"),
+            Scope::CurrentDir => write!(f, "current directory"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next() {
+        let scopes: Vec<Scope> =
+            std::iter::successors(Some(Scope::default()), |s| Some(s.next()))
+                .take(4)
+                .collect();
+        assert_eq!(
+            scopes,
+            [
+                Scope::ProjectRoot,
+                Scope::Package,
+                Scope::CurrentDir,
+                Scope::ProjectRoot
+            ]
+        );
+    }
+}
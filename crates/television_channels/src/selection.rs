@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use crate::entry::{Entry, EntryRef};
+
+/// The entries selected (e.g. with tab) to be output together.
+///
+/// Entries are identified by their name and line number, so that they stay
+/// selected whatever the pattern, and are output in the order they were
+/// selected in.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    entries: Vec<Entry>,
+    keys: HashSet<String>,
+}
+
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn contains<'a>(&self, entry: impl Into<EntryRef<'a>>) -> bool {
+        self.keys.contains(&entry.into().key())
+    }
+
+    /// Select the given entry, or deselect it if it already is, returning
+    /// whether it is now selected.
+    pub fn toggle(&mut self, entry: &Entry) -> bool {
+        let key = entry.key();
+        if self.keys.remove(&key) {
+            self.entries.retain(|selected| selected.key() != key);
+            false
+        } else {
+            self.keys.insert(key);
+            // match ranges only make sense for the pattern they matched
+            let mut entry = entry.clone();
+            entry.name_match_ranges = None;
+            entry.value_match_ranges = None;
+            self.entries.push(entry);
+            true
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.keys.clear();
+    }
+
+    /// The selected entries, in the order they were selected in.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::PreviewType;
+
+    #[test]
+    fn test_toggle() {
+        let mut selection = Selection::default();
+        let main = Entry::new("main.rs".to_string(), PreviewType::Files)
+            .with_name_match_ranges(vec![(0, 4)]);
+        let line = Entry::new("main.rs".to_string(), PreviewType::Files)
+            .with_line_number(3);
+        assert!(selection.toggle(&line));
+        assert!(selection.toggle(&main));
+        assert!(!selection.contains(
+            &Entry::new("main.rs".to_string(), PreviewType::Files)
+                .with_line_number(4)
+        ));
+        // the same entry matched by another pattern
+        assert!(selection
+            .contains(&Entry::new("main.rs".to_string(), PreviewType::Files)));
+        assert_eq!(selection.entries()[1].name_match_ranges, None);
+
+        assert!(!selection.toggle(&line));
+        assert_eq!(selection.len(), 1);
+        assert_eq!(selection.entries()[0].name, "main.rs");
+    }
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Whether the channels of a picker may start their heavy work (walking
+/// directories, spawning commands, ...).
+///
+/// Clones share the same state, the channels being handed one along with
+/// their context.
+#[derive(Debug, Clone)]
+pub struct Startup {
+    ready: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Startup {
+    fn default() -> Self {
+        Startup {
+            ready: Arc::new(watch::channel(true).0),
+        }
+    }
+}
+
+impl Startup {
+    /// Hold the heavy work of the channels created from now on until
+    /// `release` is called.
+    ///
+    /// This is meant to be called at startup, so that the UI can be drawn
+    /// right away while the entries of the initial channel are streamed in
+    /// afterwards.
+    pub fn hold(&self) {
+        self.ready.send_replace(false);
+    }
+
+    /// Let the channels start their heavy work (e.g. once the first frame
+    /// has been drawn).
+    pub fn release(&self) {
+        self.ready
+            .send_if_modified(|ready| !std::mem::replace(ready, true));
+    }
+
+    /// Wait until the channels are allowed to start their heavy work.
+    pub(crate) async fn ready(&self) {
+        let _ = self.ready.subscribe().wait_for(|ready| *ready).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hold_and_release() {
+        let startup = Startup::default();
+        startup.hold();
+        let held = startup.clone();
+        let waiting = tokio::spawn(async move { held.ready().await });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        startup.release();
+        waiting.await.unwrap();
+        // no more waiting once released
+        startup.ready().await;
+    }
+}
@@ -0,0 +1,67 @@
+//! The files the channels persist their state in (pins, annotations, hidden
+//! and recent entries), one line per entry.
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+
+/// Replace the contents of the store at `path` with the given lines.
+///
+/// The lines are written to a temporary file next to the store, which then
+/// replaces it, so that the store is never left half written (e.g. if `tv`
+/// is killed while saving it) nor read half written by another instance.
+pub fn write_lines<I>(path: &Path, lines: I) -> Result<()>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut content = String::new();
+    for line in lines {
+        content.push_str(line.as_ref());
+        content.push('\n');
+    }
+    let temp = temp_path(path);
+    let written = std::fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(content.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| std::fs::rename(&temp, path)) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// A hidden file next to the store, distinct for each `tv` process.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.{}.tmp", std::process::id()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("pins").join("files");
+
+        write_lines(&store, ["README.md", "LICENSE"]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&store).unwrap(),
+            "README.md\nLICENSE\n"
+        );
+
+        write_lines(&store, Vec::<String>::new()).unwrap();
+        assert_eq!(std::fs::read_to_string(&store).unwrap(), "");
+        // no temporary file is left behind
+        assert_eq!(
+            std::fs::read_dir(store.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+}
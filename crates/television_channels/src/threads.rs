@@ -0,0 +1,8 @@
+use television_utils::threads::default_num_threads;
+
+/// The default number of matcher worker threads for channels that may list
+/// a very large number of entries (e.g. files or stdin), based on the
+/// available parallelism.
+pub fn large_channel_default() -> usize {
+    default_num_threads().get()
+}
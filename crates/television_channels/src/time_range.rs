@@ -0,0 +1,242 @@
+use std::fmt::Display;
+use std::process::Command;
+
+use jiff::civil::{Date, DateTime};
+use jiff::Zoned;
+use television_utils::shell::shell_command;
+use television_utils::strings::shell_quote;
+
+use crate::filters::parse_age;
+
+/// The log commands taking the bounds of a time range as `--since` and
+/// `--until` options, along with the format of the timestamps they accept.
+const LOG_COMMANDS: [(&str, &str); 3] = [
+    ("journalctl", "%Y-%m-%d %H:%M:%S"),
+    ("docker logs", "%Y-%m-%dT%H:%M:%S%:z"),
+    ("podman logs", "%Y-%m-%dT%H:%M:%S%:z"),
+];
+
+/// The time range the entries of the channels listing the lines printed by
+/// a command (e.g. `journalctl -f` in the external channel) are restricted
+/// to, unbounded on the sides it has no bound.
+///
+/// Its bounds are kept as written (e.g. `2h`), so that relative ones are
+/// relative to when the command runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    since: Option<String>,
+    until: Option<String>,
+}
+
+impl TimeRange {
+    /// The time range between the given bounds (see `parse_time`), where
+    /// empty bounds are left out.
+    ///
+    /// # Errors
+    /// If one of the bounds isn't valid, or if the range ends before it
+    /// starts.
+    pub fn new(since: &str, until: &str) -> Result<Self, String> {
+        let bound = |value: &str| {
+            let value = value.trim();
+            if value.is_empty() {
+                Ok(None)
+            } else if parse_time(value, &Zoned::now()).is_some() {
+                Ok(Some(value.to_string()))
+            } else {
+                Err(format!(
+                    "invalid time `{value}` (e.g. `2h`, `yesterday` or \
+                     `2024-11-30 14:00`)"
+                ))
+            }
+        };
+        let range = Self {
+            since: bound(since)?,
+            until: bound(until)?,
+        };
+        if let (Some(since), Some(until)) = range.resolve(&Zoned::now()) {
+            if since > until {
+                return Err(format!(
+                    "the time range ends ({}) before it starts ({})",
+                    range.until.unwrap_or_default(),
+                    range.since.unwrap_or_default()
+                ));
+            }
+        }
+        Ok(range)
+    }
+
+    /// Whether the range has no bounds, i.e. doesn't restrict anything.
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn since(&self) -> Option<&str> {
+        self.since.as_deref()
+    }
+
+    pub fn until(&self) -> Option<&str> {
+        self.until.as_deref()
+    }
+
+    /// The shell command running the given one within this time range.
+    ///
+    /// The bounds are passed as options to the known log commands
+    /// (`journalctl`, `docker logs` and `podman logs`), and to any command
+    /// as the `TV_SINCE` and `TV_UNTIL` environment variables (RFC 3339
+    /// timestamps), e.g. for `journalctl --since "$TV_SINCE"` in a cable
+    /// channel wrapping it in a pipeline.
+    pub fn shell_command(&self, command: &str) -> Command {
+        let (since, until) = self.resolve(&Zoned::now());
+        let mut cmd = shell_command(&with_options(
+            command,
+            since.as_ref(),
+            until.as_ref(),
+        ));
+        for (name, bound) in [("TV_SINCE", &since), ("TV_UNTIL", &until)] {
+            if let Some(bound) = bound {
+                cmd.env(name, bound.timestamp().to_string());
+            }
+        }
+        cmd
+    }
+
+    /// The bounds of the range at the given time.
+    fn resolve(&self, now: &Zoned) -> (Option<Zoned>, Option<Zoned>) {
+        let resolve =
+            |bound: &Option<String>| parse_time(bound.as_deref()?, now);
+        (resolve(&self.since), resolve(&self.until))
+    }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.since, &self.until) {
+            (Some(since), Some(until)) => {
+                write!(f, "since {since} until {until}")
+            }
+            (Some(since), None) => write!(f, "since {since}"),
+            (None, Some(until)) => write!(f, "until {until}"),
+            (None, None) => write!(f, "any time"),
+        }
+    }
+}
+
+/// Parse a bound of a time range at the given time: `now`, `today` or
+/// `yesterday` (at midnight), a duration ago (e.g. `2h` or `2h ago`, see
+/// `parse_age`), a date (e.g. `2024-11-30`, at midnight) or a date and time
+/// (e.g. `2024-11-30 14:00`), in the time zone of the given time.
+pub fn parse_time(value: &str, now: &Zoned) -> Option<Zoned> {
+    let value = value.trim();
+    match value {
+        "now" => return Some(now.clone()),
+        "today" => return now.start_of_day().ok(),
+        "yesterday" => return now.yesterday().ok()?.start_of_day().ok(),
+        _ => {}
+    }
+    let age = value.strip_suffix("ago").map_or(value, str::trim_end);
+    if let Some(age) = parse_age(age) {
+        return now.checked_sub(age).ok();
+    }
+    let datetime = value
+        .parse::<DateTime>()
+        .or_else(|_| value.parse::<Date>().map(|date| date.at(0, 0, 0, 0)))
+        .ok()?;
+    datetime.to_zoned(now.time_zone().clone()).ok()
+}
+
+/// The given command with the given bounds passed as options, if it's one
+/// of the known log commands.
+fn with_options(
+    command: &str,
+    since: Option<&Zoned>,
+    until: Option<&Zoned>,
+) -> String {
+    let trimmed = command.trim_start();
+    let Some((name, rest, format)) =
+        LOG_COMMANDS.iter().find_map(|(name, format)| {
+            let rest = trimmed.strip_prefix(name)?;
+            (rest.is_empty() || rest.starts_with(char::is_whitespace))
+                .then_some((name, rest, format))
+        })
+    else {
+        return command.to_string();
+    };
+    let mut options = String::new();
+    for (option, bound) in [("--since", since), ("--until", until)] {
+        if let Some(bound) = bound {
+            options.push_str(&format!(
+                " {option} {}",
+                shell_quote(&bound.strftime(format).to_string())
+            ));
+        }
+    }
+    format!("{name}{options}{rest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+    use jiff::tz::{offset, TimeZone};
+
+    fn now() -> Zoned {
+        date(2024, 11, 30)
+            .at(14, 30, 0, 0)
+            .to_zoned(TimeZone::fixed(offset(1)))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = now();
+        let parse =
+            |value| parse_time(value, &now).map(|t| t.datetime().to_string());
+        assert_eq!(parse("now").as_deref(), Some("2024-11-30T14:30:00"));
+        assert_eq!(parse("today").as_deref(), Some("2024-11-30T00:00:00"));
+        assert_eq!(parse("yesterday").as_deref(), Some("2024-11-29T00:00:00"));
+        assert_eq!(parse("2h").as_deref(), Some("2024-11-30T12:30:00"));
+        assert_eq!(parse("1d ago").as_deref(), Some("2024-11-29T14:30:00"));
+        assert_eq!(
+            parse("2024-11-01 08:00").as_deref(),
+            Some("2024-11-01T08:00:00")
+        );
+        assert_eq!(
+            parse("2024-11-01").as_deref(),
+            Some("2024-11-01T00:00:00")
+        );
+        assert_eq!(parse("last tuesday"), None);
+    }
+
+    #[test]
+    fn test_time_range() {
+        assert!(TimeRange::new("", " ").unwrap().is_empty());
+        let range = TimeRange::new("2h", "now").unwrap();
+        assert_eq!(range.since(), Some("2h"));
+        assert_eq!(range.to_string(), "since 2h until now");
+        assert!(TimeRange::new("2 fortnights", "").is_err());
+        // ending before it starts
+        assert!(TimeRange::new("today", "yesterday").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_with_options() {
+        let now = now();
+        let since = parse_time("2h", &now).unwrap();
+        assert_eq!(
+            with_options("journalctl -f", Some(&since), Some(&now)),
+            "journalctl --since '2024-11-30 12:30:00' --until \
+             '2024-11-30 14:30:00' -f"
+        );
+        assert_eq!(
+            with_options("docker logs -f web", Some(&since), None),
+            "docker logs --since '2024-11-30T12:30:00+01:00' -f web"
+        );
+        // other commands read the environment variables
+        assert_eq!(
+            with_options("journalctlx -f", Some(&since), None),
+            "journalctlx -f"
+        );
+        assert_eq!(with_options("tail -f log", None, None), "tail -f log");
+    }
+}
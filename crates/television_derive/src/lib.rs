@@ -16,7 +16,7 @@ use quote::quote;
 ///     // ...
 /// }
 ///
-/// let television_channel: TelevisionChannel = CliTvChannel::Files.to_channel();
+/// let television_channel: TelevisionChannel = CliTvChannel::Files.to_channel(&ctx);
 ///
 /// assert!(matches!(television_channel, TelevisionChannel::Files(_)));
 /// ```
@@ -91,7 +91,9 @@ fn impl_cli_channel(ast: &syn::DeriveInput) -> TokenStream {
                 let inner_type = &fields.unnamed[0].ty;
 
                 quote! {
-                    CliTvChannel::#variant_name => TelevisionChannel::#variant_name(#inner_type::default())
+                    CliTvChannel::#variant_name => TelevisionChannel::#variant_name(
+                        <#inner_type as crate::context::FromContext>::from_context(ctx)
+                    )
                 }
             } else {
                 panic!("Enum variants should have exactly one unnamed field.");
@@ -105,7 +107,7 @@ fn impl_cli_channel(ast: &syn::DeriveInput) -> TokenStream {
         #cli_enum
 
         impl CliTvChannel {
-            pub fn to_channel(self) -> TelevisionChannel {
+            pub fn to_channel(self, ctx: &crate::context::Context) -> TelevisionChannel {
                 match self {
                     #(#arms),*
                 }
@@ -133,15 +135,17 @@ fn impl_cli_channel(ast: &syn::DeriveInput) -> TokenStream {
 ///     Text(text::Channel),
 /// }
 ///
-/// let mut channel = TelevisionChannel::Files(files::Channel::default());
+/// let mut channel = TelevisionChannel::Files(files::Channel::from_context(&ctx));
 ///
 /// // Use the `OnAir` trait methods directly on TelevisionChannel
 /// channel.find("pattern");
 /// let results = channel.results(10, 0);
+/// channel.for_each_result(0..10, &mut |entry| println!("{}", entry.name));
 /// let result = channel.get_result(0);
 /// let result_count = channel.result_count();
 /// let total_count = channel.total_count();
 /// let running = channel.running();
+/// channel.refresh();
 /// channel.shutdown();
 /// ```
 #[proc_macro_derive(Broadcast)]
@@ -195,6 +199,20 @@ fn impl_tv_channel(ast: &syn::DeriveInput) -> TokenStream {
                 }
             }
 
+            fn for_each_result(
+                &mut self,
+                range: std::ops::Range<u32>,
+                f: &mut dyn FnMut(&EntryRef<'_>),
+            ) {
+                match self {
+                    #(
+                        #enum_name::#variant_names(ref mut channel) => {
+                            channel.for_each_result(range, f)
+                        }
+                    )*
+                }
+            }
+
             fn get_result(&self, index: u32) -> Option<Entry> {
                 match self {
                     #(
@@ -235,6 +253,16 @@ fn impl_tv_channel(ast: &syn::DeriveInput) -> TokenStream {
                 }
             }
 
+            fn loading(&self) -> bool {
+                match self {
+                    #(
+                        #enum_name::#variant_names(ref channel) => {
+                            channel.loading()
+                        }
+                    )*
+                }
+            }
+
             fn shutdown(&self) {
                 match self {
                     #(
@@ -244,6 +272,36 @@ fn impl_tv_channel(ast: &syn::DeriveInput) -> TokenStream {
                     )*
                 }
             }
+
+            fn refresh(&mut self) {
+                match self {
+                    #(
+                        #enum_name::#variant_names(ref mut channel) => {
+                            channel.refresh()
+                        }
+                    )*
+                }
+            }
+
+            fn warnings(&self) -> Option<String> {
+                match self {
+                    #(
+                        #enum_name::#variant_names(ref channel) => {
+                            channel.warnings()
+                        }
+                    )*
+                }
+            }
+
+            fn memory_usage(&self) -> usize {
+                match self {
+                    #(
+                        #enum_name::#variant_names(ref channel) => {
+                            channel.memory_usage()
+                        }
+                    )*
+                }
+            }
         }
     };
 
@@ -303,13 +361,15 @@ fn impl_unit_channel(ast: &syn::DeriveInput) -> TokenStream {
         }
     };
 
-    // Generate Into<TelevisionChannel> implementation
+    // Generate the `to_channel` method, creating the channel from the context
     let into_impl = quote! {
-        impl Into<TelevisionChannel> for UnitChannel {
-            fn into(self) -> TelevisionChannel {
+        impl UnitChannel {
+            pub fn to_channel(self, ctx: &crate::context::Context) -> TelevisionChannel {
                 match self {
                     #(
-                        UnitChannel::#variant_names => TelevisionChannel::#variant_names(Default::default()),
+                        UnitChannel::#variant_names => TelevisionChannel::#variant_names(
+                            crate::context::FromContext::from_context(ctx)
+                        ),
                     )*
                 }
             }
@@ -369,3 +429,125 @@ fn impl_unit_channel(ast: &syn::DeriveInput) -> TokenStream {
 
     gen.into()
 }
+
+/// This macro generates the constructors and the `OnAir` trait
+/// implementation of a channel described by a `ChannelBuilder`.
+///
+/// The channel should be a newtype around a `BuiltChannel` and provide a
+/// `builder` associated function:
+///
+/// ```ignore
+/// use television_derive::FromBuilder;
+/// use crate::channels::builder::{BuiltChannel, ChannelBuilder};
+///
+/// #[derive(FromBuilder)]
+/// pub struct Channel(BuiltChannel<EnvVar>);
+///
+/// impl Channel {
+///     fn builder() -> ChannelBuilder<EnvVar> {
+///         ChannelBuilder::new("env", env_vars, |var| {
+///             (var.name.clone(), Some(var.value.clone()))
+///         })
+///     }
+/// }
+///
+/// let mut channel = Channel::new(&ctx);
+/// channel.find("pattern");
+/// ```
+#[proc_macro_derive(FromBuilder)]
+pub fn from_builder_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+
+    impl_from_builder(&ast)
+}
+
+fn impl_from_builder(ast: &syn::DeriveInput) -> TokenStream {
+    // Ensure the struct is a newtype
+    let is_newtype = matches!(
+        &ast.data,
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Unnamed(fields),
+            ..
+        }) if fields.unnamed.len() == 1
+    );
+    assert!(
+        is_newtype,
+        "#[derive(FromBuilder)] is only defined for newtype structs"
+    );
+
+    let name = &ast.ident;
+
+    let gen = quote! {
+        impl #name {
+            pub fn new(ctx: &crate::context::Context) -> Self {
+                Self(Self::builder().build(ctx))
+            }
+        }
+
+        impl crate::context::FromContext for #name {
+            fn from_context(ctx: &crate::context::Context) -> Self {
+                Self::new(ctx)
+            }
+        }
+
+        impl crate::channels::OnAir for #name {
+            fn find(&mut self, pattern: &str) {
+                self.0.find(pattern);
+            }
+
+            fn results(
+                &mut self,
+                num_entries: u32,
+                offset: u32,
+            ) -> Vec<crate::entry::Entry> {
+                self.0.results(num_entries, offset)
+            }
+
+            fn for_each_result(
+                &mut self,
+                range: std::ops::Range<u32>,
+                f: &mut dyn FnMut(&crate::entry::EntryRef<'_>),
+            ) {
+                self.0.for_each_result(range, f);
+            }
+
+            fn get_result(&self, index: u32) -> Option<crate::entry::Entry> {
+                self.0.get_result(index)
+            }
+
+            fn result_count(&self) -> u32 {
+                self.0.result_count()
+            }
+
+            fn total_count(&self) -> u32 {
+                self.0.total_count()
+            }
+
+            fn running(&self) -> bool {
+                self.0.running()
+            }
+
+            fn loading(&self) -> bool {
+                self.0.loading()
+            }
+
+            fn shutdown(&self) {
+                self.0.shutdown();
+            }
+
+            fn refresh(&mut self) {
+                self.0.refresh();
+            }
+
+            fn warnings(&self) -> Option<String> {
+                self.0.warnings()
+            }
+
+            fn memory_usage(&self) -> usize {
+                self.0.memory_usage()
+            }
+        }
+    };
+
+    gen.into()
+}
@@ -18,6 +18,9 @@ pub struct Config {
     pub prefer_prefix: bool,
     /// Whether to optimize for matching paths.
     pub match_paths: bool,
+    /// How items are pushed in bulk into the matcher (see
+    /// `Injector::chunked`).
+    pub chunking: Chunking,
 }
 
 impl Default for Config {
@@ -27,6 +30,7 @@ impl Default for Config {
             ignore_case: true,
             prefer_prefix: false,
             match_paths: false,
+            chunking: Chunking::default(),
         }
     }
 }
@@ -55,6 +59,32 @@ impl Config {
         self.match_paths = match_paths;
         self
     }
+
+    /// Set how items are pushed in bulk into the matcher.
+    pub fn chunking(mut self, chunking: Chunking) -> Self {
+        self.chunking = chunking;
+        self
+    }
+}
+
+/// How items are pushed in bulk into the matcher, so that mass injections
+/// (e.g. walking a huge directory tree) don't starve the matcher and the UI.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Chunking {
+    /// The number of items pushed between two yield points.
+    pub chunk_size: usize,
+    /// The number of items pushed but not yet taken into account by the
+    /// matcher above which pushing backs off, giving it time to catch up.
+    pub high_watermark: usize,
+}
+
+impl Default for Chunking {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1024,
+            high_watermark: 1024 * 1024,
+        }
+    }
 }
 
 impl From<&Config> for nucleo::Config {
@@ -1,3 +1,18 @@
+use std::mem::{size_of, size_of_val};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nucleo::{Utf32Str, Utf32String};
+
+use crate::matcher::config::Chunking;
+
+/// How long pushing backs off at a time when the matcher is behind.
+const BACKOFF: Duration = Duration::from_millis(1);
+/// The longest pushing backs off after a chunk, so that producers don't
+/// stall when the matcher isn't ticked (e.g. before the UI starts).
+const MAX_BACKOFF: Duration = Duration::from_millis(20);
+
 /// An injector that can be used to push items of type `I` into the fuzzy matcher.
 ///
 /// This is a wrapper around the `Injector` type from the `Nucleo` fuzzy matcher.
@@ -11,14 +26,31 @@ where
 {
     /// The inner `Injector` from the `Nucleo` fuzzy matcher.
     inner: nucleo::Injector<I>,
+    chunking: Chunking,
+    /// The number of items taken into account by the matcher as of its last
+    /// tick.
+    seen_item_count: Arc<AtomicU32>,
+    /// The approximate size of the items pushed so far (see
+    /// `Matcher::memory_usage`).
+    haystack_size: Arc<AtomicUsize>,
 }
 
 impl<I> Injector<I>
 where
     I: Sync + Send + Clone + 'static,
 {
-    pub fn new(inner: nucleo::Injector<I>) -> Self {
-        Self { inner }
+    pub fn new(
+        inner: nucleo::Injector<I>,
+        chunking: Chunking,
+        seen_item_count: Arc<AtomicU32>,
+        haystack_size: Arc<AtomicUsize>,
+    ) -> Self {
+        Self {
+            inner,
+            chunking,
+            seen_item_count,
+            haystack_size,
+        }
     }
 
     /// Push an item into the fuzzy matcher.
@@ -42,8 +74,124 @@ where
     /// ```
     pub fn push<F>(&self, item: I, f: F)
     where
-        F: FnOnce(&I, &mut [nucleo::Utf32String]),
+        F: FnOnce(&I, &mut [Utf32String]),
     {
-        self.inner.push(item, f);
+        self.inner.push(item, |item, cols| {
+            f(item, cols);
+            self.haystack_size
+                .fetch_add(item_size::<I>(cols), Ordering::Relaxed);
+        });
+    }
+
+    /// Push many items from the current thread by chunks (see `Chunking`),
+    /// yielding between them.
+    ///
+    /// # Example
+    /// ```
+    /// use television_fuzzy::matcher::{config::Config, Matcher};
+    ///
+    /// let matcher = Matcher::new(Config::default());
+    /// let mut chunked = matcher.injector().chunked();
+    /// for i in 0..10_000 {
+    ///     chunked.push(i.to_string(), |s, cols| cols[0] = s.as_str().into());
+    /// }
+    /// ```
+    pub fn chunked(self) -> Chunked<I> {
+        Chunked {
+            injector: self,
+            pushed: 0,
+        }
+    }
+
+    /// The number of items pushed but not yet taken into account by the
+    /// matcher.
+    fn pending(&self) -> u32 {
+        self.inner
+            .injected_items()
+            .saturating_sub(self.seen_item_count.load(Ordering::Relaxed))
+    }
+}
+
+/// The approximate size of an item and of the text it's matched against,
+/// not counting what the item itself owns on the heap.
+fn item_size<I>(cols: &[Utf32String]) -> usize {
+    size_of::<I>()
+        + cols
+            .iter()
+            .map(|col| match col.slice(..) {
+                Utf32Str::Ascii(bytes) => bytes.len(),
+                Utf32Str::Unicode(chars) => size_of_val(chars),
+            })
+            .sum::<usize>()
+}
+
+/// Pushes items through an injector by chunks, giving the matcher (and
+/// whatever else runs on this thread's CPU) a chance to run between them,
+/// and backing off while the matcher is too far behind.
+pub struct Chunked<I>
+where
+    I: Sync + Send + Clone + 'static,
+{
+    injector: Injector<I>,
+    /// The number of items pushed in the current chunk.
+    pushed: usize,
+}
+
+impl<I> Chunked<I>
+where
+    I: Sync + Send + Clone + 'static,
+{
+    /// Push an item into the fuzzy matcher, yielding if it ends a chunk.
+    pub fn push<F>(&mut self, item: I, f: F)
+    where
+        F: FnOnce(&I, &mut [Utf32String]),
+    {
+        self.injector.push(item, f);
+        self.pushed += 1;
+        if self.pushed >= self.injector.chunking.chunk_size {
+            self.pushed = 0;
+            self.yield_now();
+        }
+    }
+
+    fn yield_now(&self) {
+        std::thread::yield_now();
+        let high_watermark =
+            u32::try_from(self.injector.chunking.high_watermark)
+                .unwrap_or(u32::MAX);
+        let mut backoff = Duration::ZERO;
+        while backoff < MAX_BACKOFF && self.injector.pending() > high_watermark
+        {
+            std::thread::sleep(BACKOFF);
+            backoff += BACKOFF;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matcher::{config::Config, Matcher};
+
+    use super::*;
+
+    #[test]
+    fn test_chunked_push() {
+        let mut matcher = Matcher::new(Config::default().chunking(Chunking {
+            chunk_size: 10,
+            high_watermark: 100,
+        }));
+        let injector = matcher.injector();
+        let mut chunked = injector.clone().chunked();
+        for i in 0..200 {
+            chunked.push(i, |i, cols| cols[0] = i.to_string().into());
+        }
+        // the matcher was never ticked
+        assert_eq!(injector.pending(), 200);
+
+        while matcher.status.running || matcher.total_item_count < 200 {
+            matcher.tick();
+            let _ = matcher.results(1, 0);
+        }
+        assert_eq!(injector.pending(), 0);
     }
 }
@@ -18,3 +18,30 @@ where
     /// The indices of the matched characters.
     pub match_indices: Vec<(u32, u32)>,
 }
+
+/// A matched item borrowed from the matcher's snapshot, along with the
+/// dimension against which it was matched and the indices of the matched
+/// characters (see `Matcher::for_each_result`).
+#[derive(Debug)]
+pub struct MatchedItemRef<'a, I> {
+    /// The matched item.
+    pub inner: &'a I,
+    /// The dimension against which the item was matched (as a string).
+    pub matched_string: &'a str,
+    /// The indices of the matched characters.
+    pub match_indices: &'a [(u32, u32)],
+}
+
+impl<I> MatchedItemRef<'_, I>
+where
+    I: Sync + Send + Clone + 'static,
+{
+    /// An owned copy of the matched item.
+    pub fn to_matched_item(&self) -> MatchedItem<I> {
+        MatchedItem {
+            inner: self.inner.clone(),
+            matched_string: self.matched_string.to_string(),
+            match_indices: self.match_indices.to_vec(),
+        }
+    }
+}
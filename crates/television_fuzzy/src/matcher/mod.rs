@@ -1,14 +1,24 @@
 use injector::Injector;
+use nucleo::Utf32Str;
+pub use nucleo::Utf32String;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use crate::matcher::{
-    config::Config, lazy::MATCHER, matched_item::MatchedItem,
+    config::{Chunking, Config},
+    lazy::MATCHER,
+    matched_item::{MatchedItem, MatchedItemRef},
+    query::Query,
 };
 
 pub mod config;
 pub mod injector;
 pub mod lazy;
 pub mod matched_item;
+pub mod query;
 
 const MATCHER_TICK_TIMEOUT: u64 = 2;
 
@@ -32,6 +42,28 @@ impl From<nucleo::Status> for Status {
     }
 }
 
+/// Whether an item passes a filter.
+pub type Predicate<I> = Arc<dyn Fn(&I) -> bool + Send + Sync>;
+
+/// A filter applied to the items matching the pattern (e.g. on their
+/// metadata), on top of the fuzzy matching.
+#[derive(Clone)]
+pub struct Filter<I> {
+    /// The part of the query the filter was parsed from, used to tell
+    /// whether it changed.
+    pub query: String,
+    /// Whether an item passes the filter.
+    pub predicate: Predicate<I>,
+}
+
+impl<I> std::fmt::Debug for Filter<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Filter")
+            .field("query", &self.query)
+            .finish()
+    }
+}
+
 /// A fuzzy matcher that can be used to match items of type `I`.
 ///
 /// `I` should be `Sync`, `Send`, `Clone`, and `'static`.
@@ -54,6 +86,44 @@ where
     pub status: Status,
     /// The last pattern that was matched against.
     pub last_pattern: String,
+    /// The last pattern, parsed.
+    last_query: Query,
+    /// The filter applied to the matched items, if any.
+    filter: Option<Filter<I>>,
+    /// Whether an item was removed by `update_items`: nucleo can't remove
+    /// items, so they're hidden from the results until the next restart.
+    removed: Option<Predicate<I>>,
+    /// The number of items hidden by `removed`.
+    removed_count: u32,
+    /// The indices (among the matched items) of the items passing the
+    /// filter and not removed, or `None` if they need to be computed again.
+    filtered: Option<Vec<u32>>,
+    /// Changes whenever the results may have changed, i.e. on a new
+    /// snapshot, pattern or filter.
+    generation: u64,
+    /// The results last visited by `for_each_result`.
+    visited: Option<VisitedResults>,
+    /// How the injectors push items in bulk.
+    chunking: Chunking,
+    /// The number of items taken into account by the matcher as of the last
+    /// tick, shared with the injectors.
+    seen_item_count: Arc<AtomicU32>,
+    /// The approximate size of the items, shared with the injectors.
+    haystack_size: Arc<AtomicUsize>,
+}
+
+/// The results in a range, visited at a given generation of the matcher,
+/// along with the indices of their matched characters, which are costly to
+/// compute and reused as long as neither changes (e.g. from one frame to
+/// the next).
+#[derive(Debug)]
+struct VisitedResults {
+    generation: u64,
+    range: Range<u32>,
+    /// The index of each visited result and the range of its matched
+    /// characters in `match_indices`.
+    results: Vec<(u32, Range<usize>)>,
+    match_indices: Vec<(u32, u32)>,
 }
 
 impl<I> Matcher<I>
@@ -73,6 +143,16 @@ where
             matched_item_count: 0,
             status: Status::default(),
             last_pattern: String::new(),
+            last_query: Query::default(),
+            filter: None,
+            removed: None,
+            removed_count: 0,
+            filtered: None,
+            generation: 0,
+            visited: None,
+            chunking: config.chunking,
+            seen_item_count: Arc::default(),
+            haystack_size: Arc::default(),
         }
     }
 
@@ -80,7 +160,62 @@ where
     ///
     /// This should be called periodically to update the state of the matcher.
     pub fn tick(&mut self) {
-        self.status = self.inner.tick(MATCHER_TICK_TIMEOUT).into();
+        let status = self.inner.tick(MATCHER_TICK_TIMEOUT);
+        if status.changed {
+            self.filtered = None;
+            self.generation = self.generation.wrapping_add(1);
+        }
+        self.status = status.into();
+        self.seen_item_count
+            .store(self.inner.snapshot().item_count(), Ordering::Relaxed);
+    }
+
+    /// Set the filter applied to the items matching the pattern.
+    ///
+    /// Setting the same filter (i.e. parsed from the same query) again does
+    /// nothing.
+    pub fn set_filter(&mut self, filter: Option<Filter<I>>) {
+        let query =
+            |f: &Option<Filter<I>>| f.as_ref().map(|f| f.query.clone());
+        if query(&filter) != query(&self.filter) {
+            self.filter = filter;
+            self.filtered = None;
+            self.generation = self.generation.wrapping_add(1);
+        }
+    }
+
+    /// Compute the indices of the matched items passing the filter and not
+    /// removed, if some items are filtered out or removed and the indices
+    /// aren't up to date.
+    fn apply_filter(&mut self) {
+        if self.filter.is_none() && self.removed.is_none() {
+            self.filtered = None;
+            return;
+        }
+        if self.filtered.is_some() {
+            return;
+        }
+        let filter = self.filter.as_ref().map(|f| &f.predicate);
+        let removed = self.removed.as_ref();
+        let snapshot = self.inner.snapshot();
+        self.filtered = Some(
+            (0..)
+                .zip(snapshot.matched_items(..))
+                .filter(|(_, item)| {
+                    filter.is_none_or(|f| f(item.data))
+                        && !removed.is_some_and(|r| r(item.data))
+                })
+                .map(|(i, _)| i)
+                .collect(),
+        );
+    }
+
+    /// The index among nucleo's matched items of the given result.
+    fn matched_index(&self, index: u32) -> Option<u32> {
+        match &self.filtered {
+            Some(filtered) => filtered.get(index as usize).copied(),
+            None => Some(index),
+        }
     }
 
     /// Get an injector that can be used to push items into the fuzzy matcher.
@@ -102,25 +237,111 @@ where
     /// );
     /// ```
     pub fn injector(&self) -> Injector<I> {
-        Injector::new(self.inner.injector())
+        Injector::new(
+            self.inner.injector(),
+            self.chunking,
+            Arc::clone(&self.seen_item_count),
+            Arc::clone(&self.haystack_size),
+        )
     }
 
-    /// Find items that match the given pattern.
+    /// Remove all the items, keeping the current pattern.
+    ///
+    /// Injectors created before the restart are disconnected: items pushed
+    /// through them are ignored.
+    pub fn restart(&mut self) {
+        self.inner.restart(false);
+        self.removed = None;
+        self.removed_count = 0;
+        self.filtered = None;
+        // the disconnected injectors keep adding to the previous count
+        self.haystack_size = Arc::default();
+    }
+
+    /// Update the items to the given ones (e.g. after re-running the source
+    /// of a channel), keeping the current pattern.
+    ///
+    /// The update is incremental: only the new items are pushed, and the
+    /// items that are gone are hidden from the results, which preserves the
+    /// current results (and hence the selection) while the new items are
+    /// being matched. Hidden items still take up memory though, so once
+    /// they outnumber the given items, the matcher is restarted with the
+    /// given items only.
+    ///
+    /// Returns whether the matcher was restarted.
+    pub fn update_items<F>(&mut self, items: Vec<I>, fill_columns: F) -> bool
+    where
+        I: Eq + Hash,
+        F: Fn(&I, &mut [Utf32String]),
+    {
+        let injector = self.inner.injector();
+        let injected: Vec<&I> = (0..injector.injected_items())
+            .filter_map(|i| injector.get(i))
+            .map(|item| item.data)
+            .collect();
+        let current: HashSet<&I> = injected.iter().copied().collect();
+        let new: HashSet<&I> = items.iter().collect();
+        let removed: HashSet<I> =
+            current.difference(&new).map(|&item| item.clone()).collect();
+        if removed.len() > new.len() {
+            self.restart();
+            let pusher = self.injector();
+            for item in items {
+                pusher.push(item, &fill_columns);
+            }
+            return true;
+        }
+
+        let removed_count = injected
+            .iter()
+            .filter(|&&item| removed.contains(item))
+            .count();
+        let added: Vec<I> = items
+            .iter()
+            .filter(|item| !current.contains(item))
+            .cloned()
+            .collect();
+        self.removed_count = u32::try_from(removed_count).unwrap();
+        self.removed = (!removed.is_empty()).then(|| {
+            Arc::new(move |item: &I| removed.contains(item)) as Predicate<I>
+        });
+        self.filtered = None;
+        self.generation = self.generation.wrapping_add(1);
+        let pusher = self.injector();
+        for item in added {
+            pusher.push(item, &fill_columns);
+        }
+        false
+    }
+
+    /// The approximate number of bytes taken by the items and the text
+    /// they're matched against, not counting what the items own on the heap
+    /// (e.g. the contents of a `String`).
+    pub fn memory_usage(&self) -> usize {
+        self.haystack_size.load(Ordering::Relaxed)
+    }
+
+    /// Find items that match the given pattern (see the `query` module for
+    /// its syntax).
     ///
     /// This should be called whenever the pattern changes.
     /// The `Matcher` will keep track of the last pattern and only reparse the
     /// pattern if it has changed, allowing for more efficient matching when
-    /// `self.last_pattern` is a prefix of the new `pattern`.
+    /// the new `pattern` only narrows the last one down (e.g. `foo` after
+    /// `fo`, but not `foo$` after `fo$`).
     pub fn find(&mut self, pattern: &str) {
         if pattern != self.last_pattern {
+            let query = Query::parse(pattern);
             self.inner.pattern.reparse(
                 0,
                 pattern,
                 nucleo::pattern::CaseMatching::Smart,
                 nucleo::pattern::Normalization::Smart,
-                pattern.starts_with(&self.last_pattern),
+                query.narrows(&self.last_query),
             );
             self.last_pattern = pattern.to_string();
+            self.last_query = query;
+            self.generation = self.generation.wrapping_add(1);
         }
     }
 
@@ -159,36 +380,122 @@ where
         num_entries: u32,
         offset: u32,
     ) -> Vec<MatchedItem<I>> {
+        let mut results = Vec::new();
+        self.for_each_result(
+            offset..offset.saturating_add(num_entries),
+            |item| {
+                results.push(item.to_matched_item());
+            },
+        );
+        results
+    }
+
+    /// Visit the matched items in the given range, borrowed from the
+    /// matcher's snapshot.
+    ///
+    /// This is what `results` does, without copying the items, which makes
+    /// it cheap enough to call on every frame.
+    ///
+    /// # Example
+    /// ```
+    /// use television_fuzzy::matcher::{config::Config, Matcher};
+    ///
+    /// let config = Config::default();
+    /// let mut matcher: Matcher<String> = Matcher::new(config);
+    /// matcher.find("some pattern");
+    ///
+    /// matcher.for_each_result(0..10, |item| {
+    ///     println!("{}: {:?}", item.matched_string, item.match_indices);
+    /// });
+    /// ```
+    pub fn for_each_result(
+        &mut self,
+        range: Range<u32>,
+        mut f: impl FnMut(MatchedItemRef<'_, I>),
+    ) {
+        self.apply_filter();
         let snapshot = self.inner.snapshot();
-        self.total_item_count = snapshot.item_count();
-        self.matched_item_count = snapshot.matched_item_count();
+        self.total_item_count =
+            snapshot.item_count().saturating_sub(self.removed_count);
+        self.matched_item_count = match &self.filtered {
+            Some(filtered) => u32::try_from(filtered.len()).unwrap(),
+            None => snapshot.matched_item_count(),
+        };
 
-        let mut col_indices = Vec::new();
-        let mut matcher = MATCHER.lock();
+        let range = range.start..range.end.min(self.matched_item_count);
+        let visited = match self.visited.take() {
+            Some(visited)
+                if visited.generation == self.generation
+                    && visited.range == range =>
+            {
+                visited
+            }
+            _ => self.visit_results(range),
+        };
 
-        snapshot
-            .matched_items(
-                offset..(num_entries + offset).min(self.matched_item_count),
-            )
-            .map(move |item| {
-                snapshot.pattern().column_pattern(0).indices(
-                    item.matcher_columns[0].slice(..),
-                    &mut matcher,
-                    &mut col_indices,
-                );
-                col_indices.sort_unstable();
-                col_indices.dedup();
-
-                let indices = col_indices.drain(..);
-
-                let matched_string = item.matcher_columns[0].to_string();
-                MatchedItem {
-                    inner: item.data.clone(),
-                    matched_string,
-                    match_indices: indices.map(|i| (i, i + 1)).collect(),
+        let snapshot = self.inner.snapshot();
+        // the characters of the items that aren't ASCII are stored as UTF-32
+        let mut unicode_string = String::new();
+        for (i, match_indices) in &visited.results {
+            let Some(item) = self
+                .matched_index(*i)
+                .and_then(|index| snapshot.get_matched_item(index))
+            else {
+                continue;
+            };
+            let matched_string = match item.matcher_columns[0].slice(..) {
+                // ASCII is valid UTF-8
+                Utf32Str::Ascii(bytes) => std::str::from_utf8(bytes).unwrap(),
+                Utf32Str::Unicode(chars) => {
+                    unicode_string.clear();
+                    unicode_string.extend(chars);
+                    unicode_string.as_str()
                 }
-            })
-            .collect()
+            };
+            f(MatchedItemRef {
+                inner: item.data,
+                matched_string,
+                match_indices: &visited.match_indices[match_indices.clone()],
+            });
+        }
+        self.visited = Some(visited);
+    }
+
+    /// Compute the indices of the matched characters of the results in the
+    /// given range.
+    fn visit_results(&self, range: Range<u32>) -> VisitedResults {
+        let snapshot = self.inner.snapshot();
+        let mut visited = VisitedResults {
+            generation: self.generation,
+            range: range.clone(),
+            results: Vec::with_capacity(range.len()),
+            match_indices: Vec::new(),
+        };
+        let mut col_indices = Vec::new();
+        let mut matcher = MATCHER.lock();
+        for i in range {
+            let Some(item) = self
+                .matched_index(i)
+                .and_then(|index| snapshot.get_matched_item(index))
+            else {
+                continue;
+            };
+            snapshot.pattern().column_pattern(0).indices(
+                item.matcher_columns[0].slice(..),
+                &mut matcher,
+                &mut col_indices,
+            );
+            col_indices.sort_unstable();
+            col_indices.dedup();
+            let start = visited.match_indices.len();
+            visited
+                .match_indices
+                .extend(col_indices.drain(..).map(|i| (i, i + 1)));
+            visited
+                .results
+                .push((i, start..visited.match_indices.len()));
+        }
+        visited
     }
 
     /// Get a single matched item.
@@ -208,6 +515,7 @@ where
     /// ```
     pub fn get_result(&self, index: u32) -> Option<MatchedItem<I>> {
         let snapshot = self.inner.snapshot();
+        let index = self.matched_index(index)?;
         snapshot.get_matched_item(index).map(|item| {
             let matched_string = item.matcher_columns[0].to_string();
             MatchedItem {
@@ -218,3 +526,178 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::ptr_arg)]
+    fn fill_columns(item: &String, cols: &mut [Utf32String]) {
+        cols[0] = item.clone().into();
+    }
+
+    fn items(items: &[&str]) -> Vec<String> {
+        items.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_update_items() {
+        let mut matcher = Matcher::new(Config::default());
+        assert!(!matcher.update_items(items(&["a", "b"]), fill_columns));
+        // additions only
+        assert!(!matcher.update_items(items(&["a", "b", "c"]), fill_columns));
+        matcher.tick();
+        matcher.results(10, 0);
+        assert_eq!(matcher.total_item_count, 3);
+        let item_size = std::mem::size_of::<String>() + 1;
+        assert_eq!(matcher.memory_usage(), 3 * item_size);
+        // removal, hiding the removed item
+        assert!(!matcher.update_items(items(&["a", "c"]), fill_columns));
+        matcher.tick();
+        matcher.results(10, 0);
+        assert_eq!(matcher.total_item_count, 2);
+        assert_eq!(matcher.memory_usage(), 3 * item_size);
+        // the removed item comes back
+        assert!(!matcher.update_items(items(&["a", "b", "c"]), fill_columns));
+        matcher.results(10, 0);
+        assert_eq!(matcher.total_item_count, 3);
+        // more items removed than left
+        assert!(matcher.update_items(items(&["d"]), fill_columns));
+        matcher.tick();
+        matcher.results(10, 0);
+        assert_eq!(matcher.total_item_count, 1);
+        assert_eq!(matcher.memory_usage(), item_size);
+    }
+
+    #[test]
+    fn test_update_items_keeps_selection() {
+        let mut matcher = Matcher::new(Config::default());
+        matcher.update_items(items(&["a", "b", "c", "d"]), fill_columns);
+        while matcher.status.running || matcher.total_item_count < 4 {
+            matcher.tick();
+            matcher.results(10, 0);
+        }
+        let selected = 1;
+        assert_eq!(matcher.get_result(selected).unwrap().inner, "b");
+
+        // an item after the selection is removed, and one is added
+        let restarted =
+            matcher.update_items(items(&["a", "b", "d", "e"]), fill_columns);
+        assert!(!restarted);
+        // the results are up to date without waiting for the matcher
+        matcher.results(10, 0);
+        assert_eq!(matcher.get_result(selected).unwrap().inner, "b");
+        while matcher.status.running || matcher.total_item_count < 4 {
+            matcher.tick();
+            matcher.results(10, 0);
+        }
+        let results: Vec<String> = matcher
+            .results(10, 0)
+            .into_iter()
+            .map(|i| i.inner)
+            .collect();
+        assert_eq!(results, items(&["a", "b", "d", "e"]));
+        assert_eq!(matcher.get_result(selected).unwrap().inner, "b");
+    }
+
+    #[test]
+    fn test_for_each_result() {
+        let mut matcher = Matcher::new(Config::default());
+        matcher.update_items(
+            items(&["main.rs", "lib.rs", "été.rs"]),
+            fill_columns,
+        );
+        matcher.find("s");
+        while matcher.status.running || matcher.total_item_count < 3 {
+            matcher.tick();
+            matcher.results(10, 0);
+        }
+        let mut visited = Vec::new();
+        matcher.for_each_result(1..10, |item| {
+            visited.push((
+                item.matched_string.to_string(),
+                item.match_indices.to_vec(),
+            ));
+        });
+        let results: Vec<_> = matcher
+            .results(9, 1)
+            .into_iter()
+            .map(|item| (item.matched_string, item.match_indices))
+            .collect();
+        assert_eq!(visited.len(), 2);
+        assert_eq!(visited, results);
+        assert!(visited.iter().any(|(s, _)| s == "été.rs"));
+
+        // the match indices are reused until the results change
+        let match_indices = |matcher: &Matcher<String>| {
+            matcher.visited.as_ref().unwrap().match_indices.as_ptr()
+        };
+        let before = match_indices(&matcher);
+        matcher.for_each_result(1..10, |_| {});
+        assert_eq!(match_indices(&matcher), before);
+        matcher.find("r");
+        while matcher.status.running || matcher.generation == 0 {
+            matcher.tick();
+        }
+        let mut visited = Vec::new();
+        matcher.for_each_result(0..1, |item| {
+            visited.push(item.match_indices.to_vec());
+        });
+        assert_eq!(visited, [vec![(5, 6)]]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut matcher = Matcher::new(Config::default());
+        matcher.update_items(
+            items(&["main.rs", "lib.rs", "README.md"]),
+            fill_columns,
+        );
+        matcher.set_filter(Some(Filter {
+            query: "ext:rs".to_string(),
+            predicate: Arc::new(|item: &String| item.ends_with(".rs")),
+        }));
+        while matcher.status.running || matcher.total_item_count < 3 {
+            matcher.tick();
+            matcher.results(10, 0);
+        }
+        let results: Vec<String> = matcher
+            .results(10, 0)
+            .into_iter()
+            .map(|i| i.inner)
+            .collect();
+        assert_eq!(results, items(&["main.rs", "lib.rs"]));
+        assert_eq!(matcher.matched_item_count, 2);
+        assert_eq!(matcher.get_result(1).unwrap().inner, "lib.rs");
+
+        matcher.set_filter(None);
+        matcher.results(10, 0);
+        assert_eq!(matcher.matched_item_count, 3);
+    }
+
+    #[test]
+    fn test_find_query_operators() {
+        let mut matcher = Matcher::new(Config::default());
+        matcher.update_items(items(&["xfoo", "fo", "bar"]), fill_columns);
+        let mut matches = |pattern: &str| {
+            matcher.find(pattern);
+            matcher.tick();
+            while matcher.status.running || matcher.total_item_count < 3 {
+                matcher.tick();
+                matcher.results(10, 0);
+            }
+            let mut results: Vec<String> = matcher
+                .results(10, 0)
+                .into_iter()
+                .map(|i| i.inner)
+                .collect();
+            results.sort();
+            results
+        };
+        assert_eq!(matches("fo$"), items(&["fo"]));
+        // not a narrowing of the previous pattern, despite extending it
+        assert_eq!(matches("foo$"), items(&["xfoo"]));
+        assert_eq!(matches("!foo"), items(&["bar", "fo"]));
+        assert_eq!(matches("!foo ^b"), items(&["bar"]));
+    }
+}
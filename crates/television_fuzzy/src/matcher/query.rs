@@ -0,0 +1,196 @@
+//! The query syntax shared by every channel, following fzf's:
+//!
+//! - `foo` fuzzy matches `foo`;
+//! - `'foo` matches the substring `foo`;
+//! - `^foo` matches the prefix `foo`;
+//! - `foo$` matches the suffix `foo`;
+//! - `^foo$` matches `foo` exactly;
+//! - `!` negates any of the above except fuzzy matching (`!foo` doesn't
+//!   contain `foo`);
+//! - space-separated terms must all match (`\ ` matches a space).
+//!
+//! The matching itself is done by nucleo's pattern, which understands the
+//! same syntax. The parsed query tells whether a new query only narrows the
+//! previous one down, in which case only the items matching the previous
+//! query need to be matched again.
+
+/// How a term of the query matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermKind {
+    Fuzzy,
+    Substring,
+    Prefix,
+    Suffix,
+    Exact,
+}
+
+/// A term of the query, e.g. `!^foo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub kind: TermKind,
+    pub negated: bool,
+    /// The text to match, unescaped.
+    pub text: String,
+}
+
+impl Term {
+    /// Parse a term the way nucleo does (see `nucleo::pattern::Atom::parse`).
+    fn parse(term: &str) -> Self {
+        let (negated, term) = match term.strip_prefix('!') {
+            Some(term) => (true, term),
+            // an escaped `!`
+            None if term.starts_with("\\!") => (false, &term[1..]),
+            None => (false, term),
+        };
+        let (mut kind, term) = match term.as_bytes() {
+            [b'^', ..] => (TermKind::Prefix, &term[1..]),
+            [b'\'', ..] => (TermKind::Substring, &term[1..]),
+            [b'\\', b'^' | b'\'', ..] => (TermKind::Fuzzy, &term[1..]),
+            _ => (TermKind::Fuzzy, term),
+        };
+        let text = if let Some(term) = term.strip_suffix("\\$") {
+            format!("{term}$")
+        } else if let Some(term) = term.strip_suffix('$') {
+            kind = match kind {
+                TermKind::Fuzzy => TermKind::Suffix,
+                _ => TermKind::Exact,
+            };
+            term.to_string()
+        } else {
+            term.to_string()
+        };
+        // negated fuzzy terms would exclude nearly everything
+        if negated && kind == TermKind::Fuzzy {
+            kind = TermKind::Substring;
+        }
+        Term {
+            kind,
+            negated,
+            text: text.replace("\\ ", " "),
+        }
+    }
+
+    /// Whether every item this term matches is matched by the given one.
+    fn implies(&self, other: &Term) -> bool {
+        if self == other {
+            return true;
+        }
+        if self.negated || other.negated {
+            return false;
+        }
+        let text = &self.text;
+        match (other.kind, self.kind) {
+            (TermKind::Fuzzy, _) => is_subsequence(&other.text, text),
+            (TermKind::Substring, TermKind::Fuzzy) => false,
+            (TermKind::Substring, _) => text.contains(&other.text),
+            (TermKind::Prefix, TermKind::Prefix | TermKind::Exact) => {
+                text.starts_with(&other.text)
+            }
+            (TermKind::Suffix, TermKind::Suffix | TermKind::Exact) => {
+                text.ends_with(&other.text)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+/// A parsed query: terms that must all match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Self {
+        Query {
+            terms: split_terms(query)
+                .into_iter()
+                .map(Term::parse)
+                .filter(|term| !term.text.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Whether every item this query matches is matched by the given
+    /// (previous) one, e.g. `foo ^ba` narrows `fo ^b` down, but `foo$`
+    /// doesn't narrow `fo$` down.
+    pub fn narrows(&self, previous: &Query) -> bool {
+        previous.terms.iter().enumerate().all(|(i, term)| {
+            self.terms.get(i).is_some_and(|new| new.implies(term))
+        })
+    }
+}
+
+/// The space-separated terms of the given query, as written (spaces escaped
+/// with a backslash don't separate terms).
+pub fn split_terms(query: &str) -> Vec<&str> {
+    let mut escaped = false;
+    query
+        .split(move |c| {
+            let separates = c == ' ' && !escaped;
+            escaped = c == '\\';
+            separates
+        })
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(kind: TermKind, negated: bool, text: &str) -> Term {
+        Term {
+            kind,
+            negated,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Query::parse(
+                "foo 'bar !baz ^qu ux$ ^exact$ !^no a\\ b c\\$ \\^d '"
+            )
+            .terms,
+            [
+                term(TermKind::Fuzzy, false, "foo"),
+                term(TermKind::Substring, false, "bar"),
+                term(TermKind::Substring, true, "baz"),
+                term(TermKind::Prefix, false, "qu"),
+                term(TermKind::Suffix, false, "ux"),
+                term(TermKind::Exact, false, "exact"),
+                term(TermKind::Prefix, true, "no"),
+                term(TermKind::Fuzzy, false, "a b"),
+                term(TermKind::Fuzzy, false, "c$"),
+                term(TermKind::Fuzzy, false, "^d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_narrows() {
+        let narrows = |new: &str, old: &str| {
+            Query::parse(new).narrows(&Query::parse(old))
+        };
+        assert!(narrows("foo", "fo"));
+        assert!(narrows("foo bar", "foo"));
+        assert!(narrows("foo ^ba", "foo ^b"));
+        assert!(narrows("'foo", "fo"));
+        assert!(narrows("^foo$", "'oo"));
+        assert!(narrows("fzo", "fo"));
+        assert!(narrows("oo$", "o$"));
+        assert!(narrows("!foo", ""));
+        assert!(!narrows("foo$", "fo$"));
+        assert!(!narrows("!foo", "!fo"));
+        assert!(!narrows("fo", "foo"));
+        assert!(!narrows("foo", "'fo"));
+        assert!(!narrows("bar", "foo"));
+    }
+}
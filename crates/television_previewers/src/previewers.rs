@@ -1,32 +1,65 @@
+use std::mem::size_of;
+use std::ops::Range;
 use std::sync::Arc;
 
+use television_channels::bus::Bus;
+use television_channels::context::Context;
 use television_channels::entry::{Entry, PreviewType};
+use television_utils::ansi::{has_escapes, AnsiLine, AnsiStyle};
+use television_utils::hexdump::Hexdump;
+use television_utils::image::Image;
+use television_utils::strings::preprocess_line;
 
 pub mod basic;
 pub mod cache;
+pub mod command;
 pub mod directory;
 pub mod env;
 pub mod files;
 pub mod meta;
+pub mod plain_text;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+#[cfg(feature = "scripting")]
+pub mod script;
 
 // previewer types
 pub use basic::BasicPreviewer;
 pub use basic::BasicPreviewerConfig;
+pub use command::CommandPreviewer;
+pub use command::CommandPreviewerConfig;
 pub use directory::DirectoryPreviewer;
 pub use directory::DirectoryPreviewerConfig;
 pub use env::EnvVarPreviewer;
 pub use env::EnvVarPreviewerConfig;
 pub use files::FilePreviewer;
 pub use files::FilePreviewerConfig;
-//use ratatui_image::protocol::StatefulProtocol;
+pub use plain_text::PlainTextPreviewer;
+pub use plain_text::PlainTextPreviewerConfig;
+#[cfg(feature = "plugins")]
+pub use plugin::PluginPreviewer;
+#[cfg(feature = "scripting")]
+pub use script::ScriptPreviewer;
+#[cfg(feature = "syntax-highlighting")]
 use syntect::highlighting::Style;
+#[cfg(feature = "syntax-highlighting")]
+use television_utils::syntax::{highlighted_lines_size, LazyHighlighter};
 
 #[derive(Clone, Debug)]
 pub enum PreviewContent {
     Empty,
     FileTooLarge,
+    #[cfg(feature = "syntax-highlighting")]
     SyntectHighlightedText(Vec<Vec<(Style, String)>>),
-    //Image(Box<dyn StatefulProtocol>),
+    /// Text highlighted as it comes into view.
+    #[cfg(feature = "syntax-highlighting")]
+    LazyHighlightedText(Arc<LazyHighlighter>),
+    /// An image, downsized to fit any preview pane.
+    Image(Arc<Image>),
+    /// The hexdump of a binary file, read as it comes into view.
+    Hexdump(Arc<Hexdump>),
+    /// Text colored by ANSI escape sequences, e.g. the output of a command.
+    AnsiText(Vec<AnsiLine>),
     Loading,
     NotSupported,
     PlainText(Vec<String>),
@@ -62,25 +95,119 @@ impl Preview {
         Preview { title, content }
     }
 
+    /// A preview of the given text, displayed with its own colors if it
+    /// has ANSI escape sequences.
+    pub fn from_text(title: String, text: &str) -> Self {
+        let content = if has_escapes(text) {
+            PreviewContent::AnsiText(
+                text.lines().map(AnsiLine::parse).collect(),
+            )
+        } else {
+            PreviewContent::PlainText(
+                text.lines().map(preprocess_line).collect(),
+            )
+        };
+        Preview::new(title, content)
+    }
+
     pub fn total_lines(&self) -> u16 {
         match &self.content {
+            #[cfg(feature = "syntax-highlighting")]
             PreviewContent::SyntectHighlightedText(lines) => {
                 lines.len().try_into().unwrap_or(u16::MAX)
             }
+            #[cfg(feature = "syntax-highlighting")]
+            PreviewContent::LazyHighlightedText(highlighter) => {
+                highlighter.len().try_into().unwrap_or(u16::MAX)
+            }
             PreviewContent::PlainText(lines) => {
                 lines.len().try_into().unwrap_or(u16::MAX)
             }
+            PreviewContent::AnsiText(lines) => {
+                lines.len().try_into().unwrap_or(u16::MAX)
+            }
+            // with a last line telling what's left out of a truncated dump
+            PreviewContent::Hexdump(hexdump) => (hexdump.len()
+                + usize::from(hexdump.is_truncated()))
+            .try_into()
+            .unwrap_or(u16::MAX),
             _ => 0,
         }
     }
+
+    /// The approximate number of bytes taken by the preview.
+    pub fn memory_usage(&self) -> usize {
+        let content = match &self.content {
+            #[cfg(feature = "syntax-highlighting")]
+            PreviewContent::SyntectHighlightedText(lines) => {
+                highlighted_lines_size(lines)
+            }
+            #[cfg(feature = "syntax-highlighting")]
+            PreviewContent::LazyHighlightedText(highlighter) => {
+                highlighter.memory_usage()
+            }
+            PreviewContent::Image(image) => {
+                image.width as usize * image.height as usize * 4
+            }
+            PreviewContent::Hexdump(hexdump) => hexdump.memory_usage(),
+            PreviewContent::AnsiText(lines) => lines
+                .iter()
+                .map(|line| {
+                    line.text.len()
+                        + line.styles.len()
+                            * size_of::<(Range<usize>, AnsiStyle)>()
+                })
+                .sum(),
+            PreviewContent::PlainText(lines) => {
+                lines.iter().map(String::len).sum()
+            }
+            PreviewContent::PlainTextWrapped(text) => text.len(),
+            PreviewContent::Empty
+            | PreviewContent::FileTooLarge
+            | PreviewContent::Loading
+            | PreviewContent::NotSupported => 0,
+        };
+        self.title.len() + content
+    }
 }
 
-#[derive(Debug, Default)]
+/// The names of the previewers compiled in, as reported by `tv about`.
+pub fn previewer_names() -> Vec<&'static str> {
+    let mut names = vec![
+        "basic",
+        "directory",
+        "env_var",
+        "files",
+        "plain_text",
+        "command",
+    ];
+    if cfg!(feature = "image-preview") {
+        names.push("image");
+    }
+    if cfg!(feature = "scripting") {
+        names.push("script");
+    }
+    if cfg!(feature = "plugins") {
+        names.push("plugin");
+    }
+    names
+}
+
+#[derive(Debug)]
 pub struct Previewer {
+    /// The bus of the picker, the previews computed in the background are
+    /// announced on.
+    bus: Bus,
     basic: BasicPreviewer,
     directory: DirectoryPreviewer,
     file: FilePreviewer,
     env_var: EnvVarPreviewer,
+    plain_text: PlainTextPreviewer,
+    command: CommandPreviewer,
+    #[cfg(feature = "scripting")]
+    script: ScriptPreviewer,
+    #[cfg(feature = "plugins")]
+    plugin: PluginPreviewer,
 }
 
 #[derive(Debug, Default)]
@@ -89,6 +216,8 @@ pub struct PreviewerConfig {
     directory: DirectoryPreviewerConfig,
     file: FilePreviewerConfig,
     env_var: EnvVarPreviewerConfig,
+    plain_text: PlainTextPreviewerConfig,
+    command: CommandPreviewerConfig,
 }
 
 impl PreviewerConfig {
@@ -111,32 +240,126 @@ impl PreviewerConfig {
         self.env_var = config;
         self
     }
+
+    pub fn plain_text(mut self, config: PlainTextPreviewerConfig) -> Self {
+        self.plain_text = config;
+        self
+    }
+
+    pub fn command(mut self, config: CommandPreviewerConfig) -> Self {
+        self.command = config;
+        self
+    }
 }
 
 impl Previewer {
-    pub fn new(config: Option<PreviewerConfig>) -> Self {
+    /// Create the previewer of the picker with the given context (for its
+    /// bus, scripts and plugins).
+    pub fn new(config: Option<PreviewerConfig>, ctx: &Context) -> Self {
         let config = config.unwrap_or_default();
+        let bus = ctx.bus.clone();
+        let file = FilePreviewer::new(Some(config.file), bus.clone());
         Previewer {
             basic: BasicPreviewer::new(Some(config.basic)),
-            directory: DirectoryPreviewer::new(Some(config.directory)),
-            file: FilePreviewer::new(Some(config.file)),
+            directory: DirectoryPreviewer::new(
+                Some(config.directory),
+                bus.clone(),
+            ),
             env_var: EnvVarPreviewer::new(Some(config.env_var)),
+            plain_text: PlainTextPreviewer::new(Some(config.plain_text)),
+            command: Self::command_previewer(config.command, &file, &bus),
+            file,
+            #[cfg(feature = "scripting")]
+            script: ScriptPreviewer::new(ctx),
+            #[cfg(feature = "plugins")]
+            plugin: PluginPreviewer::new(ctx),
+            bus,
         }
     }
 
+    /// The command previewer, highlighting the output of the commands with
+    /// the syntaxes and theme of the file previewer.
+    #[cfg_attr(not(feature = "syntax-highlighting"), allow(unused_variables))]
+    fn command_previewer(
+        config: CommandPreviewerConfig,
+        file: &FilePreviewer,
+        bus: &Bus,
+    ) -> CommandPreviewer {
+        let command = CommandPreviewer::new(Some(config), bus.clone());
+        #[cfg(feature = "syntax-highlighting")]
+        let command = command.with_highlighting(
+            file.syntax_set.clone(),
+            file.syntax_theme.clone(),
+        );
+        command
+    }
+
     pub async fn preview(&mut self, entry: &Entry) -> Arc<Preview> {
-        match entry.preview_type {
+        match &entry.preview_type {
             PreviewType::Basic => self.basic.preview(entry),
             PreviewType::Directory => self.directory.preview(entry).await,
             PreviewType::EnvVar => self.env_var.preview(entry),
             PreviewType::Files => self.file.preview(entry).await,
+            PreviewType::PlainText => self.plain_text.preview(entry),
+            PreviewType::Command(command) => {
+                self.command.preview(entry, command)
+            }
+            #[cfg(feature = "scripting")]
+            PreviewType::Script(script) => self.script.preview(entry, script),
+            // script channels are compiled out
+            #[cfg(not(feature = "scripting"))]
+            PreviewType::Script(_) => self.basic.preview(entry),
+            #[cfg(feature = "plugins")]
+            PreviewType::Plugin(plugin) => self.plugin.preview(entry, plugin),
+            // plugin channels are compiled out
+            #[cfg(not(feature = "plugins"))]
+            PreviewType::Plugin(_) => self.basic.preview(entry),
         }
     }
 
+    /// Kill the preview commands still running.
+    pub fn cancel_all(&mut self) {
+        self.command.cancel_all();
+    }
+
+    /// The approximate number of bytes taken by the cached previews.
+    pub fn memory_usage(&self) -> usize {
+        self.directory.memory_usage()
+            + self.file.memory_usage()
+            + self.env_var.memory_usage()
+            + self.command.memory_usage()
+            + self.script_memory_usage()
+            + self.plugin_memory_usage()
+    }
+
+    #[cfg(feature = "scripting")]
+    fn script_memory_usage(&self) -> usize {
+        self.script.memory_usage()
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn script_memory_usage(&self) -> usize {
+        0
+    }
+
+    #[cfg(feature = "plugins")]
+    fn plugin_memory_usage(&self) -> usize {
+        self.plugin.memory_usage()
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn plugin_memory_usage(&self) -> usize {
+        0
+    }
+
     pub fn set_config(&mut self, config: PreviewerConfig) {
         self.basic = BasicPreviewer::new(Some(config.basic));
-        self.directory = DirectoryPreviewer::new(Some(config.directory));
-        self.file = FilePreviewer::new(Some(config.file));
+        self.directory =
+            DirectoryPreviewer::new(Some(config.directory), self.bus.clone());
+        self.file = FilePreviewer::new(Some(config.file), self.bus.clone());
         self.env_var = EnvVarPreviewer::new(Some(config.env_var));
+        self.plain_text = PlainTextPreviewer::new(Some(config.plain_text));
+        self.command =
+            Self::command_previewer(config.command, &self.file, &self.bus);
     }
 }
@@ -141,6 +141,19 @@ impl PreviewCache {
         }
     }
 
+    /// Remove the preview for the given key from the cache.
+    pub fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    /// The approximate number of bytes taken by the cached previews.
+    pub fn memory_usage(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(key, preview)| key.len() + preview.memory_usage())
+            .sum()
+    }
+
     /// Get the preview for the given key, or insert a new preview if it doesn't exist.
     #[allow(dead_code)]
     pub fn get_or_insert<F>(&mut self, key: String, f: F) -> Arc<Preview>
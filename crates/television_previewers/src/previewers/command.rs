@@ -0,0 +1,283 @@
+use parking_lot::Mutex;
+#[cfg(feature = "syntax-highlighting")]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[cfg(feature = "syntax-highlighting")]
+use syntect::{highlighting::Theme, parsing::SyntaxSet};
+use tracing::{debug, warn};
+
+use super::cache::PreviewCache;
+use crate::previewers::{meta, Preview, PreviewContent};
+use television_channels::bus::{Bus, Message};
+use television_channels::entry::{self, PreviewCommand};
+use television_utils::ansi::{has_escapes, AnsiLine};
+use television_utils::sandbox::{Sandbox, Termination};
+use television_utils::strings::preprocess_line;
+#[cfg(feature = "syntax-highlighting")]
+use television_utils::syntax::compute_highlights_for_path;
+
+#[derive(Debug, Default)]
+pub struct CommandPreviewer {
+    cache: Arc<Mutex<PreviewCache>>,
+    config: CommandPreviewerConfig,
+    running: Vec<RunningCommand>,
+    bus: Bus,
+    /// The syntaxes and theme highlighting the output of the commands that
+    /// have a syntax (shared with the file previewer).
+    #[cfg(feature = "syntax-highlighting")]
+    highlighting: Option<(Arc<SyntaxSet>, Arc<Theme>)>,
+}
+
+#[derive(Debug, Default)]
+pub struct CommandPreviewerConfig {
+    sandbox: Sandbox,
+}
+
+impl CommandPreviewerConfig {
+    pub fn new(sandbox: Sandbox) -> Self {
+        CommandPreviewerConfig { sandbox }
+    }
+}
+
+/// A preview command running in the background.
+#[derive(Debug)]
+struct RunningCommand {
+    /// The name of the entry the command previews.
+    entry: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CommandPreviewer {
+    pub fn new(config: Option<CommandPreviewerConfig>, bus: Bus) -> Self {
+        CommandPreviewer {
+            cache: Arc::new(Mutex::new(PreviewCache::default())),
+            config: config.unwrap_or_default(),
+            running: Vec::new(),
+            bus,
+            #[cfg(feature = "syntax-highlighting")]
+            highlighting: None,
+        }
+    }
+
+    /// Highlight the output of the commands having a syntax with the given
+    /// syntaxes and theme.
+    #[cfg(feature = "syntax-highlighting")]
+    #[must_use]
+    pub fn with_highlighting(
+        mut self,
+        syntax_set: Arc<SyntaxSet>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        self.highlighting = Some((syntax_set, theme));
+        self
+    }
+
+    /// Preview an entry by running its preview command.
+    ///
+    /// The command is run in the background, in a sandbox, and a loading
+    /// preview is returned in the meantime. Once the command has completed,
+    /// its output replaces the loading preview in the cache.
+    ///
+    /// The commands still running for other entries are killed, since the
+    /// selection moved on.
+    pub fn preview(
+        &mut self,
+        entry: &entry::Entry,
+        command: &PreviewCommand,
+    ) -> Arc<Preview> {
+        self.cancel_other_entries(&entry.name);
+
+        // do we have a preview in cache for that command?
+        if let Some(preview) = self.cache.lock().get(&command.command) {
+            return preview.clone();
+        }
+        debug!("No preview in cache for {:?}", command.command);
+
+        let preview = meta::loading(&entry.name);
+        self.cache
+            .lock()
+            .insert(command.command.clone(), preview.clone());
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.running.push(RunningCommand {
+            entry: entry.name.clone(),
+            cancelled: cancelled.clone(),
+        });
+        let (cache, bus) = (self.cache.clone(), self.bus.clone());
+        let title = entry.name.clone();
+        let command = command.clone();
+        let sandbox = self.config.sandbox.clone();
+        #[cfg(feature = "syntax-highlighting")]
+        let highlighting = self.highlighting.clone();
+        tokio::task::spawn_blocking(move || {
+            match run_command(&sandbox, &command.command, &cancelled) {
+                Some(lines) => {
+                    // colored output is displayed with its own colors
+                    let content = if lines.iter().any(|l| has_escapes(l)) {
+                        PreviewContent::AnsiText(
+                            lines.iter().map(|l| AnsiLine::parse(l)).collect(),
+                        )
+                    } else {
+                        let lines =
+                            lines.iter().map(|l| preprocess_line(l)).collect();
+                        #[cfg(feature = "syntax-highlighting")]
+                        let content = match (&command.syntax, highlighting) {
+                            (Some(syntax), Some((syntax_set, theme))) => {
+                                highlight(lines, syntax, &syntax_set, &theme)
+                            }
+                            _ => PreviewContent::PlainText(lines),
+                        };
+                        #[cfg(not(feature = "syntax-highlighting"))]
+                        let content = PreviewContent::PlainText(lines);
+                        content
+                    };
+                    let preview =
+                        Arc::new(Preview::new(title.clone(), content));
+                    cache.lock().insert(command.command, preview);
+                    bus.send(Message::PreviewReady(title));
+                }
+                // drop the loading preview so that the command is run again
+                // if the entry gets selected again
+                None => cache.lock().remove(&command.command),
+            }
+        });
+        preview
+    }
+
+    /// Kill the commands still running, e.g. because the channel they
+    /// preview entries of is closed.
+    pub fn cancel_all(&mut self) {
+        for running in self.running.drain(..) {
+            running.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// The approximate number of bytes taken by the cached previews.
+    pub fn memory_usage(&self) -> usize {
+        self.cache.lock().memory_usage()
+    }
+
+    fn cancel_other_entries(&mut self, entry: &str) {
+        self.running.retain(|running| {
+            // the background task holds the other reference until it's done
+            if Arc::strong_count(&running.cancelled) == 1 {
+                return false;
+            }
+            if running.entry == entry {
+                return true;
+            }
+            running.cancelled.store(true, Ordering::Relaxed);
+            false
+        });
+    }
+}
+
+/// Highlight the output of a command with the syntax of the given file
+/// name, falling back to plain text.
+#[cfg(feature = "syntax-highlighting")]
+fn highlight(
+    lines: Vec<String>,
+    syntax: &str,
+    syntax_set: &SyntaxSet,
+    theme: &Theme,
+) -> PreviewContent {
+    // sublime syntaxes expect the lines to end with a newline
+    let newline_terminated =
+        lines.iter().map(|line| format!("{line}\n")).collect();
+    match compute_highlights_for_path(
+        Path::new(syntax),
+        newline_terminated,
+        syntax_set,
+        theme,
+    ) {
+        Ok(highlighted_lines) => {
+            PreviewContent::SyntectHighlightedText(highlighted_lines)
+        }
+        Err(e) => {
+            warn!("Error highlighting the preview command output: {:?}", e);
+            PreviewContent::PlainText(lines)
+        }
+    }
+}
+
+/// Run a shell command in the given sandbox and return the raw lines of its
+/// output (stdout followed by stderr), or `None` if it was cancelled.
+fn run_command(
+    sandbox: &Sandbox,
+    command: &str,
+    cancelled: &AtomicBool,
+) -> Option<Vec<String>> {
+    debug!("Running preview command: {:?}", command);
+    let output = match sandbox.run(command, cancelled) {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("Error running preview command: {:?}", e);
+            return Some(vec![format!("Error running `{command}`: {e}")]);
+        }
+    };
+    let mut lines: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+        .map(str::to_string)
+        .collect();
+    match output.termination {
+        Termination::Exited => {}
+        Termination::TimedOut => lines.push(format!(
+            "[killed after {:.1}s]",
+            sandbox.timeout.as_secs_f64()
+        )),
+        Termination::OutputTooLarge => lines.push(format!(
+            "[output truncated to {} bytes]",
+            sandbox.max_output_size
+        )),
+        Termination::Cancelled => return None,
+    }
+    Some(lines)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_run_command() {
+        let sandbox = Sandbox {
+            timeout: Duration::from_millis(100),
+            ..Sandbox::default()
+        };
+        let not_cancelled = AtomicBool::new(false);
+        assert_eq!(
+            run_command(&sandbox, "echo hello", &not_cancelled),
+            Some(vec!["hello".to_string()])
+        );
+        assert_eq!(
+            run_command(&sandbox, "echo hello; sleep 5", &not_cancelled),
+            Some(vec!["hello".to_string(), "[killed after 0.1s]".to_string()])
+        );
+        assert_eq!(
+            run_command(&sandbox, "sleep 5", &AtomicBool::new(true)),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all() {
+        let mut previewer = CommandPreviewer::new(None, Bus::default());
+        let command = PreviewCommand::new("sleep 5".to_string());
+        let entry = entry::Entry::new(
+            "entry".to_string(),
+            entry::PreviewType::Command(command.clone()),
+        );
+        previewer.preview(&entry, &command);
+        previewer.cancel_all();
+        // the loading preview is dropped once the command is killed
+        let start = std::time::Instant::now();
+        while previewer.cache.lock().get(&command.command).is_some() {
+            assert!(start.elapsed() < Duration::from_secs(2));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
@@ -5,6 +5,7 @@ use devicons::FileIcon;
 use parking_lot::Mutex;
 use termtree::Tree;
 
+use television_channels::bus::{Bus, Message};
 use television_channels::entry::Entry;
 
 use crate::previewers::cache::PreviewCache;
@@ -15,19 +16,26 @@ use television_utils::files::walk_builder;
 pub struct DirectoryPreviewer {
     cache: Arc<Mutex<PreviewCache>>,
     _config: DirectoryPreviewerConfig,
+    bus: Bus,
 }
 
 #[derive(Debug, Default)]
 pub struct DirectoryPreviewerConfig {}
 
 impl DirectoryPreviewer {
-    pub fn new(config: Option<DirectoryPreviewerConfig>) -> Self {
+    pub fn new(config: Option<DirectoryPreviewerConfig>, bus: Bus) -> Self {
         DirectoryPreviewer {
             cache: Arc::new(Mutex::new(PreviewCache::default())),
             _config: config.unwrap_or_default(),
+            bus,
         }
     }
 
+    /// The approximate number of bytes taken by the cached previews.
+    pub fn memory_usage(&self) -> usize {
+        self.cache.lock().memory_usage()
+    }
+
     pub async fn preview(&mut self, entry: &Entry) -> Arc<Preview> {
         if let Some(preview) = self.cache.lock().get(&entry.name) {
             return preview;
@@ -37,10 +45,11 @@ impl DirectoryPreviewer {
             .lock()
             .insert(entry.name.clone(), preview.clone());
         let entry_c = entry.clone();
-        let cache = self.cache.clone();
+        let (cache, bus) = (self.cache.clone(), self.bus.clone());
         tokio::spawn(async move {
             let preview = Arc::new(build_tree_preview(&entry_c));
             cache.lock().insert(entry_c.name.clone(), preview.clone());
+            bus.send(Message::PreviewReady(entry_c.name));
         });
         preview
     }
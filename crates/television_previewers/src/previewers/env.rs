@@ -21,6 +21,14 @@ impl EnvVarPreviewer {
         }
     }
 
+    /// The approximate number of bytes taken by the cached previews.
+    pub fn memory_usage(&self) -> usize {
+        self.cache
+            .values()
+            .map(|preview| preview.memory_usage())
+            .sum()
+    }
+
     pub fn preview(&mut self, entry: &entry::Entry) -> Arc<Preview> {
         // check if we have that preview in the cache
         if let Some(preview) = self.cache.get(entry) {
@@ -29,8 +37,15 @@ impl EnvVarPreviewer {
         let preview = Arc::new(Preview {
             title: entry.name.clone(),
             content: if let Some(preview) = &entry.value {
+                let value = if cfg!(windows) {
+                    expand_windows_vars(preview, |name| {
+                        std::env::var(name).ok()
+                    })
+                } else {
+                    preview.clone()
+                };
                 PreviewContent::PlainTextWrapped(
-                    maybe_add_newline_after_colon(preview, &entry.name),
+                    maybe_add_newline_after_colon(&value, &entry.name),
                 )
             } else {
                 PreviewContent::Empty
@@ -43,9 +58,74 @@ impl EnvVarPreviewer {
 
 const PATH: &str = "PATH";
 
+/// The separator of the directories of `PATH` like variables.
+const PATH_SEPARATOR: &str = if cfg!(windows) { ";" } else { ":" };
+
 fn maybe_add_newline_after_colon(s: &str, name: &str) -> String {
-    if name.contains(PATH) {
-        return s.replace(":", "\n");
+    if name.to_uppercase().contains(PATH) {
+        return s.replace(PATH_SEPARATOR, "\n");
     }
     s.to_string()
 }
+
+/// Expand the `%VAR%` references of a windows environment variable value
+/// (e.g. `%USERPROFILE%\bin`) with the given lookup, leaving the unknown
+/// ones as they are, like `cmd` does.
+fn expand_windows_vars(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = &after[..end];
+        match lookup(name).filter(|_| !name.is_empty()) {
+            Some(value) => {
+                expanded.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            // the closing `%` might open the next reference
+            None => {
+                expanded.push('%');
+                expanded.push_str(name);
+                rest = &after[end..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_windows_vars() {
+        let lookup = |name: &str| match name {
+            "USERPROFILE" => Some(r"C:\Users\tv".to_string()),
+            "APP" => Some("television".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_windows_vars(r"%USERPROFILE%\bin;%APP%", lookup),
+            r"C:\Users\tv\bin;television"
+        );
+        assert_eq!(
+            expand_windows_vars("100% %APP%", lookup),
+            "100% television"
+        );
+        assert_eq!(
+            expand_windows_vars("%NOPE%%APP%", lookup),
+            "%NOPE%television"
+        );
+        assert_eq!(expand_windows_vars("50%", lookup), "50%");
+        assert_eq!(expand_windows_vars("%%", lookup), "%%");
+    }
+}
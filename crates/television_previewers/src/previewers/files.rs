@@ -1,12 +1,12 @@
-use color_eyre::Result;
-//use image::{ImageReader, Rgb};
-//use ratatui_image::picker::Picker;
 use parking_lot::Mutex;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Seek};
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+#[cfg(feature = "syntax-highlighting")]
+use std::io::Seek;
+#[cfg(feature = "syntax-highlighting")]
 use syntect::{
     highlighting::{Theme, ThemeSet},
     parsing::SyntaxSet,
@@ -15,23 +15,38 @@ use tracing::{debug, warn};
 
 use super::cache::PreviewCache;
 use crate::previewers::{meta, Preview, PreviewContent};
+use television_channels::bus::Bus;
+#[cfg(any(feature = "syntax-highlighting", feature = "image-preview"))]
+use television_channels::bus::Message;
 use television_channels::entry;
 use television_utils::files::FileType;
 use television_utils::files::{get_file_size, is_known_text_extension};
+use television_utils::hexdump::Hexdump;
+#[cfg(feature = "image-preview")]
+use television_utils::image::load_thumbnail;
 use television_utils::strings::{
     preprocess_line, proportion_of_printable_ascii_characters,
     PRINTABLE_ASCII_THRESHOLD,
 };
+#[cfg(feature = "syntax-highlighting")]
 use television_utils::syntax::{
-    self, load_highlighting_assets, HighlightingAssetsExt,
+    load_highlighting_assets, HighlightingAssetsExt, LazyHighlighter,
 };
 
 #[derive(Debug, Default)]
 pub struct FilePreviewer {
     cache: Arc<Mutex<PreviewCache>>,
+    /// Announces the previews computed in the background (highlighted text
+    /// and images).
+    #[cfg_attr(
+        not(any(feature = "syntax-highlighting", feature = "image-preview")),
+        allow(dead_code)
+    )]
+    bus: Bus,
+    #[cfg(feature = "syntax-highlighting")]
     pub syntax_set: Arc<SyntaxSet>,
+    #[cfg(feature = "syntax-highlighting")]
     pub syntax_theme: Arc<Theme>,
-    //image_picker: Arc<Mutex<Picker>>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -46,7 +61,8 @@ impl FilePreviewerConfig {
 }
 
 impl FilePreviewer {
-    pub fn new(config: Option<FilePreviewerConfig>) -> Self {
+    #[cfg(feature = "syntax-highlighting")]
+    pub fn new(config: Option<FilePreviewerConfig>, bus: Bus) -> Self {
         let hl_assets = load_highlighting_assets();
         let syntax_set = hl_assets.get_syntax_set().unwrap().clone();
 
@@ -57,18 +73,28 @@ impl FilePreviewer {
             },
             |c| hl_assets.get_theme_no_output(&c.theme).clone(),
         );
-        //info!("getting image picker");
-        //let image_picker = get_image_picker();
-        //info!("got image picker");
 
         FilePreviewer {
             cache: Arc::new(Mutex::new(PreviewCache::default())),
+            bus,
             syntax_set: Arc::new(syntax_set),
             syntax_theme: Arc::new(theme),
-            //image_picker: Arc::new(Mutex::new(image_picker)),
         }
     }
 
+    #[cfg(not(feature = "syntax-highlighting"))]
+    pub fn new(_config: Option<FilePreviewerConfig>, bus: Bus) -> Self {
+        FilePreviewer {
+            cache: Arc::new(Mutex::new(PreviewCache::default())),
+            bus,
+        }
+    }
+
+    /// The approximate number of bytes taken by the cached previews.
+    pub fn memory_usage(&self) -> usize {
+        self.cache.lock().memory_usage()
+    }
+
     pub async fn preview(&mut self, entry: &entry::Entry) -> Arc<Preview> {
         let path_buf = PathBuf::from(&entry.name);
 
@@ -78,8 +104,14 @@ impl FilePreviewer {
         }
         debug!("No preview in cache for {:?}", entry.name);
 
-        // check file size
-        if get_file_size(&path_buf).map_or(false, |s| s > Self::MAX_FILE_SIZE)
+        // try to determine file type
+        debug!("Computing preview for {:?}", entry.name);
+        let file_type = self.get_file_type(&path_buf);
+
+        // check file size, binary files are only dumped up to a point
+        if matches!(file_type, FileType::Text | FileType::Image)
+            && get_file_size(&path_buf)
+                .is_some_and(|s| s > Self::MAX_FILE_SIZE)
         {
             debug!("File too large: {:?}", entry.name);
             let preview = meta::file_too_large(&entry.name);
@@ -88,9 +120,7 @@ impl FilePreviewer {
             return preview;
         }
 
-        // try to determine file type
-        debug!("Computing preview for {:?}", entry.name);
-        match self.get_file_type(&path_buf) {
+        match file_type {
             FileType::Text => {
                 match File::open(&path_buf) {
                     Ok(file) => {
@@ -104,11 +134,16 @@ impl FilePreviewer {
                         .await;
 
                         // compute the highlighted version in the background
-                        let mut reader =
-                            BufReader::new(file.try_clone().unwrap());
-                        reader.seek(std::io::SeekFrom::Start(0)).unwrap();
-                        self.compute_highlighted_text_preview(entry, reader)
+                        #[cfg(feature = "syntax-highlighting")]
+                        {
+                            let mut reader =
+                                BufReader::new(file.try_clone().unwrap());
+                            reader.seek(std::io::SeekFrom::Start(0)).unwrap();
+                            self.compute_highlighted_text_preview(
+                                entry, reader,
+                            )
                             .await;
+                        }
                         preview
                     }
                     Err(e) => {
@@ -120,27 +155,31 @@ impl FilePreviewer {
                     }
                 }
             }
+            #[cfg(feature = "image-preview")]
             FileType::Image => {
                 debug!("Previewing image file: {:?}", entry.name);
                 // insert a loading preview into the cache
-                //let preview = loading(&entry.name);
-                let preview = meta::not_supported(&entry.name);
-                self.cache_preview(entry.name.clone(), preview.clone())
-                    .await;
-                //// compute the image preview in the background
-                //self.compute_image_preview(entry).await;
-                preview
-            }
-            FileType::Other => {
-                debug!("Previewing other file: {:?}", entry.name);
-                let preview = meta::not_supported(&entry.name);
+                let preview = meta::loading(&entry.name);
                 self.cache_preview(entry.name.clone(), preview.clone())
                     .await;
+                // decode the image in the background
+                self.compute_image_preview(entry);
                 preview
             }
-            FileType::Unknown => {
-                debug!("Unknown file type: {:?}", entry.name);
-                let preview = meta::not_supported(&entry.name);
+            // images too, without image previews
+            _ => {
+                debug!("Previewing binary file: {:?}", entry.name);
+                let preview =
+                    match Hexdump::new(&path_buf, Self::MAX_HEXDUMP_SIZE) {
+                        Ok(hexdump) => Arc::new(Preview::new(
+                            entry.name.clone(),
+                            PreviewContent::Hexdump(Arc::new(hexdump)),
+                        )),
+                        Err(e) => {
+                            warn!("Error reading file: {:?}", e);
+                            meta::not_supported(&entry.name)
+                        }
+                    };
                 self.cache_preview(entry.name.clone(), preview.clone())
                     .await;
                 preview
@@ -148,42 +187,40 @@ impl FilePreviewer {
         }
     }
 
-    //async fn compute_image_preview(&self, entry: &entry::Entry) {
-    //    let cache = self.cache.clone();
-    //    let picker = self.image_picker.clone();
-    //    let entry_c = entry.clone();
-    //    tokio::spawn(async move {
-    //        info!("Loading image: {:?}", entry_c.name);
-    //        if let Ok(dyn_image) =
-    //            ImageReader::open(entry_c.name.clone()).unwrap().decode()
-    //        {
-    //            let image = picker.lock().await.new_resize_protocol(dyn_image);
-    //            let preview = Arc::new(Preview::new(
-    //                entry_c.name.clone(),
-    //                PreviewContent::Image(image),
-    //            ));
-    //            cache
-    //                .lock()
-    //                .await
-    //                .insert(entry_c.name.clone(), preview.clone());
-    //        }
-    //    });
-    //}
+    #[cfg(feature = "image-preview")]
+    fn compute_image_preview(&self, entry: &entry::Entry) {
+        let (cache, bus) = (self.cache.clone(), self.bus.clone());
+        let name = entry.name.clone();
+        tokio::task::spawn_blocking(move || {
+            debug!("Loading image: {:?}", name);
+            let preview = match load_thumbnail(Path::new(&name)) {
+                Ok(image) => Arc::new(Preview::new(
+                    name.clone(),
+                    PreviewContent::Image(Arc::new(image)),
+                )),
+                Err(e) => {
+                    warn!("Error loading image {:?}: {:?}", name, e);
+                    meta::not_supported(&name)
+                }
+            };
+            cache.lock().insert(name.clone(), preview);
+            bus.send(Message::PreviewReady(name));
+        });
+    }
 
+    #[cfg(feature = "syntax-highlighting")]
     async fn compute_highlighted_text_preview(
         &self,
         entry: &entry::Entry,
         reader: BufReader<File>,
     ) {
-        let cache = self.cache.clone();
+        let (cache, bus) = (self.cache.clone(), self.bus.clone());
         let syntax_set = self.syntax_set.clone();
         let syntax_theme = self.syntax_theme.clone();
         let entry_c = entry.clone();
-        tokio::spawn(async move {
-            debug!(
-                "Computing highlights in the background for {:?}",
-                entry_c.name
-            );
+        // reading the whole file is blocking, keep it off the async workers
+        tokio::task::spawn_blocking(move || {
+            debug!("Reading the whole file for {:?}", entry_c.name);
             let lines: Vec<String> = reader
                 .lines()
                 .map_while(Result::ok)
@@ -192,32 +229,23 @@ impl FilePreviewer {
                 .map(|line| preprocess_line(&line) + "\n")
                 .collect();
 
-            match syntax::compute_highlights_for_path(
+            // the lines are only highlighted when they are displayed, which
+            // keeps huge files cheap to preview
+            let highlighter = LazyHighlighter::new(
                 &PathBuf::from(&entry_c.name),
                 lines,
-                &syntax_set,
-                &syntax_theme,
-            ) {
-                Ok(highlighted_lines) => {
-                    debug!(
-                        "Successfully computed highlights for {:?}",
-                        entry_c.name
-                    );
-                    cache.lock().insert(
-                        entry_c.name.clone(),
-                        Arc::new(Preview::new(
-                            entry_c.name,
-                            PreviewContent::SyntectHighlightedText(
-                                highlighted_lines,
-                            ),
-                        )),
-                    );
-                    debug!("Inserted highlighted preview into cache");
-                }
-                Err(e) => {
-                    warn!("Error computing highlights: {:?}", e);
-                }
-            };
+                syntax_set,
+                syntax_theme,
+            );
+            cache.lock().insert(
+                entry_c.name.clone(),
+                Arc::new(Preview::new(
+                    entry_c.name.clone(),
+                    PreviewContent::LazyHighlightedText(Arc::new(highlighter)),
+                )),
+            );
+            debug!("Inserted highlighted preview into cache");
+            bus.send(Message::PreviewReady(entry_c.name));
         });
     }
 
@@ -225,6 +253,11 @@ impl FilePreviewer {
     /// 4 MB
     const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
 
+    /// The number of bytes of a binary file that we will dump, about as many
+    /// lines as a preview can scroll through.
+    /// 1 MB
+    const MAX_HEXDUMP_SIZE: u64 = 1024 * 1024;
+
     fn get_file_type(&self, path: &Path) -> FileType {
         debug!("Getting file type for {:?}", path);
         let mut file_type = match infer::get_from_path(path) {
@@ -268,16 +301,6 @@ impl FilePreviewer {
     }
 }
 
-//fn get_image_picker() -> Picker {
-//    let mut picker = match Picker::from_termios() {
-//        Ok(p) => p,
-//        Err(_) => Picker::new((7, 14)),
-//    };
-//    picker.guess_protocol();
-//    picker.background_color = Some(Rgb::<u8>([255, 0, 255]));
-//    picker
-//}
-
 /// This should be enough to most standard terminal sizes
 const TEMP_PLAIN_TEXT_PREVIEW_HEIGHT: usize = 200;
 
@@ -1,5 +1,10 @@
 use crate::previewers::{Preview, PreviewContent};
+use jiff::{tz::TimeZone, Timestamp};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::SystemTime;
+use television_channels::entry::{Entry, PreviewType};
+use television_utils::format;
 
 pub fn not_supported(title: &str) -> Arc<Preview> {
     Arc::new(Preview::new(
@@ -15,7 +20,112 @@ pub fn file_too_large(title: &str) -> Arc<Preview> {
     ))
 }
 
-#[allow(dead_code)]
 pub fn loading(title: &str) -> Arc<Preview> {
     Arc::new(Preview::new(title.to_string(), PreviewContent::Loading))
 }
+
+/// A preview of the metadata of an entry.
+///
+/// For files and directories, this includes their type, size, permissions
+/// and last modification time. Other entries get their name and value.
+pub fn metadata(entry: &Entry) -> Arc<Preview> {
+    let lines = match entry.preview_type {
+        PreviewType::Files | PreviewType::Directory => {
+            path_metadata(Path::new(&entry.name))
+        }
+        _ => std::iter::once(format!("Name: {}", entry.name))
+            .chain(entry.value.iter().map(|v| format!("Value: {v}")))
+            .collect(),
+    };
+    Arc::new(Preview::new(
+        entry.name.clone(),
+        PreviewContent::PlainText(lines),
+    ))
+}
+
+fn path_metadata(path: &Path) -> Vec<String> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => return vec![format!("Could not read metadata: {e}")],
+    };
+    let file_type = metadata.file_type();
+    let mut lines = vec![
+        format!(
+            "Type: {}",
+            if file_type.is_symlink() {
+                "symlink"
+            } else if file_type.is_dir() {
+                "directory"
+            } else {
+                "file"
+            }
+        ),
+        if format::is_raw() {
+            format!("Size: {} bytes", metadata.len())
+        } else {
+            format!(
+                "Size: {} ({} bytes)",
+                format::size(metadata.len()),
+                format::count(metadata.len())
+            )
+        },
+        format!("Permissions: {}", permissions(&metadata)),
+    ];
+    if let Some((modified, timestamp)) = metadata
+        .modified()
+        .ok()
+        .and_then(|t| Some((t, Timestamp::try_from(t).ok()?)))
+    {
+        lines.push(format!(
+            "Modified: {} ({})",
+            timestamp
+                .to_zoned(TimeZone::system())
+                .strftime("%Y-%m-%d %H:%M:%S %Z"),
+            format::relative_time(
+                SystemTime::now()
+                    .duration_since(modified)
+                    .unwrap_or_default()
+            )
+        ));
+    }
+    lines
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &std::fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = metadata.permissions().mode();
+    ["r", "w", "x"]
+        .iter()
+        .cycle()
+        .take(9)
+        .enumerate()
+        .map(|(i, c)| if mode & (0o400 >> i) == 0 { "-" } else { c })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &std::fs::Metadata) -> String {
+    if metadata.permissions().readonly() {
+        "read-only".to_string()
+    } else {
+        "read-write".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_of_non_file_entry() {
+        let entry = Entry::new("HOME".to_string(), PreviewType::EnvVar)
+            .with_value("/home/user".to_string());
+        let preview = metadata(&entry);
+        assert!(matches!(
+            &preview.content,
+            PreviewContent::PlainText(lines)
+                if lines == &["Name: HOME", "Value: /home/user"]
+        ));
+    }
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use crate::previewers::{Preview, PreviewContent};
+use television_channels::entry::Entry;
+
+#[derive(Debug, Default)]
+pub struct PlainTextPreviewer {
+    _config: PlainTextPreviewerConfig,
+}
+
+#[derive(Debug, Default)]
+pub struct PlainTextPreviewerConfig {}
+
+impl PlainTextPreviewer {
+    pub fn new(config: Option<PlainTextPreviewerConfig>) -> Self {
+        PlainTextPreviewer {
+            _config: config.unwrap_or_default(),
+        }
+    }
+
+    pub fn preview(&self, entry: &Entry) -> Arc<Preview> {
+        Arc::new(Preview {
+            title: entry.name.clone(),
+            content: if let Some(value) = &entry.value {
+                PreviewContent::PlainText(
+                    value.lines().map(String::from).collect(),
+                )
+            } else {
+                PreviewContent::Empty
+            },
+        })
+    }
+}
@@ -0,0 +1,63 @@
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+use super::cache::PreviewCache;
+use crate::previewers::{meta, Preview};
+use television_channels::bus::Message;
+use television_channels::context::Context;
+use television_channels::entry;
+
+/// Previews the entries of the plugin channels with the `preview` function
+/// of their plugin.
+#[derive(Debug)]
+pub struct PluginPreviewer {
+    cache: Arc<Mutex<PreviewCache>>,
+    /// The context the plugins are registered in, along with the bus.
+    ctx: Context,
+}
+
+impl PluginPreviewer {
+    pub fn new(ctx: &Context) -> Self {
+        PluginPreviewer {
+            cache: Arc::default(),
+            ctx: ctx.clone(),
+        }
+    }
+
+    /// Preview an entry with the plugin of the given name.
+    ///
+    /// The plugin is run in the background and a loading preview is
+    /// returned in the meantime, replaced in the cache by the text the
+    /// plugin returns (or the error it failed with).
+    pub fn preview(
+        &mut self,
+        entry: &entry::Entry,
+        plugin_name: &str,
+    ) -> Arc<Preview> {
+        let key = format!("{plugin_name}\0{}", entry.name);
+        if let Some(preview) = self.cache.lock().get(&key) {
+            return preview.clone();
+        }
+        let Some(plugin) = self.ctx.plugin(plugin_name) else {
+            return meta::not_supported(&entry.name);
+        };
+        let preview = meta::loading(&entry.name);
+        self.cache.lock().insert(key.clone(), preview.clone());
+
+        let (cache, bus) = (self.cache.clone(), self.ctx.bus.clone());
+        let title = entry.name.clone();
+        tokio::task::spawn_blocking(move || {
+            let text =
+                plugin.preview(&title).unwrap_or_else(|e| e.to_string());
+            let preview = Arc::new(Preview::from_text(title.clone(), &text));
+            cache.lock().insert(key, preview);
+            bus.send(Message::PreviewReady(title));
+        });
+        preview
+    }
+
+    /// The approximate number of bytes taken by the cached previews.
+    pub fn memory_usage(&self) -> usize {
+        self.cache.lock().memory_usage()
+    }
+}
@@ -0,0 +1,63 @@
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+use super::cache::PreviewCache;
+use crate::previewers::{meta, Preview};
+use television_channels::bus::Message;
+use television_channels::context::Context;
+use television_channels::entry;
+
+/// Previews the entries of the script channels with the `preview` function
+/// of their script.
+#[derive(Debug)]
+pub struct ScriptPreviewer {
+    cache: Arc<Mutex<PreviewCache>>,
+    /// The context the scripts are registered in, along with the bus.
+    ctx: Context,
+}
+
+impl ScriptPreviewer {
+    pub fn new(ctx: &Context) -> Self {
+        ScriptPreviewer {
+            cache: Arc::default(),
+            ctx: ctx.clone(),
+        }
+    }
+
+    /// Preview an entry with the script of the given name.
+    ///
+    /// The script is run in the background and a loading preview is
+    /// returned in the meantime, replaced in the cache by the text the
+    /// script returns (or the error it failed with).
+    pub fn preview(
+        &mut self,
+        entry: &entry::Entry,
+        script_name: &str,
+    ) -> Arc<Preview> {
+        let key = format!("{script_name}\0{}", entry.name);
+        if let Some(preview) = self.cache.lock().get(&key) {
+            return preview.clone();
+        }
+        let Some(script) = self.ctx.script(script_name) else {
+            return meta::not_supported(&entry.name);
+        };
+        let preview = meta::loading(&entry.name);
+        self.cache.lock().insert(key.clone(), preview.clone());
+
+        let (cache, bus) = (self.cache.clone(), self.ctx.bus.clone());
+        let title = entry.name.clone();
+        tokio::task::spawn_blocking(move || {
+            let text =
+                script.preview(&title).unwrap_or_else(|e| e.to_string());
+            let preview = Arc::new(Preview::from_text(title.clone(), &text));
+            cache.lock().insert(key, preview);
+            bus.send(Message::PreviewReady(title));
+        });
+        preview
+    }
+
+    /// The approximate number of bytes taken by the cached previews.
+    pub fn memory_usage(&self) -> usize {
+        self.cache.lock().memory_usage()
+    }
+}
@@ -0,0 +1,444 @@
+//! Scaffolding of new built-in channels.
+//!
+//! Given the command line name of a new channel (e.g. `my-channel`), this
+//! writes a module describing the channel with a `ChannelBuilder`, whose
+//! constructors and `OnAir` implementation are generated by the
+//! `FromBuilder` derive of `television-derive`, along with a test loading
+//! its entries. It then wires the channel up:
+//! - its `mod` declaration in `channels.rs`;
+//! - its `TelevisionChannel` variant, from which the `ToCliChannel`,
+//!   `ToUnitChannel` and `Broadcast` derives generate the `CliTvChannel`
+//!   and `UnitChannel` variants and the `OnAir` forwarding;
+//! - its arms in `TelevisionChannel::from_entry` and `variant_to_module!`;
+//! - its name in the channels listed by the README.
+//!
+//! All that's left is listing the channel's items, and describing it.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CHANNELS_MODULE: &str = "crates/television_channels/src/channels.rs";
+const CHANNELS_DIR: &str = "crates/television_channels/src/channels";
+const README: &str = "README.md";
+
+/// New channels are inserted right before this one, after the other
+/// built-in channels listing their own entries.
+const NEXT_VARIANT: &str = "HiddenEntries";
+const NEXT_MODULE: &str = "hidden_entries";
+const NEXT_CLI_NAME: &str = "hidden-entries";
+
+/// The names of a channel, derived from its command line name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelNames {
+    /// The command line name, e.g. `my-channel`.
+    pub cli: String,
+    /// The name of its module, e.g. `my_channel`.
+    pub module: String,
+    /// The name of its `TelevisionChannel` variant, e.g. `MyChannel`.
+    pub variant: String,
+    /// The lowercase name of the channel, used to pick it from the remote
+    /// control and in its settings, e.g. `mychannel`.
+    pub name: String,
+}
+
+impl ChannelNames {
+    /// The names of the channel with the given command line name, made of
+    /// lowercase words separated by dashes.
+    pub fn new(cli: &str) -> Result<Self, String> {
+        let valid = cli.starts_with(|c: char| c.is_ascii_lowercase())
+            && cli.split('-').all(|word| {
+                !word.is_empty()
+                    && word
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            });
+        if !valid {
+            return Err(format!(
+                "invalid channel name `{cli}`, expected lowercase words \
+                 separated by dashes (e.g. `my-channel`)"
+            ));
+        }
+        let variant = cli
+            .split('-')
+            .map(|word| word[..1].to_uppercase() + &word[1..])
+            .collect();
+        Ok(Self {
+            cli: cli.to_string(),
+            module: cli.replace('-', "_"),
+            variant,
+            name: cli.replace('-', ""),
+        })
+    }
+}
+
+/// Scaffold the channel with the given command line name in the repository
+/// at `root`, returning the paths of the files written.
+///
+/// Nothing is written if the channel can't be wired up (e.g. if it already
+/// exists).
+pub fn scaffold(root: &Path, cli: &str) -> Result<Vec<PathBuf>, String> {
+    let names = ChannelNames::new(cli)?;
+    let module_path =
+        root.join(CHANNELS_DIR).join(format!("{}.rs", names.module));
+    if module_path.exists() {
+        return Err(format!("{} already exists", module_path.display()));
+    }
+    let channels_path = root.join(CHANNELS_MODULE);
+    let channels = wire_channel(&read(&channels_path)?, &names)?;
+    let readme_path = root.join(README);
+    let readme = list_in_readme(&read(&readme_path)?, &names)?;
+
+    let files = [
+        (module_path, module_source(&names)),
+        (channels_path, channels),
+        (readme_path, readme),
+    ];
+    for (path, contents) in &files {
+        fs::write(path, contents)
+            .map_err(|e| format!("writing {}: {e}", path.display()))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path)
+        .map_err(|e| format!("reading {}: {e}", path.display()))
+}
+
+/// The source of the module of the channel: a `ChannelBuilder` listing no
+/// items yet, and a test checking the channel displays them all.
+pub fn module_source(names: &ChannelNames) -> String {
+    let ChannelNames { module, name, .. } = names;
+    format!(
+        r#"use television_derive::FromBuilder;
+use television_utils::cancel::CancellationToken;
+
+use super::builder::{{BuiltChannel, ChannelBuilder}};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Item {{
+    name: String,
+}}
+
+#[derive(FromBuilder)]
+pub struct Channel(BuiltChannel<Item>);
+
+impl Channel {{
+    fn builder() -> ChannelBuilder<Item> {{
+        ChannelBuilder::new("{name}", items, |item| {{
+            (item.name.clone(), None)
+        }})
+    }}
+}}
+
+/// The items of the channel, giving up once `token` is cancelled.
+fn items(_token: &CancellationToken) -> Vec<Item> {{
+    // TODO: list the items of the channel
+    Vec::new()
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+    use crate::channels::OnAir;
+    use crate::context::Context;
+
+    #[tokio::test]
+    async fn test_{module}_entries() {{
+        let mut channel = Channel::new(&Context::default());
+        channel.find("");
+        let results = loop {{
+            tokio::task::yield_now().await;
+            let results = channel.results(u32::MAX, 0);
+            if !channel.loading() && !channel.running() {{
+                break results;
+            }}
+        }};
+        let items = items(&CancellationToken::new());
+        assert_eq!(results.len(), items.len());
+        assert!(results
+            .iter()
+            .all(|entry| items.iter().any(|item| item.name == entry.name)));
+    }}
+}}
+"#
+    )
+}
+
+/// Declare the module of the channel in `channels.rs`, and add its variant
+/// and arms right before the ones of the hidden entries channel.
+pub fn wire_channel(
+    channels: &str,
+    names: &ChannelNames,
+) -> Result<String, String> {
+    let ChannelNames {
+        module,
+        variant,
+        name,
+        ..
+    } = names;
+    if channels.contains(&format!("    {variant}(")) {
+        return Err(format!("the `{variant}` channel already exists"));
+    }
+    let channels = declare_module(channels, module)?;
+    let channels = insert_before(
+        &channels,
+        &format!("{NEXT_VARIANT}({NEXT_MODULE}::Channel),"),
+        &format!(
+            "    /// The {description} channel.
+    ///
+    /// TODO: describe what this channel allows to search through.
+    {variant}({module}::Channel),
+",
+            description = names.cli.replace('-', " "),
+        ),
+    )?;
+    let channels = insert_before(
+        &channels,
+        &format!("\"{}\" =>", NEXT_VARIANT.to_lowercase()),
+        &format!(
+            "            \"{name}\" => Ok(TelevisionChannel::{variant}(
+                {module}::Channel::from_context(ctx),
+            )),
+"
+        ),
+    )?;
+    insert_before(
+        &channels,
+        &format!("({NEXT_VARIANT}) => {{"),
+        &format!(
+            "    ({variant}) => {{
+        {module}::Channel
+    }};
+"
+        ),
+    )
+}
+
+/// Declare the module among the others, in alphabetical order.
+fn declare_module(channels: &str, module: &str) -> Result<String, String> {
+    let declared = |line: &str| {
+        line.trim_start_matches("pub ")
+            .strip_prefix("mod ")
+            .and_then(|line| line.strip_suffix(';'))
+            .map(str::to_string)
+    };
+    let modules: Vec<String> = channels.lines().filter_map(declared).collect();
+    if modules.iter().any(|declared| declared == module) {
+        return Err(format!("the `{module}` module already exists"));
+    }
+    match modules.iter().find(|declared| declared.as_str() > module) {
+        Some(next) => insert_before(
+            channels,
+            &format!("mod {next};"),
+            &format!("mod {module};\n"),
+        ),
+        None => {
+            let last = modules.last().ok_or("no modules are declared")?;
+            insert_after(
+                channels,
+                &format!("mod {last};"),
+                &format!("mod {module};\n"),
+            )
+        }
+    }
+}
+
+/// Insert `text` before the first line ending with (or, once trimmed,
+/// starting with) `anchor`, and the doc comments and attributes above it.
+fn insert_before(
+    source: &str,
+    anchor: &str,
+    text: &str,
+) -> Result<String, String> {
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let mut index = find_line(&lines, anchor)?;
+    while index > 0 {
+        let previous = lines[index - 1].trim_start();
+        if !(previous.starts_with("///") || previous.starts_with("#[")) {
+            break;
+        }
+        index -= 1;
+    }
+    Ok(splice(&lines, index, text))
+}
+
+/// Insert `text` after the first line ending with (or, once trimmed,
+/// starting with) `anchor`.
+fn insert_after(
+    source: &str,
+    anchor: &str,
+    text: &str,
+) -> Result<String, String> {
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let index = find_line(&lines, anchor)?;
+    Ok(splice(&lines, index + 1, text))
+}
+
+fn find_line(lines: &[&str], anchor: &str) -> Result<usize, String> {
+    lines
+        .iter()
+        .position(|line| {
+            let line = line.trim();
+            line.starts_with(anchor) || line.ends_with(anchor)
+        })
+        .ok_or_else(|| format!("could not find `{anchor}`"))
+}
+
+fn splice(lines: &[&str], index: usize, text: &str) -> String {
+    let mut source = lines[..index].concat();
+    source.push_str(text);
+    source.push_str(&lines[index..].concat());
+    source
+}
+
+/// Add the channel to the possible values of the channel argument listed
+/// in the README.
+pub fn list_in_readme(
+    readme: &str,
+    names: &ChannelNames,
+) -> Result<String, String> {
+    let next = format!(", {NEXT_CLI_NAME},");
+    if !readme.contains(&next) {
+        return Err(format!("could not find `{NEXT_CLI_NAME}` in the README"));
+    }
+    Ok(readme.replacen(&next, &format!(", {}{next}", names.cli), 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANNELS: &str =
+        include_str!("../../television_channels/src/channels.rs");
+
+    fn names() -> ChannelNames {
+        ChannelNames::new("k8s-pods").unwrap()
+    }
+
+    /// The lines of `new` missing from `old`, which only had lines added.
+    fn added_lines<'a>(old: &str, new: &'a str) -> Vec<&'a str> {
+        let mut old = old.lines().peekable();
+        new.lines()
+            .filter(|line| {
+                if old.peek() == Some(line) {
+                    old.next();
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_channel_names() {
+        assert_eq!(
+            names(),
+            ChannelNames {
+                cli: "k8s-pods".to_string(),
+                module: "k8s_pods".to_string(),
+                variant: "K8sPods".to_string(),
+                name: "k8spods".to_string(),
+            }
+        );
+        for invalid in ["", "MyChannel", "my_channel", "-pods", "pods-", "8s"]
+        {
+            assert!(ChannelNames::new(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_wire_channel() {
+        let wired = wire_channel(CHANNELS, &names()).unwrap();
+        let added = added_lines(CHANNELS, &wired);
+        assert_eq!(
+            added,
+            [
+                "mod k8s_pods;",
+                "    /// The k8s pods channel.",
+                "    ///",
+                "    /// TODO: describe what this channel allows to search \
+                 through.",
+                "    K8sPods(k8s_pods::Channel),",
+                "            \"k8spods\" => Ok(TelevisionChannel::K8sPods(",
+                "                k8s_pods::Channel::from_context(ctx),",
+                "            )),",
+                "    (K8sPods) => {",
+                "        k8s_pods::Channel",
+                "    };",
+            ]
+        );
+        // the variant goes before the doc comment of the next one
+        assert!(wired.contains(
+            "    K8sPods(k8s_pods::Channel),\n    /// The hidden entries \
+             channel."
+        ));
+        // and the module among the others, in alphabetical order
+        assert!(wired.contains("mod journal;\nmod k8s_pods;\n#[cfg("));
+
+        assert!(wire_channel(&wired, &names()).is_err());
+    }
+
+    #[test]
+    fn test_declare_module() {
+        let channels = "use a;\n\npub mod alias;\n#[cfg(feature = \"x\")]\n\
+                        mod cron;\nmod text;\n\nfn f() {}\n";
+        assert_eq!(
+            declare_module(channels, "config").unwrap(),
+            "use a;\n\npub mod alias;\nmod config;\n#[cfg(feature = \
+             \"x\")]\nmod cron;\nmod text;\n\nfn f() {}\n"
+        );
+        assert_eq!(
+            declare_module(channels, "zsh").unwrap(),
+            "use a;\n\npub mod alias;\n#[cfg(feature = \"x\")]\nmod \
+             cron;\nmod text;\nmod zsh;\n\nfn f() {}\n"
+        );
+        assert!(declare_module(channels, "cron").is_err());
+    }
+
+    #[test]
+    fn test_list_in_readme() {
+        let readme = "[possible values: env, files, hidden-entries, history]";
+        assert_eq!(
+            list_in_readme(readme, &names()).unwrap(),
+            "[possible values: env, files, k8s-pods, hidden-entries, \
+             history]"
+        );
+        assert!(list_in_readme("[possible values: env]", &names()).is_err());
+    }
+
+    #[test]
+    fn test_module_source() {
+        let source = module_source(&names());
+        assert!(source.contains("#[derive(FromBuilder)]"));
+        assert!(source.contains("ChannelBuilder::new(\"k8spods\", items,"));
+        assert!(source.contains("async fn test_k8s_pods_entries()"));
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let root = std::env::temp_dir()
+            .join(format!("tv-scaffold-{}", std::process::id()));
+        fs::create_dir_all(root.join(CHANNELS_DIR)).unwrap();
+        fs::write(root.join(CHANNELS_MODULE), CHANNELS).unwrap();
+        fs::write(root.join(README), "env, hidden-entries, history").unwrap();
+
+        let written = scaffold(&root, "k8s-pods").unwrap();
+        assert_eq!(written[0], root.join(CHANNELS_DIR).join("k8s_pods.rs"));
+        assert_eq!(
+            fs::read_to_string(&written[0]).unwrap(),
+            module_source(&names())
+        );
+        assert!(read(&root.join(CHANNELS_MODULE))
+            .unwrap()
+            .contains("K8sPods(k8s_pods::Channel)"));
+        assert_eq!(
+            read(&root.join(README)).unwrap(),
+            "env, k8s-pods, hidden-entries, history"
+        );
+
+        // the channel can't be scaffolded twice
+        assert!(scaffold(&root, "k8s-pods").is_err());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
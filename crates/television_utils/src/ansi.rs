@@ -0,0 +1,292 @@
+use std::ops::Range;
+
+use crate::strings::{replace_non_printable, truncate_line, TAB_WIDTH};
+
+const ESCAPE: char = '\x1b';
+const BELL: char = '\x07';
+
+/// A color set by an SGR escape sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AnsiColor {
+    /// One of the 256 colors of the terminal's palette, the first 16 being
+    /// the basic and bright colors (e.g. `31` or `38;5;1` for red).
+    Indexed(u8),
+    /// A truecolor (e.g. `38;2;255;136;0`).
+    Rgb(u8, u8, u8),
+}
+
+/// The style set by SGR escape sequences, `None` colors being the
+/// terminal's default ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AnsiStyle {
+    pub fg: Option<AnsiColor>,
+    pub bg: Option<AnsiColor>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub reversed: bool,
+    pub crossed_out: bool,
+}
+
+impl AnsiStyle {
+    /// Apply the parameters of an SGR sequence (the `1;31` of `ESC[1;31m`).
+    fn apply(&mut self, params: &str) {
+        let mut params = params.split(';');
+        while let Some(param) = params.next() {
+            // the extended colors may also be written with colons, as in
+            // `38:2::255:136:0`
+            let mut subparams = param.split(':');
+            let code = subparams.next().unwrap_or_default();
+            match code.parse::<u8>().unwrap_or(0) {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underlined = true,
+                7 => self.reversed = true,
+                9 => self.crossed_out = true,
+                22 => (self.bold, self.dim) = (false, false),
+                23 => self.italic = false,
+                24 => self.underlined = false,
+                27 => self.reversed = false,
+                29 => self.crossed_out = false,
+                n @ 30..=37 => self.fg = Some(AnsiColor::Indexed(n - 30)),
+                38 => {
+                    let color = if param.contains(':') {
+                        extended_color(&mut subparams, true)
+                    } else {
+                        extended_color(&mut params, false)
+                    };
+                    self.fg = color.or(self.fg);
+                }
+                39 => self.fg = None,
+                n @ 40..=47 => self.bg = Some(AnsiColor::Indexed(n - 40)),
+                48 => {
+                    let color = if param.contains(':') {
+                        extended_color(&mut subparams, true)
+                    } else {
+                        extended_color(&mut params, false)
+                    };
+                    self.bg = color.or(self.bg);
+                }
+                49 => self.bg = None,
+                n @ 90..=97 => self.fg = Some(AnsiColor::Indexed(n - 90 + 8)),
+                n @ 100..=107 => {
+                    self.bg = Some(AnsiColor::Indexed(n - 100 + 8));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parse the color following a `38` or `48` parameter: `5;<index>` or
+/// `2;<r>;<g>;<b>` (with colons, the truecolor components may be preceded
+/// by an empty color space id).
+fn extended_color<'a>(
+    params: &mut impl Iterator<Item = &'a str>,
+    colons: bool,
+) -> Option<AnsiColor> {
+    let mut next = || params.next().and_then(|p| p.parse::<u8>().ok());
+    match next()? {
+        5 => next().map(AnsiColor::Indexed),
+        2 => {
+            let mut components: Vec<u8> = Vec::with_capacity(4);
+            let count = if colons { 4 } else { 3 };
+            for _ in 0..count {
+                match next() {
+                    Some(c) => components.push(c),
+                    // an empty color space id
+                    None if colons && components.is_empty() => {}
+                    None => break,
+                }
+            }
+            match components[..] {
+                [r, g, b] | [_, r, g, b] => Some(AnsiColor::Rgb(r, g, b)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A line of text stripped of its escape sequences, along with the styles
+/// the SGR sequences among them gave its parts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AnsiLine {
+    /// The text, whose non-printable characters are replaced as by
+    /// `replace_non_printable`.
+    pub text: String,
+    /// The byte ranges of the text that aren't in the default style, and
+    /// their style, in order.
+    pub styles: Vec<(Range<usize>, AnsiStyle)>,
+}
+
+impl AnsiLine {
+    /// Parse the escape sequences of the given line, keeping the styles set
+    /// by the SGR ones (colors and text attributes) and dropping the others
+    /// (e.g. cursor movements or hyperlinks).
+    ///
+    /// # Examples
+    /// ```
+    /// use television_utils::ansi::{AnsiColor, AnsiLine, AnsiStyle};
+    ///
+    /// let line = AnsiLine::parse("\x1b[1;31merror\x1b[0m: oops");
+    /// assert_eq!(line.text, "error: oops");
+    /// assert_eq!(
+    ///     line.styles,
+    ///     vec![(
+    ///         0..5,
+    ///         AnsiStyle {
+    ///             fg: Some(AnsiColor::Indexed(1)),
+    ///             bold: true,
+    ///             ..AnsiStyle::default()
+    ///         }
+    ///     )]
+    /// );
+    /// ```
+    pub fn parse(line: &str) -> Self {
+        let mut parsed = Self::default();
+        let mut style = AnsiStyle::default();
+        let mut rest = line;
+        while !rest.is_empty() {
+            let end = rest.find(ESCAPE).unwrap_or(rest.len());
+            parsed.push(&rest[..end], style);
+            rest = &rest[end..];
+            let mut chars = rest.chars();
+            if chars.next() != Some(ESCAPE) {
+                break;
+            }
+            rest = match chars.next() {
+                // control sequence: parameters, intermediate bytes and a
+                // final byte
+                Some('[') => {
+                    let sequence = &rest[2..];
+                    let end = sequence
+                        .find(|c| matches!(c, '\x40'..='\x7e'))
+                        .unwrap_or(sequence.len());
+                    if sequence[end..].starts_with('m') {
+                        style.apply(&sequence[..end]);
+                    }
+                    sequence.get(end + 1..).unwrap_or_default()
+                }
+                // operating system command, ended by BEL or ESC \
+                Some(']') => {
+                    let sequence = &rest[2..];
+                    match sequence.find([BELL, ESCAPE]) {
+                        Some(end) if sequence[end..].starts_with(BELL) => {
+                            &sequence[end + 1..]
+                        }
+                        Some(end) => sequence
+                            .get(end..)
+                            .and_then(|s| s.strip_prefix("\x1b\\"))
+                            .unwrap_or(&sequence[end..]),
+                        None => "",
+                    }
+                }
+                // a two characters sequence
+                Some(c) => &rest[1 + c.len_utf8()..],
+                None => "",
+            };
+        }
+        parsed
+    }
+
+    fn push(&mut self, text: &str, style: AnsiStyle) {
+        if text.is_empty() {
+            return;
+        }
+        let start = self.text.len();
+        self.text
+            .push_str(&replace_non_printable(text.as_bytes(), TAB_WIDTH));
+        if style == AnsiStyle::default() {
+            return;
+        }
+        let end = self.text.len();
+        match self.styles.last_mut() {
+            Some((range, last)) if range.end == start && *last == style => {
+                range.end = end;
+            }
+            _ => self.styles.push((start..end, style)),
+        }
+    }
+}
+
+/// The `preprocess_line` of lines containing escape sequences: the line,
+/// truncated (escape sequences included) and trimmed the same way, is
+/// parsed.
+pub fn preprocess_ansi_line(line: &str) -> AnsiLine {
+    AnsiLine::parse(truncate_line(line))
+}
+
+/// Whether the given line contains escape sequences to parse.
+pub fn has_escapes(line: &str) -> bool {
+    line.contains(ESCAPE)
+}
+
+/// The given line without its escape sequences.
+///
+/// # Examples
+/// ```
+/// use television_utils::ansi::strip;
+///
+/// assert_eq!(strip("\x1b[35msrc/main.rs\x1b[0m:\x1b[32m12\x1b[0m"), "src/main.rs:12");
+/// ```
+pub fn strip(line: &str) -> String {
+    AnsiLine::parse(line).text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fg(color: AnsiColor) -> AnsiStyle {
+        AnsiStyle {
+            fg: Some(color),
+            ..AnsiStyle::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_colors() {
+        let line = AnsiLine::parse(
+            "\x1b[38;5;208ma\x1b[38;2;1;2;3mb\x1b[38:2::4:5:6mc\x1b[92md\x1b[39me",
+        );
+        assert_eq!(line.text, "abcde");
+        assert_eq!(
+            line.styles,
+            vec![
+                (0..1, fg(AnsiColor::Indexed(208))),
+                (1..2, fg(AnsiColor::Rgb(1, 2, 3))),
+                (2..3, fg(AnsiColor::Rgb(4, 5, 6))),
+                (3..4, fg(AnsiColor::Indexed(10))),
+            ]
+        );
+
+        let line = AnsiLine::parse("\x1b[1;44mab\x1b[22mc\x1b[mdef");
+        let bg = AnsiStyle {
+            bg: Some(AnsiColor::Indexed(4)),
+            ..AnsiStyle::default()
+        };
+        assert_eq!(
+            line.styles,
+            vec![(0..2, AnsiStyle { bold: true, ..bg }), (2..3, bg)]
+        );
+    }
+
+    #[test]
+    fn test_parse_other_sequences() {
+        // cursor movements, hyperlinks and truncated sequences are dropped
+        assert_eq!(strip("a\x1b[2Kb\x1b[1Gc"), "abc");
+        assert_eq!(
+            strip("\x1b]8;;file:///etc\x1b\\etc\x1b]8;;\x07/passwd"),
+            "etc/passwd"
+        );
+        assert_eq!(strip("abc\x1b[38;5"), "abc");
+        assert_eq!(strip("abc\x1b"), "abc");
+        // the other control characters are replaced as usual
+        assert_eq!(strip("a\tb"), "a    b");
+        assert!(!has_escapes("abc") && has_escapes("\x1b[0mabc"));
+    }
+}
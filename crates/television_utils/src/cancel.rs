@@ -0,0 +1,143 @@
+use std::io::{self, Read};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Tells some background work (walking directories, running a command, ...)
+/// that its result isn't needed anymore, so that it stops as soon as it
+/// checks the token instead of running to completion.
+///
+/// Clones share the same state: cancelling any of them cancels them all.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// The flag set once cancelled, for the APIs taking one (e.g.
+    /// `Sandbox::run`).
+    pub fn as_flag(&self) -> &AtomicBool {
+        &self.cancelled
+    }
+}
+
+/// Run the given command and collect its output, like `Command::output`,
+/// killing it as soon as the token is cancelled.
+///
+/// Unlike sandboxed commands, the command stays in our process group (so that
+/// e.g. interactive shells don't get stopped when touching the terminal),
+/// hence only the command itself is killed, not the processes it spawned.
+///
+/// # Errors
+/// An `Interrupted` error if the command was cancelled, or the error that
+/// occurred while spawning it.
+///
+/// # Examples
+/// ```
+/// use television_utils::cancel::{output, CancellationToken};
+/// use television_utils::shell::shell_command;
+///
+/// #[cfg(unix)]
+/// {
+///     let token = CancellationToken::new();
+///     let output = output(&mut shell_command("echo hello"), &token).unwrap();
+///     assert_eq!(output.stdout, b"hello\n");
+/// }
+/// ```
+pub fn output(
+    command: &mut Command,
+    token: &CancellationToken,
+) -> io::Result<Output> {
+    if token.is_cancelled() {
+        return Err(cancelled());
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+    let status = loop {
+        if token.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(cancelled());
+        }
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Read the given pipe to the end in the background.
+fn read_pipe<R>(pipe: Option<R>) -> JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "cancelled")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::shell::shell_command;
+
+    #[test]
+    fn test_output() {
+        let token = CancellationToken::new();
+        let out = output(&mut shell_command("echo out; echo err >&2"), &token);
+        let out = out.unwrap();
+        assert!(out.status.success());
+        assert_eq!(
+            (&out.stdout[..], &out.stderr[..]),
+            (&b"out\n"[..], &b"err\n"[..])
+        );
+
+        let start = Instant::now();
+        let cancel = token.clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            cancel.cancel();
+        });
+        let out = output(&mut shell_command("sleep 10"), &token);
+        assert_eq!(out.unwrap_err().kind(), io::ErrorKind::Interrupted);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        canceller.join().unwrap();
+        assert!(token.is_cancelled());
+    }
+}
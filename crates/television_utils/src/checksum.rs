@@ -0,0 +1,135 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// The checksums of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksums {
+    pub sha256: String,
+    pub md5: String,
+}
+
+/// The state of a checksum computation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// The checksums are being computed, the value being the proportion of
+    /// the file (in percent) that was read so far.
+    Running(u8),
+    Done(Checksums),
+    Failed(String),
+}
+
+/// The computation of the checksums of a file, in a background thread.
+///
+/// Both checksums are computed in a single pass over the file.
+#[derive(Debug, Clone)]
+pub struct Checksum {
+    pub path: PathBuf,
+    size: u64,
+    read: Arc<AtomicU64>,
+    result: Arc<Mutex<Option<io::Result<Checksums>>>>,
+}
+
+impl Checksum {
+    /// Start computing the checksums of the given file.
+    pub fn spawn(path: PathBuf) -> Self {
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let read = Arc::new(AtomicU64::new(0));
+        let result = Arc::new(Mutex::new(None));
+        {
+            let path = path.clone();
+            let read = read.clone();
+            let result = result.clone();
+            thread::spawn(move || {
+                let checksums = compute(&path, &read);
+                *result.lock().unwrap() = Some(checksums);
+            });
+        }
+        Checksum {
+            path,
+            size,
+            read,
+            result,
+        }
+    }
+
+    pub fn status(&self) -> ChecksumStatus {
+        match &*self.result.lock().unwrap() {
+            Some(Ok(checksums)) => ChecksumStatus::Done(checksums.clone()),
+            Some(Err(e)) => ChecksumStatus::Failed(e.to_string()),
+            None => {
+                let read = self.read.load(Ordering::Relaxed);
+                let percent = (read * 100).checked_div(self.size).unwrap_or(0);
+                ChecksumStatus::Running(
+                    u8::try_from(percent.min(100)).unwrap_or(100),
+                )
+            }
+        }
+    }
+}
+
+fn compute(path: &Path, read: &AtomicU64) -> io::Result<Checksums> {
+    let mut file = File::open(path)?;
+    let mut sha256 = Sha256::new();
+    let mut md5 = Md5::new();
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        sha256.update(&buffer[..n]);
+        md5.update(&buffer[..n]);
+        read.fetch_add(n as u64, Ordering::Relaxed);
+    }
+    Ok(Checksums {
+        sha256: hex(&sha256.finalize()),
+        md5: hex(&md5.finalize()),
+    })
+}
+
+/// The sha256 checksum of the given bytes, in hexadecimal.
+pub fn sha256(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hello");
+        std::fs::write(&path, "hello\n").unwrap();
+        let checksum = Checksum::spawn(path.clone());
+        let status = loop {
+            match checksum.status() {
+                ChecksumStatus::Running(_) => {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                status => break status,
+            }
+        };
+        assert_eq!(
+            status,
+            ChecksumStatus::Done(Checksums {
+                sha256: "5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03".to_string(),
+                md5: "b1946ac92492d2347c6235b4d2611184".to_string(),
+            })
+        );
+    }
+}
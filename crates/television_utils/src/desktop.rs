@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::files::is_not_text;
+use crate::shell::{spawn_background, ExecutionBackend};
+use crate::strings::shell_quote;
+
+/// An application described by a freedesktop `.desktop` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopApplication {
+    pub name: String,
+    /// The command line template, with field codes such as `%f`.
+    exec: String,
+    /// Whether the application runs in a terminal.
+    pub terminal: bool,
+    mime_types: Vec<String>,
+}
+
+impl DesktopApplication {
+    /// Parse the `[Desktop Entry]` group of a `.desktop` file, skipping
+    /// entries that aren't (displayed) applications.
+    fn parse(content: &str) -> Option<Self> {
+        let mut fields = HashMap::new();
+        let mut in_entry = false;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_entry = line == "[Desktop Entry]";
+            } else if let Some((key, value)) =
+                line.split_once('=').filter(|_| in_entry)
+            {
+                // localized keys (e.g. `Name[fr]`) are ignored
+                fields.insert(key.trim(), value.trim());
+            }
+        }
+        let flag = |key| fields.get(key).is_some_and(|v| *v == "true");
+        if fields.get("Type").is_some_and(|t| *t != "Application")
+            || flag("NoDisplay")
+            || flag("Hidden")
+        {
+            return None;
+        }
+        Some(DesktopApplication {
+            name: fields.get("Name")?.to_string(),
+            exec: fields.get("Exec")?.to_string(),
+            terminal: flag("Terminal"),
+            mime_types: fields
+                .get("MimeType")
+                .map(|m| {
+                    m.split(';')
+                        .filter(|m| !m.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    fn handles(&self, mime_type: &str) -> bool {
+        let family = mime_type.split('/').next().unwrap_or_default();
+        self.mime_types.iter().any(|m| {
+            m == mime_type || m.strip_suffix("/*").is_some_and(|f| f == family)
+        })
+    }
+
+    /// The shell command opening the given file with the application.
+    ///
+    /// File and URL field codes are replaced by the (quoted) path, and the
+    /// other ones are dropped.
+    pub fn command(&self, path: &Path) -> String {
+        let path = shell_quote(&path.to_string_lossy());
+        let mut command = String::new();
+        let mut has_file = false;
+        let mut chars = self.exec.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                command.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('f' | 'F' | 'u' | 'U') => {
+                    command.push_str(&path);
+                    has_file = true;
+                }
+                Some('%') => command.push('%'),
+                _ => {}
+            }
+        }
+        let mut command = command.trim().to_string();
+        if !has_file {
+            command.push(' ');
+            command.push_str(&path);
+        }
+        command
+    }
+
+    /// Open the given file with the (graphical) application, in the
+    /// background.
+    pub fn launch(
+        &self,
+        path: &Path,
+        backend: ExecutionBackend,
+    ) -> io::Result<()> {
+        spawn_background(&self.command(path), backend)
+    }
+}
+
+/// The command opening files with their default application.
+#[cfg(target_os = "macos")]
+const OPEN_COMMAND: &str = "open";
+#[cfg(windows)]
+const OPEN_COMMAND: &str = "start \"\"";
+#[cfg(not(any(target_os = "macos", windows)))]
+const OPEN_COMMAND: &str = "xdg-open";
+
+/// Open the given file (or directory) with its default application, in the
+/// background.
+pub fn open_default(path: &Path, backend: ExecutionBackend) -> io::Result<()> {
+    spawn_background(
+        &format!("{OPEN_COMMAND} {}", shell_quote(&path.to_string_lossy())),
+        backend,
+    )
+}
+
+/// The directories `.desktop` files are looked up in, by decreasing
+/// priority.
+fn application_dirs() -> Vec<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".local/share"))
+        });
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    data_home
+        .into_iter()
+        .chain(data_dirs.split(':').map(PathBuf::from))
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// The mime type of the given file, as known by the system (`xdg-mime`) or
+/// guessed from its content.
+pub fn mime_type(path: &Path) -> Option<String> {
+    if path.is_dir() {
+        return Some("inode/directory".to_string());
+    }
+    if let Some(mime_type) = Command::new("xdg-mime")
+        .args(["query", "filetype"])
+        .arg(path)
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        })
+        .filter(|mime_type| !mime_type.is_empty())
+    {
+        return Some(mime_type);
+    }
+    if let Ok(Some(kind)) = infer::get_from_path(path) {
+        return Some(kind.mime_type().to_string());
+    }
+    let mut buffer = [0u8; 256];
+    let n = std::fs::File::open(path)
+        .and_then(|mut f| f.read(&mut buffer))
+        .ok()?;
+    (is_not_text(&buffer[..n]) != Some(true)).then(|| "text/plain".to_string())
+}
+
+/// The applications that can open the given file, sorted by name.
+pub fn applications_for(path: &Path) -> Vec<DesktopApplication> {
+    let Some(mime_type) = mime_type(path) else {
+        return Vec::new();
+    };
+    // desktop file ids shadow the ones from lower priority directories
+    let mut applications: HashMap<String, Option<DesktopApplication>> =
+        HashMap::new();
+    for dir in application_dirs() {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "desktop") {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            applications.entry(id).or_insert_with(|| {
+                std::fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| DesktopApplication::parse(&content))
+            });
+        }
+    }
+    let mut applications: Vec<DesktopApplication> = applications
+        .into_values()
+        .flatten()
+        .filter(|app| app.handles(&mime_type))
+        .collect();
+    applications.sort_by(|a, b| a.name.cmp(&b.name));
+    applications
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESKTOP_FILE: &str = "\
+[Desktop Entry]
+Type=Application
+Name=Text Editor
+Name[fr]=Éditeur de texte
+Exec=editor --new-window %U
+Terminal=false
+MimeType=text/plain;text/x-rust;image/*;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=editor --new-window
+";
+
+    #[test]
+    fn test_parse() {
+        let app = DesktopApplication::parse(DESKTOP_FILE).unwrap();
+        assert_eq!(app.name, "Text Editor");
+        assert!(!app.terminal);
+        assert!(app.handles("text/x-rust"));
+        assert!(app.handles("image/png"));
+        assert!(!app.handles("text/html"));
+        assert!(DesktopApplication::parse(
+            &DESKTOP_FILE.replace("Terminal=false", "NoDisplay=true")
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_command() {
+        let app = DesktopApplication::parse(DESKTOP_FILE).unwrap();
+        assert_eq!(
+            app.command(Path::new("my file.rs")),
+            format!("editor --new-window {}", shell_quote("my file.rs"))
+        );
+        let app = DesktopApplication {
+            exec: "viewer --percent=100%% %i".to_string(),
+            ..app
+        };
+        assert_eq!(
+            app.command(Path::new("a.png")),
+            format!("viewer --percent=100% {}", shell_quote("a.png"))
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use std::{env, path::PathBuf};
+
+use directories::ProjectDirs;
+use lazy_static::lazy_static;
+use tracing::info;
+
+lazy_static! {
+    pub static ref PROJECT_NAME: String = String::from("television");
+    pub static ref PROJECT_NAME_UPPER: String = PROJECT_NAME.to_uppercase().to_string();
+    pub static ref DATA_FOLDER: Option<PathBuf> =
+        // if `TELEVISION_DATA` is set, use that as the data directory
+        env::var_os(format!("{}_DATA", PROJECT_NAME_UPPER.clone())).or_else(|| {
+            // otherwise, use the XDG data directory
+            env::var_os("XDG_DATA_HOME")
+        }).map(PathBuf::from).map(|p| p.join(PROJECT_NAME.as_str())).filter(|p| p.is_absolute());
+    pub static ref CONFIG_FOLDER: Option<PathBuf> =
+        // if `TELEVISION_CONFIG` is set, use that as the config directory
+        env::var_os(format!("{}_CONFIG", PROJECT_NAME_UPPER.clone())).or_else(|| {
+            // otherwise, use the XDG config directory
+            env::var_os("XDG_CONFIG_HOME")
+        }).map(PathBuf::from).map(|p| p.join(PROJECT_NAME.as_str())).filter(|p| p.is_absolute());
+}
+
+pub fn get_data_dir() -> PathBuf {
+    let directory = if let Some(s) = DATA_FOLDER.clone() {
+        s
+    } else if let Some(proj_dirs) = project_directory() {
+        proj_dirs.data_local_dir().to_path_buf()
+    } else {
+        PathBuf::from(".").join(".data")
+    };
+    directory
+}
+
+pub fn get_config_dir() -> PathBuf {
+    let directory = if let Some(s) = CONFIG_FOLDER.clone() {
+        s
+    } else if let Some(proj_dirs) = project_directory() {
+        proj_dirs.config_local_dir().to_path_buf()
+    } else {
+        PathBuf::from(".").join(".config")
+    };
+    info!("Using config directory: {:?}", directory);
+    directory
+}
+
+fn project_directory() -> Option<ProjectDirs> {
+    ProjectDirs::from("com", "alexpasmantier", PROJECT_NAME.as_str())
+}
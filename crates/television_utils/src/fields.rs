@@ -0,0 +1,406 @@
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::strings::{replace_non_printable, TAB_WIDTH};
+
+/// What separates the fields of a line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Delimiter {
+    /// Runs of whitespace, the leading and trailing ones being ignored (as
+    /// with `awk`).
+    #[default]
+    Whitespace,
+    /// The given string (e.g. `,` for CSV), each occurrence of which
+    /// separates two fields, empty ones included.
+    Literal(String),
+}
+
+impl Delimiter {
+    /// The delimiter given on the command line, where `\t` stands for a tab
+    /// and an empty string for whitespace.
+    pub fn new(delimiter: &str) -> Self {
+        match delimiter {
+            "" => Delimiter::Whitespace,
+            _ => Delimiter::Literal(delimiter.replace("\\t", "\t")),
+        }
+    }
+
+    /// The byte ranges of the fields of the given line.
+    ///
+    /// # Examples
+    /// ```
+    /// use television_utils::fields::Delimiter;
+    ///
+    /// assert_eq!(Delimiter::Whitespace.split("  ab  c"), vec![2..4, 6..7]);
+    /// assert_eq!(Delimiter::new(",").split("a,,b"), vec![0..1, 2..2, 3..4]);
+    /// ```
+    pub fn split(&self, line: &str) -> Vec<Range<usize>> {
+        match self {
+            Delimiter::Whitespace => {
+                let mut fields = Vec::new();
+                let mut start = None;
+                for (i, c) in line.char_indices() {
+                    match (c.is_whitespace(), start) {
+                        (true, Some(s)) => {
+                            fields.push(s..i);
+                            start = None;
+                        }
+                        (false, None) => start = Some(i),
+                        _ => {}
+                    }
+                }
+                if let Some(s) = start {
+                    fields.push(s..line.len());
+                }
+                fields
+            }
+            Delimiter::Literal(delimiter) => {
+                let mut fields = Vec::new();
+                let mut start = 0;
+                for (i, _) in line.match_indices(delimiter.as_str()) {
+                    fields.push(start..i);
+                    start = i + delimiter.len();
+                }
+                fields.push(start..line.len());
+                fields
+            }
+        }
+    }
+
+    /// What joins fields that weren't next to each other in the line.
+    pub fn separator(&self) -> &str {
+        match self {
+            Delimiter::Whitespace => " ",
+            Delimiter::Literal(delimiter) => delimiter,
+        }
+    }
+}
+
+/// A range of fields, numbered from 1 (or from -1 backwards from the last
+/// one), both ends included: `2`, `2..`, `..-2`, `2..4` or `..`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldRange {
+    start: Option<i32>,
+    end: Option<i32>,
+}
+
+impl FieldRange {
+    /// The indices of the fields in the range, among the given number of
+    /// fields.
+    fn indices(self, count: usize) -> Range<usize> {
+        let index = |n: i32| -> i64 {
+            if n > 0 {
+                i64::from(n) - 1
+            } else {
+                count as i64 + i64::from(n)
+            }
+        };
+        let start = self.start.map_or(0, index).max(0);
+        let end = self
+            .end
+            .map_or(count as i64 - 1, index)
+            .min(count as i64 - 1);
+        if start > end {
+            return 0..0;
+        }
+        usize::try_from(start).unwrap()..usize::try_from(end).unwrap() + 1
+    }
+}
+
+impl FromStr for FieldRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bound = |bound: &str| -> Result<Option<i32>, String> {
+            match bound {
+                "" => Ok(None),
+                _ => match bound.parse::<i32>() {
+                    Ok(0) | Err(_) => Err(format!(
+                        "invalid field `{bound}` (expected a number other \
+                         than 0)"
+                    )),
+                    Ok(n) => Ok(Some(n)),
+                },
+            }
+        };
+        match s.split_once("..") {
+            Some((start, end)) => Ok(FieldRange {
+                start: bound(start)?,
+                end: bound(end)?,
+            }),
+            None if s.is_empty() => Err("missing field".to_string()),
+            None => {
+                let n = bound(s)?;
+                Ok(FieldRange { start: n, end: n })
+            }
+        }
+    }
+}
+
+/// A comma-separated list of field ranges, e.g. `1,3..`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fields(Vec<FieldRange>);
+
+impl Fields {
+    /// The indices of the fields in the list, in order, among the given
+    /// number of fields.
+    ///
+    /// # Examples
+    /// ```
+    /// use television_utils::fields::Fields;
+    ///
+    /// let fields: Fields = "-1,2..3,5..".parse().unwrap();
+    /// assert_eq!(fields.indices(6), vec![5, 1, 2, 4, 5]);
+    /// assert_eq!(fields.indices(2), vec![1, 1]);
+    /// ```
+    pub fn indices(&self, count: usize) -> Vec<usize> {
+        self.0
+            .iter()
+            .flat_map(|range| range.indices(count))
+            .collect()
+    }
+}
+
+impl FromStr for Fields {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|range| range.trim().parse())
+            .collect::<Result<_, _>>()
+            .map(Fields)
+    }
+}
+
+/// Text made of some of the fields of a line, along with where its parts
+/// come from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Picked {
+    pub text: String,
+    /// The byte range of each field of the text.
+    pub fields: Vec<Range<usize>>,
+    /// The parts of the text: fields and the delimiters between adjacent
+    /// ones.
+    parts: Vec<Part>,
+}
+
+/// A part of a `Picked` text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Part {
+    /// Its byte range in the line.
+    line: Range<usize>,
+    /// Its byte range in the text.
+    text: Range<usize>,
+    /// Whether it's copied as is from the line, i.e. whether it has no
+    /// non-printable characters.
+    verbatim: bool,
+}
+
+impl Part {
+    /// The offset in the text of the given offset of the part in the line.
+    fn to_text(&self, offset: usize) -> usize {
+        if offset == self.line.end {
+            self.text.end
+        } else if self.verbatim {
+            self.text.start + offset - self.line.start
+        } else {
+            self.text.start
+        }
+    }
+
+    /// The offset in the line of the given offset of the part in the text.
+    fn to_line(&self, offset: usize) -> usize {
+        if self.verbatim {
+            self.line.start + offset - self.text.start
+        } else {
+            self.line.start
+        }
+    }
+}
+
+impl Picked {
+    /// The fields of the line with the given indices (the line's fields
+    /// being at the given byte ranges), the ones next to each other in the
+    /// line keeping the delimiter between them and the others being joined
+    /// with the delimiter's separator.
+    ///
+    /// # Examples
+    /// ```
+    /// use television_utils::fields::{Delimiter, Picked};
+    ///
+    /// let line = "root  1 0.0 /sbin/init";
+    /// let delimiter = Delimiter::Whitespace;
+    /// let fields = delimiter.split(line);
+    /// let picked = Picked::new(line, &fields, &[0, 1, 3], &delimiter);
+    /// assert_eq!(picked.text, "root  1 /sbin/init");
+    /// ```
+    pub fn new(
+        line: &str,
+        fields: &[Range<usize>],
+        indices: &[usize],
+        delimiter: &Delimiter,
+    ) -> Self {
+        let mut picked = Picked::default();
+        let mut previous: Option<usize> = None;
+        for &index in indices {
+            match previous {
+                Some(p) if p + 1 == index => {
+                    picked.push(line, fields[p].end..fields[index].start);
+                }
+                Some(_) => picked.text.push_str(delimiter.separator()),
+                None => {}
+            }
+            let start = picked.text.len();
+            picked.push(line, fields[index].clone());
+            picked.fields.push(start..picked.text.len());
+            previous = Some(index);
+        }
+        picked
+    }
+
+    /// All of the line, split at the given fields: the same as the line,
+    /// its non-printable characters being replaced as by
+    /// `replace_non_printable`, like those of any picked text.
+    pub fn all(line: &str, fields: &[Range<usize>]) -> Self {
+        let mut picked = Picked::default();
+        let mut end = 0;
+        for field in fields {
+            picked.push(line, end..field.start);
+            let start = picked.text.len();
+            picked.push(line, field.clone());
+            picked.fields.push(start..picked.text.len());
+            end = field.end;
+        }
+        picked.push(line, end..line.len());
+        picked
+    }
+
+    fn push(&mut self, line: &str, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let start = self.text.len();
+        let text =
+            replace_non_printable(line[range.clone()].as_bytes(), TAB_WIDTH);
+        self.text.push_str(&text);
+        self.parts.push(Part {
+            verbatim: text == line[range.clone()],
+            line: range,
+            text: start..self.text.len(),
+        });
+    }
+
+    /// The given byte ranges of the line (e.g. the parts colored by ANSI
+    /// escape sequences), translated to the text, in order.
+    ///
+    /// The parts of the line whose non-printable characters were replaced
+    /// are only translated as a whole.
+    pub fn map_ranges<T: Clone>(
+        &self,
+        ranges: &[(Range<usize>, T)],
+    ) -> Vec<(Range<usize>, T)> {
+        let mut mapped = Vec::new();
+        for part in &self.parts {
+            for (range, value) in ranges {
+                let start = range.start.max(part.line.start);
+                let end = range.end.min(part.line.end);
+                if start < end {
+                    mapped.push((
+                        part.to_text(start)..part.to_text(end),
+                        value.clone(),
+                    ));
+                }
+            }
+        }
+        mapped
+    }
+
+    /// The given ranges of characters of the text (e.g. matched ones),
+    /// translated to the ranges of characters of the line, the characters
+    /// of the separators being dropped.
+    ///
+    /// The replaced non-printable characters of the line are translated to
+    /// the first character of their part.
+    ///
+    /// # Examples
+    /// ```
+    /// use television_utils::fields::{Delimiter, Picked};
+    ///
+    /// let line = "é,b,c";
+    /// let delimiter = Delimiter::new(",");
+    /// let picked = Picked::new(line, &delimiter.split(line), &[0, 2], &delimiter);
+    /// assert_eq!(picked.text, "é,c");
+    /// assert_eq!(picked.line_char_ranges(line, &[(0, 1), (1, 2), (2, 3)]), vec![(0, 1), (4, 5)]);
+    /// ```
+    pub fn line_char_ranges(
+        &self,
+        line: &str,
+        char_ranges: &[(u32, u32)],
+    ) -> Vec<(u32, u32)> {
+        let text_offsets: Vec<usize> =
+            self.text.char_indices().map(|(offset, _)| offset).collect();
+        let char_index = |offset: usize| {
+            u32::try_from(line[..offset].chars().count()).unwrap_or(u32::MAX)
+        };
+        let text_chars = u32::try_from(text_offsets.len()).unwrap_or(u32::MAX);
+        char_ranges
+            .iter()
+            .flat_map(|&(start, end)| start..end.min(text_chars))
+            .filter_map(|i| {
+                let offset = text_offsets[i as usize];
+                let part = self
+                    .parts
+                    .iter()
+                    .find(|part| part.text.contains(&offset))?;
+                let i = char_index(part.to_line(offset));
+                Some((i, i + 1))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_ranges() {
+        let fields =
+            |s: &str, count| s.parse::<Fields>().unwrap().indices(count);
+        assert_eq!(fields("2", 3), vec![1]);
+        assert_eq!(fields("2..", 4), vec![1, 2, 3]);
+        assert_eq!(fields("..-2", 4), vec![0, 1, 2]);
+        assert_eq!(fields("..", 2), vec![0, 1]);
+        // out of range
+        assert_eq!(fields("3,-4", 2), Vec::<usize>::new());
+        assert_eq!(fields("2..9", 3), vec![1, 2]);
+        for invalid in ["", "0", "a", "1..b", "1,,2"] {
+            assert!(invalid.parse::<Fields>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_picked() {
+        let line = "  a\tb  c ";
+        let delimiter = Delimiter::Whitespace;
+        let fields = delimiter.split(line);
+        assert_eq!(fields.len(), 3);
+
+        let all = Picked::all(line, &fields);
+        assert_eq!(all.text, "  a    b  c ");
+        assert_eq!(all.fields, vec![2..3, 7..8, 10..11]);
+
+        let picked = Picked::new(line, &fields, &[2, 0, 1], &delimiter);
+        assert_eq!(picked.text, "c a    b");
+        assert_eq!(picked.fields, vec![0..1, 2..3, 7..8]);
+        // the replaced tab is styled as a whole
+        assert_eq!(
+            picked.map_ranges(&[(3..4, ()), (7..9, ())]),
+            vec![(0..1, ()), (3..7, ())]
+        );
+        assert_eq!(
+            picked.line_char_ranges(line, &[(0, 4), (5, 6)]),
+            vec![(7, 8), (2, 3), (3, 4), (3, 4)]
+        );
+    }
+}
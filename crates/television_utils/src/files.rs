@@ -1,4 +1,7 @@
+use std::io;
 use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{collections::HashSet, path::PathBuf};
 
 use ignore::{overrides::Override, types::TypesBuilder, WalkBuilder};
@@ -19,6 +22,14 @@ pub fn walk_builder(
     ignore_paths: Option<Vec<PathBuf>>,
 ) -> WalkBuilder {
     let mut builder = WalkBuilder::new(path);
+    // in git repositories only, like ripgrep: the `.gitignore` files,
+    // `.git/info/exclude` and the global excludes file of `~/.gitconfig`
+    // (see `add_git_excludes_file` for the other ones)
+    builder
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true)
+        .require_git(true);
 
     // ft-based filtering
     let mut types_builder = TypesBuilder::new();
@@ -44,6 +55,164 @@ pub fn walk_builder(
     builder
 }
 
+/// Also ignore the files matched by git's excludes file (`core.excludesFile`)
+/// when walking a git repository.
+///
+/// The `ignore` crate only reads it from `~/.gitconfig`, this asks git for
+/// it so that it is found wherever git finds it: in the repository's own
+/// config, in `$GIT_CONFIG_GLOBAL`, in included files, ...
+pub fn add_git_excludes_file(builder: &mut WalkBuilder, path: &Path) {
+    let Ok(path) = path.canonicalize() else {
+        return;
+    };
+    if !path.ancestors().any(|dir| dir.join(".git").exists()) {
+        return;
+    }
+    let Ok(output) = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["config", "--path", "--get", "core.excludesFile"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+    else {
+        return;
+    };
+    let excludes_file = String::from_utf8_lossy(&output.stdout);
+    let excludes_file = Path::new(excludes_file.trim_end());
+    if output.status.success() && excludes_file.is_file() {
+        debug!("Using git excludes file: {:?}", excludes_file);
+        if let Some(e) = builder.add_ignore(excludes_file) {
+            debug!("Error reading git excludes file: {}", e);
+        }
+    }
+}
+
+/// The entries skipped while walking directories, counted by reason so
+/// that they can be reported instead of silently missing (or aborting the
+/// walk).
+#[derive(Debug, Default)]
+pub struct WalkWarnings {
+    /// Directories and files that couldn't be read (e.g. permission denied).
+    unreadable: AtomicUsize,
+    /// Symbolic links leading back to one of their ancestors.
+    loops: AtomicUsize,
+    /// Entries removed while the walk was running.
+    vanished: AtomicUsize,
+    other: AtomicUsize,
+}
+
+impl WalkWarnings {
+    /// Count an error yielded by a walker, the entry it concerns being
+    /// skipped.
+    pub fn record(&self, error: &ignore::Error) {
+        debug!("Skipping entry: {}", error);
+        match error {
+            ignore::Error::Partial(errors) => {
+                errors.iter().for_each(|error| self.record(error));
+            }
+            ignore::Error::WithLineNumber { err, .. }
+            | ignore::Error::WithPath { err, .. }
+            | ignore::Error::WithDepth { err, .. } => self.record(err),
+            ignore::Error::Loop { .. } => self.add(&self.loops),
+            ignore::Error::Io(error) => self.record_io(error),
+            _ => self.add(&self.other),
+        }
+    }
+
+    /// Count an error reading an entry found by a walker.
+    pub fn record_io(&self, error: &io::Error) {
+        match error.kind() {
+            io::ErrorKind::PermissionDenied => self.add(&self.unreadable),
+            io::ErrorKind::NotFound => self.add(&self.vanished),
+            // e.g. when opening a symbolic link to itself
+            #[cfg(unix)]
+            _ if error.raw_os_error() == Some(libc::ELOOP) => {
+                self.add(&self.loops);
+            }
+            _ => self.add(&self.other),
+        }
+    }
+
+    fn add(&self, counter: &AtomicUsize) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How many entries were skipped.
+    pub fn count(&self) -> usize {
+        [&self.unreadable, &self.loops, &self.vanished, &self.other]
+            .iter()
+            .map(|counter| counter.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// A summary of the skipped entries, e.g. `2 unreadable, 1 symlink
+    /// loop`, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use television_utils::files::WalkWarnings;
+    ///
+    /// let warnings = WalkWarnings::default();
+    /// assert_eq!(warnings.summary(), None);
+    /// warnings.record_io(&std::io::ErrorKind::NotFound.into());
+    /// assert_eq!(warnings.summary().as_deref(), Some("1 vanished"));
+    /// ```
+    pub fn summary(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            (&self.unreadable, "unreadable", "unreadable"),
+            (&self.loops, "symlink loop", "symlink loops"),
+            (&self.vanished, "vanished", "vanished"),
+            (&self.other, "error", "errors"),
+        ]
+        .into_iter()
+        .filter_map(|(counter, singular, plural)| {
+            match counter.load(Ordering::Relaxed) {
+                0 => None,
+                1 => Some(format!("1 {singular}")),
+                n => Some(format!("{n} {plural}")),
+            }
+        })
+        .collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// The paths starting with the given partial path, sorted, directories being
+/// followed by a separator so that their content can be completed next.
+///
+/// Hidden files are only listed once their leading `.` has been typed.
+pub fn complete_path(partial: &str) -> Vec<String> {
+    let (dir, prefix) = match partial.rfind(std::path::is_separator) {
+        Some(i) => partial.split_at(i + 1),
+        None => ("", partial),
+    };
+    let Ok(entries) =
+        std::fs::read_dir(if dir.is_empty() { "." } else { dir })
+    else {
+        return Vec::new();
+    };
+    let mut completions: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix)
+                || (name.starts_with('.') && !prefix.starts_with('.'))
+            {
+                return None;
+            }
+            let separator = if entry.path().is_dir() {
+                std::path::MAIN_SEPARATOR_STR
+            } else {
+                ""
+            };
+            Some(format!("{dir}{name}{separator}"))
+        })
+        .collect();
+    completions.sort();
+    completions
+}
+
 pub fn get_file_size(path: &Path) -> Option<u64> {
     std::fs::metadata(path).ok().map(|m| m.len())
 }
@@ -415,3 +584,77 @@ lazy_static! {
     ]
     .into();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_git_excludes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git").arg("-C").arg(&repo).args(args).output()
+        };
+        if !git(&["init", "-q"]).is_ok_and(|output| output.status.success()) {
+            // git isn't installed
+            return;
+        }
+        // only configured in the repository, where the ignore crate doesn't
+        // look
+        let excludes_file = dir.path().join("excludes");
+        std::fs::write(&excludes_file, "*.excluded\n").unwrap();
+        git(&[
+            "config",
+            "core.excludesFile",
+            &excludes_file.to_string_lossy(),
+        ])
+        .unwrap();
+        for file in ["main.rs", "notes.excluded"] {
+            std::fs::write(repo.join(file), "").unwrap();
+        }
+
+        let walk = |builder: &mut WalkBuilder| -> Vec<String> {
+            let mut names: Vec<String> = builder
+                .build()
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_file())
+                .map(|entry| entry.file_name().to_string_lossy().into())
+                .collect();
+            names.sort();
+            names
+        };
+        let mut builder = walk_builder(&repo, 1, None, None);
+        assert_eq!(walk(&mut builder), ["main.rs", "notes.excluded"]);
+        add_git_excludes_file(&mut builder, &repo);
+        assert_eq!(walk(&mut builder), ["main.rs"]);
+    }
+
+    #[test]
+    fn test_walk_warnings() {
+        let warnings = WalkWarnings::default();
+        let denied = || io::Error::from(io::ErrorKind::PermissionDenied);
+        warnings.record(&ignore::Error::WithPath {
+            path: PathBuf::from("secret"),
+            err: Box::new(ignore::Error::Io(denied())),
+        });
+        warnings.record(&ignore::Error::WithDepth {
+            depth: 2,
+            err: Box::new(ignore::Error::Loop {
+                ancestor: PathBuf::from("a"),
+                child: PathBuf::from("a/b/a"),
+            }),
+        });
+        warnings.record(&ignore::Error::Partial(vec![
+            ignore::Error::Io(denied()),
+            ignore::Error::Io(io::ErrorKind::NotFound.into()),
+        ]));
+        warnings.record_io(&io::ErrorKind::InvalidData.into());
+        assert_eq!(warnings.count(), 5);
+        assert_eq!(
+            warnings.summary().as_deref(),
+            Some("2 unreadable, 1 symlink loop, 1 vanished, 1 error")
+        );
+    }
+}
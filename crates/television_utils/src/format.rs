@@ -0,0 +1,189 @@
+//! Human-friendly display of sizes, relative times and counts, following the
+//! number separators of the user's locale.
+//!
+//! The raw values (bytes, seconds, plain numbers) are displayed instead once
+//! `set_raw` is called, which the UI toggles with the `ToggleRawValues`
+//! action.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+
+static RAW: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref SEPARATORS: Separators =
+        Separators::detect(|name| std::env::var(name).ok());
+}
+
+/// Display the raw values instead of the human-friendly ones.
+pub fn set_raw(raw: bool) {
+    RAW.store(raw, Ordering::Relaxed);
+}
+
+pub fn is_raw() -> bool {
+    RAW.load(Ordering::Relaxed)
+}
+
+/// The separators numbers are written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Separators {
+    pub thousands: char,
+    pub decimal: char,
+}
+
+impl Default for Separators {
+    fn default() -> Self {
+        Self {
+            thousands: ',',
+            decimal: '.',
+        }
+    }
+}
+
+impl Separators {
+    /// The separators of the locale set in the given environment (in
+    /// `LC_ALL`, `LC_NUMERIC` or `LANG`), by language.
+    pub fn detect(env: impl Fn(&str) -> Option<String>) -> Self {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .into_iter()
+            .filter_map(env)
+            .find(|locale| !locale.is_empty())
+            .unwrap_or_default();
+        let language = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default();
+        match language {
+            "de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr" | "el"
+            | "ro" | "sl" | "hr" | "sr" | "vi" => Self {
+                thousands: '.',
+                decimal: ',',
+            },
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "nn"
+            | "no" | "uk" | "hu" | "bg" | "et" | "lv" | "lt" => Self {
+                thousands: '\u{a0}',
+                decimal: ',',
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// Write the given number with thousands separators (e.g. `12,345`).
+    pub fn count(self, n: u64) -> String {
+        let digits = n.to_string();
+        let mut grouped = String::with_capacity(digits.len() * 4 / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(self.thousands);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// Write the given size in bytes with binary units (e.g. `1.5 KiB`).
+    #[allow(clippy::cast_precision_loss)]
+    pub fn size(self, bytes: u64) -> String {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut size = bytes as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            format!("{bytes} B")
+        } else {
+            let size =
+                format!("{size:.1}").replace('.', &self.decimal.to_string());
+            format!("{size} {}", UNITS[unit])
+        }
+    }
+}
+
+/// The given count with thousands separators, or as is in raw mode.
+pub fn count(n: u64) -> String {
+    if is_raw() {
+        n.to_string()
+    } else {
+        SEPARATORS.count(n)
+    }
+}
+
+/// The given size in bytes with binary units, or in bytes in raw mode.
+pub fn size(bytes: u64) -> String {
+    if is_raw() {
+        format!("{bytes} B")
+    } else {
+        SEPARATORS.size(bytes)
+    }
+}
+
+/// How long ago something happened, in the largest relevant unit (e.g.
+/// `3 h ago`), or in seconds in raw mode.
+pub fn relative_time(age: Duration) -> String {
+    let seconds = age.as_secs();
+    if is_raw() {
+        return format!("{seconds} s ago");
+    }
+    let (value, unit) = [
+        ("y", 365 * 24 * 60 * 60),
+        ("mo", 30 * 24 * 60 * 60),
+        ("w", 7 * 24 * 60 * 60),
+        ("d", 24 * 60 * 60),
+        ("h", 60 * 60),
+        ("min", 60),
+    ]
+    .into_iter()
+    .find(|(_, length)| seconds >= *length)
+    .map_or((seconds, "s"), |(unit, length)| (seconds / length, unit));
+    if value == 0 {
+        "just now".to_string()
+    } else {
+        format!("{value} {unit} ago")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separators() {
+        let env = |locale: &'static str| {
+            move |name: &str| (name == "LANG").then(|| locale.to_string())
+        };
+        assert_eq!(
+            Separators::detect(env("en_US.UTF-8")).count(1234567),
+            "1,234,567"
+        );
+        assert_eq!(
+            Separators::detect(env("de_DE.UTF-8")).count(1234),
+            "1.234"
+        );
+        assert_eq!(Separators::detect(env("fr_FR")).count(1234), "1\u{a0}234");
+        assert_eq!(Separators::detect(env("C")).count(999), "999");
+        assert_eq!(Separators::detect(|_| None).count(0), "0");
+        assert_eq!(Separators::detect(env("de_DE")).size(1536), "1,5 KiB");
+    }
+
+    #[test]
+    fn test_size() {
+        let separators = Separators::default();
+        assert_eq!(separators.size(0), "0 B");
+        assert_eq!(separators.size(1023), "1023 B");
+        assert_eq!(separators.size(1536), "1.5 KiB");
+        assert_eq!(separators.size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let ago = |seconds| relative_time(Duration::from_secs(seconds));
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(42), "42 s ago");
+        assert_eq!(ago(3 * 60 * 60 + 59), "3 h ago");
+        assert_eq!(ago(2 * 24 * 60 * 60), "2 d ago");
+        assert_eq!(ago(400 * 24 * 60 * 60), "1 y ago");
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use tracing::warn;
+
+/// The number of bytes displayed on each line of a hexdump.
+pub const BYTES_PER_LINE: usize = 16;
+/// The number of lines read at once by a `Hexdump`.
+const CHUNK_LINES: usize = 256;
+/// The number of chunks a `Hexdump` keeps around.
+const MAX_CHUNKS: usize = 8;
+
+/// A line of a hexdump, in the style of `hexdump -C`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexdumpLine {
+    /// The offset of the first byte of the line in the file.
+    pub offset: u64,
+    /// The bytes in hexadecimal, in two groups of eight.
+    pub hex: String,
+    /// The bytes as ASCII characters, with `.` for the non-printable ones.
+    pub ascii: String,
+}
+
+impl HexdumpLine {
+    pub fn new(offset: u64, bytes: &[u8]) -> Self {
+        let mut hex = String::with_capacity(3 * BYTES_PER_LINE + 1);
+        for i in 0..BYTES_PER_LINE {
+            if i == BYTES_PER_LINE / 2 {
+                hex.push(' ');
+            }
+            match bytes.get(i) {
+                Some(byte) => hex.push_str(&format!("{byte:02x} ")),
+                None => hex.push_str("   "),
+            }
+        }
+        hex.pop();
+        let ascii = bytes
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        HexdumpLine { offset, hex, ascii }
+    }
+}
+
+impl std::fmt::Display for HexdumpLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:08x}  {}  |{}|", self.offset, self.hex, self.ascii)
+    }
+}
+
+/// The hexdump of (the beginning of) a file, whose bytes are only read when
+/// the lines they're on are displayed, so that large binaries are cheap to
+/// preview.
+pub struct Hexdump {
+    path: PathBuf,
+    /// The number of bytes dumped.
+    size: u64,
+    /// The size of the whole file.
+    file_size: u64,
+    /// The last read chunks, by index, the most recent last.
+    chunks: Mutex<VecDeque<(usize, Arc<Vec<u8>>)>>,
+}
+
+impl Hexdump {
+    /// The hexdump of the first `max_size` bytes of the file at the given
+    /// path.
+    pub fn new(path: &Path, max_size: u64) -> io::Result<Self> {
+        let file_size = std::fs::metadata(path)?.len();
+        Ok(Hexdump {
+            path: path.to_path_buf(),
+            size: file_size.min(max_size),
+            file_size,
+            chunks: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    /// The number of lines of the hexdump.
+    pub fn len(&self) -> usize {
+        usize::try_from(self.size)
+            .unwrap_or(usize::MAX)
+            .div_ceil(BYTES_PER_LINE)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// The number of bytes dumped.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The size of the whole file, which is more than what's dumped if the
+    /// file was truncated.
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.size < self.file_size
+    }
+
+    /// The number of bytes read and kept around.
+    pub fn memory_usage(&self) -> usize {
+        self.chunks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, chunk)| chunk.len())
+            .sum()
+    }
+
+    /// The lines in the given range, reading them from the file if they
+    /// weren't already.
+    pub fn lines(&self, range: Range<usize>) -> Vec<HexdumpLine> {
+        let end = range.end.min(self.len());
+        if range.start >= end {
+            return Vec::new();
+        }
+        let mut chunks = self.chunks.lock().unwrap();
+        let mut lines = Vec::with_capacity(end - range.start);
+        for index in range.start / CHUNK_LINES..=(end - 1) / CHUNK_LINES {
+            let chunk = self.chunk(&mut chunks, index);
+            let first_line = index * CHUNK_LINES;
+            for line in
+                range.start.max(first_line)..end.min(first_line + CHUNK_LINES)
+            {
+                let start = (line - first_line) * BYTES_PER_LINE;
+                let bytes = chunk.get(start..).unwrap_or_default();
+                if bytes.is_empty() {
+                    break;
+                }
+                lines.push(HexdumpLine::new(
+                    (line * BYTES_PER_LINE) as u64,
+                    &bytes[..bytes.len().min(BYTES_PER_LINE)],
+                ));
+            }
+        }
+        lines
+    }
+
+    /// The chunk with the given index, from the last read chunks or read
+    /// from the file.
+    fn chunk(
+        &self,
+        chunks: &mut VecDeque<(usize, Arc<Vec<u8>>)>,
+        index: usize,
+    ) -> Arc<Vec<u8>> {
+        if let Some(position) = chunks.iter().position(|(i, _)| *i == index) {
+            let chunk = chunks.remove(position).unwrap();
+            chunks.push_back(chunk.clone());
+            return chunk.1;
+        }
+        let chunk = Arc::new(self.read_chunk(index).unwrap_or_else(|e| {
+            // the file changed or disappeared, there's nothing to display
+            warn!("Error reading {:?}: {:?}", self.path, e);
+            Vec::new()
+        }));
+        if chunks.len() == MAX_CHUNKS {
+            chunks.pop_front();
+        }
+        chunks.push_back((index, chunk.clone()));
+        chunk
+    }
+
+    fn read_chunk(&self, index: usize) -> io::Result<Vec<u8>> {
+        let start = (index * CHUNK_LINES * BYTES_PER_LINE) as u64;
+        let len = (CHUNK_LINES * BYTES_PER_LINE) as u64;
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.take(len.min(self.size.saturating_sub(start)))
+            .read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl std::fmt::Debug for Hexdump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hexdump")
+            .field("path", &self.path)
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump_line() {
+        let line = HexdumpLine::new(0x10, b"\x7fELF\x02\x01\x01\0\0\0 hello");
+        assert_eq!(
+            line.to_string(),
+            "00000010  7f 45 4c 46 02 01 01 00  00 00 20 68 65 6c 6c 6f  \
+             |.ELF...... hello|"
+        );
+        // the hex column keeps its width on the last, shorter line
+        let line = HexdumpLine::new(0x20, b"ab");
+        assert_eq!(line.hex.len(), 3 * BYTES_PER_LINE);
+        assert_eq!(
+            line.to_string(),
+            format!("00000020  {:<48}  |ab|", "61 62")
+        );
+    }
+
+    #[test]
+    fn test_hexdump() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bin");
+        let bytes: Vec<u8> = (0..10_010u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &bytes).unwrap();
+
+        let hexdump = Hexdump::new(&path, 8192).unwrap();
+        assert!(hexdump.is_truncated());
+        assert_eq!(hexdump.file_size(), 10_010);
+        assert_eq!(hexdump.len(), 512);
+
+        // across two chunks
+        let lines = hexdump.lines(250..260);
+        assert_eq!(lines.len(), 10);
+        for (line, i) in lines.iter().zip(250..) {
+            assert_eq!(line.offset, (i * BYTES_PER_LINE) as u64);
+            assert_eq!(
+                *line,
+                HexdumpLine::new(
+                    line.offset,
+                    &bytes[i * BYTES_PER_LINE..(i + 1) * BYTES_PER_LINE]
+                )
+            );
+        }
+        assert_eq!(hexdump.memory_usage(), 2 * CHUNK_LINES * BYTES_PER_LINE);
+        // past the cap
+        assert_eq!(hexdump.lines(510..600).len(), 2);
+
+        let hexdump = Hexdump::new(&path, u64::MAX).unwrap();
+        assert!(!hexdump.is_truncated());
+        let last = hexdump.lines(625..700);
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].ascii.len(), 10_010 % BYTES_PER_LINE);
+    }
+}
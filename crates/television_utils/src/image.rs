@@ -0,0 +1,445 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fmt::Write as _;
+#[cfg(feature = "image-preview")]
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+#[cfg(feature = "image-preview")]
+use color_eyre::eyre::Result;
+
+/// The ways images can be drawn in a terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    /// The kitty graphics protocol (kitty, ghostty).
+    Kitty,
+    /// Sixel graphics (foot, mlterm, konsole, Windows Terminal...).
+    Sixel,
+    /// iTerm2 inline images (iTerm2, WezTerm).
+    Iterm2,
+    /// Unicode half blocks, i.e. two pixels per cell, which any terminal
+    /// supporting truecolor can display.
+    HalfBlocks,
+}
+
+impl GraphicsProtocol {
+    /// The best protocol supported by the terminal, guessed from the given
+    /// environment.
+    ///
+    /// # Example
+    /// ```
+    /// use television_utils::image::GraphicsProtocol;
+    ///
+    /// let env = |name: &str| {
+    ///     (name == "TERM").then(|| "xterm-kitty".to_string())
+    /// };
+    /// assert_eq!(GraphicsProtocol::detect(env), GraphicsProtocol::Kitty);
+    /// assert_eq!(GraphicsProtocol::detect(|_| None), GraphicsProtocol::HalfBlocks);
+    /// ```
+    pub fn detect(env: impl Fn(&str) -> Option<String>) -> Self {
+        let term = env("TERM").unwrap_or_default();
+        let term_program = env("TERM_PROGRAM").unwrap_or_default();
+        // tmux swallows the escape sequences it doesn't know about
+        if env("TMUX").is_some() {
+            Self::HalfBlocks
+        } else if term.contains("kitty")
+            || env("KITTY_WINDOW_ID").is_some()
+            || term.contains("ghostty")
+            || term_program == "ghostty"
+        {
+            Self::Kitty
+        } else if term_program == "iTerm.app" || term_program == "WezTerm" {
+            Self::Iterm2
+        } else if term.starts_with("foot")
+            || term.contains("mlterm")
+            || term.contains("sixel")
+            || env("KONSOLE_VERSION").is_some()
+            || env("WT_SESSION").is_some()
+        {
+            Self::Sixel
+        } else {
+            Self::HalfBlocks
+        }
+    }
+
+    /// The escape sequence displaying the given image at the cursor, at its
+    /// actual size in pixels, or `None` for half blocks, which are drawn
+    /// like text.
+    pub fn encode(self, image: &Image) -> Option<String> {
+        match self {
+            Self::Kitty => Some(kitty_sequence(&image.to_png())),
+            Self::Sixel => Some(sixel_sequence(image)),
+            Self::Iterm2 => Some(iterm2_sequence(image)),
+            Self::HalfBlocks => None,
+        }
+    }
+}
+
+impl fmt::Display for GraphicsProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Kitty => "kitty graphics",
+            Self::Sixel => "sixel",
+            Self::Iterm2 => "iTerm2 inline images",
+            Self::HalfBlocks => "unicode half blocks",
+        })
+    }
+}
+
+/// Deletes all the images displayed with the kitty graphics protocol, which
+/// stay on screen until then, whatever is drawn over them.
+pub const KITTY_DELETE_ALL: &str = "\x1b_Ga=d,d=A,q=2\x1b\\";
+
+/// An RGBA image.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Image")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The largest images that can be decoded, in pixels.
+#[cfg(feature = "image-preview")]
+const MAX_PIXELS: u64 = 64 * 1024 * 1024;
+
+/// The size of the longest side of thumbnails, in pixels: no preview pane is
+/// wider than that.
+#[cfg(feature = "image-preview")]
+const MAX_THUMBNAIL_SIZE: u32 = 1024;
+
+impl Image {
+    /// An image of the given size, made of the given pixels (row by row).
+    ///
+    /// # Panics
+    /// If there are not exactly `width * height` pixels.
+    pub fn new(width: u32, height: u32, pixels: Vec<[u8; 4]>) -> Self {
+        assert_eq!(pixels.len() as u64, u64::from(width) * u64::from(height));
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// The image resized to the given size, each pixel averaging the pixels
+    /// of the original image it covers.
+    pub fn resize(&self, width: u32, height: u32) -> Self {
+        if (width, height) == (self.width, self.height) {
+            return self.clone();
+        }
+        let span = |i: u32, size: u32, original: u32| {
+            let start = u64::from(i) * u64::from(original) / u64::from(size);
+            let end = (u64::from(i + 1) * u64::from(original)
+                / u64::from(size))
+            .max(start + 1);
+            // both fit since they're at most `original`
+            (start as u32, end as u32)
+        };
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            let (y0, y1) = span(y, height, self.height);
+            for x in 0..width {
+                let (x0, x1) = span(x, width, self.width);
+                let mut sums = [0u64; 4];
+                for sy in y0..y1 {
+                    for sx in x0..x1 {
+                        for (sum, c) in sums.iter_mut().zip(self.pixel(sx, sy))
+                        {
+                            *sum += u64::from(c);
+                        }
+                    }
+                }
+                let count = u64::from((x1 - x0) * (y1 - y0));
+                pixels.push(sums.map(|sum| (sum / count) as u8));
+            }
+        }
+        Self::new(width, height, pixels)
+    }
+
+    /// The size of the image once fitted in a box of the given size,
+    /// keeping its aspect ratio and never enlarging it.
+    pub fn fit(&self, max_width: u32, max_height: u32) -> (u32, u32) {
+        if self.width <= max_width && self.height <= max_height {
+            return (self.width, self.height);
+        }
+        let scale = f64::min(
+            f64::from(max_width) / f64::from(self.width),
+            f64::from(max_height) / f64::from(self.height),
+        );
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let size = |side: u32| ((f64::from(side) * scale) as u32).max(1);
+        (size(self.width), size(self.height))
+    }
+
+    /// The image encoded as an RGBA PNG.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(png::Compression::Fast);
+        // writing a well-formed image to a vector can't fail
+        encoder
+            .write_header()
+            .and_then(|mut writer| {
+                writer.write_image_data(self.pixels.as_flattened())
+            })
+            .unwrap();
+        png
+    }
+}
+
+/// Load the image at the given path (the first frame of animations),
+/// downsized so that it fits any preview pane.
+///
+/// PNG, JPEG, GIF and WebP images are supported.
+#[cfg(feature = "image-preview")]
+pub fn load_thumbnail(path: &Path) -> Result<Image> {
+    let mut reader = image::ImageReader::open(path)?.with_guessed_format()?;
+    let mut limits = image::Limits::default();
+    limits.max_alloc = Some(MAX_PIXELS * 4);
+    reader.limits(limits);
+    let image = reader.decode()?;
+    let image = if image.width() > MAX_THUMBNAIL_SIZE
+        || image.height() > MAX_THUMBNAIL_SIZE
+    {
+        image.thumbnail(MAX_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE)
+    } else {
+        image
+    };
+    let image = image.into_rgba8();
+    let (width, height) = image.dimensions();
+    let pixels = image.pixels().map(|pixel| pixel.0).collect();
+    Ok(Image::new(width, height, pixels))
+}
+
+/// The kitty graphics protocol sequence displaying the given PNG image,
+/// split in chunks of at most 4096 bytes as the protocol requires.
+fn kitty_sequence(png: &[u8]) -> String {
+    let encoded = BASE64.encode(png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+    let mut sequence =
+        String::with_capacity(encoded.len() + chunks.len() * 16);
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        // base64 is ASCII
+        let chunk = std::str::from_utf8(chunk).unwrap();
+        if i == 0 {
+            // transmit and display the image without moving the cursor,
+            // quietly
+            let _ = write!(sequence, "\x1b_Ga=T,f=100,q=2,C=1,m={more};");
+        } else {
+            let _ = write!(sequence, "\x1b_Gm={more};");
+        }
+        sequence.push_str(chunk);
+        sequence.push_str("\x1b\\");
+    }
+    sequence
+}
+
+/// The iTerm2 inline image sequence displaying the given image.
+fn iterm2_sequence(image: &Image) -> String {
+    let png = image.to_png();
+    format!(
+        "\x1b]1337;File=inline=1;size={};width={}px;height={}px;\
+         preserveAspectRatio=1:{}\x07",
+        png.len(),
+        image.width,
+        image.height,
+        BASE64.encode(&png)
+    )
+}
+
+/// The number of levels of each channel of the sixel palette.
+const SIXEL_LEVELS: u8 = 6;
+
+/// The sixel sequence displaying the given image, in a 216 colors palette
+/// (transparent pixels are left untouched).
+fn sixel_sequence(image: &Image) -> String {
+    let level =
+        |c: u8| (u16::from(c) * u16::from(SIXEL_LEVELS - 1) + 127) / 255;
+    let color = |[r, g, b, a]: [u8; 4]| -> Option<u16> {
+        let levels = u16::from(SIXEL_LEVELS);
+        (a >= 128).then(|| (level(r) * levels + level(g)) * levels + level(b))
+    };
+
+    let (width, height) = (image.width as usize, image.height as usize);
+    let colors: Vec<Option<u16>> =
+        image.pixels.iter().copied().map(color).collect();
+
+    // no background, since transparent pixels are skipped
+    let mut sequence = format!("\x1bP0;1;0q\"1;1;{width};{height}");
+    let used: BTreeSet<u16> = colors.iter().flatten().copied().collect();
+    for &index in &used {
+        let levels = u16::from(SIXEL_LEVELS);
+        let percent = |l: u16| l * 100 / (levels - 1);
+        let _ = write!(
+            sequence,
+            "#{index};2;{};{};{}",
+            percent(index / (levels * levels)),
+            percent(index / levels % levels),
+            percent(index % levels)
+        );
+    }
+
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let band_colors: BTreeSet<u16> = rows
+            .clone()
+            .flat_map(|y| &colors[y * width..(y + 1) * width])
+            .flatten()
+            .copied()
+            .collect();
+        for (n, &index) in band_colors.iter().enumerate() {
+            if n > 0 {
+                // back to the start of the band for the next color
+                sequence.push('$');
+            }
+            let _ = write!(sequence, "#{index}");
+            let mut run: Option<(u8, usize)> = None;
+            for x in 0..width {
+                let bits =
+                    rows.clone().enumerate().fold(0u8, |bits, (i, y)| {
+                        if colors[y * width + x] == Some(index) {
+                            bits | (1 << i)
+                        } else {
+                            bits
+                        }
+                    });
+                let sixel = b'?' + bits;
+                run = match run {
+                    Some((c, count)) if c == sixel => Some((c, count + 1)),
+                    Some((c, count)) => {
+                        push_sixel_run(&mut sequence, c, count);
+                        Some((sixel, 1))
+                    }
+                    None => Some((sixel, 1)),
+                };
+            }
+            if let Some((c, count)) = run {
+                push_sixel_run(&mut sequence, c, count);
+            }
+        }
+        sequence.push('-');
+    }
+    sequence.push_str("\x1b\\");
+    sequence
+}
+
+fn push_sixel_run(sequence: &mut String, sixel: u8, count: usize) {
+    if count > 3 {
+        let _ = write!(sequence, "!{count}{}", char::from(sixel));
+    } else {
+        sequence.extend(std::iter::repeat_n(char::from(sixel), count));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Image {
+        let pixels = (0..12)
+            .map(|i| {
+                if (i % 4 + i / 4) % 2 == 0 {
+                    [255, 0, 0, 255]
+                } else {
+                    [0, 0, 255, 0]
+                }
+            })
+            .collect();
+        Image::new(4, 3, pixels)
+    }
+
+    #[cfg(feature = "image-preview")]
+    #[test]
+    fn test_load_thumbnail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkerboard.png");
+        let image = checkerboard();
+        std::fs::write(&path, image.to_png()).unwrap();
+        assert_eq!(load_thumbnail(&path).unwrap(), image);
+
+        // large images are downsized
+        let path = dir.path().join("large.png");
+        let large = Image::new(2048, 512, vec![[0, 0, 255, 255]; 2048 * 512]);
+        std::fs::write(&path, large.to_png()).unwrap();
+        let thumbnail = load_thumbnail(&path).unwrap();
+        assert_eq!((thumbnail.width, thumbnail.height), (1024, 256));
+        assert_eq!(thumbnail.pixel(0, 0), [0, 0, 255, 255]);
+
+        let path = dir.path().join("not-an-image.png");
+        std::fs::write(&path, "GIF89a").unwrap();
+        assert!(load_thumbnail(&path).is_err());
+    }
+
+    #[test]
+    fn test_resize_and_fit() {
+        let image =
+            Image::new(2, 1, vec![[0, 0, 0, 255], [200, 100, 50, 255]]);
+        assert_eq!(image.resize(1, 1).pixel(0, 0), [100, 50, 25, 255]);
+        assert_eq!(image.resize(4, 2).pixel(3, 1), [200, 100, 50, 255]);
+        assert_eq!(image.fit(10, 10), (2, 1));
+        let image = Image::new(100, 50, vec![[0; 4]; 5000]);
+        assert_eq!(image.fit(10, 10), (10, 5));
+        assert_eq!(image.fit(200, 10), (20, 10));
+    }
+
+    #[test]
+    fn test_sixel_sequence() {
+        let sequence = sixel_sequence(&checkerboard());
+        assert!(sequence.starts_with("\x1bP0;1;0q\"1;1;4;3#180;2;100;0;0"));
+        // a single band, where only the red pixels are drawn
+        assert!(sequence.ends_with("#180DADA-\x1b\\"));
+
+        let mut line = String::new();
+        push_sixel_run(&mut line, b'~', 10);
+        push_sixel_run(&mut line, b'?', 2);
+        assert_eq!(line, "!10~??");
+    }
+
+    #[test]
+    fn test_kitty_sequence() {
+        let sequence = kitty_sequence(&[0; 4000]);
+        assert!(sequence.starts_with("\x1b_Ga=T,f=100,q=2,C=1,m=1;AAAA"));
+        assert!(sequence.contains("\x1b\\\x1b_Gm=0;AAAA"));
+        assert!(sequence.ends_with("\x1b\\"));
+    }
+
+    #[test]
+    fn test_detect() {
+        let detect = |vars: &[(&str, &str)]| {
+            GraphicsProtocol::detect(|name| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| (*v).to_string())
+            })
+        };
+        assert_eq!(
+            detect(&[("TERM_PROGRAM", "WezTerm")]),
+            GraphicsProtocol::Iterm2
+        );
+        assert_eq!(detect(&[("TERM", "foot")]), GraphicsProtocol::Sixel);
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux")]),
+            GraphicsProtocol::HalfBlocks
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color")]),
+            GraphicsProtocol::HalfBlocks
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 pub fn sep_name_and_value_indices(
     indices: &mut Vec<u32>,
     name_len: u32,
@@ -29,3 +31,75 @@ pub fn sep_name_and_value_indices(
         should_add_value_indices,
     )
 }
+
+/// The byte ranges of the given text matching the given ranges of
+/// characters (such as `Entry::name_match_ranges`), clamped to the text.
+///
+/// # Examples
+/// ```
+/// use television_utils::indices::byte_ranges;
+///
+/// assert_eq!(byte_ranges("été.rs", &[(1, 2), (3, 9)]), vec![2..3, 5..8]);
+/// ```
+pub fn byte_ranges(
+    text: &str,
+    char_ranges: &[(u32, u32)],
+) -> Vec<Range<usize>> {
+    let offsets: Vec<usize> = text
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain([text.len()])
+        .collect();
+    let offset = |i: u32| offsets[(i as usize).min(offsets.len() - 1)];
+    char_ranges
+        .iter()
+        .map(|&(start, end)| offset(start)..offset(end.max(start)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_sep_name_and_value_indices(
+            indices in proptest::collection::vec(0u32..64, 0..32),
+            name_len in 0u32..32,
+        ) {
+            let (name, value, has_name, has_value) =
+                sep_name_and_value_indices(&mut indices.clone(), name_len);
+            prop_assert_eq!(has_name, !name.is_empty());
+            prop_assert_eq!(has_value, !value.is_empty());
+            prop_assert!(name.windows(2).all(|w| w[0] < w[1]));
+            prop_assert!(value.windows(2).all(|w| w[0] < w[1]));
+            prop_assert!(name.iter().all(|&i| i < name_len));
+            // splitting loses nothing but the duplicates
+            let mut expected = indices;
+            expected.sort_unstable();
+            expected.dedup();
+            let joined: Vec<u32> = name
+                .into_iter()
+                .chain(value.into_iter().map(|i| i + name_len))
+                .collect();
+            prop_assert_eq!(joined, expected);
+        }
+
+        #[test]
+        fn test_byte_ranges(
+            text in "\\PC{0,16}",
+            char_ranges in proptest::collection::vec((0u32..20, 0u32..20), 0..8),
+        ) {
+            let chars: Vec<char> = text.chars().collect();
+            for (range, &(start, end)) in
+                byte_ranges(&text, &char_ranges).into_iter().zip(&char_ranges)
+            {
+                let start = (start as usize).min(chars.len());
+                let end = (end as usize).min(chars.len()).max(start);
+                let expected: String = chars[start..end].iter().collect();
+                prop_assert_eq!(&text[range], expected);
+            }
+        }
+    }
+}
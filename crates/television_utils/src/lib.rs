@@ -1,6 +1,19 @@
+pub mod ansi;
+pub mod cancel;
+pub mod checksum;
+pub mod desktop;
+pub mod dirs;
+pub mod fields;
 pub mod files;
+pub mod format;
+pub mod hexdump;
+pub mod image;
 pub mod indices;
+pub mod project;
+pub mod sandbox;
+pub mod shell;
 pub mod stdin;
 pub mod strings;
+#[cfg(feature = "syntax-highlighting")]
 pub mod syntax;
 pub mod threads;
@@ -0,0 +1,130 @@
+use std::path::{Component, Path, PathBuf};
+
+/// A file marking the root of a project explicitly, e.g. in a directory
+/// that isn't a repository.
+pub const ROOT_MARKER: &str = ".tv-root";
+
+/// The files marking the root of a package, e.g. a crate of a cargo
+/// workspace.
+const PACKAGE_MARKERS: [&str; 4] =
+    ["Cargo.toml", "package.json", "BUILD", "BUILD.bazel"];
+
+/// The files marking the root of a workspace (a project made of several
+/// packages) on their own.
+const WORKSPACE_MARKERS: [&str; 4] = [
+    "pnpm-workspace.yaml",
+    "WORKSPACE",
+    "WORKSPACE.bazel",
+    "MODULE.bazel",
+];
+
+/// The root of the project the given directory belongs to, if any.
+///
+/// An explicit `.tv-root` marker wins, then the root of the git repository,
+/// then the root of the workspace (cargo, pnpm, npm or bazel), then the
+/// closest package.
+pub fn find_project_root(dir: &Path) -> Option<PathBuf> {
+    let find = |marker: &str| {
+        dir.ancestors()
+            .find(|ancestor| ancestor.join(marker).exists())
+            .map(Path::to_path_buf)
+    };
+    find(ROOT_MARKER)
+        .or_else(|| find(".git"))
+        .or_else(|| {
+            // the outermost one, as cargo workspaces may nest packages
+            dir.ancestors()
+                .filter(|ancestor| is_workspace_root(ancestor))
+                .last()
+                .map(Path::to_path_buf)
+        })
+        .or_else(|| find_package_root(dir))
+}
+
+/// The root of the package the given directory belongs to, i.e. the closest
+/// directory containing a `Cargo.toml`, a `package.json` or a bazel `BUILD`
+/// file.
+pub fn find_package_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|ancestor| {
+            PACKAGE_MARKERS
+                .iter()
+                .any(|marker| ancestor.join(marker).is_file())
+        })
+        .map(Path::to_path_buf)
+}
+
+fn is_workspace_root(dir: &Path) -> bool {
+    let manifest_contains = |manifest: &str, needle: &str| {
+        std::fs::read_to_string(dir.join(manifest))
+            .is_ok_and(|content| content.contains(needle))
+    };
+    WORKSPACE_MARKERS
+        .iter()
+        .any(|marker| dir.join(marker).is_file())
+        || manifest_contains("Cargo.toml", "[workspace]")
+        || manifest_contains("package.json", "\"workspaces\"")
+}
+
+/// The given path relative to the base directory (both being absolute),
+/// going up with `..` if needed, e.g. to display the files of the project
+/// relative to the current directory.
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    if let Ok(relative) = path.strip_prefix(base) {
+        return relative.to_path_buf();
+    }
+    let path_components: Vec<Component> = path.components().collect();
+    let base_components: Vec<Component> = base.components().collect();
+    let common = path_components
+        .iter()
+        .zip(&base_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+    // e.g. on another drive
+    if common == 0 {
+        return path.to_path_buf();
+    }
+    base_components[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .chain(path_components[common..].iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let tmp = dir.path();
+        let crate_dir = tmp.join("repo/crates/foo");
+        let src = crate_dir.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::create_dir_all(tmp.join("repo/.git")).unwrap();
+        std::fs::write(crate_dir.join("Cargo.toml"), "").unwrap();
+
+        assert_eq!(find_project_root(&src), Some(tmp.join("repo")));
+        std::fs::remove_dir_all(tmp.join("repo/.git")).unwrap();
+        assert_eq!(find_project_root(&src), Some(crate_dir.clone()));
+        std::fs::write(tmp.join("repo/Cargo.toml"), "[workspace]").unwrap();
+        assert_eq!(find_project_root(&src), Some(tmp.join("repo")));
+        assert_eq!(find_package_root(&src), Some(crate_dir.clone()));
+        std::fs::write(tmp.join("repo/crates").join(ROOT_MARKER), "").unwrap();
+        assert_eq!(find_project_root(&src), Some(tmp.join("repo/crates")));
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let base = Path::new("/repo/crates/foo");
+        assert_eq!(
+            relative_path(Path::new("/repo/crates/foo/src/lib.rs"), base),
+            Path::new("src/lib.rs")
+        );
+        assert_eq!(
+            relative_path(Path::new("/repo/README.md"), base),
+            Path::new("../../README.md")
+        );
+    }
+}
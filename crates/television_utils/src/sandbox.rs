@@ -0,0 +1,289 @@
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::shell::shell_command;
+
+/// The environment variables sandboxed commands get by default, when they
+/// are set.
+pub const DEFAULT_ENV: [&str; 24] = [
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "COLORTERM",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "LC_MESSAGES",
+    "TZ",
+    "TMPDIR",
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+    // needed for most programs to run at all on windows
+    "SYSTEMROOT",
+    "WINDIR",
+    "COMSPEC",
+    "PATHEXT",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+    "APPDATA",
+];
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_MAX_OUTPUT_SIZE: usize = 1024 * 1024;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Runs shell commands under some restrictions, so that a misbehaving
+/// command can't hang, flood or leak into its caller.
+///
+/// - the command is killed (along with the processes it spawned, on unix)
+///   once it runs for longer than the `timeout` or writes more than
+///   `max_output_size` bytes to either of its standard output or error;
+/// - its environment only contains the variables from `DEFAULT_ENV` and
+///   `env`;
+/// - it runs in `working_directory`, or the current directory if not set,
+///   with its standard input closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sandbox {
+    pub timeout: Duration,
+    pub max_output_size: usize,
+    /// Additional environment variables passed through to the command.
+    pub env: Vec<String>,
+    pub working_directory: Option<PathBuf>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            max_output_size: DEFAULT_MAX_OUTPUT_SIZE,
+            env: Vec::new(),
+            working_directory: None,
+        }
+    }
+}
+
+/// Why a sandboxed command stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The command exited on its own.
+    Exited,
+    /// The command was killed after running for too long.
+    TimedOut,
+    /// The command was killed after writing too much output, which was
+    /// truncated.
+    OutputTooLarge,
+    /// The command was killed because it was cancelled.
+    Cancelled,
+}
+
+/// The output of a sandboxed command.
+#[derive(Debug, Clone)]
+pub struct SandboxedOutput {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub termination: Termination,
+}
+
+impl Sandbox {
+    /// Run the given command line through the platform's shell and collect
+    /// its output.
+    ///
+    /// The command is killed as soon as `cancelled` is set (e.g. from another
+    /// thread, once its output isn't needed anymore).
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::atomic::AtomicBool;
+    /// use television_utils::sandbox::{Sandbox, Termination};
+    ///
+    /// #[cfg(unix)]
+    /// {
+    ///     let output = Sandbox::default()
+    ///         .run("echo hello", &AtomicBool::new(false))
+    ///         .unwrap();
+    ///     assert_eq!(output.stdout, b"hello\n");
+    ///     assert_eq!(output.termination, Termination::Exited);
+    /// }
+    /// ```
+    pub fn run(
+        &self,
+        command: &str,
+        cancelled: &AtomicBool,
+    ) -> io::Result<SandboxedOutput> {
+        let mut cmd = shell_command(command);
+        cmd.env_clear()
+            .envs(self.allowed_env())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(dir) = &self.working_directory {
+            cmd.current_dir(dir);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        let mut child = cmd.spawn()?;
+
+        let overflowed = Arc::new(AtomicBool::new(false));
+        let stdout = read_capped(
+            child.stdout.take(),
+            self.max_output_size,
+            overflowed.clone(),
+        );
+        let stderr = read_capped(
+            child.stderr.take(),
+            self.max_output_size,
+            overflowed.clone(),
+        );
+
+        let start = Instant::now();
+        let termination = loop {
+            if cancelled.load(Ordering::Relaxed) {
+                break Termination::Cancelled;
+            }
+            if overflowed.load(Ordering::Relaxed) {
+                break Termination::OutputTooLarge;
+            }
+            if start.elapsed() >= self.timeout {
+                break Termination::TimedOut;
+            }
+            // the output is complete once every process writing to it exited
+            if stdout.is_finished()
+                && stderr.is_finished()
+                && child.try_wait()?.is_some()
+            {
+                break Termination::Exited;
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        if termination != Termination::Exited {
+            kill(&mut child);
+        }
+        let _ = child.wait();
+
+        Ok(SandboxedOutput {
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+            termination,
+        })
+    }
+
+    fn allowed_env(&self) -> Vec<(String, String)> {
+        DEFAULT_ENV
+            .iter()
+            .copied()
+            .chain(self.env.iter().map(String::as_str))
+            .filter_map(|name| {
+                std::env::var(name)
+                    .ok()
+                    .map(|value| (name.to_string(), value))
+            })
+            .collect()
+    }
+}
+
+/// Read the given pipe in the background, flagging `overflowed` if it
+/// yields more than `max_size` bytes.
+fn read_capped<R>(
+    pipe: Option<R>,
+    max_size: usize,
+    overflowed: Arc<AtomicBool>,
+) -> JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let Some(pipe) = pipe else {
+            return output;
+        };
+        let _ = pipe.take(max_size as u64 + 1).read_to_end(&mut output);
+        if output.len() > max_size {
+            output.truncate(max_size);
+            overflowed.store(true, Ordering::Relaxed);
+        }
+        output
+    })
+}
+
+/// Kill the command, along with the processes it spawned since it runs in
+/// its own process group.
+#[cfg(unix)]
+fn kill(child: &mut Child) {
+    match i32::try_from(child.id()) {
+        // SAFETY: this only sends a signal to the child's process group,
+        // which can't have been reused since the child wasn't reaped yet.
+        Ok(pgid) => unsafe {
+            libc::kill(-pgid, libc::SIGKILL);
+        },
+        Err(_) => {
+            let _ = child.kill();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child) {
+    let _ = child.kill();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox() {
+        let sandbox = Sandbox {
+            timeout: Duration::from_millis(200),
+            max_output_size: 8,
+            ..Sandbox::default()
+        };
+        let not_cancelled = AtomicBool::new(false);
+
+        let output = sandbox.run("echo out; echo err >&2", &not_cancelled);
+        let output = output.unwrap();
+        assert_eq!(output.termination, Termination::Exited);
+        assert_eq!(
+            (&output.stdout[..], &output.stderr[..]),
+            (&b"out\n"[..], &b"err\n"[..])
+        );
+
+        let output = sandbox.run("yes", &not_cancelled).unwrap();
+        assert_eq!(output.termination, Termination::OutputTooLarge);
+        assert_eq!(output.stdout, b"y\ny\ny\ny\n");
+
+        // the background sleep holds the output open, and is killed too
+        let start = Instant::now();
+        let output = sandbox.run("sleep 10 & echo started", &not_cancelled);
+        assert_eq!(output.unwrap().termination, Termination::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let cancelled = AtomicBool::new(true);
+        let output = sandbox.run("sleep 10", &cancelled).unwrap();
+        assert_eq!(output.termination, Termination::Cancelled);
+
+        // cargo sets the variable when running the tests, but it is only
+        // passed through when asked to
+        let version = std::env::var("CARGO_PKG_VERSION").unwrap_or_default();
+        let output = sandbox.run("echo $CARGO_PKG_VERSION", &not_cancelled);
+        assert_eq!(output.unwrap().stdout, b"\n");
+        let sandbox = Sandbox {
+            env: vec!["CARGO_PKG_VERSION".to_string()],
+            ..Sandbox::default()
+        };
+        let output = sandbox.run("echo $CARGO_PKG_VERSION", &not_cancelled);
+        assert_eq!(output.unwrap().stdout, format!("{version}\n").as_bytes());
+    }
+}
@@ -0,0 +1,145 @@
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Build a command that runs the given command line through the platform's
+/// shell (`sh -c` on unix, `cmd /C` on windows).
+///
+/// # Examples
+/// ```
+/// use television_utils::shell::shell_command;
+///
+/// #[cfg(unix)]
+/// {
+///     let output = shell_command("echo hello").output().unwrap();
+///     assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+/// }
+/// ```
+pub fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+/// The user's editor (`$EDITOR`), `vi` if not set.
+pub fn editor() -> String {
+    std::env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// How commands run in the background (e.g. graphical applications) are
+/// spawned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExecutionBackend {
+    /// As children of `tv`, which are killed along with the terminal.
+    #[default]
+    Child,
+    /// In a new session, detached from the terminal (unix only).
+    Setsid,
+    /// As transient systemd user units, outside of the terminal's cgroup.
+    SystemdRun,
+}
+
+/// Run the given command line in the background, with the given backend,
+/// ignoring its output.
+pub fn spawn_background(
+    command: &str,
+    backend: ExecutionBackend,
+) -> io::Result<()> {
+    let mut cmd = match backend {
+        ExecutionBackend::SystemdRun => {
+            let mut cmd = Command::new("systemd-run");
+            cmd.args(["--user", "--quiet", "--collect", "--", "sh", "-c"])
+                .arg(command);
+            cmd
+        }
+        ExecutionBackend::Child | ExecutionBackend::Setsid => {
+            shell_command(command)
+        }
+    };
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    if backend == ExecutionBackend::Setsid {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: `setsid` is async-signal-safe.
+        unsafe {
+            cmd.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+    let mut child = cmd.spawn()?;
+    // reap the process once it exits
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Find an executable in the directories of the `PATH` environment variable.
+///
+/// # Examples
+/// ```
+/// use television_utils::shell::find_executable;
+///
+/// #[cfg(unix)]
+/// assert!(find_executable("sh").is_some());
+/// assert!(find_executable("surely-not-an-executable").is_none());
+/// ```
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        let candidate = dir.join(name);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if cfg!(windows) {
+            let candidate = candidate.with_extension("exe");
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_background() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ids");
+        // the session id of a session leader is its pid
+        let command = format!(
+            "echo $(ps -o sid= -p $$) $$ > {0}.tmp && mv {0}.tmp {0}",
+            path.display()
+        );
+        spawn_background(&command, ExecutionBackend::Setsid).unwrap();
+        let start = Instant::now();
+        let content = loop {
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                break content;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let ids: Vec<&str> = content.split_whitespace().collect();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0], ids[1]);
+    }
+}
@@ -284,18 +284,15 @@ const MAX_LINE_LENGTH: usize = 300;
 /// assert_eq!(processed.len(), 300);
 /// ```
 pub fn preprocess_line(line: &str) -> String {
-    replace_non_printable(
-        {
-            if line.len() > MAX_LINE_LENGTH {
-                slice_up_to_char_boundary(line, MAX_LINE_LENGTH)
-            } else {
-                line
-            }
-        }
+    replace_non_printable(truncate_line(line).as_bytes(), TAB_WIDTH)
+}
+
+/// The part of the given line `preprocess_line` keeps: the line truncated
+/// and trimmed of its line ending, before its non-printable characters are
+/// replaced.
+pub fn truncate_line(line: &str) -> &str {
+    slice_up_to_char_boundary(line, MAX_LINE_LENGTH)
         .trim_end_matches(['\r', '\n', '\0'])
-        .as_bytes(),
-        TAB_WIDTH,
-    )
 }
 
 /// Shrink a string to a maximum length, adding an ellipsis in the middle.
@@ -313,20 +310,69 @@ pub fn preprocess_line(line: &str) -> String {
 /// assert_eq!(shrink_with_ellipsis(s, 6), "H…!");
 /// ```
 pub fn shrink_with_ellipsis(s: &str, max_length: usize) -> String {
+    shrink_with_custom_ellipsis(s, max_length, "…")
+}
+
+/// Shrink a string to a maximum length, adding the given ellipsis in the
+/// middle (see `shrink_with_ellipsis`).
+///
+/// # Examples
+/// ```
+/// use television_utils::strings::shrink_with_custom_ellipsis;
+///
+/// let s = "Hello, World!";
+/// assert_eq!(shrink_with_custom_ellipsis(s, 8, ".."), "He..d!");
+/// assert_eq!(shrink_with_custom_ellipsis(s, 8, ""), "Hed!");
+/// ```
+pub fn shrink_with_custom_ellipsis(
+    s: &str,
+    max_length: usize,
+    ellipsis: &str,
+) -> String {
     if s.len() <= max_length {
         return s.to_string();
     }
 
     let half_max_length = (max_length / 2).saturating_sub(2);
-    let first_half = slice_up_to_char_boundary(s, half_max_length);
-    let second_half =
-        slice_at_char_boundaries(s, s.len() - half_max_length, s.len());
-    format!("{first_half}…{second_half}")
+    // rounded so that each half stays within the budget when it would end
+    // in the middle of a character
+    let first_half = &s[..prev_char_boundary(s, half_max_length)];
+    let second_half = &s[next_char_boundary(s, s.len() - half_max_length)..];
+    format!("{first_half}{ellipsis}{second_half}")
+}
+
+/// Quotes the given string so that it can be safely interpolated into a
+/// shell command as a single argument.
+///
+/// On unix, the string is wrapped in single quotes (any existing single quote
+/// being escaped), while on windows it is wrapped in double quotes.
+///
+/// # Examples
+/// ```
+/// use television_utils::strings::shell_quote;
+///
+/// #[cfg(unix)]
+/// {
+///     assert_eq!(shell_quote("foo"), "'foo'");
+///     assert_eq!(shell_quote("foo bar"), "'foo bar'");
+///     assert_eq!(shell_quote("it's"), "'it'\\''s'");
+/// }
+/// ```
+pub fn shell_quote(s: &str) -> String {
+    #[cfg(unix)]
+    {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+    #[cfg(not(unix))]
+    {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     fn test_next_char_boundary(input: &str, start: usize, expected: usize) {
         let actual = next_char_boundary(input, start);
@@ -482,4 +528,52 @@ mod tests {
         test_preprocess_line("Hello, World!\u{FEFF}", "Hello, World!");
         test_preprocess_line(&"a".repeat(400), &"a".repeat(300));
     }
+
+    proptest! {
+        #[test]
+        fn test_char_boundaries(s in "\\PC{0,16}", i in 0usize..80) {
+            // the closest boundaries on either side
+            let i = i.min(s.len());
+            let next = next_char_boundary(&s, i);
+            prop_assert!(s.is_char_boundary(next));
+            prop_assert!((i..next).all(|j| !s.is_char_boundary(j)));
+            let prev = prev_char_boundary(&s, i);
+            prop_assert!(s.is_char_boundary(prev));
+            prop_assert!((prev + 1..=i).all(|j| !s.is_char_boundary(j)));
+        }
+
+        #[test]
+        fn test_slice_at_char_boundaries_covers_range(
+            s in "\\PC{0,16}",
+            start in 0usize..80,
+            end in 0usize..80,
+        ) {
+            let slice = slice_at_char_boundaries(&s, start, end);
+            if start <= end && end <= s.len() {
+                // the slice is the smallest one covering the range
+                let offset = prev_char_boundary(&s, start);
+                prop_assert_eq!(slice, &s[offset..offset + slice.len()]);
+                prop_assert!(offset + slice.len() >= end);
+                prop_assert!(slice.len() <= end - start + 6);
+            } else {
+                prop_assert_eq!(slice, "");
+            }
+        }
+
+        #[test]
+        fn test_shrink_with_ellipsis(s in "\\PC{0,40}", max_length in 0usize..48) {
+            // an ellipsis the generated strings don't contain
+            let shrunk = shrink_with_custom_ellipsis(&s, max_length, "\t");
+            if s.len() <= max_length {
+                prop_assert_eq!(shrunk, s);
+            } else {
+                let (first, second) = shrunk.split_once('\t').unwrap();
+                prop_assert!(s.starts_with(first));
+                prop_assert!(s.ends_with(second));
+                let half_max_length = (max_length / 2).saturating_sub(2);
+                prop_assert!(first.len() <= half_max_length);
+                prop_assert!(second.len() <= half_max_length);
+            }
+        }
+    }
 }
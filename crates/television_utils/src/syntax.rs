@@ -1,10 +1,75 @@
 use bat::assets::HighlightingAssets;
 use gag::Gag;
+use std::collections::VecDeque;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{Style, Theme};
-use syntect::parsing::SyntaxSet;
-use tracing::warn;
+use syntect::highlighting::{
+    HighlightIterator, HighlightState, Highlighter, Style, Theme,
+};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
+use tracing::{debug, warn};
+
+/// Interpreters whose name doesn't identify their syntax, and the extension
+/// of the files they run.
+const INTERPRETER_EXTENSIONS: &[(&str, &str)] = &[
+    ("node", "js"),
+    ("nodejs", "js"),
+    ("bun", "js"),
+    ("deno", "ts"),
+    ("sh", "sh"),
+    ("dash", "sh"),
+    ("ksh", "sh"),
+    ("pwsh", "ps1"),
+    ("runghc", "hs"),
+    ("rust-script", "rs"),
+];
+
+/// The syntax of a file, detected from its name or extension, then from its
+/// first line (a shebang, a modeline, ...), and plain text if all fails.
+pub fn detect_syntax<'a>(
+    file_path: &Path,
+    first_line: &str,
+    syntax_set: &'a SyntaxSet,
+) -> &'a SyntaxReference {
+    let file_name = file_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let extension = file_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+    syntax_set
+        .find_syntax_by_extension(file_name)
+        .or_else(|| syntax_set.find_syntax_by_extension(extension))
+        .or_else(|| syntax_set.find_syntax_by_first_line(first_line))
+        .or_else(|| {
+            let interpreter = shebang_interpreter(first_line)?;
+            let token = INTERPRETER_EXTENSIONS
+                .iter()
+                .find(|(name, _)| *name == interpreter)
+                .map_or(interpreter, |(_, extension)| extension);
+            syntax_set.find_syntax_by_token(token)
+        })
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+}
+
+/// The interpreter run by a shebang line, without its version, e.g.
+/// `python` for `#!/usr/bin/env -S python3.12 -u`.
+fn shebang_interpreter(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        // skip the options and variable assignments of `env`
+        program = words
+            .find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    let name =
+        program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    (!name.is_empty()).then_some(name)
+}
 
 pub fn compute_highlights_for_path(
     file_path: &Path,
@@ -12,16 +77,12 @@ pub fn compute_highlights_for_path(
     syntax_set: &SyntaxSet,
     syntax_theme: &Theme,
 ) -> color_eyre::Result<Vec<Vec<(Style, String)>>> {
-    let syntax =
-        syntax_set
-            .find_syntax_for_file(file_path)?
-            .unwrap_or_else(|| {
-                warn!(
-                    "No syntax found for {:?}, defaulting to plain text",
-                    file_path
-                );
-                syntax_set.find_syntax_plain_text()
-            });
+    let syntax = detect_syntax(
+        file_path,
+        lines.first().map_or("", String::as_str),
+        syntax_set,
+    );
+    debug!("Highlighting {:?} as {}", file_path, syntax.name);
     let mut highlighter = HighlightLines::new(syntax, syntax_theme);
     let mut highlighted_lines = Vec::new();
     for line in lines {
@@ -36,6 +97,199 @@ pub fn compute_highlights_for_path(
     Ok(highlighted_lines)
 }
 
+/// A line split into its highlighted regions.
+pub type HighlightedLine = Vec<(Style, String)>;
+
+/// The approximate number of bytes taken by the given highlighted lines.
+pub fn highlighted_lines_size(lines: &[HighlightedLine]) -> usize {
+    lines
+        .iter()
+        .flatten()
+        .map(|(_, text)| std::mem::size_of::<(Style, String)>() + text.len())
+        .sum()
+}
+
+/// The number of lines highlighted at once by a `LazyHighlighter`.
+const HIGHLIGHTED_CHUNK_SIZE: usize = 128;
+/// The number of highlighted chunks a `LazyHighlighter` keeps around.
+const MAX_HIGHLIGHTED_CHUNKS: usize = 16;
+
+/// The highlighting of a text that's only computed for the lines that are
+/// actually displayed, for texts too large to be highlighted up front.
+///
+/// The lines are highlighted by chunks. Since highlighting a line depends on
+/// all the lines before it, the state of the parser at the start of each
+/// chunk reached so far is saved, so that any chunk can be highlighted
+/// again without going through the whole text from the start.
+pub struct LazyHighlighter {
+    lines: Vec<String>,
+    syntax_set: Arc<SyntaxSet>,
+    theme: Arc<Theme>,
+    state: Mutex<LazyHighlighterState>,
+}
+
+struct LazyHighlighterState {
+    /// The parser and highlighter states at the start of each chunk.
+    checkpoints: Vec<(ParseState, HighlightState)>,
+    /// The last highlighted chunks, by index, the most recent last.
+    chunks: VecDeque<(usize, Arc<Vec<HighlightedLine>>)>,
+}
+
+// SAFETY: with the oniguruma regex engine, the parser states hold the
+// regions of the regex captures, whose raw pointers make them `!Send`. These
+// regions are owned by the states and not tied to the thread that allocated
+// them, and the states are only accessed behind the mutex.
+unsafe impl Send for LazyHighlighterState {}
+
+impl LazyHighlighter {
+    /// The lazy highlighting of the given lines (which must end with a
+    /// newline) of the file at the given path.
+    pub fn new(
+        file_path: &Path,
+        lines: Vec<String>,
+        syntax_set: Arc<SyntaxSet>,
+        theme: Arc<Theme>,
+    ) -> Self {
+        let syntax = detect_syntax(
+            file_path,
+            lines.first().map_or("", String::as_str),
+            &syntax_set,
+        );
+        debug!("Highlighting {:?} lazily as {}", file_path, syntax.name);
+        let highlighter = Highlighter::new(&theme);
+        let checkpoint = (
+            ParseState::new(syntax),
+            HighlightState::new(&highlighter, ScopeStack::new()),
+        );
+        LazyHighlighter {
+            state: Mutex::new(LazyHighlighterState {
+                checkpoints: vec![checkpoint],
+                chunks: VecDeque::new(),
+            }),
+            lines,
+            syntax_set,
+            theme,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The approximate number of bytes taken by the text and the chunks
+    /// highlighted so far.
+    pub fn memory_usage(&self) -> usize {
+        let chunks: usize = self
+            .state
+            .lock()
+            .unwrap()
+            .chunks
+            .iter()
+            .map(|(_, chunk)| highlighted_lines_size(chunk))
+            .sum();
+        self.lines.iter().map(String::len).sum::<usize>() + chunks
+    }
+
+    /// The highlighted lines in the given range, highlighting them if they
+    /// weren't already.
+    pub fn highlight(&self, range: Range<usize>) -> Vec<HighlightedLine> {
+        let end = range.end.min(self.lines.len());
+        if range.start >= end {
+            return Vec::new();
+        }
+        let mut state = self.state.lock().unwrap();
+        let mut highlighted_lines = Vec::with_capacity(end - range.start);
+        for index in range.start / HIGHLIGHTED_CHUNK_SIZE
+            ..=(end - 1) / HIGHLIGHTED_CHUNK_SIZE
+        {
+            let chunk = self.chunk(&mut state, index);
+            let offset = index * HIGHLIGHTED_CHUNK_SIZE;
+            highlighted_lines.extend_from_slice(
+                &chunk[range.start.max(offset) - offset
+                    ..end.min(offset + chunk.len()) - offset],
+            );
+        }
+        highlighted_lines
+    }
+
+    /// The highlighted chunk with the given index, from the last
+    /// highlighted chunks or highlighted from the closest checkpoint.
+    fn chunk(
+        &self,
+        state: &mut LazyHighlighterState,
+        index: usize,
+    ) -> Arc<Vec<HighlightedLine>> {
+        if let Some(position) =
+            state.chunks.iter().position(|(i, _)| *i == index)
+        {
+            let chunk = state.chunks.remove(position).unwrap();
+            state.chunks.push_back(chunk.clone());
+            return chunk.1;
+        }
+        // reach the chunk from the last checkpoint, saving the checkpoints
+        // of the chunks in between
+        while state.checkpoints.len() <= index {
+            let last = state.checkpoints.len() - 1;
+            self.highlight_chunk(state, last);
+        }
+        let chunk = Arc::new(self.highlight_chunk(state, index));
+        if state.chunks.len() == MAX_HIGHLIGHTED_CHUNKS {
+            state.chunks.pop_front();
+        }
+        state.chunks.push_back((index, chunk.clone()));
+        chunk
+    }
+
+    /// Highlight the chunk with the given index from its checkpoint, saving
+    /// the checkpoint of the next chunk if it's the first time we get there.
+    fn highlight_chunk(
+        &self,
+        state: &mut LazyHighlighterState,
+        index: usize,
+    ) -> Vec<HighlightedLine> {
+        let highlighter = Highlighter::new(&self.theme);
+        let (mut parse_state, mut highlight_state) =
+            state.checkpoints[index].clone();
+        let start = index * HIGHLIGHTED_CHUNK_SIZE;
+        let end = (start + HIGHLIGHTED_CHUNK_SIZE).min(self.lines.len());
+        let highlighted_lines = self.lines[start..end]
+            .iter()
+            .map(
+                |line| match parse_state.parse_line(line, &self.syntax_set) {
+                    Ok(ops) => HighlightIterator::new(
+                        &mut highlight_state,
+                        &ops,
+                        line,
+                        &highlighter,
+                    )
+                    .map(|(style, text)| (style, text.to_string()))
+                    .collect(),
+                    Err(e) => {
+                        warn!("Error highlighting line: {:?}", e);
+                        vec![(highlighter.get_default(), line.clone())]
+                    }
+                },
+            )
+            .collect();
+        if state.checkpoints.len() == index + 1 && end < self.lines.len() {
+            state.checkpoints.push((parse_state, highlight_state));
+        }
+        highlighted_lines
+    }
+}
+
+impl std::fmt::Debug for LazyHighlighter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyHighlighter")
+            .field("lines", &self.lines.len())
+            .finish_non_exhaustive()
+    }
+}
+
 #[allow(dead_code)]
 pub fn compute_highlights_for_line<'a>(
     line: &'a str,
@@ -143,3 +397,67 @@ impl HighlightingAssetsExt for HighlightingAssets {
         theme
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shebang_interpreter() {
+        assert_eq!(shebang_interpreter("#!/bin/bash\n"), Some("bash"));
+        assert_eq!(
+            shebang_interpreter(
+                "#!/usr/bin/env -S PYTHONUTF8=1 python3.12 -u"
+            ),
+            Some("python")
+        );
+        assert_eq!(shebang_interpreter("#!/usr/bin/env"), None);
+        assert_eq!(shebang_interpreter("# not a shebang"), None);
+    }
+
+    #[test]
+    fn test_detect_syntax() {
+        let assets = HighlightingAssets::from_binary();
+        let syntax_set = assets.get_syntax_set().unwrap();
+        let name = |path: &str, first_line: &str| {
+            detect_syntax(Path::new(path), first_line, syntax_set)
+                .name
+                .clone()
+        };
+        assert_eq!(name("src/main.rs", ""), "Rust");
+        assert_eq!(name("Makefile", ""), "Makefile");
+        assert_eq!(
+            name("bin/serve", "#!/usr/bin/env -S deno run\n"),
+            "TypeScript"
+        );
+        assert_eq!(name("bin/check", "#!/usr/bin/env python3\n"), "Python");
+        assert_eq!(name("notes", "just some text\n"), "Plain Text");
+    }
+
+    #[test]
+    fn test_lazy_highlighter() {
+        let assets = HighlightingAssets::from_binary();
+        let syntax_set = Arc::new(assets.get_syntax_set().unwrap().clone());
+        let theme = Arc::new(assets.get_theme("base16").clone());
+        // a comment spanning several chunks, which must be resumed correctly
+        let lines: Vec<String> = std::iter::once("/*\n".to_string())
+            .chain((0..300).map(|i| format!("line {i}\n")))
+            .chain(["*/\n".to_string(), "fn main() {}\n".to_string()])
+            .collect();
+        let path = Path::new("main.rs");
+        let expected = compute_highlights_for_path(
+            path,
+            lines.clone(),
+            &syntax_set,
+            &theme,
+        )
+        .unwrap();
+
+        let highlighter = LazyHighlighter::new(path, lines, syntax_set, theme);
+        assert_eq!(highlighter.len(), 303);
+        assert_eq!(highlighter.highlight(290..310), expected[290..]);
+        assert_eq!(highlighter.highlight(120..140), expected[120..140]);
+        assert_eq!(highlighter.highlight(0..303), expected);
+        assert!(highlighter.highlight(400..410).is_empty());
+    }
+}
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use television_channels::channels::alias::parse_alias_line;
+
+// a line printed by `alias` in any of the supported shells, e.g. with
+// unbalanced quotes or a trailing backslash
+fuzz_target!(|data: &[u8]| {
+    let line = String::from_utf8_lossy(data);
+    for shell in ["bash", "zsh", "fish", "pwsh", "cmd"] {
+        if let Some((name, _)) = parse_alias_line(shell, &line) {
+            assert!(!name.is_empty());
+        }
+    }
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use television_fuzzy::matcher::query::{split_terms, Query};
+
+// the pattern typed in the input, e.g. with unbalanced quotes or escapes
+fuzz_target!(|data: &[u8]| {
+    let pattern = String::from_utf8_lossy(data);
+    let query = Query::parse(&pattern);
+    let _ = split_terms(&pattern);
+    // typing the pattern character by character
+    let mut previous = Query::default();
+    for (i, _) in pattern.char_indices() {
+        let query = Query::parse(&pattern[..i]);
+        let _ = query.narrows(&previous);
+        previous = query;
+    }
+    let _ = query.narrows(&previous);
+});
@@ -0,0 +1,43 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use television_utils::ansi::preprocess_ansi_line;
+use television_utils::fields::{Delimiter, Fields, Picked};
+
+// the `--delimiter`, `--with-nth` and `--nth` options and a line read from
+// stdin, separated by newlines
+fuzz_target!(|data: &[u8]| {
+    let data = String::from_utf8_lossy(data);
+    let mut parts = data.splitn(4, '\n');
+    let delimiter = Delimiter::new(parts.next().unwrap_or_default());
+    let with_nth = parts.next().unwrap_or_default().parse::<Fields>();
+    let nth = parts.next().unwrap_or_default().parse::<Fields>();
+    let line = preprocess_ansi_line(parts.next().unwrap_or_default());
+
+    let fields = delimiter.split(&line.text);
+    let displayed = match with_nth {
+        Ok(with_nth) => Picked::new(
+            &line.text,
+            &fields,
+            &with_nth.indices(fields.len()),
+            &delimiter,
+        ),
+        Err(_) => Picked::all(&line.text, &fields),
+    };
+    let styles = displayed.map_ranges(&line.styles);
+    assert!(styles
+        .iter()
+        .all(|(range, _)| displayed.text.get(range.clone()).is_some()));
+    if let Ok(nth) = nth {
+        let matched = Picked::new(
+            &displayed.text,
+            &displayed.fields,
+            &nth.indices(displayed.fields.len()),
+            &delimiter,
+        );
+        let matched_chars = matched.text.chars().count() as u32;
+        let ranges: Vec<(u32, u32)> =
+            (0..matched_chars).map(|i| (i, i + 1)).collect();
+        let _ = matched.line_char_ranges(&displayed.text, &ranges);
+    }
+});